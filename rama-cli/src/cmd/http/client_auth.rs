@@ -0,0 +1,231 @@
+//! client certificate (mTLS) support for the rama http client
+
+use p12_keystore::KeyStore;
+use rama::{
+    error::{ErrorContext, OpaqueError},
+    net::tls::{
+        client::{ClientAuth, ClientAuthData},
+        DataEncoding,
+    },
+    tls::rustls::dep::{
+        pemfile,
+        pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+        rustls::{crypto::aws_lc_rs, sign::CertifiedKey},
+    },
+};
+use std::io::BufReader;
+
+use super::CliCommandHttp;
+
+/// Load the client certificate and private key defined by the cli config, if any.
+///
+/// The key-certificate pair is validated, such that an invalid
+/// or mismatched pair is reported before any connection is made.
+pub(super) fn load_client_auth(cfg: &CliCommandHttp) -> Result<Option<ClientAuth>, OpaqueError> {
+    if let Some(path) = cfg.pkcs12.as_deref() {
+        let der =
+            std::fs::read(path).with_context(|| format!("read client PKCS#12 file '{path}'"))?;
+        let (cert_chain, private_key) =
+            parse_pkcs12(&der, cfg.pkcs12_pass.as_deref().unwrap_or_default())
+                .with_context(|| format!("parse client PKCS#12 file '{path}'"))?;
+        return client_auth_from_der(cert_chain, private_key).map(Some);
+    }
+
+    match (cfg.cert.as_deref(), cfg.key.as_deref()) {
+        (None, None) => Ok(None),
+        (Some(cert_path), Some(key_path)) => {
+            let cert_pem = std::fs::read(cert_path)
+                .with_context(|| format!("read client certificate file '{cert_path}'"))?;
+            let key_pem = std::fs::read(key_path)
+                .with_context(|| format!("read client key file '{key_path}'"))?;
+            client_auth_from_pem(&cert_pem, &key_pem).map(Some)
+        }
+        (Some(_), None) => Err(OpaqueError::from_display(
+            "client certificate defined without a client key (--key)",
+        )),
+        (None, Some(_)) => Err(OpaqueError::from_display(
+            "client key defined without a client certificate (--cert)",
+        )),
+    }
+}
+
+/// Create a validated [`ClientAuth`] from a PEM-encoded certificate (chain) and private key.
+fn client_auth_from_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<ClientAuth, OpaqueError> {
+    let cert_chain = pemfile::certs(&mut BufReader::new(cert_pem))
+        .collect::<Result<Vec<_>, _>>()
+        .context("parse client certificate (PEM)")?;
+    let private_key = pemfile::private_key(&mut BufReader::new(key_pem))
+        .context("parse client private key (PEM)")?
+        .context("no client private key found (in PEM)")?;
+    client_auth_from_der(cert_chain, private_key)
+}
+
+/// Extract the certificate (chain) and private key from a DER-encoded PKCS#12 archive.
+///
+/// The archive is decoded in pure rust, independent of the tls backend in use.
+fn parse_pkcs12(
+    der: &[u8],
+    pass: &str,
+) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), OpaqueError> {
+    let keystore = KeyStore::from_pkcs12(der, pass).context("decode PKCS#12 archive")?;
+    let (_, key_chain) = keystore
+        .private_key_chain()
+        .context("no private key (with certificate) found in PKCS#12 archive")?;
+
+    // the chain starts with the certificate matching the private key
+    let cert_chain = key_chain
+        .chain()
+        .iter()
+        .map(|cert| CertificateDer::from(cert.as_der().to_vec()))
+        .collect();
+    let private_key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key_chain.key().to_vec()));
+
+    Ok((cert_chain, private_key))
+}
+
+/// Create a [`ClientAuth`] from a DER-encoded certificate (chain) and private key,
+/// validating that the private key matches the (leaf) certificate.
+fn client_auth_from_der(
+    cert_chain: Vec<CertificateDer<'static>>,
+    private_key: PrivateKeyDer<'static>,
+) -> Result<ClientAuth, OpaqueError> {
+    if cert_chain.is_empty() {
+        return Err(OpaqueError::from_display("no client certificate found"));
+    }
+
+    let signing_key = aws_lc_rs::default_provider()
+        .key_provider
+        .load_private_key(private_key.clone_key())
+        .context("load client private key")?;
+    CertifiedKey::new(cert_chain.clone(), signing_key)
+        .keys_match()
+        .context("validate client private key against client certificate")?;
+
+    Ok(ClientAuth::Single(ClientAuthData {
+        private_key: DataEncoding::Der(private_key.secret_der().to_vec()),
+        cert_chain: DataEncoding::DerStack(
+            cert_chain
+                .into_iter()
+                .map(|cert| cert.as_ref().to_vec())
+                .collect(),
+        ),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama::{
+        cli::service::echo::EchoServiceBuilder,
+        http::{client::HttpClient, Body, Request},
+        net::tls::{
+            client::{ClientConfig, ServerVerifyMode},
+            server::{ClientVerifyMode, SelfSignedData, ServerAuth, ServerConfig},
+        },
+        rt::Executor,
+        tcp::server::TcpListener,
+        tls::rustls::dep::rcgen,
+        Context, Service,
+    };
+
+    fn generate_client_cert() -> (String, String) {
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["client.rama.local".to_owned()]).unwrap();
+        (cert.pem(), key_pair.serialize_pem())
+    }
+
+    #[test]
+    fn test_client_auth_from_pem_mismatch() {
+        let (cert_pem, key_pem) = generate_client_cert();
+        let (_, other_key_pem) = generate_client_cert();
+
+        assert!(client_auth_from_pem(cert_pem.as_bytes(), key_pem.as_bytes()).is_ok());
+        assert!(client_auth_from_pem(cert_pem.as_bytes(), other_key_pem.as_bytes()).is_err());
+        assert!(client_auth_from_pem(b"", key_pem.as_bytes()).is_err());
+        assert!(client_auth_from_pem(cert_pem.as_bytes(), b"").is_err());
+    }
+
+    /// PKCS#12 archive of a self-signed `client.rama.local` certificate
+    /// and its (P-256) private key, protected with the password `rama`.
+    ///
+    /// Generated using openssl (AES-256-CBC, HMAC-SHA256):
+    ///
+    /// ```text
+    /// openssl req -x509 -newkey ec -pkeyopt ec_paramgen_curve:prime256v1 -nodes \
+    ///     -keyout key.pem -out cert.pem -days 36500 -subj "/CN=client.rama.local" \
+    ///     -addext "subjectAltName=DNS:client.rama.local" \
+    ///     -addext "basicConstraints=critical,CA:FALSE" \
+    ///     -addext "keyUsage=critical,digitalSignature" \
+    ///     -addext "extendedKeyUsage=clientAuth"
+    /// openssl pkcs12 -export -inkey key.pem -in cert.pem -name client.rama.local \
+    ///     -passout pass:rama -out client.p12
+    /// ```
+    const CLIENT_P12: &[u8] = include_bytes!("../../../fixtures/client.p12");
+
+    #[test]
+    fn test_parse_pkcs12() {
+        let (cert_chain, private_key) = parse_pkcs12(CLIENT_P12, "rama").unwrap();
+        assert_eq!(cert_chain.len(), 1);
+        assert!(client_auth_from_der(cert_chain, private_key).is_ok());
+
+        assert!(parse_pkcs12(CLIENT_P12, "wrong").is_err());
+        assert!(parse_pkcs12(b"not a PKCS#12 archive", "rama").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_client_auth_required_by_server() {
+        let (cert_pem, key_pem) = generate_client_cert();
+        let client_cert_der = pemfile::certs(&mut BufReader::new(cert_pem.as_bytes()))
+            .next()
+            .unwrap()
+            .unwrap();
+        let client_auth = client_auth_from_pem(cert_pem.as_bytes(), key_pem.as_bytes()).unwrap();
+        assert_client_auth_required_by_server(client_cert_der, client_auth).await;
+    }
+
+    #[tokio::test]
+    async fn test_pkcs12_client_auth_required_by_server() {
+        let (cert_chain, private_key) = parse_pkcs12(CLIENT_P12, "rama").unwrap();
+        let client_cert_der = cert_chain[0].clone();
+        let client_auth = client_auth_from_der(cert_chain, private_key).unwrap();
+        assert_client_auth_required_by_server(client_cert_der, client_auth).await;
+    }
+
+    async fn assert_client_auth_required_by_server(
+        client_cert_der: CertificateDer<'static>,
+        client_auth: ClientAuth,
+    ) {
+        let server_config = ServerConfig {
+            client_verify_mode: ClientVerifyMode::ClientAuth(DataEncoding::Der(
+                client_cert_der.as_ref().to_vec(),
+            )),
+            ..ServerConfig::new(ServerAuth::SelfSigned(SelfSignedData::default()))
+        };
+        let tcp_service = EchoServiceBuilder::new()
+            .tls_server_config(server_config)
+            .build(Executor::default())
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(listener.serve(tcp_service));
+
+        let send = |client_auth: Option<ClientAuth>| async move {
+            let mut client = HttpClient::default();
+            client.set_tls_config(ClientConfig {
+                server_verify_mode: Some(ServerVerifyMode::Disable),
+                client_auth,
+                ..Default::default()
+            });
+            let request = Request::builder()
+                .uri(format!("https://{addr}/"))
+                .body(Body::empty())
+                .unwrap();
+            client.serve(Context::default(), request).await
+        };
+
+        assert!(send(None).await.is_err());
+        let response = send(Some(client_auth)).await.unwrap();
+        assert!(response.status().is_success());
+    }
+}
@@ -0,0 +1,300 @@
+//! HAR (HTTP Archive) 1.2 recorder for the http client command.
+//!
+//! The `dns`, `connect` and `ssl` timings are taken from the [`ConnectTimings`]
+//! of the response, and are `-1` when not available (e.g. no dns resolution took place).
+//! The body is not streamed to the recorder, so `send` and `receive` are always `0`,
+//! and `wait` is the remainder of the total time of the exchange.
+//!
+//! <http://www.softwareishard.com/blog/har-12-spec/>
+
+use rama::{
+    error::{BoxError, ErrorContext},
+    http::{
+        dep::http_body_util::BodyExt,
+        header::{AUTHORIZATION, CONTENT_TYPE, COOKIE, LOCATION, PROXY_AUTHORIZATION, SET_COOKIE},
+        layer::traffic_writer::{RequestWriter, ResponseWriter},
+        HeaderMap, Request, Response,
+    },
+    net::client::{ConnectTimings, PhaseTiming},
+};
+use serde_json::{json, Value};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Records the requests and responses of a single client exchange
+/// (including redirects) as HAR entries.
+///
+/// A disabled recorder does not record anything.
+#[derive(Debug, Clone)]
+pub(super) struct HarRecorder {
+    inner: Option<Arc<HarRecorderInner>>,
+}
+
+#[derive(Debug)]
+struct HarRecorderInner {
+    bodies: bool,
+    redact: bool,
+    state: Mutex<HarState>,
+}
+
+#[derive(Debug, Default)]
+struct HarState {
+    pending: Option<PendingEntry>,
+    entries: Vec<Value>,
+}
+
+#[derive(Debug)]
+struct PendingEntry {
+    started: SystemTime,
+    instant: Instant,
+    request: Value,
+}
+
+impl HarRecorder {
+    /// Create a new [`HarRecorder`] which records all exchanges.
+    pub(super) fn new(bodies: bool, redact: bool) -> Self {
+        Self {
+            inner: Some(Arc::new(HarRecorderInner {
+                bodies,
+                redact,
+                state: Mutex::new(HarState::default()),
+            })),
+        }
+    }
+
+    /// Create a disabled [`HarRecorder`], which records nothing.
+    pub(super) fn disabled() -> Self {
+        Self { inner: None }
+    }
+
+    /// Serialize the recorded entries as a HAR 1.2 log.
+    pub(super) fn to_har(&self) -> Value {
+        let entries = self
+            .inner
+            .as_ref()
+            .map(|inner| inner.state.lock().unwrap().entries.clone())
+            .unwrap_or_default();
+        json!({
+            "log": {
+                "version": "1.2",
+                "creator": {
+                    "name": rama::utils::info::NAME,
+                    "version": rama::utils::info::VERSION,
+                },
+                "entries": entries,
+            }
+        })
+    }
+
+    /// Write the recorded entries as a HAR 1.2 file to the given path.
+    pub(super) async fn write_to_file(&self, path: impl AsRef<Path>) -> Result<(), BoxError> {
+        let har = serde_json::to_vec_pretty(&self.to_har()).context("serialize HAR log")?;
+        tokio::fs::write(path, har)
+            .await
+            .context("write HAR log to file")?;
+        Ok(())
+    }
+}
+
+impl HarRecorderInner {
+    fn headers(&self, headers: &HeaderMap) -> Vec<Value> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if self.redact
+                    && [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE, SET_COOKIE].contains(name)
+                {
+                    REDACTED.into()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes())
+                };
+                json!({ "name": name.as_str(), "value": value })
+            })
+            .collect()
+    }
+}
+
+fn mime_type(headers: &HeaderMap) -> String {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_owned()
+}
+
+impl RequestWriter for HarRecorder {
+    async fn write_request(&self, req: Request) {
+        let Some(inner) = self.inner.as_ref() else {
+            return;
+        };
+
+        let started = SystemTime::now();
+        let (parts, body) = req.into_parts();
+        let body = match body.collect().await {
+            Ok(body) => body.to_bytes(),
+            Err(err) => {
+                tracing::error!(err = %err, "HAR recorder: failed to collect request body");
+                Default::default()
+            }
+        };
+
+        let query_string: Vec<_> = parts
+            .uri
+            .query()
+            .into_iter()
+            .flat_map(|query| query.split('&'))
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                json!({ "name": name, "value": value })
+            })
+            .collect();
+
+        let mut request = json!({
+            "method": parts.method.as_str(),
+            "url": parts.uri.to_string(),
+            "httpVersion": format!("{:?}", parts.version),
+            "cookies": [],
+            "headers": inner.headers(&parts.headers),
+            "queryString": query_string,
+            "headersSize": -1,
+            "bodySize": body.len(),
+        });
+        if inner.bodies && !body.is_empty() {
+            request["postData"] = json!({
+                "mimeType": mime_type(&parts.headers),
+                "text": String::from_utf8_lossy(&body),
+            });
+        }
+
+        inner.state.lock().unwrap().pending = Some(PendingEntry {
+            started,
+            instant: Instant::now(),
+            request,
+        });
+    }
+}
+
+impl ResponseWriter for HarRecorder {
+    async fn write_response(&self, res: Response) {
+        let Some(inner) = self.inner.as_ref() else {
+            return;
+        };
+
+        let (parts, body) = res.into_parts();
+        let body = match body.collect().await {
+            Ok(body) => body.to_bytes(),
+            Err(err) => {
+                tracing::error!(err = %err, "HAR recorder: failed to collect response body");
+                Default::default()
+            }
+        };
+
+        let mut state = inner.state.lock().unwrap();
+        let Some(PendingEntry {
+            started,
+            instant,
+            request,
+        }) = state.pending.take()
+        else {
+            tracing::error!("HAR recorder: received response without a recorded request");
+            return;
+        };
+        let time = duration_ms(instant.elapsed());
+        let timings = parts
+            .extensions
+            .get::<ConnectTimings>()
+            .copied()
+            .unwrap_or_default();
+        let dns = phase_ms(timings.dns);
+        let ssl = phase_ms(timings.tls);
+        // as per the HAR spec the connect time includes the ssl time
+        let connect = match (timings.tcp_connect, timings.tls) {
+            (None, None) => -1.0,
+            (tcp, tls) => phase_ms(tcp).max(0.0) + phase_ms(tls).max(0.0),
+        };
+        let wait = (time - dns.max(0.0) - connect.max(0.0)).max(0.0);
+
+        let mut content = json!({
+            "size": body.len(),
+            "mimeType": mime_type(&parts.headers),
+        });
+        if inner.bodies {
+            content["text"] = String::from_utf8_lossy(&body).into();
+        }
+
+        let response = json!({
+            "status": parts.status.as_u16(),
+            "statusText": parts.status.canonical_reason().unwrap_or_default(),
+            "httpVersion": format!("{:?}", parts.version),
+            "cookies": [],
+            "headers": inner.headers(&parts.headers),
+            "content": content,
+            "redirectURL": parts
+                .headers
+                .get(LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default(),
+            "headersSize": -1,
+            "bodySize": body.len(),
+        });
+
+        state.entries.push(json!({
+            "startedDateTime": format_rfc3339(started),
+            "time": time,
+            "request": request,
+            "response": response,
+            "cache": {},
+            "timings": {
+                "blocked": -1,
+                "dns": dns,
+                "connect": connect,
+                "send": 0,
+                "wait": wait,
+                "receive": 0,
+                "ssl": ssl,
+            },
+        }));
+    }
+}
+
+fn duration_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Duration of the given phase in milliseconds, or `-1` if it did not take place.
+fn phase_ms(phase: Option<PhaseTiming>) -> f64 {
+    phase.map_or(-1.0, |phase| duration_ms(phase.duration()))
+}
+
+/// Format a [`SystemTime`] as an RFC 3339 (UTC) timestamp with millisecond precision,
+/// as required for the `startedDateTime` field of a HAR entry.
+fn format_rfc3339(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO);
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // civil from days, see <http://howardhinnant.github.io/date_algorithms.html>
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        secs_of_day / 3_600,
+        (secs_of_day % 3_600) / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis(),
+    )
+}
@@ -16,7 +16,7 @@ use rama::{
             follow_redirect::{policy::Limited, FollowRedirectLayer},
             required_header::AddRequiredRequestHeadersLayer,
             timeout::TimeoutLayer,
-            traffic_writer::WriterMode,
+            traffic_writer::{RequestWriterLayer, ResponseWriterLayer, WriterMode},
         },
         IntoResponse, Request, Response, StatusCode,
     },
@@ -41,6 +41,8 @@ use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, Env
 
 use crate::error::ErrorWithExitCode;
 
+mod client_auth;
+mod har;
 mod writer;
 
 #[derive(Args, Debug, Clone)]
@@ -93,9 +95,25 @@ pub struct CliCommandHttp {
     /// the desired tls version to use (automatically defined by default, choices are: 1.2, 1.3)
     tls: Option<String>,
 
+    #[arg(long, requires = "key", conflicts_with = "pkcs12")]
+    /// the client tls certificate (chain) file path to use (PEM),
+    /// used for client authentication (mTLS)
+    cert: Option<String>,
+
+    #[arg(long, requires = "cert", conflicts_with = "pkcs12")]
+    /// the client tls private key file path to use (PEM),
+    /// used for client authentication (mTLS)
+    key: Option<String>,
+
     #[arg(long)]
-    /// the client tls key file path to use
-    cert_key: Option<String>,
+    /// the client tls PKCS#12 file path to use (DER),
+    /// containing both certificate (chain) and private key
+    /// used for client authentication (mTLS)
+    pkcs12: Option<String>,
+
+    #[arg(long, requires = "pkcs12")]
+    /// the password of the client tls PKCS#12 file (see --pkcs12)
+    pkcs12_pass: Option<String>,
 
     #[arg(long, short = 't', default_value = "0")]
     /// the timeout in seconds for each connection (0 = default timeout of 180s)
@@ -133,6 +151,18 @@ pub struct CliCommandHttp {
     /// write output to file instead of stdout
     output: Option<String>,
 
+    #[arg(long)]
+    /// export the exchange (including redirects) as a HAR 1.2 file to the given path
+    har: Option<String>,
+
+    #[arg(long)]
+    /// include request and response bodies in the HAR file (see --har)
+    har_bodies: bool,
+
+    #[arg(long)]
+    /// redact sensitive headers (e.g. Authorization, Cookie) in the HAR file (see --har)
+    har_redact: bool,
+
     #[arg(long)]
     /// print debug info
     debug: bool,
@@ -265,10 +295,20 @@ async fn run_inner(guard: ShutdownGuard, cfg: CliCommandHttp) -> Result<(), BoxE
 
     let request = request_args_builder.build()?;
 
-    let client = create_client(guard, cfg.clone()).await?;
+    let har_recorder = if cfg.har.is_some() {
+        har::HarRecorder::new(cfg.har_bodies, cfg.har_redact)
+    } else {
+        har::HarRecorder::disabled()
+    };
+
+    let client = create_client(guard, cfg.clone(), har_recorder.clone()).await?;
 
     let response = client.serve(Context::default(), request).await?;
 
+    if let Some(path) = cfg.har.as_deref() {
+        har_recorder.write_to_file(path).await?;
+    }
+
     if cfg.check_status {
         let status = response.status();
         if status.is_client_error() {
@@ -292,6 +332,7 @@ async fn run_inner(guard: ShutdownGuard, cfg: CliCommandHttp) -> Result<(), BoxE
 async fn create_client<S>(
     guard: ShutdownGuard,
     mut cfg: CliCommandHttp,
+    har_recorder: har::HarRecorder,
 ) -> Result<impl Service<S, Request, Response = Response, Error = BoxError>, BoxError>
 where
     S: Clone + Send + Sync + 'static,
@@ -339,6 +380,8 @@ where
     )
     .await?;
 
+    let client_auth = client_auth::load_client_auth(&cfg).context("load client tls auth")?;
+
     let mut inner_client = HttpClient::default();
 
     let server_verify_mode = if cfg.insecure {
@@ -349,6 +392,7 @@ where
 
     inner_client.set_tls_config(ClientConfig {
         server_verify_mode,
+        client_auth,
         extensions: Some(vec![
             ClientHelloExtension::ApplicationLayerProtocolNegotiation(vec![
                 ApplicationProtocol::HTTP_2,
@@ -376,6 +420,7 @@ where
             0
         })),
         response_writer,
+        ResponseWriterLayer::new(har_recorder.clone()),
         DecompressionLayer::new(),
         cfg.auth
             .as_deref()
@@ -400,6 +445,7 @@ where
             .unwrap_or_else(AddAuthorizationLayer::none),
         AddRequiredRequestHeadersLayer::default(),
         request_writer,
+        RequestWriterLayer::new(har_recorder),
         match cfg.proxy {
             None => HttpProxyAddressLayer::try_from_env_default()?,
             Some(proxy) => {
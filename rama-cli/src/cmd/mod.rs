@@ -4,4 +4,6 @@ pub mod echo;
 pub mod fp;
 pub mod http;
 pub mod ip;
+pub mod probe;
 pub mod proxy;
+pub mod serve;
@@ -0,0 +1,25 @@
+//! rama probe commands
+
+use clap::{Args, Subcommand};
+use rama::error::BoxError;
+
+mod tls;
+
+#[derive(Debug, Args)]
+/// rama probe commands (inspect the capabilities of a remote server)
+pub struct CliCommandProbe {
+    #[command(subcommand)]
+    cmd: ProbeCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum ProbeCommand {
+    Tls(tls::CliCommandProbeTls),
+}
+
+/// run the rama probe command
+pub async fn run(cfg: CliCommandProbe) -> Result<(), BoxError> {
+    match cfg.cmd {
+        ProbeCommand::Tls(cfg) => tls::run(cfg).await,
+    }
+}
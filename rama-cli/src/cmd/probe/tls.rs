@@ -0,0 +1,251 @@
+//! rama probe tls command
+
+use clap::Args;
+use rama::{
+    error::{BoxError, ErrorContext, OpaqueError},
+    net::address::Authority,
+    tls::rustls::{
+        dep::{
+            pki_types::ServerName,
+            rustls::{
+                crypto::{aws_lc_rs, CryptoProvider},
+                version::{TLS12, TLS13},
+                ClientConfig, SupportedCipherSuite, SupportedProtocolVersion,
+            },
+            tokio_rustls::TlsConnector,
+        },
+        verify::NoServerCertVerifier,
+    },
+};
+use serde::Serialize;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{
+    io::{stdout, AsyncWriteExt},
+    net::TcpStream,
+};
+
+#[derive(Debug, Args)]
+/// enumerate the tls versions and cipher suites supported by a server
+///
+/// This is done by attempting a handshake per tls version and cipher suite,
+/// each time using a client config restricted to only that version or suite.
+/// Only the versions and cipher suites supported by rama (rustls) can be probed.
+pub(super) struct CliCommandProbeTls {
+    /// the authority (host:port) of the server to probe
+    authority: Authority,
+
+    #[arg(long)]
+    /// print the report as json
+    json: bool,
+
+    #[arg(long, short = 't', default_value_t = 5)]
+    /// the timeout in seconds for each handshake attempt
+    timeout: u64,
+}
+
+/// Support of a server for a single tls version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct TlsVersionSupport {
+    version: String,
+    supported: bool,
+    cipher_suites: Vec<String>,
+}
+
+/// run the rama probe tls command
+pub(super) async fn run(cfg: CliCommandProbeTls) -> Result<(), BoxError> {
+    let addr = tokio::net::lookup_host(cfg.authority.to_string())
+        .await
+        .context("resolve authority")?
+        .next()
+        .ok_or_else(|| {
+            OpaqueError::from_display(format!("no address found for {}", cfg.authority))
+        })?;
+    let server_name = ServerName::try_from(cfg.authority.host().to_string())
+        .context("create tls server name from host")?;
+
+    let report = probe_tls(addr, server_name, Duration::from_secs(cfg.timeout)).await;
+
+    let output = if cfg.json {
+        let mut output = serde_json::to_string_pretty(&report).context("encode json report")?;
+        output.push('\n');
+        output
+    } else {
+        let mut output = String::new();
+        for support in &report {
+            if support.supported {
+                output.push_str(&format!("{}: supported\n", support.version));
+                for cipher_suite in &support.cipher_suites {
+                    output.push_str(&format!("  {cipher_suite}\n"));
+                }
+            } else {
+                output.push_str(&format!("{}: not supported\n", support.version));
+            }
+        }
+        output
+    };
+
+    let mut stdout = stdout();
+    stdout.write_all(output.as_bytes()).await?;
+    stdout.flush().await?;
+
+    Ok(())
+}
+
+/// Probe the tls versions and cipher suites supported by the server at the given address.
+pub(crate) async fn probe_tls(
+    addr: SocketAddr,
+    server_name: ServerName<'static>,
+    timeout: Duration,
+) -> Vec<TlsVersionSupport> {
+    let provider = aws_lc_rs::default_provider();
+
+    let mut report = Vec::new();
+    for version in [&TLS12, &TLS13] {
+        let cipher_suites: Vec<SupportedCipherSuite> = provider
+            .cipher_suites
+            .iter()
+            .filter(|suite| suite.version() == version)
+            .copied()
+            .collect();
+
+        let supported = handshake(
+            addr,
+            server_name.clone(),
+            version,
+            cipher_suites.clone(),
+            timeout,
+        )
+        .await;
+
+        let mut supported_cipher_suites = Vec::new();
+        if supported {
+            for suite in cipher_suites {
+                if handshake(addr, server_name.clone(), version, vec![suite], timeout).await {
+                    supported_cipher_suites.push(format!("{:?}", suite.suite()));
+                }
+            }
+        }
+
+        report.push(TlsVersionSupport {
+            version: format!("{:?}", version.version),
+            supported,
+            cipher_suites: supported_cipher_suites,
+        });
+    }
+    report
+}
+
+/// Attempt a tls handshake restricted to the given version and cipher suites,
+/// returning whether or not it succeeded.
+async fn handshake(
+    addr: SocketAddr,
+    server_name: ServerName<'static>,
+    version: &'static SupportedProtocolVersion,
+    cipher_suites: Vec<SupportedCipherSuite>,
+    timeout: Duration,
+) -> bool {
+    let provider = CryptoProvider {
+        cipher_suites,
+        ..aws_lc_rs::default_provider()
+    };
+    let config = match ClientConfig::builder_with_provider(Arc::new(provider))
+        .with_protocol_versions(&[version])
+    {
+        Ok(builder) => builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoServerCertVerifier::new()))
+            .with_no_client_auth(),
+        Err(err) => {
+            tracing::debug!(%err, ?version, "invalid tls client config");
+            return false;
+        }
+    };
+    let connector = TlsConnector::from(Arc::new(config));
+
+    let result = tokio::time::timeout(timeout, async move {
+        let stream = TcpStream::connect(addr).await?;
+        connector.connect(server_name, stream).await
+    })
+    .await;
+
+    match result {
+        Ok(Ok(_)) => true,
+        Ok(Err(err)) => {
+            tracing::debug!(%err, ?version, "tls handshake failed");
+            false
+        }
+        Err(_) => {
+            tracing::debug!(?version, "tls handshake timed out");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama::tls::rustls::dep::{
+        pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer},
+        rcgen,
+        rustls::{crypto::aws_lc_rs::cipher_suite, ServerConfig},
+        tokio_rustls::TlsAcceptor,
+    };
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_probe_tls_restricted_server() {
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+
+        // only TLS 1.3 with a single cipher suite
+        let provider = CryptoProvider {
+            cipher_suites: vec![cipher_suite::TLS13_AES_256_GCM_SHA384],
+            ..aws_lc_rs::default_provider()
+        };
+        let server_config = ServerConfig::builder_with_provider(Arc::new(provider))
+            .with_protocol_versions(&[&TLS13])
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![cert.der().clone()],
+                PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key_pair.serialize_der())),
+            )
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    let _ = acceptor.accept(stream).await;
+                });
+            }
+        });
+
+        let report = probe_tls(
+            addr,
+            ServerName::try_from("localhost").unwrap(),
+            Duration::from_secs(5),
+        )
+        .await;
+
+        assert_eq!(
+            report,
+            vec![
+                TlsVersionSupport {
+                    version: "TLSv1_2".to_owned(),
+                    supported: false,
+                    cipher_suites: vec![],
+                },
+                TlsVersionSupport {
+                    version: "TLSv1_3".to_owned(),
+                    supported: true,
+                    cipher_suites: vec!["TLS13_AES_256_GCM_SHA384".to_owned()],
+                },
+            ]
+        );
+    }
+}
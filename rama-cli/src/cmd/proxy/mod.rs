@@ -2,9 +2,13 @@
 
 use clap::Args;
 use rama::{
-    error::BoxError,
+    combinators::Either,
+    error::{BoxError, ErrorContext, OpaqueError},
     http::{
-        client::HttpClient,
+        client::{
+            proxy::layer::{HttpProxyConnector, SetProxyAuthHttpHeaderLayer},
+            HttpClient,
+        },
         layer::{
             remove_header::{RemoveRequestHeaderLayer, RemoveResponseHeaderLayer},
             trace::TraceLayer,
@@ -15,17 +19,31 @@ use rama::{
         Body, IntoResponse, Request, Response, StatusCode,
     },
     layer::{limit::policy::ConcurrentPolicy, LimitLayer, TimeoutLayer},
-    net::http::RequestContext,
-    net::stream::layer::http::BodyLimitLayer,
+    net::{
+        address::Authority,
+        client::{ConnectorService, EstablishedClientConnection},
+        http::RequestContext,
+        stream::layer::http::BodyLimitLayer,
+        transport::TransportContext,
+        Protocol,
+    },
     rt::Executor,
     service::service_fn,
-    tcp::{client::default_tcp_connect, server::TcpListener, utils::is_connection_error},
+    tcp::{
+        client::{default_tcp_connect, service::TcpConnector},
+        server::TcpListener,
+        utils::is_connection_error,
+    },
     Context, Layer, Service,
 };
-use std::{convert::Infallible, time::Duration};
+use std::{convert::Infallible, path::PathBuf, sync::Arc, time::Duration};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
+mod config;
+pub(crate) use config::ProxyRules;
+
 #[derive(Debug, Args)]
 /// rama proxy server
 pub struct CliCommandProxy {
@@ -44,6 +62,10 @@ pub struct CliCommandProxy {
     #[arg(long, short = 't', default_value_t = 8)]
     /// the timeout in seconds for each connection (0 = no timeout)
     timeout: u64,
+
+    #[arg(long)]
+    /// YAML (or JSON) file with the rules used to select the upstream proxy (if any) per target
+    proxy_config: Option<PathBuf>,
 }
 
 /// run the rama proxy service
@@ -57,31 +79,25 @@ pub async fn run(cfg: CliCommandProxy) -> Result<(), BoxError> {
         )
         .init();
 
+    let rules = match cfg.proxy_config.as_ref() {
+        Some(path) => ProxyRules::load(path)?,
+        None => ProxyRules::default(),
+    };
+
     let graceful = rama::graceful::Shutdown::default();
 
     let address = format!("{}:{}", cfg.interface, cfg.port);
     tracing::info!("starting proxy on: {}", address);
 
     graceful.spawn_task_fn(move |guard| async move {
-        let tcp_service = TcpListener::build()
+        let tcp_service = TcpListener::build_with_state(Arc::new(rules))
             .bind(address)
             .await
             .expect("bind proxy to 127.0.0.1:62001");
 
         let exec = Executor::graceful(guard.clone());
-        let http_service = HttpServer::auto(exec).service(
-            (
-                TraceLayer::new_for_http(),
-                UpgradeLayer::new(
-                    MethodMatcher::CONNECT,
-                    service_fn(http_connect_accept),
-                    service_fn(http_connect_proxy),
-                ),
-                RemoveResponseHeaderLayer::hop_by_hop(),
-                RemoveRequestHeaderLayer::hop_by_hop(),
-            )
-                .layer(service_fn(http_plain_proxy)),
-        );
+        let http_service =
+            HttpServer::auto(exec).service(TraceLayer::new_for_http().layer(http_proxy_service()));
 
         let tcp_service_builder = (
             // protect the http proxy from too large bodies, both from request and response end
@@ -90,6 +106,7 @@ pub async fn run(cfg: CliCommandProxy) -> Result<(), BoxError> {
             (cfg.timeout > 0).then(|| TimeoutLayer::new(Duration::from_secs(cfg.timeout))),
         );
 
+        tracing::info!("proxy service ready");
         tcp_service
             .serve_graceful(guard, tcp_service_builder.layer(http_service))
             .await;
@@ -102,6 +119,22 @@ pub async fn run(cfg: CliCommandProxy) -> Result<(), BoxError> {
     Ok(())
 }
 
+/// The http proxy service, tunneling CONNECT requests and forwarding plain http requests,
+/// via the upstream proxy selected by the [`ProxyRules`] (if any).
+pub(crate) fn http_proxy_service(
+) -> impl Service<Arc<ProxyRules>, Request, Response = Response, Error = Infallible> + Clone {
+    (
+        UpgradeLayer::new(
+            MethodMatcher::CONNECT,
+            service_fn(http_connect_accept),
+            service_fn(http_connect_proxy),
+        ),
+        RemoveResponseHeaderLayer::hop_by_hop(),
+        RemoveRequestHeaderLayer::hop_by_hop(),
+    )
+        .layer(service_fn(http_plain_proxy))
+}
+
 async fn http_connect_accept<S>(
     mut ctx: Context<S>,
     req: Request,
@@ -120,17 +153,17 @@ where
     Ok((StatusCode::OK.into_response(), ctx, req))
 }
 
-async fn http_connect_proxy<S>(ctx: Context<S>, mut upgraded: Upgraded) -> Result<(), Infallible>
-where
-    S: Clone + Send + Sync + 'static,
-{
+async fn http_connect_proxy(
+    ctx: Context<Arc<ProxyRules>>,
+    mut upgraded: Upgraded,
+) -> Result<(), Infallible> {
     let authority = ctx // assumption validated by `http_connect_accept`
         .get::<RequestContext>()
         .unwrap()
         .authority
         .clone();
     tracing::info!("CONNECT to {authority}");
-    let (mut stream, _) = match default_tcp_connect(&ctx, authority).await {
+    let mut stream = match connect_upstream(ctx, authority).await {
         Ok(stream) => stream,
         Err(err) => {
             tracing::error!(error = %err, "error connecting to host");
@@ -145,11 +178,51 @@ where
     Ok(())
 }
 
-async fn http_plain_proxy<S>(ctx: Context<S>, req: Request) -> Result<Response, Infallible>
-where
-    S: Clone + Send + Sync + 'static,
-{
-    let client = HttpClient::default();
+async fn connect_upstream(
+    mut ctx: Context<Arc<ProxyRules>>,
+    authority: Authority,
+) -> Result<impl AsyncRead + AsyncWrite + Unpin, OpaqueError> {
+    let Some(upstream) = ctx.state().upstream_for(&authority).cloned() else {
+        let (stream, _) = default_tcp_connect(&ctx, authority)
+            .await
+            .context("connect to target")?;
+        return Ok(Either::A(stream));
+    };
+    tracing::info!(
+        "CONNECT to {authority} via upstream proxy {}",
+        upstream.authority
+    );
+
+    // a CONNECT request is an opaque tunnel, so the upstream proxy
+    // always has to be asked to tunnel it, regardless of the target port
+    ctx.remove::<TransportContext>();
+    ctx.insert(upstream);
+    let req = rama::tcp::client::Request::new(authority).with_protocol(Protocol::HTTPS);
+
+    let EstablishedClientConnection { conn, .. } =
+        HttpProxyConnector::required(TcpConnector::new())
+            .connect(ctx, req)
+            .await
+            .map_err(OpaqueError::from_boxed)
+            .context("connect to target via upstream proxy")?;
+    Ok(Either::B(conn))
+}
+
+async fn http_plain_proxy(
+    mut ctx: Context<Arc<ProxyRules>>,
+    req: Request,
+) -> Result<Response, Infallible> {
+    if let Some(upstream) = ctx
+        .get_or_try_insert_with_ctx::<RequestContext, _>(|ctx| (ctx, &req).try_into())
+        .ok()
+        .map(|request_ctx| request_ctx.authority.clone())
+        .and_then(|authority| ctx.state().upstream_for(&authority).cloned())
+    {
+        tracing::info!("forward request via upstream proxy {}", upstream.authority);
+        ctx.insert(upstream);
+    }
+
+    let client = SetProxyAuthHttpHeaderLayer::default().layer(HttpClient::default());
     match client.serve(ctx, req).await {
         Ok(resp) => Ok(resp),
         Err(err) => {
@@ -161,3 +234,148 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    /// Spawn a tcp echo server, which optionally first acts as an http proxy,
+    /// accepting the CONNECT request it receives.
+    async fn spawn_echo(as_proxy: bool) -> (Authority, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut head = Vec::new();
+            if as_proxy {
+                let mut buf = [0u8; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    assert!(n > 0, "unexpected eof");
+                    head.extend_from_slice(&buf[..n]);
+                }
+                stream.write_all(b"HTTP/1.1 200 OK\r\n\r\n").await.unwrap();
+            }
+            let (mut reader, mut writer) = stream.split();
+            tokio::io::copy(&mut reader, &mut writer).await.unwrap();
+            String::from_utf8(head).unwrap()
+        });
+        (addr.into(), handle)
+    }
+
+    async fn ping(mut stream: impl AsyncRead + AsyncWrite + Unpin) {
+        stream.write_all(b"ping").await.unwrap();
+        let mut buf = [0u8; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+        stream.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connect_upstream_direct() {
+        let (target, echo) = spawn_echo(false).await;
+
+        let rules = ProxyRules::from_yaml(
+            br##"{"rules": [{"match": {"host": "a.test"}, "upstream": "http://127.0.0.1:1"}]}"##,
+        )
+        .unwrap();
+        let stream = connect_upstream(Context::with_state(Arc::new(rules)), target)
+            .await
+            .unwrap();
+        ping(stream).await;
+
+        assert_eq!(echo.await.unwrap(), "");
+    }
+
+    #[tokio::test]
+    async fn test_connect_upstream_via_proxy() {
+        let (proxy, echo) = spawn_echo(true).await;
+
+        let rules = ProxyRules::from_yaml(
+            format!(
+                r##"{{"rules": [
+                    {{"name": "other", "match": {{"host": "b.test"}}, "upstream": "http://127.0.0.1:1"}},
+                    {{"name": "a", "match": {{"host": "a.test", "port": 443}}, "upstream": "http://john:secret@{proxy}"}}
+                ]}}"##
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+        let stream = connect_upstream(
+            Context::with_state(Arc::new(rules)),
+            "sub.a.test:443".parse().unwrap(),
+        )
+        .await
+        .unwrap();
+        ping(stream).await;
+
+        let head = echo.await.unwrap();
+        let mut lines = head.lines();
+        assert_eq!(lines.next(), Some("CONNECT sub.a.test:443 HTTP/1.1"));
+        assert!(
+            lines.any(|line| line.split_once(':').is_some_and(|(name, value)| {
+                name.eq_ignore_ascii_case("proxy-authorization")
+                    && value.trim() == "Basic am9objpzZWNyZXQ="
+            })),
+            "{head}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connect_upstream_yaml_rules() {
+        let (proxy, echo) = spawn_echo(true).await;
+
+        let rules = ProxyRules::from_yaml(
+            format!(
+                r##"
+rules:
+  - name: direct
+    match:
+      host: internal.test
+  - name: a
+    match:
+      host: a.test
+    upstream: http://{proxy}
+  - name: catch-all
+    upstream: http://127.0.0.1:1
+"##
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+        assert!(rules
+            .upstream_for(&"internal.test:443".parse().unwrap())
+            .is_none());
+        assert_eq!(
+            rules
+                .upstream_for(&"example.com:80".parse().unwrap())
+                .unwrap()
+                .authority,
+            "127.0.0.1:1".parse::<Authority>().unwrap()
+        );
+
+        let stream = connect_upstream(
+            Context::with_state(Arc::new(rules)),
+            "a.test:8443".parse().unwrap(),
+        )
+        .await
+        .unwrap();
+        ping(stream).await;
+
+        let head = echo.await.unwrap();
+        assert_eq!(head.lines().next(), Some("CONNECT a.test:8443 HTTP/1.1"));
+    }
+
+    #[test]
+    fn test_proxy_rules_invalid_rule() {
+        let err = ProxyRules::from_yaml(
+            br##"{"rules": [{"name": "ok"}, {"name": "bad", "upstream": "socks5://127.0.0.1:1080"}]}"##,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("rule #2 (bad)"), "{err}");
+    }
+}
@@ -0,0 +1,250 @@
+//! rama serve service
+
+use super::proxy::{http_proxy_service, ProxyRules};
+use clap::Args;
+use rama::{
+    error::{BoxError, OpaqueError},
+    http::{
+        header::ACCEPT,
+        layer::trace::TraceLayer,
+        server::HttpServer,
+        service::fs::{ServeDir, ServeFile},
+        IntoResponse, Method, Request, Response, StatusCode, Version,
+    },
+    layer::{limit::policy::ConcurrentPolicy, LimitLayer, TimeoutLayer},
+    rt::Executor,
+    tcp::server::TcpListener,
+    Context, Layer, Service,
+};
+use std::{convert::Infallible, path::PathBuf, sync::Arc, time::Duration};
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+#[derive(Debug, Args)]
+/// rama serve service (serves static files from a directory)
+pub struct CliCommandServe {
+    #[arg(long, short = 'p', default_value_t = 8080)]
+    /// the port to listen on
+    port: u16,
+
+    #[arg(long, short = 'i', default_value = "127.0.0.1")]
+    /// the interface to listen on
+    interface: String,
+
+    #[arg(long, short = 'c', default_value_t = 0)]
+    /// the number of concurrent connections to allow (0 = no limit)
+    concurrent: usize,
+
+    #[arg(long, short = 't', default_value_t = 8)]
+    /// the timeout in seconds for each connection (0 = no timeout)
+    timeout: u64,
+
+    #[arg(long, short = 'd', default_value = ".")]
+    /// the directory to serve the static files from
+    dir: PathBuf,
+
+    #[arg(long)]
+    /// single page application (SPA) mode: serve the `index.html` file of the root directory
+    /// for unknown paths, in case the request accepts `text/html`
+    ///
+    /// Requests which do not accept `text/html` (e.g. missing assets)
+    /// still result in a `404 Not Found` response.
+    spa: bool,
+
+    #[arg(long)]
+    /// YAML (or JSON) file with the rules used to select the upstream proxy (if any) per target,
+    /// see `rama proxy` for its format
+    ///
+    /// When defined the service also acts as an http proxy: CONNECT requests
+    /// and absolute-form http requests are proxied, all other requests are still
+    /// served from the directory.
+    proxy_config: Option<PathBuf>,
+}
+
+/// run the rama serve service
+pub async fn run(cfg: CliCommandServe) -> Result<(), BoxError> {
+    tracing_subscriber::registry()
+        .with(fmt::layer())
+        .with(
+            EnvFilter::builder()
+                .with_default_directive(LevelFilter::INFO.into())
+                .from_env_lossy(),
+        )
+        .init();
+
+    if !cfg.dir.is_dir() {
+        return Err(OpaqueError::from_display(format!(
+            "serve dir '{}' is not a directory",
+            cfg.dir.display()
+        ))
+        .into());
+    }
+
+    let rules = match cfg.proxy_config.as_ref() {
+        Some(path) => Some(ProxyRules::load(path)?),
+        None => None,
+    };
+
+    let graceful = rama::graceful::Shutdown::default();
+
+    let address = format!("{}:{}", cfg.interface, cfg.port);
+    tracing::info!(
+        "starting serve service for dir '{}' on: {}",
+        cfg.dir.display(),
+        address
+    );
+
+    graceful.spawn_task_fn(move |guard| async move {
+        let proxy = rules.is_some().then(http_proxy_service);
+        let tcp_listener = TcpListener::build_with_state(Arc::new(rules.unwrap_or_default()))
+            .bind(address)
+            .await
+            .expect("bind serve service");
+
+        let exec = Executor::graceful(guard.clone());
+        let serve_dir = ServeDir::new(&cfg.dir).fallback(SpaFallback {
+            index: cfg.spa.then(|| ServeFile::new(cfg.dir.join("index.html"))),
+        });
+        let http_service = HttpServer::auto(exec)
+            .service(TraceLayer::new_for_http().layer(ProxyOrServe { proxy, serve_dir }));
+
+        let tcp_service_builder = (
+            (cfg.concurrent > 0).then(|| LimitLayer::new(ConcurrentPolicy::max(cfg.concurrent))),
+            (cfg.timeout > 0).then(|| TimeoutLayer::new(Duration::from_secs(cfg.timeout))),
+        );
+
+        tracing::info!("serve service ready");
+        tcp_listener
+            .serve_graceful(guard, tcp_service_builder.layer(http_service))
+            .await;
+    });
+
+    graceful
+        .shutdown_with_limit(Duration::from_secs(30))
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+/// Proxies the proxy requests in case an http proxy is defined,
+/// serving all other requests using the inner service.
+struct ProxyOrServe<P, S> {
+    proxy: Option<P>,
+    serve_dir: S,
+}
+
+impl<P, S> Service<Arc<ProxyRules>, Request> for ProxyOrServe<P, S>
+where
+    P: Service<Arc<ProxyRules>, Request, Response = Response, Error = Infallible>,
+    S: Service<Arc<ProxyRules>, Request, Response = Response, Error = Infallible>,
+{
+    type Response = Response;
+    type Error = Infallible;
+
+    async fn serve(
+        &self,
+        ctx: Context<Arc<ProxyRules>>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        match self.proxy.as_ref() {
+            Some(proxy) if is_proxy_request(&req) => proxy.serve(ctx, req).await,
+            _ => self.serve_dir.serve(ctx, req).await,
+        }
+    }
+}
+
+/// Returns `true` for CONNECT requests and absolute-form http/1 requests,
+/// as these are meant for a proxy rather than the origin server.
+///
+/// Http/2 requests always define an authority and are therefore only
+/// proxied when they are CONNECT requests.
+fn is_proxy_request(req: &Request) -> bool {
+    req.method() == Method::CONNECT
+        || (req.version() <= Version::HTTP_11 && req.uri().authority().is_some())
+}
+
+#[derive(Debug, Clone)]
+/// Fallback used for paths that do not map to a file.
+///
+/// In SPA mode it serves the root `index.html` for requests accepting `text/html`,
+/// in all other cases it responds with `404 Not Found`.
+struct SpaFallback {
+    index: Option<ServeFile>,
+}
+
+impl<State> Service<State, Request> for SpaFallback
+where
+    State: Clone + Send + Sync + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        match self.index.as_ref() {
+            Some(index) if accepts_html(&req) => index.serve(ctx, req).await,
+            _ => Ok(StatusCode::NOT_FOUND.into_response()),
+        }
+    }
+}
+
+fn accepts_html(req: &Request) -> bool {
+    req.headers()
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|mime| {
+            mime.split(';')
+                .next()
+                .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("text/html"))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_proxy_request() {
+        let req = |method: Method, uri: &str, version: Version| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .version(version)
+                .body(Default::default())
+                .unwrap()
+        };
+
+        assert!(is_proxy_request(&req(
+            Method::CONNECT,
+            "example.com:443",
+            Version::HTTP_11
+        )));
+        assert!(is_proxy_request(&req(
+            Method::GET,
+            "http://example.com/",
+            Version::HTTP_11
+        )));
+        assert!(is_proxy_request(&req(
+            Method::CONNECT,
+            "example.com:443",
+            Version::HTTP_2
+        )));
+
+        assert!(!is_proxy_request(&req(
+            Method::GET,
+            "/index.html",
+            Version::HTTP_11
+        )));
+        assert!(!is_proxy_request(&req(
+            Method::GET,
+            "http://example.com/index.html",
+            Version::HTTP_2
+        )));
+    }
+}
@@ -8,7 +8,7 @@ use clap::{Parser, Subcommand};
 use rama::error::BoxError;
 
 pub mod cmd;
-use cmd::{echo, fp, http, ip, proxy};
+use cmd::{echo, fp, http, ip, probe, proxy, serve};
 
 pub mod error;
 
@@ -29,6 +29,8 @@ enum CliCommands {
     Echo(echo::CliCommandEcho),
     Ip(ip::CliCommandIp),
     Fp(fp::CliCommandFingerprint),
+    Probe(probe::CliCommandProbe),
+    Serve(serve::CliCommandServe),
 }
 
 #[tokio::main]
@@ -42,6 +44,8 @@ async fn main() -> Result<(), BoxError> {
         CliCommands::Echo(cfg) => echo::run(cfg).await,
         CliCommands::Ip(cfg) => ip::run(cfg).await,
         CliCommands::Fp(cfg) => fp::run(cfg).await,
+        CliCommands::Probe(cfg) => probe::run(cfg).await,
+        CliCommands::Serve(cfg) => serve::run(cfg).await,
     } {
         Ok(()) => Ok(()),
         Err(err) => {
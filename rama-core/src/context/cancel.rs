@@ -0,0 +1,45 @@
+use std::{error, fmt};
+
+use super::Context;
+
+/// A token which can be used to signal cancellation to services and tasks.
+///
+/// Insert a [`CancellationToken`] in the [`Context`] to allow a parent
+/// to cancel the work done on its behalf. Services (e.g. connectors)
+/// wait on [`Context::cancelled`] to abort their in-flight work as soon
+/// as the token is cancelled, failing with a [`Cancelled`] error.
+///
+/// Cancelling a token also cancels all its child tokens,
+/// while cancelling a child token does not affect its parent.
+///
+/// A [`Timeout`] cancels the child token it hands to its inner service
+/// once its [`Deadline`] expires, such that work spawned on behalf
+/// of the request stops as well.
+///
+/// [`Timeout`]: crate::layer::Timeout
+/// [`Deadline`]: crate::layer::Deadline
+pub use tokio_util::sync::CancellationToken;
+
+/// Insert a child of the [`CancellationToken`] found in the [`Context`]
+/// (or a new token if there is none) in the [`Context`], and return it.
+pub(crate) fn insert_child_token<S>(ctx: &mut Context<S>) -> CancellationToken {
+    let token = ctx
+        .get::<CancellationToken>()
+        .map(CancellationToken::child_token)
+        .unwrap_or_default();
+    ctx.insert(token.clone());
+    token
+}
+
+/// Error returned by services which aborted their work
+/// because the [`CancellationToken`] in the [`Context`] was cancelled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("operation cancelled")
+    }
+}
+
+impl error::Error for Cancelled {}
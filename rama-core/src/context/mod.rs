@@ -90,15 +90,21 @@
 //!   to specific properties they need, which can be useful in case that service
 //!   is used in multiple branches, each with their own concrete _state_ type.
 
+use crate::error::{BoxError, ErrorContext, OpaqueError};
 use crate::graceful::ShutdownGuard;
 use crate::rt::Executor;
-use std::{fmt, future::Future};
+use std::{any::type_name, fmt, future::Future};
 use tokio::task::JoinHandle;
 
 mod extensions;
 #[doc(inline)]
 pub use extensions::Extensions;
 
+mod cancel;
+pub(crate) use cancel::insert_child_token;
+#[doc(inline)]
+pub use cancel::{CancellationToken, Cancelled};
+
 /// Context passed to and between services as input.
 ///
 /// See [`crate::context`] for more information.
@@ -284,6 +290,78 @@ impl<S> Context<S> {
         self.extensions.get_mut::<T>()
     }
 
+    /// Get a shared reference to an extension which is required to be present.
+    ///
+    /// Same as [`Self::get`], but returning a [`MissingExtension`] error,
+    /// naming the missing type, in case the extension is absent. Use it
+    /// instead of unwrapping the result of [`Self::get`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rama_core::Context;
+    /// let mut ctx = Context::default();
+    /// assert!(ctx.require::<i32>().is_err());
+    ///
+    /// ctx.insert(5i32);
+    /// assert_eq!(ctx.require::<i32>().unwrap(), &5i32);
+    /// ```
+    pub fn require<T: Send + Sync + 'static>(&self) -> Result<&T, MissingExtension> {
+        self.extensions
+            .get::<T>()
+            .ok_or_else(MissingExtension::new::<T>)
+    }
+
+    /// Get an exclusive reference to an extension which is required to be present.
+    ///
+    /// Same as [`Self::get_mut`], but returning a [`MissingExtension`] error,
+    /// naming the missing type, in case the extension is absent.
+    pub fn require_mut<T: Send + Sync + 'static>(&mut self) -> Result<&mut T, MissingExtension> {
+        self.extensions
+            .get_mut::<T>()
+            .ok_or_else(MissingExtension::new::<T>)
+    }
+
+    /// Map the (required) extension `T` fallibly into an extension `U`,
+    /// inserting it into the [`Context`] and returning an exclusive reference to it.
+    ///
+    /// The extension `T` is kept as is. An error is returned,
+    /// naming the types involved, in case `T` is missing or the conversion failed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rama_core::Context;
+    /// let mut ctx = Context::default();
+    /// ctx.insert(String::from("42"));
+    ///
+    /// let n = ctx.try_map_extension(|s: &String| s.parse::<u8>()).unwrap();
+    /// assert_eq!(*n, 42);
+    /// assert_eq!(ctx.get::<u8>(), Some(&42));
+    /// ```
+    pub fn try_map_extension<T, U, E>(
+        &mut self,
+        f: impl FnOnce(&T) -> Result<U, E>,
+    ) -> Result<&mut U, OpaqueError>
+    where
+        T: Send + Sync + 'static,
+        U: Clone + Send + Sync + 'static,
+        E: Into<BoxError>,
+    {
+        let src = self.require::<T>().map_err(OpaqueError::from_std)?;
+        let value = f(src)
+            .map_err(|err| OpaqueError::from_boxed(err.into()))
+            .with_context(|| {
+                format!(
+                    "map context extension {} into {}",
+                    type_name::<T>(),
+                    type_name::<U>()
+                )
+            })?;
+        self.extensions.insert(value);
+        Ok(self.extensions.get_mut().unwrap())
+    }
+
     /// Inserts a value into the map computed from `f` into if it is [`None`],
     /// then returns an exclusive reference to the contained value.
     ///
@@ -516,6 +594,26 @@ impl<S> Context<S> {
     pub fn guard(&self) -> Option<&ShutdownGuard> {
         self.executor.guard()
     }
+
+    /// Returns `true` if the [`CancellationToken`] in this [`Context`] is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.get::<CancellationToken>()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Returns a future which resolves once the [`CancellationToken`]
+    /// in this [`Context`] is cancelled.
+    ///
+    /// The future never resolves in case the [`Context`] has no [`CancellationToken`].
+    pub fn cancelled(&self) -> impl Future<Output = ()> + Send + 'static {
+        let token = self.get::<CancellationToken>().cloned();
+        async move {
+            match token {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        }
+    }
 }
 
 impl<S: Clone> Context<S> {
@@ -524,3 +622,95 @@ impl<S: Clone> Context<S> {
         self.state.clone()
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Error returned by [`Context::require`] and [`Context::require_mut`]
+/// in case the required extension is missing.
+pub struct MissingExtension {
+    type_name: &'static str,
+}
+
+impl MissingExtension {
+    /// Create a new [`MissingExtension`] error for the extension type `T`.
+    pub fn new<T: ?Sized>() -> Self {
+        Self {
+            type_name: type_name::<T>(),
+        }
+    }
+
+    /// Returns the type name of the missing extension.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl fmt::Display for MissingExtension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "missing required context extension: {}", self.type_name)
+    }
+}
+
+impl std::error::Error for MissingExtension {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Port(u16);
+
+    #[test]
+    fn test_require_present() {
+        let mut ctx = Context::default();
+        ctx.insert(Port(8080));
+        assert_eq!(ctx.require::<Port>().unwrap(), &Port(8080));
+
+        ctx.require_mut::<Port>().unwrap().0 = 443;
+        assert_eq!(ctx.get::<Port>(), Some(&Port(443)));
+    }
+
+    #[test]
+    fn test_require_missing() {
+        let mut ctx = Context::default();
+
+        let err = ctx.require::<Port>().unwrap_err();
+        assert_eq!(err, MissingExtension::new::<Port>());
+        assert!(err.type_name().ends_with("Port"), "{err}");
+        assert!(err.to_string().contains("Port"), "{err}");
+
+        assert!(ctx.require_mut::<Port>().is_err());
+    }
+
+    #[test]
+    fn test_try_map_extension() {
+        let mut ctx = Context::default();
+        ctx.insert(String::from("8080"));
+
+        let port = ctx
+            .try_map_extension(|s: &String| s.parse().map(Port))
+            .unwrap();
+        assert_eq!(*port, Port(8080));
+        assert_eq!(ctx.get::<Port>(), Some(&Port(8080)));
+        // source extension is kept
+        assert_eq!(ctx.get::<String>().map(String::as_str), Some("8080"));
+    }
+
+    #[test]
+    fn test_try_map_extension_failure() {
+        let mut ctx = Context::default();
+
+        // missing source extension
+        let err = ctx
+            .try_map_extension(|s: &String| s.parse().map(Port))
+            .unwrap_err();
+        assert!(err.to_string().contains("String"), "{err}");
+
+        // failing conversion
+        ctx.insert(String::from("not a port"));
+        let err = ctx
+            .try_map_extension(|s: &String| s.parse().map(Port))
+            .unwrap_err();
+        assert!(err.to_string().contains("Port"), "{err}");
+        assert!(ctx.get::<Port>().is_none());
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{Context, Layer, Service};
+use rama_utils::macros::define_inner_service_accessors;
+use std::{fmt, future::Future};
+
+/// Service returned by the [`and_then`] combinator.
+///
+/// Once the inner service resolves successfully, its response is passed
+/// to an async function, which can either produce a (new) response or an error.
+/// Errors of the inner service are passed through, converted into the
+/// error type of that async function.
+///
+/// It is similar to the [`Result::and_then`] method.
+///
+/// [`and_then`]: crate::Service::and_then
+#[derive(Clone)]
+pub struct AndThen<S, F> {
+    inner: S,
+    f: F,
+}
+
+impl<S, F> fmt::Debug for AndThen<S, F>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AndThen")
+            .field("inner", &self.inner)
+            .field("f", &format_args!("{}", std::any::type_name::<F>()))
+            .finish()
+    }
+}
+
+/// A [`Layer`] that produces [`AndThen`] services.
+///
+/// [`Layer`]: crate::Layer
+#[derive(Clone)]
+pub struct AndThenLayer<F> {
+    f: F,
+}
+
+impl<F> fmt::Debug for AndThenLayer<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AndThenLayer")
+            .field("f", &format_args!("{}", std::any::type_name::<F>()))
+            .finish()
+    }
+}
+
+impl<S, F> AndThen<S, F> {
+    /// Creates a new [`AndThen`] service.
+    pub const fn new(inner: S, f: F) -> Self {
+        AndThen { f, inner }
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S, F, Fut, State, Request, Response, Error> Service<State, Request> for AndThen<S, F>
+where
+    S: Service<State, Request, Error: Into<Error>>,
+    F: FnOnce(S::Response) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Result<Response, Error>> + Send + 'static,
+    State: Clone + Send + Sync + 'static,
+    Request: Send + 'static,
+    Response: Send + 'static,
+    Error: Send + Sync + 'static,
+{
+    type Response = Response;
+    type Error = Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        match self.inner.serve(ctx, req).await {
+            Ok(resp) => (self.f.clone())(resp).await,
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl<F> AndThenLayer<F> {
+    /// Creates a new [`AndThenLayer`].
+    pub const fn new(f: F) -> Self {
+        AndThenLayer { f }
+    }
+}
+
+impl<S, F> Layer<S> for AndThenLayer<F>
+where
+    F: Clone,
+{
+    type Service = AndThen<S, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AndThen {
+            f: self.f.clone(),
+            inner,
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Middleware that aborts requests once the [`CancellationToken`]
+//! in the [`Context`] is cancelled.
+//!
+//! A parent can insert a [`CancellationToken`] in the [`Context`]
+//! and cancel it to signal the services it called to stop their work.
+//! The [`Cancel`] middleware races the inner service against that token,
+//! failing promptly with a [`Cancelled`] error once it is cancelled,
+//! dropping the in-flight future of the inner service.
+//!
+//! Services which spawn their own (sub)tasks should wait on
+//! [`Context::cancelled`] themselves to abort that work as well.
+//!
+//! # Example
+//!
+//! ```
+//! use rama_core::context::{CancellationToken, Cancelled};
+//! use rama_core::error::BoxError;
+//! use rama_core::layer::CancelLayer;
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Layer, Service};
+//! use std::time::Duration;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let service = CancelLayer::new().layer(service_fn(|()| async move {
+//!     tokio::time::sleep(Duration::from_secs(60)).await;
+//!     Ok::<_, BoxError>(())
+//! }));
+//!
+//! let token = CancellationToken::new();
+//! let mut ctx = Context::default();
+//! ctx.insert(token.clone());
+//!
+//! token.cancel();
+//! let err = service.serve(ctx, ()).await.unwrap_err();
+//! assert!(err.is::<Cancelled>());
+//! # }
+//! ```
+//!
+//! [`CancellationToken`]: crate::context::CancellationToken
+//! [`Context::cancelled`]: crate::Context::cancelled
+
+use crate::context::Cancelled;
+use crate::{Context, Layer, Service};
+use rama_utils::macros::define_inner_service_accessors;
+use std::fmt;
+
+/// Aborts requests once the [`CancellationToken`] in the [`Context`] is cancelled.
+///
+/// See the [module docs](self) for more details.
+///
+/// [`CancellationToken`]: crate::context::CancellationToken
+pub struct Cancel<S> {
+    inner: S,
+}
+
+impl<S> Cancel<S> {
+    /// Creates a new [`Cancel`] service wrapping the given inner service.
+    pub const fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S: fmt::Debug> fmt::Debug for Cancel<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cancel")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<S: Clone> Clone for Cancel<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, S, Request> Service<S, Request> for Cancel<T>
+where
+    Request: Send + 'static,
+    S: Clone + Send + Sync + 'static,
+    T: Service<S, Request, Error: From<Cancelled>>,
+{
+    type Response = T::Response;
+    type Error = T::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<S>,
+        request: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        if ctx.is_cancelled() {
+            return Err(Cancelled.into());
+        }
+        let cancelled = ctx.cancelled();
+        tokio::select! {
+            biased;
+            _ = cancelled => Err(Cancelled.into()),
+            res = self.inner.serve(ctx, request) => res,
+        }
+    }
+}
+
+/// A layer that produces [`Cancel`] services.
+///
+/// See the [module docs](self) for more details.
+#[derive(Debug, Clone, Default)]
+pub struct CancelLayer;
+
+impl CancelLayer {
+    /// Creates a new [`CancelLayer`].
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for CancelLayer {
+    type Service = Cancel<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Cancel::new(inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::CancellationToken;
+    use crate::error::BoxError;
+    use crate::service::service_fn;
+    use std::time::{Duration, Instant};
+
+    fn slow() -> impl Service<(), (), Response = (), Error = BoxError> {
+        service_fn(|()| async move {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(())
+        })
+    }
+
+    #[tokio::test]
+    async fn test_cancel_aborts_slow_call() {
+        let service = CancelLayer::new().layer(slow());
+
+        let token = CancellationToken::new();
+        let mut ctx = Context::default();
+        ctx.insert(token.child_token());
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            token.cancel();
+        });
+
+        let start = Instant::now();
+        let err = service.serve(ctx, ()).await.unwrap_err();
+        assert!(err.is::<Cancelled>());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_without_token() {
+        let service = CancelLayer::new().layer(service_fn(|()| async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok::<_, BoxError>(42)
+        }));
+        assert_eq!(service.serve(Context::default(), ()).await.unwrap(), 42);
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut ctx = Context::default();
+        ctx.insert(token);
+        let err = service.serve(ctx, ()).await.unwrap_err();
+        assert!(err.is::<Cancelled>());
+    }
+}
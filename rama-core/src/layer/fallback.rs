@@ -0,0 +1,314 @@
+//! Middleware to serve a request using a fallback [`Service`],
+//! in case the result of the primary [`Service`] is not desired.
+//!
+//! Common usecases for a fallback are:
+//! - Serving a request using a backup service in case the primary service fails.
+//! - Retrying a request using a different strategy (e.g. a different upstream)
+//!   in case the response of the primary service is rejected by a [`FallbackPolicy`].
+//!
+//! [`Service`]: crate
+
+use crate::{Context, Layer, Service};
+use rama_utils::macros::define_inner_service_accessors;
+use std::fmt;
+
+/// Policy used by a [`Fallback`] service to decide,
+/// based on the result of the primary [`Service`],
+/// whether or not the fallback [`Service`] is to be used instead.
+///
+/// It is implemented for any `Fn(&Result<Response, Error>) -> bool`.
+///
+/// [`Service`]: crate
+pub trait FallbackPolicy<Response, Error>: Send + Sync + 'static {
+    /// Returns `true` in case the fallback [`Service`]
+    /// has to be used instead of the given result.
+    ///
+    /// [`Service`]: crate
+    fn should_fallback(&self, result: &Result<Response, Error>) -> bool;
+}
+
+impl<F, Response, Error> FallbackPolicy<Response, Error> for F
+where
+    F: Fn(&Result<Response, Error>) -> bool + Send + Sync + 'static,
+{
+    fn should_fallback(&self, result: &Result<Response, Error>) -> bool {
+        (self)(result)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+/// The default [`FallbackPolicy`], using the fallback [`Service`]
+/// only in case the primary [`Service`] returned an error.
+///
+/// [`Service`]: crate
+pub struct FallbackOnError;
+
+impl FallbackOnError {
+    /// Create a new [`FallbackOnError`] policy.
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl<Response, Error> FallbackPolicy<Response, Error> for FallbackOnError {
+    fn should_fallback(&self, result: &Result<Response, Error>) -> bool {
+        result.is_err()
+    }
+}
+
+/// Middleware which serves a request using the primary (inner) [`Service`],
+/// and serves that same request using the fallback [`Service`] instead
+/// in case the [`FallbackPolicy`] rejects the result of the primary [`Service`].
+///
+/// Both services receive a clone of the original [`Context`] and request,
+/// which is why the request is required to be [`Clone`]. Requests with
+/// a body which can only be consumed once have to be buffered prior to
+/// this middleware in order to be used with it.
+///
+/// The result of the primary [`Service`] is dropped in case the fallback is used.
+///
+/// [`Service`]: crate
+pub struct Fallback<S, F, P = FallbackOnError> {
+    inner: S,
+    fallback: F,
+    policy: P,
+}
+
+impl<S: fmt::Debug, F: fmt::Debug, P: fmt::Debug> fmt::Debug for Fallback<S, F, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Fallback")
+            .field("inner", &self.inner)
+            .field("fallback", &self.fallback)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl<S, F, P> Clone for Fallback<S, F, P>
+where
+    S: Clone,
+    F: Clone,
+    P: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            fallback: self.fallback.clone(),
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+impl<S, F> Fallback<S, F> {
+    /// Create a new [`Fallback`] service,
+    /// using the fallback service only in case the inner service errors.
+    pub const fn new(inner: S, fallback: F) -> Self {
+        Self {
+            inner,
+            fallback,
+            policy: FallbackOnError,
+        }
+    }
+}
+
+impl<S, F, P> Fallback<S, F, P> {
+    /// Create a new [`Fallback`] service,
+    /// using the fallback service in case the given [`FallbackPolicy`]
+    /// rejects the result of the inner service.
+    pub const fn with_policy(inner: S, fallback: F, policy: P) -> Self {
+        Self {
+            inner,
+            fallback,
+            policy,
+        }
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S, F, P, State, Request> Service<State, Request> for Fallback<S, F, P>
+where
+    S: Service<State, Request>,
+    F: Service<State, Request, Response: Into<S::Response>, Error: Into<S::Error>>,
+    P: FallbackPolicy<S::Response, S::Error>,
+    State: Clone + Send + Sync + 'static,
+    Request: Clone + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        let result = self.inner.serve(ctx.clone(), req.clone()).await;
+        if !self.policy.should_fallback(&result) {
+            return result;
+        }
+
+        tracing::trace!("fallback service: primary result rejected by policy");
+        match self.fallback.serve(ctx, req).await {
+            Ok(response) => Ok(response.into()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// A [`Layer`] which wraps a [`Service`] in a [`Fallback`] service.
+///
+/// [`Service`]: crate
+pub struct FallbackLayer<F, P = FallbackOnError> {
+    fallback: F,
+    policy: P,
+}
+
+impl<F: fmt::Debug, P: fmt::Debug> fmt::Debug for FallbackLayer<F, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FallbackLayer")
+            .field("fallback", &self.fallback)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl<F, P> Clone for FallbackLayer<F, P>
+where
+    F: Clone,
+    P: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            fallback: self.fallback.clone(),
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+impl<F> FallbackLayer<F> {
+    /// Create a new [`FallbackLayer`],
+    /// using the fallback service only in case the inner service errors.
+    pub const fn new(fallback: F) -> Self {
+        Self {
+            fallback,
+            policy: FallbackOnError,
+        }
+    }
+}
+
+impl<F, P> FallbackLayer<F, P> {
+    /// Create a new [`FallbackLayer`],
+    /// using the fallback service in case the given [`FallbackPolicy`]
+    /// rejects the result of the inner service.
+    pub const fn with_policy(fallback: F, policy: P) -> Self {
+        Self { fallback, policy }
+    }
+}
+
+impl<S, F, P> Layer<S> for FallbackLayer<F, P>
+where
+    F: Clone,
+    P: Clone,
+{
+    type Service = Fallback<S, F, P>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Fallback::with_policy(inner, self.fallback.clone(), self.policy.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::service_fn;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[tokio::test]
+    async fn test_fallback_on_primary_error() {
+        let service = FallbackLayer::new(service_fn(|req: String| async move {
+            Ok::<_, String>(format!("fallback: {req}"))
+        }))
+        .layer(service_fn(|_: String| async move {
+            Err::<String, _>("primary failed".to_owned())
+        }));
+
+        let resp = service
+            .serve(Context::default(), "hello".to_owned())
+            .await
+            .unwrap();
+        assert_eq!(resp, "fallback: hello");
+    }
+
+    #[tokio::test]
+    async fn test_fallback_untouched_on_primary_success() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let fallback = service_fn({
+            let counter = counter.clone();
+            move |req: String| {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok::<_, String>(format!("fallback: {req}"))
+                }
+            }
+        });
+
+        let service = FallbackLayer::new(fallback).layer(service_fn(|req: String| async move {
+            Ok::<_, String>(format!("primary: {req}"))
+        }));
+
+        let resp = service
+            .serve(Context::default(), "hello".to_owned())
+            .await
+            .unwrap();
+        assert_eq!(resp, "primary: hello");
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_with_policy() {
+        let service = FallbackLayer::with_policy(
+            service_fn(|req: String| async move { Ok::<_, String>(format!("fallback: {req}")) }),
+            |result: &Result<String, String>| {
+                result.as_ref().map(|resp| resp.is_empty()).unwrap_or(true)
+            },
+        )
+        .layer(service_fn(|req: String| async move {
+            Ok::<_, String>(if req == "empty" {
+                String::new()
+            } else {
+                format!("primary: {req}")
+            })
+        }));
+
+        let resp = service
+            .serve(Context::default(), "hello".to_owned())
+            .await
+            .unwrap();
+        assert_eq!(resp, "primary: hello");
+
+        let resp = service
+            .serve(Context::default(), "empty".to_owned())
+            .await
+            .unwrap();
+        assert_eq!(resp, "fallback: empty");
+    }
+
+    #[tokio::test]
+    async fn test_fallback_error_is_returned() {
+        let service = Fallback::new(
+            service_fn(|_: String| async move { Err::<String, _>("primary failed".to_owned()) }),
+            service_fn(|_: String| async move { Err::<String, _>("fallback failed".to_owned()) }),
+        );
+
+        let err = service
+            .serve(Context::default(), "hello".to_owned())
+            .await
+            .unwrap_err();
+        assert_eq!(err, "fallback failed");
+    }
+}
@@ -19,6 +19,30 @@ pub use layer::LimitLayer;
 
 mod into_response;
 
+/// A [`Limit`] which bounds the number of concurrent in-flight requests,
+/// using a [`SemaphorePolicy`].
+///
+/// [`SemaphorePolicy`]: policy::SemaphorePolicy
+pub type ConcurrencyLimit<S> = Limit<S, policy::SemaphorePolicy>;
+
+/// A [`LimitLayer`] which bounds the number of concurrent in-flight requests,
+/// using a [`SemaphorePolicy`].
+///
+/// [`SemaphorePolicy`]: policy::SemaphorePolicy
+pub type ConcurrencyLimitLayer = LimitLayer<policy::SemaphorePolicy>;
+
+/// A [`Limit`] which sheds requests while the service is overloaded,
+/// using a [`LoadShedPolicy`].
+///
+/// [`LoadShedPolicy`]: policy::LoadShedPolicy
+pub type LoadShed<T, S> = Limit<T, policy::LoadShedPolicy<S>>;
+
+/// A [`LimitLayer`] which sheds requests while the service is overloaded,
+/// using a [`LoadShedPolicy`].
+///
+/// [`LoadShedPolicy`]: policy::LoadShedPolicy
+pub type LoadShedLayer<S> = LimitLayer<policy::LoadShedPolicy<S>>;
+
 /// Limit requests based on a [`Policy`].
 ///
 /// [`Policy`]: crate::layer::limit::Policy
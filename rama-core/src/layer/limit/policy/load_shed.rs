@@ -0,0 +1,528 @@
+//! A [`Policy`] that sheds load when a measured signal exceeds a threshold.
+//!
+//! See [`LoadShedPolicy`].
+//!
+//! # Examples
+//!
+//! ```
+//! use rama_core::layer::limit::{Limit, policy::{InFlightSignal, LoadShedPolicy}};
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Service};
+//! # use std::convert::Infallible;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//!
+//! let service = service_fn(|_, _| async {
+//!     Ok::<_, Infallible>(())
+//! });
+//! // start shedding above 64 in-flight requests,
+//! // and only accept requests again once it dropped to 48 or less
+//! let policy = LoadShedPolicy::new(InFlightSignal::new(), 64).with_resume_threshold(48);
+//! let mut service = Limit::new(service, policy);
+//!
+//! let response = service.serve(Context::default(), ()).await;
+//! assert!(response.is_ok());
+//! # }
+//! ```
+
+use super::{Overloaded, Policy, PolicyOutput, PolicyResult};
+use crate::Context;
+use parking_lot::Mutex;
+use rama_utils::latency::LatencyHistogram;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A load signal, measured by the [`LoadShedPolicy`] to decide whether to shed a request.
+pub trait LoadSignal: Send + Sync + 'static {
+    /// The value of the signal, compared against the thresholds of the [`LoadShedPolicy`].
+    type Value: PartialOrd + fmt::Debug + Send + Sync + 'static;
+
+    /// The guard returned for an accepted request,
+    /// dropped once the request has been served.
+    type Guard: Send + 'static;
+
+    /// Measure the current value of the signal.
+    fn load(&self) -> Self::Value;
+
+    /// Start tracking an accepted request.
+    fn track(&self) -> Self::Guard;
+}
+
+/// A [`Policy`] that fast-fails requests with an [`Overloaded`] error
+/// when a measured [`LoadSignal`] exceeds a threshold.
+///
+/// Once shedding, requests keep being shed until the signal dropped
+/// to the resume threshold (or below), which defaults to the shed threshold.
+/// Using a resume threshold lower than the shed threshold
+/// adds hysteresis, preventing the policy from flapping
+/// between shedding and accepting around the threshold.
+///
+/// Clones of this policy share the same signal and shedding state.
+pub struct LoadShedPolicy<S: LoadSignal> {
+    signal: Arc<S>,
+    threshold: S::Value,
+    resume_threshold: Option<S::Value>,
+    shedding: Arc<AtomicBool>,
+}
+
+impl<S: LoadSignal<Value: Clone>> Clone for LoadShedPolicy<S> {
+    fn clone(&self) -> Self {
+        Self {
+            signal: self.signal.clone(),
+            threshold: self.threshold.clone(),
+            resume_threshold: self.resume_threshold.clone(),
+            shedding: self.shedding.clone(),
+        }
+    }
+}
+
+impl<S: LoadSignal + fmt::Debug> fmt::Debug for LoadShedPolicy<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoadShedPolicy")
+            .field("signal", &self.signal)
+            .field("threshold", &self.threshold)
+            .field("resume_threshold", &self.resume_threshold)
+            .field("shedding", &self.shedding)
+            .finish()
+    }
+}
+
+impl<S: LoadSignal> LoadShedPolicy<S> {
+    /// Create a new [`LoadShedPolicy`] which sheds requests
+    /// while the given [`LoadSignal`] exceeds the given threshold.
+    pub fn new(signal: S, threshold: S::Value) -> Self {
+        Self {
+            signal: Arc::new(signal),
+            threshold,
+            resume_threshold: None,
+            shedding: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Set the threshold the signal has to drop to (or below)
+    /// before requests are accepted again once shedding.
+    pub fn with_resume_threshold(mut self, threshold: S::Value) -> Self {
+        self.resume_threshold = Some(threshold);
+        self
+    }
+
+    /// Set the threshold the signal has to drop to (or below)
+    /// before requests are accepted again once shedding.
+    pub fn set_resume_threshold(&mut self, threshold: S::Value) -> &mut Self {
+        self.resume_threshold = Some(threshold);
+        self
+    }
+
+    /// Reference to the [`LoadSignal`] used by this policy.
+    pub fn signal(&self) -> &S {
+        &self.signal
+    }
+
+    /// Returns `true` if this policy is currently shedding requests.
+    pub fn is_shedding(&self) -> bool {
+        self.shedding.load(Ordering::Acquire)
+    }
+
+    fn should_shed(&self) -> bool {
+        let load = self.signal.load();
+        if self.shedding.load(Ordering::Acquire) {
+            let resume_threshold = self.resume_threshold.as_ref().unwrap_or(&self.threshold);
+            if load <= *resume_threshold {
+                tracing::trace!(?load, "load shed policy: resume accepting requests");
+                self.shedding.store(false, Ordering::Release);
+                false
+            } else {
+                true
+            }
+        } else if load > self.threshold {
+            tracing::debug!(?load, threshold = ?self.threshold, "load shed policy: start shedding requests");
+            self.shedding.store(true, Ordering::Release);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<S, State, Request> Policy<State, Request> for LoadShedPolicy<S>
+where
+    S: LoadSignal,
+    State: Clone + Send + Sync + 'static,
+    Request: Send + 'static,
+{
+    type Guard = S::Guard;
+    type Error = Overloaded;
+
+    async fn check(
+        &self,
+        ctx: Context<State>,
+        request: Request,
+    ) -> PolicyResult<State, Request, Self::Guard, Self::Error> {
+        let output = if self.should_shed() {
+            PolicyOutput::Abort(Overloaded)
+        } else {
+            PolicyOutput::Ready(self.signal.track())
+        };
+        PolicyResult {
+            ctx,
+            request,
+            output,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A [`LoadSignal`] measuring the number of in-flight requests.
+///
+/// Clones share the same counter.
+pub struct InFlightSignal {
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl InFlightSignal {
+    /// Create a new [`InFlightSignal`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LoadSignal for InFlightSignal {
+    type Value = usize;
+    type Guard = InFlightGuard;
+
+    fn load(&self) -> Self::Value {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    fn track(&self) -> Self::Guard {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        InFlightGuard {
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// The guard of an [`InFlightSignal`], counting the request as in-flight until dropped.
+pub struct InFlightGuard {
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// A [`LoadSignal`] estimating the p99 latency of the requests
+/// served within a rolling time window.
+///
+/// The latency of a request is recorded when it finished being served,
+/// in a [`LatencyHistogram`]. Two histograms are used, each covering
+/// half of the window, such that samples are discarded at the latest once
+/// older than the window and the estimate recovers once the service
+/// is no longer slow, even while all requests are shed.
+/// The signal is [`Duration::ZERO`] while no samples are available.
+///
+/// The estimate is cached and only recomputed once older than the refresh interval,
+/// such that measuring the signal for every request stays cheap.
+///
+/// Clones share the same samples.
+#[derive(Debug, Clone)]
+pub struct LatencySignal {
+    window: Duration,
+    refresh_interval: Duration,
+    state: Arc<Mutex<LatencyState>>,
+}
+
+#[derive(Debug)]
+struct LatencyState {
+    epoch_start: Instant,
+    current: LatencyHistogram,
+    previous: LatencyHistogram,
+    p99: Option<(Instant, Duration)>,
+}
+
+impl LatencyState {
+    fn new(max_samples: usize) -> Self {
+        Self {
+            epoch_start: Instant::now(),
+            current: LatencyHistogram::new(max_samples),
+            previous: LatencyHistogram::new(max_samples),
+            p99: None,
+        }
+    }
+
+    /// Start a new epoch once the current one covered half of the window,
+    /// dropping the samples of the previous epoch.
+    fn rotate(&mut self, now: Instant, window: Duration) {
+        let elapsed = now.duration_since(self.epoch_start);
+        if elapsed < window / 2 {
+            return;
+        }
+        let max_samples = self.current.window();
+        let current = std::mem::replace(&mut self.current, LatencyHistogram::new(max_samples));
+        self.previous = if elapsed < window {
+            current
+        } else {
+            LatencyHistogram::new(max_samples)
+        };
+        self.epoch_start = now;
+        self.p99 = None;
+    }
+}
+
+impl LatencySignal {
+    const DEFAULT_MAX_SAMPLES: usize = 1024;
+    const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Create a new [`LatencySignal`] using the given rolling window.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            refresh_interval: Self::DEFAULT_REFRESH_INTERVAL,
+            state: Arc::new(Mutex::new(LatencyState::new(Self::DEFAULT_MAX_SAMPLES))),
+        }
+    }
+
+    /// Set the maximum number of samples kept for each half of the window,
+    /// dropping the oldest samples first. Defaults to `1024`.
+    ///
+    /// The samples are shared, so this applies to all clones of this signal.
+    pub fn with_max_samples(mut self, max: usize) -> Self {
+        self.set_max_samples(max);
+        self
+    }
+
+    /// Set the maximum number of samples kept for each half of the window,
+    /// dropping the oldest samples first. Defaults to `1024`.
+    ///
+    /// The samples are shared, so this applies to all clones of this signal.
+    pub fn set_max_samples(&mut self, max: usize) -> &mut Self {
+        let mut state = self.state.lock();
+        state.current.set_window(max);
+        state.previous.set_window(max);
+        state.p99 = None;
+        drop(state);
+        self
+    }
+
+    /// Set the interval at which the p99 estimate is recomputed. Defaults to `100ms`.
+    pub fn with_refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Set the interval at which the p99 estimate is recomputed. Defaults to `100ms`.
+    pub fn set_refresh_interval(&mut self, interval: Duration) -> &mut Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Record the latency of a served request.
+    pub fn record(&self, latency: Duration) {
+        let mut state = self.state.lock();
+        state.rotate(Instant::now(), self.window);
+        state.current.record_mut(latency);
+    }
+
+    /// Estimate the p99 latency of the samples within the window.
+    pub fn p99(&self) -> Duration {
+        let mut state = self.state.lock();
+        let now = Instant::now();
+        state.rotate(now, self.window);
+        if let Some((computed_at, p99)) = state.p99 {
+            if now.duration_since(computed_at) < self.refresh_interval {
+                return p99;
+            }
+        }
+
+        let p99 = state
+            .current
+            .snapshot_mut()
+            .p99()
+            .max(state.previous.snapshot_mut().p99());
+        state.p99 = Some((now, p99));
+        p99
+    }
+}
+
+impl LoadSignal for LatencySignal {
+    type Value = Duration;
+    type Guard = LatencyGuard;
+
+    fn load(&self) -> Self::Value {
+        self.p99()
+    }
+
+    fn track(&self) -> Self::Guard {
+        LatencyGuard {
+            signal: self.clone(),
+            start: Instant::now(),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// The guard of a [`LatencySignal`], recording the latency of the request when dropped.
+pub struct LatencyGuard {
+    signal: LatencySignal,
+    start: Instant,
+}
+
+impl Drop for LatencyGuard {
+    fn drop(&mut self) {
+        self.signal.record(self.start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layer::limit::LoadShedLayer, service::service_fn, Layer, Service};
+    use std::convert::Infallible;
+
+    fn is_shed<S, R, G, E>(result: PolicyResult<S, R, G, E>) -> Option<G> {
+        match result.output {
+            PolicyOutput::Ready(guard) => Some(guard),
+            PolicyOutput::Abort(_) => None,
+            PolicyOutput::Retry => panic!("unexpected output, expected ready or abort"),
+        }
+    }
+
+    #[tokio::test]
+    async fn load_shed_in_flight() {
+        let policy = LoadShedPolicy::new(InFlightSignal::new(), 2);
+
+        let guard_1 = is_shed(policy.check(Context::default(), ()).await).unwrap();
+        let guard_2 = is_shed(policy.check(Context::default(), ()).await).unwrap();
+        let guard_3 = is_shed(policy.check(Context::default(), ()).await).unwrap();
+        assert!(!policy.is_shedding());
+
+        // 3 in-flight requests exceed the threshold
+        assert!(is_shed(policy.check(Context::default(), ()).await).is_none());
+        assert!(policy.is_shedding());
+
+        drop(guard_3);
+        let _guard = is_shed(policy.check(Context::default(), ()).await).unwrap();
+        assert!(!policy.is_shedding());
+
+        drop((guard_1, guard_2));
+        assert_eq!(policy.signal().load(), 1);
+    }
+
+    #[tokio::test]
+    async fn load_shed_hysteresis() {
+        let policy = LoadShedPolicy::new(InFlightSignal::new(), 2).with_resume_threshold(1);
+
+        let mut guards: Vec<_> = Vec::new();
+        for _ in 0..3 {
+            guards.push(is_shed(policy.check(Context::default(), ()).await).unwrap());
+        }
+        assert!(is_shed(policy.check(Context::default(), ()).await).is_none());
+
+        // below the shed threshold, but still above the resume threshold
+        guards.pop();
+        assert!(is_shed(policy.check(Context::default(), ()).await).is_none());
+        assert!(policy.is_shedding());
+
+        guards.pop();
+        guards.push(is_shed(policy.check(Context::default(), ()).await).unwrap());
+        assert!(!policy.is_shedding());
+
+        // back above the resume threshold, but not above the shed threshold
+        guards.push(is_shed(policy.check(Context::default(), ()).await).unwrap());
+        assert!(!policy.is_shedding());
+    }
+
+    fn assert_in_bucket(actual: Duration, expected: Duration) {
+        // the latency histogram has a relative error of at most 12.5%
+        assert!(
+            actual >= expected && actual <= expected + expected / 8,
+            "{actual:?} not within the bucket of {expected:?}"
+        );
+    }
+
+    #[test]
+    fn latency_signal_p99() {
+        let signal =
+            LatencySignal::new(Duration::from_secs(60)).with_refresh_interval(Duration::ZERO);
+        assert_eq!(signal.p99(), Duration::ZERO);
+
+        for ms in 1..=100 {
+            signal.record(Duration::from_millis(ms));
+        }
+        assert_in_bucket(signal.p99(), Duration::from_millis(99));
+
+        let signal = signal.with_max_samples(10);
+        assert_in_bucket(signal.p99(), Duration::from_millis(99));
+        for ms in 1..=100 {
+            signal.record(Duration::from_millis(ms));
+        }
+        signal.record(Duration::from_millis(1));
+        assert_in_bucket(signal.p99(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn latency_signal_max_samples_shared_with_clones() {
+        let signal =
+            LatencySignal::new(Duration::from_secs(60)).with_refresh_interval(Duration::ZERO);
+        let clone = signal.clone();
+        clone.record(Duration::from_millis(500));
+
+        let signal = signal.with_max_samples(1);
+        assert_in_bucket(clone.p99(), Duration::from_millis(500));
+
+        clone.record(Duration::from_millis(1));
+        assert_in_bucket(signal.p99(), Duration::from_millis(1));
+    }
+
+    #[test]
+    fn latency_signal_p99_cached() {
+        let signal = LatencySignal::new(Duration::from_secs(60))
+            .with_refresh_interval(Duration::from_secs(30));
+
+        signal.record(Duration::from_millis(1));
+        assert_in_bucket(signal.p99(), Duration::from_millis(1));
+
+        // not recomputed until the refresh interval elapsed
+        signal.record(Duration::from_millis(500));
+        assert_in_bucket(signal.p99(), Duration::from_millis(1));
+    }
+
+    #[tokio::test]
+    async fn load_shed_latency() {
+        let signal =
+            LatencySignal::new(Duration::from_millis(50)).with_refresh_interval(Duration::ZERO);
+        let policy = LoadShedPolicy::new(signal.clone(), Duration::from_millis(100))
+            .with_resume_threshold(Duration::from_millis(20));
+
+        signal.record(Duration::from_millis(10));
+        assert!(is_shed(policy.check(Context::default(), ()).await).is_some());
+
+        signal.record(Duration::from_millis(500));
+        assert!(is_shed(policy.check(Context::default(), ()).await).is_none());
+        assert!(policy.is_shedding());
+
+        // slow samples expire from the window
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(is_shed(policy.check(Context::default(), ()).await).is_some());
+        assert!(!policy.is_shedding());
+    }
+
+    #[tokio::test]
+    async fn load_shed_layer_overloaded_error() {
+        let signal = InFlightSignal::new();
+        let layer = LoadShedLayer::new(LoadShedPolicy::new(signal.clone(), 0));
+        let service = layer.layer(service_fn(|_, ()| async { Ok::<_, Infallible>(()) }));
+
+        assert!(service.serve(Context::default(), ()).await.is_ok());
+
+        let _guard = signal.track();
+        let err = service.serve(Context::default(), ()).await.unwrap_err();
+        assert!(err.downcast_ref::<Overloaded>().is_some());
+    }
+}
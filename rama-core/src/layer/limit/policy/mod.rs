@@ -35,6 +35,20 @@ mod concurrent;
 #[doc(inline)]
 pub use concurrent::{ConcurrentCounter, ConcurrentPolicy, ConcurrentTracker, LimitReached};
 
+mod semaphore;
+#[doc(inline)]
+pub use semaphore::{Overloaded, SemaphorePolicy};
+
+mod token_bucket;
+#[doc(inline)]
+pub use token_bucket::{RateLimitState, RateLimited, TokenBucketPolicy};
+
+mod load_shed;
+#[doc(inline)]
+pub use load_shed::{
+    InFlightGuard, InFlightSignal, LatencyGuard, LatencySignal, LoadShedPolicy, LoadSignal,
+};
+
 mod matcher;
 
 /// The full result of a limit policy.
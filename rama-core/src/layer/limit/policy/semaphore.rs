@@ -0,0 +1,229 @@
+//! A [`Policy`] that limits the number of concurrent requests,
+//! waiting for a permit to become available when the limit is reached.
+//!
+//! See [`SemaphorePolicy`].
+//!
+//! # Examples
+//!
+//! ```
+//! use rama_core::layer::limit::{Limit, policy::SemaphorePolicy};
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Service};
+//! use std::time::Duration;
+//! # use std::convert::Infallible;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//!
+//! let service = service_fn(|_, _| async {
+//!     Ok::<_, Infallible>(())
+//! });
+//! let policy = SemaphorePolicy::new(2).with_acquire_timeout(Duration::from_secs(1));
+//! let mut service = Limit::new(service, policy);
+//!
+//! let response = service.serve(Context::default(), ()).await;
+//! assert!(response.is_ok());
+//! # }
+//! ```
+
+use super::{Policy, PolicyOutput, PolicyResult};
+use crate::Context;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// A [`Policy`] that limits the number of concurrent requests using a semaphore.
+///
+/// Contrary to the [`ConcurrentPolicy`], requests wait (in FIFO order)
+/// for a permit to become available once the limit is reached.
+/// When an acquire timeout is configured, requests which could not acquire
+/// a permit within that timeout are aborted with an [`Overloaded`] error,
+/// which can for example be mapped to a `503 Service Unavailable` response.
+///
+/// The permit is held for as long as the inner service is serving the request,
+/// and is released when it finishes, be it with a response, an error or a panic.
+///
+/// Clones of this policy share the same permits.
+///
+/// [`ConcurrentPolicy`]: super::ConcurrentPolicy
+#[derive(Debug, Clone)]
+pub struct SemaphorePolicy {
+    semaphore: Arc<Semaphore>,
+    acquire_timeout: Option<Duration>,
+}
+
+impl SemaphorePolicy {
+    /// Create a new [`SemaphorePolicy`] with the given amount of permits,
+    /// waiting without timeout for a permit when none are available.
+    pub fn new(permits: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(permits)),
+            acquire_timeout: None,
+        }
+    }
+
+    /// Set the maximum duration to wait for a permit,
+    /// after which the request is aborted with an [`Overloaded`] error.
+    ///
+    /// Use [`Duration::ZERO`] to abort immediately when no permit is available.
+    pub fn with_acquire_timeout(mut self, timeout: Duration) -> Self {
+        self.acquire_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum duration to wait for a permit,
+    /// after which the request is aborted with an [`Overloaded`] error.
+    ///
+    /// Use [`Duration::ZERO`] to abort immediately when no permit is available.
+    pub fn set_acquire_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.acquire_timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the amount of permits currently available.
+    pub fn available_permits(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+}
+
+impl<State, Request> Policy<State, Request> for SemaphorePolicy
+where
+    State: Clone + Send + Sync + 'static,
+    Request: Send + 'static,
+{
+    type Guard = OwnedSemaphorePermit;
+    type Error = Overloaded;
+
+    async fn check(
+        &self,
+        ctx: Context<State>,
+        request: Request,
+    ) -> PolicyResult<State, Request, Self::Guard, Self::Error> {
+        let acquire = self.semaphore.clone().acquire_owned();
+        let permit = match self.acquire_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, acquire).await.ok(),
+            None => Some(acquire.await),
+        };
+
+        // the semaphore is never closed, so an acquire error cannot happen
+        let output = match permit {
+            Some(Ok(permit)) => PolicyOutput::Ready(permit),
+            Some(Err(_)) | None => PolicyOutput::Abort(Overloaded),
+        };
+
+        PolicyResult {
+            ctx,
+            request,
+            output,
+        }
+    }
+}
+
+rama_utils::macros::error::static_str_error! {
+    #[doc = "request aborted as the service is overloaded"]
+    pub struct Overloaded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        layer::limit::{ConcurrencyLimitLayer, Limit},
+        service::service_fn,
+        Layer, Service,
+    };
+    use std::convert::Infallible;
+
+    fn assert_ready<S, R, G, E>(result: PolicyResult<S, R, G, E>) -> G {
+        match result.output {
+            PolicyOutput::Ready(guard) => guard,
+            _ => panic!("unexpected output, expected ready"),
+        }
+    }
+
+    fn assert_abort<S, R, G, E>(result: PolicyResult<S, R, G, E>) -> E {
+        match result.output {
+            PolicyOutput::Abort(err) => err,
+            _ => panic!("unexpected output, expected abort"),
+        }
+    }
+
+    #[tokio::test]
+    async fn semaphore_policy_timeout() {
+        let policy = SemaphorePolicy::new(2).with_acquire_timeout(Duration::from_millis(10));
+
+        let guard_1 = assert_ready(policy.check(Context::default(), ()).await);
+        let _guard_2 = assert_ready(policy.clone().check(Context::default(), ()).await);
+        assert_eq!(policy.available_permits(), 0);
+
+        assert_abort(policy.check(Context::default(), ()).await);
+
+        drop(guard_1);
+        assert_eq!(policy.available_permits(), 1);
+        let _guard = assert_ready(policy.check(Context::default(), ()).await);
+    }
+
+    #[tokio::test]
+    async fn semaphore_policy_zero_timeout() {
+        let policy = SemaphorePolicy::new(1).with_acquire_timeout(Duration::ZERO);
+
+        let _guard = assert_ready(policy.check(Context::default(), ()).await);
+        assert_abort(policy.check(Context::default(), ()).await);
+    }
+
+    #[tokio::test]
+    async fn semaphore_policy_waits_for_permit() {
+        let policy = SemaphorePolicy::new(1);
+
+        let guard = assert_ready(policy.check(Context::default(), ()).await);
+
+        let waiting_policy = policy.clone();
+        let waiter =
+            tokio::spawn(async move { waiting_policy.check(Context::default(), ()).await });
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiter.is_finished());
+
+        drop(guard);
+        let _guard = assert_ready(waiter.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn semaphore_limit_overloaded_error() {
+        let policy = SemaphorePolicy::new(1).with_acquire_timeout(Duration::from_millis(10));
+        let _guard = assert_ready(policy.check(Context::default(), ()).await);
+
+        let service = Limit::new(
+            service_fn(|_, ()| async { Ok::<_, Infallible>(()) }),
+            policy,
+        );
+        let err = service.serve(Context::default(), ()).await.unwrap_err();
+        assert!(err.downcast_ref::<Overloaded>().is_some());
+    }
+
+    #[tokio::test]
+    async fn semaphore_limit_releases_permit_on_error_and_panic() {
+        let policy = SemaphorePolicy::new(1).with_acquire_timeout(Duration::from_millis(10));
+        let layer = ConcurrencyLimitLayer::new(policy.clone());
+
+        let service = layer.layer(service_fn(|_, fail: bool| async move {
+            if fail {
+                Err("failed")
+            } else {
+                Ok(())
+            }
+        }));
+        assert!(service.serve(Context::default(), true).await.is_err());
+        assert_eq!(policy.available_permits(), 1);
+
+        let service = Arc::new(layer.layer(service_fn(|_, ()| async {
+            if true {
+                panic!("service panic");
+            }
+            Ok::<_, Infallible>(())
+        })));
+        let result = tokio::spawn(async move { service.serve(Context::default(), ()).await }).await;
+        assert!(result.unwrap_err().is_panic());
+        assert_eq!(policy.available_permits(), 1);
+    }
+}
@@ -0,0 +1,247 @@
+//! A [`Policy`] that rate limits requests using a token bucket.
+//!
+//! See [`TokenBucketPolicy`].
+//!
+//! # Examples
+//!
+//! ```
+//! use rama_core::layer::limit::{Limit, policy::TokenBucketPolicy};
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Service};
+//! # use std::convert::Infallible;
+//! use std::time::Duration;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//!
+//! let service = service_fn(|_, _| async {
+//!     Ok::<_, Infallible>(())
+//! });
+//! // allow bursts of 2 requests, refilling at a rate of 2 requests per minute
+//! let service = Limit::new(service, TokenBucketPolicy::new(2, Duration::from_secs(60)));
+//!
+//! assert!(service.serve(Context::default(), ()).await.is_ok());
+//! assert!(service.serve(Context::default(), ()).await.is_ok());
+//! assert!(service.serve(Context::default(), ()).await.is_err());
+//! # }
+//! ```
+
+use super::{Policy, PolicyOutput, PolicyResult};
+use crate::Context;
+use parking_lot::Mutex;
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// A [`Policy`] that rate limits requests using a token bucket.
+///
+/// The bucket holds up to `limit` tokens and starts full.
+/// Each request consumes a single token, and is aborted with
+/// a [`RateLimited`] error when the bucket is empty.
+/// Tokens are refilled continuously, such that an empty bucket
+/// is full again after the given `window`.
+///
+/// The [`RateLimitState`] after consuming a token
+/// is inserted into the [`Context`] of allowed requests.
+///
+/// Cloning the policy shares the bucket.
+#[derive(Debug, Clone)]
+pub struct TokenBucketPolicy {
+    limit: u64,
+    window: Duration,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucketPolicy {
+    /// Create a new [`TokenBucketPolicy`], allowing `limit` requests per `window`.
+    pub fn new(limit: u64, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: limit as f64,
+                updated: Instant::now(),
+            })),
+        }
+    }
+
+    /// Try to consume a token, returning the state of the bucket afterwards.
+    fn try_consume(&self) -> Result<RateLimitState, RateLimited> {
+        let limit = self.limit as f64;
+
+        let mut bucket = self.bucket.lock();
+        let now = Instant::now();
+        if self.window.is_zero() {
+            bucket.tokens = limit;
+        } else {
+            let elapsed = now.duration_since(bucket.updated);
+            let refill = elapsed.as_secs_f64() / self.window.as_secs_f64() * limit;
+            bucket.tokens = (bucket.tokens + refill).min(limit);
+        }
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(self.state(bucket.tokens))
+        } else {
+            Err(RateLimited {
+                state: self.state(bucket.tokens),
+                retry_after: self.refill_duration(1.0 - bucket.tokens),
+            })
+        }
+    }
+
+    fn state(&self, tokens: f64) -> RateLimitState {
+        RateLimitState {
+            limit: self.limit,
+            remaining: tokens as u64,
+            reset: self.refill_duration(self.limit as f64 - tokens),
+            window: self.window,
+        }
+    }
+
+    /// Time it takes to refill the given amount of tokens.
+    fn refill_duration(&self, tokens: f64) -> Duration {
+        if self.limit == 0 {
+            return self.window;
+        }
+        self.window.mul_f64(tokens.max(0.0) / self.limit as f64)
+    }
+}
+
+impl<State, Request> Policy<State, Request> for TokenBucketPolicy
+where
+    State: Clone + Send + Sync + 'static,
+    Request: Send + 'static,
+{
+    type Guard = ();
+    type Error = RateLimited;
+
+    async fn check(
+        &self,
+        mut ctx: Context<State>,
+        request: Request,
+    ) -> PolicyResult<State, Request, Self::Guard, Self::Error> {
+        let output = match self.try_consume() {
+            Ok(state) => {
+                ctx.insert(state);
+                PolicyOutput::Ready(())
+            }
+            Err(err) => PolicyOutput::Abort(err),
+        };
+        PolicyResult {
+            ctx,
+            request,
+            output,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The state of a rate limit, as seen by a request.
+///
+/// Inserted into the [`Context`] by the [`TokenBucketPolicy`]
+/// for requests which are allowed to proceed, such that it can
+/// be communicated to the client (e.g. using http headers).
+pub struct RateLimitState {
+    /// The maximum amount of requests allowed within the window.
+    pub limit: u64,
+    /// The amount of requests still allowed right now.
+    pub remaining: u64,
+    /// The time until the full limit is available again.
+    pub reset: Duration,
+    /// The time window of the limit.
+    pub window: Duration,
+}
+
+#[derive(Debug, Clone)]
+/// Error returned by the [`TokenBucketPolicy`] in case the rate limit is exhausted.
+pub struct RateLimited {
+    state: RateLimitState,
+    retry_after: Duration,
+}
+
+impl RateLimited {
+    /// The [`RateLimitState`] at the time the request was rate limited.
+    pub fn state(&self) -> &RateLimitState {
+        &self.state
+    }
+
+    /// The time after which a request will be allowed again.
+    pub fn retry_after(&self) -> Duration {
+        self.retry_after
+    }
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "request aborted due to exhausted rate limit (retry after {:?})",
+            self.retry_after
+        )
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn check(policy: &TokenBucketPolicy) -> Result<RateLimitState, RateLimited> {
+        let result = Policy::<(), ()>::check(policy, Context::default(), ()).await;
+        match result.output {
+            PolicyOutput::Ready(()) => Ok(*result.ctx.get::<RateLimitState>().unwrap()),
+            PolicyOutput::Abort(err) => Err(err),
+            PolicyOutput::Retry => panic!("unexpected output, expected ready or abort"),
+        }
+    }
+
+    #[tokio::test]
+    async fn token_bucket_policy() {
+        let policy = TokenBucketPolicy::new(3, Duration::from_secs(60));
+
+        for expected_remaining in [2, 1, 0] {
+            let state = check(&policy).await.unwrap();
+            assert_eq!(state.limit, 3);
+            assert_eq!(state.remaining, expected_remaining);
+            assert_eq!(state.window, Duration::from_secs(60));
+            assert!(state.reset <= Duration::from_secs(60));
+        }
+
+        let err = check(&policy).await.unwrap_err();
+        assert_eq!(err.state().remaining, 0);
+        assert!(err.retry_after() > Duration::from_secs(19));
+        assert!(err.retry_after() <= Duration::from_secs(20));
+
+        // the bucket is shared between clones
+        assert!(check(&policy.clone()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn token_bucket_policy_refill() {
+        let policy = TokenBucketPolicy::new(1, Duration::from_millis(50));
+
+        assert_eq!(check(&policy).await.unwrap().remaining, 0);
+        assert!(check(&policy).await.is_err());
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(check(&policy).await.unwrap().remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn token_bucket_policy_zero() {
+        let policy = TokenBucketPolicy::new(0, Duration::from_secs(1));
+        let err = check(&policy).await.unwrap_err();
+        assert_eq!(err.retry_after(), Duration::from_secs(1));
+    }
+}
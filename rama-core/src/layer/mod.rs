@@ -701,6 +701,10 @@ mod map_err;
 #[doc(inline)]
 pub use map_err::{MapErr, MapErrLayer};
 
+mod and_then;
+#[doc(inline)]
+pub use and_then::{AndThen, AndThenLayer};
+
 mod consume_err;
 #[doc(inline)]
 pub use consume_err::{ConsumeErr, ConsumeErrLayer};
@@ -713,11 +717,23 @@ mod map_result;
 #[doc(inline)]
 pub use map_result::{MapResult, MapResultLayer};
 
+mod fallback;
+#[doc(inline)]
+pub use fallback::{Fallback, FallbackLayer, FallbackOnError, FallbackPolicy};
+
+pub mod cancel;
+pub use cancel::{Cancel, CancelLayer};
+
 pub mod timeout;
-pub use timeout::{Timeout, TimeoutLayer};
+pub use timeout::{Deadline, Timeout, TimeoutFallback, TimeoutFallbackLayer, TimeoutLayer};
 
 pub mod limit;
-pub use limit::{Limit, LimitLayer};
+pub use limit::{
+    ConcurrencyLimit, ConcurrencyLimitLayer, Limit, LimitLayer, LoadShed, LoadShedLayer,
+};
+
+pub mod observe;
+pub use observe::{ObservedLayer, ObservedService};
 
 pub mod add_extension;
 pub use add_extension::{AddExtension, AddExtensionLayer};
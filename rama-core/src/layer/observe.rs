@@ -0,0 +1,343 @@
+//! Protocol-agnostic observability for [`Service`]s, using [`tracing`] spans.
+//!
+//! [`ObservedService`] wraps every call of the inner [`Service`] in a [`Span`],
+//! created by a [`MakeServiceSpan`] implementation, and records the outcome
+//! of that call as fields of that span once the inner service finished:
+//!
+//! - `outcome`: `"ok"` or `"err"`;
+//! - `duration_ms`: the time it took for the inner service to produce its result;
+//! - `error`: the [`Display`] of the error, only recorded on failure.
+//!
+//! Spans created by a custom [`MakeServiceSpan`] have to declare these fields
+//! (e.g. using [`tracing::field::Empty`]) in order for them to be recorded,
+//! as [`tracing`] ignores values recorded for undeclared fields.
+//!
+//! Unlike the http `Trace` layer this makes no assumptions about the
+//! request or response, making it usable for any kind of service,
+//! such as connectors, dns resolvers or grpc clients.
+//!
+//! # Example
+//!
+//! ```
+//! use rama_core::{service::service_fn, Context, Layer, Service};
+//! use rama_core::layer::observe::{DefaultMakeServiceSpan, ObservedLayer};
+//! use std::convert::Infallible;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let service = ObservedLayer::new(DefaultMakeServiceSpan::new("echo"))
+//!     .layer(service_fn(|req: &'static str| async move { Ok::<_, Infallible>(req) }));
+//!
+//! let resp = service.serve(Context::default(), "hello").await.unwrap();
+//! assert_eq!(resp, "hello");
+//! # }
+//! ```
+//!
+//! [`Display`]: std::fmt::Display
+
+use crate::{Context, Layer, Service};
+use rama_utils::macros::define_inner_service_accessors;
+use std::{fmt, time::Instant};
+use tracing::{Instrument, Level, Span};
+
+/// Trait used to create the [`Span`] in which an [`ObservedService`]
+/// serves a request.
+///
+/// See the [module docs](self) for the fields that are recorded
+/// on the created [`Span`].
+pub trait MakeServiceSpan<State, Request>: Send + Sync + 'static {
+    /// Make a span for the given context and request.
+    fn make_span(&self, ctx: &Context<State>, req: &Request) -> Span;
+}
+
+impl<State, Request, F> MakeServiceSpan<State, Request> for F
+where
+    F: Fn(&Context<State>, &Request) -> Span + Send + Sync + 'static,
+{
+    fn make_span(&self, ctx: &Context<State>, req: &Request) -> Span {
+        self(ctx, req)
+    }
+}
+
+/// The default [`MakeServiceSpan`] implementation.
+///
+/// Creates a `rama.service` span, with the configured name
+/// recorded as the `service` field, at the configured [`Level`].
+#[derive(Debug, Clone)]
+pub struct DefaultMakeServiceSpan {
+    name: &'static str,
+    level: Level,
+}
+
+impl DefaultMakeServiceSpan {
+    /// Create a new [`DefaultMakeServiceSpan`] for a service with the given name.
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            level: Level::DEBUG,
+        }
+    }
+
+    /// Set the [`Level`] used for the created spans.
+    ///
+    /// Defaults to [`Level::DEBUG`].
+    pub const fn with_level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Set the [`Level`] used for the created spans.
+    ///
+    /// Defaults to [`Level::DEBUG`].
+    pub fn set_level(&mut self, level: Level) -> &mut Self {
+        self.level = level;
+        self
+    }
+}
+
+macro_rules! make_service_span {
+    ($level:expr, $name:expr) => {
+        match $level {
+            Level::TRACE => make_service_span!(@span Level::TRACE, $name),
+            Level::DEBUG => make_service_span!(@span Level::DEBUG, $name),
+            Level::INFO => make_service_span!(@span Level::INFO, $name),
+            Level::WARN => make_service_span!(@span Level::WARN, $name),
+            Level::ERROR => make_service_span!(@span Level::ERROR, $name),
+        }
+    };
+    (@span $level:expr, $name:expr) => {
+        tracing::span!(
+            $level,
+            "rama.service",
+            service = $name,
+            outcome = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+            error = tracing::field::Empty,
+        )
+    };
+}
+
+impl<State, Request> MakeServiceSpan<State, Request> for DefaultMakeServiceSpan {
+    fn make_span(&self, _ctx: &Context<State>, _req: &Request) -> Span {
+        make_service_span!(self.level, self.name)
+    }
+}
+
+/// Service which serves each request of the inner [`Service`]
+/// within a [`Span`], recording the outcome of the call on it.
+///
+/// See the [module docs](self) for more information.
+pub struct ObservedService<S, F> {
+    inner: S,
+    make_span: F,
+}
+
+impl<S, F> fmt::Debug for ObservedService<S, F>
+where
+    S: fmt::Debug,
+    F: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservedService")
+            .field("inner", &self.inner)
+            .field("make_span", &self.make_span)
+            .finish()
+    }
+}
+
+impl<S, F> Clone for ObservedService<S, F>
+where
+    S: Clone,
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            make_span: self.make_span.clone(),
+        }
+    }
+}
+
+impl<S, F> ObservedService<S, F> {
+    /// Creates a new [`ObservedService`] using the given [`MakeServiceSpan`].
+    pub const fn new(inner: S, make_span: F) -> Self {
+        Self { inner, make_span }
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S, F, State, Request> Service<State, Request> for ObservedService<S, F>
+where
+    Request: Send + 'static,
+    S: Service<State, Request, Error: fmt::Display + Send + Sync + 'static>,
+    F: MakeServiceSpan<State, Request>,
+    State: Clone + Send + Sync + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        let span = self.make_span.make_span(&ctx, &req);
+
+        let start = Instant::now();
+        let res = self.inner.serve(ctx, req).instrument(span.clone()).await;
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        span.record("duration_ms", duration_ms);
+        match &res {
+            Ok(_) => {
+                span.record("outcome", "ok");
+            }
+            Err(err) => {
+                span.record("outcome", "err");
+                span.record("error", tracing::field::display(err));
+            }
+        }
+
+        res
+    }
+}
+
+/// A [`Layer`] that produces [`ObservedService`] services.
+#[derive(Debug, Clone)]
+pub struct ObservedLayer<F> {
+    make_span: F,
+}
+
+impl<F> ObservedLayer<F> {
+    /// Creates a new [`ObservedLayer`] using the given [`MakeServiceSpan`].
+    pub const fn new(make_span: F) -> Self {
+        Self { make_span }
+    }
+}
+
+impl<S, F: Clone> Layer<S> for ObservedLayer<F> {
+    type Service = ObservedService<S, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ObservedService::new(inner, self.make_span.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::OpaqueError;
+    use crate::service::service_fn;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing_subscriber::layer::{Context as LayerContext, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+
+    #[derive(Debug, Default)]
+    struct SpanFields(HashMap<&'static str, String>);
+
+    impl Visit for SpanFields {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name(), value.to_owned());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(field.name(), format!("{value:?}"));
+        }
+    }
+
+    #[derive(Debug, Clone, Default)]
+    struct ClosedSpans(Arc<Mutex<Vec<(&'static str, HashMap<&'static str, String>)>>>);
+
+    impl<S> tracing_subscriber::Layer<S> for ClosedSpans
+    where
+        S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: LayerContext<'_, S>) {
+            let mut fields = SpanFields::default();
+            attrs.record(&mut fields);
+            ctx.span(id).unwrap().extensions_mut().insert(fields);
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: LayerContext<'_, S>) {
+            let span = ctx.span(id).unwrap();
+            let mut extensions = span.extensions_mut();
+            values.record(extensions.get_mut::<SpanFields>().unwrap());
+        }
+
+        fn on_close(&self, id: Id, ctx: LayerContext<'_, S>) {
+            let span = ctx.span(&id).unwrap();
+            let fields = span.extensions_mut().remove::<SpanFields>().unwrap();
+            self.0.lock().unwrap().push((span.name(), fields.0));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_observed_service_records_outcome() {
+        let closed = ClosedSpans::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(closed.clone()));
+
+        let service = ObservedLayer::new(DefaultMakeServiceSpan::new("test")).layer(service_fn(
+            |req: bool| async move {
+                if req {
+                    Ok(())
+                } else {
+                    Err(OpaqueError::from_display("boom"))
+                }
+            },
+        ));
+
+        service.serve(Context::default(), true).await.unwrap();
+        service.serve(Context::default(), false).await.unwrap_err();
+
+        let closed = closed.0.lock().unwrap();
+        assert_eq!(closed.len(), 2);
+
+        let (name, fields) = &closed[0];
+        assert_eq!(*name, "rama.service");
+        assert_eq!(fields["service"], "test");
+        assert_eq!(fields["outcome"], "ok");
+        assert!(fields.contains_key("duration_ms"));
+        assert!(!fields.contains_key("error"));
+
+        let (name, fields) = &closed[1];
+        assert_eq!(*name, "rama.service");
+        assert_eq!(fields["service"], "test");
+        assert_eq!(fields["outcome"], "err");
+        assert!(fields.contains_key("duration_ms"));
+        assert_eq!(fields["error"], "boom");
+    }
+
+    #[tokio::test]
+    async fn test_observed_service_custom_span() {
+        let closed = ClosedSpans::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(closed.clone()));
+
+        let service = ObservedService::new(
+            service_fn(|req: u16| async move { Ok::<_, OpaqueError>(req) }),
+            |_ctx: &Context<()>, req: &u16| {
+                tracing::info_span!(
+                    "connect",
+                    port = *req,
+                    outcome = tracing::field::Empty,
+                    duration_ms = tracing::field::Empty,
+                )
+            },
+        );
+
+        service.serve(Context::default(), 443).await.unwrap();
+
+        let closed = closed.0.lock().unwrap();
+        assert_eq!(closed.len(), 1);
+
+        let (name, fields) = &closed[0];
+        assert_eq!(*name, "connect");
+        assert_eq!(fields["port"], "443");
+        assert_eq!(fields["outcome"], "ok");
+    }
+}
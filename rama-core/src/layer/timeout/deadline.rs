@@ -0,0 +1,78 @@
+use crate::Context;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The point in time by which a request has to be served,
+/// shared by all timeout-aware layers through the [`Context`].
+///
+/// A [`Timeout`] does not outlive the [`Deadline`] found in the [`Context`]:
+/// it times out at the earliest of its own timeout and that deadline,
+/// and narrows the deadline for the inner layers accordingly.
+/// This way nested timeouts consume a single shrinking budget,
+/// instead of compounding unpredictably.
+///
+/// Insert a [`Deadline`] in the [`Context`] to define a budget
+/// for the entire stack, or use a [`Timeout`] as the outermost layer.
+/// Layers can query their remaining budget using [`Deadline::remaining_budget`].
+///
+/// [`Timeout`]: super::Timeout
+pub struct Deadline(Instant);
+
+impl Deadline {
+    /// Create a [`Deadline`] at the given point in time.
+    pub fn at(instant: Instant) -> Self {
+        Self(instant)
+    }
+
+    /// Create a [`Deadline`] that expires after the given duration, starting now.
+    ///
+    /// Returns `None` if the deadline is too far in the future
+    /// to be represented (e.g. [`Duration::MAX`]), meaning there is no deadline.
+    pub fn after(timeout: Duration) -> Option<Self> {
+        Instant::now().checked_add(timeout).map(Self::at)
+    }
+
+    /// The point in time at which this deadline expires.
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// The time remaining until this deadline expires,
+    /// which is zero when it already expired.
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+
+    /// Returns `true` if this deadline expired.
+    pub fn is_expired(&self) -> bool {
+        self.0 <= Instant::now()
+    }
+
+    /// The remaining budget of the [`Deadline`] in the given [`Context`], if any.
+    pub fn remaining_budget<S>(ctx: &Context<S>) -> Option<Duration> {
+        ctx.get::<Self>().map(Self::remaining)
+    }
+
+    /// Narrow the [`Deadline`] in the given [`Context`] to at most the given timeout
+    /// from now, returning the (possibly narrowed) deadline.
+    ///
+    /// The returned deadline is inserted in the [`Context`],
+    /// such that inner layers do not outlive it. `None` is returned,
+    /// and nothing inserted, in case the [`Context`] has no deadline and the
+    /// timeout is too far in the future to be represented (see [`Deadline::after`]).
+    pub fn narrow<S>(ctx: &mut Context<S>, timeout: Duration) -> Option<Self> {
+        let deadline = match (ctx.get::<Self>().copied(), Self::after(timeout)) {
+            (Some(current), Some(deadline)) => current.min(deadline),
+            (Some(deadline), None) | (None, Some(deadline)) => deadline,
+            (None, None) => return None,
+        };
+        ctx.insert(deadline);
+        Some(deadline)
+    }
+}
+
+impl From<Instant> for Deadline {
+    fn from(instant: Instant) -> Self {
+        Self::at(instant)
+    }
+}
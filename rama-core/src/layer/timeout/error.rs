@@ -1,5 +1,6 @@
 //! Default Error type for Timeout middleware.
 
+use crate::error::{ErrorClass, ErrorKindClass};
 use std::{error, fmt, time::Duration};
 
 /// The timeout elapsed.
@@ -20,3 +21,26 @@ impl fmt::Display for Elapsed {
 }
 
 impl error::Error for Elapsed {}
+
+impl ErrorKindClass for Elapsed {
+    fn error_class(&self) -> Option<ErrorClass> {
+        Some(ErrorClass::Timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{BoxError, ErrorClassifier, ErrorExt};
+
+    #[test]
+    fn test_elapsed_error_class() {
+        let classifier = ErrorClassifier::new().with_error_type::<Elapsed>();
+
+        let err: BoxError = Elapsed::new(Duration::from_secs(1)).into();
+        assert_eq!(classifier.classify(err.as_ref()), ErrorClass::Timeout);
+
+        let err = Elapsed::new(Duration::from_secs(1)).context("serve request");
+        assert_eq!(classifier.classify(&err), ErrorClass::Timeout);
+    }
+}
@@ -0,0 +1,222 @@
+use super::Deadline;
+use crate::{context::insert_child_token, Context, Layer, Service};
+use rama_utils::macros::define_inner_service_accessors;
+use std::{fmt, time::Duration};
+
+/// Serves requests using the inner [`Service`] within a timeout,
+/// serving them using a fallback [`Service`] instead in case that timeout elapses.
+///
+/// This allows for graceful degradation, e.g. serving a cached
+/// or otherwise degraded response in case the primary [`Service`] is too slow.
+///
+/// Only the timeout triggers the fallback: errors returned by the inner
+/// [`Service`] are returned as-is. Use [`Fallback`] to (also) fall back on errors.
+///
+/// Like [`Timeout`], the timeout is aware of the [`Deadline`] in the [`Context`],
+/// and the inner [`Service`] is served with the narrowed [`Deadline`].
+/// The fallback [`Service`] is served with the original [`Context`] instead,
+/// as the narrowed [`Deadline`] has already expired by then.
+/// The [`CancellationToken`] handed to the inner [`Service`] is cancelled
+/// once it times out, such that work spawned on its behalf stops as well.
+///
+/// Both services can receive the request,
+/// which is why the request is required to be [`Clone`].
+///
+/// [`Fallback`]: crate::layer::Fallback
+/// [`Timeout`]: super::Timeout
+/// [`CancellationToken`]: crate::context::CancellationToken
+pub struct TimeoutFallback<S, F> {
+    inner: S,
+    fallback: F,
+    timeout: Duration,
+}
+
+impl<S: fmt::Debug, F: fmt::Debug> fmt::Debug for TimeoutFallback<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimeoutFallback")
+            .field("inner", &self.inner)
+            .field("fallback", &self.fallback)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl<S, F> Clone for TimeoutFallback<S, F>
+where
+    S: Clone,
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            fallback: self.fallback.clone(),
+            timeout: self.timeout,
+        }
+    }
+}
+
+impl<S, F> TimeoutFallback<S, F> {
+    /// Create a new [`TimeoutFallback`] service,
+    /// using the fallback service in case the inner service
+    /// did not finish within the given timeout.
+    pub const fn new(inner: S, timeout: Duration, fallback: F) -> Self {
+        Self {
+            inner,
+            fallback,
+            timeout,
+        }
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S, F, State, Request> Service<State, Request> for TimeoutFallback<S, F>
+where
+    S: Service<State, Request>,
+    F: Service<State, Request, Response: Into<S::Response>, Error: Into<S::Error>>,
+    State: Clone + Send + Sync + 'static,
+    Request: Clone + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        let mut inner_ctx = ctx.clone();
+        let Some(deadline) = Deadline::narrow(&mut inner_ctx, self.timeout) else {
+            // the timeout is too large to ever expire
+            return self.inner.serve(inner_ctx, req).await;
+        };
+        let token = insert_child_token(&mut inner_ctx);
+        let sleep = tokio::time::sleep_until(deadline.instant().into());
+        tokio::select! {
+            res = self.inner.serve(inner_ctx, req.clone()) => return res,
+            _ = sleep => token.cancel(),
+        }
+
+        tracing::trace!(
+            timeout = ?self.timeout,
+            "timeout fallback service: primary timed out",
+        );
+        match self.fallback.serve(ctx, req).await {
+            Ok(response) => Ok(response.into()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// A [`Layer`] which wraps a [`Service`] in a [`TimeoutFallback`] service.
+pub struct TimeoutFallbackLayer<F> {
+    fallback: F,
+    timeout: Duration,
+}
+
+impl<F: fmt::Debug> fmt::Debug for TimeoutFallbackLayer<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimeoutFallbackLayer")
+            .field("fallback", &self.fallback)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl<F: Clone> Clone for TimeoutFallbackLayer<F> {
+    fn clone(&self) -> Self {
+        Self {
+            fallback: self.fallback.clone(),
+            timeout: self.timeout,
+        }
+    }
+}
+
+impl<F> TimeoutFallbackLayer<F> {
+    /// Create a new [`TimeoutFallbackLayer`],
+    /// using the fallback service in case the inner service
+    /// did not finish within the given timeout.
+    pub const fn new(timeout: Duration, fallback: F) -> Self {
+        Self { fallback, timeout }
+    }
+}
+
+impl<S, F: Clone> Layer<S> for TimeoutFallbackLayer<F> {
+    type Service = TimeoutFallback<S, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimeoutFallback::new(inner, self.timeout, self.fallback.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::service_fn;
+
+    fn degraded() -> impl Service<(), &'static str, Response = String, Error = String> + Clone {
+        service_fn(|req: &'static str| async move { Ok::<_, String>(format!("degraded: {req}")) })
+    }
+
+    #[tokio::test]
+    async fn test_timeout_fallback_serves_degraded_on_timeout() {
+        let service = TimeoutFallbackLayer::new(Duration::from_millis(50), degraded()).layer(
+            service_fn(|ctx: Context<()>, req: &'static str| async move {
+                assert!(Deadline::remaining_budget(&ctx).unwrap() <= Duration::from_millis(50));
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok::<_, String>(format!("primary: {req}"))
+            }),
+        );
+
+        let resp = service.serve(Context::default(), "hello").await.unwrap();
+        assert_eq!(resp, "degraded: hello");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_fallback_primary_within_timeout() {
+        let service = TimeoutFallback::new(
+            service_fn(
+                |req: &'static str| async move { Ok::<_, String>(format!("primary: {req}")) },
+            ),
+            Duration::from_secs(5),
+            degraded(),
+        );
+
+        let resp = service.serve(Context::default(), "hello").await.unwrap();
+        assert_eq!(resp, "primary: hello");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_fallback_propagates_non_timeout_error() {
+        let service = TimeoutFallback::new(
+            service_fn(
+                |_: &'static str| async move { Err::<String, _>("primary failed".to_owned()) },
+            ),
+            Duration::from_secs(5),
+            degraded(),
+        );
+
+        let err = service
+            .serve(Context::default(), "hello")
+            .await
+            .unwrap_err();
+        assert_eq!(err, "primary failed");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_fallback_error_is_returned() {
+        let service = TimeoutFallback::new(
+            service_fn(|_: &'static str| std::future::pending::<Result<String, String>>()),
+            Duration::from_millis(10),
+            service_fn(
+                |_: &'static str| async move { Err::<String, _>("fallback failed".to_owned()) },
+            ),
+        );
+
+        let err = service
+            .serve(Context::default(), "hello")
+            .await
+            .unwrap_err();
+        assert_eq!(err, "fallback failed");
+    }
+}
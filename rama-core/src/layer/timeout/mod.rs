@@ -2,9 +2,16 @@
 //!
 //! If the response does not complete within the specified timeout, the response
 //! will be aborted.
+//!
+//! Timeouts are aware of the [`Deadline`] in the [`Context`], such that nested
+//! timeouts share a single shrinking budget and inner layers never outlive it.
+//! Once the [`Deadline`] expires the [`CancellationToken`] handed to the inner
+//! service is cancelled, such that work spawned on behalf of the request stops as well.
+//!
+//! [`CancellationToken`]: crate::context::CancellationToken
 
 use super::{LayerErrorFn, LayerErrorStatic, MakeLayerError};
-use crate::{Context, Service};
+use crate::{context::insert_child_token, Context, Service};
 use rama_utils::macros::define_inner_service_accessors;
 use std::{fmt, time::Duration};
 
@@ -12,10 +19,18 @@ mod error;
 #[doc(inline)]
 pub use error::Elapsed;
 
+mod deadline;
+#[doc(inline)]
+pub use deadline::Deadline;
+
 mod layer;
 #[doc(inline)]
 pub use layer::TimeoutLayer;
 
+mod fallback;
+#[doc(inline)]
+pub use fallback::{TimeoutFallback, TimeoutFallbackLayer};
+
 /// Applies a timeout to requests.
 pub struct Timeout<S, F> {
     inner: S,
@@ -119,12 +134,184 @@ where
 
     async fn serve(
         &self,
-        ctx: Context<S>,
+        mut ctx: Context<S>,
         request: Request,
     ) -> Result<Self::Response, Self::Error> {
+        let Some(deadline) = Deadline::narrow(&mut ctx, self.timeout) else {
+            // the timeout is too large to ever expire
+            return self.inner.serve(ctx, request).await;
+        };
+        let token = insert_child_token(&mut ctx);
+        let sleep = tokio::time::sleep_until(deadline.instant().into());
         tokio::select! {
             res = self.inner.serve(ctx, request) => res,
-            _ = tokio::time::sleep(self.timeout) => Err(self.into_error.make_layer_error().into()),
+            _ = sleep => {
+                token.cancel();
+                Err(self.into_error.make_layer_error().into())
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{context::CancellationToken, error::BoxError, service::service_fn, Layer};
+    use std::time::Instant;
+
+    fn sleepy(
+        sleep: Duration,
+    ) -> impl Service<(), (), Response = Option<Duration>, Error = BoxError> {
+        service_fn(move |ctx: Context<()>, ()| async move {
+            let budget = Deadline::remaining_budget(&ctx);
+            tokio::time::sleep(sleep).await;
+            Ok(budget)
+        })
+    }
+
+    #[tokio::test]
+    async fn test_nested_timeouts_share_budget() {
+        // the inner timeout is longer than the outer one,
+        // so the inner service only gets the budget of the outer timeout
+        let service = (
+            TimeoutLayer::new(Duration::from_millis(200)),
+            TimeoutLayer::new(Duration::from_secs(10)),
+        )
+            .layer(sleepy(Duration::ZERO));
+        let budget = service
+            .serve(Context::default(), ())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(budget <= Duration::from_millis(200));
+
+        // the inner timeout is shorter, so it narrows the budget
+        let service = (
+            TimeoutLayer::new(Duration::from_secs(10)),
+            TimeoutLayer::new(Duration::from_millis(100)),
+        )
+            .layer(sleepy(Duration::ZERO));
+        let budget = service
+            .serve(Context::default(), ())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(budget <= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_respects_deadline_in_context() {
+        let service = TimeoutLayer::with_error(Duration::from_secs(10), "inner timeout")
+            .layer(sleepy(Duration::from_secs(5)));
+
+        let mut ctx = Context::default();
+        ctx.insert(Deadline::after(Duration::from_millis(50)).unwrap());
+
+        let start = Instant::now();
+        let err = service.serve(ctx, ()).await.unwrap_err();
+        assert_eq!(err.to_string(), "inner timeout");
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_with_expired_deadline() {
+        let service =
+            TimeoutLayer::new(Duration::from_secs(10)).layer(sleepy(Duration::from_secs(5)));
+
+        let mut ctx = Context::default();
+        ctx.insert(Deadline::at(Instant::now()));
+
+        let err = service.serve(ctx, ()).await.unwrap_err();
+        assert!(err.downcast_ref::<Elapsed>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_deadline_shrinks_as_it_is_consumed() {
+        let service = TimeoutLayer::new(Duration::from_millis(500)).layer(service_fn(
+            |ctx: Context<()>, ()| async move {
+                let first = Deadline::remaining_budget(&ctx).unwrap();
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                let second = Deadline::remaining_budget(&ctx).unwrap();
+                Ok::<_, BoxError>((first, second))
+            },
+        ));
+        let (first, second) = service.serve(Context::default(), ()).await.unwrap();
+        assert!(first <= Duration::from_millis(500));
+        assert!(second + Duration::from_millis(50) <= first);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_cancels_spawned_work() {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let tx = std::sync::Mutex::new(Some(tx));
+        let service = TimeoutLayer::new(Duration::from_millis(50)).layer(service_fn(
+            move |ctx: Context<()>, ()| {
+                let tx = tx.lock().unwrap().take().unwrap();
+                async move {
+                    tokio::spawn({
+                        let cancelled = ctx.cancelled();
+                        async move {
+                            cancelled.await;
+                            let _ = tx.send(());
+                        }
+                    });
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Ok::<_, BoxError>(())
+                }
+            },
+        ));
+
+        let parent = CancellationToken::new();
+        let mut ctx = Context::default();
+        ctx.insert(parent.clone());
+
+        let err = service.serve(ctx, ()).await.unwrap_err();
+        assert!(err.downcast_ref::<Elapsed>().is_some());
+        tokio::time::timeout(Duration::from_secs(1), rx)
+            .await
+            .unwrap()
+            .unwrap();
+        // only the child token handed to the inner service is cancelled
+        assert!(!parent.is_cancelled());
+    }
+
+    #[test]
+    fn test_deadline_narrow() {
+        let mut ctx = Context::<()>::default();
+        assert!(Deadline::remaining_budget(&ctx).is_none());
+
+        // a timeout too large to be represented is no deadline
+        assert!(Deadline::after(Duration::MAX).is_none());
+        assert!(Deadline::narrow(&mut ctx, Duration::MAX).is_none());
+        assert!(Deadline::remaining_budget(&ctx).is_none());
+
+        let outer = Deadline::narrow(&mut ctx, Duration::from_secs(1)).unwrap();
+        assert_eq!(ctx.get::<Deadline>(), Some(&outer));
+
+        // a longer timeout does not extend the deadline
+        assert_eq!(
+            Deadline::narrow(&mut ctx, Duration::from_secs(10)),
+            Some(outer)
+        );
+        assert_eq!(Deadline::narrow(&mut ctx, Duration::MAX), Some(outer));
+
+        let inner = Deadline::narrow(&mut ctx, Duration::from_millis(10)).unwrap();
+        assert!(inner < outer);
+        assert_eq!(ctx.get::<Deadline>(), Some(&inner));
+        assert!(!inner.is_expired());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_with_duration_max() {
+        let service = TimeoutLayer::new(Duration::MAX).layer(sleepy(Duration::from_millis(10)));
+        let budget = service.serve(Context::default(), ()).await.unwrap();
+        assert!(budget.is_none());
+
+        // a deadline in the context still applies
+        let mut ctx = Context::default();
+        ctx.insert(Deadline::after(Duration::from_millis(50)).unwrap());
+        let service = TimeoutLayer::new(Duration::MAX).layer(sleepy(Duration::from_secs(5)));
+        let err = service.serve(ctx, ()).await.unwrap_err();
+        assert!(err.downcast_ref::<Elapsed>().is_some());
+    }
+}
@@ -5,9 +5,15 @@
 //! client and servers, when serving protocols such as h2 and h3.
 //!
 //! See the [`Executor`] for more information on how to use it.
+//! Use [`spawn_with_context`] to spawn background work from within a service,
+//! while keeping it attached to the current tracing span.
 //!
 //! [`Executor`]: crate::rt::Executor
 
 mod executor;
 #[doc(inline)]
 pub use executor::Executor;
+
+mod spawn;
+#[doc(inline)]
+pub use spawn::spawn_with_context;
@@ -0,0 +1,73 @@
+use crate::Context;
+use std::future::Future;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+
+/// Spawn a future, created from a clone of the given [`Context`],
+/// on the executor of that [`Context`].
+///
+/// Unlike [`Context::spawn`], the spawned future is instrumented with the
+/// tracing span that is current at the time of spawning, such that anything
+/// logged by the background work remains attributed to the (request) span
+/// it originated from.
+///
+/// As the future is spawned using the [`Executor`] of the [`Context`],
+/// it is awaited during graceful shutdown in case the executor was created
+/// with a shutdown guard.
+///
+/// [`Executor`]: super::Executor
+pub fn spawn_with_context<S, F, Fut>(ctx: &Context<S>, f: F) -> JoinHandle<Fut::Output>
+where
+    S: Clone,
+    F: FnOnce(Context<S>) -> Fut,
+    Fut: Future<Output: Send + 'static> + Send + 'static,
+{
+    let future = f(ctx.clone()).instrument(tracing::Span::current());
+    ctx.spawn(future)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graceful::Shutdown;
+    use crate::rt::Executor;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_spawn_with_context_preserves_span_and_is_graceful() {
+        let _subscriber = tracing::subscriber::set_default(tracing_subscriber::registry());
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let shutdown = Shutdown::new(async move {
+            let _ = rx.await;
+        });
+
+        let done = Arc::new(AtomicBool::new(false));
+
+        let span = tracing::info_span!("parent");
+        let parent_id = span.id().unwrap();
+        {
+            let _enter = span.enter();
+
+            let mut ctx = Context::new((), Executor::graceful(shutdown.guard()));
+            ctx.insert("extension");
+
+            let done = done.clone();
+            spawn_with_context(&ctx, move |ctx| async move {
+                assert_eq!(tracing::Span::current().id(), Some(parent_id));
+                assert_eq!(ctx.get::<&'static str>(), Some(&"extension"));
+                tracing::info!("background work");
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                done.store(true, Ordering::SeqCst);
+            });
+        }
+
+        tx.send(()).unwrap();
+        shutdown.shutdown().await;
+        assert!(done.load(Ordering::SeqCst));
+    }
+}
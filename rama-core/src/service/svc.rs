@@ -1,6 +1,7 @@
 //! [`Service`] and [`BoxService`] traits.
 
 use crate::error::BoxError;
+use crate::layer::{AndThen, MapErr, MapResponse};
 use crate::Context;
 use std::convert::Infallible;
 use std::future::Future;
@@ -29,6 +30,41 @@ pub trait Service<S, Request>: Sized + Send + Sync + 'static {
             inner: Box::new(self),
         }
     }
+
+    /// Map the response of this service, once it resolved successfully,
+    /// similar to [`Result::map`].
+    ///
+    /// See [`MapResponse`] for more details.
+    fn map_response<F, Response>(self, f: F) -> MapResponse<Self, F>
+    where
+        F: FnOnce(Self::Response) -> Response + Clone + Send + Sync + 'static,
+    {
+        MapResponse::new(self, f)
+    }
+
+    /// Map the error of this service, in case it failed,
+    /// similar to [`Result::map_err`].
+    ///
+    /// See [`MapErr`] for more details.
+    fn map_err<F, Error>(self, f: F) -> MapErr<Self, F>
+    where
+        F: FnOnce(Self::Error) -> Error + Clone + Send + Sync + 'static,
+    {
+        MapErr::new(self, f)
+    }
+
+    /// Chain an async function to this service, called with its response
+    /// once it resolved successfully, similar to [`Result::and_then`].
+    ///
+    /// See [`AndThen`] for more details.
+    fn and_then<F, Fut, Response, Error>(self, f: F) -> AndThen<Self, F>
+    where
+        F: FnOnce(Self::Response) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = Result<Response, Error>> + Send + 'static,
+        Self::Error: Into<Error>,
+    {
+        AndThen::new(self, f)
+    }
 }
 
 impl<S, Request> Service<S, Request> for ()
@@ -297,6 +333,98 @@ mod tests {
         }
     }
 
+    #[derive(Debug)]
+    struct SubSvc(usize);
+
+    impl Service<(), usize> for SubSvc {
+        type Response = usize;
+        type Error = crate::error::OpaqueError;
+
+        async fn serve(
+            &self,
+            _ctx: Context<()>,
+            req: usize,
+        ) -> Result<Self::Response, Self::Error> {
+            req.checked_sub(self.0)
+                .ok_or_else(|| crate::error::OpaqueError::from_display("subtract with overflow"))
+        }
+    }
+
+    #[derive(Debug)]
+    struct SquareSvc;
+
+    impl Service<(), usize> for SquareSvc {
+        type Response = usize;
+        type Error = std::io::Error;
+
+        async fn serve(
+            &self,
+            _ctx: Context<()>,
+            req: usize,
+        ) -> Result<Self::Response, Self::Error> {
+            Ok(req * req)
+        }
+    }
+
+    #[test]
+    fn assert_either4_service() {
+        fn assert_service<S: Service<(), usize, Response = usize, Error = BoxError>>() {}
+
+        assert_service::<crate::combinators::Either4<AddSvc, MulSvc, SubSvc, SquareSvc>>();
+    }
+
+    #[tokio::test]
+    async fn either4_dispatch() {
+        use crate::combinators::Either4;
+
+        let services: Vec<Either4<AddSvc, MulSvc, SubSvc, SquareSvc>> = vec![
+            Either4::A(AddSvc(1)),
+            Either4::B(MulSvc(2)),
+            Either4::C(SubSvc(3)),
+            Either4::D(SquareSvc),
+        ];
+
+        let mut responses = Vec::new();
+        for svc in &services {
+            responses.push(svc.serve(Context::default(), 5).await.unwrap());
+        }
+        assert_eq!(responses, [6, 10, 2, 25]);
+
+        // errors of each variant are unified as a BoxError
+        let err = services[2].serve(Context::default(), 1).await.unwrap_err();
+        assert_eq!(err.to_string(), "subtract with overflow");
+    }
+
+    #[tokio::test]
+    async fn map_response_and_err() {
+        let svc = SubSvc(3)
+            .map_response(|n: usize| n.to_string())
+            .map_err(|err: crate::error::OpaqueError| format!("sub failed: {err}"));
+
+        let response = svc.serve(Context::default(), 5).await.unwrap();
+        assert_eq!(response, "2");
+
+        let err = svc.serve(Context::default(), 1).await.unwrap_err();
+        assert_eq!(err, "sub failed: subtract with overflow");
+    }
+
+    #[tokio::test]
+    async fn and_then() {
+        let svc = AddSvc(1).and_then(|n: usize| async move {
+            if n % 2 == 0 {
+                Ok(n / 2)
+            } else {
+                Err(BoxError::from("odd number"))
+            }
+        });
+
+        let response = svc.serve(Context::default(), 3).await.unwrap();
+        assert_eq!(response, 2);
+
+        let err = svc.serve(Context::default(), 4).await.unwrap_err();
+        assert_eq!(err.to_string(), "odd number");
+    }
+
     #[tokio::test]
     async fn service_arc() {
         let svc = std::sync::Arc::new(AddSvc(1));
@@ -0,0 +1,35 @@
+//! Message digest support, as defined in [FIPS 180-4].
+//!
+//! Used to fingerprint messages, e.g. the bodies of requests.
+//!
+//! [FIPS 180-4]: https://csrc.nist.gov/publications/detail/fips/180/4/final
+
+use aws_lc_rs::digest;
+
+/// The length in bytes of a SHA-256 digest.
+pub const SHA256_LEN: usize = 32;
+
+/// Compute the SHA-256 digest of the given message.
+pub fn sha256(message: impl AsRef<[u8]>) -> [u8; SHA256_LEN] {
+    let digest = digest::digest(&digest::SHA256, message.as_ref());
+    let mut output = [0; SHA256_LEN];
+    output.copy_from_slice(digest.as_ref());
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256() {
+        // test vector from FIPS 180-4 examples
+        let expected = [
+            0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae,
+            0x22, 0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61,
+            0xf2, 0x00, 0x15, 0xad,
+        ];
+        assert_eq!(sha256("abc"), expected);
+        assert_ne!(sha256("abd"), expected);
+    }
+}
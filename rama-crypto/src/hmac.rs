@@ -0,0 +1,105 @@
+//! HMAC (keyed-hash message authentication code) support, as defined in [RFC 2104].
+//!
+//! Used to sign and verify messages using a shared secret,
+//! e.g. the bodies of webhook requests.
+//!
+//! [RFC 2104]: https://datatracker.ietf.org/doc/html/rfc2104
+
+use aws_lc_rs::{constant_time, hmac};
+use std::fmt;
+
+/// The length in bytes of an HMAC-SHA256 tag.
+pub const HMAC_SHA256_LEN: usize = 32;
+
+#[derive(Clone)]
+/// A shared secret key used to sign and verify messages using HMAC-SHA256.
+pub struct HmacSha256Key(hmac::Key);
+
+impl fmt::Debug for HmacSha256Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HmacSha256Key").finish_non_exhaustive()
+    }
+}
+
+impl HmacSha256Key {
+    /// Create a new [`HmacSha256Key`] from the given shared secret.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self(hmac::Key::new(hmac::HMAC_SHA256, secret.as_ref()))
+    }
+
+    /// Compute the HMAC-SHA256 tag of the given message.
+    pub fn sign(&self, message: impl AsRef<[u8]>) -> [u8; HMAC_SHA256_LEN] {
+        let tag = hmac::sign(&self.0, message.as_ref());
+        let mut output = [0; HMAC_SHA256_LEN];
+        output.copy_from_slice(tag.as_ref());
+        output
+    }
+
+    /// Compute the HMAC-SHA256 tag of the message made up of the given parts,
+    /// without having to concatenate them first.
+    pub fn sign_parts<I>(&self, parts: I) -> [u8; HMAC_SHA256_LEN]
+    where
+        I: IntoIterator<Item: AsRef<[u8]>>,
+    {
+        let mut ctx = hmac::Context::with_key(&self.0);
+        for part in parts {
+            ctx.update(part.as_ref());
+        }
+        let mut output = [0; HMAC_SHA256_LEN];
+        output.copy_from_slice(ctx.sign().as_ref());
+        output
+    }
+
+    /// Verify the given tag against the HMAC-SHA256 tag of the given message.
+    ///
+    /// The tags are compared in constant time.
+    pub fn verify(&self, message: impl AsRef<[u8]>, tag: impl AsRef<[u8]>) -> bool {
+        hmac::verify(&self.0, message.as_ref(), tag.as_ref()).is_ok()
+    }
+
+    /// Verify the given tag against the HMAC-SHA256 tag of the message made up of the given parts.
+    ///
+    /// The tags are compared in constant time.
+    pub fn verify_parts<I>(&self, parts: I, tag: impl AsRef<[u8]>) -> bool
+    where
+        I: IntoIterator<Item: AsRef<[u8]>>,
+    {
+        constant_time::verify_slices_are_equal(&self.sign_parts(parts), tag.as_ref()).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_sha256_rfc4231() {
+        // test case 2 of RFC 4231
+        let key = HmacSha256Key::new("Jefe");
+        let expected = [
+            0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95,
+            0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9,
+            0x64, 0xec, 0x38, 0x43,
+        ];
+        let message = "what do ya want for nothing?";
+
+        assert_eq!(key.sign(message), expected);
+        assert_eq!(
+            key.sign_parts(["what do ya ", "want ", "for nothing?"]),
+            expected
+        );
+        assert!(key.verify(message, expected));
+        assert!(key.verify_parts(["what do ya want", " for nothing?"], expected));
+    }
+
+    #[test]
+    fn test_hmac_sha256_verify_mismatch() {
+        let key = HmacSha256Key::new("secret");
+        let tag = key.sign("hello");
+
+        assert!(!key.verify("hello!", tag));
+        assert!(!key.verify("hello", &tag[..16]));
+        assert!(!HmacSha256Key::new("other").verify("hello", tag));
+        assert!(!key.verify_parts(["hel", "lo", "!"], tag));
+    }
+}
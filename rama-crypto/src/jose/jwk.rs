@@ -0,0 +1,268 @@
+use super::{b64, JwsAlgorithm, JwsError};
+use aws_lc_rs::{digest, signature};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A (public) JSON Web Key, as defined in [RFC 7517].
+///
+/// Only the parameters required to verify signatures are supported.
+///
+/// [RFC 7517]: https://datatracker.ietf.org/doc/html/rfc7517
+pub struct Jwk {
+    #[serde(flatten)]
+    key: JwkKey,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    alg: Option<JwsAlgorithm>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kty")]
+#[non_exhaustive]
+/// The key type and type-specific parameters of a [`Jwk`].
+pub enum JwkKey {
+    #[serde(rename = "OKP")]
+    /// Octet key pair, as defined in [RFC 8037].
+    ///
+    /// [RFC 8037]: https://datatracker.ietf.org/doc/html/rfc8037
+    Okp {
+        /// The curve of the key.
+        crv: OkpCurve,
+        #[serde(with = "b64")]
+        /// The public key.
+        x: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+/// The curve of an [`JwkKey::Okp`] key.
+pub enum OkpCurve {
+    /// Ed25519 signature algorithm key pairs.
+    Ed25519,
+}
+
+impl Jwk {
+    /// Create a new [`Jwk`] for the given [`JwkKey`].
+    pub fn new(key: JwkKey) -> Self {
+        Self {
+            key,
+            kid: None,
+            alg: None,
+        }
+    }
+
+    /// Create a new [`Jwk`] for the given Ed25519 public key.
+    pub fn ed25519(public_key: impl Into<Vec<u8>>) -> Self {
+        Self::new(JwkKey::Okp {
+            crv: OkpCurve::Ed25519,
+            x: public_key.into(),
+        })
+    }
+
+    /// The [`JwkKey`] of this [`Jwk`].
+    pub fn key(&self) -> &JwkKey {
+        &self.key
+    }
+
+    /// The key id (`kid`) of this [`Jwk`], if defined.
+    pub fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    /// Set the key id (`kid`) of this [`Jwk`].
+    pub fn with_kid(mut self, kid: impl Into<String>) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    /// Set the key id (`kid`) of this [`Jwk`].
+    pub fn set_kid(&mut self, kid: impl Into<String>) -> &mut Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    /// The algorithm (`alg`) this [`Jwk`] is intended for, if defined.
+    pub fn alg(&self) -> Option<JwsAlgorithm> {
+        self.alg
+    }
+
+    /// Set the algorithm (`alg`) this [`Jwk`] is intended for.
+    pub fn with_alg(mut self, alg: JwsAlgorithm) -> Self {
+        self.alg = Some(alg);
+        self
+    }
+
+    /// Set the algorithm (`alg`) this [`Jwk`] is intended for.
+    pub fn set_alg(&mut self, alg: JwsAlgorithm) -> &mut Self {
+        self.alg = Some(alg);
+        self
+    }
+
+    /// The [`JwsAlgorithm`] signatures verified by this [`Jwk`] have to use.
+    ///
+    /// This is the `alg` of the key if defined, or otherwise
+    /// the algorithm implied by its key type.
+    pub fn algorithm(&self) -> JwsAlgorithm {
+        self.alg.unwrap_or(match self.key {
+            JwkKey::Okp {
+                crv: OkpCurve::Ed25519,
+                ..
+            } => JwsAlgorithm::EdDSA,
+        })
+    }
+
+    /// Compute the thumbprint of this [`Jwk`], as defined in [RFC 7638],
+    /// using SHA-256 as hash function.
+    ///
+    /// The thumbprint is base64url encoded and can be used as key id.
+    ///
+    /// [RFC 7638]: https://datatracker.ietf.org/doc/html/rfc7638
+    pub fn thumbprint(&self) -> String {
+        // required members in lexicographic order, without whitespace
+        let input = match &self.key {
+            JwkKey::Okp { crv, x } => format!(
+                r#"{{"crv":{},"kty":"OKP","x":"{}"}}"#,
+                serde_json::to_string(crv).unwrap_or_default(),
+                b64::encode(x),
+            ),
+        };
+        b64::encode(digest::digest(&digest::SHA256, input.as_bytes()))
+    }
+
+    /// Verify the `signature` of the given `message` using this [`Jwk`],
+    /// for the given [`JwsAlgorithm`].
+    ///
+    /// Verification fails with [`JwsError::AlgorithmMismatch`] in case the
+    /// given algorithm is not the [algorithm](Self::algorithm) of this key.
+    pub fn verify(
+        &self,
+        alg: JwsAlgorithm,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), JwsError> {
+        let expected = self.algorithm();
+        if alg != expected {
+            return Err(JwsError::AlgorithmMismatch {
+                expected,
+                found: alg,
+            });
+        }
+        match &self.key {
+            JwkKey::Okp {
+                crv: OkpCurve::Ed25519,
+                x,
+            } => signature::UnparsedPublicKey::new(&signature::ED25519, x)
+                .verify(message, signature)
+                .map_err(|_| JwsError::InvalidSignature),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+/// A JSON Web Key Set, as defined in [RFC 7517, section 5].
+///
+/// Keys of an unsupported key type are ignored when deserializing a [`JwkSet`].
+///
+/// [RFC 7517, section 5]: https://datatracker.ietf.org/doc/html/rfc7517#section-5
+pub struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+impl JwkSet {
+    /// Create a new [`JwkSet`] for the given keys.
+    pub fn new(keys: impl IntoIterator<Item = Jwk>) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+        }
+    }
+
+    /// The keys of this [`JwkSet`].
+    pub fn keys(&self) -> &[Jwk] {
+        &self.keys
+    }
+
+    /// Find the key with the given key id (`kid`).
+    pub fn find(&self, kid: &str) -> Option<&Jwk> {
+        self.keys.iter().find(|key| key.kid() == Some(kid))
+    }
+}
+
+impl From<Jwk> for JwkSet {
+    fn from(key: Jwk) -> Self {
+        Self { keys: vec![key] }
+    }
+}
+
+impl FromIterator<Jwk> for JwkSet {
+    fn from_iter<T: IntoIterator<Item = Jwk>>(iter: T) -> Self {
+        Self::new(iter)
+    }
+}
+
+impl<'de> Deserialize<'de> for JwkSet {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct RawJwkSet {
+            keys: Vec<serde_json::Value>,
+        }
+
+        let raw = RawJwkSet::deserialize(deserializer)?;
+        Ok(Self {
+            keys: raw
+                .keys
+                .into_iter()
+                .filter_map(|key| serde_json::from_value(key).ok())
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jwk_rfc8037_thumbprint() {
+        let jwk: Jwk = serde_json::from_str(
+            r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#,
+        )
+        .unwrap();
+        assert_eq!(jwk.algorithm(), JwsAlgorithm::EdDSA);
+        assert_eq!(
+            jwk.thumbprint(),
+            "kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k"
+        );
+    }
+
+    #[test]
+    fn test_jwk_serde() {
+        let jwk = Jwk::ed25519([1; 32])
+            .with_kid("foo")
+            .with_alg(JwsAlgorithm::EdDSA);
+        let s = serde_json::to_string(&jwk).unwrap();
+        assert_eq!(
+            s,
+            r#"{"kty":"OKP","crv":"Ed25519","x":"AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE","kid":"foo","alg":"EdDSA"}"#
+        );
+        assert_eq!(jwk, serde_json::from_str(&s).unwrap());
+    }
+
+    #[test]
+    fn test_jwk_set_ignores_unsupported_keys() {
+        let set: JwkSet = serde_json::from_str(
+            r#"{"keys":[
+                {"kty":"RSA","kid":"rsa","n":"AQAB","e":"AQAB"},
+                {"kty":"OKP","crv":"Ed25519","kid":"ed","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(set.keys().len(), 1);
+        assert!(set.find("ed").is_some());
+        assert!(set.find("rsa").is_none());
+    }
+}
@@ -0,0 +1,604 @@
+use super::{b64, Jwk};
+use aws_lc_rs::signature::{self, KeyPair as _};
+use rama_core::error::{ErrorContext, OpaqueError};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+/// The algorithm (`alg`) used to sign a JWS.
+pub enum JwsAlgorithm {
+    /// Edwards-curve Digital Signature Algorithm, as defined in [RFC 8037].
+    ///
+    /// Only Ed25519 keys are supported.
+    ///
+    /// [RFC 8037]: https://datatracker.ietf.org/doc/html/rfc8037
+    EdDSA,
+}
+
+impl JwsAlgorithm {
+    /// The name of the algorithm, as used in the `alg` header.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::EdDSA => "EdDSA",
+        }
+    }
+}
+
+impl fmt::Display for JwsAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for JwsAlgorithm {
+    type Err = JwsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "EdDSA" => Ok(Self::EdDSA),
+            _ => Err(JwsError::UnsupportedAlgorithm(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// The (protected) header of a JWS.
+pub struct JwsHeader {
+    alg: JwsAlgorithm,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    typ: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    crit: Option<Vec<String>>,
+    #[serde(flatten)]
+    params: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The header parameters registered in [RFC 7515, section 4.1],
+/// which are not allowed to be listed as critical (`crit`).
+///
+/// [RFC 7515, section 4.1]: https://datatracker.ietf.org/doc/html/rfc7515#section-4.1
+const REGISTERED_HEADER_PARAMS: &[&str] = &[
+    "alg", "jku", "jwk", "kid", "x5u", "x5c", "x5t", "x5t#S256", "typ", "cty", "crit",
+];
+
+impl JwsHeader {
+    /// Create a new [`JwsHeader`] for the given [`JwsAlgorithm`].
+    pub fn new(alg: JwsAlgorithm) -> Self {
+        Self {
+            alg,
+            kid: None,
+            typ: None,
+            crit: None,
+            params: serde_json::Map::new(),
+        }
+    }
+
+    /// The algorithm (`alg`) of this [`JwsHeader`].
+    pub fn alg(&self) -> JwsAlgorithm {
+        self.alg
+    }
+
+    /// The key id (`kid`) of this [`JwsHeader`], if defined.
+    pub fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    /// Set the key id (`kid`) of this [`JwsHeader`].
+    pub fn with_kid(mut self, kid: impl Into<String>) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    /// Set the key id (`kid`) of this [`JwsHeader`].
+    pub fn set_kid(&mut self, kid: impl Into<String>) -> &mut Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    /// The type (`typ`) of this [`JwsHeader`], if defined.
+    pub fn typ(&self) -> Option<&str> {
+        self.typ.as_deref()
+    }
+
+    /// Set the type (`typ`) of this [`JwsHeader`], e.g. `JWT`.
+    pub fn with_typ(mut self, typ: impl Into<String>) -> Self {
+        self.typ = Some(typ.into());
+        self
+    }
+
+    /// Set the type (`typ`) of this [`JwsHeader`], e.g. `JWT`.
+    pub fn set_typ(&mut self, typ: impl Into<String>) -> &mut Self {
+        self.typ = Some(typ.into());
+        self
+    }
+
+    /// The extension header parameters listed as critical (`crit`)
+    /// in this [`JwsHeader`], if defined.
+    ///
+    /// A JWS is only accepted if all of them are understood by the verifier,
+    /// see [`verify_compact_with_critical`].
+    pub fn crit(&self) -> Option<&[String]> {
+        self.crit.as_deref()
+    }
+
+    /// Mark the given extension header parameter as critical (`crit`).
+    ///
+    /// Multiple parameters can be pushed, all of which are listed as critical.
+    pub fn with_pushed_crit(mut self, name: impl Into<String>) -> Self {
+        self.crit.get_or_insert_with(Vec::new).push(name.into());
+        self
+    }
+
+    /// Mark the given extension header parameter as critical (`crit`).
+    ///
+    /// Multiple parameters can be pushed, all of which are listed as critical.
+    pub fn push_crit(&mut self, name: impl Into<String>) -> &mut Self {
+        self.crit.get_or_insert_with(Vec::new).push(name.into());
+        self
+    }
+
+    /// The value of the extension header parameter with the given name, if defined.
+    pub fn param(&self, name: &str) -> Option<&serde_json::Value> {
+        self.params.get(name)
+    }
+
+    /// Set an extension header parameter of this [`JwsHeader`].
+    pub fn with_param(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.params.insert(name.into(), value.into());
+        self
+    }
+
+    /// Set an extension header parameter of this [`JwsHeader`].
+    pub fn set_param(
+        &mut self,
+        name: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> &mut Self {
+        self.params.insert(name.into(), value.into());
+        self
+    }
+}
+
+#[derive(Debug)]
+/// Error returned in case a JWS could not be verified.
+pub enum JwsError {
+    /// The JWS is not a valid compact serialized JWS.
+    Malformed(OpaqueError),
+    /// The `alg` of the JWS is not supported.
+    UnsupportedAlgorithm(String),
+    /// The `alg` of the JWS does not match the algorithm of the key.
+    AlgorithmMismatch {
+        /// The algorithm of the key.
+        expected: JwsAlgorithm,
+        /// The algorithm of the JWS.
+        found: JwsAlgorithm,
+    },
+    /// The JWS lists a critical (`crit`) extension header parameter
+    /// which is not understood by the verifier.
+    UnsupportedCritical(String),
+    /// The signature of the JWS is invalid.
+    InvalidSignature,
+}
+
+impl fmt::Display for JwsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(err) => write!(f, "malformed jws: {err}"),
+            Self::UnsupportedAlgorithm(alg) => write!(f, "unsupported jws algorithm: {alg}"),
+            Self::AlgorithmMismatch { expected, found } => write!(
+                f,
+                "jws algorithm mismatch: expected {expected}, found {found}"
+            ),
+            Self::UnsupportedCritical(name) => {
+                write!(f, "unsupported critical jws header parameter: {name}")
+            }
+            Self::InvalidSignature => write!(f, "invalid jws signature"),
+        }
+    }
+}
+
+impl std::error::Error for JwsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Malformed(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// A key which can be used to sign a JWS.
+pub trait JwsSigner {
+    /// The [`JwsAlgorithm`] used by this signer.
+    fn algorithm(&self) -> JwsAlgorithm;
+
+    /// Sign the given message.
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, OpaqueError>;
+}
+
+impl<S: JwsSigner> JwsSigner for &S {
+    fn algorithm(&self) -> JwsAlgorithm {
+        (**self).algorithm()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, OpaqueError> {
+        (**self).sign(message)
+    }
+}
+
+/// An Ed25519 private key, used to sign a JWS using [`JwsAlgorithm::EdDSA`].
+pub struct Ed25519SigningKey {
+    key_pair: signature::Ed25519KeyPair,
+}
+
+impl fmt::Debug for Ed25519SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ed25519SigningKey")
+            .field("public_key", &b64::encode(self.public_key()))
+            .finish()
+    }
+}
+
+impl Ed25519SigningKey {
+    /// Generate a new random [`Ed25519SigningKey`].
+    pub fn generate() -> Result<Self, OpaqueError> {
+        let key_pair = signature::Ed25519KeyPair::generate()
+            .map_err(|_| OpaqueError::from_display("failed to generate ed25519 key pair"))?;
+        Ok(Self { key_pair })
+    }
+
+    /// Create an [`Ed25519SigningKey`] from its 32 byte private key (seed).
+    pub fn from_seed(seed: &[u8]) -> Result<Self, OpaqueError> {
+        let key_pair = signature::Ed25519KeyPair::from_seed_unchecked(seed)
+            .map_err(OpaqueError::from_std)
+            .context("create ed25519 key pair from seed")?;
+        Ok(Self { key_pair })
+    }
+
+    /// Create an [`Ed25519SigningKey`] from a PKCS#8 (v1 or v2) DER document.
+    pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self, OpaqueError> {
+        let key_pair = signature::Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8)
+            .map_err(OpaqueError::from_std)
+            .context("create ed25519 key pair from pkcs8 document")?;
+        Ok(Self { key_pair })
+    }
+
+    /// The 32 byte public key of this [`Ed25519SigningKey`].
+    pub fn public_key(&self) -> &[u8] {
+        self.key_pair.public_key().as_ref()
+    }
+
+    /// The public [`Jwk`] of this [`Ed25519SigningKey`],
+    /// which can be used to verify its signatures.
+    pub fn public_jwk(&self) -> Jwk {
+        Jwk::ed25519(self.public_key()).with_alg(JwsAlgorithm::EdDSA)
+    }
+}
+
+impl JwsSigner for Ed25519SigningKey {
+    fn algorithm(&self) -> JwsAlgorithm {
+        JwsAlgorithm::EdDSA
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, OpaqueError> {
+        Ok(self.key_pair.sign(message).as_ref().to_vec())
+    }
+}
+
+/// Sign the given `payload` as a compact serialized JWS.
+///
+/// The `alg` of the given [`JwsHeader`] is always
+/// overwritten with the algorithm of the [`JwsSigner`].
+pub fn sign_compact(
+    signer: impl JwsSigner,
+    mut header: JwsHeader,
+    payload: &[u8],
+) -> Result<String, OpaqueError> {
+    header.alg = signer.algorithm();
+    let header = serde_json::to_vec(&header).context("serialize jws header")?;
+
+    let mut token = b64::encode(header);
+    token.push('.');
+    token.push_str(&b64::encode(payload));
+
+    let signature = signer.sign(token.as_bytes()).context("sign jws")?;
+    token.push('.');
+    token.push_str(&b64::encode(signature));
+
+    Ok(token)
+}
+
+#[derive(Debug, Clone)]
+/// A JWS verified by [`verify_compact`].
+pub struct VerifiedJws {
+    header: JwsHeader,
+    payload: Vec<u8>,
+}
+
+impl VerifiedJws {
+    /// The (protected) [`JwsHeader`] of the JWS.
+    pub fn header(&self) -> &JwsHeader {
+        &self.header
+    }
+
+    /// The verified payload of the JWS.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Consume this [`VerifiedJws`] into its verified payload.
+    pub fn into_payload(self) -> Vec<u8> {
+        self.payload
+    }
+}
+
+#[derive(Deserialize)]
+struct RawJwsHeader {
+    alg: String,
+    #[serde(default)]
+    kid: Option<String>,
+    #[serde(default)]
+    typ: Option<String>,
+    #[serde(default)]
+    crit: Option<Vec<String>>,
+    #[serde(flatten)]
+    params: serde_json::Map<String, serde_json::Value>,
+}
+
+fn split_compact(token: &str) -> Result<(&str, &str, &str), JwsError> {
+    let mut parts = token.split('.');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(header), Some(payload), Some(signature), None) => Ok((header, payload, signature)),
+        _ => Err(JwsError::Malformed(OpaqueError::from_display(
+            "expected three dot-separated parts",
+        ))),
+    }
+}
+
+fn parse_header(header: &str) -> Result<JwsHeader, JwsError> {
+    let raw_header: RawJwsHeader = b64::decode(header)
+        .context("decode header")
+        .and_then(|header| serde_json::from_slice(&header).context("parse header"))
+        .map_err(JwsError::Malformed)?;
+
+    // RFC 7515, section 4.1.11: the critical extensions have to be
+    // a non-empty list of extension parameters present in the header
+    if let Some(crit) = &raw_header.crit {
+        let malformed = |msg| Err(JwsError::Malformed(OpaqueError::from_display(msg)));
+        if crit.is_empty() {
+            return malformed("empty crit header parameter");
+        }
+        for (index, name) in crit.iter().enumerate() {
+            if REGISTERED_HEADER_PARAMS.contains(&name.as_str()) {
+                return malformed("registered header parameter listed as critical");
+            }
+            if !raw_header.params.contains_key(name) {
+                return malformed("critical header parameter missing from header");
+            }
+            if crit[..index].contains(name) {
+                return malformed("duplicate critical header parameter");
+            }
+        }
+    }
+
+    Ok(JwsHeader {
+        alg: raw_header.alg.parse()?,
+        kid: raw_header.kid,
+        typ: raw_header.typ,
+        crit: raw_header.crit,
+        params: raw_header.params,
+    })
+}
+
+/// Decode the [`JwsHeader`] of a compact serialized JWS,
+/// without verifying its signature.
+///
+/// This can be used to select the key to verify the JWS with,
+/// e.g. using its key id (`kid`). The header cannot be trusted
+/// until the JWS is verified using [`verify_compact`].
+pub fn decode_header(token: &str) -> Result<JwsHeader, JwsError> {
+    let (header, _, _) = split_compact(token)?;
+    parse_header(header)
+}
+
+/// Verify a compact serialized JWS using the given [`Jwk`].
+///
+/// The `alg` of the JWS has to match the [algorithm](Jwk::algorithm) of the key,
+/// in order to prevent algorithm confusion attacks.
+///
+/// No critical (`crit`) extension header parameters are understood,
+/// use [`verify_compact_with_critical`] to accept these.
+pub fn verify_compact(token: &str, key: &Jwk) -> Result<VerifiedJws, JwsError> {
+    verify_compact_with_critical(token, key, &[])
+}
+
+/// Verify a compact serialized JWS using the given [`Jwk`],
+/// understanding the given critical (`crit`) extension header parameters.
+///
+/// As required by [RFC 7515, section 4.1.11], the JWS is rejected with
+/// [`JwsError::UnsupportedCritical`] in case it lists a critical extension
+/// header parameter which is not part of `critical`. It is up to the caller
+/// to process the understood parameters, available via [`JwsHeader::param`].
+///
+/// [RFC 7515, section 4.1.11]: https://datatracker.ietf.org/doc/html/rfc7515#section-4.1.11
+pub fn verify_compact_with_critical(
+    token: &str,
+    key: &Jwk,
+    critical: &[&str],
+) -> Result<VerifiedJws, JwsError> {
+    let (header, payload, signature) = split_compact(token)?;
+    let parsed_header = parse_header(header)?;
+    if let Some(name) = parsed_header
+        .crit()
+        .unwrap_or_default()
+        .iter()
+        .find(|name| !critical.contains(&name.as_str()))
+    {
+        return Err(JwsError::UnsupportedCritical(name.clone()));
+    }
+
+    let signature = b64::decode(signature)
+        .context("decode signature")
+        .map_err(JwsError::Malformed)?;
+    let signing_input_len = header.len() + 1 + payload.len();
+    key.verify(
+        parsed_header.alg,
+        &token.as_bytes()[..signing_input_len],
+        &signature,
+    )?;
+
+    let payload = b64::decode(payload)
+        .context("decode payload")
+        .map_err(JwsError::Malformed)?;
+
+    Ok(VerifiedJws {
+        header: parsed_header,
+        payload,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // test vectors from RFC 8037, appendix A
+    const RFC8037_D: &str = "nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A";
+    const RFC8037_X: &str = "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo";
+    const RFC8037_PAYLOAD: &[u8] = b"Example of Ed25519 signing";
+    const RFC8037_JWS: &str = "eyJhbGciOiJFZERTQSJ9.RXhhbXBsZSBvZiBFZDI1NTE5IHNpZ25pbmc.hgyY0il_MGCjP0JzlnLWG1PPOt7-09PGcvMg3AIbQR6dWbhijcNR4ki4iylGjg5BhVsPt9g7sVvpAr_MuM0KAg";
+
+    fn rfc8037_key() -> Ed25519SigningKey {
+        Ed25519SigningKey::from_seed(&b64::decode(RFC8037_D).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_sign_rfc8037() {
+        let key = rfc8037_key();
+        assert_eq!(b64::encode(key.public_key()), RFC8037_X);
+
+        let jws = sign_compact(&key, JwsHeader::new(JwsAlgorithm::EdDSA), RFC8037_PAYLOAD).unwrap();
+        assert_eq!(jws, RFC8037_JWS);
+    }
+
+    #[test]
+    fn test_verify_rfc8037() {
+        let jwk = Jwk::ed25519(b64::decode(RFC8037_X).unwrap());
+        let verified = verify_compact(RFC8037_JWS, &jwk).unwrap();
+        assert_eq!(verified.header().alg(), JwsAlgorithm::EdDSA);
+        assert_eq!(verified.payload(), RFC8037_PAYLOAD);
+    }
+
+    #[test]
+    fn test_sign_and_verify_roundtrip() {
+        let key = Ed25519SigningKey::generate().unwrap();
+        let header = JwsHeader::new(JwsAlgorithm::EdDSA)
+            .with_kid("test")
+            .with_typ("JWT");
+        let jws = sign_compact(&key, header.clone(), b"hello").unwrap();
+
+        let verified = verify_compact(&jws, &key.public_jwk()).unwrap();
+        assert_eq!(verified.header(), &header);
+        assert_eq!(verified.into_payload(), b"hello");
+
+        let other_key = Ed25519SigningKey::generate().unwrap();
+        assert!(matches!(
+            verify_compact(&jws, &other_key.public_jwk()),
+            Err(JwsError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_verify_rejects_other_algorithms() {
+        let jwk = Jwk::ed25519(b64::decode(RFC8037_X).unwrap());
+        let (_, rest) = RFC8037_JWS.split_once('.').unwrap();
+
+        for alg in ["none", "HS256", "RS256"] {
+            let header = b64::encode(format!(r#"{{"alg":"{alg}"}}"#));
+            let jws = format!("{header}.{rest}");
+            match verify_compact(&jws, &jwk) {
+                Err(JwsError::UnsupportedAlgorithm(found)) => assert_eq!(found, alg),
+                result => panic!("unexpected result for alg {alg}: {result:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_malformed() {
+        let jwk = Jwk::ed25519(b64::decode(RFC8037_X).unwrap());
+        for jws in [
+            "",
+            "a.b",
+            "a.b.c.d",
+            "!!.b.c",
+            "eyJhbGciOiJFZERTQSJ9.RXhh.!!",
+        ] {
+            assert!(
+                matches!(verify_compact(jws, &jwk), Err(JwsError::Malformed(_))),
+                "{jws}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_critical() {
+        let key = Ed25519SigningKey::generate().unwrap();
+        let header = JwsHeader::new(JwsAlgorithm::EdDSA)
+            .with_pushed_crit("http://example.com/ext")
+            .with_param("http://example.com/ext", true);
+        let jws = sign_compact(&key, header.clone(), b"hello").unwrap();
+
+        match verify_compact(&jws, &key.public_jwk()) {
+            Err(JwsError::UnsupportedCritical(name)) => assert_eq!(name, "http://example.com/ext"),
+            result => panic!("unexpected result: {result:?}"),
+        }
+        assert!(matches!(
+            verify_compact_with_critical(&jws, &key.public_jwk(), &["other"]),
+            Err(JwsError::UnsupportedCritical(_))
+        ));
+
+        let verified =
+            verify_compact_with_critical(&jws, &key.public_jwk(), &["http://example.com/ext"])
+                .unwrap();
+        assert_eq!(verified.header(), &header);
+        assert_eq!(
+            verified.header().param("http://example.com/ext"),
+            Some(&serde_json::Value::Bool(true))
+        );
+        assert_eq!(verified.payload(), b"hello");
+    }
+
+    #[test]
+    fn test_verify_malformed_critical() {
+        let key = Ed25519SigningKey::generate().unwrap();
+        for header in [
+            // empty list
+            JwsHeader::new(JwsAlgorithm::EdDSA).with_param("crit", serde_json::json!([])),
+            // registered header parameter
+            JwsHeader::new(JwsAlgorithm::EdDSA)
+                .with_kid("a")
+                .with_pushed_crit("kid"),
+            // missing from header
+            JwsHeader::new(JwsAlgorithm::EdDSA).with_pushed_crit("ext"),
+            // duplicate
+            JwsHeader::new(JwsAlgorithm::EdDSA)
+                .with_pushed_crit("ext")
+                .with_pushed_crit("ext")
+                .with_param("ext", 1),
+        ] {
+            let jws = sign_compact(&key, header, b"hello").unwrap();
+            assert!(
+                matches!(
+                    verify_compact_with_critical(&jws, &key.public_jwk(), &["ext", "kid"]),
+                    Err(JwsError::Malformed(_))
+                ),
+                "{jws}"
+            );
+        }
+    }
+}
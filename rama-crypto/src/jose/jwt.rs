@@ -0,0 +1,778 @@
+use super::{decode_header, verify_compact_with_critical, JwkSet, JwsError};
+use rama_core::error::{ErrorContext, OpaqueError};
+use serde::{
+    de::{self, DeserializeOwned},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+/// The audience (`aud`) claim of a JWT,
+/// which can be either a single or multiple audiences.
+pub enum Audience {
+    /// A single audience.
+    Single(String),
+    /// Multiple audiences.
+    Multiple(Vec<String>),
+}
+
+impl Audience {
+    /// Returns `true` if the given audience is part of this [`Audience`].
+    pub fn contains(&self, audience: &str) -> bool {
+        match self {
+            Self::Single(aud) => aud == audience,
+            Self::Multiple(auds) => auds.iter().any(|aud| aud == audience),
+        }
+    }
+}
+
+impl From<String> for Audience {
+    fn from(audience: String) -> Self {
+        Self::Single(audience)
+    }
+}
+
+impl From<&str> for Audience {
+    fn from(audience: &str) -> Self {
+        Self::Single(audience.to_owned())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A `NumericDate` as defined in [RFC 7519, section 2]:
+/// the time since the unix epoch, in seconds.
+///
+/// The RFC allows non-integer values (e.g. `1700000000.5`),
+/// which is why the sub-second precision is preserved.
+///
+/// Deserialized dates are guaranteed to be representable as a [`SystemTime`],
+/// dates which are too far in the future are rejected as invalid.
+///
+/// [RFC 7519, section 2]: https://datatracker.ietf.org/doc/html/rfc7519#section-2
+pub struct NumericDate(Duration);
+
+impl NumericDate {
+    /// Create a [`NumericDate`] from the given (integer) seconds since the unix epoch.
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(Duration::from_secs(secs))
+    }
+
+    /// Create a [`NumericDate`] from the given time since the unix epoch.
+    pub const fn from_duration(duration: Duration) -> Self {
+        Self(duration)
+    }
+
+    /// Return the time since the unix epoch of this [`NumericDate`].
+    pub const fn as_duration(&self) -> Duration {
+        self.0
+    }
+
+    /// Return the whole seconds since the unix epoch of this [`NumericDate`].
+    pub const fn as_secs(&self) -> u64 {
+        self.0.as_secs()
+    }
+
+    /// Return this [`NumericDate`] as a [`SystemTime`],
+    /// or `None` in case it cannot be represented as one.
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        SystemTime::UNIX_EPOCH.checked_add(self.0)
+    }
+}
+
+impl From<u64> for NumericDate {
+    fn from(secs: u64) -> Self {
+        Self::from_secs(secs)
+    }
+}
+
+impl From<SystemTime> for NumericDate {
+    fn from(time: SystemTime) -> Self {
+        Self(
+            time.duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default(),
+        )
+    }
+}
+
+impl TryFrom<NumericDate> for SystemTime {
+    type Error = OpaqueError;
+
+    fn try_from(date: NumericDate) -> Result<Self, Self::Error> {
+        date.to_system_time()
+            .context("NumericDate out of range of SystemTime")
+    }
+}
+
+impl Serialize for NumericDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0.subsec_nanos() == 0 {
+            serializer.serialize_u64(self.0.as_secs())
+        } else {
+            serializer.serialize_f64(self.0.as_secs_f64())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for NumericDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = NumericDate;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a non-negative number of seconds since the unix epoch")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Some(NumericDate::from_secs(v))
+                    .filter(|date| date.to_system_time().is_some())
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                u64::try_from(v)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+                    .and_then(|v| self.visit_u64(v))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                Duration::try_from_secs_f64(v)
+                    .ok()
+                    .map(NumericDate)
+                    .filter(|date| date.to_system_time().is_some())
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Float(v), &self))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// The registered claims of a JWT, as defined in [RFC 7519, section 4.1].
+///
+/// All times are expressed as a [`NumericDate`], the seconds since the unix epoch.
+///
+/// [RFC 7519, section 4.1]: https://datatracker.ietf.org/doc/html/rfc7519#section-4.1
+pub struct RegisteredClaims {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The issuer (`iss`) of the JWT.
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The subject (`sub`) of the JWT.
+    pub sub: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The audience (`aud`) of the JWT.
+    pub aud: Option<Audience>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The expiration time (`exp`) of the JWT.
+    pub exp: Option<NumericDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The time before which the JWT is not valid (`nbf`).
+    pub nbf: Option<NumericDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The time at which the JWT was issued (`iat`).
+    pub iat: Option<NumericDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The unique identifier (`jti`) of the JWT.
+    pub jti: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// The claims of a JWT, validated by a [`JwtValidator`].
+///
+/// The private claims are by default kept as a json object,
+/// but can also be deserialized in a type of your choice.
+pub struct JwtClaims<C = serde_json::Map<String, serde_json::Value>> {
+    #[serde(flatten)]
+    /// The registered claims of the JWT.
+    pub registered: RegisteredClaims,
+    #[serde(flatten)]
+    /// The private claims of the JWT.
+    pub private: C,
+}
+
+#[derive(Debug)]
+/// Error returned by [`JwtValidator`] in case a JWT is invalid.
+pub enum JwtValidationError {
+    /// The JWT is not signed (`alg: none`), which is never accepted.
+    AlgNone,
+    /// No key is known to verify the JWT with.
+    UnknownKey,
+    /// The JWS of the JWT is invalid, e.g. because the signature is invalid.
+    Jws(JwsError),
+    /// The claims of the JWT could not be deserialized.
+    InvalidClaims(OpaqueError),
+    /// A claim required by the [`JwtValidator`] is missing.
+    MissingClaim(&'static str),
+    /// The JWT is expired (`exp`).
+    Expired,
+    /// The JWT is not valid yet (`nbf`).
+    NotYetValid,
+    /// The JWT is issued in the future (`iat`).
+    IssuedInFuture,
+    /// The issuer (`iss`) of the JWT is not the expected one.
+    InvalidIssuer,
+    /// The audience (`aud`) of the JWT does not contain the expected one.
+    InvalidAudience,
+}
+
+impl JwtValidationError {
+    /// A short, static description of the error,
+    /// which is safe to expose to clients.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::AlgNone => "unsigned token",
+            Self::UnknownKey => "unknown signing key",
+            Self::Jws(_) => "invalid signature",
+            Self::InvalidClaims(_) => "invalid claims",
+            Self::MissingClaim(_) => "missing claim",
+            Self::Expired => "token expired",
+            Self::NotYetValid => "token not valid yet",
+            Self::IssuedInFuture => "token issued in the future",
+            Self::InvalidIssuer => "invalid issuer",
+            Self::InvalidAudience => "invalid audience",
+        }
+    }
+}
+
+impl fmt::Display for JwtValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Jws(err) => write!(f, "invalid jwt: {err}"),
+            Self::InvalidClaims(err) => write!(f, "invalid jwt: invalid claims: {err}"),
+            Self::MissingClaim(claim) => write!(f, "invalid jwt: missing claim: {claim}"),
+            err => write!(f, "invalid jwt: {}", err.description()),
+        }
+    }
+}
+
+impl std::error::Error for JwtValidationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Jws(err) => Some(err),
+            Self::InvalidClaims(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<JwsError> for JwtValidationError {
+    fn from(err: JwsError) -> Self {
+        match err {
+            JwsError::UnsupportedAlgorithm(alg) if alg.eq_ignore_ascii_case("none") => {
+                Self::AlgNone
+            }
+            err => Self::Jws(err),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Validator of JWTs, as defined in [RFC 7519].
+///
+/// A JWT is valid if its signature can be verified using one of the keys
+/// of the validator and its registered claims are valid:
+///
+/// - `exp` and `nbf` are validated against the current time,
+///   allowing for a configurable leeway (60 seconds by default);
+/// - `iat` is not allowed to be in the future (respecting the same leeway);
+/// - `iss` and `aud` are validated if an issuer or audience is configured.
+///
+/// Unsigned tokens (`alg: none`) are always rejected.
+///
+/// [RFC 7519]: https://datatracker.ietf.org/doc/html/rfc7519
+pub struct JwtValidator {
+    keys: JwkSet,
+    leeway: Duration,
+    issuer: Option<String>,
+    audience: Option<String>,
+    require_exp: bool,
+    critical: Vec<String>,
+}
+
+impl JwtValidator {
+    /// Create a new [`JwtValidator`] verifying JWTs using the given key(s).
+    ///
+    /// In case the JWT defines a key id (`kid`), only the key with that id is used.
+    pub fn new(keys: impl Into<JwkSet>) -> Self {
+        Self {
+            keys: keys.into(),
+            leeway: Duration::from_secs(60),
+            issuer: None,
+            audience: None,
+            require_exp: true,
+            critical: Vec::new(),
+        }
+    }
+
+    /// Set the leeway allowed when validating the time based claims.
+    pub fn with_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    /// Set the leeway allowed when validating the time based claims.
+    pub fn set_leeway(&mut self, leeway: Duration) -> &mut Self {
+        self.leeway = leeway;
+        self
+    }
+
+    /// Require the JWT to be issued (`iss`) by the given issuer.
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Require the JWT to be issued (`iss`) by the given issuer.
+    pub fn set_issuer(&mut self, issuer: impl Into<String>) -> &mut Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Require the JWT to be intended for (`aud`) the given audience.
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Require the JWT to be intended for (`aud`) the given audience.
+    pub fn set_audience(&mut self, audience: impl Into<String>) -> &mut Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Define whether the JWT is required to have an expiration time (`exp`),
+    /// which is the case by default.
+    pub fn with_require_exp(mut self, require: bool) -> Self {
+        self.require_exp = require;
+        self
+    }
+
+    /// Define whether the JWT is required to have an expiration time (`exp`),
+    /// which is the case by default.
+    pub fn set_require_exp(&mut self, require: bool) -> &mut Self {
+        self.require_exp = require;
+        self
+    }
+
+    /// Understand the given critical (`crit`) extension header parameter,
+    /// JWTs listing any other critical parameter are rejected.
+    ///
+    /// Multiple parameters can be pushed, all of which are understood.
+    pub fn with_pushed_critical(mut self, name: impl Into<String>) -> Self {
+        self.critical.push(name.into());
+        self
+    }
+
+    /// Understand the given critical (`crit`) extension header parameter,
+    /// JWTs listing any other critical parameter are rejected.
+    ///
+    /// Multiple parameters can be pushed, all of which are understood.
+    pub fn push_critical(&mut self, name: impl Into<String>) -> &mut Self {
+        self.critical.push(name.into());
+        self
+    }
+
+    /// Validate the given JWT at the current time, returning its claims if valid.
+    pub fn validate<C>(&self, token: &str) -> Result<JwtClaims<C>, JwtValidationError>
+    where
+        C: DeserializeOwned,
+    {
+        self.validate_at(token, SystemTime::now())
+    }
+
+    /// Validate the given JWT at the given time, returning its claims if valid.
+    pub fn validate_at<C>(
+        &self,
+        token: &str,
+        time: SystemTime,
+    ) -> Result<JwtClaims<C>, JwtValidationError>
+    where
+        C: DeserializeOwned,
+    {
+        let header = decode_header(token)?;
+        let critical: Vec<&str> = self.critical.iter().map(String::as_str).collect();
+        let verify_compact = |token, key| verify_compact_with_critical(token, key, &critical);
+        let verified = match header.kid() {
+            Some(kid) => {
+                let key = self.keys.find(kid).ok_or(JwtValidationError::UnknownKey)?;
+                verify_compact(token, key)?
+            }
+            None => {
+                let mut keys = self.keys.keys().iter();
+                let key = keys.next().ok_or(JwtValidationError::UnknownKey)?;
+                let mut result = verify_compact(token, key);
+                for key in keys {
+                    if result.is_ok() {
+                        break;
+                    }
+                    result = verify_compact(token, key);
+                }
+                result?
+            }
+        };
+
+        let claims: JwtClaims<C> = serde_json::from_slice(verified.payload())
+            .context("deserialize jwt claims")
+            .map_err(JwtValidationError::InvalidClaims)?;
+        self.validate_registered_claims(&claims.registered, time)?;
+
+        Ok(claims)
+    }
+
+    fn validate_registered_claims(
+        &self,
+        claims: &RegisteredClaims,
+        time: SystemTime,
+    ) -> Result<(), JwtValidationError> {
+        let now = NumericDate::from(time).as_duration();
+        let leeway = self.leeway;
+
+        match claims.exp {
+            Some(exp) if exp.as_duration().saturating_add(leeway) <= now => {
+                return Err(JwtValidationError::Expired)
+            }
+            None if self.require_exp => return Err(JwtValidationError::MissingClaim("exp")),
+            _ => (),
+        }
+        if claims
+            .nbf
+            .is_some_and(|nbf| nbf.as_duration() > now.saturating_add(leeway))
+        {
+            return Err(JwtValidationError::NotYetValid);
+        }
+        if claims
+            .iat
+            .is_some_and(|iat| iat.as_duration() > now.saturating_add(leeway))
+        {
+            return Err(JwtValidationError::IssuedInFuture);
+        }
+
+        if let Some(issuer) = &self.issuer {
+            match &claims.iss {
+                Some(iss) if iss == issuer => (),
+                Some(_) => return Err(JwtValidationError::InvalidIssuer),
+                None => return Err(JwtValidationError::MissingClaim("iss")),
+            }
+        }
+
+        if let Some(audience) = &self.audience {
+            match &claims.aud {
+                Some(aud) if aud.contains(audience) => (),
+                Some(_) => return Err(JwtValidationError::InvalidAudience),
+                None => return Err(JwtValidationError::MissingClaim("aud")),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jose::{b64, sign_compact, Ed25519SigningKey, JwsAlgorithm, JwsHeader};
+    use serde_json::json;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn now() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(NOW)
+    }
+
+    fn sign(key: &Ed25519SigningKey, claims: serde_json::Value) -> String {
+        sign_compact(
+            key,
+            JwsHeader::new(JwsAlgorithm::EdDSA).with_typ("JWT"),
+            &serde_json::to_vec(&claims).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn validator(key: &Ed25519SigningKey) -> JwtValidator {
+        JwtValidator::new(key.public_jwk())
+            .with_issuer("https://issuer.example.com")
+            .with_audience("rama")
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Custom {
+        scope: String,
+    }
+
+    #[test]
+    fn test_validate_valid_token() {
+        let key = Ed25519SigningKey::generate().unwrap();
+        let token = sign(
+            &key,
+            json!({
+                "iss": "https://issuer.example.com",
+                "sub": "alice",
+                "aud": ["other", "rama"],
+                "exp": NOW + 300,
+                "nbf": NOW - 10,
+                "iat": NOW - 10,
+                "scope": "read write",
+            }),
+        );
+
+        let claims: JwtClaims<Custom> = validator(&key).validate_at(&token, now()).unwrap();
+        assert_eq!(claims.registered.sub.as_deref(), Some("alice"));
+        assert_eq!(
+            claims.registered.exp,
+            Some(NumericDate::from_secs(NOW + 300))
+        );
+        assert_eq!(claims.private.scope, "read write");
+
+        let claims: JwtClaims = validator(&key).validate_at(&token, now()).unwrap();
+        assert_eq!(claims.private["scope"], "read write");
+    }
+
+    #[test]
+    fn test_validate_expired_token() {
+        let key = Ed25519SigningKey::generate().unwrap();
+        let token = sign(
+            &key,
+            json!({
+                "iss": "https://issuer.example.com",
+                "aud": "rama",
+                "exp": NOW - 30,
+            }),
+        );
+
+        // within the default leeway
+        assert!(validator(&key)
+            .validate_at::<serde_json::Value>(&token, now())
+            .is_ok());
+
+        let err = validator(&key)
+            .with_leeway(Duration::from_secs(10))
+            .validate_at::<serde_json::Value>(&token, now())
+            .unwrap_err();
+        assert!(matches!(err, JwtValidationError::Expired), "{err}");
+    }
+
+    #[test]
+    fn test_validate_fractional_numeric_date() {
+        let key = Ed25519SigningKey::generate().unwrap();
+        let token = sign(
+            &key,
+            json!({
+                "iss": "https://issuer.example.com",
+                "aud": "rama",
+                "exp": NOW as f64 + 0.5,
+                "iat": NOW as f64 - 10.25,
+            }),
+        );
+
+        let validator = validator(&key).with_leeway(Duration::ZERO);
+        let claims: JwtClaims = validator.validate_at(&token, now()).unwrap();
+        assert_eq!(
+            claims.registered.exp,
+            Some(NumericDate::from_duration(Duration::from_millis(
+                NOW * 1000 + 500
+            )))
+        );
+
+        let err = validator
+            .validate_at::<serde_json::Value>(&token, now() + Duration::from_millis(500))
+            .unwrap_err();
+        assert!(matches!(err, JwtValidationError::Expired), "{err}");
+    }
+
+    #[test]
+    fn test_numeric_date_serde() {
+        for (value, expected) in [
+            (json!(NOW), NumericDate::from_secs(NOW)),
+            (
+                json!(1.5),
+                NumericDate::from_duration(Duration::from_millis(1500)),
+            ),
+        ] {
+            let date: NumericDate = serde_json::from_value(value.clone()).unwrap();
+            assert_eq!(date, expected);
+            assert_eq!(serde_json::to_value(date).unwrap(), value);
+        }
+
+        for value in [
+            json!(-1),
+            json!(-1.5),
+            json!("1700000000"),
+            json!(u64::MAX),
+            json!(1e300),
+        ] {
+            assert!(
+                serde_json::from_value::<NumericDate>(value.clone()).is_err(),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_numeric_date_to_system_time() {
+        assert_eq!(
+            SystemTime::try_from(NumericDate::from_secs(NOW)).unwrap(),
+            now()
+        );
+        assert!(NumericDate::from_secs(u64::MAX).to_system_time().is_none());
+        assert!(SystemTime::try_from(NumericDate::from_secs(u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_validate_huge_exp_is_invalid() {
+        let key = Ed25519SigningKey::generate().unwrap();
+        for exp in [json!(u64::MAX), json!(1e300)] {
+            let token = sign(
+                &key,
+                json!({
+                    "iss": "https://issuer.example.com",
+                    "aud": "rama",
+                    "exp": exp,
+                }),
+            );
+            let err = validator(&key)
+                .validate_at::<serde_json::Value>(&token, now())
+                .unwrap_err();
+            assert!(matches!(err, JwtValidationError::InvalidClaims(_)), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_validate_wrong_audience() {
+        let key = Ed25519SigningKey::generate().unwrap();
+        let token = sign(
+            &key,
+            json!({
+                "iss": "https://issuer.example.com",
+                "aud": "other",
+                "exp": NOW + 300,
+            }),
+        );
+
+        let err = validator(&key)
+            .validate_at::<serde_json::Value>(&token, now())
+            .unwrap_err();
+        assert!(matches!(err, JwtValidationError::InvalidAudience), "{err}");
+    }
+
+    #[test]
+    fn test_validate_claim_errors() {
+        let key = Ed25519SigningKey::generate().unwrap();
+        for (claims, expected) in [
+            (
+                json!({"iss": "https://issuer.example.com", "aud": "rama"}),
+                "missing claim: exp",
+            ),
+            (
+                json!({"iss": "https://evil.example.com", "aud": "rama", "exp": NOW + 300}),
+                "invalid issuer",
+            ),
+            (
+                json!({"iss": "https://issuer.example.com", "aud": "rama", "exp": NOW + 300, "nbf": NOW + 120}),
+                "token not valid yet",
+            ),
+            (
+                json!({"iss": "https://issuer.example.com", "aud": "rama", "exp": NOW + 300, "iat": NOW + 120}),
+                "token issued in the future",
+            ),
+            (
+                json!({"iss": "https://issuer.example.com", "exp": NOW + 300}),
+                "missing claim: aud",
+            ),
+        ] {
+            let token = sign(&key, claims);
+            let err = validator(&key)
+                .validate_at::<serde_json::Value>(&token, now())
+                .unwrap_err();
+            assert!(err.to_string().ends_with(expected), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_alg_none() {
+        let key = Ed25519SigningKey::generate().unwrap();
+        let token = format!(
+            "{}.{}.",
+            b64::encode(r#"{"alg":"none"}"#),
+            b64::encode(
+                json!({"iss": "https://issuer.example.com", "aud": "rama", "exp": NOW + 300})
+                    .to_string()
+            ),
+        );
+        let err = validator(&key)
+            .validate_at::<serde_json::Value>(&token, now())
+            .unwrap_err();
+        assert!(matches!(err, JwtValidationError::AlgNone), "{err}");
+    }
+
+    #[test]
+    fn test_validate_key_selection() {
+        let key_a = Ed25519SigningKey::generate().unwrap();
+        let key_b = Ed25519SigningKey::generate().unwrap();
+        let validator = JwtValidator::new(JwkSet::new([
+            key_a.public_jwk().with_kid("a"),
+            key_b.public_jwk().with_kid("b"),
+        ]));
+        let claims = json!({"exp": NOW + 300});
+
+        // without kid all keys are tried
+        let token = sign(&key_b, claims.clone());
+        assert!(validator
+            .validate_at::<serde_json::Value>(&token, now())
+            .is_ok());
+
+        // with kid only the matching key is used
+        let token = sign_compact(
+            &key_b,
+            JwsHeader::new(JwsAlgorithm::EdDSA).with_kid("a"),
+            claims.to_string().as_bytes(),
+        )
+        .unwrap();
+        assert!(matches!(
+            validator.validate_at::<serde_json::Value>(&token, now()),
+            Err(JwtValidationError::Jws(JwsError::InvalidSignature))
+        ));
+
+        let token = sign_compact(
+            &key_b,
+            JwsHeader::new(JwsAlgorithm::EdDSA).with_kid("c"),
+            claims.to_string().as_bytes(),
+        )
+        .unwrap();
+        assert!(matches!(
+            validator.validate_at::<serde_json::Value>(&token, now()),
+            Err(JwtValidationError::UnknownKey)
+        ));
+    }
+
+    #[test]
+    fn test_validate_critical() {
+        let key = Ed25519SigningKey::generate().unwrap();
+        let claims = json!({ "exp": NOW + 300 });
+        let token = sign_compact(
+            &key,
+            JwsHeader::new(JwsAlgorithm::EdDSA)
+                .with_pushed_crit("ext")
+                .with_param("ext", "value"),
+            claims.to_string().as_bytes(),
+        )
+        .unwrap();
+
+        let validator = JwtValidator::new(key.public_jwk());
+        assert!(matches!(
+            validator.validate_at::<serde_json::Value>(&token, now()),
+            Err(JwtValidationError::Jws(JwsError::UnsupportedCritical(_)))
+        ));
+
+        let validator = validator.with_pushed_critical("ext");
+        assert!(validator
+            .validate_at::<serde_json::Value>(&token, now())
+            .is_ok());
+    }
+}
@@ -0,0 +1,55 @@
+//! JOSE (JSON Object Signing and Encryption) support.
+//!
+//! - [`Jwk`]: JSON Web Key, as defined in [RFC 7517];
+//! - [`sign_compact`] and [`verify_compact`]: JSON Web Signatures (JWS)
+//!   in compact serialization, as defined in [RFC 7515];
+//! - [`JwtValidator`]: JSON Web Token (JWT) validation, as defined in [RFC 7519].
+//!
+//! The only algorithm supported for now is [`JwsAlgorithm::EdDSA`],
+//! using Ed25519 keys as defined in [RFC 8037].
+//!
+//! [RFC 7517]: https://datatracker.ietf.org/doc/html/rfc7517
+//! [RFC 7515]: https://datatracker.ietf.org/doc/html/rfc7515
+//! [RFC 7519]: https://datatracker.ietf.org/doc/html/rfc7519
+//! [RFC 8037]: https://datatracker.ietf.org/doc/html/rfc8037
+
+mod jwk;
+#[doc(inline)]
+pub use jwk::{Jwk, JwkKey, JwkSet, OkpCurve};
+
+mod jws;
+#[doc(inline)]
+pub use jws::{
+    decode_header, sign_compact, verify_compact, verify_compact_with_critical, Ed25519SigningKey,
+    JwsAlgorithm, JwsError, JwsHeader, JwsSigner, VerifiedJws,
+};
+
+mod jwt;
+#[doc(inline)]
+pub use jwt::{
+    Audience, JwtClaims, JwtValidationError, JwtValidator, NumericDate, RegisteredClaims,
+};
+
+mod b64 {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn encode(input: impl AsRef<[u8]>) -> String {
+        URL_SAFE_NO_PAD.encode(input)
+    }
+
+    pub(super) fn decode(input: impl AsRef<[u8]>) -> Result<Vec<u8>, base64::DecodeError> {
+        URL_SAFE_NO_PAD.decode(input)
+    }
+
+    pub(super) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode(bytes))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        decode(s.as_bytes()).map_err(serde::de::Error::custom)
+    }
+}
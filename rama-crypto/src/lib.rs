@@ -0,0 +1,61 @@
+//! Cryptographic support for rama.
+//!
+//! At the moment this crate provides support for JOSE
+//! (JSON Object Signing and Encryption), see the [`jose`] module,
+//! HMAC (keyed-hash message authentication code), see the [`hmac`] module,
+//! SHA-256 digests, see the [`digest`] module,
+//! and X.509 certificate chain validation, see the [`x509`] module.
+//!
+//! # Rama
+//!
+//! Crate used by the end-user `rama` crate and `rama` crate authors alike.
+//!
+//! Learn more about `rama`:
+//!
+//! - Github: <https://github.com/plabayo/rama>
+//! - Book: <https://ramaproxy.org/book/>
+
+#![doc(
+    html_favicon_url = "https://raw.githubusercontent.com/plabayo/rama/main/docs/img/old_logo.png"
+)]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/plabayo/rama/main/docs/img/old_logo.png")]
+#![cfg_attr(docsrs, feature(doc_auto_cfg, doc_cfg))]
+#![cfg_attr(not(test), warn(clippy::print_stdout, clippy::dbg_macro))]
+
+pub mod digest;
+pub mod hmac;
+pub mod jose;
+pub mod x509;
+
+pub mod dep {
+    //! Dependencies for rama crypto modules.
+    //!
+    //! Exported for your convenience.
+
+    pub mod pki_types {
+        //! Re-export of the [`pki-types`] crate.
+        //!
+        //! [`pki-types`]: https://docs.rs/rustls-pki-types
+
+        #[doc(inline)]
+        pub use rustls_pki_types::*;
+    }
+
+    pub mod webpki {
+        //! Re-export of the [`rustls-webpki`] crate.
+        //!
+        //! [`rustls-webpki`]: https://docs.rs/rustls-webpki
+
+        #[doc(inline)]
+        pub use webpki::*;
+    }
+
+    pub mod x509_parser {
+        //! Re-export of the [`x509-parser`] crate.
+        //!
+        //! [`x509-parser`]: https://docs.rs/x509-parser
+
+        #[doc(inline)]
+        pub use x509_parser::*;
+    }
+}
@@ -0,0 +1,527 @@
+//! X.509 certificate chain building and validation.
+//!
+//! [`CertificateChainVerifier`] validates a leaf certificate
+//! against a set of intermediates and trusted roots, returning the built chain
+//! together with the [`CertificateInfo`] of the leaf, such as its
+//! subject alternative names, validity window and key usage.
+//!
+//! It can be used by tls acceptors and ACME flows alike to validate
+//! the certificates they are about to serve or that they received.
+//!
+//! Path building and signature verification is done by [`webpki`],
+//! using the [`SignatureVerificationAlgorithm`]s given by the caller,
+//! e.g. those of the configured rustls `CryptoProvider`.
+//! The certificates themselves are parsed using [`x509_parser`].
+//!
+//! [`webpki`]: crate::dep::webpki
+//! [`x509_parser`]: crate::dep::x509_parser
+
+use crate::dep::pki_types::{
+    CertificateDer, ServerName, SignatureVerificationAlgorithm, TrustAnchor, UnixTime,
+};
+use rama_core::error::{ErrorClass, ErrorContext, ErrorKindClass, OpaqueError};
+use std::{
+    fmt,
+    net::IpAddr,
+    time::{Duration, SystemTime},
+};
+use x509_parser::extensions::GeneralName;
+
+#[derive(Debug)]
+/// Error returned by [`CertificateChainVerifier`] in case
+/// the certificate chain could not be validated.
+pub enum CertificateChainError {
+    /// The leaf (or another certificate in the chain) is expired.
+    Expired,
+    /// The leaf (or another certificate in the chain) is not valid yet.
+    NotYetValid,
+    /// The leaf certificate is not valid for the requested name.
+    NameMismatch,
+    /// No chain could be built from the leaf to one of the trusted roots.
+    UnknownIssuer,
+    /// The certificate chain is invalid for another reason.
+    Invalid(OpaqueError),
+}
+
+impl fmt::Display for CertificateChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Expired => write!(f, "certificate is expired"),
+            Self::NotYetValid => write!(f, "certificate is not valid yet"),
+            Self::NameMismatch => write!(f, "certificate is not valid for name"),
+            Self::UnknownIssuer => write!(f, "certificate is issued by an unknown issuer"),
+            Self::Invalid(err) => write!(f, "invalid certificate chain: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CertificateChainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Invalid(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl ErrorKindClass for CertificateChainError {
+    fn error_class(&self) -> Option<ErrorClass> {
+        Some(ErrorClass::Tls)
+    }
+}
+
+impl From<webpki::Error> for CertificateChainError {
+    fn from(err: webpki::Error) -> Self {
+        match err {
+            webpki::Error::CertExpired => Self::Expired,
+            webpki::Error::CertNotValidYet => Self::NotYetValid,
+            webpki::Error::CertNotValidForName => Self::NameMismatch,
+            webpki::Error::UnknownIssuer => Self::UnknownIssuer,
+            err => Self::Invalid(OpaqueError::from_std(err)),
+        }
+    }
+}
+
+/// Validates certificate chains against a set of trusted roots.
+///
+/// Chains are validated for server authentication purposes,
+/// using the given [`SignatureVerificationAlgorithm`]s.
+pub struct CertificateChainVerifier {
+    roots: Vec<CertificateDer<'static>>,
+    anchors: Vec<TrustAnchor<'static>>,
+    algorithms: &'static [&'static dyn SignatureVerificationAlgorithm],
+}
+
+impl fmt::Debug for CertificateChainVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CertificateChainVerifier")
+            .field("roots", &self.roots.len())
+            .field("algorithms", &self.algorithms)
+            .finish()
+    }
+}
+
+impl CertificateChainVerifier {
+    /// Create a new [`CertificateChainVerifier`] trusting the given root certificates,
+    /// verifying signatures using the given algorithms.
+    ///
+    /// When used together with rustls, the algorithms are typically
+    /// the `signature_verification_algorithms.all` of its `CryptoProvider`.
+    pub fn new(
+        roots: impl IntoIterator<Item = CertificateDer<'static>>,
+        algorithms: &'static [&'static dyn SignatureVerificationAlgorithm],
+    ) -> Result<Self, OpaqueError> {
+        let roots: Vec<_> = roots.into_iter().collect();
+        let anchors = roots
+            .iter()
+            .map(|root| {
+                webpki::anchor_from_trusted_cert(root)
+                    .map(|anchor| anchor.to_owned())
+                    .map_err(OpaqueError::from_std)
+                    .context("create trust anchor from root certificate")
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            roots,
+            anchors,
+            algorithms,
+        })
+    }
+
+    /// Validate the `leaf` certificate at the current time, building a chain
+    /// to one of the trusted roots using the given `intermediates`.
+    ///
+    /// The leaf is also validated for the given `name`, if any.
+    pub fn verify(
+        &self,
+        leaf: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        name: Option<&ServerName<'_>>,
+    ) -> Result<VerifiedCertificateChain, CertificateChainError> {
+        self.verify_at(leaf, intermediates, name, SystemTime::now())
+    }
+
+    /// Validate the `leaf` certificate at the given time,
+    /// see [`CertificateChainVerifier::verify`] for more information.
+    pub fn verify_at(
+        &self,
+        leaf: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        name: Option<&ServerName<'_>>,
+        time: SystemTime,
+    ) -> Result<VerifiedCertificateChain, CertificateChainError> {
+        let time = UnixTime::since_unix_epoch(
+            time.duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default(),
+        );
+
+        let end_entity = webpki::EndEntityCert::try_from(leaf)?;
+        let path = end_entity.verify_for_usage(
+            self.algorithms,
+            &self.anchors,
+            intermediates,
+            time,
+            webpki::KeyUsage::server_auth(),
+            None,
+            None,
+        )?;
+
+        if let Some(name) = name {
+            end_entity.verify_is_valid_for_subject_name(name)?;
+        }
+
+        let root = self
+            .anchors
+            .iter()
+            .position(|anchor| {
+                anchor.subject == path.anchor().subject
+                    && anchor.subject_public_key_info == path.anchor().subject_public_key_info
+            })
+            .map(|index| self.roots[index].clone())
+            .ok_or_else(|| {
+                CertificateChainError::Invalid(OpaqueError::from_display(
+                    "trust anchor of verified path not found in roots",
+                ))
+            })?;
+
+        let mut chain = Vec::with_capacity(intermediates.len() + 2);
+        chain.push(leaf.clone().into_owned());
+        chain.extend(
+            path.intermediate_certificates()
+                .map(|cert| cert.der().into_owned()),
+        );
+        chain.push(root);
+
+        let leaf_info =
+            CertificateInfo::try_from_der(leaf).map_err(CertificateChainError::Invalid)?;
+
+        Ok(VerifiedCertificateChain { chain, leaf_info })
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A certificate chain verified by [`CertificateChainVerifier`].
+pub struct VerifiedCertificateChain {
+    chain: Vec<CertificateDer<'static>>,
+    leaf_info: CertificateInfo,
+}
+
+impl VerifiedCertificateChain {
+    /// The built chain, starting with the leaf and ending with the trusted root.
+    pub fn chain(&self) -> &[CertificateDer<'static>] {
+        &self.chain
+    }
+
+    /// Consume this [`VerifiedCertificateChain`] into the built chain,
+    /// starting with the leaf and ending with the trusted root.
+    pub fn into_chain(self) -> Vec<CertificateDer<'static>> {
+        self.chain
+    }
+
+    /// The [`CertificateInfo`] of the leaf certificate.
+    pub fn leaf_info(&self) -> &CertificateInfo {
+        &self.leaf_info
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A subject alternative name of a certificate.
+pub enum SubjectAltName {
+    /// A DNS name.
+    Dns(String),
+    /// An IP address.
+    Ip(IpAddr),
+    /// An email address.
+    Email(String),
+    /// An URI.
+    Uri(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The key usage of a certificate, as defined in
+/// [RFC 5280, section 4.2.1.3](https://datatracker.ietf.org/doc/html/rfc5280#section-4.2.1.3).
+pub struct KeyUsage(u16);
+
+impl KeyUsage {
+    fn bit(&self, index: u16) -> bool {
+        self.0 & (1 << index) != 0
+    }
+
+    /// The key can be used for digital signatures.
+    pub fn digital_signature(&self) -> bool {
+        self.bit(0)
+    }
+
+    /// The key can be used for non-repudiation (content commitment).
+    pub fn non_repudiation(&self) -> bool {
+        self.bit(1)
+    }
+
+    /// The key can be used to encipher keys.
+    pub fn key_encipherment(&self) -> bool {
+        self.bit(2)
+    }
+
+    /// The key can be used to encipher data.
+    pub fn data_encipherment(&self) -> bool {
+        self.bit(3)
+    }
+
+    /// The key can be used for key agreement.
+    pub fn key_agreement(&self) -> bool {
+        self.bit(4)
+    }
+
+    /// The key can be used to sign certificates.
+    pub fn key_cert_sign(&self) -> bool {
+        self.bit(5)
+    }
+
+    /// The key can be used to sign certificate revocation lists.
+    pub fn crl_sign(&self) -> bool {
+        self.bit(6)
+    }
+
+    /// The key can only be used to encipher data during key agreement.
+    pub fn encipher_only(&self) -> bool {
+        self.bit(7)
+    }
+
+    /// The key can only be used to decipher data during key agreement.
+    pub fn decipher_only(&self) -> bool {
+        self.bit(8)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Information extracted from a (DER encoded) X.509 certificate.
+pub struct CertificateInfo {
+    subject_alt_names: Vec<SubjectAltName>,
+    not_before: SystemTime,
+    not_after: SystemTime,
+    key_usage: Option<KeyUsage>,
+}
+
+impl CertificateInfo {
+    /// Extract the [`CertificateInfo`] from a DER encoded certificate.
+    pub fn try_from_der(der: &CertificateDer<'_>) -> Result<Self, OpaqueError> {
+        let (_, cert) = x509_parser::parse_x509_certificate(der.as_ref())
+            .map_err(OpaqueError::from_std)
+            .context("parse x509 certificate")?;
+
+        let validity = cert.validity();
+        let not_before = unix_time(validity.not_before.timestamp()).context("parse not before")?;
+        let not_after = unix_time(validity.not_after.timestamp()).context("parse not after")?;
+
+        let key_usage = cert
+            .key_usage()
+            .map_err(OpaqueError::from_std)
+            .context("parse key usage")?
+            .map(|ext| KeyUsage(ext.value.flags));
+
+        let subject_alt_names = match cert
+            .subject_alternative_name()
+            .map_err(OpaqueError::from_std)
+            .context("parse subject alt names")?
+        {
+            Some(ext) => ext
+                .value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::RFC822Name(email) => {
+                        Some(Ok(SubjectAltName::Email((*email).to_owned())))
+                    }
+                    GeneralName::DNSName(dns) => Some(Ok(SubjectAltName::Dns((*dns).to_owned()))),
+                    GeneralName::URI(uri) => Some(Ok(SubjectAltName::Uri((*uri).to_owned()))),
+                    GeneralName::IPAddress(ip) => Some(ip_addr(ip).map(SubjectAltName::Ip)),
+                    _ => None, // other names are not supported
+                })
+                .collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            subject_alt_names,
+            not_before,
+            not_after,
+            key_usage,
+        })
+    }
+
+    /// The subject alternative names of the certificate.
+    pub fn subject_alt_names(&self) -> &[SubjectAltName] {
+        &self.subject_alt_names
+    }
+
+    /// The start of the validity window of the certificate.
+    pub fn not_before(&self) -> SystemTime {
+        self.not_before
+    }
+
+    /// The end of the validity window of the certificate.
+    pub fn not_after(&self) -> SystemTime {
+        self.not_after
+    }
+
+    /// Returns `true` if the certificate is valid at the given time.
+    pub fn is_valid_at(&self, time: SystemTime) -> bool {
+        self.not_before <= time && time <= self.not_after
+    }
+
+    /// The key usage of the certificate, if defined.
+    pub fn key_usage(&self) -> Option<KeyUsage> {
+        self.key_usage
+    }
+}
+
+fn unix_time(timestamp: i64) -> Result<SystemTime, OpaqueError> {
+    let seconds = u64::try_from(timestamp).context("time before unix epoch")?;
+    Ok(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+fn ip_addr(ip: &[u8]) -> Result<IpAddr, OpaqueError> {
+    if let Ok(ip) = <[u8; 4]>::try_from(ip) {
+        Ok(IpAddr::from(ip))
+    } else if let Ok(ip) = <[u8; 16]>::try_from(ip) {
+        Ok(IpAddr::from(ip))
+    } else {
+        Err(OpaqueError::from_display("invalid ip address length"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::error::ErrorExt;
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair, KeyUsagePurpose};
+
+    struct TestChain {
+        root: CertificateDer<'static>,
+        intermediate: CertificateDer<'static>,
+        leaf: CertificateDer<'static>,
+    }
+
+    fn ca_params(name: &str) -> CertificateParams {
+        let mut params = CertificateParams::new(Vec::new()).unwrap();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, name);
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign];
+        params
+    }
+
+    fn test_chain(leaf_validity: (i32, i32)) -> TestChain {
+        let root_key = KeyPair::generate().unwrap();
+        let root = ca_params("rama test root").self_signed(&root_key).unwrap();
+
+        let intermediate_key = KeyPair::generate().unwrap();
+        let intermediate = ca_params("rama test intermediate")
+            .signed_by(&intermediate_key, &root, &root_key)
+            .unwrap();
+
+        let leaf_key = KeyPair::generate().unwrap();
+        let mut leaf_params =
+            CertificateParams::new(vec!["example.com".to_owned(), "127.0.0.1".to_owned()]).unwrap();
+        leaf_params.not_before = rcgen::date_time_ymd(leaf_validity.0, 1, 1);
+        leaf_params.not_after = rcgen::date_time_ymd(leaf_validity.1, 1, 1);
+        leaf_params.key_usages = vec![
+            KeyUsagePurpose::DigitalSignature,
+            KeyUsagePurpose::KeyEncipherment,
+        ];
+        leaf_params.extended_key_usages = vec![rcgen::ExtendedKeyUsagePurpose::ServerAuth];
+        let leaf = leaf_params
+            .signed_by(&leaf_key, &intermediate, &intermediate_key)
+            .unwrap();
+
+        TestChain {
+            root: root.der().clone(),
+            intermediate: intermediate.der().clone(),
+            leaf: leaf.der().clone(),
+        }
+    }
+
+    fn verifier(root: CertificateDer<'static>) -> CertificateChainVerifier {
+        CertificateChainVerifier::new([root], webpki::ALL_VERIFICATION_ALGS).unwrap()
+    }
+
+    fn ymd(year: i32, month: u8, day: u8) -> SystemTime {
+        rcgen::date_time_ymd(year, month, day).into()
+    }
+
+    #[test]
+    fn test_verify_chain() {
+        let chain = test_chain((2020, 2040));
+        let verifier = verifier(chain.root.clone());
+
+        let verified = verifier
+            .verify_at(
+                &chain.leaf,
+                std::slice::from_ref(&chain.intermediate),
+                Some(&ServerName::try_from("example.com").unwrap()),
+                ymd(2025, 6, 1),
+            )
+            .unwrap();
+        assert_eq!(
+            verified.chain(),
+            &[chain.leaf.clone(), chain.intermediate, chain.root]
+        );
+
+        let info = verified.leaf_info();
+        assert_eq!(
+            info.subject_alt_names(),
+            &[
+                SubjectAltName::Dns("example.com".to_owned()),
+                SubjectAltName::Ip(IpAddr::from([127, 0, 0, 1])),
+            ]
+        );
+        assert_eq!(info.not_before(), ymd(2020, 1, 1));
+        assert_eq!(info.not_after(), ymd(2040, 1, 1));
+        assert!(info.is_valid_at(ymd(2025, 6, 1)));
+
+        let key_usage = info.key_usage().unwrap();
+        assert!(key_usage.digital_signature());
+        assert!(key_usage.key_encipherment());
+        assert!(!key_usage.key_cert_sign());
+        assert!(!key_usage.decipher_only());
+    }
+
+    #[test]
+    fn test_verify_chain_errors() {
+        let chain = test_chain((2020, 2022));
+        let verifier = verifier(chain.root.clone());
+        let intermediates = [chain.intermediate.clone()];
+
+        let err = verifier
+            .verify_at(&chain.leaf, &intermediates, None, ymd(2025, 6, 1))
+            .unwrap_err();
+        assert!(matches!(err, CertificateChainError::Expired), "{err}");
+        assert_eq!(
+            rama_core::error::ErrorClassifier::new()
+                .with_error_type::<CertificateChainError>()
+                .classify(&err.context("verify leaf")),
+            ErrorClass::Tls
+        );
+
+        let err = verifier
+            .verify_at(&chain.leaf, &intermediates, None, ymd(2019, 6, 1))
+            .unwrap_err();
+        assert!(matches!(err, CertificateChainError::NotYetValid), "{err}");
+
+        let err = verifier
+            .verify_at(
+                &chain.leaf,
+                &intermediates,
+                Some(&ServerName::try_from("example.org").unwrap()),
+                ymd(2021, 6, 1),
+            )
+            .unwrap_err();
+        assert!(matches!(err, CertificateChainError::NameMismatch), "{err}");
+
+        let err = verifier
+            .verify_at(&chain.leaf, &[], None, ymd(2021, 6, 1))
+            .unwrap_err();
+        assert!(matches!(err, CertificateChainError::UnknownIssuer), "{err}");
+    }
+}
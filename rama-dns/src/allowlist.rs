@@ -0,0 +1,178 @@
+use crate::{DnsDeniedError, DnsResolver, SrvDnsResolver, SrvRecord};
+use rama_core::error::BoxError;
+use rama_net::address::Domain;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+#[derive(Debug, Clone)]
+/// a [`DnsResolver`] implementation which only permits
+/// lookups for allowed domains, denying all others with a [`DnsDeniedError`].
+///
+/// Allowed lookups are resolved by the inner [`DnsResolver`],
+/// while denied lookups never reach it.
+///
+/// A domain is allowed if it is equal to one of the allowed domains,
+/// or if it is equal to or a subdomain of one of the allowed suffixes.
+pub struct AllowlistDns<R> {
+    inner: R,
+    domains: Vec<Domain>,
+    suffixes: Vec<Domain>,
+}
+
+impl<R> AllowlistDns<R> {
+    /// Create a new [`AllowlistDns`] wrapping the given [`DnsResolver`],
+    /// which denies all lookups until domains or suffixes are allowed.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            domains: Vec::new(),
+            suffixes: Vec::new(),
+        }
+    }
+
+    /// Allow lookups for the given [`Domain`] (exact match).
+    pub fn with_domain(mut self, domain: Domain) -> Self {
+        self.domains.push(domain);
+        self
+    }
+
+    /// Allow lookups for the given [`Domain`] (exact match).
+    pub fn set_domain(&mut self, domain: Domain) -> &mut Self {
+        self.domains.push(domain);
+        self
+    }
+
+    /// Allow lookups for the given [`Domain`] and all its subdomains.
+    ///
+    /// E.g. a suffix of `internal` allows `internal`, `foo.internal` and `bar.foo.internal`.
+    pub fn with_suffix(mut self, suffix: Domain) -> Self {
+        self.suffixes.push(suffix);
+        self
+    }
+
+    /// Allow lookups for the given [`Domain`] and all its subdomains.
+    ///
+    /// E.g. a suffix of `internal` allows `internal`, `foo.internal` and `bar.foo.internal`.
+    pub fn set_suffix(&mut self, suffix: Domain) -> &mut Self {
+        self.suffixes.push(suffix);
+        self
+    }
+
+    /// Returns `true` if lookups for the given [`Domain`] are allowed.
+    pub fn is_allowed(&self, domain: &Domain) -> bool {
+        self.domains.iter().any(|allowed| allowed == domain)
+            || self.suffixes.iter().any(|suffix| domain.is_sub_of(suffix))
+    }
+
+    /// Reference to the inner [`DnsResolver`].
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Consume `self`, returning the inner [`DnsResolver`].
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R> DnsResolver for AllowlistDns<R>
+where
+    R: DnsResolver<Error: Into<BoxError>>,
+{
+    type Error = BoxError;
+
+    async fn ipv4_lookup(&self, domain: Domain) -> Result<Vec<Ipv4Addr>, Self::Error> {
+        if !self.is_allowed(&domain) {
+            return Err(DnsDeniedError.into());
+        }
+        self.inner.ipv4_lookup(domain).await.map_err(Into::into)
+    }
+
+    async fn ipv6_lookup(&self, domain: Domain) -> Result<Vec<Ipv6Addr>, Self::Error> {
+        if !self.is_allowed(&domain) {
+            return Err(DnsDeniedError.into());
+        }
+        self.inner.ipv6_lookup(domain).await.map_err(Into::into)
+    }
+}
+
+impl<R> SrvDnsResolver for AllowlistDns<R>
+where
+    R: SrvDnsResolver<Error: Into<BoxError>>,
+{
+    async fn srv_lookup(&self, name: Domain) -> Result<Vec<SrvRecord>, Self::Error> {
+        if !self.is_allowed(&name) {
+            return Err(DnsDeniedError.into());
+        }
+        self.inner.srv_lookup(name).await.map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDns;
+
+    fn resolver() -> AllowlistDns<InMemoryDns> {
+        let mut dns = InMemoryDns::new();
+        for domain in ["api.internal", "internal", "example.com", "evil.com"] {
+            dns.insert(
+                Domain::from_static(domain),
+                vec![Ipv4Addr::LOCALHOST.into(), Ipv6Addr::LOCALHOST.into()],
+            );
+        }
+        AllowlistDns::new(dns)
+            .with_suffix(Domain::from_static("internal"))
+            .with_domain(Domain::from_static("example.com"))
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_dns_permitted() {
+        let dns = resolver();
+        for domain in ["api.internal", "internal", "example.com"] {
+            let domain = Domain::from_static(domain);
+            assert_eq!(
+                dns.ipv4_lookup(domain.clone()).await.unwrap(),
+                vec![Ipv4Addr::LOCALHOST]
+            );
+            assert_eq!(
+                dns.ipv6_lookup(domain).await.unwrap(),
+                vec![Ipv6Addr::LOCALHOST]
+            );
+        }
+    }
+
+    #[test]
+    fn test_allowlist_dns_is_allowed_case_insensitive() {
+        let dns = resolver();
+        assert!(dns.is_allowed(&Domain::from_static("EXAMPLE.COM")));
+        assert!(dns.is_allowed(&Domain::from_static("Api.Internal")));
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_dns_permitted_but_not_found() {
+        let dns = resolver();
+        let err = dns
+            .ipv4_lookup(Domain::from_static("db.internal"))
+            .await
+            .unwrap_err();
+        assert!(!err.is::<DnsDeniedError>());
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_dns_denied() {
+        let dns = resolver();
+        for domain in ["evil.com", "www.example.com", "internal.evil.com"] {
+            let domain = Domain::from_static(domain);
+            assert!(dns
+                .ipv4_lookup(domain.clone())
+                .await
+                .unwrap_err()
+                .is::<DnsDeniedError>());
+            assert!(dns
+                .ipv6_lookup(domain)
+                .await
+                .unwrap_err()
+                .is::<DnsDeniedError>());
+        }
+    }
+}
@@ -0,0 +1,596 @@
+//! [`DnsResolver`] caching the answers of an inner [`DnsResolver`]
+//! for a fixed, configured TTL.
+//!
+//! See [`CachingDnsResolver`].
+
+use crate::{observed::error_kind, DnsResolver, SrvDnsResolver, SrvRecord};
+use moka::{policy::EvictionPolicy, sync::Cache, Expiry};
+use rama_core::error::{BoxError, OpaqueError};
+use rama_net::address::Domain;
+use rama_utils::macros::error::static_str_error;
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    net::{Ipv4Addr, Ipv6Addr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// A [`DnsResolver`] which caches the answers of the inner [`DnsResolver`],
+/// keyed by [`Domain`] and record type.
+///
+/// Known gaps:
+///
+/// - The [`DnsResolver`] trait does not expose the TTL of the resolved records,
+///   so the TTL of the records is not honoured. Answers are instead cached for a
+///   fixed TTL, optionally overwritten for specific domains
+///   (see [`CachingDnsResolverBuilder`]). Configure a TTL no longer
+///   than the TTLs of the records you expect to resolve.
+/// - Only A, AAAA and SRV answers are cached. The [`DnsResolver`] trait has
+///   no TXT lookup, so TXT records are neither resolved nor cached.
+///
+/// The cache is bound by a maximum amount of entries,
+/// evicting the least recently used entries once that bound is reached.
+///
+/// Failed lookups are not cached, unless negative caching is enabled
+/// using [`CachingDnsResolverBuilder::with_negative_ttl`], in which case
+/// only lookups for which no records exist (e.g. NXDOMAIN) are cached.
+///
+/// Cloning a [`CachingDnsResolver`] shares the cache.
+pub struct CachingDnsResolver<R> {
+    inner: R,
+    cache: Cache<CacheKey, CacheEntry>,
+    ttl: Duration,
+    domain_ttls: Arc<HashMap<Domain, Duration>>,
+    negative_ttl: Option<Duration>,
+}
+
+impl<R: fmt::Debug> fmt::Debug for CachingDnsResolver<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachingDnsResolver")
+            .field("inner", &self.inner)
+            .field("max_entries", &self.cache.policy().max_capacity())
+            .field("ttl", &self.ttl)
+            .field("domain_ttls", &self.domain_ttls)
+            .field("negative_ttl", &self.negative_ttl)
+            .finish()
+    }
+}
+
+impl<R: Clone> Clone for CachingDnsResolver<R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            cache: self.cache.clone(),
+            ttl: self.ttl,
+            domain_ttls: self.domain_ttls.clone(),
+            negative_ttl: self.negative_ttl,
+        }
+    }
+}
+
+impl CachingDnsResolver<()> {
+    #[inline]
+    /// Construct a [`CachingDnsResolverBuilder`] used to build
+    /// a custom [`CachingDnsResolver`] instead of the default [`CachingDnsResolver::new`].
+    pub fn builder() -> CachingDnsResolverBuilder {
+        CachingDnsResolverBuilder::default()
+    }
+}
+
+impl<R> CachingDnsResolver<R> {
+    /// Create a new [`CachingDnsResolver`] wrapping the given [`DnsResolver`],
+    /// using the [`Default`] [`CachingDnsResolverBuilder`] settings.
+    pub fn new(inner: R) -> Self {
+        CachingDnsResolverBuilder::default().build(inner)
+    }
+
+    /// Remove all cached answers.
+    pub fn purge(&self) {
+        self.cache.invalidate_all();
+    }
+
+    /// Reference to the inner [`DnsResolver`].
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Consume `self`, returning the inner [`DnsResolver`].
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn ttl_for(&self, domain: &Domain) -> Duration {
+        self.domain_ttls.get(domain).copied().unwrap_or(self.ttl)
+    }
+}
+
+impl<R> CachingDnsResolver<R>
+where
+    R: DnsResolver<Error: Into<BoxError>>,
+{
+    async fn lookup<T, F, Fut>(
+        &self,
+        domain: Domain,
+        record_type: RecordType,
+        lookup: F,
+    ) -> Result<Vec<T>, BoxError>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce(Domain) -> Fut,
+        Fut: Future<Output = Result<Vec<T>, R::Error>>,
+        CachedAnswer: From<Arc<[T]>> + TryInto<Arc<[T]>>,
+    {
+        let key = CacheKey {
+            domain,
+            record_type,
+        };
+
+        if let Some(entry) = self.cache.get(&key) {
+            tracing::trace!(domain = %key.domain, ?record_type, "dns cache hit");
+            return match entry.answer.try_into() {
+                Ok(records) => Ok(records.to_vec()),
+                Err(_) => Err(NegativeCachedError.into()),
+            };
+        }
+
+        match lookup(key.domain.clone()).await {
+            Ok(records) => {
+                let ttl = self.ttl_for(&key.domain);
+                self.cache.insert(
+                    key,
+                    CacheEntry {
+                        answer: Arc::<[T]>::from(records.as_slice()).into(),
+                        ttl,
+                    },
+                );
+                Ok(records)
+            }
+            Err(err) => {
+                let err = err.into();
+                // only cache answers that no records exist,
+                // transient failures (e.g. timeouts) are to be retried
+                if let Some(ttl) = self
+                    .negative_ttl
+                    .filter(|_| error_kind(&err) == "no_records")
+                {
+                    tracing::trace!(domain = %key.domain, ?record_type, %err, "dns cache: cache negative answer");
+                    self.cache.insert(
+                        key,
+                        CacheEntry {
+                            answer: CachedAnswer::Negative,
+                            ttl,
+                        },
+                    );
+                }
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<R> DnsResolver for CachingDnsResolver<R>
+where
+    R: DnsResolver<Error: Into<BoxError>>,
+{
+    type Error = BoxError;
+
+    async fn ipv4_lookup(&self, domain: Domain) -> Result<Vec<Ipv4Addr>, Self::Error> {
+        self.lookup(domain, RecordType::A, |domain| {
+            self.inner.ipv4_lookup(domain)
+        })
+        .await
+    }
+
+    async fn ipv6_lookup(&self, domain: Domain) -> Result<Vec<Ipv6Addr>, Self::Error> {
+        self.lookup(domain, RecordType::Aaaa, |domain| {
+            self.inner.ipv6_lookup(domain)
+        })
+        .await
+    }
+}
+
+impl<R> SrvDnsResolver for CachingDnsResolver<R>
+where
+    R: SrvDnsResolver<Error: Into<BoxError>>,
+{
+    async fn srv_lookup(&self, name: Domain) -> Result<Vec<SrvRecord>, Self::Error> {
+        self.lookup(name, RecordType::Srv, |name| self.inner.srv_lookup(name))
+            .await
+    }
+}
+
+static_str_error! {
+    #[doc = "dns lookup failed (cached negative answer)"]
+    pub struct NegativeCachedError;
+}
+
+#[derive(Debug, Clone)]
+/// A builder to [`build`][`Self::build`] a [`CachingDnsResolver`] instance.
+pub struct CachingDnsResolverBuilder {
+    max_entries: u64,
+    ttl: Duration,
+    domain_ttls: HashMap<Domain, Duration>,
+    negative_ttl: Option<Duration>,
+}
+
+impl Default for CachingDnsResolverBuilder {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            ttl: Duration::from_secs(60),
+            domain_ttls: HashMap::new(),
+            negative_ttl: None,
+        }
+    }
+}
+
+impl CachingDnsResolverBuilder {
+    /// Set the maximum amount of cached answers,
+    /// evicting the least recently used answers once reached.
+    ///
+    /// Defaults to 10_000.
+    pub fn with_max_entries(mut self, max_entries: u64) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Set the maximum amount of cached answers,
+    /// evicting the least recently used answers once reached.
+    ///
+    /// Defaults to 10_000.
+    pub fn set_max_entries(&mut self, max_entries: u64) -> &mut Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Set the time to live of cached answers.
+    ///
+    /// Defaults to 60 seconds.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the time to live of cached answers.
+    ///
+    /// Defaults to 60 seconds.
+    pub fn set_ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Overwrite the time to live of cached answers for the given [`Domain`].
+    pub fn with_domain_ttl(mut self, domain: Domain, ttl: Duration) -> Self {
+        self.domain_ttls.insert(domain, ttl);
+        self
+    }
+
+    /// Overwrite the time to live of cached answers for the given [`Domain`].
+    pub fn set_domain_ttl(&mut self, domain: Domain, ttl: Duration) -> &mut Self {
+        self.domain_ttls.insert(domain, ttl);
+        self
+    }
+
+    /// Enable negative caching, caching lookups for which no records exist
+    /// (e.g. NXDOMAIN) for the given time to live.
+    ///
+    /// Other failures (e.g. timeouts or I/O errors) are never cached.
+    /// Errors of [`DnsResolver`]s not provided by this crate cannot be classified,
+    /// and are therefore not cached either.
+    /// Cached failures are returned as a [`NegativeCachedError`].
+    ///
+    /// Disabled by default.
+    pub fn with_negative_ttl(mut self, ttl: Duration) -> Self {
+        self.negative_ttl = Some(ttl);
+        self
+    }
+
+    /// Enable negative caching, caching lookups for which no records exist
+    /// (e.g. NXDOMAIN) for the given time to live.
+    ///
+    /// Other failures (e.g. timeouts or I/O errors) are never cached.
+    /// Errors of [`DnsResolver`]s not provided by this crate cannot be classified,
+    /// and are therefore not cached either.
+    /// Cached failures are returned as a [`NegativeCachedError`].
+    ///
+    /// Disabled by default.
+    pub fn set_negative_ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.negative_ttl = Some(ttl);
+        self
+    }
+
+    /// Build a [`CachingDnsResolver`] instance wrapping the given [`DnsResolver`],
+    /// consuming [`self`].
+    pub fn build<R>(self, inner: R) -> CachingDnsResolver<R> {
+        CachingDnsResolver {
+            inner,
+            cache: Cache::builder()
+                .max_capacity(self.max_entries)
+                .eviction_policy(EvictionPolicy::lru())
+                .expire_after(EntryExpiry)
+                .build(),
+            ttl: self.ttl,
+            domain_ttls: Arc::new(self.domain_ttls),
+            negative_ttl: self.negative_ttl,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum RecordType {
+    A,
+    Aaaa,
+    Srv,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    domain: Domain,
+    record_type: RecordType,
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    answer: CachedAnswer,
+    ttl: Duration,
+}
+
+#[derive(Debug, Clone)]
+enum CachedAnswer {
+    Ipv4(Arc<[Ipv4Addr]>),
+    Ipv6(Arc<[Ipv6Addr]>),
+    Srv(Arc<[SrvRecord]>),
+    Negative,
+}
+
+macro_rules! impl_cached_answer_conversions {
+    ($($variant:ident($ty:ty)),+ $(,)?) => {
+        $(
+            impl From<Arc<[$ty]>> for CachedAnswer {
+                fn from(records: Arc<[$ty]>) -> Self {
+                    Self::$variant(records)
+                }
+            }
+
+            impl TryFrom<CachedAnswer> for Arc<[$ty]> {
+                type Error = OpaqueError;
+
+                fn try_from(answer: CachedAnswer) -> Result<Self, Self::Error> {
+                    match answer {
+                        CachedAnswer::$variant(records) => Ok(records),
+                        _ => Err(OpaqueError::from_display("unexpected cached dns answer")),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_cached_answer_conversions!(Ipv4(Ipv4Addr), Ipv6(Ipv6Addr), Srv(SrvRecord));
+
+struct EntryExpiry;
+
+impl Expiry<CacheKey, CacheEntry> for EntryExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &CacheKey,
+        value: &CacheEntry,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        Some(value.ttl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDns;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// [`InMemoryDns`] counting the lookups which reach it.
+    #[derive(Debug, Default)]
+    struct CountingDns {
+        dns: InMemoryDns,
+        lookups: AtomicUsize,
+    }
+
+    impl CountingDns {
+        fn new() -> Self {
+            let mut dns = InMemoryDns::new();
+            dns.insert_addresses(
+                Domain::from_static("example.com"),
+                [
+                    Ipv4Addr::new(127, 0, 0, 1).into(),
+                    Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1).into(),
+                ]
+                .into_iter()
+                .collect::<Vec<std::net::IpAddr>>(),
+            );
+            dns.insert_address(
+                Domain::from_static("plabayo.tech"),
+                Ipv4Addr::new(127, 0, 0, 2),
+            );
+            Self {
+                dns,
+                lookups: AtomicUsize::new(0),
+            }
+        }
+
+        fn lookups(&self) -> usize {
+            self.lookups.load(Ordering::SeqCst)
+        }
+    }
+
+    impl DnsResolver for CountingDns {
+        type Error = BoxError;
+
+        async fn ipv4_lookup(&self, domain: Domain) -> Result<Vec<Ipv4Addr>, Self::Error> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(self.dns.ipv4_lookup(domain).await?)
+        }
+
+        async fn ipv6_lookup(&self, domain: Domain) -> Result<Vec<Ipv6Addr>, Self::Error> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(self.dns.ipv6_lookup(domain).await?)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_caching_dns_resolver_caches_per_record_type() {
+        let dns = CachingDnsResolver::new(CountingDns::new());
+        let domain = Domain::from_static("example.com");
+
+        for _ in 0..3 {
+            assert_eq!(
+                dns.ipv4_lookup(domain.clone()).await.unwrap(),
+                [Ipv4Addr::new(127, 0, 0, 1)]
+            );
+        }
+        assert_eq!(dns.inner().lookups(), 1);
+
+        for _ in 0..3 {
+            assert_eq!(
+                dns.ipv6_lookup(domain.clone()).await.unwrap(),
+                [Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)]
+            );
+        }
+        assert_eq!(dns.inner().lookups(), 2);
+
+        dns.purge();
+        dns.ipv4_lookup(domain).await.unwrap();
+        assert_eq!(dns.inner().lookups(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_caching_dns_resolver_ttl() {
+        let dns = CachingDnsResolver::builder()
+            .with_ttl(Duration::from_millis(50))
+            .with_domain_ttl(Domain::from_static("plabayo.tech"), Duration::from_secs(60))
+            .build(CountingDns::new());
+
+        dns.ipv4_lookup(Domain::from_static("example.com"))
+            .await
+            .unwrap();
+        dns.ipv4_lookup(Domain::from_static("plabayo.tech"))
+            .await
+            .unwrap();
+        assert_eq!(dns.inner().lookups(), 2);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        dns.ipv4_lookup(Domain::from_static("example.com"))
+            .await
+            .unwrap();
+        dns.ipv4_lookup(Domain::from_static("plabayo.tech"))
+            .await
+            .unwrap();
+        assert_eq!(dns.inner().lookups(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_caching_dns_resolver_no_negative_caching_by_default() {
+        let dns = CachingDnsResolver::new(CountingDns::new());
+
+        for _ in 0..2 {
+            assert!(dns
+                .ipv6_lookup(Domain::from_static("plabayo.tech"))
+                .await
+                .is_err());
+        }
+        assert_eq!(dns.inner().lookups(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_caching_dns_resolver_negative_caching() {
+        let dns = CachingDnsResolver::builder()
+            .with_negative_ttl(Duration::from_millis(50))
+            .build(CountingDns::new());
+
+        let err = dns
+            .ipv6_lookup(Domain::from_static("plabayo.tech"))
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<NegativeCachedError>().is_none());
+
+        let err = dns
+            .ipv6_lookup(Domain::from_static("plabayo.tech"))
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<NegativeCachedError>().is_some());
+        assert_eq!(dns.inner().lookups(), 1);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert!(dns
+            .ipv6_lookup(Domain::from_static("plabayo.tech"))
+            .await
+            .is_err());
+        assert_eq!(dns.inner().lookups(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_caching_dns_resolver_does_not_cache_transient_errors() {
+        use hickory_resolver::error::{ResolveError, ResolveErrorKind};
+
+        #[derive(Debug, Default)]
+        struct TimeoutDns {
+            lookups: AtomicUsize,
+        }
+
+        impl DnsResolver for TimeoutDns {
+            type Error = ResolveError;
+
+            async fn ipv4_lookup(&self, _domain: Domain) -> Result<Vec<Ipv4Addr>, Self::Error> {
+                self.lookups.fetch_add(1, Ordering::SeqCst);
+                Err(ResolveErrorKind::Timeout.into())
+            }
+
+            async fn ipv6_lookup(&self, _domain: Domain) -> Result<Vec<Ipv6Addr>, Self::Error> {
+                self.lookups.fetch_add(1, Ordering::SeqCst);
+                Err(ResolveErrorKind::Timeout.into())
+            }
+        }
+
+        let dns = CachingDnsResolver::builder()
+            .with_negative_ttl(Duration::from_secs(60))
+            .build(TimeoutDns::default());
+
+        for _ in 0..2 {
+            let err = dns
+                .ipv4_lookup(Domain::from_static("example.com"))
+                .await
+                .unwrap_err();
+            assert!(err.downcast_ref::<NegativeCachedError>().is_none());
+        }
+        assert_eq!(dns.inner().lookups.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_caching_dns_resolver_max_entries() {
+        let dns = CachingDnsResolver::builder()
+            .with_max_entries(1)
+            .build(CountingDns::new());
+
+        dns.ipv4_lookup(Domain::from_static("example.com"))
+            .await
+            .unwrap();
+        dns.cache.run_pending_tasks();
+        dns.ipv4_lookup(Domain::from_static("plabayo.tech"))
+            .await
+            .unwrap();
+        dns.cache.run_pending_tasks();
+        assert_eq!(dns.cache.entry_count(), 1);
+
+        // the least recently used entry got evicted
+        dns.ipv4_lookup(Domain::from_static("plabayo.tech"))
+            .await
+            .unwrap();
+        assert_eq!(dns.inner().lookups(), 2);
+        dns.ipv4_lookup(Domain::from_static("example.com"))
+            .await
+            .unwrap();
+        assert_eq!(dns.inner().lookups(), 3);
+    }
+}
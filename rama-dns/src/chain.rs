@@ -3,7 +3,7 @@ use std::net::{Ipv4Addr, Ipv6Addr};
 use rama_core::error::BoxError;
 use rama_net::address::Domain;
 
-use crate::DnsResolver;
+use crate::{DnsResolver, SrvDnsResolver, SrvRecord};
 
 macro_rules! dns_resolver_chain_impl {
     () => {
@@ -31,6 +31,21 @@ macro_rules! dns_resolver_chain_impl {
     };
 }
 
+macro_rules! srv_dns_resolver_chain_impl {
+    () => {
+        async fn srv_lookup(&self, name: Domain) -> Result<Vec<SrvRecord>, Self::Error> {
+            let mut errors = Vec::new();
+            for resolver in self {
+                match resolver.srv_lookup(name.clone()).await {
+                    Ok(records) => return Ok(records),
+                    Err(err) => errors.push(err.into()),
+                }
+            }
+            Err(errors)
+        }
+    };
+}
+
 impl<R> DnsResolver for Vec<R>
 where
     R: DnsResolver + Send,
@@ -51,6 +66,22 @@ where
     dns_resolver_chain_impl!();
 }
 
+impl<R> SrvDnsResolver for Vec<R>
+where
+    R: SrvDnsResolver + Send,
+    R::Error: Into<BoxError>,
+{
+    srv_dns_resolver_chain_impl!();
+}
+
+impl<R, const N: usize> SrvDnsResolver for [R; N]
+where
+    R: SrvDnsResolver + Send,
+    R::Error: Into<BoxError>,
+{
+    srv_dns_resolver_chain_impl!();
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
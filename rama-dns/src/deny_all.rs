@@ -1,4 +1,5 @@
-use crate::DnsResolver;
+use crate::{DnsResolver, SrvDnsResolver, SrvRecord};
+use rama_core::error::{ErrorClass, ErrorKindClass};
 use rama_net::address::Domain;
 use rama_utils::macros::error::static_str_error;
 use std::net::{Ipv4Addr, Ipv6Addr};
@@ -22,6 +23,13 @@ static_str_error! {
     pub struct DnsDeniedError;
 }
 
+impl ErrorKindClass for DnsDeniedError {
+    fn error_class(&self) -> Option<ErrorClass> {
+        // a denial is permanent, retrying it is pointless
+        Some(ErrorClass::Denied)
+    }
+}
+
 impl DnsResolver for DenyAllDns {
     type Error = DnsDeniedError;
 
@@ -33,3 +41,32 @@ impl DnsResolver for DenyAllDns {
         Err(DnsDeniedError)
     }
 }
+
+impl SrvDnsResolver for DenyAllDns {
+    async fn srv_lookup(&self, _name: Domain) -> Result<Vec<SrvRecord>, Self::Error> {
+        Err(DnsDeniedError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::error::{BoxError, ErrorClassifier, ErrorContext};
+
+    #[tokio::test]
+    async fn test_denied_error_is_not_transient() {
+        let classifier = ErrorClassifier::new().with_error_type::<DnsDeniedError>();
+
+        let err = DenyAllDns::new()
+            .ipv4_lookup(Domain::from_static("example.com"))
+            .await
+            .context("resolve upstream")
+            .unwrap_err();
+        let class = classifier.classify(&err);
+        assert_eq!(class, ErrorClass::Denied);
+        assert!(!class.is_transient());
+
+        let err: BoxError = DnsDeniedError.into();
+        assert_eq!(classifier.classify(err.as_ref()), ErrorClass::Denied);
+    }
+}
@@ -0,0 +1,479 @@
+//! DNS forwarding, answering DNS queries using any [`DnsResolver`].
+//!
+//! [`DnsForwardService`] answers `A` and `AAAA` queries using the [`DnsResolver`]
+//! it wraps, and can serve these over UDP using [`DnsForwardService::serve_udp`].
+//! Combined with a caching resolver this can be used to build a caching DNS proxy.
+//!
+//! Responses sent over UDP which are larger than the maximum payload size of the query
+//! (512 bytes, or the payload size advertised using EDNS) are truncated, by dropping
+//! the answers which do not fit, and have the `TC` bit set. Incoming DNS responses are
+//! silently dropped, such that the service cannot be tricked into answering them.
+
+use crate::{DnsResolver, DomainNotMappedErr};
+use hickory_resolver::proto::{
+    error::ProtoError,
+    op::{MessageType, OpCode, ResponseCode},
+    rr::{
+        rdata::{A, AAAA},
+        RData, Record, RecordType,
+    },
+};
+use rama_core::{
+    error::{BoxError, ErrorContext},
+    rt::Executor,
+    Context, Service,
+};
+use rama_net::address::Domain;
+use std::{convert::Infallible, fmt, sync::Arc};
+use tokio::{net::UdpSocket, sync::Semaphore};
+
+#[doc(inline)]
+pub use hickory_resolver::proto::op::Message;
+
+/// Maximum size of a DNS message received over UDP (with EDNS).
+const MAX_UDP_MESSAGE_SIZE: usize = 4096;
+
+/// Default maximum amount of queries answered concurrently by [`DnsForwardService::serve_udp`].
+const DEFAULT_MAX_CONCURRENT_QUERIES: usize = 1024;
+
+/// A service answering DNS queries using a [`DnsResolver`].
+///
+/// Only standard `A` and `AAAA` queries are supported,
+/// other queries are answered with a `NotImp` response code.
+///
+/// See the [module docs](self) for more information.
+pub struct DnsForwardService<R> {
+    resolver: Arc<R>,
+    ttl: u32,
+    executor: Executor,
+    max_concurrent_queries: usize,
+}
+
+impl<R: fmt::Debug> fmt::Debug for DnsForwardService<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DnsForwardService")
+            .field("resolver", &self.resolver)
+            .field("ttl", &self.ttl)
+            .field("executor", &self.executor)
+            .field("max_concurrent_queries", &self.max_concurrent_queries)
+            .finish()
+    }
+}
+
+impl<R> Clone for DnsForwardService<R> {
+    fn clone(&self) -> Self {
+        Self {
+            resolver: self.resolver.clone(),
+            ttl: self.ttl,
+            executor: self.executor.clone(),
+            max_concurrent_queries: self.max_concurrent_queries,
+        }
+    }
+}
+
+impl<R> DnsForwardService<R> {
+    /// Create a new [`DnsForwardService`] answering queries using the given [`DnsResolver`].
+    pub fn new(resolver: R) -> Self {
+        Self {
+            resolver: Arc::new(resolver),
+            ttl: 60,
+            executor: Executor::new(),
+            max_concurrent_queries: DEFAULT_MAX_CONCURRENT_QUERIES,
+        }
+    }
+
+    /// Set the TTL (in seconds) of the records in the answers, 60 seconds by default.
+    pub fn with_ttl(mut self, ttl: u32) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the TTL (in seconds) of the records in the answers, 60 seconds by default.
+    pub fn set_ttl(&mut self, ttl: u32) -> &mut Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the [`Executor`] used by [`Self::serve_udp`] to spawn the task answering a query,
+    /// e.g. a graceful executor such that in-flight queries are answered on shutdown.
+    pub fn with_executor(mut self, executor: Executor) -> Self {
+        self.executor = executor;
+        self
+    }
+
+    /// Set the [`Executor`] used by [`Self::serve_udp`] to spawn the task answering a query,
+    /// e.g. a graceful executor such that in-flight queries are answered on shutdown.
+    pub fn set_executor(&mut self, executor: Executor) -> &mut Self {
+        self.executor = executor;
+        self
+    }
+
+    /// Set the maximum amount of queries answered concurrently by [`Self::serve_udp`],
+    /// 1024 by default. Receiving queries is paused while this limit is reached.
+    ///
+    /// # Panics
+    ///
+    /// Panics in case the given limit is zero.
+    pub fn with_max_concurrent_queries(mut self, max: usize) -> Self {
+        self.set_max_concurrent_queries(max);
+        self
+    }
+
+    /// Set the maximum amount of queries answered concurrently by [`Self::serve_udp`],
+    /// 1024 by default. Receiving queries is paused while this limit is reached.
+    ///
+    /// # Panics
+    ///
+    /// Panics in case the given limit is zero.
+    pub fn set_max_concurrent_queries(&mut self, max: usize) -> &mut Self {
+        assert!(max > 0, "max concurrent queries must be at least one");
+        self.max_concurrent_queries = max;
+        self
+    }
+}
+
+impl<R> DnsForwardService<R>
+where
+    R: DnsResolver<Error: Into<BoxError>>,
+{
+    /// Answer the given DNS query.
+    ///
+    /// The response carries the id of the query,
+    /// such that clients can match it with their query.
+    ///
+    /// Returns `None` in case the given message is a response,
+    /// as these are never to be answered.
+    pub async fn answer(&self, query: &Message) -> Option<Message> {
+        if query.message_type() == MessageType::Response {
+            tracing::trace!(id = query.id(), "ignore incoming dns response");
+            return None;
+        }
+
+        let mut response = Message::new();
+        response
+            .set_id(query.id())
+            .set_message_type(MessageType::Response)
+            .set_op_code(query.op_code())
+            .set_recursion_desired(query.recursion_desired())
+            .set_recursion_available(true)
+            .add_queries(query.queries().iter().cloned());
+
+        if query.op_code() != OpCode::Query {
+            response.set_response_code(ResponseCode::NotImp);
+            return Some(response);
+        }
+
+        for question in query.queries() {
+            let name = question.name();
+            let Ok(domain) = Domain::try_from(name.to_ascii().trim_end_matches('.').to_owned())
+            else {
+                response.set_response_code(ResponseCode::FormErr);
+                return Some(response);
+            };
+
+            let result = match question.query_type() {
+                RecordType::A => self.resolver.ipv4_lookup(domain).await.map(|ips| {
+                    ips.into_iter()
+                        .map(|ip| RData::A(A(ip)))
+                        .collect::<Vec<_>>()
+                }),
+                RecordType::AAAA => self.resolver.ipv6_lookup(domain).await.map(|ips| {
+                    ips.into_iter()
+                        .map(|ip| RData::AAAA(AAAA(ip)))
+                        .collect::<Vec<_>>()
+                }),
+                _ => {
+                    response.set_response_code(ResponseCode::NotImp);
+                    return Some(response);
+                }
+            };
+
+            match result {
+                Ok(records) => {
+                    response.add_answers(
+                        records
+                            .into_iter()
+                            .map(|rdata| Record::from_rdata(name.clone(), self.ttl, rdata)),
+                    );
+                }
+                Err(err) => {
+                    let err: BoxError = err.into();
+                    tracing::debug!(error = %err, %name, "failed to resolve dns query");
+                    response.set_response_code(if err.is::<DomainNotMappedErr>() {
+                        ResponseCode::NXDomain
+                    } else {
+                        ResponseCode::ServFail
+                    });
+                    return Some(response);
+                }
+            }
+        }
+
+        Some(response)
+    }
+
+    /// Serve DNS queries received on the given [`UdpSocket`],
+    /// writing the responses back to the clients.
+    ///
+    /// Queries are answered concurrently, each in their own task spawned using
+    /// the configured [`Executor`], with at most the configured maximum amount
+    /// of queries being answered at once.
+    /// This method only returns in case the socket fails to receive.
+    pub async fn serve_udp(self, socket: UdpSocket) -> Result<(), BoxError> {
+        let socket = Arc::new(socket);
+        let limit = Arc::new(Semaphore::new(self.max_concurrent_queries));
+        let mut buf = vec![0; MAX_UDP_MESSAGE_SIZE];
+        loop {
+            let permit = limit
+                .clone()
+                .acquire_owned()
+                .await
+                .context("acquire dns query permit")?;
+            let (len, peer) = socket
+                .recv_from(&mut buf)
+                .await
+                .context("receive dns query")?;
+
+            let query = match Message::from_vec(&buf[..len]) {
+                Ok(query) => query,
+                Err(err) => {
+                    tracing::debug!(error = %err, %peer, "ignore invalid dns query");
+                    continue;
+                }
+            };
+
+            let service = self.clone();
+            let socket = socket.clone();
+            self.executor.spawn_task(async move {
+                let _permit = permit;
+                let Some(mut response) = service.answer(&query).await else {
+                    return;
+                };
+                match encode_udp_response(&mut response, query.max_payload().into()) {
+                    Ok(bytes) => {
+                        if let Err(err) = socket.send_to(&bytes, peer).await {
+                            tracing::debug!(error = %err, %peer, "failed to send dns response");
+                        }
+                    }
+                    Err(err) => {
+                        tracing::debug!(error = %err, %peer, "failed to encode dns response");
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Encode the response to be sent over UDP, truncating it in case
+/// it is larger than the given maximum size.
+///
+/// Answers which do not fit are dropped, and the `TC` bit is set,
+/// such that the client can retry over TCP if it needs all answers.
+fn encode_udp_response(response: &mut Message, max_size: usize) -> Result<Vec<u8>, ProtoError> {
+    let mut bytes = response.to_vec()?;
+    while bytes.len() > max_size {
+        response.set_truncated(true);
+        if response.answers_mut().pop().is_none() {
+            // even without answers the response does not fit
+            *response = response.truncate();
+            return response.to_vec();
+        }
+        bytes = response.to_vec()?;
+    }
+    Ok(bytes)
+}
+
+impl<State, R> Service<State, Message> for DnsForwardService<R>
+where
+    State: Clone + Send + Sync + 'static,
+    R: DnsResolver<Error: Into<BoxError>>,
+{
+    type Response = Option<Message>;
+    type Error = Infallible;
+
+    async fn serve(
+        &self,
+        _ctx: Context<State>,
+        query: Message,
+    ) -> Result<Option<Message>, Infallible> {
+        Ok(self.answer(&query).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDns;
+    use hickory_resolver::{
+        proto::op::{Edns, Query},
+        Name,
+    };
+    use std::{
+        net::{IpAddr, Ipv4Addr, Ipv6Addr},
+        str::FromStr,
+    };
+
+    fn query(id: u16, name: &str, record_type: RecordType) -> Message {
+        let mut message = Message::new();
+        message
+            .set_id(id)
+            .set_recursion_desired(true)
+            .add_query(Query::query(Name::from_str(name).unwrap(), record_type));
+        message
+    }
+
+    async fn spawn_forwarder() -> UdpSocket {
+        let mut dns = InMemoryDns::new();
+        dns.insert(
+            Domain::from_static("example.com"),
+            vec![
+                IpAddr::V4(Ipv4Addr::new(93, 184, 216, 34)),
+                IpAddr::V6(Ipv6Addr::LOCALHOST),
+            ],
+        );
+        dns.insert_address(
+            Domain::from_static("ramaproxy.org"),
+            Ipv4Addr::new(127, 0, 0, 1),
+        );
+        dns.insert(
+            Domain::from_static("many.example"),
+            (0..100)
+                .map(|i| IpAddr::V4(Ipv4Addr::new(10, 0, 0, i)))
+                .collect(),
+        );
+
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let server_addr = server.local_addr().unwrap();
+        tokio::spawn(
+            DnsForwardService::new(dns)
+                .with_max_concurrent_queries(2)
+                .serve_udp(server),
+        );
+
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        client.connect(server_addr).await.unwrap();
+        client
+    }
+
+    async fn recv(client: &UdpSocket) -> Message {
+        let mut buf = vec![0; MAX_UDP_MESSAGE_SIZE];
+        let len = client.recv(&mut buf).await.unwrap();
+        Message::from_vec(&buf[..len]).unwrap()
+    }
+
+    fn answer_ips(response: &Message) -> Vec<IpAddr> {
+        response
+            .answers()
+            .iter()
+            .filter_map(|record| match record.data() {
+                Some(RData::A(A(ip))) => Some(IpAddr::V4(*ip)),
+                Some(RData::AAAA(AAAA(ip))) => Some(IpAddr::V6(*ip)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_forward_udp_query() {
+        let client = spawn_forwarder().await;
+
+        client
+            .send(&query(42, "example.com.", RecordType::A).to_vec().unwrap())
+            .await
+            .unwrap();
+        let response = recv(&client).await;
+
+        assert_eq!(response.id(), 42);
+        assert_eq!(response.message_type(), MessageType::Response);
+        assert_eq!(response.response_code(), ResponseCode::NoError);
+        assert_eq!(
+            answer_ips(&response),
+            vec![IpAddr::V4(Ipv4Addr::new(93, 184, 216, 34))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forward_concurrent_udp_queries() {
+        let client = spawn_forwarder().await;
+
+        for (id, name, record_type) in [
+            (1, "example.com.", RecordType::AAAA),
+            (2, "ramaproxy.org.", RecordType::A),
+            (3, "unknown.example.", RecordType::A),
+            (4, "example.com.", RecordType::MX),
+        ] {
+            client
+                .send(&query(id, name, record_type).to_vec().unwrap())
+                .await
+                .unwrap();
+        }
+
+        let mut responses = Vec::new();
+        for _ in 0..4 {
+            responses.push(recv(&client).await);
+        }
+        responses.sort_by_key(|response| response.id());
+
+        assert_eq!(
+            answer_ips(&responses[0]),
+            vec![IpAddr::V6(Ipv6Addr::LOCALHOST)]
+        );
+        assert_eq!(
+            answer_ips(&responses[1]),
+            vec![IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))]
+        );
+        assert_eq!(responses[2].response_code(), ResponseCode::NXDomain);
+        assert_eq!(responses[3].response_code(), ResponseCode::NotImp);
+    }
+
+    #[tokio::test]
+    async fn test_forward_ignores_incoming_responses() {
+        let client = spawn_forwarder().await;
+
+        let mut response = query(1, "example.com.", RecordType::A);
+        response.set_message_type(MessageType::Response);
+        assert!(DnsForwardService::new(InMemoryDns::new())
+            .answer(&response)
+            .await
+            .is_none());
+
+        client.send(&response.to_vec().unwrap()).await.unwrap();
+        client
+            .send(&query(2, "example.com.", RecordType::A).to_vec().unwrap())
+            .await
+            .unwrap();
+
+        // only the query is answered
+        assert_eq!(recv(&client).await.id(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_forward_truncates_large_udp_responses() {
+        let client = spawn_forwarder().await;
+
+        // without edns the response is limited to 512 bytes
+        client
+            .send(&query(1, "many.example.", RecordType::A).to_vec().unwrap())
+            .await
+            .unwrap();
+        let mut buf = vec![0; MAX_UDP_MESSAGE_SIZE];
+        let len = client.recv(&mut buf).await.unwrap();
+        assert!(len <= 512, "{len}");
+        let response = Message::from_vec(&buf[..len]).unwrap();
+        assert_eq!(response.id(), 1);
+        assert!(response.truncated());
+        let ips = answer_ips(&response);
+        assert!(!ips.is_empty() && ips.len() < 100, "{}", ips.len());
+
+        // with edns the advertised payload size is used
+        let mut edns = Edns::new();
+        edns.set_max_payload(4096);
+        let mut edns_query = query(2, "many.example.", RecordType::A);
+        edns_query.set_edns(edns);
+        client.send(&edns_query.to_vec().unwrap()).await.unwrap();
+        let response = recv(&client).await;
+        assert_eq!(response.id(), 2);
+        assert!(!response.truncated());
+        assert_eq!(answer_ips(&response).len(), 100);
+    }
+}
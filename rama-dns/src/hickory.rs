@@ -1,14 +1,17 @@
 //! dns using the [`hickory_resolver`] crate
 
-use crate::DnsResolver;
+use crate::{stream_lookup_result, DnsResolver, ReverseDnsResolver, SrvDnsResolver, SrvRecord};
+use futures_lite::{stream, Stream, StreamExt};
 use hickory_resolver::{
-    proto::rr::rdata::{A, AAAA},
+    error::{ResolveError, ResolveErrorKind},
+    proto::rr::rdata::{A, AAAA, PTR, SRV},
     Name, TokioAsyncResolver,
 };
-use rama_core::error::{ErrorContext, OpaqueError};
+use rama_core::error::{ErrorClass, ErrorContext, OpaqueError};
 use rama_net::address::Domain;
 use std::{
-    net::{Ipv4Addr, Ipv6Addr},
+    error::Error,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
     sync::{Arc, OnceLock},
 };
 
@@ -16,6 +19,14 @@ pub use hickory_resolver::config;
 
 #[derive(Debug, Clone)]
 /// [`DnsResolver`] using the [`hickory_resolver`] crate
+///
+/// The streaming lookups yield the records straight from the hickory lookup,
+/// without collecting them first. As hickory only completes a lookup once all its
+/// records are received, [`DnsResolver::ipv4_lookup_stream`] and
+/// [`DnsResolver::ipv6_lookup_stream`] start yielding once their lookup finished.
+/// [`DnsResolver::ip_lookup_stream`] issues the 'A' and 'AAAA' lookups
+/// as separate concurrent lookups, yielding the records of each one as soon as it completes,
+/// such that a caller can connect to the first address without waiting on the slowest lookup.
 pub struct HickoryDns(Arc<TokioAsyncResolver>);
 
 impl Default for HickoryDns {
@@ -126,6 +137,84 @@ impl DnsResolver for HickoryDns {
             .map(|AAAA(ip)| ip)
             .collect())
     }
+
+    fn ipv4_lookup_stream(
+        &self,
+        domain: Domain,
+    ) -> impl Stream<Item = Result<Ipv4Addr, Self::Error>> + Send + '_
+    where
+        Self::Error: Send,
+    {
+        stream::once_future(async move {
+            let name = fqdn_from_domain(domain)?;
+            self.0
+                .ipv4_lookup(name)
+                .await
+                .context("lookup IPv4 address(es)")
+        })
+        .flat_map(|result| {
+            stream_lookup_result(result.map(|lookup| lookup.into_iter().map(|A(ip)| ip)))
+        })
+    }
+
+    fn ipv6_lookup_stream(
+        &self,
+        domain: Domain,
+    ) -> impl Stream<Item = Result<Ipv6Addr, Self::Error>> + Send + '_
+    where
+        Self::Error: Send,
+    {
+        stream::once_future(async move {
+            let name = fqdn_from_domain(domain)?;
+            self.0
+                .ipv6_lookup(name)
+                .await
+                .context("lookup IPv6 address(es)")
+        })
+        .flat_map(|result| {
+            stream_lookup_result(result.map(|lookup| lookup.into_iter().map(|AAAA(ip)| ip)))
+        })
+    }
+}
+
+impl SrvDnsResolver for HickoryDns {
+    async fn srv_lookup(&self, name: Domain) -> Result<Vec<SrvRecord>, Self::Error> {
+        let name = fqdn_from_domain(name)?;
+        self.0
+            .srv_lookup(name)
+            .await
+            .context("lookup SRV record(s)")?
+            .into_iter()
+            // a target of "." means the service is decidedly not available (RFC 2782)
+            .filter(|srv| !srv.target().is_root())
+            .map(|srv: SRV| {
+                let target = Domain::try_from(srv.target().to_utf8())
+                    .context("try to convert a SRV target Dns Name into a Domain")?;
+                Ok(SrvRecord::new(
+                    srv.priority(),
+                    srv.weight(),
+                    srv.port(),
+                    target,
+                ))
+            })
+            .collect()
+    }
+}
+
+impl ReverseDnsResolver for HickoryDns {
+    type Error = OpaqueError;
+
+    async fn reverse_lookup(&self, ip: IpAddr) -> Result<Vec<Domain>, Self::Error> {
+        self.0
+            .reverse_lookup(ip)
+            .await
+            .context("reverse lookup domain(s)")?
+            .into_iter()
+            .map(|PTR(name)| {
+                Domain::try_from(name.to_utf8()).context("try to convert a Dns Name into a Domain")
+            })
+            .collect()
+    }
 }
 
 fn fqdn_from_domain(domain: Domain) -> Result<Name, OpaqueError> {
@@ -133,3 +222,49 @@ fn fqdn_from_domain(domain: Domain) -> Result<Name, OpaqueError> {
     name.set_fqdn(true);
     Ok(name)
 }
+
+/// Classify the [`ResolveError`]s of the [`hickory_resolver`] crate,
+/// as returned (wrapped) by [`HickoryDns`], returning `None` for all other errors.
+///
+/// Register it in an [`ErrorClassifier`] using [`ErrorClassifier::with_classify_fn`].
+/// Timeouts are classified as [`ErrorClass::Timeout`],
+/// all other resolve errors as [`ErrorClass::Dns`].
+///
+/// [`ErrorClassifier`]: rama_core::error::ErrorClassifier
+/// [`ErrorClassifier::with_classify_fn`]: rama_core::error::ErrorClassifier::with_classify_fn
+pub fn classify_error(err: &(dyn Error + 'static)) -> Option<ErrorClass> {
+    let err = err.downcast_ref::<ResolveError>()?;
+    Some(match err.kind() {
+        ResolveErrorKind::Timeout => ErrorClass::Timeout,
+        ResolveErrorKind::Io(err) if err.kind() == std::io::ErrorKind::TimedOut => {
+            ErrorClass::Timeout
+        }
+        _ => ErrorClass::Dns,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::error::ErrorClassifier;
+
+    #[test]
+    fn test_classify_error() {
+        let classifier = ErrorClassifier::new().with_classify_fn(classify_error);
+
+        for (kind, expected) in [
+            (ResolveErrorKind::NoConnections, ErrorClass::Dns),
+            (ResolveErrorKind::Timeout, ErrorClass::Timeout),
+            (ResolveErrorKind::Message("bad response"), ErrorClass::Dns),
+        ] {
+            // as returned by HickoryDns
+            let err = Err::<(), _>(ResolveError::from(kind))
+                .context("lookup IPv4 address(es)")
+                .unwrap_err();
+            assert_eq!(classifier.classify(&err), expected);
+        }
+
+        let err = OpaqueError::from_display("not a dns error");
+        assert_eq!(classifier.classify(&err), ErrorClass::Application);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::DnsResolver;
+use crate::{DnsResolver, SrvDnsResolver, SrvRecord};
 use rama_net::address::Domain;
 use rama_utils::macros::{error::static_str_error, impl_deref};
 use serde::{Deserialize, Serialize};
@@ -17,6 +17,12 @@ pub struct DnsOverwrite(InMemoryDns);
 
 impl_deref! {DnsOverwrite: InMemoryDns}
 
+impl From<InMemoryDns> for DnsOverwrite {
+    fn from(dns: InMemoryDns) -> Self {
+        Self(dns)
+    }
+}
+
 impl<'de> Deserialize<'de> for DnsOverwrite {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -25,6 +31,7 @@ impl<'de> Deserialize<'de> for DnsOverwrite {
         let map = HashMap::<Domain, Vec<IpAddr>>::deserialize(deserializer)?;
         Ok(DnsOverwrite(InMemoryDns {
             map: (!map.is_empty()).then_some(map),
+            srv_map: None,
         }))
     }
 }
@@ -46,6 +53,7 @@ impl Serialize for DnsOverwrite {
 /// or wrapped in [`DnsOverwrite`] to indicate dns overwrites.
 pub struct InMemoryDns {
     map: Option<HashMap<Domain, Vec<IpAddr>>>,
+    srv_map: Option<HashMap<Domain, Vec<SrvRecord>>>,
 }
 
 impl InMemoryDns {
@@ -94,6 +102,20 @@ impl InMemoryDns {
         self.map.get_or_insert_with(HashMap::new).extend(overwrites);
         self
     }
+
+    /// Inserts a service name to [`SrvRecord`]s mapping to the [`InMemoryDns`].
+    ///
+    /// Existing mappings will be overwritten.
+    pub fn insert_srv_records<I: IntoIterator<Item = SrvRecord>>(
+        &mut self,
+        name: Domain,
+        records: I,
+    ) -> &mut Self {
+        self.srv_map
+            .get_or_insert_with(HashMap::new)
+            .insert(name, records.into_iter().collect());
+        self
+    }
 }
 
 static_str_error! {
@@ -139,6 +161,16 @@ impl DnsResolver for InMemoryDns {
     }
 }
 
+impl SrvDnsResolver for InMemoryDns {
+    async fn srv_lookup(&self, name: Domain) -> Result<Vec<SrvRecord>, Self::Error> {
+        self.srv_map
+            .as_ref()
+            .and_then(|m| m.get(&name))
+            .and_then(|records| (!records.is_empty()).then(|| records.clone()))
+            .ok_or(DomainNotMappedErr)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::net::{Ipv4Addr, Ipv6Addr};
@@ -254,4 +286,67 @@ mod tests {
             .await
             .is_err());
     }
+
+    #[tokio::test]
+    async fn test_in_memory_dns_srv_lookup() {
+        let records = [
+            SrvRecord::new(10, 60, 5060, Domain::from_static("sip1.example.com")),
+            SrvRecord::new(20, 0, 5061, Domain::from_static("sip2.example.com")),
+        ];
+
+        let mut dns = InMemoryDns::new();
+        assert!(dns
+            .srv_lookup(Domain::from_static("_sip._tcp.example.com"))
+            .await
+            .is_err());
+
+        dns.insert_srv_records(
+            Domain::from_static("_sip._tcp.example.com"),
+            records.clone(),
+        );
+        assert_eq!(
+            dns.srv_lookup(Domain::from_static("_sip._tcp.example.com"))
+                .await
+                .unwrap(),
+            records
+        );
+        assert!(dns
+            .srv_lookup(Domain::from_static("_sip._udp.example.com"))
+            .await
+            .is_err());
+        // SRV records do not resolve into addresses
+        assert!(dns
+            .ipv4_lookup(Domain::from_static("_sip._tcp.example.com"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dns_overwrite_srv_lookup() {
+        let mut dns_overwrite: DnsOverwrite =
+            serde_html_form::from_str("example.com=127.0.0.1").unwrap();
+        dns_overwrite.insert_srv_records(
+            Domain::from_static("_http._tcp.example.com"),
+            [SrvRecord::new(
+                0,
+                0,
+                8080,
+                Domain::from_static("example.com"),
+            )],
+        );
+
+        let records = dns_overwrite
+            .srv_lookup(Domain::from_static("_http._tcp.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].port(), 8080);
+        assert_eq!(
+            dns_overwrite
+                .ipv4_lookup(records[0].target().clone())
+                .await
+                .unwrap(),
+            [Ipv4Addr::new(127, 0, 0, 1)]
+        );
+    }
 }
@@ -17,12 +17,16 @@
 #![cfg_attr(test, allow(clippy::float_cmp))]
 #![cfg_attr(not(test), warn(clippy::print_stdout, clippy::dbg_macro))]
 
+use futures_lite::{stream, Stream, StreamExt};
+use pin_project_lite::pin_project;
 use rama_core::error::BoxError;
 use rama_net::address::Domain;
 use std::{
     future::Future,
-    net::{Ipv4Addr, Ipv6Addr},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    pin::Pin,
     sync::Arc,
+    task::{Context as TaskContext, Poll},
 };
 
 /// A resolver of domains into IP addresses.
@@ -41,6 +45,152 @@ pub trait DnsResolver: Send + Sync + 'static {
         &self,
         domain: Domain,
     ) -> impl Future<Output = Result<Vec<Ipv6Addr>, Self::Error>> + Send + '_;
+
+    /// Resolve the 'A' records accessible by this resolver for the given [`Domain`]
+    /// into a [`Stream`] of [`Ipv4Addr`]esses.
+    ///
+    /// This allows callers which are only interested in the first address(es)
+    /// to drop the stream early, e.g. to connect as soon as an address is known.
+    ///
+    /// The default implementation yields the addresses
+    /// resolved by [`DnsResolver::ipv4_lookup`].
+    fn ipv4_lookup_stream(
+        &self,
+        domain: Domain,
+    ) -> impl Stream<Item = Result<Ipv4Addr, Self::Error>> + Send + '_
+    where
+        Self::Error: Send,
+    {
+        stream::once_future(self.ipv4_lookup(domain)).flat_map(stream_lookup_result)
+    }
+
+    /// Resolve the 'AAAA' records accessible by this resolver for the given [`Domain`]
+    /// into a [`Stream`] of [`Ipv6Addr`]esses.
+    ///
+    /// This allows callers which are only interested in the first address(es)
+    /// to drop the stream early, e.g. to connect as soon as an address is known.
+    ///
+    /// The default implementation yields the addresses
+    /// resolved by [`DnsResolver::ipv6_lookup`].
+    fn ipv6_lookup_stream(
+        &self,
+        domain: Domain,
+    ) -> impl Stream<Item = Result<Ipv6Addr, Self::Error>> + Send + '_
+    where
+        Self::Error: Send,
+    {
+        stream::once_future(self.ipv6_lookup(domain)).flat_map(stream_lookup_result)
+    }
+
+    /// Resolve both the 'AAAA' and 'A' records accessible by this resolver
+    /// for the given [`Domain`] into a [`Stream`] of [`IpAddr`]esses.
+    ///
+    /// Both lookups run concurrently, and the addresses of each lookup
+    /// are yielded as soon as that lookup completes, without waiting on the other one.
+    /// IPv6 addresses are yielded first in case both are available at the same time.
+    /// A failed lookup yields its error, while the other lookup continues.
+    ///
+    /// The default implementation merges [`DnsResolver::ipv6_lookup_stream`]
+    /// and [`DnsResolver::ipv4_lookup_stream`].
+    fn ip_lookup_stream(
+        &self,
+        domain: Domain,
+    ) -> impl Stream<Item = Result<IpAddr, Self::Error>> + Send + '_
+    where
+        Self::Error: Send,
+    {
+        let ipv6 = self
+            .ipv6_lookup_stream(domain.clone())
+            .map(|result| result.map(IpAddr::V6));
+        let ipv4 = self
+            .ipv4_lookup_stream(domain)
+            .map(|result| result.map(IpAddr::V4));
+        MergeStream {
+            preferred: Some(ipv6),
+            other: Some(ipv4),
+        }
+    }
+}
+
+/// A [`DnsResolver`] which can also resolve 'SRV' records,
+/// e.g. for service discovery.
+pub trait SrvDnsResolver: DnsResolver {
+    /// Resolve the 'SRV' records accessible by this resolver for the given [`Domain`] into [`SrvRecord`]s.
+    ///
+    /// The [`Domain`] is expected to be the full service name,
+    /// e.g. `_sip._tcp.example.com`.
+    fn srv_lookup(
+        &self,
+        name: Domain,
+    ) -> impl Future<Output = Result<Vec<SrvRecord>, Self::Error>> + Send + '_;
+}
+
+/// Turn the result of a lookup into a [`Stream`] of its records,
+/// or a single error in case the lookup failed.
+fn stream_lookup_result<I, T, E>(
+    result: Result<I, E>,
+) -> stream::Iter<impl Iterator<Item = Result<T, E>>>
+where
+    I: IntoIterator<Item = T>,
+{
+    let (records, err) = match result {
+        Ok(records) => (Some(records), None),
+        Err(err) => (None, Some(err)),
+    };
+    stream::iter(
+        records
+            .into_iter()
+            .flatten()
+            .map(Ok)
+            .chain(err.into_iter().map(Err)),
+    )
+}
+
+pin_project! {
+    /// A [`Stream`] yielding the items of two streams as soon as they are available,
+    /// preferring the items of the `preferred` stream when both are ready.
+    ///
+    /// Unlike [`StreamExt::or`] it only ends once both streams ended.
+    struct MergeStream<S1, S2> {
+        #[pin]
+        preferred: Option<S1>,
+        #[pin]
+        other: Option<S2>,
+    }
+}
+
+impl<S1, S2, T> Stream for MergeStream<S1, S2>
+where
+    S1: Stream<Item = T>,
+    S2: Stream<Item = T>,
+{
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<T>> {
+        let mut this = self.project();
+
+        let mut pending = false;
+        if let Some(stream) = this.preferred.as_mut().as_pin_mut() {
+            match stream.poll_next(cx) {
+                Poll::Ready(Some(item)) => return Poll::Ready(Some(item)),
+                Poll::Ready(None) => this.preferred.set(None),
+                Poll::Pending => pending = true,
+            }
+        }
+        if let Some(stream) = this.other.as_mut().as_pin_mut() {
+            match stream.poll_next(cx) {
+                Poll::Ready(Some(item)) => return Poll::Ready(Some(item)),
+                Poll::Ready(None) => this.other.set(None),
+                Poll::Pending => pending = true,
+            }
+        }
+
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(None)
+        }
+    }
 }
 
 impl<R: DnsResolver> DnsResolver for Arc<R> {
@@ -59,6 +209,36 @@ impl<R: DnsResolver> DnsResolver for Arc<R> {
     ) -> impl Future<Output = Result<Vec<Ipv6Addr>, Self::Error>> + Send + '_ {
         (**self).ipv6_lookup(domain)
     }
+
+    fn ipv4_lookup_stream(
+        &self,
+        domain: Domain,
+    ) -> impl Stream<Item = Result<Ipv4Addr, Self::Error>> + Send + '_
+    where
+        Self::Error: Send,
+    {
+        (**self).ipv4_lookup_stream(domain)
+    }
+
+    fn ipv6_lookup_stream(
+        &self,
+        domain: Domain,
+    ) -> impl Stream<Item = Result<Ipv6Addr, Self::Error>> + Send + '_
+    where
+        Self::Error: Send,
+    {
+        (**self).ipv6_lookup_stream(domain)
+    }
+
+    fn ip_lookup_stream(
+        &self,
+        domain: Domain,
+    ) -> impl Stream<Item = Result<IpAddr, Self::Error>> + Send + '_
+    where
+        Self::Error: Send,
+    {
+        (**self).ip_lookup_stream(domain)
+    }
 }
 
 impl<R: DnsResolver<Error: Into<BoxError>>> DnsResolver for Option<R> {
@@ -79,6 +259,51 @@ impl<R: DnsResolver<Error: Into<BoxError>>> DnsResolver for Option<R> {
     }
 }
 
+impl<R: SrvDnsResolver> SrvDnsResolver for Arc<R> {
+    fn srv_lookup(
+        &self,
+        name: Domain,
+    ) -> impl Future<Output = Result<Vec<SrvRecord>, Self::Error>> + Send + '_ {
+        (**self).srv_lookup(name)
+    }
+}
+
+impl<R: SrvDnsResolver<Error: Into<BoxError>>> SrvDnsResolver for Option<R> {
+    async fn srv_lookup(&self, name: Domain) -> Result<Vec<SrvRecord>, Self::Error> {
+        match self {
+            Some(d) => d.srv_lookup(name).await.map_err(Into::into),
+            None => Err(DomainNotMappedErr.into()),
+        }
+    }
+}
+
+/// A resolver of IP addresses into domains, using reverse (DNS) lookups.
+pub trait ReverseDnsResolver: Send + Sync + 'static {
+    /// Error returned by the [`ReverseDnsResolver`]
+    type Error;
+
+    /// Resolve the 'PTR' records accessible by this resolver for the given [`IpAddr`] into [`Domain`]s.
+    fn reverse_lookup(
+        &self,
+        ip: IpAddr,
+    ) -> impl Future<Output = Result<Vec<Domain>, Self::Error>> + Send + '_;
+}
+
+impl<R: ReverseDnsResolver> ReverseDnsResolver for Arc<R> {
+    type Error = R::Error;
+
+    fn reverse_lookup(
+        &self,
+        ip: IpAddr,
+    ) -> impl Future<Output = Result<Vec<Domain>, Self::Error>> + Send + '_ {
+        (**self).reverse_lookup(ip)
+    }
+}
+
+mod srv;
+#[doc(inline)]
+pub use srv::SrvRecord;
+
 pub mod hickory;
 #[doc(inline)]
 pub use hickory::HickoryDns;
@@ -91,6 +316,135 @@ mod deny_all;
 #[doc(inline)]
 pub use deny_all::{DenyAllDns, DnsDeniedError};
 
+mod allowlist;
+#[doc(inline)]
+pub use allowlist::AllowlistDns;
+
 pub mod chain;
 
+pub mod cache;
+#[doc(inline)]
+pub use cache::CachingDnsResolver;
+
+mod observed;
+#[doc(inline)]
+pub use observed::ObservedDnsResolver;
+
+pub mod forward;
+#[doc(inline)]
+pub use forward::DnsForwardService;
+
 mod variant;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ipv4_lookup_stream_default() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_addresses(
+            Domain::from_static("example.com"),
+            [Ipv4Addr::new(127, 0, 0, 1), Ipv4Addr::new(127, 0, 0, 2)],
+        );
+
+        let addresses: Vec<_> = dns
+            .ipv4_lookup_stream(Domain::from_static("example.com"))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            addresses,
+            [Ipv4Addr::new(127, 0, 0, 1), Ipv4Addr::new(127, 0, 0, 2)]
+        );
+
+        let mut stream = std::pin::pin!(dns.ipv4_lookup_stream(Domain::from_static("example.com")));
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            Ipv4Addr::new(127, 0, 0, 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ipv6_lookup_stream_default_err() {
+        let dns = DenyAllDns::new();
+
+        let mut stream = std::pin::pin!(dns.ipv6_lookup_stream(Domain::from_static("example.com")));
+        assert!(stream.next().await.unwrap().is_err());
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ip_lookup_stream_yields_each_lookup_once_completed() {
+        struct SlowIpv6Dns;
+
+        impl DnsResolver for SlowIpv6Dns {
+            type Error = BoxError;
+
+            async fn ipv4_lookup(&self, _domain: Domain) -> Result<Vec<Ipv4Addr>, Self::Error> {
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                Ok(vec![Ipv4Addr::LOCALHOST])
+            }
+
+            async fn ipv6_lookup(&self, _domain: Domain) -> Result<Vec<Ipv6Addr>, Self::Error> {
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                Ok(vec![Ipv6Addr::LOCALHOST])
+            }
+        }
+
+        let mut stream =
+            std::pin::pin!(SlowIpv6Dns.ip_lookup_stream(Domain::from_static("example.com")));
+
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        );
+
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            IpAddr::V6(Ipv6Addr::LOCALHOST)
+        );
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ip_lookup_stream_continues_after_failed_lookup() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_addresses(
+            Domain::from_static("example.com"),
+            [Ipv4Addr::new(127, 0, 0, 1)],
+        );
+
+        let results: Vec<_> = dns
+            .ip_lookup_stream(Domain::from_static("example.com"))
+            .collect()
+            .await;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().any(Result::is_err));
+        assert!(results.iter().any(
+            |result| matches!(result, Ok(ip) if *ip == IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_srv_lookup_wrappers_forward() {
+        let name = Domain::from_static("_sip._tcp.example.com");
+        let mut dns = InMemoryDns::new();
+        dns.insert_srv_records(
+            name.clone(),
+            [SrvRecord::new(
+                10,
+                5,
+                5060,
+                Domain::from_static("sip.example.com"),
+            )],
+        );
+
+        let records = Some(Arc::new(dns)).srv_lookup(name.clone()).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].port(), 5060);
+
+        let err = None::<InMemoryDns>.srv_lookup(name).await.unwrap_err();
+        assert!(err.downcast_ref::<DomainNotMappedErr>().is_some());
+    }
+}
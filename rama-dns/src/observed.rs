@@ -0,0 +1,538 @@
+//! Observability for [`DnsResolver`]s.
+//!
+//! [`ObservedDnsResolver`] wraps any [`DnsResolver`], emitting a tracing span per lookup,
+//! and recording OpenTelemetry metrics when the `telemetry` feature is enabled.
+
+use crate::{
+    cache::NegativeCachedError, DnsDeniedError, DnsResolver, DomainNotMappedErr, SrvDnsResolver,
+    SrvRecord,
+};
+use hickory_resolver::error::{ResolveError, ResolveErrorKind};
+use rama_core::error::{BoxError, OpaqueError};
+use rama_net::address::Domain;
+use std::{
+    any::Any,
+    error::Error,
+    fmt,
+    future::Future,
+    io,
+    net::{Ipv4Addr, Ipv6Addr},
+    time::Instant,
+};
+use tracing::{field::Empty, Instrument};
+
+#[cfg(feature = "telemetry")]
+use {
+    rama_core::telemetry::opentelemetry::{
+        global,
+        metrics::{Counter, Histogram, Meter},
+        semantic_conventions::{
+            self,
+            resource::{SERVICE_NAME, SERVICE_VERSION},
+        },
+        InstrumentationScope, KeyValue, MeterOptions, ServiceInfo,
+    },
+    std::{borrow::Cow, sync::Arc},
+};
+
+#[cfg(feature = "telemetry")]
+const DNS_LOOKUPS: &str = "dns.client.lookups";
+#[cfg(feature = "telemetry")]
+const DNS_LOOKUP_ERRORS: &str = "dns.client.lookup_errors";
+#[cfg(feature = "telemetry")]
+const DNS_LOOKUP_DURATION: &str = "dns.client.lookup_duration";
+#[cfg(feature = "telemetry")]
+const DNS_RECORD_TYPE: &str = "dns.record_type";
+#[cfg(feature = "telemetry")]
+const ERROR_TYPE: &str = "error.type";
+
+/// Records dns lookup metrics
+#[cfg(feature = "telemetry")]
+#[derive(Clone, Debug)]
+struct Metrics {
+    lookups: Counter<u64>,
+    lookup_errors: Counter<u64>,
+    lookup_duration: Histogram<f64>,
+}
+
+#[cfg(feature = "telemetry")]
+impl Metrics {
+    fn new(meter: Meter, prefix: Option<String>) -> Self {
+        let name = |name: &'static str| match &prefix {
+            Some(prefix) => Cow::Owned(format!("{prefix}.{name}")),
+            None => Cow::Borrowed(name),
+        };
+
+        let lookups = meter
+            .u64_counter(name(DNS_LOOKUPS))
+            .with_description("measures the number of dns lookups")
+            .build();
+
+        let lookup_errors = meter
+            .u64_counter(name(DNS_LOOKUP_ERRORS))
+            .with_description("measures the number of failed dns lookups")
+            .build();
+
+        let lookup_duration = meter
+            .f64_histogram(name(DNS_LOOKUP_DURATION))
+            .with_description("Measures the duration of dns lookups.")
+            .with_unit("s")
+            .build();
+
+        Self {
+            lookups,
+            lookup_errors,
+            lookup_duration,
+        }
+    }
+}
+
+#[cfg(feature = "telemetry")]
+fn get_versioned_meter() -> Meter {
+    global::meter_with_scope(
+        InstrumentationScope::builder(const_format::formatcp!(
+            "{}-dns-client",
+            rama_utils::info::NAME
+        ))
+        .with_version(rama_utils::info::VERSION)
+        .with_schema_url(semantic_conventions::SCHEMA_URL)
+        .build(),
+    )
+}
+
+/// A [`DnsResolver`] wrapper which observes the lookups of the inner [`DnsResolver`].
+///
+/// A `dns.lookup` tracing span is emitted for each lookup, with the following fields:
+///
+/// - `dns.domain`: the domain that is looked up;
+/// - `dns.record_type`: the record type that is looked up (`A` or `AAAA`);
+/// - `dns.result_count`: the number of addresses found, in case of success;
+/// - `dns.error`: the error, in case of failure;
+/// - `dns.error_kind`: the kind of error, in case of failure (see below);
+/// - `dns.duration_ms`: the duration of the lookup, in milliseconds.
+///
+/// When the `telemetry` feature is enabled, the number of lookups, failed lookups
+/// and the duration of the lookups are also recorded as OpenTelemetry metrics,
+/// using the global [`Meter`] provider. All metrics have a `dns.record_type` attribute,
+/// and failed lookups are counted by kind of error using the `error.type` attribute.
+///
+/// The kind of error is one of `no_records`, `timeout`, `io`, `proto`,
+/// `no_connections`, `denied`, `cached` (a cached negative answer) or `other`,
+/// and is derived from the error (chain) returned by the inner [`DnsResolver`].
+///
+/// [`Meter`]: rama_core::telemetry::opentelemetry::metrics::Meter
+pub struct ObservedDnsResolver<R> {
+    inner: R,
+    #[cfg(feature = "telemetry")]
+    metrics: Arc<Metrics>,
+    #[cfg(feature = "telemetry")]
+    base_attributes: Vec<KeyValue>,
+}
+
+impl<R: fmt::Debug> fmt::Debug for ObservedDnsResolver<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("ObservedDnsResolver");
+        d.field("inner", &self.inner);
+        #[cfg(feature = "telemetry")]
+        d.field("metrics", &self.metrics)
+            .field("base_attributes", &self.base_attributes);
+        d.finish()
+    }
+}
+
+impl<R: Clone> Clone for ObservedDnsResolver<R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            #[cfg(feature = "telemetry")]
+            metrics: self.metrics.clone(),
+            #[cfg(feature = "telemetry")]
+            base_attributes: self.base_attributes.clone(),
+        }
+    }
+}
+
+impl<R> ObservedDnsResolver<R> {
+    #[cfg(not(feature = "telemetry"))]
+    /// Create a new [`ObservedDnsResolver`] observing the given [`DnsResolver`].
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    #[cfg(feature = "telemetry")]
+    /// Create a new [`ObservedDnsResolver`] observing the given [`DnsResolver`],
+    /// recording metrics using the global [`Meter`] provider with the default name and version.
+    pub fn new(inner: R) -> Self {
+        Self::custom(inner, MeterOptions::default())
+    }
+
+    #[cfg(feature = "telemetry")]
+    /// Create a new [`ObservedDnsResolver`] observing the given [`DnsResolver`],
+    /// recording metrics using the global [`Meter`] provider with a custom name and version.
+    pub fn custom(inner: R, opts: MeterOptions) -> Self {
+        let service_info = opts.service.unwrap_or_else(|| ServiceInfo {
+            name: rama_utils::info::NAME.to_owned(),
+            version: rama_utils::info::VERSION.to_owned(),
+        });
+
+        let mut attributes = opts.attributes.unwrap_or_else(|| Vec::with_capacity(3));
+        attributes.push(KeyValue::new(SERVICE_NAME, service_info.name));
+        attributes.push(KeyValue::new(SERVICE_VERSION, service_info.version));
+
+        Self {
+            inner,
+            metrics: Arc::new(Metrics::new(get_versioned_meter(), opts.metric_prefix)),
+            base_attributes: attributes,
+        }
+    }
+
+    /// Reference to the inner [`DnsResolver`].
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Consume `self`, returning the inner [`DnsResolver`].
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    #[cfg(feature = "telemetry")]
+    fn compute_attributes(&self, record_type: &'static str) -> Vec<KeyValue> {
+        let mut attributes = Vec::with_capacity(self.base_attributes.len() + 2);
+        attributes.extend(self.base_attributes.iter().cloned());
+        attributes.push(KeyValue::new(DNS_RECORD_TYPE, record_type));
+        attributes
+    }
+
+    async fn observe<T, E, F>(
+        &self,
+        domain: &Domain,
+        record_type: &'static str,
+        lookup: F,
+    ) -> Result<Vec<T>, E>
+    where
+        E: fmt::Display + 'static,
+        F: Future<Output = Result<Vec<T>, E>>,
+    {
+        let span = tracing::debug_span!(
+            "dns.lookup",
+            dns.domain = %domain,
+            dns.record_type = record_type,
+            dns.result_count = Empty,
+            dns.error = Empty,
+            dns.error_kind = Empty,
+            dns.duration_ms = Empty,
+        );
+
+        let start = Instant::now();
+        let result = lookup.instrument(span.clone()).await;
+        let duration = start.elapsed();
+
+        span.record("dns.duration_ms", duration.as_secs_f64() * 1000.0);
+        match &result {
+            Ok(addresses) => {
+                span.record("dns.result_count", addresses.len());
+            }
+            Err(err) => {
+                span.record("dns.error", tracing::field::display(err));
+                span.record("dns.error_kind", error_kind(err));
+            }
+        }
+
+        #[cfg(feature = "telemetry")]
+        {
+            let mut attributes = self.compute_attributes(record_type);
+            self.metrics.lookups.add(1, &attributes);
+            self.metrics
+                .lookup_duration
+                .record(duration.as_secs_f64(), &attributes);
+            if let Err(err) = &result {
+                attributes.push(KeyValue::new(ERROR_TYPE, error_kind(err)));
+                self.metrics.lookup_errors.add(1, &attributes);
+            }
+        }
+
+        result
+    }
+}
+
+/// Classify a dns lookup error, for use as a low-cardinality
+/// metric attribute and span field.
+///
+/// Only the errors of the [`DnsResolver`]s provided by this crate
+/// (and the errors they wrap) are recognised, all other errors are `other`.
+pub(crate) fn error_kind<E: 'static>(err: &E) -> &'static str {
+    let err: &dyn Any = err;
+    let mut next: Option<&(dyn Error + 'static)> =
+        if let Some(err) = err.downcast_ref::<OpaqueError>() {
+            Some(err)
+        } else if let Some(err) = err.downcast_ref::<BoxError>() {
+            Some(err.as_ref())
+        } else if let Some(err) = err.downcast_ref::<ResolveError>() {
+            Some(err)
+        } else if let Some(err) = err.downcast_ref::<DomainNotMappedErr>() {
+            Some(err)
+        } else if let Some(err) = err.downcast_ref::<DnsDeniedError>() {
+            Some(err)
+        } else {
+            err.downcast_ref::<NegativeCachedError>()
+                .map(|err| err as &(dyn Error + 'static))
+        };
+
+    let mut visited = Vec::new();
+    while let Some(err) = next {
+        if let Some(err) = err.downcast_ref::<ResolveError>() {
+            return match err.kind() {
+                ResolveErrorKind::NoRecordsFound { .. } => "no_records",
+                ResolveErrorKind::Timeout => "timeout",
+                ResolveErrorKind::Io(err) if err.kind() == io::ErrorKind::TimedOut => "timeout",
+                ResolveErrorKind::Io(_) => "io",
+                ResolveErrorKind::Proto(_) => "proto",
+                ResolveErrorKind::NoConnections => "no_connections",
+                _ => "other",
+            };
+        }
+        if let Some(err) = err.downcast_ref::<io::Error>() {
+            return match err.kind() {
+                io::ErrorKind::TimedOut => "timeout",
+                _ => "io",
+            };
+        }
+        if err.is::<DomainNotMappedErr>() {
+            return "no_records";
+        }
+        if err.is::<DnsDeniedError>() {
+            return "denied";
+        }
+        if err.is::<NegativeCachedError>() {
+            return "cached";
+        }
+        // some errors (e.g. those defined using `static_str_error!`)
+        // return themselves as their source, and error chains
+        // can in general be cyclic, so stop at the first revisited error
+        visited.push(err as *const dyn Error as *const ());
+        next = err
+            .source()
+            .filter(|source| !visited.contains(&(*source as *const dyn Error as *const ())));
+    }
+
+    "other"
+}
+
+impl<R> DnsResolver for ObservedDnsResolver<R>
+where
+    R: DnsResolver<Error: fmt::Display + 'static>,
+{
+    type Error = R::Error;
+
+    async fn ipv4_lookup(&self, domain: Domain) -> Result<Vec<Ipv4Addr>, Self::Error> {
+        self.observe(&domain, "A", self.inner.ipv4_lookup(domain.clone()))
+            .await
+    }
+
+    async fn ipv6_lookup(&self, domain: Domain) -> Result<Vec<Ipv6Addr>, Self::Error> {
+        self.observe(&domain, "AAAA", self.inner.ipv6_lookup(domain.clone()))
+            .await
+    }
+}
+
+impl<R> SrvDnsResolver for ObservedDnsResolver<R>
+where
+    R: SrvDnsResolver<Error: fmt::Display + 'static>,
+{
+    async fn srv_lookup(&self, name: Domain) -> Result<Vec<SrvRecord>, Self::Error> {
+        self.observe(&name, "SRV", self.inner.srv_lookup(name.clone()))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDns;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Subscriber,
+    };
+    use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+
+    type SpanFields = Arc<Mutex<HashMap<String, String>>>;
+
+    #[derive(Default, Clone)]
+    struct CaptureLayer {
+        fields: Arc<Mutex<Vec<SpanFields>>>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(field.name().to_owned(), format!("{value:?}"));
+        }
+    }
+
+    impl<S> Layer<S> for CaptureLayer
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            if attrs.metadata().name() != "dns.lookup" {
+                return;
+            }
+            let mut fields = HashMap::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            let fields = Arc::new(Mutex::new(fields));
+            self.fields.lock().unwrap().push(fields.clone());
+            ctx.span(id).unwrap().extensions_mut().insert(fields);
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+            if let Some(fields) = ctx.span(id).unwrap().extensions().get::<SpanFields>() {
+                values.record(&mut FieldVisitor(&mut fields.lock().unwrap()));
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_observed_dns_resolver_emits_spans() {
+        let layer = CaptureLayer::default();
+        let _guard = tracing_subscriber::registry()
+            .with(layer.clone())
+            .set_default();
+
+        let mut dns = InMemoryDns::new();
+        dns.insert_address(
+            Domain::from_static("example.com"),
+            Ipv4Addr::new(127, 0, 0, 1),
+        );
+        let resolver = ObservedDnsResolver::new(dns);
+
+        let ips = resolver
+            .ipv4_lookup(Domain::from_static("example.com"))
+            .await
+            .unwrap();
+        assert_eq!(ips, vec![Ipv4Addr::new(127, 0, 0, 1)]);
+        assert!(resolver
+            .ipv6_lookup(Domain::from_static("unknown.example"))
+            .await
+            .is_err());
+
+        let spans = layer.fields.lock().unwrap();
+        assert_eq!(spans.len(), 2);
+
+        let ok = spans[0].lock().unwrap();
+        assert_eq!(ok["dns.domain"], "example.com");
+        assert_eq!(ok["dns.record_type"], "\"A\"");
+        assert_eq!(ok["dns.result_count"], "1");
+        assert!(ok.contains_key("dns.duration_ms"));
+        assert!(!ok.contains_key("dns.error"));
+
+        let err = spans[1].lock().unwrap();
+        assert_eq!(err["dns.domain"], "unknown.example");
+        assert_eq!(err["dns.record_type"], "\"AAAA\"");
+        assert!(err.contains_key("dns.error"));
+        assert_eq!(err["dns.error_kind"], "\"no_records\"");
+        assert!(err.contains_key("dns.duration_ms"));
+        assert!(!err.contains_key("dns.result_count"));
+    }
+
+    #[test]
+    fn test_error_kind() {
+        use rama_core::error::ErrorContext;
+
+        assert_eq!(error_kind(&DomainNotMappedErr), "no_records");
+        assert_eq!(error_kind(&DnsDeniedError), "denied");
+        assert_eq!(error_kind(&NegativeCachedError), "cached");
+        assert_eq!(error_kind(&BoxError::from(NegativeCachedError)), "cached");
+        assert_eq!(
+            error_kind(&ResolveError::from(ResolveErrorKind::Timeout)),
+            "timeout"
+        );
+        assert_eq!(
+            error_kind(
+                &Err::<(), _>(ResolveError::from(ResolveErrorKind::NoConnections))
+                    .context("lookup IPv4 address(es)")
+                    .unwrap_err()
+            ),
+            "no_connections"
+        );
+        assert_eq!(
+            error_kind(
+                &Err::<(), _>(ResolveError::from(io::Error::from(
+                    io::ErrorKind::ConnectionRefused
+                )))
+                .context("lookup IPv6 address(es)")
+                .unwrap_err()
+            ),
+            "io"
+        );
+        assert_eq!(
+            error_kind(&OpaqueError::from_display("something went wrong")),
+            "other"
+        );
+        assert_eq!(
+            error_kind(&BoxError::from(rama_net::asn::InvalidAsn::new())),
+            "other"
+        );
+        assert_eq!(error_kind(&"not an error"), "other");
+    }
+
+    #[test]
+    fn test_error_kind_cyclic_source() {
+        #[derive(Debug)]
+        struct Cyclic(&'static Cyclic);
+
+        impl fmt::Display for Cyclic {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("cyclic error")
+            }
+        }
+
+        impl Error for Cyclic {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                Some(self.0)
+            }
+        }
+
+        // a -> b -> a
+        static A: Cyclic = Cyclic(&B);
+        static B: Cyclic = Cyclic(&A);
+        assert_eq!(error_kind(&BoxError::from(&A)), "other");
+
+        // a -> a
+        static C: Cyclic = Cyclic(&C);
+        assert_eq!(error_kind(&BoxError::from(&C)), "other");
+    }
+
+    #[cfg(feature = "telemetry")]
+    #[test]
+    fn test_observed_dns_resolver_metric_attributes() {
+        let resolver = ObservedDnsResolver::custom(
+            InMemoryDns::new(),
+            MeterOptions {
+                service: Some(ServiceInfo {
+                    name: "test".to_owned(),
+                    version: "42".to_owned(),
+                }),
+                metric_prefix: Some("foo".to_owned()),
+                ..Default::default()
+            },
+        );
+
+        let attributes = resolver.compute_attributes("AAAA");
+        assert!(attributes
+            .iter()
+            .any(|attr| attr.key.as_str() == SERVICE_NAME && attr.value.as_str() == "test"));
+        assert!(attributes
+            .iter()
+            .any(|attr| attr.key.as_str() == SERVICE_VERSION && attr.value.as_str() == "42"));
+        assert!(attributes
+            .iter()
+            .any(|attr| attr.key.as_str() == DNS_RECORD_TYPE && attr.value.as_str() == "AAAA"));
+    }
+}
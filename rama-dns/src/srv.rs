@@ -0,0 +1,53 @@
+use rama_net::address::Domain;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A DNS 'SRV' record, as defined in [RFC 2782].
+///
+/// It locates the host and port of a service,
+/// e.g. for the `_sip._tcp.example.com` service name.
+///
+/// [RFC 2782]: https://datatracker.ietf.org/doc/html/rfc2782
+pub struct SrvRecord {
+    priority: u16,
+    weight: u16,
+    port: u16,
+    target: Domain,
+}
+
+impl SrvRecord {
+    /// Create a new [`SrvRecord`].
+    pub const fn new(priority: u16, weight: u16, port: u16, target: Domain) -> Self {
+        Self {
+            priority,
+            weight,
+            port,
+            target,
+        }
+    }
+
+    /// The priority of the target host, lower values are to be tried first.
+    pub fn priority(&self) -> u16 {
+        self.priority
+    }
+
+    /// The relative weight of the target host,
+    /// used to select among targets of the same priority.
+    pub fn weight(&self) -> u16 {
+        self.weight
+    }
+
+    /// The port of the service on the target host.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// The [`Domain`] of the target host.
+    pub fn target(&self) -> &Domain {
+        &self.target
+    }
+
+    /// Consume `self`, returning the [`Domain`] of the target host.
+    pub fn into_target(self) -> Domain {
+        self.target
+    }
+}
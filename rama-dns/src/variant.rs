@@ -1,4 +1,4 @@
-use crate::DnsResolver;
+use crate::{DnsResolver, SrvDnsResolver, SrvRecord};
 use rama_net::address::Domain;
 use std::net::{Ipv4Addr, Ipv6Addr};
 
@@ -41,9 +41,33 @@ macro_rules! impl_dns_resolver_either_either {
 
 rama_core::combinators::impl_either!(impl_dns_resolver_either_either);
 
+macro_rules! impl_srv_dns_resolver_either_either {
+    ($id:ident, $($param:ident),+ $(,)?) => {
+        impl<$($param),+> SrvDnsResolver for ::rama_core::combinators::$id<$($param),+>
+        where
+            $($param: SrvDnsResolver<Error: Into<::rama_core::error::BoxError>>),+,
+        {
+            async fn srv_lookup(
+                &self,
+                name: Domain,
+            ) -> Result<Vec<SrvRecord>, Self::Error> {
+                match self {
+                    $(
+                        ::rama_core::combinators::$id::$param(d) => d.srv_lookup(name)
+                            .await
+                            .map_err(Into::into),
+                    )+
+                }
+            }
+        }
+    };
+}
+
+rama_core::combinators::impl_either!(impl_srv_dns_resolver_either_either);
+
 #[cfg(test)]
 mod tests {
-    use crate::DnsResolver;
+    use crate::{DnsResolver, SrvDnsResolver, SrvRecord};
     use rama_core::combinators::Either;
     use rama_core::error::BoxError;
     use rama_net::address::Domain;
@@ -72,6 +96,20 @@ mod tests {
         }
     }
 
+    impl SrvDnsResolver for MockResolver1 {
+        fn srv_lookup(
+            &self,
+            _name: Domain,
+        ) -> impl Future<Output = Result<Vec<SrvRecord>, Self::Error>> {
+            std::future::ready(Ok(vec![SrvRecord::new(
+                10,
+                5,
+                5060,
+                Domain::from_static("sip1.example.com"),
+            )]))
+        }
+    }
+
     impl DnsResolver for MockResolver2 {
         type Error = BoxError;
 
@@ -90,6 +128,20 @@ mod tests {
         }
     }
 
+    impl SrvDnsResolver for MockResolver2 {
+        fn srv_lookup(
+            &self,
+            _name: Domain,
+        ) -> impl Future<Output = Result<Vec<SrvRecord>, Self::Error>> + Send + '_ {
+            std::future::ready(Ok(vec![SrvRecord::new(
+                20,
+                10,
+                5061,
+                Domain::from_static("sip2.example.com"),
+            )]))
+        }
+    }
+
     #[tokio::test]
     async fn test_either_ipv4_lookup() {
         let resolver1 = Either::<MockResolver1, MockResolver2>::A(MockResolver1);
@@ -117,4 +169,24 @@ mod tests {
         let result2 = resolver2.ipv6_lookup(domain).await.unwrap();
         assert_eq!(result2, vec![Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 2)]);
     }
+
+    #[tokio::test]
+    async fn test_either_srv_lookup() {
+        let resolver1 = Either::<MockResolver1, MockResolver2>::A(MockResolver1);
+        let resolver2 = Either::<MockResolver1, MockResolver2>::B(MockResolver2);
+
+        let name = Domain::from_static("_sip._tcp.example.com");
+
+        let result1 = resolver1.srv_lookup(name.clone()).await.unwrap();
+        assert_eq!(
+            result1[0].target(),
+            &Domain::from_static("sip1.example.com")
+        );
+
+        let result2 = resolver2.srv_lookup(name).await.unwrap();
+        assert_eq!(
+            result2[0].target(),
+            &Domain::from_static("sip2.example.com")
+        );
+    }
 }
@@ -0,0 +1,302 @@
+use crate::OpaqueError;
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The class of an error, as used by retry, circuit-breaker and metric layers
+/// to make consistent decisions on (type-erased) errors.
+///
+/// See [`ErrorClassifier`] on how to classify an error.
+pub enum ErrorClass {
+    /// An operation timed out.
+    Timeout,
+    /// A connection was refused by the peer.
+    ConnectionRefused,
+    /// A DNS lookup failed.
+    Dns,
+    /// An operation was denied by a (local) policy,
+    /// e.g. a DNS lookup of a domain which is not allowed.
+    Denied,
+    /// A TLS handshake or TLS protocol failure.
+    Tls,
+    /// A protocol violation, e.g. an invalid http message.
+    Protocol,
+    /// Any other error, including all errors that could not be classified.
+    Application,
+}
+
+impl ErrorClass {
+    /// Returns `true` if errors of this class are (usually) transient,
+    /// meaning that the operation can be retried.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Timeout | Self::ConnectionRefused | Self::Dns)
+    }
+
+    /// Returns the class as a static string, e.g. for use as a metric label.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Timeout => "timeout",
+            Self::ConnectionRefused => "connection_refused",
+            Self::Dns => "dns",
+            Self::Denied => "denied",
+            Self::Tls => "tls",
+            Self::Protocol => "protocol",
+            Self::Application => "application",
+        }
+    }
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Implemented by error types which know their own [`ErrorClass`].
+///
+/// Register such a type in an [`ErrorClassifier`] using [`ErrorClassifier::with_error_type`]
+/// to have it classified when found in the chain of a (type-erased) error.
+pub trait ErrorKindClass {
+    /// The [`ErrorClass`] of this error,
+    /// or `None` to let the [`ErrorClassifier`] continue with its source.
+    fn error_class(&self) -> Option<ErrorClass>;
+}
+
+impl ErrorKindClass for io::Error {
+    fn error_class(&self) -> Option<ErrorClass> {
+        match self.kind() {
+            io::ErrorKind::TimedOut => Some(ErrorClass::Timeout),
+            io::ErrorKind::ConnectionRefused => Some(ErrorClass::ConnectionRefused),
+            _ => None,
+        }
+    }
+}
+
+type ClassifyFn = fn(&(dyn StdError + 'static)) -> Option<ErrorClass>;
+
+fn classify_type<E>(err: &(dyn StdError + 'static)) -> Option<ErrorClass>
+where
+    E: ErrorKindClass + StdError + 'static,
+{
+    err.downcast_ref::<E>()
+        .and_then(ErrorKindClass::error_class)
+}
+
+#[derive(Debug, Clone)]
+/// Classifies (type-erased) errors into an [`ErrorClass`],
+/// by downcasting the error and its sources to known error types.
+///
+/// The error chain is walked from the outer error to its root cause,
+/// and the first error of a known type that can be classified determines the class.
+/// [`ErrorClass::Application`] is returned if no such error is found.
+///
+/// [`std::io::Error`] is known by default, classified by its [`std::io::ErrorKind`].
+/// Other error types, such as the ones of the different rama crates,
+/// can be registered using [`ErrorClassifier::with_error_type`],
+/// or using [`ErrorClassifier::with_classify_fn`] for (foreign) error types
+/// which do not implement [`ErrorKindClass`].
+///
+/// # Example
+///
+/// ```
+/// use rama_error::{BoxError, ErrorClass, ErrorClassifier, ErrorContext};
+///
+/// let classifier = ErrorClassifier::new();
+///
+/// let err: Result<(), _> = Err(std::io::Error::from(std::io::ErrorKind::ConnectionRefused));
+/// let err: BoxError = err.context("connect to upstream").unwrap_err().into();
+///
+/// let class = classifier.classify(err.as_ref());
+/// assert_eq!(class, ErrorClass::ConnectionRefused);
+/// assert!(class.is_transient());
+/// ```
+pub struct ErrorClassifier {
+    rules: Vec<ClassifyFn>,
+}
+
+impl Default for ErrorClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ErrorClassifier {
+    /// Create a new [`ErrorClassifier`] which knows the [`std::io::Error`] type.
+    pub fn new() -> Self {
+        Self {
+            rules: vec![classify_type::<io::Error>],
+        }
+    }
+
+    /// Register an error type which knows its own [`ErrorClass`].
+    pub fn with_error_type<E>(mut self) -> Self
+    where
+        E: ErrorKindClass + StdError + 'static,
+    {
+        self.rules.push(classify_type::<E>);
+        self
+    }
+
+    /// Register an error type which knows its own [`ErrorClass`].
+    pub fn set_error_type<E>(&mut self) -> &mut Self
+    where
+        E: ErrorKindClass + StdError + 'static,
+    {
+        self.rules.push(classify_type::<E>);
+        self
+    }
+
+    /// Register a function classifying the errors it knows,
+    /// returning `None` for all other errors.
+    ///
+    /// This is useful for (foreign) error types which do not implement [`ErrorKindClass`].
+    pub fn with_classify_fn(
+        mut self,
+        classify: fn(&(dyn StdError + 'static)) -> Option<ErrorClass>,
+    ) -> Self {
+        self.rules.push(classify);
+        self
+    }
+
+    /// Register a function classifying the errors it knows,
+    /// returning `None` for all other errors.
+    ///
+    /// This is useful for (foreign) error types which do not implement [`ErrorKindClass`].
+    pub fn set_classify_fn(
+        &mut self,
+        classify: fn(&(dyn StdError + 'static)) -> Option<ErrorClass>,
+    ) -> &mut Self {
+        self.rules.push(classify);
+        self
+    }
+
+    /// Classify the given error.
+    pub fn classify(&self, err: &(dyn StdError + 'static)) -> ErrorClass {
+        self.classify_chain(err).unwrap_or(ErrorClass::Application)
+    }
+
+    fn classify_chain(&self, err: &(dyn StdError + 'static)) -> Option<ErrorClass> {
+        let mut next = Some(err);
+        while let Some(err) = next {
+            if let Some(class) = self.rules.iter().find_map(|rule| rule(err)) {
+                return Some(class);
+            }
+
+            // the sources of these wrapper errors skip the wrapped error itself
+            // in case it has a source of its own
+            let inner = match err.downcast_ref::<OpaqueError>() {
+                Some(err) => Some(err.as_dyn()),
+                None => err
+                    .downcast_ref::<io::Error>()
+                    .and_then(|err| err.get_ref())
+                    .map(|err| err as &(dyn StdError + 'static)),
+            };
+            if let Some(class) = inner.and_then(|inner| self.classify_chain(inner)) {
+                return Some(class);
+            }
+
+            next = err.source();
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoxError, ErrorExt};
+
+    #[derive(Debug)]
+    struct TlsError(Option<io::Error>);
+
+    impl fmt::Display for TlsError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("tls error")
+        }
+    }
+
+    impl StdError for TlsError {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            self.0.as_ref().map(|err| err as &(dyn StdError + 'static))
+        }
+    }
+
+    impl ErrorKindClass for TlsError {
+        fn error_class(&self) -> Option<ErrorClass> {
+            match &self.0 {
+                Some(_) => None,
+                None => Some(ErrorClass::Tls),
+            }
+        }
+    }
+
+    #[test]
+    fn test_classify_io_errors() {
+        let classifier = ErrorClassifier::new();
+        for (kind, expected) in [
+            (io::ErrorKind::TimedOut, ErrorClass::Timeout),
+            (
+                io::ErrorKind::ConnectionRefused,
+                ErrorClass::ConnectionRefused,
+            ),
+            (io::ErrorKind::Other, ErrorClass::Application),
+        ] {
+            let err: BoxError = io::Error::from(kind).into();
+            assert_eq!(classifier.classify(err.as_ref()), expected);
+
+            let err = io::Error::from(kind).context("foo").context("bar");
+            assert_eq!(classifier.classify(&err), expected);
+        }
+
+        let err = io::Error::other(io::Error::from(io::ErrorKind::TimedOut));
+        assert_eq!(classifier.classify(&err), ErrorClass::Timeout);
+    }
+
+    #[test]
+    fn test_classify_registered_error_type() {
+        let err: BoxError = TlsError(None).into();
+        assert_eq!(
+            ErrorClassifier::new().classify(err.as_ref()),
+            ErrorClass::Application
+        );
+
+        let classifier = ErrorClassifier::new().with_error_type::<TlsError>();
+        assert_eq!(classifier.classify(err.as_ref()), ErrorClass::Tls);
+        assert_eq!(
+            classifier.classify(&TlsError(None).context("handshake")),
+            ErrorClass::Tls
+        );
+
+        // unclassified by the outer error, so classified by its source
+        let err = TlsError(Some(io::ErrorKind::TimedOut.into())).into_opaque();
+        assert_eq!(classifier.classify(&err), ErrorClass::Timeout);
+    }
+
+    #[test]
+    fn test_classify_fn() {
+        fn classify(err: &(dyn StdError + 'static)) -> Option<ErrorClass> {
+            err.downcast_ref::<fmt::Error>()
+                .map(|_| ErrorClass::Protocol)
+        }
+
+        let err = fmt::Error.context("format");
+        assert_eq!(
+            ErrorClassifier::new().classify(&err),
+            ErrorClass::Application
+        );
+        let classifier = ErrorClassifier::new().with_classify_fn(classify);
+        assert_eq!(classifier.classify(&err), ErrorClass::Protocol);
+    }
+
+    #[test]
+    fn test_error_class_is_transient() {
+        assert!(ErrorClass::Timeout.is_transient());
+        assert!(ErrorClass::ConnectionRefused.is_transient());
+        assert!(ErrorClass::Dns.is_transient());
+        assert!(!ErrorClass::Denied.is_transient());
+        assert!(!ErrorClass::Tls.is_transient());
+        assert!(!ErrorClass::Protocol.is_transient());
+        assert!(!ErrorClass::Application.is_transient());
+    }
+}
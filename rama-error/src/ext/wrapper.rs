@@ -34,6 +34,11 @@ impl OpaqueError {
         self.0.is::<T>()
     }
 
+    /// Reference to the wrapped error.
+    pub(crate) fn as_dyn(&self) -> &(dyn std::error::Error + 'static) {
+        self.0.as_ref()
+    }
+
     /// Consumes the [`OpaqueError`] and returns it as a [`BoxError`].
     pub fn into_boxed(self) -> BoxError {
         self.0
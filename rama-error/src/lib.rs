@@ -63,6 +63,14 @@
 //! is that it is Sized and can be used in places where a `Sized`` type is required,
 //! while [`BoxError`] is `?Sized` and can give you a hard time in certain scenarios.
 //!
+//! ## Error Classification
+//!
+//! Middleware such as retry, circuit-breaker and metric layers often need to know
+//! what kind of error occurred, e.g. to decide whether it is transient.
+//! The [`ErrorClassifier`] classifies a (type-erased) error into an [`ErrorClass`]
+//! by walking its chain and downcasting to known error types,
+//! which implement [`ErrorKindClass`] to define their class.
+//!
 //! ## `error` macro
 //!
 //! The `error` macro is a convenient way to create an [`OpaqueError`]
@@ -193,6 +201,9 @@ pub type BoxError = Box<dyn StdError + Send + Sync>;
 mod ext;
 pub use ext::{ErrorContext, ErrorExt, OpaqueError};
 
+mod class;
+pub use class::{ErrorClass, ErrorClassifier, ErrorKindClass};
+
 mod macros;
 #[doc(inline)]
 pub use macros::error;
@@ -88,6 +88,32 @@ impl<P> HaProxyLayer<P> {
         self.version.payload = Some(payload);
         self
     }
+
+    /// Attach a Type-Length-Value (TLV) to the PROXY header.
+    ///
+    /// Multiple TLVs can be attached, and are written in insertion order,
+    /// prior to the custom payload (if any).
+    ///
+    /// NOTE this is only possible in Version two of the PROXY Protocol.
+    /// In case you downgrade this [`HaProxyLayer`] to version one later
+    /// using [`Self::v1`] these TLVs will be dropped.
+    pub fn tlv(mut self, kind: impl Into<u8>, value: impl Into<Vec<u8>>) -> Self {
+        self.version.tlvs.push((kind.into(), value.into()));
+        self
+    }
+
+    /// Attach a Type-Length-Value (TLV) to the PROXY header.
+    ///
+    /// Multiple TLVs can be attached, and are written in insertion order,
+    /// prior to the custom payload (if any).
+    ///
+    /// NOTE this is only possible in Version two of the PROXY Protocol.
+    /// In case you downgrade this [`HaProxyLayer`] to version one later
+    /// using [`Self::v1`] these TLVs will be dropped.
+    pub fn push_tlv(&mut self, kind: impl Into<u8>, value: impl Into<Vec<u8>>) -> &mut Self {
+        self.version.tlvs.push((kind.into(), value.into()));
+        self
+    }
 }
 
 impl<S, P, V: Clone> Layer<S> for HaProxyLayer<P, V> {
@@ -181,6 +207,32 @@ impl<S, P> HaProxyService<S, P> {
         self.version.payload = Some(payload);
         self
     }
+
+    /// Attach a Type-Length-Value (TLV) to the PROXY header.
+    ///
+    /// Multiple TLVs can be attached, and are written in insertion order,
+    /// prior to the custom payload (if any).
+    ///
+    /// NOTE this is only possible in Version two of the PROXY Protocol.
+    /// In case you downgrade this [`HaProxyService`] to version one later
+    /// using [`Self::v1`] these TLVs will be dropped.
+    pub fn tlv(mut self, kind: impl Into<u8>, value: impl Into<Vec<u8>>) -> Self {
+        self.version.tlvs.push((kind.into(), value.into()));
+        self
+    }
+
+    /// Attach a Type-Length-Value (TLV) to the PROXY header.
+    ///
+    /// Multiple TLVs can be attached, and are written in insertion order,
+    /// prior to the custom payload (if any).
+    ///
+    /// NOTE this is only possible in Version two of the PROXY Protocol.
+    /// In case you downgrade this [`HaProxyService`] to version one later
+    /// using [`Self::v1`] these TLVs will be dropped.
+    pub fn push_tlv(&mut self, kind: impl Into<u8>, value: impl Into<Vec<u8>>) -> &mut Self {
+        self.version.tlvs.push((kind.into(), value.into()));
+        self
+    }
 }
 
 impl<S: fmt::Debug, P, V: fmt::Debug> fmt::Debug for HaProxyService<S, P, V> {
@@ -319,6 +371,15 @@ where
             }
         };
 
+        let builder = self
+            .version
+            .tlvs
+            .iter()
+            .try_fold(builder, |builder, (kind, value)| {
+                builder.write_tlv(*kind, value)
+            })
+            .context("PROXY client (v2): write TLV to header")?;
+
         let builder = if let Some(payload) = self.version.payload.as_deref() {
             builder
                 .write_payload(payload)
@@ -359,6 +420,7 @@ pub mod version {
     /// See [`crate::protocol`] for more information.
     pub struct Two {
         pub(crate) payload: Option<Vec<u8>>,
+        pub(crate) tlvs: Vec<(u8, Vec<u8>)>,
     }
 }
 
@@ -591,6 +653,54 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_v2_tcp4_tlvs() {
+        let mut ctx = Context::default();
+        ctx.insert(SocketInfo::new(None, "127.0.0.1:80".parse().unwrap()));
+
+        let svc = HaProxyLayer::tcp()
+            .tlv(v2::Type::Authority, "example.com")
+            .tlv(v2::Type::UniqueId, [1, 2, 3])
+            .payload(vec![42])
+            .layer(service_fn(move |ctx, req| async move {
+                Ok::<_, Infallible>(EstablishedClientConnection {
+                    ctx,
+                    req,
+                    conn: Builder::new()
+                        .write(&[
+                            b'\r', b'\n', b'\r', b'\n', b'\0', b'\r', b'\n', b'Q', b'U', b'I',
+                            b'T', b'\n', 0x21, 0x11, 0, 33, 127, 0, 0, 1, 192, 168, 1, 1, 0, 80, 1,
+                            187, 0x02, 0, 11, b'e', b'x', b'a', b'm', b'p', b'l', b'e', b'.', b'c',
+                            b'o', b'm', 0x05, 0, 3, 1, 2, 3, 42,
+                        ])
+                        .build(),
+                    addr: "192.168.1.1:443".parse().unwrap(),
+                })
+            }));
+        svc.serve(ctx, ()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_v2_tlvs_dropped_for_v1() {
+        let mut ctx = Context::default();
+        ctx.insert(SocketInfo::new(None, "127.0.0.1:80".parse().unwrap()));
+
+        let svc = HaProxyLayer::tcp()
+            .tlv(v2::Type::Authority, "example.com")
+            .v1()
+            .layer(service_fn(move |ctx, req| async move {
+                Ok::<_, Infallible>(EstablishedClientConnection {
+                    ctx,
+                    req,
+                    conn: Builder::new()
+                        .write(b"PROXY TCP4 127.0.0.1 192.168.1.1 80 443\r\n")
+                        .build(),
+                    addr: "192.168.1.1:443".parse().unwrap(),
+                })
+            }));
+        svc.serve(ctx, ()).await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_v2_udp4() {
         for input_ctx in [
@@ -7,18 +7,65 @@ use rama_net::{
     forwarded::{Forwarded, ForwardedElement},
     stream::{ChainReader, HeapReader, Stream},
 };
-use std::{fmt, net::SocketAddr};
+use std::{fmt, net::SocketAddr, time::Duration};
 use tokio::io::AsyncReadExt;
 
 /// Layer to decode the HaProxy Protocol
+///
+/// By default a valid PROXY protocol (v1 or v2) header is required,
+/// and connections missing it or sending a malformed header are rejected (closed).
+/// Use [`HaProxyLayer::optional`] to accept connections without a PROXY protocol header.
 #[derive(Debug, Default, Clone)]
-#[non_exhaustive]
-pub struct HaProxyLayer;
+pub struct HaProxyLayer {
+    optional: bool,
+    header_timeout: Option<Duration>,
+}
 
 impl HaProxyLayer {
-    /// Create a new [`HaProxyLayer`].
+    /// Create a new [`HaProxyLayer`], requiring a PROXY protocol header.
     pub const fn new() -> Self {
-        HaProxyLayer
+        HaProxyLayer {
+            optional: false,
+            header_timeout: None,
+        }
+    }
+
+    /// Create a new [`HaProxyLayer`], which parses the PROXY protocol header if present.
+    ///
+    /// Connections that do not start with a PROXY protocol header are passed as-is
+    /// to the inner service, in which case the socket peer address remains
+    /// the client address (e.g. as found in the [`SocketInfo`]).
+    ///
+    /// [`SocketInfo`]: rama_net::stream::SocketInfo
+    pub const fn optional() -> Self {
+        HaProxyLayer {
+            optional: true,
+            header_timeout: None,
+        }
+    }
+
+    /// Set the maximum duration to wait for the PROXY protocol header,
+    /// rejecting the connection if it was not received in time.
+    ///
+    /// In optional mode a connection that did not send any data
+    /// within this duration is passed as-is to the inner service.
+    ///
+    /// By default there is no timeout.
+    pub const fn with_header_timeout(mut self, timeout: Duration) -> Self {
+        self.header_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum duration to wait for the PROXY protocol header,
+    /// rejecting the connection if it was not received in time.
+    ///
+    /// In optional mode a connection that did not send any data
+    /// within this duration is passed as-is to the inner service.
+    ///
+    /// By default there is no timeout.
+    pub fn set_header_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.header_timeout = Some(timeout);
+        self
     }
 }
 
@@ -26,7 +73,11 @@ impl<S> Layer<S> for HaProxyLayer {
     type Service = HaProxyService<S>;
 
     fn layer(&self, inner: S) -> Self::Service {
-        HaProxyService { inner }
+        HaProxyService {
+            inner,
+            optional: self.optional,
+            header_timeout: self.header_timeout,
+        }
     }
 }
 
@@ -34,14 +85,51 @@ impl<S> Layer<S> for HaProxyLayer {
 ///
 /// This service will decode the HaProxy Protocol header and pass the decoded
 /// information to the inner service.
+///
+/// See [`HaProxyLayer`] for more information.
 pub struct HaProxyService<S> {
     inner: S,
+    optional: bool,
+    header_timeout: Option<Duration>,
 }
 
 impl<S> HaProxyService<S> {
-    /// Create a new [`HaProxyService`] with the given inner service.
+    /// Create a new [`HaProxyService`] with the given inner service,
+    /// requiring a PROXY protocol header.
     pub const fn new(inner: S) -> Self {
-        HaProxyService { inner }
+        HaProxyService {
+            inner,
+            optional: false,
+            header_timeout: None,
+        }
+    }
+
+    /// Create a new [`HaProxyService`] with the given inner service,
+    /// which parses the PROXY protocol header if present.
+    ///
+    /// See [`HaProxyLayer::optional`] for more information.
+    pub const fn optional(inner: S) -> Self {
+        HaProxyService {
+            inner,
+            optional: true,
+            header_timeout: None,
+        }
+    }
+
+    /// Set the maximum duration to wait for the PROXY protocol header.
+    ///
+    /// See [`HaProxyLayer::with_header_timeout`] for more information.
+    pub const fn with_header_timeout(mut self, timeout: Duration) -> Self {
+        self.header_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum duration to wait for the PROXY protocol header.
+    ///
+    /// See [`HaProxyLayer::with_header_timeout`] for more information.
+    pub fn set_header_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.header_timeout = Some(timeout);
+        self
     }
 }
 
@@ -49,6 +137,8 @@ impl<S: fmt::Debug> fmt::Debug for HaProxyService<S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("HaProxyService")
             .field("inner", &self.inner)
+            .field("optional", &self.optional)
+            .field("header_timeout", &self.header_timeout)
             .finish()
     }
 }
@@ -57,10 +147,44 @@ impl<S: Clone> Clone for HaProxyService<S> {
     fn clone(&self) -> Self {
         HaProxyService {
             inner: self.inner.clone(),
+            optional: self.optional,
+            header_timeout: self.header_timeout,
         }
     }
 }
 
+/// Returns `Some(true)` if the data starts with a PROXY protocol signature,
+/// `Some(false)` if it cannot and `None` if more data is required to know.
+fn starts_with_proxy_protocol_signature(data: &[u8]) -> Option<bool> {
+    let mut incomplete = false;
+    for signature in [v1::PROTOCOL_PREFIX.as_bytes(), v2::PROTOCOL_PREFIX] {
+        let n = data.len().min(signature.len());
+        if data[..n] == signature[..n] {
+            if n == signature.len() {
+                return Some(true);
+            }
+            incomplete = true;
+        }
+    }
+    (!incomplete).then_some(false)
+}
+
+fn header_peer_addr(header: &HeaderResult<'_>) -> Option<SocketAddr> {
+    match header {
+        HeaderResult::V1(Ok(header)) => match &header.addresses {
+            v1::Addresses::Tcp4(info) => Some((info.source_address, info.source_port).into()),
+            v1::Addresses::Tcp6(info) => Some((info.source_address, info.source_port).into()),
+            v1::Addresses::Unknown => None,
+        },
+        HeaderResult::V2(Ok(header)) => match &header.addresses {
+            v2::Addresses::IPv4(info) => Some((info.source_address, info.source_port).into()),
+            v2::Addresses::IPv6(info) => Some((info.source_address, info.source_port).into()),
+            v2::Addresses::Unix(_) | v2::Addresses::Unspecified => None,
+        },
+        HeaderResult::V1(Err(_)) | HeaderResult::V2(Err(_)) => None,
+    }
+}
+
 impl<State, S, IO> Service<State, IO> for HaProxyService<S>
 where
     State: Clone + Send + Sync + 'static,
@@ -81,94 +205,76 @@ where
     ) -> Result<Self::Response, Self::Error> {
         let mut buffer = [0; 512];
         let mut read = 0;
-        let header = loop {
-            let n = stream.read(&mut buffer[read..]).await?;
-            read += n;
 
-            let header = HeaderResult::parse(&buffer[..read]);
-            if header.is_complete() {
-                break header;
-            }
+        // reads the header, returning the amount of bytes consumed
+        // and the client address found in it, or `None` if the header is absent (optional mode)
+        let read_header = async {
+            loop {
+                let n = stream.read(&mut buffer[read..]).await?;
+                read += n;
 
-            if n == 0 {
-                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof)
-                    .context("HaProxy header incomplete")
-                    .into_boxed());
-            }
+                if self.optional {
+                    match starts_with_proxy_protocol_signature(&buffer[..read]) {
+                        Some(true) => (),
+                        Some(false) => return Ok(None),
+                        None if n == 0 => return Ok(None),
+                        None => continue,
+                    }
+                }
 
-            tracing::debug!("Incomplete header. Read {} bytes so far.", read);
+                let header = HeaderResult::parse(&buffer[..read]);
+                if header.is_complete() {
+                    let peer_addr = header_peer_addr(&header);
+                    let consumed = match header {
+                        HeaderResult::V1(Ok(header)) => header.header.len(),
+                        HeaderResult::V2(Ok(header)) => header.header.len(),
+                        HeaderResult::V1(Err(error)) => return Err(error.into()),
+                        HeaderResult::V2(Err(error)) => return Err(error.into()),
+                    };
+                    return Ok(Some((consumed, peer_addr)));
+                }
+
+                if n == 0 {
+                    return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof)
+                        .context("HaProxy header incomplete")
+                        .into_boxed());
+                }
+
+                tracing::debug!("Incomplete header. Read {} bytes so far.", read);
+            }
         };
 
-        let consumed = match header {
-            HeaderResult::V1(Ok(header)) => {
-                match header.addresses {
-                    v1::Addresses::Tcp4(info) => {
-                        let peer_addr: SocketAddr = (info.source_address, info.source_port).into();
-                        let el = ForwardedElement::forwarded_for(peer_addr);
-                        match ctx.get_mut::<Forwarded>() {
-                            Some(forwarded) => {
-                                forwarded.append(el);
-                            }
-                            None => {
-                                let forwarded = Forwarded::new(el);
-                                ctx.insert(forwarded);
-                            }
+        let header: Result<Option<(usize, Option<SocketAddr>)>, BoxError> =
+            match self.header_timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, read_header).await {
+                    Ok(result) => result,
+                    Err(_) if self.optional && read == 0 => Ok(None),
+                    Err(_) => Err(std::io::Error::from(std::io::ErrorKind::TimedOut)
+                        .context("HaProxy header timeout")
+                        .into_boxed()),
+                },
+                None => read_header.await,
+            };
+
+        let consumed = match header? {
+            Some((consumed, peer_addr)) => {
+                if let Some(peer_addr) = peer_addr {
+                    let el = ForwardedElement::forwarded_for(peer_addr);
+                    match ctx.get_mut::<Forwarded>() {
+                        Some(forwarded) => {
+                            forwarded.append(el);
                         }
-                    }
-                    v1::Addresses::Tcp6(info) => {
-                        let peer_addr: SocketAddr = (info.source_address, info.source_port).into();
-                        let el = ForwardedElement::forwarded_for(peer_addr);
-                        match ctx.get_mut::<Forwarded>() {
-                            Some(forwarded) => {
-                                forwarded.append(el);
-                            }
-                            None => {
-                                let forwarded = Forwarded::new(el);
-                                ctx.insert(forwarded);
-                            }
+                        None => {
+                            let forwarded = Forwarded::new(el);
+                            ctx.insert(forwarded);
                         }
                     }
-                    v1::Addresses::Unknown => (),
-                };
-                header.header.len()
+                }
+                consumed
             }
-            HeaderResult::V2(Ok(header)) => {
-                match header.addresses {
-                    v2::Addresses::IPv4(info) => {
-                        let peer_addr: SocketAddr = (info.source_address, info.source_port).into();
-                        let el = ForwardedElement::forwarded_for(peer_addr);
-                        match ctx.get_mut::<Forwarded>() {
-                            Some(forwarded) => {
-                                forwarded.append(el);
-                            }
-                            None => {
-                                let forwarded = Forwarded::new(el);
-                                ctx.insert(forwarded);
-                            }
-                        }
-                    }
-                    v2::Addresses::IPv6(info) => {
-                        let peer_addr: SocketAddr = (info.source_address, info.source_port).into();
-                        let el = ForwardedElement::forwarded_for(peer_addr);
-                        match ctx.get_mut::<Forwarded>() {
-                            Some(forwarded) => {
-                                forwarded.append(el);
-                            }
-                            None => {
-                                let forwarded = Forwarded::new(el);
-                                ctx.insert(forwarded);
-                            }
-                        }
-                    }
-                    v2::Addresses::Unix(_) | v2::Addresses::Unspecified => (),
-                };
-                header.header.len()
-            }
-            HeaderResult::V1(Err(error)) => {
-                return Err(error.into());
-            }
-            HeaderResult::V2(Err(error)) => {
-                return Err(error.into());
+            None => {
+                tracing::trace!("no HaProxy header found: fallback to socket peer address");
+                0
             }
         };
 
@@ -185,3 +291,128 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::service::service_fn;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_test::io::Builder;
+
+    async fn serve<IO: Stream + Unpin>(
+        layer: HaProxyLayer,
+        stream: IO,
+    ) -> Result<(Option<SocketAddr>, String), BoxError> {
+        let svc = layer.layer(service_fn(
+            |ctx: Context<()>, mut stream: tokio::io::Join<_, _>| async move {
+                let mut data = String::new();
+                stream.read_to_string(&mut data).await?;
+                Ok::<_, std::io::Error>((
+                    ctx.get::<Forwarded>().and_then(|f| f.client_socket_addr()),
+                    data,
+                ))
+            },
+        ));
+        svc.serve(Context::default(), stream).await
+    }
+
+    #[tokio::test]
+    async fn test_required_v1_header_present() {
+        let stream = Builder::new()
+            .read(b"PROXY TCP4 192.168.1.1 10.0.0.1 56324 443\r\nhello")
+            .build();
+        let (addr, data) = serve(HaProxyLayer::new(), stream).await.unwrap();
+        assert_eq!(addr, Some("192.168.1.1:56324".parse().unwrap()));
+        assert_eq!(data, "hello");
+    }
+
+    #[tokio::test]
+    async fn test_required_v2_header_present() {
+        let mut header = v2::Builder::with_addresses(
+            v2::Version::Two | v2::Command::Proxy,
+            v2::Protocol::Stream,
+            v2::IPv6::new(
+                "2001:db8::1".parse::<std::net::Ipv6Addr>().unwrap(),
+                "2001:db8::2".parse::<std::net::Ipv6Addr>().unwrap(),
+                8080,
+                443,
+            ),
+        )
+        .build()
+        .unwrap();
+        header.extend_from_slice(b"hello");
+
+        let stream = Builder::new().read(&header).build();
+        let (addr, data) = serve(HaProxyLayer::new(), stream).await.unwrap();
+        assert_eq!(addr, Some("[2001:db8::1]:8080".parse().unwrap()));
+        assert_eq!(data, "hello");
+    }
+
+    #[tokio::test]
+    async fn test_required_header_absent() {
+        let stream = Builder::new().read(b"GET / HTTP/1.1\r\n\r\n").build();
+        assert!(serve(HaProxyLayer::new(), stream).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_required_header_malformed() {
+        let stream = Builder::new()
+            .read(b"PROXY TCP4 192.168.1.1 10.0.0.1 foo 443\r\nhello")
+            .build();
+        assert!(serve(HaProxyLayer::new(), stream).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_required_header_timeout() {
+        let (_client, server) = tokio::io::duplex(64);
+        let err = serve(
+            HaProxyLayer::new().with_header_timeout(Duration::from_millis(10)),
+            server,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("timeout"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_optional_header_present() {
+        let stream = Builder::new()
+            .read(b"PROXY TCP4 192.168.1.1 10.0.0.1 56324 443\r\nhello")
+            .build();
+        let (addr, data) = serve(HaProxyLayer::optional(), stream).await.unwrap();
+        assert_eq!(addr, Some("192.168.1.1:56324".parse().unwrap()));
+        assert_eq!(data, "hello");
+    }
+
+    #[tokio::test]
+    async fn test_optional_header_absent() {
+        for input in [
+            &b"GET / HTTP/1.1\r\n\r\n"[..],
+            &b"PRO"[..],
+            &b"\r\n\r\n\0"[..],
+            &b""[..],
+        ] {
+            let stream = Builder::new().read(input).build();
+            let (addr, data) = serve(HaProxyLayer::optional(), stream).await.unwrap();
+            assert_eq!(addr, None);
+            assert_eq!(data.as_bytes(), input);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_optional_header_absent_timeout() {
+        let (mut client, server) = tokio::io::duplex(64);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            client.write_all(b"hello").await.unwrap();
+        });
+        let (addr, data) = serve(
+            HaProxyLayer::optional().with_header_timeout(Duration::from_millis(10)),
+            server,
+        )
+        .await
+        .unwrap();
+        assert_eq!(addr, None);
+        assert_eq!(data, "hello");
+    }
+}
@@ -7,7 +7,7 @@ use rama_core::{
     Context, Service,
 };
 use rama_http_types::{dep::http_body, Request, Response};
-use rama_net::client::{ConnectorService, EstablishedClientConnection};
+use rama_net::client::{ConnectTimings, ConnectorService, EstablishedClientConnection};
 use rama_tcp::client::service::TcpConnector;
 
 #[cfg(any(feature = "rustls", feature = "boring"))]
@@ -26,6 +26,10 @@ pub use svc::HttpClientService;
 mod conn;
 #[doc(inline)]
 pub use conn::{HttpConnector, HttpConnectorLayer};
+
+mod stream;
+#[doc(inline)]
+pub use stream::StreamConnector;
 use tracing::trace;
 
 pub mod proxy;
@@ -39,6 +43,9 @@ pub mod proxy;
 /// passed through your "connector" setup. All this and more is possible by defining your own
 /// http client. Rama is here to empower you, the building blocks are there, go crazy
 /// with your own service fork and use the full power of Rust at your fingertips ;)
+///
+/// The [`ConnectTimings`] recorded while connecting, if any,
+/// are inserted as an extension of the [`Response`].
 pub struct HttpClient {
     #[cfg(any(feature = "rustls", feature = "boring"))]
     tls_config: Option<ClientConfig>,
@@ -169,6 +176,9 @@ where
             .await
             .map_err(|err| OpaqueError::from_boxed(err).with_context(|| uri.to_string()))?;
 
+        // expose the connect timings to the layers wrapping this client
+        let connect_timings = ctx.get::<ConnectTimings>().copied();
+
         trace!(uri = %uri, "send http req to connector stack");
         let mut resp = conn.serve(ctx, req).await.map_err(|err| {
             OpaqueError::from_boxed(err)
@@ -185,6 +195,10 @@ where
         // we might need to do more complex normalization here... Worries for the future, maybe
         *resp.version_mut() = original_req_version;
 
+        if let Some(connect_timings) = connect_timings {
+            resp.extensions_mut().insert(connect_timings);
+        }
+
         Ok(resp)
     }
 }
@@ -2,7 +2,10 @@ use rama_core::{
     error::{ErrorContext, OpaqueError},
     Context, Layer, Service,
 };
-use rama_net::address::ProxyAddress;
+use rama_net::{
+    address::ProxyAddress,
+    client::{ProxyCredentials, ProxyTarget},
+};
 use std::{fmt, future::Future};
 
 #[derive(Debug, Clone, Default)]
@@ -63,13 +66,15 @@ impl HttpProxyAddressLayer {
         Ok(Self::maybe(proxy_address))
     }
 
-    /// Preserve the existing [`ProxyAddress`] in the context if it already exists.
+    /// Preserve the existing proxy in the context if it already exists,
+    /// defined as either a [`ProxyAddress`] or a [`ProxyTarget`].
     pub fn preserve(mut self, preserve: bool) -> Self {
         self.preserve = preserve;
         self
     }
 
-    /// Preserve the existing [`ProxyAddress`] in the context if it already exists.
+    /// Preserve the existing proxy in the context if it already exists,
+    /// defined as either a [`ProxyAddress`] or a [`ProxyTarget`].
     pub fn set_preserve(&mut self, preserve: bool) -> &mut Self {
         self.preserve = preserve;
         self
@@ -161,13 +166,15 @@ impl<S> HttpProxyAddressService<S> {
         Ok(Self::maybe(inner, proxy_address))
     }
 
-    /// Preserve the existing [`ProxyAddress`] in the context if it already exists.
+    /// Preserve the existing proxy in the context if it already exists,
+    /// defined as either a [`ProxyAddress`] or a [`ProxyTarget`].
     pub const fn preserve(mut self, preserve: bool) -> Self {
         self.preserve = preserve;
         self
     }
 
-    /// Preserve the existing [`ProxyAddress`] in the context if it already exists.
+    /// Preserve the existing proxy in the context if it already exists,
+    /// defined as either a [`ProxyAddress`] or a [`ProxyTarget`].
     pub fn set_preserve(&mut self, preserve: bool) -> &mut Self {
         self.preserve = preserve;
         self
@@ -188,8 +195,11 @@ where
         req: Request,
     ) -> impl Future<Output = Result<Self::Response, Self::Error>> + Send + '_ {
         if let Some(ref address) = self.address {
-            if !self.preserve || !ctx.contains::<ProxyAddress>() {
+            if !self.preserve || !ProxyTarget::is_proxied(&ctx) {
                 tracing::trace!(authority = %address.authority, "setting proxy address");
+                // a proxy target takes precedence, so remove it to use this proxy address
+                ctx.remove::<ProxyTarget>();
+                ctx.remove::<ProxyCredentials>();
                 ctx.insert(address.clone());
             }
         }
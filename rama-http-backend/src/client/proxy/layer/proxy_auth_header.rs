@@ -3,13 +3,14 @@ use rama_http_types::{
     headers::{HeaderMapExt, ProxyAuthorization},
     Request,
 };
-use rama_net::{address::ProxyAddress, http::RequestContext, user::ProxyCredential};
+use rama_net::{client::ProxyTarget, http::RequestContext, user::ProxyCredential};
 use std::{fmt, future::Future};
 
 #[derive(Debug, Clone, Default)]
 #[non_exhaustive]
 /// A [`Layer`] which will set the http auth header
-/// in case there is a [`ProxyAddress`] in the [`Context`].
+/// in case there is a proxy with credentials defined in the [`Context`],
+/// see [`ProxyTarget::proxy_address`].
 pub struct SetProxyAuthHttpHeaderLayer;
 
 impl SetProxyAuthHttpHeaderLayer {
@@ -28,7 +29,8 @@ impl<S> Layer<S> for SetProxyAuthHttpHeaderLayer {
 }
 
 /// A [`Service`] wwhich will set the http auth header
-/// in case there is a [`ProxyAddress`] in the [`Context`].
+/// in case there is a proxy with credentials defined in the [`Context`],
+/// see [`ProxyTarget::proxy_address`].
 pub struct SetProxyAuthHttpHeaderService<S> {
     inner: S,
 }
@@ -79,8 +81,8 @@ where
         {
             // only do this for non-secure requests!!!
 
-            if let Some(pa) = ctx.get::<ProxyAddress>() {
-                if let Some(credential) = pa.credential.clone() {
+            if let Some(pa) = ProxyTarget::proxy_address(&ctx) {
+                if let Some(credential) = pa.credential {
                     match credential {
                         ProxyCredential::Basic(basic) => {
                             tracing::trace!("inserted proxy Basic credentials into (http) request");
@@ -100,3 +102,61 @@ where
         self.inner.serve(ctx, req)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::service::service_fn;
+    use rama_http_types::{header::PROXY_AUTHORIZATION, Body};
+    use rama_net::{
+        address::ProxyAddress,
+        client::ProxyTarget,
+        user::{Basic, ProvideProxyCredentialLayer, StaticCredentialProvider},
+    };
+    use std::convert::Infallible;
+
+    fn request(uri: &'static str) -> Request<Body> {
+        Request::builder().uri(uri).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_set_proxy_auth_header_with_provided_credential() {
+        let svc = (
+            ProvideProxyCredentialLayer::new(StaticCredentialProvider::new(Basic::new(
+                "john", "secret",
+            ))),
+            SetProxyAuthHttpHeaderLayer::new(),
+        )
+            .layer(service_fn(|req: Request<Body>| async move {
+                Ok::<_, Infallible>(req.headers().get(PROXY_AUTHORIZATION).cloned())
+            }));
+
+        // credential provided for a proxy target
+        let mut ctx = Context::default();
+        ctx.insert(ProxyTarget::new(([127, 0, 0, 1], 8080)));
+        let header = svc.serve(ctx, request("http://example.com")).await.unwrap();
+        assert_eq!(header.unwrap(), "Basic am9objpzZWNyZXQ=");
+
+        // credential provided for a proxy address
+        let mut ctx = Context::default();
+        ctx.insert(ProxyAddress::try_from("127.0.0.1:8080").unwrap());
+        let header = svc.serve(ctx, request("http://example.com")).await.unwrap();
+        assert_eq!(header.unwrap(), "Basic am9objpzZWNyZXQ=");
+
+        // never sent in plain text for secure requests
+        let mut ctx = Context::default();
+        ctx.insert(ProxyTarget::new(([127, 0, 0, 1], 8080)));
+        let header = svc
+            .serve(ctx, request("https://example.com"))
+            .await
+            .unwrap();
+        assert!(header.is_none());
+
+        // no proxy, no header
+        let header = svc
+            .serve(Context::default(), request("http://example.com"))
+            .await
+            .unwrap();
+        assert!(header.is_none());
+    }
+}
@@ -11,21 +11,21 @@ pub struct HttpProxyConnectorLayer {
 
 impl HttpProxyConnectorLayer {
     /// Create a new [`HttpProxyConnectorLayer`] which creates a [`HttpProxyConnector`]
-    /// which will only connect via an http proxy in case the [`ProxyAddress`] is available
-    /// in the [`Context`].
+    /// which will only connect via an http proxy in case a proxy is defined
+    /// in the [`Context`], see [`ProxyTarget::proxy_address`].
     ///
     /// [`Context`]: rama_core::Context
-    /// [`ProxyAddress`]: rama_net::address::ProxyAddress
+    /// [`ProxyTarget::proxy_address`]: rama_net::client::ProxyTarget::proxy_address
     pub fn optional() -> Self {
         Self { required: false }
     }
 
     /// Create a new [`HttpProxyConnectorLayer`] which creates a [`HttpProxyConnector`]
-    /// which will always connect via an http proxy, but fail in case the [`ProxyAddress`] is
-    /// not available in the [`Context`].
+    /// which will always connect via an http proxy, but fail in case no proxy is
+    /// defined in the [`Context`], see [`ProxyTarget::proxy_address`].
     ///
     /// [`Context`]: rama_core::Context
-    /// [`ProxyAddress`]: rama_net::address::ProxyAddress
+    /// [`ProxyTarget::proxy_address`]: rama_net::client::ProxyTarget::proxy_address
     pub fn required() -> Self {
         Self { required: true }
     }
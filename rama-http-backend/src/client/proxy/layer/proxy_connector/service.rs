@@ -9,8 +9,7 @@ use rama_core::{
 use rama_http_core::upgrade;
 use rama_http_types::headers::ProxyAuthorization;
 use rama_net::{
-    address::ProxyAddress,
-    client::{ConnectorService, EstablishedClientConnection},
+    client::{ConnectorService, EstablishedClientConnection, ProxyTarget},
     stream::Stream,
     transport::TryRefIntoTransportContext,
     user::ProxyCredential,
@@ -24,7 +23,7 @@ use rama_net::tls::TlsTunnel;
 /// A connector which can be used to establish a connection over an HTTP Proxy.
 ///
 /// This behaviour is optional and only triggered in case there
-/// is a [`ProxyAddress`] found in the [`Context`].
+/// is a proxy defined in the [`Context`], see [`ProxyTarget::proxy_address`].
 pub struct HttpProxyConnector<S> {
     inner: S,
     required: bool,
@@ -55,15 +54,15 @@ impl<S> HttpProxyConnector<S> {
     }
 
     /// Create a new [`HttpProxyConnector`]
-    /// which will only connect via an http proxy in case the [`ProxyAddress`] is available
-    /// in the [`Context`].
+    /// which will only connect via an http proxy in case a proxy is defined
+    /// in the [`Context`], see [`ProxyTarget::proxy_address`].
     pub fn optional(inner: S) -> Self {
         Self::new(inner, false)
     }
 
     /// Create a new [`HttpProxyConnector`]
-    /// which will always connect via an http proxy, but fail in case the [`ProxyAddress`] is
-    /// not available in the [`Context`].
+    /// which will always connect via an http proxy, but fail in case no proxy is
+    /// defined in the [`Context`], see [`ProxyTarget::proxy_address`].
     pub fn required(inner: S) -> Self {
         Self::new(inner, true)
     }
@@ -88,7 +87,7 @@ where
         mut ctx: Context<State>,
         req: Request,
     ) -> Result<Self::Response, Self::Error> {
-        let address = ctx.get::<ProxyAddress>().cloned();
+        let address = ProxyTarget::proxy_address(&ctx);
 
         let transport_ctx = ctx
             .get_or_try_insert_with_ctx(|ctx| req.try_ref_into_transport_ctx(ctx))
@@ -98,11 +97,9 @@ where
             })?
             .clone();
 
-        // in case the provider gave us a proxy info, we insert it into the context
+        // in case a proxy is used, prepare the tls tunnel to it if needed
+        #[cfg(feature = "tls")]
         if let Some(address) = &address {
-            ctx.insert(address.clone());
-
-            #[cfg(feature = "tls")]
             if address
                 .protocol
                 .as_ref()
@@ -223,3 +220,62 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::Layer;
+    use rama_http_types::{Body, Request};
+    use rama_net::{
+        client::ProxyTarget,
+        user::{Basic, ProvideProxyCredentialLayer, StaticCredentialProvider},
+    };
+    use rama_tcp::client::service::TcpConnector;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    #[tokio::test]
+    async fn test_http_proxy_connector_with_provided_credential() {
+        // a minimal http proxy, capturing the CONNECT request it received
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_addr = listener.local_addr().unwrap();
+        let proxy = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut head = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !head.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await.unwrap();
+                assert!(n > 0, "unexpected eof");
+                head.extend_from_slice(&buf[..n]);
+            }
+            stream.write_all(b"HTTP/1.1 200 OK\r\n\r\n").await.unwrap();
+            String::from_utf8(head).unwrap()
+        });
+
+        let connector = ProvideProxyCredentialLayer::new(StaticCredentialProvider::new(
+            Basic::new("john", "secret"),
+        ))
+        .layer(HttpProxyConnector::required(TcpConnector::new()));
+
+        let mut ctx = Context::default();
+        ctx.insert(ProxyTarget::new(proxy_addr));
+        let req = Request::builder()
+            .uri("https://example.com")
+            .body(Body::empty())
+            .unwrap();
+        let EstablishedClientConnection { addr, .. } = connector.serve(ctx, req).await.unwrap();
+        assert_eq!(addr, proxy_addr);
+
+        let head = proxy.await.unwrap();
+        let mut lines = head.lines();
+        assert_eq!(lines.next(), Some("CONNECT example.com:443 HTTP/1.1"));
+        let proxy_auth = lines.find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("proxy-authorization")
+                .then(|| value.trim())
+        });
+        assert_eq!(proxy_auth, Some("Basic am9objpzZWNyZXQ="), "{head}");
+    }
+}
@@ -0,0 +1,231 @@
+use rama_core::{
+    error::{BoxError, OpaqueError},
+    Context, Service,
+};
+use rama_net::{client::EstablishedClientConnection, stream::Stream};
+use std::{
+    fmt,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+
+/// A connector which "establishes" a connection using a provided IO Byte Stream
+/// (e.g. a Unix socket or an in-memory duplex stream) instead of connecting to the
+/// target of the request.
+///
+/// The stream can only be used for a single connection,
+/// connecting a second time results in an error.
+///
+/// Combine it with an [`HttpConnector`] to send requests over the provided stream.
+///
+/// [`HttpConnector`]: super::HttpConnector
+pub struct StreamConnector<IO> {
+    stream: Arc<Mutex<Option<IO>>>,
+    addr: SocketAddr,
+}
+
+impl<IO> fmt::Debug for StreamConnector<IO> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamConnector")
+            .field("addr", &self.addr)
+            .finish()
+    }
+}
+
+impl<IO> Clone for StreamConnector<IO> {
+    fn clone(&self) -> Self {
+        Self {
+            stream: self.stream.clone(),
+            addr: self.addr,
+        }
+    }
+}
+
+impl<IO> StreamConnector<IO> {
+    /// Create a new [`StreamConnector`] for the given IO Byte Stream.
+    pub fn new(stream: IO) -> Self {
+        Self {
+            stream: Arc::new(Mutex::new(Some(stream))),
+            addr: SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        }
+    }
+
+    /// Set the address reported as connected to,
+    /// which is the unspecified address `0.0.0.0:0` by default.
+    pub fn with_addr(mut self, addr: SocketAddr) -> Self {
+        self.addr = addr;
+        self
+    }
+
+    /// Set the address reported as connected to,
+    /// which is the unspecified address `0.0.0.0:0` by default.
+    pub fn set_addr(&mut self, addr: SocketAddr) -> &mut Self {
+        self.addr = addr;
+        self
+    }
+}
+
+impl<State, Request, IO> Service<State, Request> for StreamConnector<IO>
+where
+    State: Clone + Send + Sync + 'static,
+    Request: Send + 'static,
+    IO: Stream,
+{
+    type Response = EstablishedClientConnection<IO, State, Request>;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        let conn = self
+            .stream
+            .lock()
+            .map_err(|_| OpaqueError::from_display("StreamConnector: stream lock poisoned"))?
+            .take()
+            .ok_or_else(|| OpaqueError::from_display("StreamConnector: stream already used"))?;
+        Ok(EstablishedClientConnection {
+            ctx,
+            req,
+            conn,
+            addr: self.addr,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client::HttpConnector, server::HttpServer};
+    use rama_core::{rt::Executor, service::service_fn};
+    use rama_http_types::{
+        dep::http_body_util::BodyExt, header, Body, HeaderMap, HeaderValue, Method, Request,
+        Response, Version,
+    };
+    use rama_net::client::ConnectorService;
+    use std::convert::Infallible;
+
+    #[tokio::test]
+    async fn test_http1_over_duplex_stream() {
+        let (client_io, server_io) = tokio::io::duplex(1024);
+
+        tokio::spawn(async move {
+            HttpServer::http1()
+                .serve_stream(
+                    server_io,
+                    service_fn(|req: Request| async move {
+                        let path = req.uri().path().to_owned();
+                        Ok::<_, Infallible>(Response::new(Body::from(format!("hello {path}"))))
+                    }),
+                )
+                .await
+                .unwrap();
+        });
+
+        let connector = HttpConnector::new(StreamConnector::new(client_io));
+        let req = Request::builder()
+            .uri("http://example.com/world")
+            .version(Version::HTTP_11)
+            .body(Body::empty())
+            .unwrap();
+        let EstablishedClientConnection { ctx, req, conn, .. } =
+            connector.connect(Context::default(), req).await.unwrap();
+
+        let resp = conn.serve(ctx, req).await.unwrap();
+        assert!(resp.status().is_success());
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "hello /world");
+
+        let req = Request::new(Body::empty());
+        assert!(connector.connect(Context::default(), req).await.is_err());
+    }
+
+    fn trailers_service(
+    ) -> impl Service<(), Request, Response = Response, Error = Infallible> + Clone {
+        service_fn(|req: Request| async move {
+            let req_trailers = req
+                .into_body()
+                .collect()
+                .await
+                .unwrap()
+                .trailers()
+                .cloned()
+                .unwrap_or_default();
+
+            let mut trailers = HeaderMap::new();
+            trailers.insert("x-checksum", HeaderValue::from_static("abc123"));
+            if let Some(value) = req_trailers.get("x-request-checksum") {
+                trailers.insert("x-request-checksum", value.clone());
+            }
+            Ok(Response::builder()
+                .header(header::TRAILER, "x-checksum, x-request-checksum")
+                .body(Body::from("hello").with_trailers(trailers))
+                .unwrap())
+        })
+    }
+
+    fn trailers_request(version: Version) -> Request {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("x-request-checksum", HeaderValue::from_static("def456"));
+        Request::builder()
+            .method(Method::POST)
+            .uri("http://example.com/")
+            .version(version)
+            .header(header::TE, "trailers")
+            .header(header::TRAILER, "x-request-checksum")
+            .body(Body::from("world").with_trailers(trailers))
+            .unwrap()
+    }
+
+    async fn assert_trailers_response(resp: Response) {
+        assert!(resp.status().is_success());
+        let collected = resp.into_body().collect().await.unwrap();
+        let trailers = collected.trailers().cloned().unwrap();
+        assert_eq!(trailers.get("x-checksum").unwrap(), "abc123");
+        assert_eq!(trailers.get("x-request-checksum").unwrap(), "def456");
+        assert_eq!(collected.to_bytes(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_http1_trailers_round_trip() {
+        let (client_io, server_io) = tokio::io::duplex(1024);
+
+        tokio::spawn(async move {
+            HttpServer::http1()
+                .serve_stream(server_io, trailers_service())
+                .await
+                .unwrap();
+        });
+
+        let connector = HttpConnector::new(StreamConnector::new(client_io));
+        let EstablishedClientConnection { ctx, req, conn, .. } = connector
+            .connect(Context::default(), trailers_request(Version::HTTP_11))
+            .await
+            .unwrap();
+
+        let resp = conn.serve(ctx, req).await.unwrap();
+        assert_trailers_response(resp).await;
+    }
+
+    #[tokio::test]
+    async fn test_h2_trailers_round_trip() {
+        let (client_io, server_io) = tokio::io::duplex(1024);
+
+        tokio::spawn(async move {
+            HttpServer::h2(Executor::default())
+                .serve_stream(server_io, trailers_service())
+                .await
+                .unwrap();
+        });
+
+        let connector = HttpConnector::new(StreamConnector::new(client_io));
+        let EstablishedClientConnection { ctx, req, conn, .. } = connector
+            .connect(Context::default(), trailers_request(Version::HTTP_2))
+            .await
+            .unwrap();
+
+        let resp = conn.serve(ctx, req).await.unwrap();
+        assert_trailers_response(resp).await;
+    }
+}
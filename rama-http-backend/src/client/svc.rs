@@ -8,7 +8,7 @@ use rama_http_types::{
     headers::HeaderMapExt,
     Method, Request, Response, Version,
 };
-use rama_net::{address::ProxyAddress, http::RequestContext};
+use rama_net::{client::ProxyTarget, http::RequestContext};
 
 #[derive(Debug)]
 pub(super) enum SendRequest<Body> {
@@ -66,7 +66,7 @@ fn sanitize_client_req_header<S, B>(
         Version::HTTP_09 | Version::HTTP_10 | Version::HTTP_11 => {
             // remove authority and scheme for non-connect requests
             // cfr: <https://datatracker.ietf.org/doc/html/rfc2616#section-5.1.2>
-            if !ctx.contains::<ProxyAddress>() && req.uri().host().is_some() {
+            if !ProxyTarget::is_proxied(ctx) && req.uri().host().is_some() {
                 tracing::trace!(
                     "remove authority and scheme from non-connect direct http(~1) request"
                 );
@@ -160,6 +160,26 @@ where
             .await
     }
 
+    /// Serve a single IO Byte Stream (e.g. a Unix socket or an in-memory duplex stream) as HTTP.
+    ///
+    /// Same as [`Self::serve`], but using a default [`Context`],
+    /// which is graceful in case a [`ShutdownGuard`] was set for this [`HttpServer`].
+    ///
+    /// [`Context`]: rama_core::Context
+    pub async fn serve_stream<S, Response, IO>(&self, stream: IO, service: S) -> HttpServeResult
+    where
+        S: Service<(), Request, Response = Response, Error = Infallible> + Clone,
+        Response: IntoResponse + Send + 'static,
+        IO: Stream,
+    {
+        let executor = match &self.guard {
+            Some(guard) => Executor::graceful(guard.clone()),
+            None => Executor::new(),
+        };
+        self.serve(Context::new((), executor), stream, service)
+            .await
+    }
+
     /// Listen for connections on the given address, serving HTTP connections.
     ///
     /// It's a shortcut in case you don't need to operate on the transport layer directly.
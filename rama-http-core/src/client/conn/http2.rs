@@ -333,6 +333,25 @@ impl Builder {
         self
     }
 
+    /// Sets the maximum size of the header compression table used to encode header blocks,
+    /// regardless of the (larger) header table size allowed by the peer.
+    ///
+    /// By default the header table size allowed by the peer is used.
+    pub fn max_encoder_header_table_size(&mut self, size: impl Into<Option<u32>>) -> &mut Self {
+        self.h2_builder.max_encoder_header_table_size = size.into();
+        self
+    }
+
+    /// Enables or disables the indexing of headers in encoded header blocks.
+    ///
+    /// When disabled, all headers are encoded as literal header fields without indexing.
+    ///
+    /// Enabled by default.
+    pub fn enable_header_indexing(&mut self, enabled: bool) -> &mut Self {
+        self.h2_builder.header_indexing = enabled;
+        self
+    }
+
     /// Sets the maximum number of concurrent streams.
     ///
     /// The maximum concurrent streams setting only controls the maximum number
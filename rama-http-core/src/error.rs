@@ -366,6 +366,22 @@ impl StdError for Error {
     }
 }
 
+impl rama_core::error::ErrorKindClass for Error {
+    fn error_class(&self) -> Option<rama_core::error::ErrorClass> {
+        if self.is_timeout() {
+            return Some(rama_core::error::ErrorClass::Timeout);
+        }
+        match self.inner.kind {
+            Kind::HeaderTimeout => Some(rama_core::error::ErrorClass::Timeout),
+            Kind::Parse(_) | Kind::UnexpectedMessage | Kind::IncompleteMessage | Kind::Http2 => {
+                Some(rama_core::error::ErrorClass::Protocol)
+            }
+            // classified by the cause (if any)
+            _ => None,
+        }
+    }
+}
+
 #[doc(hidden)]
 impl From<Parse> for Error {
     fn from(err: Parse) -> Error {
@@ -471,4 +487,26 @@ mod tests {
         let svc_err = Error::new_user_service(recvd);
         assert_eq!(svc_err.h2_reason(), h2::Reason::HTTP_1_1_REQUIRED);
     }
+
+    #[test]
+    fn error_class() {
+        use rama_core::error::{ErrorClass, ErrorClassifier};
+
+        let classifier = ErrorClassifier::new().with_error_type::<Error>();
+        for (err, expected) in [
+            (Error::new(Kind::Parse(Parse::Method)), ErrorClass::Protocol),
+            (
+                Error::new_h2(h2::Error::from(h2::Reason::PROTOCOL_ERROR)),
+                ErrorClass::Protocol,
+            ),
+            (Error::new_header_timeout(), ErrorClass::Timeout),
+            (
+                Error::new_io(std::io::ErrorKind::ConnectionRefused.into()),
+                ErrorClass::ConnectionRefused,
+            ),
+            (Error::new_closed(), ErrorClass::Application),
+        ] {
+            assert_eq!(classifier.classify(&err), expected, "{err:?}");
+        }
+    }
 }
@@ -14,6 +14,20 @@ use bytes::Bytes;
 ///
 /// When a `ReasonPhrase` is present in the extensions of the `http::Response` written by a server,
 /// its contents will be written in place of the canonical reason phrase when responding via HTTP/1.
+/// Responses without a `ReasonPhrase` are written using the canonical reason phrase of their status code.
+///
+/// As clients receive a `ReasonPhrase` for non-canonical reason phrases, proxies forwarding
+/// responses as-is preserve the reason phrase of the upstream server.
+///
+/// ```
+/// use rama_http_core::ext::ReasonPhrase;
+/// use rama_http_types::{Body, Response};
+///
+/// let mut resp = Response::new(Body::empty());
+/// // written as `HTTP/1.1 200 Totally Fine` instead of `HTTP/1.1 200 OK`
+/// resp.extensions_mut()
+///     .insert(ReasonPhrase::from_static(b"Totally Fine"));
+/// ```
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ReasonPhrase(Bytes);
 
@@ -345,6 +345,13 @@ pub struct Builder {
     ///
     /// When this gets exceeded, we issue GOAWAYs.
     local_max_error_reset_streams: Option<usize>,
+
+    /// Maximum size of the header compression table used to encode header blocks,
+    /// regardless of the (larger) header table size allowed by the peer.
+    max_encoder_header_table_size: Option<u32>,
+
+    /// Whether or not encoded headers are indexed in the header compression table.
+    header_indexing: bool,
 }
 
 #[derive(Debug)]
@@ -665,6 +672,8 @@ impl Builder {
             settings: Default::default(),
             stream_id: 1.into(),
             local_max_error_reset_streams: Some(proto::DEFAULT_LOCAL_RESET_COUNT_MAX),
+            max_encoder_header_table_size: None,
+            header_indexing: true,
         }
     }
 
@@ -1147,6 +1156,72 @@ impl Builder {
         self
     }
 
+    /// Sets the maximum size of the header compression table used to encode header blocks.
+    ///
+    /// Unlike [`Builder::header_table_size`], which informs the peer of the table
+    /// size it may use to encode its header blocks, this limits the table size
+    /// used by this client, regardless of the (larger) size allowed by the peer.
+    /// A dynamic table size update is sent to the peer in case the limit applies.
+    ///
+    /// By default the header table size allowed by the peer is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use rama_http_core::h2::client::*;
+    /// # use bytes::Bytes;
+    /// #
+    /// # async fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Result<((SendRequest<Bytes>, Connection<T, Bytes>)), rama_http_core::h2::Error>
+    /// # {
+    /// // `client_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let client_fut = Builder::new()
+    ///     .max_encoder_header_table_size(0)
+    ///     .handshake(my_io);
+    /// # client_fut.await
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn max_encoder_header_table_size(&mut self, size: u32) -> &mut Self {
+        self.max_encoder_header_table_size = Some(size);
+        self
+    }
+
+    /// Enables or disables the indexing of headers in encoded header blocks.
+    ///
+    /// When disabled, all headers are encoded as literal header fields
+    /// without indexing, only referring to the static table for header names.
+    ///
+    /// Enabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use rama_http_core::h2::client::*;
+    /// # use bytes::Bytes;
+    /// #
+    /// # async fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Result<((SendRequest<Bytes>, Connection<T, Bytes>)), rama_http_core::h2::Error>
+    /// # {
+    /// // `client_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let client_fut = Builder::new()
+    ///     .enable_header_indexing(false)
+    ///     .handshake(my_io);
+    /// # client_fut.await
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn enable_header_indexing(&mut self, enabled: bool) -> &mut Self {
+        self.header_indexing = enabled;
+        self
+    }
+
     /// Sets the first stream ID to something other than 1.
     #[cfg(feature = "unstable")]
     pub fn initial_stream_id(&mut self, stream_id: u32) -> &mut Self {
@@ -1323,6 +1398,12 @@ where
             codec.set_max_recv_header_list_size(max as usize);
         }
 
+        if let Some(max) = builder.max_encoder_header_table_size {
+            codec.set_send_header_table_size_limit(max as usize);
+        }
+
+        codec.set_send_header_indexing(builder.header_indexing);
+
         // Send initial settings frame
         codec
             .buffer(builder.settings.clone().into())
@@ -1707,3 +1788,116 @@ impl proto::Peer for Peer {
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    /// Read the payload of the first HEADERS frame written by the client.
+    async fn read_headers_payload<T: AsyncRead + Unpin>(io: &mut T) -> Vec<u8> {
+        let mut preface = [0; 24];
+        io.read_exact(&mut preface).await.unwrap();
+
+        loop {
+            let mut head = [0; 9];
+            io.read_exact(&mut head).await.unwrap();
+            let len = u32::from_be_bytes([0, head[0], head[1], head[2]]) as usize;
+            let mut payload = vec![0; len];
+            io.read_exact(&mut payload).await.unwrap();
+            if head[3] == 1 {
+                return payload;
+            }
+        }
+    }
+
+    /// Returns the first byte of each header field representation in the header block.
+    fn representations(mut block: &[u8]) -> Vec<u8> {
+        fn skip_int(block: &mut &[u8], prefix_bits: u8) {
+            let first = block[0];
+            *block = &block[1..];
+            if first & ((1 << prefix_bits) - 1) == (1 << prefix_bits) - 1 {
+                while block[0] & 0x80 != 0 {
+                    *block = &block[1..];
+                }
+                *block = &block[1..];
+            }
+        }
+
+        fn skip_str(block: &mut &[u8]) {
+            let len = (block[0] & 0x7f) as usize;
+            assert!(len < 0x7f, "multi-byte string length not supported");
+            *block = &block[1 + len..];
+        }
+
+        let mut firsts = Vec::new();
+        while !block.is_empty() {
+            let first = block[0];
+            firsts.push(first);
+            if first & 0x80 != 0 {
+                skip_int(&mut block, 7);
+            } else if first & 0xe0 == 0x20 {
+                skip_int(&mut block, 5);
+            } else {
+                let prefix_bits = if first & 0x40 != 0 { 6 } else { 4 };
+                let has_name = first & ((1 << prefix_bits) - 1) == 0;
+                skip_int(&mut block, prefix_bits);
+                if has_name {
+                    skip_str(&mut block);
+                }
+                skip_str(&mut block);
+            }
+        }
+        firsts
+    }
+
+    async fn send_request_headers(builder: &Builder) -> Vec<u8> {
+        let (io, mut server) = tokio::io::duplex(64 * 1024);
+        let (mut client, conn) = builder.handshake::<_, Bytes>(io).await.unwrap();
+        tokio::spawn(conn);
+
+        let req = Request::builder()
+            .uri("https://example.com/")
+            .header("x-custom", "foo")
+            .header("user-agent", "rama")
+            .body(())
+            .unwrap();
+        let _ = client.send_request(req, true).unwrap();
+
+        read_headers_payload(&mut server).await
+    }
+
+    #[tokio::test]
+    async fn test_header_indexing_default() {
+        let block = send_request_headers(&Builder::new()).await;
+        let firsts = representations(&block);
+        // :method GET and :scheme https are fully indexed by the static table,
+        // other headers are inserted in the dynamic table
+        assert!(firsts.iter().any(|first| first & 0x80 != 0));
+        assert!(firsts.iter().any(|first| first & 0xc0 == 0x40));
+    }
+
+    #[tokio::test]
+    async fn test_header_indexing_disabled() {
+        let mut builder = Builder::new();
+        builder.enable_header_indexing(false);
+
+        let block = send_request_headers(&builder).await;
+        let firsts = representations(&block);
+        assert_eq!(firsts.len(), 6);
+        // all literal header fields without indexing
+        assert!(firsts.iter().all(|first| first & 0xf0 == 0), "{firsts:x?}");
+    }
+
+    #[tokio::test]
+    async fn test_max_encoder_header_table_size() {
+        let mut builder = Builder::new();
+        builder.max_encoder_header_table_size(0);
+
+        let block = send_request_headers(&builder).await;
+        let firsts = representations(&block);
+        // dynamic table size update to 0, followed by the header fields
+        assert_eq!(firsts[0], 0x20);
+        assert!(firsts[1..].iter().all(|first| first & 0xc0 != 0x40));
+    }
+}
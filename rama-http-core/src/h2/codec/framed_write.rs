@@ -326,6 +326,16 @@ impl<T, B> FramedWrite<T, B> {
         self.encoder.hpack.update_max_size(val);
     }
 
+    /// Limit the header table size used to encode header blocks.
+    pub(super) fn set_header_table_size_limit(&mut self, val: usize) {
+        self.encoder.hpack.set_max_size_limit(val);
+    }
+
+    /// Enable or disable the indexing of encoded headers.
+    pub(super) fn set_header_indexing(&mut self, enabled: bool) {
+        self.encoder.hpack.set_indexing(enabled);
+    }
+
     /// Retrieve the last data frame that has been sent
     pub(super) fn take_last_data_frame(&mut self) -> Option<frame::Data<B>> {
         self.encoder.last_data_frame.take()
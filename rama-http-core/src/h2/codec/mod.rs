@@ -95,6 +95,17 @@ impl<T, B> Codec<T, B> {
         self.framed_write().set_header_table_size(val)
     }
 
+    /// Limit the header table size used to encode header blocks,
+    /// regardless of the (larger) size allowed by the peer.
+    pub fn set_send_header_table_size_limit(&mut self, val: usize) {
+        self.framed_write().set_header_table_size_limit(val)
+    }
+
+    /// Enable or disable the indexing of encoded headers.
+    pub fn set_send_header_indexing(&mut self, enabled: bool) {
+        self.framed_write().set_header_indexing(enabled)
+    }
+
     /// Set the decoder header table size size.
     pub fn set_recv_header_table_size(&mut self, val: usize) {
         self.inner.set_header_table_size(val)
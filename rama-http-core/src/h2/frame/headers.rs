@@ -310,6 +310,10 @@ impl Headers {
         self.header_block.is_over_size
     }
 
+    pub fn stream_dep(&self) -> Option<&StreamDependency> {
+        self.stream_dep.as_ref()
+    }
+
     pub fn into_parts(self) -> (Pseudo, HeaderMap, OriginalHttp1Headers) {
         (
             self.header_block.pseudo,
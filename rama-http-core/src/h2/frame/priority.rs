@@ -1,4 +1,5 @@
 use crate::h2::frame::*;
+use rama_http_types::proto;
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Priority {
@@ -33,6 +34,14 @@ impl Priority {
             dependency,
         })
     }
+
+    pub fn stream_id(&self) -> StreamId {
+        self.stream_id
+    }
+
+    pub fn dependency(&self) -> &StreamDependency {
+        &self.dependency
+    }
 }
 
 impl<B> From<Priority> for Frame<B> {
@@ -69,4 +78,24 @@ impl StreamDependency {
     pub fn dependency_id(&self) -> StreamId {
         self.dependency_id
     }
+
+    pub fn weight(&self) -> u8 {
+        self.weight
+    }
+
+    pub fn is_exclusive(&self) -> bool {
+        self.is_exclusive
+    }
+}
+
+impl From<&StreamDependency> for proto::h2::StreamDependency {
+    fn from(src: &StreamDependency) -> Self {
+        proto::h2::StreamDependency::new(src.dependency_id.into(), src.weight, src.is_exclusive)
+    }
+}
+
+impl From<&Priority> for proto::h2::Priority {
+    fn from(src: &Priority) -> Self {
+        proto::h2::Priority::new(src.stream_id.into(), src.dependency().into())
+    }
 }
@@ -2,8 +2,9 @@ use std::fmt;
 
 use crate::h2::frame::{util, Error, Frame, FrameSize, Head, Kind, StreamId};
 use bytes::{BufMut, BytesMut};
+use rama_http_types::proto::h2::{InitialPeerSettings, Setting as ReceivedSetting};
 
-#[derive(Clone, Default, Eq, PartialEq)]
+#[derive(Clone, Default, Eq)]
 pub struct Settings {
     flags: SettingsFlags,
     // Fields
@@ -14,6 +15,23 @@ pub struct Settings {
     max_frame_size: Option<u32>,
     max_header_list_size: Option<u32>,
     enable_connect_protocol: Option<u32>,
+    // All settings as received (including unknown ones), in the order received,
+    // only used for fingerprinting purposes
+    received: Vec<ReceivedSetting>,
+}
+
+impl PartialEq for Settings {
+    fn eq(&self, other: &Self) -> bool {
+        // the received order is not part of the semantics of the frame
+        self.flags == other.flags
+            && self.header_table_size == other.header_table_size
+            && self.enable_push == other.enable_push
+            && self.max_concurrent_streams == other.max_concurrent_streams
+            && self.initial_window_size == other.initial_window_size
+            && self.max_frame_size == other.max_frame_size
+            && self.max_header_list_size == other.max_header_list_size
+            && self.enable_connect_protocol == other.enable_connect_protocol
+    }
 }
 
 /// An enum that lists all valid settings that can be sent in a SETTINGS
@@ -66,6 +84,11 @@ impl Settings {
         self.flags.is_ack()
     }
 
+    /// All settings as received from the peer (including unknown ones), in the order received.
+    pub fn received(&self) -> InitialPeerSettings {
+        self.received.iter().copied().collect()
+    }
+
     pub fn initial_window_size(&self) -> Option<u32> {
         self.initial_window_size
     }
@@ -155,6 +178,10 @@ impl Settings {
         debug_assert!(!settings.flags.is_ack());
 
         for raw in payload.chunks(6) {
+            let id: u16 = (u16::from(raw[0]) << 8) | u16::from(raw[1]);
+            let val: u32 = unpack_octets_4!(raw, 2, u32);
+            settings.received.push(ReceivedSetting::new(id, val));
+
             match Setting::load(raw) {
                 Some(Setting::HeaderTableSize(val)) => {
                     settings.header_table_size = Some(val);
@@ -8,6 +8,8 @@ use rama_http_types::dep::http::header::{HeaderName, HeaderValue};
 pub struct Encoder {
     table: Table,
     size_update: Option<SizeUpdate>,
+    max_size_limit: Option<usize>,
+    indexing: bool,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -21,13 +23,41 @@ impl Encoder {
         Encoder {
             table: Table::new(max_size, capacity),
             size_update: None,
+            max_size_limit: None,
+            indexing: true,
         }
     }
 
+    /// Limit the max size of the dynamic table,
+    /// regardless of the (larger) max size allowed by the peer.
+    ///
+    /// A max size update is queued in case the current max size exceeds the limit.
+    pub fn set_max_size_limit(&mut self, limit: usize) {
+        self.max_size_limit = Some(limit);
+
+        let max_size = match self.size_update {
+            Some(SizeUpdate::One(val) | SizeUpdate::Two(_, val)) => val,
+            None => self.table.max_size(),
+        };
+        if max_size > limit {
+            self.update_max_size(limit);
+        }
+    }
+
+    /// Enable or disable the indexing of headers.
+    ///
+    /// When disabled, all headers are encoded as literals without indexing,
+    /// only referring to the static table for header names.
+    pub fn set_indexing(&mut self, enabled: bool) {
+        self.indexing = enabled;
+    }
+
     /// Queues a max size update.
     ///
     /// The next call to `encode` will include a dynamic size update frame.
     pub fn update_max_size(&mut self, val: usize) {
+        let val = self.max_size_limit.map_or(val, |limit| val.min(limit));
+
         match self.size_update {
             Some(SizeUpdate::One(old)) => {
                 if val > old {
@@ -74,7 +104,11 @@ impl Encoder {
                 // The header has an associated name. In which case, try to
                 // index it in the table.
                 Ok(header) => {
-                    let index = self.table.index(header);
+                    let index = if self.indexing {
+                        self.table.index(header)
+                    } else {
+                        Table::index_literal(header)
+                    };
                     self.encode_header(&index, dst);
 
                     last_index = Some(index);
@@ -685,6 +719,51 @@ mod test {
         assert_eq!([63, 225, 129, 148, 144, 7], &dst[..]);
     }
 
+    #[test]
+    fn test_max_size_limit() {
+        let mut encoder = Encoder::default();
+        encoder.set_max_size_limit(8000);
+        assert!(encoder.size_update.is_none());
+
+        encoder.set_max_size_limit(100);
+        assert_eq!(Some(SizeUpdate::One(100)), encoder.size_update);
+
+        // the peer allows a bigger table, but the limit applies
+        encoder.update_max_size(8000);
+        assert_eq!(Some(SizeUpdate::One(100)), encoder.size_update);
+
+        encoder.update_max_size(50);
+        assert_eq!(Some(SizeUpdate::One(50)), encoder.size_update);
+
+        let res = encode(&mut encoder, vec![header("foo", "bar")]);
+        assert_eq!(0x20 | 31, res[0]);
+        assert_eq!(50 - 31, res[1]);
+        assert_eq!(50, encoder.table.max_size());
+    }
+
+    #[test]
+    fn test_encode_without_indexing() {
+        let mut encoder = Encoder::default();
+        encoder.set_indexing(false);
+
+        // fully indexed by the static table, encoded as literal with indexed name
+        let res = encode(&mut encoder, vec![method("GET")]);
+        assert_eq!(2, res[0]);
+        assert_eq!(0x80 | 3, res[1]);
+        assert_eq!("GET", huff_decode(&res[2..]));
+
+        // never inserted in the dynamic table
+        for _ in 0..2 {
+            let res = encode(&mut encoder, vec![header("foo", "hello")]);
+            assert_eq!(0, res[0]);
+            assert_eq!(0x80 | 2, res[1]);
+            assert_eq!("foo", huff_decode(&res[2..4]));
+            assert_eq!(0x80 | 4, res[4]);
+            assert_eq!("hello", huff_decode(&res[5..]));
+        }
+        assert_eq!(0, encoder.table.len());
+    }
+
     #[test]
     #[ignore]
     fn test_evicted_overflow() {
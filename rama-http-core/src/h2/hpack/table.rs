@@ -154,6 +154,14 @@ impl Table {
         self.index_dynamic(header, statik)
     }
 
+    /// Index the header as a literal, only using the static table for its name.
+    pub(super) fn index_literal(header: Header) -> Index {
+        match index_static(&header) {
+            Some((n, _)) => Index::Name(n, header),
+            None => Index::NotIndexed(header),
+        }
+    }
+
     fn index_dynamic(&mut self, header: Header, statik: Option<(usize, bool)>) -> Index {
         debug_assert!(self.assert_valid_state("one"));
 
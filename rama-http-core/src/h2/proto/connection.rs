@@ -541,7 +541,7 @@ where
             }
             Some(Frame::Priority(frame)) => {
                 tracing::trace!(?frame, "recv PRIORITY");
-                // TODO: handle
+                self.streams.recv_priority(&frame);
             }
             None => {
                 tracing::trace!("codec closed");
@@ -4,6 +4,7 @@ use crate::h2::frame::{PushPromiseHeaderError, Reason, DEFAULT_INITIAL_WINDOW_SI
 use crate::h2::proto;
 
 use rama_http_types::proto::h1::headers::original::OriginalHttp1Headers;
+use rama_http_types::proto::h2::{InitialPeerSettings, PriorityFrames, StreamDependency};
 use rama_http_types::{HeaderMap, Request, Response};
 
 use std::cmp::Ordering;
@@ -60,8 +61,20 @@ pub(super) struct Recv {
 
     /// If extended connect protocol is enabled.
     is_extended_connect_protocol_enabled: bool,
+
+    /// PRIORITY frames received on the connection,
+    /// exposed as an extension of received requests.
+    priority_frames: PriorityFrames,
+
+    /// Settings of the initial SETTINGS frame received on the connection,
+    /// exposed as an extension of received requests.
+    initial_peer_settings: Option<InitialPeerSettings>,
 }
 
+/// Maximum amount of PRIORITY frames recorded per connection,
+/// any PRIORITY frame received once reached is ignored.
+const MAX_RECORDED_PRIORITY_FRAMES: usize = 32;
+
 #[derive(Debug)]
 pub(super) enum Event {
     Headers(peer::PollMessage),
@@ -108,6 +121,8 @@ impl Recv {
             refused: None,
             is_push_enabled: config.local_push_enabled,
             is_extended_connect_protocol_enabled: config.extended_connect_protocol_enabled,
+            priority_frames: PriorityFrames::new(),
+            initial_peer_settings: None,
         }
     }
 
@@ -235,6 +250,7 @@ impl Recv {
         }
 
         let stream_id = frame.stream_id();
+        let stream_dep = frame.stream_dep().map(StreamDependency::from);
         let (pseudo, fields, field_order) = frame.into_parts();
 
         if pseudo.protocol.is_some()
@@ -251,11 +267,25 @@ impl Recv {
         }
 
         if !pseudo.is_informational() {
-            let message =
+            let mut message =
                 counts
                     .peer()
                     .convert_poll_message(pseudo, fields, field_order, stream_id)?;
 
+            if let peer::PollMessage::Server(ref mut request) = message {
+                if let Some(stream_dep) = stream_dep {
+                    request.extensions_mut().insert(stream_dep);
+                }
+                if let Some(settings) = &self.initial_peer_settings {
+                    request.extensions_mut().insert(settings.clone());
+                }
+                if !self.priority_frames.is_empty() {
+                    request
+                        .extensions_mut()
+                        .insert(self.priority_frames.clone());
+                }
+            }
+
             // Push the frame onto the stream's recv buffer
             stream
                 .pending_recv
@@ -274,6 +304,20 @@ impl Recv {
         Ok(())
     }
 
+    /// Record the initial SETTINGS frame received
+    pub(super) fn recv_initial_settings(&mut self, frame: &frame::Settings) {
+        self.initial_peer_settings = Some(frame.received());
+    }
+
+    /// Record a received PRIORITY frame
+    pub(super) fn recv_priority(&mut self, frame: &frame::Priority) {
+        if self.priority_frames.len() >= MAX_RECORDED_PRIORITY_FRAMES {
+            tracing::trace!(?frame, "ignore PRIORITY frame: max recorded frames reached");
+            return;
+        }
+        self.priority_frames.push(frame.into());
+    }
+
     /// Called by the server to get the request
     ///
     /// # Panics
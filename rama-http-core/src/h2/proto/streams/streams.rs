@@ -219,6 +219,9 @@ where
         let send_buffer = &mut *send_buffer;
 
         me.counts.apply_remote_settings(frame, is_initial);
+        if is_initial {
+            me.actions.recv.recv_initial_settings(frame);
+        }
 
         me.actions.send.apply_remote_settings(
             frame,
@@ -399,6 +402,11 @@ impl<B> DynStreams<'_, B> {
         me.recv_window_update(self.send_buffer, frame)
     }
 
+    pub(crate) fn recv_priority(&mut self, frame: &frame::Priority) {
+        let mut me = self.inner.lock().unwrap();
+        me.actions.recv.recv_priority(frame);
+    }
+
     pub(crate) fn recv_push_promise(&mut self, frame: frame::PushPromise) -> Result<(), Error> {
         let mut me = self.inner.lock().unwrap();
         me.recv_push_promise(self.send_buffer, frame)
@@ -1668,3 +1668,116 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_http_types::proto::h2::{
+        InitialPeerSettings, Priority, PriorityFrames, Setting, StreamDependency,
+    };
+    use tokio::io::AsyncWriteExt;
+
+    fn priority_frame(stream_id: u8, dependency_id: u8, weight: u8) -> [u8; 14] {
+        [
+            0,
+            0,
+            5,
+            2,
+            0,
+            0,
+            0,
+            0,
+            stream_id,
+            0,
+            0,
+            0,
+            dependency_id,
+            weight,
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_server_exposes_priority_info() {
+        let (mut client, server) = tokio::io::duplex(4096);
+
+        let mut input = Vec::new();
+        input.extend_from_slice(&PREFACE);
+        // empty SETTINGS
+        input.extend_from_slice(&[0, 0, 0, 4, 0, 0, 0, 0, 0]);
+        // PRIORITY frames as sent by Firefox when opening a connection
+        for (stream_id, dependency_id, weight) in [
+            (3, 0, 200),
+            (5, 0, 100),
+            (7, 0, 0),
+            (9, 7, 0),
+            (11, 3, 0),
+            (13, 0, 240),
+        ] {
+            input.extend_from_slice(&priority_frame(stream_id, dependency_id, weight));
+        }
+        // HEADERS (END_STREAM | END_HEADERS | PRIORITY) with exclusive dependency on 13:
+        // :method GET, :scheme https, :path /
+        input.extend_from_slice(&[
+            0, 0, 8, 1, 0x25, 0, 0, 0, 15, 0x80, 0, 0, 13, 41, 0x82, 0x87, 0x84,
+        ]);
+        client.write_all(&input).await.unwrap();
+
+        let mut conn = handshake(server).await.unwrap();
+        let (req, _) = conn.accept().await.unwrap().unwrap();
+
+        assert_eq!(
+            req.extensions().get::<StreamDependency>(),
+            Some(&StreamDependency::new(13, 41, true)),
+        );
+
+        let expected: PriorityFrames = [
+            (3, 0, 200),
+            (5, 0, 100),
+            (7, 0, 0),
+            (9, 7, 0),
+            (11, 3, 0),
+            (13, 0, 240),
+        ]
+        .into_iter()
+        .map(|(stream_id, dependency_id, weight)| {
+            Priority::new(
+                stream_id,
+                StreamDependency::new(dependency_id, weight, false),
+            )
+        })
+        .collect();
+        assert_eq!(req.extensions().get::<PriorityFrames>(), Some(&expected));
+    }
+
+    #[tokio::test]
+    async fn test_server_exposes_initial_peer_settings() {
+        let (mut client, server) = tokio::io::duplex(4096);
+
+        // settings as sent by Chrome, including the (unknown to rama) SETTINGS_NO_RFC7540_PRIORITIES
+        let settings = [(1u16, 65536u32), (2, 0), (4, 6291456), (6, 262144), (9, 1)];
+
+        let mut input = Vec::new();
+        input.extend_from_slice(&PREFACE);
+        input.extend_from_slice(&[0, 0, (settings.len() * 6) as u8, 4, 0, 0, 0, 0, 0]);
+        for (id, value) in settings {
+            input.extend_from_slice(&id.to_be_bytes());
+            input.extend_from_slice(&value.to_be_bytes());
+        }
+        // HEADERS (END_STREAM | END_HEADERS): :method GET, :scheme https, :path /
+        input.extend_from_slice(&[0, 0, 3, 1, 0x05, 0, 0, 0, 1, 0x82, 0x87, 0x84]);
+        client.write_all(&input).await.unwrap();
+
+        let mut conn = handshake(server).await.unwrap();
+        let (req, _) = conn.accept().await.unwrap().unwrap();
+
+        let expected: InitialPeerSettings = settings
+            .into_iter()
+            .map(|(id, value)| Setting::new(id, value))
+            .collect();
+        assert_eq!(
+            req.extensions().get::<InitialPeerSettings>(),
+            Some(&expected)
+        );
+        assert!(req.extensions().get::<PriorityFrames>().is_none());
+    }
+}
@@ -53,6 +53,8 @@ where
                 method: None,
                 h1_parser_config: ParserConfig::default(),
                 h1_max_headers: None,
+                h1_max_requests: None,
+                h1_requests: 0,
                 h1_header_read_timeout: None,
                 h1_header_read_timeout_fut: None,
                 h1_header_read_timeout_running: false,
@@ -108,6 +110,10 @@ where
         self.state.h1_max_headers = Some(val);
     }
 
+    pub(crate) fn set_http1_max_requests(&mut self, val: usize) {
+        self.state.h1_max_requests = Some(val);
+    }
+
     pub(crate) fn set_http1_header_read_timeout(&mut self, val: Duration) {
         self.state.h1_header_read_timeout = Some(val);
     }
@@ -238,6 +244,16 @@ where
 
         self.state.busy();
         self.state.keep_alive &= msg.keep_alive;
+        if let Some(max_requests) = self.state.h1_max_requests {
+            self.state.h1_requests += 1;
+            if self.state.h1_requests >= max_requests {
+                debug!(
+                    "max requests per connection ({}) reached, closing connection after this request",
+                    max_requests
+                );
+                self.state.disable_keep_alive();
+            }
+        }
         self.state.version = msg.head.version;
 
         let mut wants = if msg.wants_upgrade {
@@ -855,6 +871,10 @@ struct State {
     method: Option<Method>,
     h1_parser_config: ParserConfig,
     h1_max_headers: Option<usize>,
+    /// Maximum amount of messages to read before keep-alive gets disabled.
+    h1_max_requests: Option<usize>,
+    /// Amount of messages read so far, only tracked if `h1_max_requests` is set.
+    h1_requests: usize,
     h1_header_read_timeout: Option<Duration>,
     h1_header_read_timeout_fut: Option<Pin<Box<Sleep>>>,
     h1_header_read_timeout_running: bool,
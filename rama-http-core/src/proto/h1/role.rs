@@ -2383,6 +2383,59 @@ mod tests {
         assert_eq!(&vec[..expected_response.len()], &expected_response[..]);
     }
 
+    #[test]
+    fn test_server_response_encode_reason_phrase() {
+        use crate::proto::BodyLength;
+
+        fn encode(status: StatusCode, reason: Option<crate::ext::ReasonPhrase>) -> Vec<u8> {
+            let mut head = MessageHead {
+                subject: status,
+                ..Default::default()
+            };
+            if let Some(reason) = reason {
+                head.extensions.insert(reason);
+            }
+
+            let mut vec = Vec::new();
+            Server::encode(
+                Encode {
+                    head: EncodeHead {
+                        version: head.version,
+                        subject: head.subject,
+                        headers: head.headers,
+                        extensions: &mut head.extensions,
+                    },
+                    body: Some(BodyLength::Known(0)),
+                    keep_alive: true,
+                    req_method: &mut None,
+                    title_case_headers: false,
+                    date_header: false,
+                },
+                &mut vec,
+            )
+            .unwrap();
+            vec
+        }
+
+        let custom = encode(
+            StatusCode::OK,
+            Some(crate::ext::ReasonPhrase::from_static(b"Totally Fine")),
+        );
+        assert!(custom.starts_with(b"HTTP/1.1 200 Totally Fine\r\n"));
+
+        let canonical = encode(StatusCode::OK, None);
+        assert!(canonical.starts_with(b"HTTP/1.1 200 OK\r\n"));
+
+        let canonical = encode(StatusCode::NOT_FOUND, None);
+        assert!(canonical.starts_with(b"HTTP/1.1 404 Not Found\r\n"));
+
+        let custom = encode(
+            StatusCode::NOT_FOUND,
+            Some(crate::ext::ReasonPhrase::from_static(b"Gone Fishing")),
+        );
+        assert!(custom.starts_with(b"HTTP/1.1 404 Gone Fishing\r\n"));
+    }
+
     #[test]
     fn test_disabled_date_header() {
         use crate::proto::BodyLength;
@@ -76,6 +76,8 @@ pub(crate) struct Config {
     pub(crate) max_send_buffer_size: usize,
     pub(crate) max_pending_accept_reset_streams: Option<usize>,
     pub(crate) header_table_size: Option<u32>,
+    pub(crate) max_encoder_header_table_size: Option<u32>,
+    pub(crate) header_indexing: bool,
     pub(crate) max_concurrent_streams: Option<u32>,
 }
 
@@ -95,6 +97,8 @@ impl Default for Config {
             max_send_buffer_size: DEFAULT_MAX_SEND_BUF_SIZE,
             max_pending_accept_reset_streams: None,
             header_table_size: None,
+            max_encoder_header_table_size: None,
+            header_indexing: true,
             max_concurrent_streams: None,
         }
     }
@@ -121,6 +125,10 @@ fn new_builder(config: &Config) -> Builder {
     if let Some(size) = config.header_table_size {
         builder.header_table_size(size);
     }
+    if let Some(size) = config.max_encoder_header_table_size {
+        builder.max_encoder_header_table_size(size);
+    }
+    builder.enable_header_indexing(config.header_indexing);
     if let Some(max) = config.max_concurrent_streams {
         builder.max_concurrent_streams(max);
     }
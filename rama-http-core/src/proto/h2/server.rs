@@ -53,6 +53,7 @@ pub(crate) struct Config {
     pub(crate) max_send_buffer_size: usize,
     pub(crate) max_header_list_size: u32,
     pub(crate) date_header: bool,
+    pub(crate) max_requests_per_connection: Option<usize>,
 }
 
 impl Default for Config {
@@ -71,6 +72,7 @@ impl Default for Config {
             max_send_buffer_size: DEFAULT_MAX_SEND_BUF_SIZE,
             max_header_list_size: DEFAULT_SETTINGS_MAX_HEADER_LIST_SIZE,
             date_header: true,
+            max_requests_per_connection: None,
         }
     }
 }
@@ -84,6 +86,8 @@ pin_project! {
         service: S,
         state: State<T>,
         date_header: bool,
+        // remaining requests to accept before the connection is gracefully shutdown
+        requests_left: Option<usize>,
         close_pending: bool,
     }
 }
@@ -151,6 +155,7 @@ where
             },
             service,
             date_header: config.date_header,
+            requests_left: config.max_requests_per_connection,
             close_pending: false,
         }
     }
@@ -204,7 +209,7 @@ where
                     if me.close_pending && srv.closing.is_none() {
                         srv.conn.graceful_shutdown();
                     }
-                    ready!(srv.poll_server(cx, &mut me.service, &me.exec))?;
+                    ready!(srv.poll_server(cx, &mut me.service, &me.exec, &mut me.requests_left))?;
                     return Poll::Ready(Ok(Dispatched::Shutdown));
                 }
             };
@@ -222,6 +227,7 @@ where
         cx: &mut Context<'_>,
         service: &mut S,
         exec: &Executor,
+        requests_left: &mut Option<usize>,
     ) -> Poll<crate::Result<()>>
     where
         S: HttpService<IncomingBody>,
@@ -286,6 +292,15 @@ where
                         );
 
                         exec.spawn_task(fut);
+
+                        if let Some(left) = requests_left.as_mut() {
+                            *left = left.saturating_sub(1);
+                            if *left == 0 {
+                                debug!("max requests per connection reached, initiate graceful shutdown");
+                                self.conn.graceful_shutdown();
+                                *requests_left = None;
+                            }
+                        }
                     }
                     Some(Err(e)) => {
                         return Poll::Ready(Err(crate::Error::new_h2(e)));
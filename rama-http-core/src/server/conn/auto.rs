@@ -496,6 +496,17 @@ impl Http1Builder<'_> {
         self
     }
 
+    /// Set the maximum number of requests served over a single HTTP/1 connection.
+    ///
+    /// Once the limit is reached, the last response is sent with
+    /// a `Connection: close` header, after which the connection is closed.
+    ///
+    /// Pass `None` to disable. Default is `None`.
+    pub fn max_requests_per_connection(&mut self, max: impl Into<Option<usize>>) -> &mut Self {
+        self.inner.http1.max_requests_per_connection(max);
+        self
+    }
+
     /// Set whether HTTP/1 connections will write header names as title case at
     /// the socket level.
     ///
@@ -676,6 +687,17 @@ impl Http2Builder<'_> {
         self
     }
 
+    /// Sets the maximum number of requests served over a single HTTP2 connection.
+    ///
+    /// Once the limit is reached, a graceful shutdown of the connection
+    /// is initiated by sending a GOAWAY frame.
+    ///
+    /// Passing `None` will remove any limit. Default is `None`.
+    pub fn max_requests_per_connection(&mut self, max: impl Into<Option<usize>>) -> &mut Self {
+        self.inner.http2.max_requests_per_connection(max);
+        self
+    }
+
     /// Sets an interval for HTTP2 Ping frames should be sent to keep a
     /// connection alive.
     ///
@@ -69,6 +69,7 @@ pub struct Builder {
     h1_keep_alive: bool,
     h1_title_case_headers: bool,
     h1_max_headers: Option<usize>,
+    h1_max_requests: Option<usize>,
     h1_header_read_timeout: Duration,
     h1_writev: Option<bool>,
     max_buf_size: Option<usize>,
@@ -229,6 +230,7 @@ impl Builder {
             h1_keep_alive: true,
             h1_title_case_headers: false,
             h1_max_headers: None,
+            h1_max_requests: None,
             h1_header_read_timeout: Duration::from_secs(30),
             h1_writev: None,
             max_buf_size: None,
@@ -297,6 +299,20 @@ impl Builder {
         self
     }
 
+    /// Set the maximum number of requests served over a single connection.
+    ///
+    /// Once the limit is reached, keep-alive is disabled for the connection:
+    /// the last response is sent with a `Connection: close` header,
+    /// after which the connection is closed.
+    ///
+    /// Pass `None` to disable.
+    ///
+    /// Default is `None`.
+    pub fn max_requests_per_connection(&mut self, max: impl Into<Option<usize>>) -> &mut Self {
+        self.h1_max_requests = max.into();
+        self
+    }
+
     /// Set a timeout for reading client request headers. If a client does not
     /// transmit the entire header within this time, the connection is closed.
     ///
@@ -392,6 +408,9 @@ impl Builder {
         if let Some(max_headers) = self.h1_max_headers {
             conn.set_http1_max_headers(max_headers);
         }
+        if let Some(max_requests) = self.h1_max_requests {
+            conn.set_http1_max_requests(max_requests);
+        }
         conn.set_http1_header_read_timeout(self.h1_header_read_timeout);
         if let Some(writev) = self.h1_writev {
             if writev {
@@ -197,6 +197,20 @@ impl Builder {
         self
     }
 
+    /// Sets the maximum number of requests served over a single connection.
+    ///
+    /// Once the limit is reached, a graceful shutdown of the connection is
+    /// initiated by sending a GOAWAY frame: requests already accepted are
+    /// still served, after which the connection is closed.
+    ///
+    /// Passing `None` will remove any limit.
+    ///
+    /// Default is `None`.
+    pub fn max_requests_per_connection(&mut self, max: impl Into<Option<usize>>) -> &mut Self {
+        self.h2_builder.max_requests_per_connection = max.into();
+        self
+    }
+
     /// Sets an interval for HTTP2 Ping frames should be sent to keep a
     /// connection alive.
     ///
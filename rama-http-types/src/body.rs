@@ -1,6 +1,7 @@
 //! HTTP body utilities.
 
 use crate::dep::{
+    http::HeaderMap,
     http_body::{self, Body as _, Frame},
     http_body_util::{self, BodyExt},
 };
@@ -78,6 +79,19 @@ impl Body {
         Self::new(crate::dep::http_body_util::Limited::new(self.0, limit))
     }
 
+    /// Attach trailers to this [`Body`], sent as the last frame,
+    /// after all data frames of the body.
+    ///
+    /// Trailers are sent as a chunked trailer section over HTTP/1.1,
+    /// in which case the (response) headers should declare the trailer fields
+    /// using the `Trailer` header, and as a trailing HEADERS frame over h2.
+    pub fn with_trailers(self, trailers: HeaderMap) -> Self {
+        Self::new(TrailersBody {
+            inner: self.0,
+            trailers: Some(trailers),
+        })
+    }
+
     /// Convert the body into a [`Stream`] of data frames.
     ///
     /// Non-data frames (such as trailers) will be discarded. Use [`http_body_util::BodyStream`] if
@@ -221,8 +235,62 @@ where
     }
 }
 
+pin_project! {
+    struct TrailersBody {
+        #[pin]
+        inner: BoxBody,
+        trailers: Option<HeaderMap>,
+    }
+}
+
+impl http_body::Body for TrailersBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        match futures_lite::ready!(this.inner.poll_frame(cx)) {
+            Some(frame) => Poll::Ready(Some(frame)),
+            None => Poll::Ready(
+                this.trailers
+                    .take()
+                    .map(|trailers| Ok(Frame::trailers(trailers))),
+            ),
+        }
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        // no exact size is reported, as that would result in a `Content-Length`
+        // framed body over HTTP/1.1, which has no room for trailers
+        let mut hint = http_body::SizeHint::new();
+        hint.set_lower(self.inner.size_hint().lower());
+        hint
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.trailers.is_none() && self.inner.is_end_stream()
+    }
+}
+
 #[test]
 fn test_try_downcast() {
     assert_eq!(try_downcast::<i32, _>(5_u32), Err(5_u32));
     assert_eq!(try_downcast::<i32, _>(5_i32), Ok(5_i32));
 }
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_body_with_trailers() {
+    let mut trailers = HeaderMap::new();
+    trailers.insert("x-checksum", crate::HeaderValue::from_static("abc123"));
+
+    let body = Body::from("hello").with_trailers(trailers.clone());
+    assert_eq!(body.size_hint().exact(), None);
+
+    let collected = body.collect().await.unwrap();
+    assert_eq!(collected.trailers(), Some(&trailers));
+    assert_eq!(collected.to_bytes(), "hello");
+}
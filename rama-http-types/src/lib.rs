@@ -37,6 +37,8 @@ pub mod proto;
 
 pub mod headers;
 
+pub mod uri;
+
 pub mod dep {
     //! Dependencies for rama http modules.
     //!
@@ -122,6 +124,12 @@ pub mod header {
     // standard
     static_header!["keep-alive", "proxy-connection"];
 
+    // draft-ietf-httpapi-idempotency-key-header
+    static_header!["idempotency-key"];
+
+    // W3C Network Error Logging and Reporting API
+    static_header!["nel", "report-to", "reporting-endpoints"];
+
     // non-std client ip forward headers
     static_header![
         "cf-connecting-ip",
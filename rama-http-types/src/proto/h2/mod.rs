@@ -4,3 +4,9 @@ mod pseudo_header;
 pub use pseudo_header::{
     InvalidPseudoHeaderStr, PseudoHeader, PseudoHeaderOrder, PseudoHeaderOrderIter,
 };
+
+mod priority;
+pub use priority::{Priority, PriorityFrames, StreamDependency};
+
+mod settings;
+pub use settings::{InitialPeerSettings, Setting};
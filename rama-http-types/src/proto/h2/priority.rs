@@ -0,0 +1,125 @@
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+/// The stream dependency (priority) of an h2 stream, as received from the peer.
+///
+/// This is the information found in a PRIORITY frame or
+/// in a HEADERS frame with the PRIORITY flag set.
+pub struct StreamDependency {
+    dependency_id: u32,
+    weight: u8,
+    is_exclusive: bool,
+}
+
+impl StreamDependency {
+    /// Create a new [`StreamDependency`].
+    pub fn new(dependency_id: u32, weight: u8, is_exclusive: bool) -> Self {
+        Self {
+            dependency_id,
+            weight,
+            is_exclusive,
+        }
+    }
+
+    /// The ID of the stream dependency target.
+    pub fn dependency_id(&self) -> u32 {
+        self.dependency_id
+    }
+
+    /// The weight of the stream as found on the wire.
+    ///
+    /// This value is in the range `[0, 255]` rather than the `[1, 256]`
+    /// range of the actual weight, so that it fits into a `u8`.
+    pub fn weight(&self) -> u8 {
+        self.weight
+    }
+
+    /// True if the stream dependency is exclusive.
+    pub fn is_exclusive(&self) -> bool {
+        self.is_exclusive
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+/// A PRIORITY frame as received from the peer.
+pub struct Priority {
+    stream_id: u32,
+    dependency: StreamDependency,
+}
+
+impl Priority {
+    /// Create a new [`Priority`] for the given stream.
+    pub fn new(stream_id: u32, dependency: StreamDependency) -> Self {
+        Self {
+            stream_id,
+            dependency,
+        }
+    }
+
+    /// The ID of the stream this priority applies to.
+    pub fn stream_id(&self) -> u32 {
+        self.stream_id
+    }
+
+    /// The [`StreamDependency`] of the stream.
+    pub fn dependency(&self) -> StreamDependency {
+        self.dependency
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+/// The PRIORITY frames received on an h2 connection, in the order received.
+///
+/// Inserted by the h2 server as an extension of each request,
+/// containing the frames received on the connection prior to the request.
+/// Useful for fingerprinting, e.g. as the PRIORITY part of an Akamai h2 fingerprint.
+pub struct PriorityFrames(Vec<Priority>);
+
+impl PriorityFrames {
+    /// Create a new empty [`PriorityFrames`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Push a [`Priority`] frame.
+    pub fn push(&mut self, priority: Priority) {
+        self.0.push(priority);
+    }
+
+    /// Iterate over the [`Priority`] frames, in the order received.
+    pub fn iter(&self) -> std::slice::Iter<'_, Priority> {
+        self.0.iter()
+    }
+
+    /// Returns true if no [`Priority`] frames were received.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of received [`Priority`] frames.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl FromIterator<Priority> for PriorityFrames {
+    fn from_iter<T: IntoIterator<Item = Priority>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for PriorityFrames {
+    type Item = Priority;
+    type IntoIter = std::vec::IntoIter<Priority>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PriorityFrames {
+    type Item = &'a Priority;
+    type IntoIter = std::slice::Iter<'a, Priority>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
@@ -0,0 +1,84 @@
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+/// A single setting of a SETTINGS frame, as received from the peer.
+pub struct Setting {
+    id: u16,
+    value: u32,
+}
+
+impl Setting {
+    /// Create a new [`Setting`].
+    pub fn new(id: u16, value: u32) -> Self {
+        Self { id, value }
+    }
+
+    /// The identifier of the setting, e.g. `1` for `SETTINGS_HEADER_TABLE_SIZE`.
+    ///
+    /// Identifiers unknown to rama are kept as well.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// The value of the setting.
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+/// The settings of the initial SETTINGS frame received on an h2 connection,
+/// in the order received.
+///
+/// Inserted by the h2 server as an extension of each request.
+/// Useful for fingerprinting, e.g. as the SETTINGS part of an Akamai h2 fingerprint.
+pub struct InitialPeerSettings(Vec<Setting>);
+
+impl InitialPeerSettings {
+    /// Create a new empty [`InitialPeerSettings`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Push a [`Setting`].
+    pub fn push(&mut self, setting: Setting) {
+        self.0.push(setting);
+    }
+
+    /// Iterate over the [`Setting`]s, in the order received.
+    pub fn iter(&self) -> std::slice::Iter<'_, Setting> {
+        self.0.iter()
+    }
+
+    /// Returns true if the SETTINGS frame contained no settings.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of received [`Setting`]s.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl FromIterator<Setting> for InitialPeerSettings {
+    fn from_iter<T: IntoIterator<Item = Setting>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for InitialPeerSettings {
+    type Item = Setting;
+    type IntoIter = std::vec::IntoIter<Setting>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a InitialPeerSettings {
+    type Item = &'a Setting;
+    type IntoIter = std::slice::Iter<'a, Setting>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
@@ -0,0 +1,268 @@
+use super::IntoResponse;
+use crate::headers::{ETag, HeaderMapExt, IfModifiedSince, IfNoneMatch, LastModified};
+use crate::{HeaderMap, Response, StatusCode};
+use std::{fmt, time::SystemTime};
+
+/// Utility struct to respond to conditional `GET` (and `HEAD`) requests.
+///
+/// The validators of the resource ([`ETag`] and/or last modification time)
+/// are evaluated against the `If-None-Match` and `If-Modified-Since` headers
+/// of the request. In case the resource was not modified a `304 Not Modified`
+/// response is returned, without calling the body producing closure,
+/// such that the (expensive) serialization of the resource can be skipped.
+///
+/// As defined in [RFC 9110], the `If-Modified-Since` header is
+/// only evaluated if the request has no `If-None-Match` header.
+///
+/// Both responses contain the validators as `ETag` and `Last-Modified` headers,
+/// unless already set by the response produced by the closure.
+///
+/// [RFC 9110]: https://www.rfc-editor.org/rfc/rfc9110#section-13.2.2
+///
+/// # Example
+///
+/// ```
+/// use rama_http_types::headers::ETag;
+/// use rama_http_types::response::{Conditional, IntoResponse, Json};
+/// use rama_http_types::{HeaderMap, StatusCode};
+///
+/// let etag: ETag = "\"v42\"".parse().unwrap();
+///
+/// let mut request_headers = HeaderMap::new();
+/// request_headers.insert("if-none-match", "\"v42\"".parse().unwrap());
+///
+/// let response = Conditional::new(&request_headers, || Json(serde_json::json!({"version": 42})))
+///     .with_etag(etag)
+///     .into_response();
+/// assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+/// assert_eq!(response.headers()["etag"], "\"v42\"");
+/// ```
+pub struct Conditional<F> {
+    if_none_match: Option<IfNoneMatch>,
+    if_modified_since: Option<IfModifiedSince>,
+    etag: Option<ETag>,
+    last_modified: Option<SystemTime>,
+    body: F,
+}
+
+impl<F> fmt::Debug for Conditional<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Conditional")
+            .field("if_none_match", &self.if_none_match)
+            .field("if_modified_since", &self.if_modified_since)
+            .field("etag", &self.etag)
+            .field("last_modified", &self.last_modified)
+            .field("body", &format_args!("{}", std::any::type_name::<F>()))
+            .finish()
+    }
+}
+
+impl<F> Conditional<F> {
+    /// Create a new [`Conditional`] response for a request with the given headers,
+    /// using the given closure to produce the response in case the resource was modified.
+    ///
+    /// Without any validator defined, the resource is always considered to be modified.
+    pub fn new(request_headers: &HeaderMap, body: F) -> Self {
+        Self {
+            if_none_match: request_headers.typed_get(),
+            if_modified_since: request_headers.typed_get(),
+            etag: None,
+            last_modified: None,
+            body,
+        }
+    }
+
+    /// Set the [`ETag`] of the resource.
+    pub fn with_etag(mut self, etag: ETag) -> Self {
+        self.etag = Some(etag);
+        self
+    }
+
+    /// Set the [`ETag`] of the resource.
+    pub fn set_etag(&mut self, etag: ETag) -> &mut Self {
+        self.etag = Some(etag);
+        self
+    }
+
+    /// Set the last modification time of the resource.
+    ///
+    /// The time is truncated to second precision.
+    pub fn with_last_modified(mut self, time: SystemTime) -> Self {
+        self.last_modified = Some(time);
+        self
+    }
+
+    /// Set the last modification time of the resource.
+    ///
+    /// The time is truncated to second precision.
+    pub fn set_last_modified(&mut self, time: SystemTime) -> &mut Self {
+        self.last_modified = Some(time);
+        self
+    }
+
+    /// Returns `true` if the request's conditional headers
+    /// match the validators of the resource, meaning
+    /// the resource was not modified.
+    pub fn is_not_modified(&self) -> bool {
+        match (&self.if_none_match, &self.etag) {
+            (Some(if_none_match), Some(etag)) => return !if_none_match.precondition_passes(etag),
+            (Some(_), None) => return false,
+            (None, _) => (),
+        }
+        match (&self.if_modified_since, self.last_modified) {
+            (Some(if_modified_since), Some(last_modified)) => {
+                !if_modified_since.is_modified(last_modified)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<F, R> IntoResponse for Conditional<F>
+where
+    F: FnOnce() -> R,
+    R: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        let not_modified = self.is_not_modified();
+        let Self {
+            etag,
+            last_modified,
+            body,
+            ..
+        } = self;
+
+        let mut response = if not_modified {
+            StatusCode::NOT_MODIFIED.into_response()
+        } else {
+            body().into_response()
+        };
+
+        let headers = response.headers_mut();
+        if let Some(etag) = etag {
+            if !headers.contains_key(crate::header::ETAG) {
+                headers.typed_insert(etag);
+            }
+        }
+        if let Some(last_modified) = last_modified {
+            if !headers.contains_key(crate::header::LAST_MODIFIED) {
+                headers.typed_insert(LastModified::from(last_modified));
+            }
+        }
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BodyExtractExt;
+    use std::{cell::Cell, time::Duration};
+
+    fn request_headers(headers: &[(&'static str, &'static str)]) -> HeaderMap {
+        headers
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_conditional_etag_match() {
+        let called = Cell::new(false);
+        let headers = request_headers(&[("if-none-match", "\"a\", \"v1\"")]);
+
+        let response = Conditional::new(&headers, || {
+            called.set(true);
+            "resource"
+        })
+        .with_etag("\"v1\"".parse().unwrap())
+        .into_response();
+
+        assert!(!called.get());
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()["etag"], "\"v1\"");
+        assert!(response
+            .into_body()
+            .try_into_string()
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_conditional_etag_no_match() {
+        let called = Cell::new(false);
+        let headers = request_headers(&[("if-none-match", "\"v1\"")]);
+
+        let response = Conditional::new(&headers, || {
+            called.set(true);
+            "resource"
+        })
+        .with_etag("\"v2\"".parse().unwrap())
+        .into_response();
+
+        assert!(called.get());
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["etag"], "\"v2\"");
+        assert_eq!(
+            response.into_body().try_into_string().await.unwrap(),
+            "resource"
+        );
+    }
+
+    #[test]
+    fn test_conditional_last_modified() {
+        let last_modified = SystemTime::UNIX_EPOCH + Duration::from_secs(784111777);
+        for (headers, not_modified) in [
+            (request_headers(&[]), false),
+            (
+                request_headers(&[("if-modified-since", "Sun, 06 Nov 1994 08:49:37 GMT")]),
+                true,
+            ),
+            (
+                request_headers(&[("if-modified-since", "Sun, 06 Nov 1994 08:49:38 GMT")]),
+                true,
+            ),
+            (
+                request_headers(&[("if-modified-since", "Sun, 06 Nov 1994 08:49:36 GMT")]),
+                false,
+            ),
+            // If-None-Match takes precedence over If-Modified-Since
+            (
+                request_headers(&[
+                    ("if-none-match", "\"v0\""),
+                    ("if-modified-since", "Sun, 06 Nov 1994 08:49:37 GMT"),
+                ]),
+                false,
+            ),
+        ] {
+            let response = Conditional::new(&headers, || "resource")
+                .with_etag("\"v1\"".parse().unwrap())
+                .with_last_modified(last_modified)
+                .into_response();
+            let expected = if not_modified {
+                StatusCode::NOT_MODIFIED
+            } else {
+                StatusCode::OK
+            };
+            assert_eq!(response.status(), expected, "headers: {headers:?}");
+            assert_eq!(
+                response.headers()["last-modified"],
+                "Sun, 06 Nov 1994 08:49:37 GMT"
+            );
+        }
+    }
+
+    #[test]
+    fn test_conditional_without_validators() {
+        let headers = request_headers(&[
+            ("if-none-match", "*"),
+            ("if-modified-since", "Sun, 06 Nov 1994 08:49:37 GMT"),
+        ]);
+        let response = Conditional::new(&headers, || "resource").into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key("etag"));
+        assert!(!response.headers().contains_key("last-modified"));
+    }
+}
@@ -29,10 +29,18 @@ mod form;
 #[doc(inline)]
 pub use form::Form;
 
+mod problem;
+#[doc(inline)]
+pub use problem::{Problem, PROBLEM_JSON_CONTENT_TYPE};
+
 mod redirect;
 #[doc(inline)]
 pub use redirect::Redirect;
 
+mod conditional;
+#[doc(inline)]
+pub use conditional::Conditional;
+
 /// Type alias for [`http::Response`] whose body type defaults to [`Body`], the most common body
 /// type used with rama.
 pub type Response<T = Body> = http::Response<T>;
@@ -0,0 +1,296 @@
+use crate::response::{IntoResponse, Response};
+use crate::{
+    dep::http::StatusCode,
+    header::{HeaderValue, CONTENT_TYPE},
+};
+use rama_error::BoxError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// The media type of a [`Problem`] serialized as JSON.
+pub const PROBLEM_JSON_CONTENT_TYPE: &str = "application/problem+json";
+
+/// The names of the standard members of a [`Problem`],
+/// which cannot be used as extension member names.
+const RESERVED_MEMBER_NAMES: [&str; 5] = ["type", "title", "status", "detail", "instance"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Problem details for HTTP APIs, as defined in [RFC 9457].
+///
+/// A [`Problem`] is turned into a response with its status code,
+/// serialized as JSON with the `application/problem+json` content type.
+///
+/// # Example
+///
+/// ```
+/// use rama_http_types::{response::Problem, IntoResponse, StatusCode};
+///
+/// async fn handler() -> impl IntoResponse {
+///     Problem::new(StatusCode::FORBIDDEN)
+///         .with_type("https://example.com/probs/out-of-credit")
+///         .with_title("You do not have enough credit.")
+///         .with_detail("Your current balance is 30, but that costs 50.")
+///         .with_instance("/account/12345/msgs/abc")
+///         .with_extension("balance", 30)
+/// }
+/// ```
+///
+/// [RFC 9457]: https://datatracker.ietf.org/doc/html/rfc9457
+pub struct Problem {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    problem_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(with = "status_code")]
+    status: StatusCode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    instance: Option<String>,
+    #[serde(flatten)]
+    extensions: Map<String, Value>,
+}
+
+impl Problem {
+    /// Create a new [`Problem`] for the given [`StatusCode`],
+    /// using the canonical reason of that status code as title.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            problem_type: None,
+            title: status.canonical_reason().map(ToOwned::to_owned),
+            status,
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Create a new [`Problem`] for the given [`StatusCode`],
+    /// using the given error as detail.
+    ///
+    /// Make sure the error does not contain sensitive information,
+    /// as the detail is exposed to the client.
+    pub fn from_error(status: StatusCode, error: impl Into<BoxError>) -> Self {
+        Self::new(status).with_detail(error.into().to_string())
+    }
+
+    /// The [`StatusCode`] of this [`Problem`].
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The URI reference identifying the problem type, if defined.
+    ///
+    /// When not defined it is to be treated as `about:blank`.
+    pub fn problem_type(&self) -> Option<&str> {
+        self.problem_type.as_deref()
+    }
+
+    /// Set the URI reference identifying the problem type.
+    pub fn with_type(mut self, problem_type: impl Into<String>) -> Self {
+        self.problem_type = Some(problem_type.into());
+        self
+    }
+
+    /// Set the URI reference identifying the problem type.
+    pub fn set_type(&mut self, problem_type: impl Into<String>) -> &mut Self {
+        self.problem_type = Some(problem_type.into());
+        self
+    }
+
+    /// The short, human-readable summary of the problem type, if defined.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Set the short, human-readable summary of the problem type.
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the short, human-readable summary of the problem type.
+    pub fn set_title(&mut self, title: impl Into<String>) -> &mut Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// The human-readable explanation specific to this occurrence of the problem, if defined.
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    /// Set the human-readable explanation specific to this occurrence of the problem.
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Set the human-readable explanation specific to this occurrence of the problem.
+    pub fn set_detail(&mut self, detail: impl Into<String>) -> &mut Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// The URI reference identifying this occurrence of the problem, if defined.
+    pub fn instance(&self) -> Option<&str> {
+        self.instance.as_deref()
+    }
+
+    /// Set the URI reference identifying this occurrence of the problem.
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Set the URI reference identifying this occurrence of the problem.
+    pub fn set_instance(&mut self, instance: impl Into<String>) -> &mut Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// The extension member with the given name, if defined.
+    pub fn extension(&self, name: &str) -> Option<&Value> {
+        self.extensions.get(name)
+    }
+
+    /// The extension members of this [`Problem`].
+    pub fn extensions(&self) -> &Map<String, Value> {
+        &self.extensions
+    }
+
+    /// Add an extension member to this [`Problem`],
+    /// overwriting a previous member with the same name.
+    ///
+    /// Extension members named after a standard member
+    /// (`type`, `title`, `status`, `detail` or `instance`) are ignored.
+    pub fn with_extension(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.set_extension(name, value);
+        self
+    }
+
+    /// Add an extension member to this [`Problem`],
+    /// overwriting a previous member with the same name.
+    ///
+    /// Extension members named after a standard member
+    /// (`type`, `title`, `status`, `detail` or `instance`) are ignored.
+    pub fn set_extension(&mut self, name: impl Into<String>, value: impl Into<Value>) -> &mut Self {
+        let name = name.into();
+        if RESERVED_MEMBER_NAMES.contains(&name.as_str()) {
+            tracing::debug!(%name, "ignore problem extension member named after a standard member");
+        } else {
+            self.extensions.insert(name, value.into());
+        }
+        self
+    }
+}
+
+impl From<StatusCode> for Problem {
+    fn from(status: StatusCode) -> Self {
+        Self::new(status)
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        match serde_json::to_vec(&self) {
+            Ok(body) => (
+                self.status,
+                [(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static(PROBLEM_JSON_CONTENT_TYPE),
+                )],
+                body,
+            )
+                .into_response(),
+            Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+        }
+    }
+}
+
+mod status_code {
+    use crate::dep::http::StatusCode;
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        status: &StatusCode,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(status.as_u16())
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<StatusCode, D::Error> {
+        let status = u16::deserialize(deserializer)?;
+        StatusCode::from_u16(status).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BodyExtractExt;
+
+    #[tokio::test]
+    async fn test_problem_into_response() {
+        let problem = Problem::new(StatusCode::FORBIDDEN)
+            .with_type("https://example.com/probs/out-of-credit")
+            .with_title("You do not have enough credit.")
+            .with_detail("Your current balance is 30, but that costs 50.")
+            .with_instance("/account/12345/msgs/abc")
+            .with_extension(
+                "accounts",
+                vec!["/account/12345".to_owned(), "/account/67890".to_owned()],
+            )
+            .with_extension("balance", 30);
+
+        let resp = problem.clone().into_response();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            PROBLEM_JSON_CONTENT_TYPE
+        );
+
+        let body = resp.try_into_string().await.unwrap();
+        assert_eq!(
+            body,
+            r#"{"type":"https://example.com/probs/out-of-credit","title":"You do not have enough credit.","status":403,"detail":"Your current balance is 30, but that costs 50.","instance":"/account/12345/msgs/abc","accounts":["/account/12345","/account/67890"],"balance":30}"#
+        );
+
+        let parsed: Problem = serde_json::from_str(&body).unwrap();
+        assert_eq!(parsed, problem);
+        assert_eq!(parsed.extension("balance"), Some(&Value::from(30)));
+    }
+
+    #[tokio::test]
+    async fn test_problem_ignores_reserved_extension_names() {
+        let mut problem = Problem::new(StatusCode::NOT_FOUND)
+            .with_extension("status", 200)
+            .with_extension("title", "overwritten")
+            .with_extension("resource", "/users/42");
+        for name in ["type", "detail", "instance"] {
+            problem.set_extension(name, "overwritten");
+        }
+
+        assert_eq!(problem.extensions().len(), 1);
+        assert!(problem.extension("status").is_none());
+
+        let body = problem.into_response().try_into_string().await.unwrap();
+        assert_eq!(
+            body,
+            r#"{"title":"Not Found","status":404,"resource":"/users/42"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_problem_from_error() {
+        let resp =
+            Problem::from_error(StatusCode::BAD_GATEWAY, "upstream unavailable").into_response();
+        assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(
+            resp.try_into_string().await.unwrap(),
+            r#"{"title":"Bad Gateway","status":502,"detail":"upstream unavailable"}"#
+        );
+    }
+}
@@ -0,0 +1,418 @@
+//! Utilities to work with [`Uri`]s.
+//!
+//! - [`join`] resolves a relative reference against a base [`Uri`], as defined in [RFC 3986, section 5];
+//! - [`normalize_path`] removes the `.` and `..` segments of a path;
+//! - [`query_param`], [`set_query_param`] and [`remove_query_param`] can be used
+//!   to manipulate individual query parameters, leaving the other parameters untouched.
+//!
+//! # Example
+//!
+//! ```
+//! use rama_http_types::{uri, Uri};
+//!
+//! let base = Uri::from_static("http://example.com/a/b/c?q=1");
+//! let uri = uri::join(&base, "../d?x=hello%20world").unwrap();
+//! assert_eq!(uri, "http://example.com/a/d?x=hello%20world");
+//!
+//! assert_eq!(uri::query_param(&uri, "x").as_deref(), Some("hello world"));
+//!
+//! let uri = uri::set_query_param(&uri, "y", "a&b").unwrap();
+//! assert_eq!(uri, "http://example.com/a/d?x=hello%20world&y=a%26b");
+//! ```
+//!
+//! [RFC 3986, section 5]: https://datatracker.ietf.org/doc/html/rfc3986#section-5
+
+use crate::dep::http::uri::PathAndQuery;
+use crate::Uri;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use rama_error::{ErrorContext, OpaqueError};
+use std::borrow::Cow;
+
+/// Characters percent-encoded in query parameter names and values.
+const QUERY_COMPONENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'&')
+    .add(b'+')
+    .add(b'<')
+    .add(b'=')
+    .add(b'>')
+    .add(b'[')
+    .add(b']')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+/// Resolve the given (relative) reference against the base [`Uri`],
+/// as defined in [RFC 3986, section 5.2].
+///
+/// The dot segments of the resulting path are removed, which guarantees
+/// that the resulting path cannot escape the root of the base.
+/// Fragments are ignored, as these cannot be represented by an [`Uri`].
+///
+/// [RFC 3986, section 5.2]: https://datatracker.ietf.org/doc/html/rfc3986#section-5.2
+pub fn join(base: &Uri, reference: &str) -> Result<Uri, OpaqueError> {
+    let reference = Reference::parse(reference);
+
+    let (scheme, authority, path, query) = if let Some(scheme) = reference.scheme {
+        (
+            Some(scheme),
+            reference.authority,
+            normalize_path(reference.path),
+            reference.query,
+        )
+    } else if let Some(authority) = reference.authority {
+        (
+            base.scheme_str(),
+            Some(authority),
+            normalize_path(reference.path),
+            reference.query,
+        )
+    } else if reference.path.is_empty() {
+        (
+            base.scheme_str(),
+            base.authority().map(|authority| authority.as_str()),
+            base.path().to_owned(),
+            reference.query.or(base.query()),
+        )
+    } else if reference.path.starts_with('/') {
+        (
+            base.scheme_str(),
+            base.authority().map(|authority| authority.as_str()),
+            normalize_path(reference.path),
+            reference.query,
+        )
+    } else {
+        let base_path = base.path();
+        let merged = if base.authority().is_some() && base_path.is_empty() {
+            format!("/{}", reference.path)
+        } else {
+            let dir = base_path.rfind('/').map_or("", |idx| &base_path[..=idx]);
+            format!("{dir}{}", reference.path)
+        };
+        (
+            base.scheme_str(),
+            base.authority().map(|authority| authority.as_str()),
+            normalize_path(&merged),
+            reference.query,
+        )
+    };
+
+    let mut uri = String::new();
+    if let Some(scheme) = scheme {
+        uri.push_str(scheme);
+        uri.push(':');
+    }
+    if let Some(authority) = authority {
+        uri.push_str("//");
+        uri.push_str(authority);
+        if !path.is_empty() && !path.starts_with('/') {
+            uri.push('/');
+        }
+    }
+    uri.push_str(&path);
+    if let Some(query) = query {
+        uri.push('?');
+        uri.push_str(query);
+    }
+
+    uri.parse().context("parse joined uri")
+}
+
+/// Remove the `.` and `..` segments of the given path,
+/// as defined in [RFC 3986, section 5.2.4].
+///
+/// Percent-encoded dots (`%2E`) are treated as dots, such that encoded
+/// dot segments are removed as well. A `..` segment never escapes the root of the path.
+///
+/// [RFC 3986, section 5.2.4]: https://datatracker.ietf.org/doc/html/rfc3986#section-5.2.4
+pub fn normalize_path(path: &str) -> String {
+    let path = decode_dots(path);
+    let mut input = path.as_ref();
+    let mut output = String::with_capacity(input.len());
+
+    while !input.is_empty() {
+        if let Some(rest) = input
+            .strip_prefix("../")
+            .or_else(|| input.strip_prefix("./"))
+        {
+            input = rest;
+        } else if input.starts_with("/./") {
+            input = &input[2..];
+        } else if input == "/." {
+            input = "/";
+        } else if input.starts_with("/../") {
+            input = &input[3..];
+            pop_segment(&mut output);
+        } else if input == "/.." {
+            input = "/";
+            pop_segment(&mut output);
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            let start = usize::from(input.starts_with('/'));
+            let end = input[start..]
+                .find('/')
+                .map_or(input.len(), |idx| idx + start);
+            output.push_str(&input[..end]);
+            input = &input[end..];
+        }
+    }
+
+    output
+}
+
+fn decode_dots(path: &str) -> Cow<'_, str> {
+    if path.contains("%2e") || path.contains("%2E") {
+        Cow::Owned(path.replace("%2e", ".").replace("%2E", "."))
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+fn pop_segment(output: &mut String) {
+    let idx = output.rfind('/').unwrap_or(0);
+    output.truncate(idx);
+}
+
+/// Get the (decoded) value of the first query parameter with the given name.
+///
+/// Returns `None` in case the [`Uri`] has no such query parameter.
+pub fn query_param(uri: &Uri, name: &str) -> Option<String> {
+    query_pairs(uri.query()?)
+        .find(|(key, _)| decode(key) == name)
+        .map(|(_, value)| decode(value).into_owned())
+}
+
+/// Set the query parameter with the given name to the given value, encoding both.
+///
+/// The first parameter with that name is replaced, and all other parameters
+/// with the same name are removed. In case no such parameter exists it is appended.
+/// All other query parameters are kept as-is, preserving their encoding.
+pub fn set_query_param(uri: &Uri, name: &str, value: &str) -> Result<Uri, OpaqueError> {
+    let param = format!(
+        "{}={}",
+        utf8_percent_encode(name, QUERY_COMPONENT),
+        utf8_percent_encode(value, QUERY_COMPONENT),
+    );
+
+    let mut params = Vec::new();
+    let mut replaced = false;
+    for pair in uri.query().into_iter().flat_map(raw_query_pairs) {
+        if decode(split_pair(pair).0) != name {
+            params.push(pair);
+        } else if !replaced {
+            params.push(&param);
+            replaced = true;
+        }
+    }
+    if !replaced {
+        params.push(&param);
+    }
+
+    with_query(uri, Some(&params.join("&")))
+}
+
+/// Remove all query parameters with the given name.
+///
+/// All other query parameters are kept as-is, preserving their encoding.
+pub fn remove_query_param(uri: &Uri, name: &str) -> Result<Uri, OpaqueError> {
+    let Some(query) = uri.query() else {
+        return Ok(uri.clone());
+    };
+
+    let query = raw_query_pairs(query)
+        .filter(|pair| decode(split_pair(pair).0) != name)
+        .collect::<Vec<_>>()
+        .join("&");
+
+    with_query(uri, (!query.is_empty()).then_some(query.as_str()))
+}
+
+fn raw_query_pairs(query: &str) -> impl Iterator<Item = &str> {
+    query.split('&').filter(|pair| !pair.is_empty())
+}
+
+fn query_pairs(query: &str) -> impl Iterator<Item = (&str, &str)> {
+    raw_query_pairs(query).map(split_pair)
+}
+
+fn split_pair(pair: &str) -> (&str, &str) {
+    pair.split_once('=').unwrap_or((pair, ""))
+}
+
+fn decode(s: &str) -> Cow<'_, str> {
+    if s.contains('+') {
+        Cow::Owned(
+            percent_decode_str(&s.replace('+', " "))
+                .decode_utf8_lossy()
+                .into_owned(),
+        )
+    } else {
+        percent_decode_str(s).decode_utf8_lossy()
+    }
+}
+
+fn with_query(uri: &Uri, query: Option<&str>) -> Result<Uri, OpaqueError> {
+    let path_and_query = match query {
+        Some(query) => format!("{}?{query}", uri.path()),
+        None => uri.path().to_owned(),
+    };
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query =
+        Some(PathAndQuery::try_from(path_and_query).context("create path and query of uri")?);
+    Uri::from_parts(parts).context("create uri from parts")
+}
+
+/// A parsed URI reference, see [RFC 3986, section 4.1].
+///
+/// [RFC 3986, section 4.1]: https://datatracker.ietf.org/doc/html/rfc3986#section-4.1
+struct Reference<'a> {
+    scheme: Option<&'a str>,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+}
+
+impl<'a> Reference<'a> {
+    fn parse(reference: &'a str) -> Self {
+        let reference = reference
+            .split_once('#')
+            .map_or(reference, |(reference, _)| reference);
+        let (reference, query) = match reference.split_once('?') {
+            Some((reference, query)) => (reference, Some(query)),
+            None => (reference, None),
+        };
+
+        let (scheme, rest) = match reference.split_once(':') {
+            Some((scheme, rest)) if is_scheme(scheme) => (Some(scheme), rest),
+            _ => (None, reference),
+        };
+
+        let (authority, path) = match rest.strip_prefix("//") {
+            Some(rest) => {
+                let idx = rest.find('/').unwrap_or(rest.len());
+                (Some(&rest[..idx]), &rest[idx..])
+            }
+            None => (None, rest),
+        };
+
+        Self {
+            scheme,
+            authority,
+            path,
+            query,
+        }
+    }
+}
+
+fn is_scheme(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_path() {
+        for (path, expected) in [
+            ("/a/b/c/./../../g", "/a/g"),
+            ("mid/content=5/../6", "mid/6"),
+            ("/a/./b/.", "/a/b/"),
+            ("/a/b/..", "/a/"),
+            ("/../../etc/passwd", "/etc/passwd"),
+            ("/a/%2e%2E/%2E/b", "/b"),
+            ("/a//b", "/a//b"),
+            ("", ""),
+            ("/", "/"),
+        ] {
+            assert_eq!(normalize_path(path), expected, "path: {path}");
+        }
+    }
+
+    #[test]
+    fn test_join_rfc3986_examples() {
+        let base = Uri::from_static("http://a/b/c/d;p?q");
+        for (reference, expected) in [
+            ("g:h", "g:h"),
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g/"),
+            ("?y", "http://a/b/c/d;p?y"),
+            ("g?y", "http://a/b/c/g?y"),
+            ("#s", "http://a/b/c/d;p?q"),
+            ("g#s", "http://a/b/c/g"),
+            (";x", "http://a/b/c/;x"),
+            ("", "http://a/b/c/d;p?q"),
+            (".", "http://a/b/c/"),
+            ("./", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../..", "http://a/"),
+            ("../../g", "http://a/g"),
+            ("../../../g", "http://a/g"),
+            ("../../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("/../g", "http://a/g"),
+            ("g.", "http://a/b/c/g."),
+            ("..g", "http://a/b/c/..g"),
+            ("./g/.", "http://a/b/c/g/"),
+            ("g/./h", "http://a/b/c/g/h"),
+            ("g/../h", "http://a/b/c/h"),
+            ("https://example.com/x/../y", "https://example.com/y"),
+        ] {
+            let uri = join(&base, reference).unwrap();
+            assert_eq!(uri.to_string(), expected, "reference: {reference}");
+        }
+    }
+
+    #[test]
+    fn test_join_origin_form_base() {
+        let base = Uri::from_static("/a/b");
+        assert_eq!(join(&base, "c?d=e").unwrap(), "/a/c?d=e");
+    }
+
+    #[test]
+    fn test_query_param_round_trip() {
+        let uri = Uri::from_static("http://example.com/path?a=1&b=hello%20world&c=x+y&a=2");
+        assert_eq!(query_param(&uri, "a").as_deref(), Some("1"));
+        assert_eq!(query_param(&uri, "b").as_deref(), Some("hello world"));
+        assert_eq!(query_param(&uri, "c").as_deref(), Some("x y"));
+        assert_eq!(query_param(&uri, "d"), None);
+
+        let uri = set_query_param(&uri, "a", "1&2=3").unwrap();
+        assert_eq!(
+            uri,
+            "http://example.com/path?a=1%262%3D3&b=hello%20world&c=x+y"
+        );
+        assert_eq!(query_param(&uri, "a").as_deref(), Some("1&2=3"));
+
+        let uri = set_query_param(&uri, "key with space", "é").unwrap();
+        assert_eq!(
+            uri,
+            "http://example.com/path?a=1%262%3D3&b=hello%20world&c=x+y&key%20with%20space=%C3%A9"
+        );
+        assert_eq!(query_param(&uri, "key with space").as_deref(), Some("é"));
+
+        let uri = remove_query_param(&uri, "a").unwrap();
+        assert_eq!(
+            uri,
+            "http://example.com/path?b=hello%20world&c=x+y&key%20with%20space=%C3%A9"
+        );
+
+        let uri = Uri::from_static("http://example.com/path?a=1");
+        assert_eq!(
+            remove_query_param(&uri, "a").unwrap(),
+            "http://example.com/path"
+        );
+    }
+}
@@ -1,2 +1,26 @@
 mod accept;
 pub use accept::Accept;
+
+mod retry_after;
+pub use retry_after::RetryAfter;
+
+mod nel;
+pub use nel::Nel;
+
+mod report_to;
+pub use report_to::{ReportTo, ReportToGroup};
+
+mod reporting_endpoints;
+pub use reporting_endpoints::ReportingEndpoints;
+
+mod sec_websocket_key;
+pub use sec_websocket_key::SecWebsocketKey;
+
+mod sec_websocket_accept;
+pub use sec_websocket_accept::SecWebsocketAccept;
+
+mod sec_websocket_protocol;
+pub use sec_websocket_protocol::SecWebsocketProtocol;
+
+mod sec_websocket_extensions;
+pub use sec_websocket_extensions::{SecWebsocketExtensions, WebsocketExtension};
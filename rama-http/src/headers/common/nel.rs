@@ -0,0 +1,240 @@
+use crate::headers::{self, Header};
+use crate::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The `NEL` header, defined in the [W3C Network Error Logging] specification.
+///
+/// The `NEL` response header configures the user agent to report
+/// network errors (e.g. dns, tcp or tls failures) for the origin,
+/// to the endpoint group named by `report_to`, as configured
+/// using the [`ReportTo`] or [`ReportingEndpoints`] header.
+///
+/// A `max_age` of zero removes any NEL policy the
+/// user agent might have stored for the origin.
+///
+/// # Example values
+/// * `{"report_to":"default","max_age":2592000}`
+/// * `{"report_to":"nel","max_age":86400,"include_subdomains":true,"failure_fraction":0.5}`
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use rama_http::headers::{HeaderMapExt, Nel};
+///
+/// let mut headers = rama_http::HeaderMap::new();
+/// headers.typed_insert(
+///     Nel::new("default", Duration::from_secs(86400)).with_include_subdomains(true),
+/// );
+///
+/// assert_eq!(
+///     headers["nel"],
+///     r#"{"report_to":"default","max_age":86400,"include_subdomains":true}"#,
+/// );
+/// ```
+///
+/// [W3C Network Error Logging]: https://www.w3.org/TR/network-error-logging/
+/// [`ReportTo`]: super::ReportTo
+/// [`ReportingEndpoints`]: super::ReportingEndpoints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Nel {
+    report_to: String,
+    max_age: u64,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    include_subdomains: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    success_fraction: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    failure_fraction: Option<f64>,
+}
+
+impl Nel {
+    /// Create a new [`Nel`] header reporting to the given endpoint group,
+    /// with the policy being valid for the given `max_age`.
+    ///
+    /// The `max_age` is truncated to second precision.
+    pub fn new(report_to: impl Into<String>, max_age: Duration) -> Self {
+        Self {
+            report_to: report_to.into(),
+            max_age: max_age.as_secs(),
+            include_subdomains: false,
+            success_fraction: None,
+            failure_fraction: None,
+        }
+    }
+
+    /// Returns the name of the endpoint group to report to.
+    pub fn report_to(&self) -> &str {
+        &self.report_to
+    }
+
+    /// Returns the duration this policy is valid for.
+    pub fn max_age(&self) -> Duration {
+        Duration::from_secs(self.max_age)
+    }
+
+    /// Returns `true` if this policy applies to all subdomains of the origin.
+    pub fn include_subdomains(&self) -> bool {
+        self.include_subdomains
+    }
+
+    /// Set whether or not this policy applies to all subdomains of the origin.
+    pub fn with_include_subdomains(mut self, include: bool) -> Self {
+        self.include_subdomains = include;
+        self
+    }
+
+    /// Set whether or not this policy applies to all subdomains of the origin.
+    pub fn set_include_subdomains(&mut self, include: bool) -> &mut Self {
+        self.include_subdomains = include;
+        self
+    }
+
+    /// Returns the sampling rate for successful requests,
+    /// defaulting to `0.0` (none) if not defined.
+    pub fn success_fraction(&self) -> f64 {
+        self.success_fraction.unwrap_or(0.0)
+    }
+
+    /// Set the sampling rate for successful requests.
+    ///
+    /// The fraction is clamped to the `[0.0, 1.0]` range.
+    pub fn with_success_fraction(mut self, fraction: f64) -> Self {
+        self.success_fraction = Some(fraction.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Set the sampling rate for successful requests.
+    ///
+    /// The fraction is clamped to the `[0.0, 1.0]` range.
+    pub fn set_success_fraction(&mut self, fraction: f64) -> &mut Self {
+        self.success_fraction = Some(fraction.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Returns the sampling rate for failed requests,
+    /// defaulting to `1.0` (all) if not defined.
+    pub fn failure_fraction(&self) -> f64 {
+        self.failure_fraction.unwrap_or(1.0)
+    }
+
+    /// Set the sampling rate for failed requests.
+    ///
+    /// The fraction is clamped to the `[0.0, 1.0]` range.
+    pub fn with_failure_fraction(mut self, fraction: f64) -> Self {
+        self.failure_fraction = Some(fraction.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Set the sampling rate for failed requests.
+    ///
+    /// The fraction is clamped to the `[0.0, 1.0]` range.
+    pub fn set_failure_fraction(&mut self, fraction: f64) -> &mut Self {
+        self.failure_fraction = Some(fraction.clamp(0.0, 1.0));
+        self
+    }
+}
+
+impl Header for Nel {
+    fn name() -> &'static HeaderName {
+        &crate::header::NEL
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+        if values.next().is_some() {
+            return Err(headers::Error::invalid());
+        }
+        let nel: Self =
+            serde_json::from_slice(value.as_bytes()).map_err(|_| headers::Error::invalid())?;
+        if nel.report_to.is_empty()
+            || [nel.success_fraction, nel.failure_fraction]
+                .into_iter()
+                .flatten()
+                .any(|fraction| !(0.0..=1.0).contains(&fraction))
+        {
+            return Err(headers::Error::invalid());
+        }
+        Ok(nel)
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        let value = serde_json::to_string(self)
+            .ok()
+            .and_then(|value| HeaderValue::from_str(&value).ok());
+        values.extend(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headers::HeaderMapExt;
+    use crate::HeaderMap;
+
+    fn decode(value: &str) -> Option<Nel> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            crate::header::NEL.clone(),
+            HeaderValue::from_str(value).unwrap(),
+        );
+        headers.typed_get()
+    }
+
+    fn encode(nel: Nel) -> String {
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(nel);
+        headers[&crate::header::NEL].to_str().unwrap().to_owned()
+    }
+
+    #[test]
+    fn test_nel_encode() {
+        assert_eq!(
+            encode(Nel::new("default", Duration::from_secs(2592000))),
+            r#"{"report_to":"default","max_age":2592000}"#
+        );
+        assert_eq!(
+            encode(
+                Nel::new("nel", Duration::from_secs(60))
+                    .with_include_subdomains(true)
+                    .with_success_fraction(0.01)
+                    .with_failure_fraction(2.0)
+            ),
+            r#"{"report_to":"nel","max_age":60,"include_subdomains":true,"success_fraction":0.01,"failure_fraction":1.0}"#
+        );
+    }
+
+    #[test]
+    fn test_nel_decode() {
+        let nel = decode(
+            r#"{ "report_to": "network-errors", "max_age": 86400, "include_subdomains": true, "failure_fraction": 0.5, "request_headers": ["If-None-Match"] }"#,
+        )
+        .unwrap();
+        assert_eq!(nel.report_to(), "network-errors");
+        assert_eq!(nel.max_age(), Duration::from_secs(86400));
+        assert!(nel.include_subdomains());
+        assert_eq!(nel.success_fraction(), 0.0);
+        assert_eq!(nel.failure_fraction(), 0.5);
+
+        let nel = Nel::new("default", Duration::ZERO).with_success_fraction(0.25);
+        assert_eq!(decode(&encode(nel.clone())).unwrap(), nel);
+    }
+
+    #[test]
+    fn test_nel_decode_invalid() {
+        for value in [
+            "",
+            "default",
+            r#"{"max_age":60}"#,
+            r#"{"report_to":"default"}"#,
+            r#"{"report_to":"","max_age":60}"#,
+            r#"{"report_to":"default","max_age":-1}"#,
+            r#"{"report_to":"default","max_age":60,"failure_fraction":1.5}"#,
+        ] {
+            assert!(decode(value).is_none(), "value: {value:?}");
+        }
+    }
+}
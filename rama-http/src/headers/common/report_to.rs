@@ -0,0 +1,293 @@
+use crate::headers::{self, Header};
+use crate::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The (legacy) `Report-To` header, defined in the [Reporting API (2018)] draft.
+///
+/// The `Report-To` response header configures one or more named groups
+/// of endpoints to which the user agent delivers its reports,
+/// such as the network error reports configured by the [`Nel`] header.
+///
+/// It is superseded by the [`ReportingEndpoints`] header,
+/// but remains the only way to configure the endpoints for NEL reports
+/// in most user agents.
+///
+/// # Example values
+/// * `{"group":"default","max_age":10886400,"endpoints":[{"url":"https://example.com/reports"}]}`
+/// * `{"max_age":60,"endpoints":[{"url":"https://a.example"}]}, {"group":"nel","max_age":60,"endpoints":[{"url":"https://b.example"}]}`
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use rama_http::headers::{HeaderMapExt, ReportTo, ReportToGroup};
+///
+/// let mut headers = rama_http::HeaderMap::new();
+/// headers.typed_insert(ReportTo::new(
+///     ReportToGroup::new(Duration::from_secs(86400), "https://example.com/reports")
+///         .with_name("nel"),
+/// ));
+///
+/// let report_to: ReportTo = headers.typed_get().unwrap();
+/// let group = report_to.group("nel").unwrap();
+/// assert_eq!(group.endpoints().collect::<Vec<_>>(), ["https://example.com/reports"]);
+/// ```
+///
+/// [Reporting API (2018)]: https://www.w3.org/TR/2018/WD-reporting-1-20180925/#header
+/// [`Nel`]: super::Nel
+/// [`ReportingEndpoints`]: super::ReportingEndpoints
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportTo(Vec<ReportToGroup>);
+
+/// A named group of endpoints, as configured by the [`ReportTo`] header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportToGroup {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+    max_age: u64,
+    endpoints: Vec<ReportToEndpoint>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    include_subdomains: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ReportToEndpoint {
+    url: String,
+}
+
+impl ReportTo {
+    /// Create a new [`ReportTo`] header configuring the given group.
+    pub fn new(group: ReportToGroup) -> Self {
+        Self(vec![group])
+    }
+
+    /// Add an extra group to this [`ReportTo`] header.
+    pub fn with_group(mut self, group: ReportToGroup) -> Self {
+        self.0.push(group);
+        self
+    }
+
+    /// Add an extra group to this [`ReportTo`] header.
+    pub fn add_group(&mut self, group: ReportToGroup) -> &mut Self {
+        self.0.push(group);
+        self
+    }
+
+    /// Returns the group with the given name, if configured.
+    pub fn group(&self, name: &str) -> Option<&ReportToGroup> {
+        self.0.iter().find(|group| group.name() == name)
+    }
+
+    /// Returns an iterator over all configured groups.
+    pub fn groups(&self) -> impl Iterator<Item = &ReportToGroup> {
+        self.0.iter()
+    }
+}
+
+impl ReportToGroup {
+    /// The name of a group for which no name is defined.
+    pub const DEFAULT_NAME: &'static str = "default";
+
+    /// Create a new [`ReportToGroup`] with the given endpoint,
+    /// being valid for the given `max_age`.
+    ///
+    /// The `max_age` is truncated to second precision.
+    pub fn new(max_age: Duration, endpoint: impl Into<String>) -> Self {
+        Self {
+            group: None,
+            max_age: max_age.as_secs(),
+            endpoints: vec![ReportToEndpoint {
+                url: endpoint.into(),
+            }],
+            include_subdomains: false,
+        }
+    }
+
+    /// Returns the name of this group,
+    /// which is [`Self::DEFAULT_NAME`] if not defined.
+    pub fn name(&self) -> &str {
+        self.group.as_deref().unwrap_or(Self::DEFAULT_NAME)
+    }
+
+    /// Set the name of this group.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.group = Some(name.into());
+        self
+    }
+
+    /// Set the name of this group.
+    pub fn set_name(&mut self, name: impl Into<String>) -> &mut Self {
+        self.group = Some(name.into());
+        self
+    }
+
+    /// Returns the duration this group is valid for.
+    pub fn max_age(&self) -> Duration {
+        Duration::from_secs(self.max_age)
+    }
+
+    /// Returns an iterator over the urls of the endpoints of this group.
+    pub fn endpoints(&self) -> impl Iterator<Item = &str> {
+        self.endpoints.iter().map(|endpoint| endpoint.url.as_str())
+    }
+
+    /// Add an extra endpoint to this group.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoints.push(ReportToEndpoint {
+            url: endpoint.into(),
+        });
+        self
+    }
+
+    /// Add an extra endpoint to this group.
+    pub fn add_endpoint(&mut self, endpoint: impl Into<String>) -> &mut Self {
+        self.endpoints.push(ReportToEndpoint {
+            url: endpoint.into(),
+        });
+        self
+    }
+
+    /// Returns `true` if this group applies to all subdomains of the origin.
+    pub fn include_subdomains(&self) -> bool {
+        self.include_subdomains
+    }
+
+    /// Set whether or not this group applies to all subdomains of the origin.
+    pub fn with_include_subdomains(mut self, include: bool) -> Self {
+        self.include_subdomains = include;
+        self
+    }
+
+    /// Set whether or not this group applies to all subdomains of the origin.
+    pub fn set_include_subdomains(&mut self, include: bool) -> &mut Self {
+        self.include_subdomains = include;
+        self
+    }
+}
+
+impl Header for ReportTo {
+    fn name() -> &'static HeaderName {
+        &crate::header::REPORT_TO
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        // the header value is a comma separated list of json objects,
+        // which can be parsed as the elements of a json array
+        let mut json = b"[".to_vec();
+        for (index, value) in values.enumerate() {
+            if index > 0 {
+                json.push(b',');
+            }
+            json.extend_from_slice(value.as_bytes());
+        }
+        json.push(b']');
+
+        let groups: Vec<ReportToGroup> =
+            serde_json::from_slice(&json).map_err(|_| headers::Error::invalid())?;
+        if groups.is_empty() || groups.iter().any(|group| group.endpoints.is_empty()) {
+            return Err(headers::Error::invalid());
+        }
+        Ok(Self(groups))
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        let value = self
+            .0
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+            .and_then(|groups| HeaderValue::from_str(&groups.join(", ")).ok());
+        values.extend(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headers::HeaderMapExt;
+    use crate::HeaderMap;
+
+    fn decode(values: &[&str]) -> Option<ReportTo> {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(
+                crate::header::REPORT_TO.clone(),
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        headers.typed_get()
+    }
+
+    fn encode(report_to: ReportTo) -> String {
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(report_to);
+        headers[&crate::header::REPORT_TO]
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    #[test]
+    fn test_report_to_encode() {
+        assert_eq!(
+            encode(ReportTo::new(ReportToGroup::new(
+                Duration::from_secs(10886400),
+                "https://example.com/reports"
+            ))),
+            r#"{"max_age":10886400,"endpoints":[{"url":"https://example.com/reports"}]}"#
+        );
+        assert_eq!(
+            encode(
+                ReportTo::new(
+                    ReportToGroup::new(Duration::from_secs(60), "https://a.example")
+                        .with_endpoint("https://b.example")
+                )
+                .with_group(
+                    ReportToGroup::new(Duration::from_secs(60), "https://c.example")
+                        .with_name("nel")
+                        .with_include_subdomains(true)
+                )
+            ),
+            r#"{"max_age":60,"endpoints":[{"url":"https://a.example"},{"url":"https://b.example"}]}, {"group":"nel","max_age":60,"endpoints":[{"url":"https://c.example"}],"include_subdomains":true}"#
+        );
+    }
+
+    #[test]
+    fn test_report_to_decode() {
+        let report_to = decode(&[
+            r#"{"group":"csp","max_age":60,"endpoints":[{"url":"https://a.example","priority":1}]}, {"max_age":120,"endpoints":[{"url":"https://b.example"}]}"#,
+            r#"{"group":"nel","max_age":0,"endpoints":[{"url":"https://c.example"}],"include_subdomains":true}"#,
+        ])
+        .unwrap();
+
+        assert_eq!(
+            report_to.groups().map(|g| g.name()).collect::<Vec<_>>(),
+            ["csp", "default", "nel"]
+        );
+        let group = report_to.group("default").unwrap();
+        assert_eq!(group.max_age(), Duration::from_secs(120));
+        assert_eq!(group.endpoints().collect::<Vec<_>>(), ["https://b.example"]);
+        assert!(report_to.group("nel").unwrap().include_subdomains());
+        assert!(report_to.group("unknown").is_none());
+
+        assert_eq!(decode(&[&encode(report_to.clone())]).unwrap(), report_to);
+    }
+
+    #[test]
+    fn test_report_to_decode_invalid() {
+        for value in [
+            "",
+            "default",
+            r#"{"max_age":60}"#,
+            r#"{"max_age":60,"endpoints":[]}"#,
+            r#"{"endpoints":[{"url":"https://a.example"}]}"#,
+            r#"[{"max_age":60,"endpoints":[{"url":"https://a.example"}]}]"#,
+        ] {
+            assert!(decode(&[value]).is_none(), "value: {value:?}");
+        }
+    }
+}
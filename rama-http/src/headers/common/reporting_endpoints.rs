@@ -0,0 +1,291 @@
+use crate::headers::{self, Header};
+use crate::{HeaderName, HeaderValue};
+use rama_core::error::OpaqueError;
+use std::fmt::Write;
+
+/// The `Reporting-Endpoints` header, defined in the [Reporting API] specification.
+///
+/// The `Reporting-Endpoints` response header configures named endpoints
+/// to which the user agent delivers its reports (e.g. CSP violations).
+/// It supersedes the [`ReportTo`] header.
+///
+/// Its value is a [structured field dictionary],
+/// mapping each endpoint name to the url of that endpoint.
+///
+/// # Example values
+/// * `default="https://example.com/reports"`
+/// * `csp="https://example.com/csp", default="https://example.com/reports"`
+///
+/// # Examples
+///
+/// ```
+/// use rama_http::headers::{HeaderMapExt, ReportingEndpoints};
+///
+/// let mut headers = rama_http::HeaderMap::new();
+/// headers.typed_insert(
+///     ReportingEndpoints::try_new("default", "https://example.com/reports")
+///         .unwrap()
+///         .try_with_endpoint("csp", "https://example.com/csp")
+///         .unwrap(),
+/// );
+///
+/// assert_eq!(
+///     headers["reporting-endpoints"],
+///     r#"default="https://example.com/reports", csp="https://example.com/csp""#,
+/// );
+/// ```
+///
+/// [Reporting API]: https://www.w3.org/TR/reporting-1/#header
+/// [structured field dictionary]: https://www.rfc-editor.org/rfc/rfc8941#name-dictionaries
+/// [`ReportTo`]: super::ReportTo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportingEndpoints(Vec<(String, String)>);
+
+impl ReportingEndpoints {
+    /// Create a new [`ReportingEndpoints`] header with the given endpoint.
+    ///
+    /// Returns an error in case the name is not a valid (lowercase) dictionary key,
+    /// or the url contains non-printable ascii characters.
+    pub fn try_new(name: impl Into<String>, url: impl Into<String>) -> Result<Self, OpaqueError> {
+        let mut endpoints = Self(Vec::with_capacity(1));
+        endpoints.try_add_endpoint(name, url)?;
+        Ok(endpoints)
+    }
+
+    /// Add an extra endpoint, replacing any existing endpoint with the same name.
+    ///
+    /// See [`Self::try_new`] for the possible errors.
+    pub fn try_with_endpoint(
+        mut self,
+        name: impl Into<String>,
+        url: impl Into<String>,
+    ) -> Result<Self, OpaqueError> {
+        self.try_add_endpoint(name, url)?;
+        Ok(self)
+    }
+
+    /// Add an extra endpoint, replacing any existing endpoint with the same name.
+    ///
+    /// See [`Self::try_new`] for the possible errors.
+    pub fn try_add_endpoint(
+        &mut self,
+        name: impl Into<String>,
+        url: impl Into<String>,
+    ) -> Result<&mut Self, OpaqueError> {
+        let (name, url) = (name.into(), url.into());
+        if !is_valid_key(&name) {
+            return Err(OpaqueError::from_display(format!(
+                "invalid reporting endpoint name: {name:?}"
+            )));
+        }
+        if !url.bytes().all(|b| (0x20..=0x7e).contains(&b)) {
+            return Err(OpaqueError::from_display(format!(
+                "invalid reporting endpoint url: {url:?}"
+            )));
+        }
+        self.insert(name, url);
+        Ok(self)
+    }
+
+    /// Returns the url of the endpoint with the given name, if configured.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, url)| url.as_str())
+    }
+
+    /// Returns an iterator over the name and url of all configured endpoints.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(name, url)| (name.as_str(), url.as_str()))
+    }
+
+    fn insert(&mut self, name: String, url: String) {
+        match self.0.iter_mut().find(|(key, _)| *key == name) {
+            Some((_, existing)) => *existing = url,
+            None => self.0.push((name, url)),
+        }
+    }
+}
+
+fn is_valid_key(key: &str) -> bool {
+    let mut bytes = key.bytes();
+    bytes
+        .next()
+        .is_some_and(|b| b.is_ascii_lowercase() || b == b'*')
+        && bytes.all(|b| {
+            b.is_ascii_lowercase() || b.is_ascii_digit() || matches!(b, b'_' | b'-' | b'.' | b'*')
+        })
+}
+
+/// Parse a single `key="url"` dictionary member (ignoring any parameters),
+/// returning the remainder of the input following the member.
+fn parse_member(input: &str) -> Option<(String, String, &str)> {
+    let input = input.trim_start_matches([' ', '\t']);
+    let (key, rest) = input.split_once('=')?;
+    if !is_valid_key(key) {
+        return None;
+    }
+
+    let mut chars = rest.strip_prefix('"')?.char_indices();
+    let mut url = String::new();
+    let end = loop {
+        match chars.next()? {
+            (_, '\\') => match chars.next()? {
+                (_, c @ ('"' | '\\')) => url.push(c),
+                _ => return None,
+            },
+            (index, '"') => break index + 1,
+            (_, c) if (' '..='~').contains(&c) => url.push(c),
+            _ => return None,
+        }
+    };
+
+    let rest = &rest[end + 1..];
+    let rest = match rest.find(',') {
+        Some(index) => {
+            if !rest[..index].trim().is_empty() && !rest.starts_with(';') {
+                return None;
+            }
+            &rest[index + 1..]
+        }
+        None => {
+            if !rest.trim().is_empty() && !rest.starts_with(';') {
+                return None;
+            }
+            ""
+        }
+    };
+
+    Some((key.to_owned(), url, rest))
+}
+
+impl Header for ReportingEndpoints {
+    fn name() -> &'static HeaderName {
+        &crate::header::REPORTING_ENDPOINTS
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        let mut endpoints = Self(Vec::new());
+        for value in values {
+            let mut input = value.to_str().map_err(|_| headers::Error::invalid())?;
+            while !input.trim().is_empty() {
+                let (name, url, rest) = parse_member(input).ok_or_else(headers::Error::invalid)?;
+                endpoints.insert(name, url);
+                input = rest;
+            }
+        }
+        if endpoints.0.is_empty() {
+            return Err(headers::Error::invalid());
+        }
+        Ok(endpoints)
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        let mut value = String::new();
+        for (index, (name, url)) in self.0.iter().enumerate() {
+            if index > 0 {
+                value.push_str(", ");
+            }
+            let _ = write!(
+                value,
+                "{name}=\"{}\"",
+                url.replace('\\', "\\\\").replace('"', "\\\"")
+            );
+        }
+        values.extend(HeaderValue::from_str(&value).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headers::HeaderMapExt;
+    use crate::HeaderMap;
+
+    fn decode(values: &[&str]) -> Option<ReportingEndpoints> {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(
+                crate::header::REPORTING_ENDPOINTS.clone(),
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        headers.typed_get()
+    }
+
+    fn encode(endpoints: ReportingEndpoints) -> String {
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(endpoints);
+        headers[&crate::header::REPORTING_ENDPOINTS]
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    #[test]
+    fn test_reporting_endpoints_encode() {
+        assert_eq!(
+            encode(ReportingEndpoints::try_new("default", "https://example.com/reports").unwrap()),
+            r#"default="https://example.com/reports""#
+        );
+        assert_eq!(
+            encode(
+                ReportingEndpoints::try_new("csp-endpoint", "https://example.com/\"csp\"")
+                    .unwrap()
+                    .try_with_endpoint("default", "https://a.example")
+                    .unwrap()
+                    .try_with_endpoint("csp-endpoint", "https://b.example")
+                    .unwrap()
+            ),
+            r#"csp-endpoint="https://b.example", default="https://a.example""#
+        );
+    }
+
+    #[test]
+    fn test_reporting_endpoints_invalid_config() {
+        assert!(ReportingEndpoints::try_new("Default", "https://a.example").is_err());
+        assert!(ReportingEndpoints::try_new("1st", "https://a.example").is_err());
+        assert!(ReportingEndpoints::try_new("", "https://a.example").is_err());
+        assert!(ReportingEndpoints::try_new("default", "https://a.example/\n").is_err());
+    }
+
+    #[test]
+    fn test_reporting_endpoints_decode() {
+        let endpoints = decode(&[
+            r#"csp="https://example.com/csp", default="https://example.com/\"reports\"";foo=1"#,
+            r#"nel="https://example.com/nel""#,
+        ])
+        .unwrap();
+        assert_eq!(
+            endpoints.iter().collect::<Vec<_>>(),
+            [
+                ("csp", "https://example.com/csp"),
+                ("default", "https://example.com/\"reports\""),
+                ("nel", "https://example.com/nel"),
+            ]
+        );
+        assert_eq!(endpoints.get("nel"), Some("https://example.com/nel"));
+        assert_eq!(endpoints.get("unknown"), None);
+
+        assert_eq!(decode(&[&encode(endpoints.clone())]).unwrap(), endpoints);
+    }
+
+    #[test]
+    fn test_reporting_endpoints_decode_invalid() {
+        for value in [
+            "",
+            "default",
+            "default=https://a.example",
+            r#"Default="https://a.example""#,
+            r#"default="https://a.example"#,
+            r#"default="https://a.example" csp="https://b.example""#,
+        ] {
+            assert!(decode(&[value]).is_none(), "value: {value:?}");
+        }
+    }
+}
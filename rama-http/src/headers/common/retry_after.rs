@@ -0,0 +1,216 @@
+use crate::headers::{self, Header};
+use crate::{HeaderName, HeaderValue};
+use httpdate::HttpDate;
+use std::time::{Duration, SystemTime};
+
+/// The `Retry-After` header, defined in [RFC9110](https://datatracker.ietf.org/doc/html/rfc9110#section-10.2.3)
+///
+/// The `Retry-After` response-header field can be used with a 503 (Service
+/// Unavailable) response to indicate how long the service is expected to be
+/// unavailable to the requesting client. This field MAY also be used with any
+/// 3xx (Redirection) response to indicate the minimum time the user-agent is
+/// asked wait before issuing the redirected request. The value of this field
+/// can be either an HTTP-date or an integer number of seconds (in decimal)
+/// after the time of the response.
+///
+/// # ABNF
+///
+/// ```text
+/// Retry-After = HTTP-date / delay-seconds
+/// delay-seconds  = 1*DIGIT
+/// ```
+///
+/// # Example values
+/// * `Fri, 31 Dec 1999 23:59:59 GMT`
+/// * `120`
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+/// use rama_http::headers::{HeaderMapExt, RetryAfter};
+///
+/// let mut headers = rama_http::HeaderMap::new();
+/// headers.typed_insert(RetryAfter::delay(Duration::from_secs(300)));
+///
+/// let retry_after: RetryAfter = headers.typed_get().unwrap();
+/// assert_eq!(
+///     retry_after.resolve(SystemTime::now()),
+///     Duration::from_secs(300),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RetryAfter(After);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum After {
+    /// Retry after the given [`HttpDate`].
+    DateTime(HttpDate),
+    /// Retry after the given amount of seconds.
+    Delay(u64),
+}
+
+impl RetryAfter {
+    /// Create a `RetryAfter` header with a date value.
+    ///
+    /// The date is truncated to second precision.
+    pub fn date(time: SystemTime) -> Self {
+        Self(After::DateTime(time.into()))
+    }
+
+    /// Create a `RetryAfter` header with a delay value.
+    ///
+    /// The delay is truncated to second precision.
+    pub fn delay(dur: Duration) -> Self {
+        Self(After::Delay(dur.as_secs()))
+    }
+
+    /// Returns the date of this `RetryAfter` header,
+    /// in case it is defined in the HTTP-date form.
+    pub fn as_date(&self) -> Option<SystemTime> {
+        match self.0 {
+            After::DateTime(date) => Some(date.into()),
+            After::Delay(_) => None,
+        }
+    }
+
+    /// Returns the delay of this `RetryAfter` header,
+    /// in case it is defined in the delay-seconds form.
+    pub fn as_delay(&self) -> Option<Duration> {
+        match self.0 {
+            After::DateTime(_) => None,
+            After::Delay(secs) => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// Resolve this `RetryAfter` header to the [`Duration`]
+    /// to wait, relative to the given `now`.
+    ///
+    /// A date in the past resolves to [`Duration::ZERO`].
+    pub fn resolve(&self, now: SystemTime) -> Duration {
+        match self.0 {
+            After::DateTime(date) => SystemTime::from(date)
+                .duration_since(now)
+                .unwrap_or_default(),
+            After::Delay(secs) => Duration::from_secs(secs),
+        }
+    }
+}
+
+impl Header for RetryAfter {
+    fn name() -> &'static HeaderName {
+        &crate::header::RETRY_AFTER
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+        if values.next().is_some() {
+            return Err(headers::Error::invalid());
+        }
+        let value = value
+            .to_str()
+            .map_err(|_| headers::Error::invalid())?
+            .trim();
+
+        if let Ok(secs) = value.parse::<u64>() {
+            return Ok(Self(After::Delay(secs)));
+        }
+        value
+            .parse::<HttpDate>()
+            .map(|date| Self(After::DateTime(date)))
+            .map_err(|_| headers::Error::invalid())
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        let value = match self.0 {
+            After::DateTime(date) => HeaderValue::from_str(&date.to_string()),
+            After::Delay(secs) => Ok(HeaderValue::from(secs)),
+        };
+        values.extend(value.ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headers::HeaderMapExt;
+    use crate::HeaderMap;
+
+    fn decode(value: &str) -> Option<RetryAfter> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            crate::header::RETRY_AFTER,
+            HeaderValue::from_str(value).unwrap(),
+        );
+        headers.typed_get()
+    }
+
+    fn encode(retry_after: RetryAfter) -> String {
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(retry_after);
+        headers[crate::header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    #[test]
+    fn test_retry_after_delay_round_trip() {
+        let retry_after = decode("300").unwrap();
+        assert_eq!(retry_after.as_delay(), Some(Duration::from_secs(300)));
+        assert_eq!(retry_after.as_date(), None);
+        assert_eq!(encode(retry_after), "300");
+
+        assert_eq!(
+            RetryAfter::delay(Duration::from_millis(1500)),
+            decode("1").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_retry_after_date_round_trip() {
+        let value = "Sun, 06 Nov 1994 08:49:37 GMT";
+        let date = SystemTime::UNIX_EPOCH + Duration::from_secs(784111777);
+
+        let retry_after = decode(value).unwrap();
+        assert_eq!(retry_after.as_date(), Some(date));
+        assert_eq!(retry_after.as_delay(), None);
+        assert_eq!(retry_after, RetryAfter::date(date));
+        assert_eq!(encode(retry_after), value);
+
+        // obsolete date formats are accepted as well
+        assert_eq!(
+            decode("Sunday, 06-Nov-94 08:49:37 GMT").unwrap(),
+            retry_after
+        );
+        assert_eq!(decode("Sun Nov  6 08:49:37 1994").unwrap(), retry_after);
+    }
+
+    #[test]
+    fn test_retry_after_decode_invalid() {
+        for value in ["", "-1", "1.5", "soon", "Sun, 06 Nov 1994"] {
+            assert!(decode(value).is_none(), "value: {value:?}");
+        }
+    }
+
+    #[test]
+    fn test_retry_after_resolve() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(784111777);
+
+        assert_eq!(
+            RetryAfter::delay(Duration::from_secs(120)).resolve(now),
+            Duration::from_secs(120)
+        );
+        assert_eq!(
+            RetryAfter::date(now + Duration::from_secs(60)).resolve(now),
+            Duration::from_secs(60)
+        );
+        assert_eq!(RetryAfter::date(now).resolve(now), Duration::ZERO);
+        assert_eq!(
+            RetryAfter::date(now - Duration::from_secs(60)).resolve(now),
+            Duration::ZERO
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use super::SecWebsocketKey;
+use crate::headers::{self, Header};
+use crate::{HeaderName, HeaderValue};
+use base64::engine::general_purpose::STANDARD as ENGINE;
+use base64::Engine;
+use sha1::{Digest, Sha1};
+
+/// The `Sec-WebSocket-Accept` header, defined in [RFC6455](https://datatracker.ietf.org/doc/html/rfc6455#section-11.3.3)
+///
+/// The `Sec-WebSocket-Accept` header field is sent by the server in the opening
+/// handshake, to prove that it received the client's [`SecWebsocketKey`].
+/// Its value is the base64-encoded SHA-1 of the key concatenated with
+/// the GUID `258EAFA5-E914-47DA-95CA-C5AB0DC85B11`.
+///
+/// A client validates the handshake by comparing the received header
+/// with the one derived from the key it sent, using [`SecWebsocketAccept::for_key`].
+///
+/// # Examples
+///
+/// ```
+/// use rama_http::headers::{HeaderMapExt, SecWebsocketAccept, SecWebsocketKey};
+///
+/// let mut headers = rama_http::HeaderMap::new();
+/// headers.insert("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==".parse().unwrap());
+///
+/// let key: SecWebsocketKey = headers.typed_get().unwrap();
+/// let accept = SecWebsocketAccept::for_key(&key);
+/// assert_eq!(accept.as_str(), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SecWebsocketAccept(HeaderValue);
+
+impl SecWebsocketAccept {
+    const GUID: &'static [u8] = b"258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+    /// Derive the [`SecWebsocketAccept`] for the given [`SecWebsocketKey`].
+    pub fn for_key(key: &SecWebsocketKey) -> Self {
+        let mut sha1 = Sha1::default();
+        sha1.update(key.as_bytes());
+        sha1.update(Self::GUID);
+        let value = HeaderValue::from_str(&ENGINE.encode(sha1.finalize()))
+            .expect("base64 is a valid value");
+        Self(value)
+    }
+
+    /// Returns the (base64-encoded) accept value as a string slice.
+    pub fn as_str(&self) -> &str {
+        // only valid base64 values are accepted
+        self.0.to_str().unwrap_or_default()
+    }
+}
+
+impl From<SecWebsocketKey> for SecWebsocketAccept {
+    fn from(key: SecWebsocketKey) -> Self {
+        Self::for_key(&key)
+    }
+}
+
+impl From<&SecWebsocketKey> for SecWebsocketAccept {
+    fn from(key: &SecWebsocketKey) -> Self {
+        Self::for_key(key)
+    }
+}
+
+impl Header for SecWebsocketAccept {
+    fn name() -> &'static HeaderName {
+        &crate::header::SEC_WEBSOCKET_ACCEPT
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+        if values.next().is_some() {
+            return Err(headers::Error::invalid());
+        }
+        match ENGINE.decode(value.as_bytes()) {
+            // SHA-1 digests are 20 bytes long
+            Ok(digest) if digest.len() == 20 => Ok(Self(value.clone())),
+            _ => Err(headers::Error::invalid()),
+        }
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        values.extend(Some(self.0.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headers::HeaderMapExt;
+    use crate::HeaderMap;
+
+    #[test]
+    fn test_accept_key_rfc6455_example() {
+        // https://datatracker.ietf.org/doc/html/rfc6455#section-1.3
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            crate::header::SEC_WEBSOCKET_KEY,
+            HeaderValue::from_static("dGhlIHNhbXBsZSBub25jZQ=="),
+        );
+        let key: SecWebsocketKey = headers.typed_get().unwrap();
+        assert_eq!(key, SecWebsocketKey::new(*b"the sample nonce"));
+
+        let accept = SecWebsocketAccept::from(&key);
+        assert_eq!(accept.as_str(), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(accept.clone());
+        assert_eq!(
+            headers[crate::header::SEC_WEBSOCKET_ACCEPT],
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+        assert_eq!(headers.typed_get(), Some(accept));
+    }
+
+    #[test]
+    fn test_accept_key_random_key() {
+        let key = SecWebsocketKey::random();
+        assert_ne!(key, SecWebsocketKey::random());
+
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(key.clone());
+        assert_eq!(headers.typed_get(), Some(key.clone()));
+
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(SecWebsocketAccept::from(&key));
+        assert_eq!(
+            headers.typed_get::<SecWebsocketAccept>(),
+            Some(SecWebsocketAccept::for_key(&key))
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_key_and_accept() {
+        for value in [
+            "",
+            "not base64!",
+            "dGhlIHNhbXBsZQ==",
+            "dGhlIHNhbXBsZSBub25jZQ",
+        ] {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                crate::header::SEC_WEBSOCKET_KEY,
+                HeaderValue::from_str(value).unwrap(),
+            );
+            assert!(
+                headers.typed_get::<SecWebsocketKey>().is_none(),
+                "key: {value:?}"
+            );
+
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                crate::header::SEC_WEBSOCKET_ACCEPT,
+                HeaderValue::from_str(value).unwrap(),
+            );
+            assert!(
+                headers.typed_get::<SecWebsocketAccept>().is_none(),
+                "accept: {value:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use crate::headers::{self, Header};
+use crate::{HeaderName, HeaderValue};
+use std::{fmt, iter::FromIterator, str::FromStr};
+
+/// The `Sec-WebSocket-Extensions` header, defined in [RFC6455](https://datatracker.ietf.org/doc/html/rfc6455#section-11.3.2)
+///
+/// The `Sec-WebSocket-Extensions` header field is sent by the client in the opening
+/// handshake to list the extensions it wishes to use, and by the server
+/// to list the extensions it accepted.
+///
+/// # ABNF
+///
+/// ```text
+/// Sec-WebSocket-Extensions = extension-list
+/// extension-list = 1#extension
+/// extension = extension-token *( ";" extension-param )
+/// extension-token = registered-token
+/// registered-token = token
+/// extension-param = token [ "=" (token | quoted-string) ]
+/// ```
+///
+/// # Example values
+/// * `permessage-deflate; client_max_window_bits`
+/// * `foo, bar; baz=2`
+///
+/// # Examples
+///
+/// ```
+/// use rama_http::headers::{HeaderMapExt, SecWebsocketExtensions, WebsocketExtension};
+///
+/// let mut headers = rama_http::HeaderMap::new();
+/// headers.typed_insert(SecWebsocketExtensions::new(
+///     WebsocketExtension::new("permessage-deflate")
+///         .with_param("client_max_window_bits", None::<String>),
+/// ));
+/// assert_eq!(
+///     headers["sec-websocket-extensions"],
+///     "permessage-deflate; client_max_window_bits",
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecWebsocketExtensions(Vec<WebsocketExtension>);
+
+impl SecWebsocketExtensions {
+    /// Create a new [`SecWebsocketExtensions`] header for the given extension.
+    pub fn new(extension: WebsocketExtension) -> Self {
+        Self(vec![extension])
+    }
+
+    /// Add an extension to the list of extensions.
+    pub fn with_extension(mut self, extension: WebsocketExtension) -> Self {
+        self.0.push(extension);
+        self
+    }
+
+    /// Add an extension to the list of extensions.
+    pub fn set_extension(&mut self, extension: WebsocketExtension) -> &mut Self {
+        self.0.push(extension);
+        self
+    }
+
+    /// Returns the first extension with the given name (case-insensitive), if any.
+    pub fn get(&self, name: &str) -> Option<&WebsocketExtension> {
+        self.0
+            .iter()
+            .find(|ext| ext.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns an iterator over the extensions, in the order they were listed.
+    pub fn iter(&self) -> impl Iterator<Item = &WebsocketExtension> {
+        self.0.iter()
+    }
+}
+
+impl Header for SecWebsocketExtensions {
+    fn name() -> &'static HeaderName {
+        &crate::header::SEC_WEBSOCKET_EXTENSIONS
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        let extensions: Vec<WebsocketExtension> =
+            crate::headers::util::csv::from_comma_delimited(values)?;
+        if extensions.is_empty() {
+            return Err(headers::Error::invalid());
+        }
+        Ok(Self(extensions))
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        struct Format<'a>(&'a [WebsocketExtension]);
+        impl fmt::Display for Format<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                crate::headers::util::csv::fmt_comma_delimited(f, self.0.iter())
+            }
+        }
+        if let Ok(value) = HeaderValue::from_str(&Format(&self.0).to_string()) {
+            values.extend(Some(value));
+        }
+    }
+}
+
+impl FromIterator<WebsocketExtension> for SecWebsocketExtensions {
+    fn from_iter<T: IntoIterator<Item = WebsocketExtension>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// A single extension, as listed in the [`SecWebsocketExtensions`] header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebsocketExtension {
+    name: String,
+    params: Vec<(String, Option<String>)>,
+}
+
+impl WebsocketExtension {
+    /// Create a new [`WebsocketExtension`] with the given name and no parameters.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Add a parameter, with an optional value, to this extension.
+    pub fn with_param(mut self, name: impl Into<String>, value: Option<impl Into<String>>) -> Self {
+        self.params.push((name.into(), value.map(Into::into)));
+        self
+    }
+
+    /// Add a parameter, with an optional value, to this extension.
+    pub fn set_param(
+        &mut self,
+        name: impl Into<String>,
+        value: Option<impl Into<String>>,
+    ) -> &mut Self {
+        self.params.push((name.into(), value.map(Into::into)));
+        self
+    }
+
+    /// Returns the name of this extension.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the parameter with the given name (case-insensitive), if any.
+    ///
+    /// The inner option is `None` for a parameter without value.
+    pub fn param(&self, name: &str) -> Option<Option<&str>> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_deref())
+    }
+
+    /// Returns an iterator over the parameters of this extension.
+    pub fn params(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.params
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_deref()))
+    }
+}
+
+impl FromStr for WebsocketExtension {
+    type Err = headers::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(';').map(str::trim);
+
+        let name = parts
+            .next()
+            .filter(|name| is_token(name))
+            .ok_or_else(headers::Error::invalid)?;
+
+        let params = parts
+            .map(|param| {
+                let (name, value) = match param.split_once('=') {
+                    Some((name, value)) => (name.trim_end(), Some(value.trim_start())),
+                    None => (param, None),
+                };
+                if !is_token(name) {
+                    return Err(headers::Error::invalid());
+                }
+                let value = match value {
+                    None => None,
+                    Some(value) => {
+                        // quoted-string values have to be valid tokens once unquoted
+                        let value = value
+                            .strip_prefix('"')
+                            .and_then(|value| value.strip_suffix('"'))
+                            .unwrap_or(value);
+                        if !is_token(value) {
+                            return Err(headers::Error::invalid());
+                        }
+                        Some(value.to_owned())
+                    }
+                };
+                Ok((name.to_owned(), value))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            name: name.to_owned(),
+            params,
+        })
+    }
+}
+
+impl fmt::Display for WebsocketExtension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        for (name, value) in &self.params {
+            match value {
+                Some(value) => write!(f, "; {name}={value}")?,
+                None => write!(f, "; {name}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes().all(|b| {
+            b.is_ascii_alphanumeric()
+                || matches!(
+                    b,
+                    b'!' | b'#'
+                        | b'$'
+                        | b'%'
+                        | b'&'
+                        | b'\''
+                        | b'*'
+                        | b'+'
+                        | b'-'
+                        | b'.'
+                        | b'^'
+                        | b'_'
+                        | b'`'
+                        | b'|'
+                        | b'~'
+                )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headers::HeaderMapExt;
+    use crate::HeaderMap;
+
+    fn decode(values: &[&str]) -> Option<SecWebsocketExtensions> {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(
+                crate::header::SEC_WEBSOCKET_EXTENSIONS,
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        headers.typed_get()
+    }
+
+    #[test]
+    fn test_sec_websocket_extensions_parse() {
+        let extensions = decode(&[
+            "permessage-deflate; client_max_window_bits; server_max_window_bits=10",
+            "foo, bar ;baz = \"2\"",
+        ])
+        .unwrap();
+
+        let names: Vec<_> = extensions.iter().map(|ext| ext.name()).collect();
+        assert_eq!(names, ["permessage-deflate", "foo", "bar"]);
+
+        let deflate = extensions.get("Permessage-Deflate").unwrap();
+        assert_eq!(
+            deflate.params().collect::<Vec<_>>(),
+            [
+                ("client_max_window_bits", None),
+                ("server_max_window_bits", Some("10")),
+            ]
+        );
+        assert_eq!(deflate.param("client_max_window_bits"), Some(None));
+        assert_eq!(deflate.param("server_max_window_bits"), Some(Some("10")));
+        assert_eq!(deflate.param("client_no_context_takeover"), None);
+
+        assert_eq!(extensions.get("foo").unwrap().params().count(), 0);
+        assert_eq!(extensions.get("bar").unwrap().param("baz"), Some(Some("2")));
+        assert!(extensions.get("baz").is_none());
+    }
+
+    #[test]
+    fn test_sec_websocket_extensions_parse_invalid() {
+        for value in [
+            "",
+            "; foo",
+            "foo bar",
+            "foo; =1",
+            "foo; bar=",
+            "foo; bar=\"\"",
+            "foo; bar=\"a b\"",
+            "foo; bar=1=2",
+        ] {
+            assert!(decode(&[value]).is_none(), "value: {value:?}");
+        }
+    }
+
+    #[test]
+    fn test_sec_websocket_extensions_round_trip() {
+        let extensions = SecWebsocketExtensions::new(
+            WebsocketExtension::new("permessage-deflate")
+                .with_param("client_max_window_bits", None::<String>)
+                .with_param("server_max_window_bits", Some("10")),
+        )
+        .with_extension(WebsocketExtension::new("foo"));
+
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(extensions.clone());
+        assert_eq!(
+            headers[crate::header::SEC_WEBSOCKET_EXTENSIONS],
+            "permessage-deflate; client_max_window_bits; server_max_window_bits=10, foo"
+        );
+        assert_eq!(headers.typed_get(), Some(extensions));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::headers::{self, Header};
+use crate::{HeaderName, HeaderValue};
+use base64::engine::general_purpose::STANDARD as ENGINE;
+use base64::Engine;
+
+/// The `Sec-WebSocket-Key` header, defined in [RFC6455](https://datatracker.ietf.org/doc/html/rfc6455#section-11.3.1)
+///
+/// The `Sec-WebSocket-Key` header field is sent by the client in the opening
+/// handshake, containing a base64-encoded randomly selected 16-byte nonce.
+/// The server proves the receipt of the handshake by deriving the
+/// [`SecWebsocketAccept`] from it.
+///
+/// # Examples
+///
+/// ```
+/// use rama_http::headers::{HeaderMapExt, SecWebsocketAccept, SecWebsocketKey};
+///
+/// let mut headers = rama_http::HeaderMap::new();
+/// let key = SecWebsocketKey::random();
+/// headers.typed_insert(key.clone());
+///
+/// let accept = SecWebsocketAccept::for_key(&key);
+/// assert_eq!(accept, SecWebsocketAccept::from(key));
+/// ```
+///
+/// [`SecWebsocketAccept`]: super::SecWebsocketAccept
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SecWebsocketKey(HeaderValue);
+
+impl SecWebsocketKey {
+    /// Create a new [`SecWebsocketKey`] for the given 16-byte nonce.
+    pub fn new(nonce: [u8; 16]) -> Self {
+        let value = HeaderValue::from_str(&ENGINE.encode(nonce)).expect("base64 is a valid value");
+        Self(value)
+    }
+
+    /// Create a new [`SecWebsocketKey`] for a randomly selected nonce.
+    pub fn random() -> Self {
+        Self::new(rand::random())
+    }
+
+    /// Returns the (base64-encoded) key as a string slice.
+    pub fn as_str(&self) -> &str {
+        // only valid base64 values are accepted
+        self.0.to_str().unwrap_or_default()
+    }
+
+    pub(super) fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl Header for SecWebsocketKey {
+    fn name() -> &'static HeaderName {
+        &crate::header::SEC_WEBSOCKET_KEY
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+        if values.next().is_some() {
+            return Err(headers::Error::invalid());
+        }
+        match ENGINE.decode(value.as_bytes()) {
+            Ok(nonce) if nonce.len() == 16 => Ok(Self(value.clone())),
+            _ => Err(headers::Error::invalid()),
+        }
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        values.extend(Some(self.0.clone()))
+    }
+}
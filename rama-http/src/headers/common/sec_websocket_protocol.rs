@@ -0,0 +1,149 @@
+use crate::headers::{self, Header};
+use crate::{HeaderName, HeaderValue};
+use std::iter::FromIterator;
+
+/// The `Sec-WebSocket-Protocol` header, defined in [RFC6455](https://datatracker.ietf.org/doc/html/rfc6455#section-11.3.4)
+///
+/// The `Sec-WebSocket-Protocol` header field is sent by the client in the opening
+/// handshake to list the subprotocols it wishes to speak, ordered by preference.
+/// The server responds with the single subprotocol it selected, if any.
+///
+/// # ABNF
+///
+/// ```text
+/// Sec-WebSocket-Protocol-Client = 1#token
+/// Sec-WebSocket-Protocol-Server = token
+/// ```
+///
+/// # Example values
+/// * `chat, superchat`
+/// * `chat`
+///
+/// # Examples
+///
+/// ```
+/// use rama_http::headers::{HeaderMapExt, SecWebsocketProtocol};
+///
+/// let mut headers = rama_http::HeaderMap::new();
+/// headers.typed_insert(SecWebsocketProtocol::new("chat").with_protocol("superchat"));
+/// assert_eq!(headers["sec-websocket-protocol"], "chat, superchat");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecWebsocketProtocol(Vec<String>);
+
+impl SecWebsocketProtocol {
+    /// Create a new [`SecWebsocketProtocol`] header for the given subprotocol.
+    pub fn new(protocol: impl Into<String>) -> Self {
+        Self(vec![protocol.into()])
+    }
+
+    /// Add a subprotocol, with a lower preference
+    /// than the subprotocols that were already added.
+    pub fn with_protocol(mut self, protocol: impl Into<String>) -> Self {
+        self.0.push(protocol.into());
+        self
+    }
+
+    /// Add a subprotocol, with a lower preference
+    /// than the subprotocols that were already added.
+    pub fn set_protocol(&mut self, protocol: impl Into<String>) -> &mut Self {
+        self.0.push(protocol.into());
+        self
+    }
+
+    /// Returns the most preferred subprotocol,
+    /// which is the selected subprotocol in case of a server response.
+    pub fn first(&self) -> &str {
+        &self.0[0]
+    }
+
+    /// Returns `true` if the given subprotocol is listed (case-sensitive).
+    pub fn contains(&self, protocol: &str) -> bool {
+        self.0.iter().any(|p| p == protocol)
+    }
+
+    /// Returns an iterator over the subprotocols, in order of preference.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+}
+
+impl Header for SecWebsocketProtocol {
+    fn name() -> &'static HeaderName {
+        &crate::header::SEC_WEBSOCKET_PROTOCOL
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        let protocols: Vec<String> = crate::headers::util::csv::from_comma_delimited(values)?;
+        if protocols.is_empty() {
+            return Err(headers::Error::invalid());
+        }
+        Ok(Self(protocols))
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        if let Ok(value) = HeaderValue::from_str(&self.0.join(", ")) {
+            values.extend(Some(value));
+        }
+    }
+}
+
+/// Panics if the iterator is empty,
+/// as at least one subprotocol is required.
+impl<S: Into<String>> FromIterator<S> for SecWebsocketProtocol {
+    fn from_iter<T: IntoIterator<Item = S>>(iter: T) -> Self {
+        let protocols: Vec<String> = iter.into_iter().map(Into::into).collect();
+        assert!(
+            !protocols.is_empty(),
+            "at least one websocket subprotocol is required"
+        );
+        Self(protocols)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headers::HeaderMapExt;
+    use crate::HeaderMap;
+
+    fn decode(values: &[&str]) -> Option<SecWebsocketProtocol> {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(
+                crate::header::SEC_WEBSOCKET_PROTOCOL,
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        headers.typed_get()
+    }
+
+    #[test]
+    fn test_sec_websocket_protocol_decode() {
+        let protocol = decode(&["chat, superchat", "v2.bookings.example"]).unwrap();
+        assert_eq!(
+            protocol.iter().collect::<Vec<_>>(),
+            ["chat", "superchat", "v2.bookings.example"]
+        );
+        assert_eq!(protocol.first(), "chat");
+        assert!(protocol.contains("superchat"));
+        assert!(!protocol.contains("Chat"));
+
+        assert!(decode(&[""]).is_none());
+        assert!(decode(&[" , "]).is_none());
+    }
+
+    #[test]
+    fn test_sec_websocket_protocol_round_trip() {
+        let protocol: SecWebsocketProtocol = ["chat", "superchat"].into_iter().collect();
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(protocol.clone());
+        assert_eq!(
+            headers[crate::header::SEC_WEBSOCKET_PROTOCOL],
+            "chat, superchat"
+        );
+        assert_eq!(headers.typed_get(), Some(protocol));
+    }
+}
@@ -3,7 +3,7 @@ use rama_net::forwarded::ForwardedElement;
 
 mod via;
 #[doc(inline)]
-pub use via::Via;
+pub use via::{Via, ViaElement};
 
 mod x_forwarded_for;
 #[doc(inline)]
@@ -1,5 +1,5 @@
 use crate::headers::{self, Header};
-use crate::{HeaderName, HeaderValue};
+use crate::{HeaderMap, HeaderName, HeaderValue};
 use rama_core::error::{ErrorContext, OpaqueError};
 use rama_net::forwarded::{ForwardedElement, ForwardedProtocol, ForwardedVersion, NodeId};
 
@@ -30,11 +30,135 @@ use rama_net::forwarded::{ForwardedElement, ForwardedProtocol, ForwardedVersion,
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Via(Vec<ViaElement>);
 
+impl Via {
+    /// Create a new [`Via`] header with a single [`ViaElement`].
+    pub fn new(element: ViaElement) -> Self {
+        Self(vec![element])
+    }
+
+    /// The [`ViaElement`]s of this [`Via`] header,
+    /// in the order the message was forwarded by the intermediaries.
+    pub fn elements(&self) -> &[ViaElement] {
+        &self.0
+    }
+
+    /// Append the given [`ViaElement`] to this [`Via`] header.
+    pub fn push(&mut self, element: ViaElement) -> &mut Self {
+        self.0.push(element);
+        self
+    }
+
+    /// Append the given [`ViaElement`] to the `Via` header of the given [`HeaderMap`],
+    /// as is to be done by a proxy forwarding a message.
+    ///
+    /// The existing `Via` chain is preserved as-is (even when it cannot be parsed),
+    /// with all existing `Via` header values combined into a single value.
+    pub fn append_hop(headers: &mut HeaderMap, element: ViaElement) {
+        let mut value = headers
+            .get_all(crate::header::VIA)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if !value.is_empty() {
+            value.push_str(", ");
+        }
+        value.push_str(&element.to_string());
+
+        match HeaderValue::try_from(value) {
+            Ok(value) => {
+                headers.insert(crate::header::VIA, value);
+            }
+            Err(err) => {
+                tracing::debug!(error = %err, "failed to append via element to existing via header");
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct ViaElement {
+/// A single element of the [`Via`] header,
+/// identifying a single intermediary that forwarded the message.
+///
+/// ```text
+/// [ <protocol-name> "/" ] <protocol-version> <received-by> [ "(" <comment> ")" ]
+/// ```
+pub struct ViaElement {
     protocol: Option<ForwardedProtocol>,
     version: ForwardedVersion,
     node_id: NodeId,
+    comment: Option<String>,
+}
+
+impl ViaElement {
+    /// Create a new [`ViaElement`] for the given protocol version
+    /// and the node that received the message.
+    pub fn new(version: ForwardedVersion, node_id: NodeId) -> Self {
+        Self {
+            protocol: None,
+            version,
+            node_id,
+            comment: None,
+        }
+    }
+
+    /// The protocol name of the received message, if defined.
+    ///
+    /// Defaults to `HTTP` when not defined.
+    pub fn protocol(&self) -> Option<&ForwardedProtocol> {
+        self.protocol.as_ref()
+    }
+
+    /// Set the protocol name of the received message.
+    pub fn with_protocol(mut self, protocol: ForwardedProtocol) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
+
+    /// Set the protocol name of the received message.
+    pub fn set_protocol(&mut self, protocol: ForwardedProtocol) -> &mut Self {
+        self.protocol = Some(protocol);
+        self
+    }
+
+    /// The protocol version of the received message.
+    pub fn version(&self) -> ForwardedVersion {
+        self.version
+    }
+
+    /// The node (host and optional port, or pseudonym) that received the message.
+    pub fn node_id(&self) -> &NodeId {
+        &self.node_id
+    }
+
+    /// The comment identifying the software of the intermediary, if defined.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Set the comment identifying the software of the intermediary.
+    ///
+    /// Parentheses, backslashes and non-printable characters are removed from the comment.
+    pub fn with_comment(mut self, comment: impl AsRef<str>) -> Self {
+        self.set_comment(comment);
+        self
+    }
+
+    /// Set the comment identifying the software of the intermediary.
+    ///
+    /// Parentheses, backslashes and non-printable characters are removed from the comment.
+    pub fn set_comment(&mut self, comment: impl AsRef<str>) -> &mut Self {
+        let comment: String = comment
+            .as_ref()
+            .chars()
+            .filter(|c| (c.is_ascii_graphic() || *c == ' ') && !matches!(c, '(' | ')' | '\\'))
+            .collect();
+        let comment = comment.trim();
+        self.comment = (!comment.is_empty()).then(|| comment.to_owned());
+        self
+    }
 }
 
 impl From<ViaElement> for ForwardedElement {
@@ -104,6 +228,7 @@ impl super::ForwardHeader for Via {
                     protocol,
                     version,
                     node_id,
+                    comment: None,
                 })
             })
             .collect();
@@ -174,12 +299,25 @@ impl std::str::FromStr for ViaElement {
         };
 
         bytes = trim_right(trim_left(bytes));
+        let (bytes, comment) = match bytes.iter().position(|b| *b == b'(') {
+            Some(index) if bytes.last() == Some(&b')') => {
+                let comment = std::str::from_utf8(&bytes[index + 1..bytes.len() - 1])
+                    .context("parse via comment as utf-8")?
+                    .trim();
+                (
+                    trim_right(&bytes[..index]),
+                    (!comment.is_empty()).then(|| comment.to_owned()),
+                )
+            }
+            _ => (bytes, None),
+        };
         let node_id = NodeId::from_bytes_lossy(bytes);
 
         Ok(Self {
             protocol,
             version,
             node_id,
+            comment,
         })
     }
 }
@@ -189,7 +327,11 @@ impl std::fmt::Display for ViaElement {
         if let Some(ref proto) = self.protocol {
             write!(f, "{proto}/")?;
         }
-        write!(f, "{} {}", self.version, self.node_id)
+        write!(f, "{} {}", self.version, self.node_id)?;
+        if let Some(ref comment) = self.comment {
+            write!(f, " ({comment})")?;
+        }
+        Ok(())
     }
 }
 
@@ -219,6 +361,20 @@ mod tests {
 
     use rama_http_types::HeaderValue;
 
+    fn el(
+        protocol: Option<ForwardedProtocol>,
+        version: ForwardedVersion,
+        node_id: &str,
+        comment: Option<&str>,
+    ) -> ViaElement {
+        ViaElement {
+            protocol,
+            version,
+            node_id: NodeId::try_from_str(node_id).unwrap(),
+            comment: comment.map(ToOwned::to_owned),
+        }
+    }
+
     macro_rules! test_header {
         ($name: ident, $input: expr, $expected: expr) => {
             #[test]
@@ -242,133 +398,114 @@ mod tests {
     test_header!(
         test1,
         vec!["1.1 vegur"],
-        Some(Via(vec![ViaElement {
-            protocol: None,
-            version: ForwardedVersion::HTTP_11,
-            node_id: NodeId::try_from_str("vegur").unwrap(),
-        }]))
+        Some(Via(vec![el(
+            None,
+            ForwardedVersion::HTTP_11,
+            "vegur",
+            None
+        )]))
     );
     test_header!(
         test2,
         vec!["1.1     vegur    "],
-        Some(Via(vec![ViaElement {
-            protocol: None,
-            version: ForwardedVersion::HTTP_11,
-            node_id: NodeId::try_from_str("vegur").unwrap(),
-        }]))
+        Some(Via(vec![el(
+            None,
+            ForwardedVersion::HTTP_11,
+            "vegur",
+            None
+        )]))
     );
     test_header!(
         test3,
         vec!["1.0 fred, 1.1 p.example.net"],
         Some(Via(vec![
-            ViaElement {
-                protocol: None,
-                version: ForwardedVersion::HTTP_10,
-                node_id: NodeId::try_from_str("fred").unwrap(),
-            },
-            ViaElement {
-                protocol: None,
-                version: ForwardedVersion::HTTP_11,
-                node_id: NodeId::try_from_str("p.example.net").unwrap(),
-            }
+            el(None, ForwardedVersion::HTTP_10, "fred", None),
+            el(None, ForwardedVersion::HTTP_11, "p.example.net", None),
         ]))
     );
     test_header!(
         test4,
         vec!["1.0 fred    ,    1.1 p.example.net   "],
         Some(Via(vec![
-            ViaElement {
-                protocol: None,
-                version: ForwardedVersion::HTTP_10,
-                node_id: NodeId::try_from_str("fred").unwrap(),
-            },
-            ViaElement {
-                protocol: None,
-                version: ForwardedVersion::HTTP_11,
-                node_id: NodeId::try_from_str("p.example.net").unwrap(),
-            }
+            el(None, ForwardedVersion::HTTP_10, "fred", None),
+            el(None, ForwardedVersion::HTTP_11, "p.example.net", None),
         ]))
     );
     test_header!(
         test5,
         vec!["1.0 fred", "1.1 p.example.net"],
         Some(Via(vec![
-            ViaElement {
-                protocol: None,
-                version: ForwardedVersion::HTTP_10,
-                node_id: NodeId::try_from_str("fred").unwrap(),
-            },
-            ViaElement {
-                protocol: None,
-                version: ForwardedVersion::HTTP_11,
-                node_id: NodeId::try_from_str("p.example.net").unwrap(),
-            }
+            el(None, ForwardedVersion::HTTP_10, "fred", None),
+            el(None, ForwardedVersion::HTTP_11, "p.example.net", None),
         ]))
     );
     test_header!(
         test6,
         vec!["HTTP/1.1 proxy.example.re, 1.1 edge_1"],
         Some(Via(vec![
-            ViaElement {
-                protocol: Some(ForwardedProtocol::HTTP),
-                version: ForwardedVersion::HTTP_11,
-                node_id: NodeId::try_from_str("proxy.example.re").unwrap(),
-            },
-            ViaElement {
-                protocol: None,
-                version: ForwardedVersion::HTTP_11,
-                node_id: NodeId::try_from_str("edge_1").unwrap(),
-            }
+            el(
+                Some(ForwardedProtocol::HTTP),
+                ForwardedVersion::HTTP_11,
+                "proxy.example.re",
+                None
+            ),
+            el(None, ForwardedVersion::HTTP_11, "edge_1", None),
         ]))
     );
     test_header!(
         test7,
         vec!["1.1 2e9b3ee4d534903f433e1ed8ea30e57a.cloudfront.net (CloudFront)"],
-        Some(Via(vec![ViaElement {
-            protocol: None,
-            version: ForwardedVersion::HTTP_11,
-            node_id: NodeId::try_from_str(
-                "2e9b3ee4d534903f433e1ed8ea30e57a.cloudfront.net__CloudFront_"
-            )
-            .unwrap(),
-        }]))
+        Some(Via(vec![el(
+            None,
+            ForwardedVersion::HTTP_11,
+            "2e9b3ee4d534903f433e1ed8ea30e57a.cloudfront.net",
+            Some("CloudFront"),
+        )]))
+    );
+    test_header!(
+        test8,
+        vec!["HTTP/2.0 proxy.example.com:8080 (rama proxy), 1.1 edge_1"],
+        Some(Via(vec![
+            el(
+                Some(ForwardedProtocol::HTTP),
+                ForwardedVersion::HTTP_2,
+                "proxy.example.com:8080",
+                Some("rama proxy"),
+            ),
+            el(None, ForwardedVersion::HTTP_11, "edge_1", None),
+        ]))
     );
 
     #[test]
     fn test_via_symmetric_encoder() {
         for via_input in [
             Via(vec![
-                ViaElement {
-                    protocol: None,
-                    version: ForwardedVersion::HTTP_10,
-                    node_id: NodeId::try_from_str("fred").unwrap(),
-                },
-                ViaElement {
-                    protocol: None,
-                    version: ForwardedVersion::HTTP_11,
-                    node_id: NodeId::try_from_str("p.example.net").unwrap(),
-                },
+                el(None, ForwardedVersion::HTTP_10, "fred", None),
+                el(None, ForwardedVersion::HTTP_11, "p.example.net", None),
             ]),
             Via(vec![
-                ViaElement {
-                    protocol: Some(ForwardedProtocol::HTTP),
-                    version: ForwardedVersion::HTTP_11,
-                    node_id: NodeId::try_from_str("proxy.example.re").unwrap(),
-                },
-                ViaElement {
-                    protocol: None,
-                    version: ForwardedVersion::HTTP_11,
-                    node_id: NodeId::try_from_str("edge_1").unwrap(),
-                },
+                el(
+                    Some(ForwardedProtocol::HTTP),
+                    ForwardedVersion::HTTP_11,
+                    "proxy.example.re",
+                    None,
+                ),
+                el(None, ForwardedVersion::HTTP_11, "edge_1", None),
             ]),
-            Via(vec![ViaElement {
-                protocol: None,
-                version: ForwardedVersion::HTTP_11,
-                node_id: NodeId::try_from_str(
-                    "2e9b3ee4d534903f433e1ed8ea30e57a.cloudfront.net__CloudFront_",
+            Via(vec![el(
+                None,
+                ForwardedVersion::HTTP_11,
+                "2e9b3ee4d534903f433e1ed8ea30e57a.cloudfront.net",
+                Some("CloudFront"),
+            )]),
+            Via::new(
+                ViaElement::new(
+                    ForwardedVersion::HTTP_2,
+                    NodeId::try_from_str("proxy.example.com:8080").unwrap(),
                 )
-                .unwrap(),
-            }]),
+                .with_protocol(ForwardedProtocol::HTTP)
+                .with_comment("rama (proxy)"),
+            ),
         ] {
             let mut values = Vec::new();
             via_input.encode(&mut values);
@@ -376,4 +513,46 @@ mod tests {
             assert_eq!(via_input, via_output);
         }
     }
+
+    #[test]
+    fn test_via_append_hop() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            crate::header::VIA,
+            HeaderValue::from_static("1.0 fred, 1.1 p.example.net"),
+        );
+        headers.append(crate::header::VIA, HeaderValue::from_static("HTTP/1.1 GWA"));
+
+        Via::append_hop(
+            &mut headers,
+            ViaElement::new(
+                ForwardedVersion::HTTP_11,
+                NodeId::try_from_str("rama.proxy").unwrap(),
+            )
+            .with_comment("rama"),
+        );
+
+        let values: Vec<_> = headers.get_all(crate::header::VIA).iter().collect();
+        assert_eq!(
+            values,
+            vec!["1.0 fred, 1.1 p.example.net, HTTP/1.1 GWA, 1.1 rama.proxy (rama)"]
+        );
+
+        let via = Via::decode(&mut headers.get_all(crate::header::VIA).iter()).unwrap();
+        assert_eq!(via.elements().len(), 4);
+        assert_eq!(via.elements()[3].comment(), Some("rama"));
+    }
+
+    #[test]
+    fn test_via_append_hop_empty() {
+        let mut headers = HeaderMap::new();
+        Via::append_hop(
+            &mut headers,
+            ViaElement::new(
+                ForwardedVersion::HTTP_2,
+                NodeId::try_from_str("rama.proxy").unwrap(),
+            ),
+        );
+        assert_eq!(headers.get(crate::header::VIA).unwrap(), "2 rama.proxy");
+    }
 }
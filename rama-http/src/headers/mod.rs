@@ -73,19 +73,21 @@ pub use rama_http_types::headers::{
     Authorization, CacheControl, Connection, ContentDisposition, ContentEncoding, ContentLength,
     ContentLocation, ContentRange, ContentType, Cookie, Date, ETag, Error, Expect, Expires, Host,
     IfMatch, IfModifiedSince, IfNoneMatch, IfRange, IfUnmodifiedSince, LastModified, Location,
-    Origin, Pragma, ProxyAuthorization, Range, Referer, ReferrerPolicy, RetryAfter,
-    SecWebsocketAccept, SecWebsocketKey, SecWebsocketVersion, Server, SetCookie,
-    StrictTransportSecurity, Te, TransferEncoding, Upgrade, UserAgent, Vary,
+    Origin, Pragma, ProxyAuthorization, Range, Referer, ReferrerPolicy, SecWebsocketVersion,
+    Server, SetCookie, StrictTransportSecurity, Te, TransferEncoding, Upgrade, UserAgent, Vary,
 };
 
 mod common;
 #[doc(inline)]
-pub use common::Accept;
+pub use common::{
+    Accept, Nel, ReportTo, ReportToGroup, ReportingEndpoints, RetryAfter, SecWebsocketAccept,
+    SecWebsocketExtensions, SecWebsocketKey, SecWebsocketProtocol, WebsocketExtension,
+};
 
 mod forwarded;
 #[doc(inline)]
 pub use forwarded::{
-    CFConnectingIp, ClientIp, ForwardHeader, Forwarded, TrueClientIp, Via, XClientIp,
+    CFConnectingIp, ClientIp, ForwardHeader, Forwarded, TrueClientIp, Via, ViaElement, XClientIp,
     XForwardedFor, XForwardedHost, XForwardedProto, XRealIp,
 };
 
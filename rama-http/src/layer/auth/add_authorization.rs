@@ -39,13 +39,59 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! Use [`ProvideAuthorizationLayer`] instead in case the credentials are to be
+//! sourced per request, using a [`CredentialProvider`].
+//!
+//! ```
+//! use rama_http::layer::validate_request::ValidateRequestHeader;
+//! use rama_http::layer::auth::ProvideAuthorizationLayer;
+//! use rama_http::{Body, Request, Response, StatusCode};
+//! use rama_net::user::{Bearer, ProxyCredential, credential_provider_fn};
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Service, Layer};
+//! use rama_core::error::{BoxError, OpaqueError};
+//!
+//! # async fn handle(request: Request) -> Result<Response, BoxError> {
+//! #     Ok(Response::new(Body::default()))
+//! # }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), BoxError> {
+//! # let service_that_requires_auth = ValidateRequestHeader::bearer(
+//! #     service_fn(handle),
+//! #     "token",
+//! # );
+//! let client = ProvideAuthorizationLayer::new(credential_provider_fn(|_ctx: &Context<()>| async {
+//!     // e.g. fetch a fresh token
+//!     let token = Bearer::try_from_clear_str("token")?;
+//!     Ok::<_, OpaqueError>(Some(ProxyCredential::Bearer(token)))
+//! }))
+//! .layer(service_that_requires_auth);
+//!
+//! let response = client
+//!     .serve(Context::default(), Request::new(Body::default()))
+//!     .await?;
+//!
+//! assert_eq!(StatusCode::OK, response.status());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`CredentialProvider`]: rama_net::user::CredentialProvider
 
+use crate::headers::authorization::Credentials;
 use crate::{HeaderValue, Request, Response};
 use base64::Engine as _;
-use rama_core::{Context, Layer, Service};
+use rama_core::{
+    error::{BoxError, ErrorExt, OpaqueError},
+    Context, Layer, Service,
+};
+use rama_net::user::{CredentialProvider, ProxyCredential};
 use rama_utils::macros::define_inner_service_accessors;
 use std::convert::TryFrom;
 use std::fmt;
+use std::sync::Arc;
 
 const BASE64: base64::engine::GeneralPurpose = base64::engine::general_purpose::STANDARD;
 
@@ -296,6 +342,210 @@ where
     }
 }
 
+/// Layer that applies [`ProvideAuthorization`], which adds authorization to requests
+/// using the [`Authorization`] header, with the credential sourced per request
+/// from a [`CredentialProvider`].
+///
+/// See the [module docs](crate::layer::auth::add_authorization) for an example.
+///
+/// [`Authorization`]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Authorization
+pub struct ProvideAuthorizationLayer<P> {
+    provider: Arc<P>,
+    sensitive: bool,
+    if_not_present: bool,
+}
+
+impl<P: fmt::Debug> fmt::Debug for ProvideAuthorizationLayer<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProvideAuthorizationLayer")
+            .field("provider", &self.provider)
+            .field("sensitive", &self.sensitive)
+            .field("if_not_present", &self.if_not_present)
+            .finish()
+    }
+}
+
+impl<P> Clone for ProvideAuthorizationLayer<P> {
+    fn clone(&self) -> Self {
+        Self {
+            provider: self.provider.clone(),
+            sensitive: self.sensitive,
+            if_not_present: self.if_not_present,
+        }
+    }
+}
+
+impl<P> ProvideAuthorizationLayer<P> {
+    /// Create a new [`ProvideAuthorizationLayer`] using the given [`CredentialProvider`].
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider: Arc::new(provider),
+            sensitive: false,
+            if_not_present: false,
+        }
+    }
+
+    /// Mark the header as [sensitive].
+    ///
+    /// This can for example be used to hide the header value from logs.
+    ///
+    /// [sensitive]: https://docs.rs/http/latest/http/header/struct.HeaderValue.html#method.set_sensitive
+    pub fn as_sensitive(mut self, sensitive: bool) -> Self {
+        self.sensitive = sensitive;
+        self
+    }
+
+    /// Mark the header as [sensitive].
+    ///
+    /// This can for example be used to hide the header value from logs.
+    ///
+    /// [sensitive]: https://docs.rs/http/latest/http/header/struct.HeaderValue.html#method.set_sensitive
+    pub fn set_as_sensitive(&mut self, sensitive: bool) -> &mut Self {
+        self.sensitive = sensitive;
+        self
+    }
+
+    /// Preserve the existing `Authorization` header if it exists,
+    /// in which case the provider is not asked for a credential.
+    pub fn if_not_present(mut self, value: bool) -> Self {
+        self.if_not_present = value;
+        self
+    }
+
+    /// Preserve the existing `Authorization` header if it exists,
+    /// in which case the provider is not asked for a credential.
+    pub fn set_if_not_present(&mut self, value: bool) -> &mut Self {
+        self.if_not_present = value;
+        self
+    }
+}
+
+impl<S, P> Layer<S> for ProvideAuthorizationLayer<P> {
+    type Service = ProvideAuthorization<S, P>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ProvideAuthorization {
+            inner,
+            provider: self.provider.clone(),
+            sensitive: self.sensitive,
+            if_not_present: self.if_not_present,
+        }
+    }
+}
+
+/// Middleware that adds authorization to requests using the [`Authorization`] header,
+/// with the credential sourced per request from a [`CredentialProvider`].
+///
+/// No header is added in case the provider has no credential for the request.
+///
+/// See the [module docs](crate::layer::auth::add_authorization) for an example.
+///
+/// [`Authorization`]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Authorization
+pub struct ProvideAuthorization<S, P> {
+    inner: S,
+    provider: Arc<P>,
+    sensitive: bool,
+    if_not_present: bool,
+}
+
+impl<S, P> ProvideAuthorization<S, P> {
+    /// Create a new [`ProvideAuthorization`] using the given [`CredentialProvider`].
+    pub fn new(inner: S, provider: P) -> Self {
+        ProvideAuthorizationLayer::new(provider).layer(inner)
+    }
+
+    define_inner_service_accessors!();
+
+    /// Mark the header as [sensitive].
+    ///
+    /// This can for example be used to hide the header value from logs.
+    ///
+    /// [sensitive]: https://docs.rs/http/latest/http/header/struct.HeaderValue.html#method.set_sensitive
+    pub fn as_sensitive(mut self, sensitive: bool) -> Self {
+        self.sensitive = sensitive;
+        self
+    }
+
+    /// Mark the header as [sensitive].
+    ///
+    /// This can for example be used to hide the header value from logs.
+    ///
+    /// [sensitive]: https://docs.rs/http/latest/http/header/struct.HeaderValue.html#method.set_sensitive
+    pub fn set_as_sensitive(&mut self, sensitive: bool) -> &mut Self {
+        self.sensitive = sensitive;
+        self
+    }
+
+    /// Preserve the existing `Authorization` header if it exists,
+    /// in which case the provider is not asked for a credential.
+    pub fn if_not_present(mut self, value: bool) -> Self {
+        self.if_not_present = value;
+        self
+    }
+
+    /// Preserve the existing `Authorization` header if it exists,
+    /// in which case the provider is not asked for a credential.
+    pub fn set_if_not_present(&mut self, value: bool) -> &mut Self {
+        self.if_not_present = value;
+        self
+    }
+}
+
+impl<S: fmt::Debug, P: fmt::Debug> fmt::Debug for ProvideAuthorization<S, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProvideAuthorization")
+            .field("inner", &self.inner)
+            .field("provider", &self.provider)
+            .field("sensitive", &self.sensitive)
+            .field("if_not_present", &self.if_not_present)
+            .finish()
+    }
+}
+
+impl<S: Clone, P> Clone for ProvideAuthorization<S, P> {
+    fn clone(&self) -> Self {
+        ProvideAuthorization {
+            inner: self.inner.clone(),
+            provider: self.provider.clone(),
+            sensitive: self.sensitive,
+            if_not_present: self.if_not_present,
+        }
+    }
+}
+
+impl<S, P, State, ReqBody, ResBody> Service<State, Request<ReqBody>> for ProvideAuthorization<S, P>
+where
+    S: Service<State, Request<ReqBody>, Response = Response<ResBody>, Error: Into<BoxError>>,
+    P: CredentialProvider<State, Error: Into<BoxError>>,
+    ReqBody: Send + 'static,
+    ResBody: Send + 'static,
+    State: Clone + Send + Sync + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        mut req: Request<ReqBody>,
+    ) -> Result<Self::Response, Self::Error> {
+        if !self.if_not_present || !req.headers().contains_key(http::header::AUTHORIZATION) {
+            let credential = self.provider.credential(&ctx).await.map_err(|err| {
+                OpaqueError::from_boxed(err.into()).context("provide authorization credential")
+            })?;
+            if let Some(credential) = credential {
+                let mut value = match credential {
+                    ProxyCredential::Basic(basic) => basic.encode(),
+                    ProxyCredential::Bearer(bearer) => bearer.encode(),
+                };
+                value.set_sensitive(self.sensitive);
+                req.headers_mut().insert(http::header::AUTHORIZATION, value);
+            }
+        }
+        self.inner.serve(ctx, req).await.map_err(Into::into)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[allow(unused_imports)]
@@ -361,6 +611,54 @@ mod tests {
         assert_eq!(res.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn provided_credentials() {
+        use rama_net::user::{credential_provider_fn, Basic, StaticCredentialProvider};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // service that requires auth for all requests
+        let svc = ValidateRequestHeaderLayer::basic("foo", "bar").layer(service_fn(echo));
+
+        let client = ProvideAuthorization::new(
+            svc.clone(),
+            StaticCredentialProvider::new(Basic::new("foo", "bar")),
+        );
+        let res = client
+            .serve(Context::default(), Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // the credential is sourced per request
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client =
+            ProvideAuthorizationLayer::new(credential_provider_fn({
+                let calls = calls.clone();
+                move |_ctx: &Context<()>| {
+                    let call = calls.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        Ok::<_, Infallible>((call > 0).then(|| Basic::new("foo", "bar").into()))
+                    }
+                }
+            }))
+            .as_sensitive(true)
+            .layer(svc);
+
+        // no credential provided for the first request
+        let res = client
+            .serve(Context::default(), Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = client
+            .serve(Context::default(), Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
     async fn echo<Body>(req: Request<Body>) -> Result<Response<Body>, BoxError> {
         Ok(Response::new(req.into_body()))
     }
@@ -3,11 +3,25 @@
 pub mod add_authorization;
 pub mod async_require_authorization;
 pub mod require_authorization;
+#[cfg(feature = "crypto")]
+pub mod require_bearer;
+#[cfg(feature = "crypto")]
+pub mod verify_signature;
 
 #[doc(inline)]
 pub use self::{
-    add_authorization::{AddAuthorization, AddAuthorizationLayer},
+    add_authorization::{
+        AddAuthorization, AddAuthorizationLayer, ProvideAuthorization, ProvideAuthorizationLayer,
+    },
     async_require_authorization::{
         AsyncAuthorizeRequest, AsyncRequireAuthorization, AsyncRequireAuthorizationLayer,
     },
 };
+
+#[cfg(feature = "crypto")]
+#[doc(inline)]
+pub use self::require_bearer::{ClaimsPredicate, RequireBearerAuth, RequireBearerAuthLayer};
+
+#[cfg(feature = "crypto")]
+#[doc(inline)]
+pub use self::verify_signature::{VerifySignature, VerifySignatureLayer};
@@ -65,6 +65,7 @@ use crate::{
 use rama_core::Context;
 
 use rama_net::user::UserId;
+use rama_utils::octets::ct_eq;
 
 const BASE64: base64::engine::GeneralPurpose = base64::engine::general_purpose::STANDARD;
 
@@ -213,7 +214,7 @@ where
         request: Request<B>,
     ) -> Result<(Context<S>, Request<B>), Response<Self::ResponseBody>> {
         match request.headers().get(header::AUTHORIZATION) {
-            Some(actual) if actual == self.credential.header_value => Ok((ctx, request)),
+            Some(actual) if ct_eq(actual, &self.credential.header_value) => Ok((ctx, request)),
             None if self.allow_anonymous => {
                 let mut ctx = ctx;
                 ctx.insert(UserId::Anonymous);
@@ -281,7 +282,7 @@ where
         request: Request<B>,
     ) -> Result<(Context<S>, Request<B>), Response<Self::ResponseBody>> {
         match request.headers().get(header::AUTHORIZATION) {
-            Some(actual) if actual == self.credential.header_value => Ok((ctx, request)),
+            Some(actual) if ct_eq(actual, &self.credential.header_value) => Ok((ctx, request)),
             None if self.allow_anonymous => {
                 let mut ctx = ctx;
                 ctx.insert(UserId::Anonymous);
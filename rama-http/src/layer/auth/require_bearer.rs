@@ -0,0 +1,437 @@
+//! Authorize requests using a JWT bearer token, validated by a [`JwtValidator`].
+//!
+//! Valid requests get the [`JwtClaims`] of their token inserted in the [`Context`],
+//! while requests with a missing or invalid token are answered with a `401 Unauthorized`
+//! response, containing a `WWW-Authenticate: Bearer` challenge as defined in [RFC 6750].
+//!
+//! Scope or role checks can be expressed using [`RequireBearerAuthLayer::with_predicate`],
+//! in which case requests with a valid token that does not satisfy that predicate
+//! are answered with a `403 Forbidden` response.
+//!
+//! [RFC 6750]: https://datatracker.ietf.org/doc/html/rfc6750
+//!
+//! # Example
+//!
+//! ```
+//! use rama_core::{service::service_fn, Context, Layer, Service};
+//! use rama_core::error::BoxError;
+//! use rama_crypto::jose::{Ed25519SigningKey, JwtClaims, JwtValidator};
+//! use rama_http::layer::auth::RequireBearerAuthLayer;
+//! use rama_http::{Body, Request, Response, StatusCode};
+//!
+//! async fn handle(ctx: Context<()>, _req: Request) -> Result<Response, BoxError> {
+//!     let claims: &JwtClaims = ctx.get().expect("claims of authorized request");
+//!     Ok(Response::new(Body::from(format!("hello {:?}", claims.registered.sub))))
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), BoxError> {
+//! let key = Ed25519SigningKey::generate()?;
+//! let validator = JwtValidator::new(key.public_jwk()).with_audience("rama");
+//!
+//! let service = RequireBearerAuthLayer::new(validator)
+//!     .with_predicate(|claims: &JwtClaims| {
+//!         claims.private.get("scope").and_then(|scope| scope.as_str()) == Some("admin")
+//!     })
+//!     .layer(service_fn(handle));
+//!
+//! let resp = service.serve(Context::default(), Request::new(Body::empty())).await?;
+//! assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    header::{self, HeaderValue},
+    Request, Response, StatusCode,
+};
+use rama_core::{Context, Layer, Service};
+use rama_crypto::jose::{JwtClaims, JwtValidator};
+use rama_utils::macros::define_inner_service_accessors;
+use serde::de::DeserializeOwned;
+use std::{fmt, marker::PhantomData, sync::Arc};
+
+/// A predicate that the [`JwtClaims`] of a valid bearer token have to satisfy,
+/// used by [`RequireBearerAuth`] to express scope or role checks.
+///
+/// It is implemented for `()`, accepting all claims,
+/// and for any `Fn(&JwtClaims<C>) -> bool`.
+pub trait ClaimsPredicate<C>: Send + Sync + 'static {
+    /// Returns `true` if the claims are allowed.
+    fn check(&self, claims: &JwtClaims<C>) -> bool;
+}
+
+impl<C> ClaimsPredicate<C> for () {
+    fn check(&self, _claims: &JwtClaims<C>) -> bool {
+        true
+    }
+}
+
+impl<C, F> ClaimsPredicate<C> for F
+where
+    F: Fn(&JwtClaims<C>) -> bool + Send + Sync + 'static,
+{
+    fn check(&self, claims: &JwtClaims<C>) -> bool {
+        self(claims)
+    }
+}
+
+/// Layer that applies [`RequireBearerAuth`], which authorizes requests
+/// using a JWT bearer token validated by a [`JwtValidator`].
+///
+/// See the [module docs](self) for more information.
+pub struct RequireBearerAuthLayer<C = DefaultClaims, P = ()> {
+    validator: Arc<JwtValidator>,
+    predicate: P,
+    _claims: PhantomData<fn() -> C>,
+}
+
+/// The default private claims type of [`RequireBearerAuthLayer`],
+/// keeping all private claims as a json object.
+pub type DefaultClaims = serde_json::Map<String, serde_json::Value>;
+
+impl<C, P: fmt::Debug> fmt::Debug for RequireBearerAuthLayer<C, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequireBearerAuthLayer")
+            .field("validator", &self.validator)
+            .field("predicate", &self.predicate)
+            .field(
+                "_claims",
+                &format_args!("{}", std::any::type_name::<fn() -> C>()),
+            )
+            .finish()
+    }
+}
+
+impl<C, P: Clone> Clone for RequireBearerAuthLayer<C, P> {
+    fn clone(&self) -> Self {
+        Self {
+            validator: self.validator.clone(),
+            predicate: self.predicate.clone(),
+            _claims: PhantomData,
+        }
+    }
+}
+
+impl RequireBearerAuthLayer {
+    /// Create a new [`RequireBearerAuthLayer`], validating bearer tokens using
+    /// the given [`JwtValidator`] and keeping the private claims as a json object.
+    pub fn new(validator: JwtValidator) -> Self {
+        Self::typed(validator)
+    }
+}
+
+impl<C> RequireBearerAuthLayer<C> {
+    /// Create a new [`RequireBearerAuthLayer`], validating bearer tokens using
+    /// the given [`JwtValidator`] and deserializing the private claims as `C`.
+    pub fn typed(validator: JwtValidator) -> Self {
+        Self {
+            validator: Arc::new(validator),
+            predicate: (),
+            _claims: PhantomData,
+        }
+    }
+}
+
+impl<C, P> RequireBearerAuthLayer<C, P> {
+    /// Require the [`JwtClaims`] of valid tokens to satisfy the given predicate.
+    pub fn with_predicate<T>(self, predicate: T) -> RequireBearerAuthLayer<C, T>
+    where
+        T: ClaimsPredicate<C>,
+    {
+        RequireBearerAuthLayer {
+            validator: self.validator,
+            predicate,
+            _claims: PhantomData,
+        }
+    }
+}
+
+impl<S, C, P: Clone> Layer<S> for RequireBearerAuthLayer<C, P> {
+    type Service = RequireBearerAuth<S, C, P>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequireBearerAuth {
+            inner,
+            validator: self.validator.clone(),
+            predicate: self.predicate.clone(),
+            _claims: PhantomData,
+        }
+    }
+}
+
+/// Middleware that authorizes requests using a JWT bearer token,
+/// validated by a [`JwtValidator`].
+///
+/// See the [module docs](self) for more information.
+pub struct RequireBearerAuth<S, C = DefaultClaims, P = ()> {
+    inner: S,
+    validator: Arc<JwtValidator>,
+    predicate: P,
+    _claims: PhantomData<fn() -> C>,
+}
+
+impl<S: fmt::Debug, C, P: fmt::Debug> fmt::Debug for RequireBearerAuth<S, C, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequireBearerAuth")
+            .field("inner", &self.inner)
+            .field("validator", &self.validator)
+            .field("predicate", &self.predicate)
+            .field(
+                "_claims",
+                &format_args!("{}", std::any::type_name::<fn() -> C>()),
+            )
+            .finish()
+    }
+}
+
+impl<S: Clone, C, P: Clone> Clone for RequireBearerAuth<S, C, P> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            validator: self.validator.clone(),
+            predicate: self.predicate.clone(),
+            _claims: PhantomData,
+        }
+    }
+}
+
+impl<S> RequireBearerAuth<S> {
+    /// Create a new [`RequireBearerAuth`], validating bearer tokens using
+    /// the given [`JwtValidator`] and keeping the private claims as a json object.
+    pub fn new(inner: S, validator: JwtValidator) -> Self {
+        Self::typed(inner, validator)
+    }
+}
+
+impl<S, C> RequireBearerAuth<S, C> {
+    /// Create a new [`RequireBearerAuth`], validating bearer tokens using
+    /// the given [`JwtValidator`] and deserializing the private claims as `C`.
+    pub fn typed(inner: S, validator: JwtValidator) -> Self {
+        Self {
+            inner,
+            validator: Arc::new(validator),
+            predicate: (),
+            _claims: PhantomData,
+        }
+    }
+}
+
+impl<S, C, P> RequireBearerAuth<S, C, P> {
+    /// Require the [`JwtClaims`] of valid tokens to satisfy the given predicate.
+    pub fn with_predicate<T>(self, predicate: T) -> RequireBearerAuth<S, C, T>
+    where
+        T: ClaimsPredicate<C>,
+    {
+        RequireBearerAuth {
+            inner: self.inner,
+            validator: self.validator,
+            predicate,
+            _claims: PhantomData,
+        }
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S, C, P, State, ReqBody, ResBody> Service<State, Request<ReqBody>>
+    for RequireBearerAuth<S, C, P>
+where
+    S: Service<State, Request<ReqBody>, Response = Response<ResBody>>,
+    C: DeserializeOwned + Clone + Send + Sync + 'static,
+    P: ClaimsPredicate<C>,
+    State: Clone + Send + Sync + 'static,
+    ReqBody: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        mut ctx: Context<State>,
+        req: Request<ReqBody>,
+    ) -> Result<Self::Response, Self::Error> {
+        let Some(token) = bearer_token(&req) else {
+            return Ok(challenge_response(StatusCode::UNAUTHORIZED, None));
+        };
+
+        let claims: JwtClaims<C> = match self.validator.validate(token) {
+            Ok(claims) => claims,
+            Err(err) => {
+                tracing::debug!(error = %err, "bearer token rejected");
+                return Ok(challenge_response(
+                    StatusCode::UNAUTHORIZED,
+                    Some(("invalid_token", err.description())),
+                ));
+            }
+        };
+
+        if !self.predicate.check(&claims) {
+            return Ok(challenge_response(
+                StatusCode::FORBIDDEN,
+                Some(("insufficient_scope", "token does not grant access")),
+            ));
+        }
+
+        ctx.insert(claims);
+        self.inner.serve(ctx, req).await
+    }
+}
+
+fn bearer_token<B>(req: &Request<B>) -> Option<&str> {
+    let value = req.headers().get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("bearer") {
+        return None;
+    }
+    let token = token.trim();
+    (!token.is_empty()).then_some(token)
+}
+
+fn challenge_response<ResBody: Default>(
+    status: StatusCode,
+    error: Option<(&'static str, &'static str)>,
+) -> Response<ResBody> {
+    let mut res = Response::new(ResBody::default());
+    *res.status_mut() = status;
+    let challenge = match error {
+        Some((error, description)) => HeaderValue::from_str(&format!(
+            r#"Bearer error="{error}", error_description="{description}""#
+        ))
+        .unwrap_or_else(|_| HeaderValue::from_static("Bearer")),
+        None => HeaderValue::from_static("Bearer"),
+    };
+    res.headers_mut()
+        .insert(header::WWW_AUTHENTICATE, challenge);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dep::http_body_util::BodyExt as _;
+    use crate::Body;
+    use rama_core::{error::BoxError, service::service_fn};
+    use rama_crypto::jose::{sign_compact, Ed25519SigningKey, JwsAlgorithm, JwsHeader};
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::{
+        convert::Infallible,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    #[derive(Debug, Clone, Deserialize)]
+    struct Scopes {
+        scope: String,
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn token(key: &Ed25519SigningKey, exp: u64, scope: &str) -> String {
+        let claims = json!({"sub": "alice", "aud": "rama", "exp": exp, "scope": scope});
+        sign_compact(
+            key,
+            JwsHeader::new(JwsAlgorithm::EdDSA),
+            claims.to_string().as_bytes(),
+        )
+        .unwrap()
+    }
+
+    fn request(token: Option<&str>) -> Request {
+        let mut builder = Request::builder();
+        if let Some(token) = token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    async fn echo_sub(ctx: Context<()>, _req: Request) -> Result<Response, Infallible> {
+        let claims = ctx.get::<JwtClaims<Scopes>>().unwrap();
+        Ok(Response::new(Body::from(format!(
+            "{}:{}",
+            claims.registered.sub.as_deref().unwrap_or_default(),
+            claims.private.scope
+        ))))
+    }
+
+    fn service(
+        key: &Ed25519SigningKey,
+    ) -> impl Service<(), Request, Response = Response, Error = Infallible> {
+        RequireBearerAuthLayer::<Scopes>::typed(
+            JwtValidator::new(key.public_jwk()).with_audience("rama"),
+        )
+        .with_predicate(|claims: &JwtClaims<Scopes>| {
+            claims.private.scope.split(' ').any(|scope| scope == "read")
+        })
+        .layer(service_fn(echo_sub))
+    }
+
+    fn www_authenticate(res: &Response) -> &str {
+        res.headers()
+            .get(header::WWW_AUTHENTICATE)
+            .unwrap()
+            .to_str()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn valid_token_inserts_claims() -> Result<(), BoxError> {
+        let key = Ed25519SigningKey::generate()?;
+        let token = token(&key, now() + 300, "read write");
+
+        let res = service(&key)
+            .serve(Context::default(), request(Some(&token)))
+            .await?;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await?.to_bytes();
+        assert_eq!(body, "alice:read write");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_rejected() -> Result<(), BoxError> {
+        let key = Ed25519SigningKey::generate()?;
+        let token = token(&key, now() - 3600, "read");
+
+        let res = service(&key)
+            .serve(Context::default(), request(Some(&token)))
+            .await?;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            www_authenticate(&res),
+            r#"Bearer error="invalid_token", error_description="token expired""#
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn missing_token_is_rejected() -> Result<(), BoxError> {
+        let key = Ed25519SigningKey::generate()?;
+
+        let res = service(&key)
+            .serve(Context::default(), request(None))
+            .await?;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(www_authenticate(&res), "Bearer");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn insufficient_scope_is_forbidden() -> Result<(), BoxError> {
+        let key = Ed25519SigningKey::generate()?;
+        let token = token(&key, now() + 300, "write");
+
+        let res = service(&key)
+            .serve(Context::default(), request(Some(&token)))
+            .await?;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert!(www_authenticate(&res).starts_with(r#"Bearer error="insufficient_scope""#));
+        Ok(())
+    }
+}
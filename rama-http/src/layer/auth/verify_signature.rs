@@ -0,0 +1,522 @@
+//! Verify requests signed using an HMAC-SHA256 signature over their raw body,
+//! as is common for webhooks (e.g. GitHub or Stripe style).
+//!
+//! The hex encoded signature is read from a configured header,
+//! optionally prefixed (e.g. `sha256=`), and compared in constant time
+//! against the signature computed over the raw body using a shared secret.
+//! Requests with a missing or invalid signature are answered
+//! with a `401 Unauthorized` response.
+//!
+//! To protect against replay attacks a timestamp header can be required
+//! using [`VerifySignatureLayer::with_timestamp`], in which case the signed
+//! payload is `{timestamp}.{body}`, with the timestamp in seconds since the unix epoch,
+//! signed exactly as the raw header value was received.
+//! Requests with a timestamp outside of the configured tolerance are rejected as well.
+//!
+//! The body is collected in memory in order to verify it, up to a configurable
+//! maximum size ([`DEFAULT_MAX_BODY_SIZE`] by default), also respecting the request
+//! [`BodyLimit`] if one is set in the [`Context`], and is passed as-is to the inner service.
+//! Bodies exceeding that limit are answered with a `413 Payload Too Large` response.
+//!
+//! # Example
+//!
+//! ```
+//! use rama_core::{service::service_fn, Context, Layer, Service};
+//! use rama_core::error::BoxError;
+//! use rama_http::dep::http_body_util::BodyExt;
+//! use rama_http::header::HeaderName;
+//! use rama_http::layer::auth::VerifySignatureLayer;
+//! use rama_http::{Body, Request, Response, StatusCode};
+//! use std::convert::Infallible;
+//!
+//! async fn handle(_ctx: Context<()>, req: Request) -> Result<Response, Infallible> {
+//!     let body = req.into_body().collect().await.unwrap().to_bytes();
+//!     Ok(Response::new(Body::from(body)))
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), BoxError> {
+//! let service = VerifySignatureLayer::new(
+//!     HeaderName::from_static("x-hub-signature-256"),
+//!     "webhook secret",
+//! )
+//! .with_signature_prefix("sha256=")
+//! .layer(service_fn(handle));
+//!
+//! let req = Request::builder()
+//!     .header(
+//!         "x-hub-signature-256",
+//!         "sha256=0a7b9f3ee1bc6a6d3ab5bcd8dbb4f2e9c0a3dbcd02ff6a87ea5cef7ea5a4b1d5",
+//!     )
+//!     .body(Body::from("{}"))?;
+//! let resp = service.serve(Context::default(), req).await?;
+//! assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    dep::http_body::Body as HttpBody,
+    dep::http_body_util::{BodyExt, LengthLimitError, Limited},
+    header::HeaderName,
+    Body, BodyLimit, Request, Response, StatusCode,
+};
+use bytes::Bytes;
+use rama_core::{error::BoxError, Context, Layer, Service};
+use rama_crypto::hmac::HmacSha256Key;
+use rama_utils::macros::define_inner_service_accessors;
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The default maximum size of the request bodies
+/// collected by the [`VerifySignature`] middleware.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+struct SignatureConfig {
+    key: HmacSha256Key,
+    signature_header: HeaderName,
+    signature_prefix: Option<String>,
+    timestamp: Option<(HeaderName, Duration)>,
+    max_body_size: usize,
+}
+
+/// Layer that applies [`VerifySignature`], which verifies
+/// the HMAC-SHA256 signature over the raw body of requests.
+///
+/// See the [module docs](self) for more information.
+#[derive(Debug, Clone)]
+pub struct VerifySignatureLayer {
+    config: Arc<SignatureConfig>,
+}
+
+impl VerifySignatureLayer {
+    /// Create a new [`VerifySignatureLayer`], reading the hex encoded signature
+    /// from the given header and verifying it using the given shared secret.
+    pub fn new(signature_header: HeaderName, secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            config: Arc::new(SignatureConfig {
+                key: HmacSha256Key::new(secret),
+                signature_header,
+                signature_prefix: None,
+                timestamp: None,
+                max_body_size: DEFAULT_MAX_BODY_SIZE,
+            }),
+        }
+    }
+
+    /// Set the prefix of the signature header value, e.g. `sha256=`.
+    ///
+    /// Requests of which the signature does not start with this prefix are rejected.
+    pub fn with_signature_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.config_mut().signature_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Set the prefix of the signature header value, e.g. `sha256=`.
+    ///
+    /// Requests of which the signature does not start with this prefix are rejected.
+    pub fn set_signature_prefix(&mut self, prefix: impl Into<String>) -> &mut Self {
+        self.config_mut().signature_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Require a timestamp, in seconds since the unix epoch, in the given header,
+    /// which may differ at most the given tolerance from the current time.
+    ///
+    /// The signed payload becomes `{timestamp}.{body}`.
+    pub fn with_timestamp(mut self, timestamp_header: HeaderName, tolerance: Duration) -> Self {
+        self.config_mut().timestamp = Some((timestamp_header, tolerance));
+        self
+    }
+
+    /// Require a timestamp, in seconds since the unix epoch, in the given header,
+    /// which may differ at most the given tolerance from the current time.
+    ///
+    /// The signed payload becomes `{timestamp}.{body}`.
+    pub fn set_timestamp(
+        &mut self,
+        timestamp_header: HeaderName,
+        tolerance: Duration,
+    ) -> &mut Self {
+        self.config_mut().timestamp = Some((timestamp_header, tolerance));
+        self
+    }
+
+    /// Set the maximum size of the request body,
+    /// which defaults to [`DEFAULT_MAX_BODY_SIZE`].
+    ///
+    /// Requests with a larger body are rejected with a `413 Payload Too Large` response.
+    /// The request [`BodyLimit`] found in the [`Context`] is respected as well,
+    /// with the smallest of both limits being applied.
+    pub fn with_max_body_size(mut self, size: usize) -> Self {
+        self.config_mut().max_body_size = size;
+        self
+    }
+
+    /// Set the maximum size of the request body,
+    /// which defaults to [`DEFAULT_MAX_BODY_SIZE`].
+    ///
+    /// Requests with a larger body are rejected with a `413 Payload Too Large` response.
+    /// The request [`BodyLimit`] found in the [`Context`] is respected as well,
+    /// with the smallest of both limits being applied.
+    pub fn set_max_body_size(&mut self, size: usize) -> &mut Self {
+        self.config_mut().max_body_size = size;
+        self
+    }
+
+    fn config_mut(&mut self) -> &mut SignatureConfig {
+        Arc::make_mut(&mut self.config)
+    }
+}
+
+impl<S> Layer<S> for VerifySignatureLayer {
+    type Service = VerifySignature<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        VerifySignature {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// Middleware that verifies the HMAC-SHA256 signature over the raw body of requests.
+///
+/// See the [module docs](self) for more information.
+pub struct VerifySignature<S> {
+    inner: S,
+    config: Arc<SignatureConfig>,
+}
+
+impl<S: fmt::Debug> fmt::Debug for VerifySignature<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerifySignature")
+            .field("inner", &self.inner)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<S: Clone> Clone for VerifySignature<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<S> VerifySignature<S> {
+    define_inner_service_accessors!();
+}
+
+impl<S, State, ReqBody, ResBody> Service<State, Request<ReqBody>> for VerifySignature<S>
+where
+    S: Service<State, Request, Response = Response<ResBody>>,
+    State: Clone + Send + Sync + 'static,
+    ReqBody: HttpBody<Data = Bytes, Error: Into<BoxError>> + Send + Sync + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request<ReqBody>,
+    ) -> Result<Self::Response, Self::Error> {
+        let config = &self.config;
+
+        let timestamp = match &config.timestamp {
+            Some((header, tolerance)) => {
+                let Some(value) = req.headers().get(header) else {
+                    return Ok(status_response(StatusCode::UNAUTHORIZED));
+                };
+                match value
+                    .to_str()
+                    .ok()
+                    .and_then(|value| value.trim().parse::<u64>().ok())
+                {
+                    // the raw header value is signed, exactly as it was received
+                    Some(timestamp) if is_within_tolerance(timestamp, *tolerance) => {
+                        Some(Bytes::copy_from_slice(value.as_bytes()))
+                    }
+                    Some(timestamp) => {
+                        tracing::debug!(timestamp, "signed request rejected: stale timestamp");
+                        return Ok(status_response(StatusCode::UNAUTHORIZED));
+                    }
+                    None => return Ok(status_response(StatusCode::UNAUTHORIZED)),
+                }
+            }
+            None => None,
+        };
+
+        let Some(signature) = req
+            .headers()
+            .get(&config.signature_header)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| match &config.signature_prefix {
+                Some(prefix) => value.trim().strip_prefix(prefix.as_str()),
+                None => Some(value.trim()),
+            })
+            .and_then(|value| hex::decode(value).ok())
+        else {
+            return Ok(status_response(StatusCode::UNAUTHORIZED));
+        };
+
+        let (parts, body) = req.into_parts();
+        let limit = ctx
+            .get::<BodyLimit>()
+            .and_then(BodyLimit::request)
+            .map_or(config.max_body_size, |limit| {
+                limit.min(config.max_body_size)
+            });
+        let body = match Limited::new(body, limit).collect().await {
+            Ok(body) => body.to_bytes(),
+            Err(err) if err.is::<LengthLimitError>() => {
+                tracing::debug!("signed request rejected: body exceeds limit");
+                return Ok(status_response(StatusCode::PAYLOAD_TOO_LARGE));
+            }
+            Err(err) => {
+                tracing::debug!(error = %err, "signed request rejected: failed to read body");
+                return Ok(status_response(StatusCode::BAD_REQUEST));
+            }
+        };
+
+        let verified = match timestamp {
+            Some(timestamp) => config
+                .key
+                .verify_parts([timestamp.as_ref(), b".", body.as_ref()], &signature),
+            None => config.key.verify(&body, &signature),
+        };
+        if !verified {
+            tracing::debug!("signed request rejected: signature mismatch");
+            return Ok(status_response(StatusCode::UNAUTHORIZED));
+        }
+
+        self.inner
+            .serve(ctx, Request::from_parts(parts, Body::from(body)))
+            .await
+    }
+}
+
+fn is_within_tolerance(timestamp: u64, tolerance: Duration) -> bool {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    now.abs_diff(timestamp) <= tolerance.as_secs()
+}
+
+fn status_response<ResBody: Default>(status: StatusCode) -> Response<ResBody> {
+    let mut res = Response::new(ResBody::default());
+    *res.status_mut() = status;
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::service::service_fn;
+    use std::convert::Infallible;
+
+    const SECRET: &str = "webhook secret";
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn sign(payload: &str) -> String {
+        format!(
+            "sha256={}",
+            hex::encode(HmacSha256Key::new(SECRET).sign(payload))
+        )
+    }
+
+    fn service(
+        layer: VerifySignatureLayer,
+    ) -> impl Service<(), Request, Response = Response, Error = Infallible> {
+        layer.layer(service_fn(|_ctx, req: Request| async move {
+            let body = req.into_body().collect().await.unwrap().to_bytes();
+            Ok::<_, Infallible>(Response::new(Body::from(body)))
+        }))
+    }
+
+    fn layer() -> VerifySignatureLayer {
+        VerifySignatureLayer::new(HeaderName::from_static("x-signature"), SECRET)
+            .with_signature_prefix("sha256=")
+    }
+
+    fn request(signature: &str, timestamp: Option<u64>, body: &'static str) -> Request {
+        let mut builder = Request::builder()
+            .method("POST")
+            .header("x-signature", signature);
+        if let Some(timestamp) = timestamp {
+            builder = builder.header("x-timestamp", timestamp);
+        }
+        builder.body(Body::from(body)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_valid_signature() {
+        let body = r#"{"action":"opened"}"#;
+        let res = service(layer())
+            .serve(Context::default(), request(&sign(body), None, body))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let echo = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(echo, body);
+
+        let timestamp = now();
+        let res = service(layer().with_timestamp(
+            HeaderName::from_static("x-timestamp"),
+            Duration::from_secs(300),
+        ))
+        .serve(
+            Context::default(),
+            request(&sign(&format!("{timestamp}.{body}")), Some(timestamp), body),
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_tampered_body() {
+        let signature = sign(r#"{"action":"opened"}"#);
+        let res = service(layer())
+            .serve(
+                Context::default(),
+                request(&signature, None, r#"{"action":"closed"}"#),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        for signature in ["", "sha256=", "sha256=zz", &signature[7..]] {
+            let res = service(layer())
+                .serve(
+                    Context::default(),
+                    request(signature, None, r#"{"action":"opened"}"#),
+                )
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED, "{signature}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stale_timestamp() {
+        let service = service(layer().with_timestamp(
+            HeaderName::from_static("x-timestamp"),
+            Duration::from_secs(300),
+        ));
+        let body = "{}";
+
+        let timestamp = now() - 600;
+        let res = service
+            .serve(
+                Context::default(),
+                request(&sign(&format!("{timestamp}.{body}")), Some(timestamp), body),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        // a fresh timestamp that was not part of the signed payload
+        let res = service
+            .serve(
+                Context::default(),
+                request(&sign(&format!("{timestamp}.{body}")), Some(now()), body),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = service
+            .serve(Context::default(), request(&sign(body), None, body))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_raw_timestamp_is_signed() {
+        let service = service(layer().with_timestamp(
+            HeaderName::from_static("x-timestamp"),
+            Duration::from_secs(300),
+        ));
+        let body = "{}";
+        let timestamp = format!("0{}", now());
+
+        let req = |payload: String| {
+            Request::builder()
+                .method("POST")
+                .header("x-signature", sign(&payload))
+                .header("x-timestamp", &timestamp)
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let res = service
+            .serve(Context::default(), req(format!("{timestamp}.{body}")))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // the normalized timestamp is not what the sender signed
+        let res = service
+            .serve(
+                Context::default(),
+                req(format!("{}.{body}", timestamp.trim_start_matches('0'))),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_body_exceeds_max_body_size() {
+        let body = r#"{"action":"opened"}"#;
+        let res = service(layer().with_max_body_size(4))
+            .serve(Context::default(), request(&sign(body), None, body))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // the smallest of both limits applies
+        let mut ctx = Context::default();
+        ctx.insert(BodyLimit::request_only(1024));
+        let res = service(layer().with_max_body_size(4))
+            .serve(ctx, request(&sign(body), None, body))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let res = service(layer().with_max_body_size(body.len()))
+            .serve(Context::default(), request(&sign(body), None, body))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_body_too_large() {
+        let body = r#"{"action":"opened"}"#;
+        let mut ctx = Context::default();
+        ctx.insert(BodyLimit::request_only(4));
+        let res = service(layer())
+            .serve(ctx, request(&sign(body), None, body))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
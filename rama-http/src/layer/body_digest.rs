@@ -0,0 +1,543 @@
+//! Middleware that computes a digest (hash) of request and/or response bodies
+//! while they are streamed, without buffering them.
+//!
+//! As the digest is only known once the body has been fully streamed,
+//! it is exposed as a [`BodyDigest`] handle, which is inserted in the
+//! extensions of the request and/or response before the body is streamed.
+//! Its value becomes available once the body has been consumed.
+//!
+//! For responses the digest can optionally also be sent as a
+//! [`Repr-Digest`](https://www.rfc-editor.org/rfc/rfc9530#name-the-repr-digest-field) trailer,
+//! announced using the `Trailer` header.
+//!
+//! # Example
+//!
+//! ```
+//! use rama_core::{service::service_fn, Context, Layer, Service};
+//! use rama_http::dep::http_body_util::BodyExt;
+//! use rama_http::layer::body_digest::{BodyDigest, BodyDigestLayer, DigestAlgorithm};
+//! use rama_http::{Body, Request, Response};
+//! use std::convert::Infallible;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let service = BodyDigestLayer::new(DigestAlgorithm::Sha256)
+//!     .with_repr_digest_trailer(true)
+//!     .layer(service_fn(|_req: Request| async move {
+//!         Ok::<_, Infallible>(Response::new(Body::from("hello world")))
+//!     }));
+//!
+//! let res = service
+//!     .serve(Context::default(), Request::new(Body::empty()))
+//!     .await
+//!     .unwrap();
+//! let digest = res.extensions().get::<BodyDigest>().unwrap().clone();
+//! assert!(digest.value().is_none());
+//!
+//! let collected = res.into_body().collect().await.unwrap();
+//! assert_eq!(
+//!     collected.trailers().unwrap()["repr-digest"],
+//!     "sha-256=:uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek=:",
+//! );
+//! assert!(digest.value().is_some());
+//! # }
+//! ```
+
+use crate::dep::http_body::{self, Body as HttpBody, Frame};
+use crate::{header, Body, HeaderMap, HeaderName, HeaderValue, Request, Response};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use bytes::Bytes;
+use pin_project_lite::pin_project;
+use rama_core::{error::BoxError, Context, Layer, Service};
+use rama_utils::macros::define_inner_service_accessors;
+use sha2::{Digest as _, Sha256, Sha512};
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    task::{self, Poll},
+};
+
+/// The `Repr-Digest` (trailer) header name.
+static REPR_DIGEST: HeaderName = HeaderName::from_static("repr-digest");
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+/// The (hash) algorithm used to compute a [`BodyDigest`].
+pub enum DigestAlgorithm {
+    #[default]
+    /// SHA-256
+    Sha256,
+    /// SHA-512
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    /// Returns the name of the [`DigestAlgorithm`],
+    /// as registered in the HTTP Digest Algorithm Values registry.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha-256",
+            Self::Sha512 => "sha-512",
+        }
+    }
+
+    fn hasher(&self) -> Hasher {
+        match self {
+            Self::Sha256 => Hasher::Sha256(Sha256::new()),
+            Self::Sha512 => Hasher::Sha512(Sha512::new()),
+        }
+    }
+}
+
+impl fmt::Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+/// The direction(s) in which the [`BodyDigestService`] computes body digests.
+pub enum DigestDirection {
+    /// Compute the digest of request bodies only.
+    Request,
+    #[default]
+    /// Compute the digest of response bodies only.
+    Response,
+    /// Compute the digest of both request and response bodies.
+    Both,
+}
+
+impl DigestDirection {
+    fn includes_request(self) -> bool {
+        matches!(self, Self::Request | Self::Both)
+    }
+
+    fn includes_response(self) -> bool {
+        matches!(self, Self::Response | Self::Both)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Handle to the digest of a request or response body,
+/// inserted in its extensions by the [`BodyDigestService`].
+///
+/// The value is only available once the body has been fully streamed.
+pub struct BodyDigest {
+    algorithm: DigestAlgorithm,
+    value: Arc<OnceLock<Vec<u8>>>,
+}
+
+impl BodyDigest {
+    fn new(algorithm: DigestAlgorithm) -> Self {
+        Self {
+            algorithm,
+            value: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Returns the [`DigestAlgorithm`] used to compute this digest.
+    pub fn algorithm(&self) -> DigestAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the raw digest value,
+    /// or `None` if the body has not been fully streamed (yet).
+    pub fn value(&self) -> Option<&[u8]> {
+        self.value.get().map(Vec::as_slice)
+    }
+
+    /// Returns the digest as a (`Repr-Digest`) structured header value,
+    /// e.g. `sha-256=:<base64>:`, or `None` if the body has not been fully streamed (yet).
+    pub fn header_value(&self) -> Option<HeaderValue> {
+        self.value().and_then(|value| {
+            HeaderValue::try_from(format!("{}=:{}:", self.algorithm, BASE64.encode(value))).ok()
+        })
+    }
+}
+
+/// Layer that applies [`BodyDigestService`], which computes
+/// the digest of request and/or response bodies.
+///
+/// See the [module docs](self) for more details.
+#[derive(Debug, Clone, Default)]
+pub struct BodyDigestLayer {
+    algorithm: DigestAlgorithm,
+    direction: DigestDirection,
+    repr_digest_trailer: bool,
+}
+
+impl BodyDigestLayer {
+    /// Create a new [`BodyDigestLayer`] computing the digest of
+    /// response bodies using the given [`DigestAlgorithm`].
+    pub const fn new(algorithm: DigestAlgorithm) -> Self {
+        Self {
+            algorithm,
+            direction: DigestDirection::Response,
+            repr_digest_trailer: false,
+        }
+    }
+
+    /// Set the [`DigestDirection`] in which body digests are computed.
+    pub const fn with_direction(mut self, direction: DigestDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Set the [`DigestDirection`] in which body digests are computed.
+    pub fn set_direction(&mut self, direction: DigestDirection) -> &mut Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Send the digest of response bodies as a `Repr-Digest` trailer.
+    ///
+    /// Only applies when computing the digest of response bodies.
+    pub const fn with_repr_digest_trailer(mut self, enabled: bool) -> Self {
+        self.repr_digest_trailer = enabled;
+        self
+    }
+
+    /// Send the digest of response bodies as a `Repr-Digest` trailer.
+    ///
+    /// Only applies when computing the digest of response bodies.
+    pub fn set_repr_digest_trailer(&mut self, enabled: bool) -> &mut Self {
+        self.repr_digest_trailer = enabled;
+        self
+    }
+}
+
+impl<S> Layer<S> for BodyDigestLayer {
+    type Service = BodyDigestService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BodyDigestService {
+            inner,
+            algorithm: self.algorithm,
+            direction: self.direction,
+            repr_digest_trailer: self.repr_digest_trailer,
+        }
+    }
+}
+
+/// Middleware that computes the digest of request and/or response bodies.
+///
+/// See the [module docs](self) for more details.
+pub struct BodyDigestService<S> {
+    inner: S,
+    algorithm: DigestAlgorithm,
+    direction: DigestDirection,
+    repr_digest_trailer: bool,
+}
+
+impl<S> BodyDigestService<S> {
+    /// Create a new [`BodyDigestService`] computing the digest of
+    /// response bodies using the given [`DigestAlgorithm`].
+    pub const fn new(inner: S, algorithm: DigestAlgorithm) -> Self {
+        Self {
+            inner,
+            algorithm,
+            direction: DigestDirection::Response,
+            repr_digest_trailer: false,
+        }
+    }
+
+    /// Set the [`DigestDirection`] in which body digests are computed.
+    pub const fn with_direction(mut self, direction: DigestDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Set the [`DigestDirection`] in which body digests are computed.
+    pub fn set_direction(&mut self, direction: DigestDirection) -> &mut Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Send the digest of response bodies as a `Repr-Digest` trailer.
+    ///
+    /// Only applies when computing the digest of response bodies.
+    pub const fn with_repr_digest_trailer(mut self, enabled: bool) -> Self {
+        self.repr_digest_trailer = enabled;
+        self
+    }
+
+    /// Send the digest of response bodies as a `Repr-Digest` trailer.
+    ///
+    /// Only applies when computing the digest of response bodies.
+    pub fn set_repr_digest_trailer(&mut self, enabled: bool) -> &mut Self {
+        self.repr_digest_trailer = enabled;
+        self
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S: fmt::Debug> fmt::Debug for BodyDigestService<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyDigestService")
+            .field("inner", &self.inner)
+            .field("algorithm", &self.algorithm)
+            .field("direction", &self.direction)
+            .field("repr_digest_trailer", &self.repr_digest_trailer)
+            .finish()
+    }
+}
+
+impl<S: Clone> Clone for BodyDigestService<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            algorithm: self.algorithm,
+            direction: self.direction,
+            repr_digest_trailer: self.repr_digest_trailer,
+        }
+    }
+}
+
+impl<State, S, ReqBody, ResBody> Service<State, Request<ReqBody>> for BodyDigestService<S>
+where
+    State: Clone + Send + Sync + 'static,
+    S: Service<State, Request, Response = Response<ResBody>>,
+    ReqBody: HttpBody<Data = Bytes, Error: Into<BoxError>> + Send + Sync + 'static,
+    ResBody: HttpBody<Data = Bytes, Error: Into<BoxError>> + Send + Sync + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request<ReqBody>,
+    ) -> Result<Self::Response, Self::Error> {
+        let req = if self.direction.includes_request() {
+            let digest = BodyDigest::new(self.algorithm);
+            let (mut parts, body) = req.into_parts();
+            parts.extensions.insert(digest.clone());
+            Request::from_parts(parts, Body::new(DigestBody::new(body, digest, false)))
+        } else {
+            req.map(Body::new)
+        };
+
+        let res = self.inner.serve(ctx, req).await?;
+
+        if !self.direction.includes_response() {
+            return Ok(res.map(Body::new));
+        }
+
+        let digest = BodyDigest::new(self.algorithm);
+        let (mut parts, body) = res.into_parts();
+        parts.extensions.insert(digest.clone());
+        if self.repr_digest_trailer {
+            parts
+                .headers
+                .append(header::TRAILER, HeaderValue::from_static("repr-digest"));
+        }
+        Ok(Response::from_parts(
+            parts,
+            Body::new(DigestBody::new(body, digest, self.repr_digest_trailer)),
+        ))
+    }
+}
+
+enum Hasher {
+    Sha256(Sha256),
+    Sha512(Sha512),
+}
+
+impl Hasher {
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Sha512(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Self::Sha256(hasher) => hasher.finalize().to_vec(),
+            Self::Sha512(hasher) => hasher.finalize().to_vec(),
+        }
+    }
+}
+
+pin_project! {
+    /// Body wrapper computing the digest of the inner body while it is streamed.
+    struct DigestBody<B> {
+        #[pin]
+        inner: B,
+        hasher: Option<Hasher>,
+        digest: BodyDigest,
+        trailer: bool,
+    }
+}
+
+impl<B> DigestBody<B> {
+    fn new(inner: B, digest: BodyDigest, trailer: bool) -> Self {
+        Self {
+            inner,
+            hasher: Some(digest.algorithm.hasher()),
+            digest,
+            trailer,
+        }
+    }
+}
+
+impl<B: HttpBody<Data = Bytes>> HttpBody for DigestBody<B> {
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let Some(hasher) = this.hasher.as_mut() else {
+            return Poll::Ready(None);
+        };
+
+        match futures_lite::ready!(this.inner.poll_frame(cx)) {
+            Some(Ok(frame)) => {
+                let frame = match frame.into_data() {
+                    Ok(data) => {
+                        hasher.update(&data);
+                        return Poll::Ready(Some(Ok(Frame::data(data))));
+                    }
+                    Err(frame) => frame,
+                };
+                match frame.into_trailers() {
+                    Ok(mut trailers) => {
+                        finalize(
+                            this.hasher,
+                            this.digest,
+                            this.trailer.then_some(&mut trailers),
+                        );
+                        Poll::Ready(Some(Ok(Frame::trailers(trailers))))
+                    }
+                    Err(frame) => Poll::Ready(Some(Ok(frame))),
+                }
+            }
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+            None => {
+                if *this.trailer {
+                    let mut trailers = HeaderMap::new();
+                    finalize(this.hasher, this.digest, Some(&mut trailers));
+                    Poll::Ready(Some(Ok(Frame::trailers(trailers))))
+                } else {
+                    finalize(this.hasher, this.digest, None);
+                    Poll::Ready(None)
+                }
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        // the digest is only finalized once the inner body has been polled to the end
+        self.hasher.is_none()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+fn finalize(hasher: &mut Option<Hasher>, digest: &BodyDigest, trailers: Option<&mut HeaderMap>) {
+    if let Some(hasher) = hasher.take() {
+        let _ = digest.value.set(hasher.finalize());
+    }
+    if let Some(trailers) = trailers {
+        if let Some(value) = digest.header_value() {
+            trailers.insert(REPR_DIGEST.clone(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dep::http_body_util::BodyExt;
+    use rama_core::service::service_fn;
+    use std::convert::Infallible;
+
+    // sha-256 of "hello world"
+    const HELLO_WORLD_SHA256: &str =
+        "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+
+    fn streamed_body() -> Body {
+        Body::from_stream(futures_lite::stream::iter(
+            ["hello", " ", "world"]
+                .map(|chunk| Ok::<_, Infallible>(Bytes::from_static(chunk.as_bytes()))),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_body_digest_response_streamed() {
+        let service = BodyDigestLayer::new(DigestAlgorithm::Sha256)
+            .with_repr_digest_trailer(true)
+            .layer(service_fn(|_req: Request| async move {
+                Ok::<_, Infallible>(Response::new(streamed_body()))
+            }));
+
+        let res = service
+            .serve(Context::default(), Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(res.headers()[header::TRAILER], "repr-digest");
+        let digest = res.extensions().get::<BodyDigest>().unwrap().clone();
+        assert_eq!(digest.algorithm(), DigestAlgorithm::Sha256);
+        assert!(digest.value().is_none());
+
+        let collected = res.into_body().collect().await.unwrap();
+        assert_eq!(
+            collected.trailers().unwrap()[&REPR_DIGEST],
+            "sha-256=:uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek=:"
+        );
+        assert_eq!(collected.to_bytes(), "hello world");
+        assert_eq!(hex::encode(digest.value().unwrap()), HELLO_WORLD_SHA256);
+    }
+
+    #[tokio::test]
+    async fn test_body_digest_request_streamed() {
+        let service = BodyDigestLayer::new(DigestAlgorithm::Sha256)
+            .with_direction(DigestDirection::Request)
+            .layer(service_fn(|req: Request| async move {
+                let digest = req.extensions().get::<BodyDigest>().unwrap().clone();
+                let body = req.into_body().collect().await.unwrap().to_bytes();
+                assert_eq!(body, "hello world");
+                Ok::<_, Infallible>(Response::new(Body::from(hex::encode(
+                    digest.value().unwrap(),
+                ))))
+            }));
+
+        let res = service
+            .serve(Context::default(), Request::new(streamed_body()))
+            .await
+            .unwrap();
+        assert!(res.extensions().get::<BodyDigest>().is_none());
+        assert!(!res.headers().contains_key(header::TRAILER));
+
+        let collected = res.into_body().collect().await.unwrap();
+        assert!(collected.trailers().is_none());
+        assert_eq!(collected.to_bytes(), HELLO_WORLD_SHA256);
+    }
+
+    #[tokio::test]
+    async fn test_body_digest_sha512_empty() {
+        let service = BodyDigestLayer::new(DigestAlgorithm::Sha512).layer(service_fn(
+            |_req: Request| async move { Ok::<_, Infallible>(Response::new(Body::empty())) },
+        ));
+
+        let res = service
+            .serve(Context::default(), Request::new(Body::empty()))
+            .await
+            .unwrap();
+        let digest = res.extensions().get::<BodyDigest>().unwrap().clone();
+        let collected = res.into_body().collect().await.unwrap();
+        assert!(collected.trailers().is_none());
+        assert_eq!(
+            hex::encode(digest.value().unwrap()),
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+             47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+        );
+    }
+}
@@ -99,7 +99,7 @@ mod tests {
     use crate::dep::http_body::Body as _;
     use crate::dep::http_body_util::BodyExt;
     use crate::header::{
-        ACCEPT_ENCODING, ACCEPT_RANGES, CONTENT_ENCODING, CONTENT_RANGE, CONTENT_TYPE, RANGE,
+        ACCEPT_ENCODING, ACCEPT_RANGES, CONTENT_ENCODING, CONTENT_RANGE, CONTENT_TYPE, RANGE, VARY,
     };
     use crate::{Body, HeaderValue, Request, Response};
     use async_compression::tokio::write::{BrotliDecoder, BrotliEncoder};
@@ -504,4 +504,73 @@ mod tests {
         let body = res.into_body();
         assert_eq!(body.size_hint().exact().unwrap(), MSG.len() as u64);
     }
+
+    #[tokio::test]
+    async fn negotiates_br_over_gzip_by_qvalue() {
+        let msg = "Hello, World! ".repeat(8);
+        let body = msg.clone();
+        let svc = service_fn(move |_| {
+            let body = body.clone();
+            async move { Ok::<_, Infallible>(Response::new(Body::from(body))) }
+        });
+        let svc = Compression::new(svc);
+
+        let req = Request::builder()
+            .header(ACCEPT_ENCODING, "gzip;q=0.5, br;q=0.8, deflate;q=0.1")
+            .body(Body::empty())
+            .unwrap();
+        let res = svc.serve(Context::default(), req).await.unwrap();
+
+        assert_eq!(res.headers()[CONTENT_ENCODING], "br");
+        assert_eq!(res.headers()[VARY], "accept-encoding");
+
+        let data = res.into_body().collect().await.unwrap().to_bytes();
+        let mut decoder = BrotliDecoder::new(Vec::new());
+        decoder.write_all(&data).await.unwrap();
+        decoder.shutdown().await.unwrap();
+        assert_eq!(String::from_utf8(decoder.into_inner()).unwrap(), msg);
+    }
+
+    #[tokio::test]
+    async fn doesnt_compress_tiny_responses() {
+        const MSG: &str = "tiny";
+        let svc = service_fn(|_| async { Ok::<_, Infallible>(Response::new(Body::from(MSG))) });
+        let svc = Compression::new(svc);
+
+        let req = Request::builder()
+            .header(ACCEPT_ENCODING, "br, gzip")
+            .body(Body::empty())
+            .unwrap();
+        let res = svc.serve(Context::default(), req).await.unwrap();
+
+        assert!(res.headers().get(CONTENT_ENCODING).is_none());
+        assert!(res.headers().get(VARY).is_none());
+        let data = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(data, MSG);
+    }
+
+    #[tokio::test]
+    async fn doesnt_compress_already_compressed_content_types() {
+        for content_type in ["application/zip", "video/mp4", "font/woff2"] {
+            let svc = service_fn(move |_| async move {
+                let mut res = Response::new(Body::from(
+                    "a".repeat((SizeAbove::DEFAULT_MIN_SIZE * 2) as usize),
+                ));
+                res.headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+                Ok::<_, Infallible>(res)
+            });
+            let svc = Compression::new(svc);
+
+            let req = Request::builder()
+                .header(ACCEPT_ENCODING, "gzip")
+                .body(Body::empty())
+                .unwrap();
+            let res = svc.serve(Context::default(), req).await.unwrap();
+            assert!(
+                res.headers().get(CONTENT_ENCODING).is_none(),
+                "{content_type}"
+            );
+        }
+    }
 }
@@ -91,6 +91,8 @@ where
 /// - They're gRPC, which has its own protocol specific compression scheme.
 /// - It's an image as determined by the `content-type` starting with `image/`.
 /// - They're Server-Sent Events (SSE) as determined by the `content-type` being `text/event-stream`.
+/// - The content is already compressed as determined by the `content-type`,
+///   see [`NotForCompressedContentType`].
 /// - The response is less than 32 bytes.
 ///
 /// # Configuring the defaults
@@ -115,7 +117,10 @@ where
 /// [`CompressionLayer`]: super::CompressionLayer
 #[derive(Debug, Clone)]
 pub struct DefaultPredicate(
-    And<And<And<SizeAbove, NotForContentType>, NotForContentType>, NotForContentType>,
+    And<
+        And<And<And<SizeAbove, NotForContentType>, NotForContentType>, NotForContentType>,
+        NotForCompressedContentType,
+    >,
 );
 
 impl DefaultPredicate {
@@ -124,7 +129,8 @@ impl DefaultPredicate {
         let inner = SizeAbove::new(SizeAbove::DEFAULT_MIN_SIZE)
             .and(NotForContentType::GRPC)
             .and(NotForContentType::IMAGES)
-            .and(NotForContentType::SSE);
+            .and(NotForContentType::SSE)
+            .and(NotForCompressedContentType);
         Self(inner)
     }
 }
@@ -239,6 +245,45 @@ impl Predicate for NotForContentType {
     }
 }
 
+/// Predicate that wont allow responses with a `content-type` of content
+/// which is already compressed to be compressed (again), as it is not worth the effort.
+///
+/// This covers archives (e.g. `application/zip`, `application/gzip`),
+/// audio and video (`audio/*` and `video/*`) and WOFF fonts.
+///
+/// Images are not covered by this predicate, use [`NotForContentType::IMAGES`] for those.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NotForCompressedContentType;
+
+impl NotForCompressedContentType {
+    const CONTENT_TYPES: &[&str] = &[
+        "application/gzip",
+        "application/x-gzip",
+        "application/zip",
+        "application/zstd",
+        "application/x-bzip2",
+        "application/x-xz",
+        "application/x-7z-compressed",
+        "application/x-rar-compressed",
+        "application/vnd.rar",
+        "audio/",
+        "video/",
+        "font/woff",
+    ];
+}
+
+impl Predicate for NotForCompressedContentType {
+    fn should_compress<B>(&self, response: &http::Response<B>) -> bool
+    where
+        B: Body,
+    {
+        let content_type = content_type(response);
+        !Self::CONTENT_TYPES
+            .iter()
+            .any(|compressed| content_type.starts_with(compressed))
+    }
+}
+
 #[derive(Clone)]
 enum Str {
     Static(&'static str),
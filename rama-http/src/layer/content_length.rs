@@ -0,0 +1,303 @@
+//! Middleware to check the consistency of the `Content-Length` of responses.
+//!
+//! A response with a declared `Content-Length` that disagrees with the actual
+//! length of its body results in client errors, which are often hard to debug.
+//! The [`ContentLengthCheck`] middleware catches such responses:
+//!
+//! - for non-streaming bodies (with an exact size hint) the `Content-Length`
+//!   header has to match the length of the body;
+//! - for all bodies the `Content-Length` header cannot be combined
+//!   with a chunked `Transfer-Encoding`.
+//!
+//! In the default [`ContentLengthCheckMode::Debug`] mode inconsistent responses
+//! panic when debug assertions are enabled and are only logged otherwise,
+//! while the [`ContentLengthCheckMode::Strict`] mode turns them into errors.
+//!
+//! # Example
+//!
+//! ```
+//! use rama_core::{service::service_fn, Context, Layer, Service};
+//! use rama_http::layer::content_length::ContentLengthCheckLayer;
+//! use rama_http::{header::CONTENT_LENGTH, Body, Request, Response};
+//! use std::convert::Infallible;
+//!
+//! async fn handle(_req: Request) -> Result<Response, Infallible> {
+//!     Ok(Response::builder()
+//!         .header(CONTENT_LENGTH, 3)
+//!         .body(Body::from("hello"))
+//!         .unwrap())
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let service = ContentLengthCheckLayer::strict().layer(service_fn(handle));
+//!
+//! let result = service.serve(Context::default(), Request::new(Body::empty())).await;
+//! assert!(result.is_err());
+//! # }
+//! ```
+
+use crate::dep::http_body::Body as HttpBody;
+use crate::{header, HeaderMap, Request, Response};
+use rama_core::{
+    error::{BoxError, OpaqueError},
+    Context, Layer, Service,
+};
+use rama_utils::macros::define_inner_service_accessors;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The mode of the [`ContentLengthCheck`] middleware,
+/// defining what happens on an inconsistent response.
+pub enum ContentLengthCheckMode {
+    #[default]
+    /// Panic when debug assertions are enabled, log a warning otherwise.
+    ///
+    /// The response is returned as-is when not panicking.
+    Debug,
+    /// Return an error instead of the response.
+    Strict,
+}
+
+/// Layer that applies [`ContentLengthCheck`], which checks the
+/// consistency of the `Content-Length` of responses.
+///
+/// See the [module docs](self) for more details.
+#[derive(Debug, Clone, Default)]
+pub struct ContentLengthCheckLayer {
+    mode: ContentLengthCheckMode,
+}
+
+impl ContentLengthCheckLayer {
+    /// Create a new [`ContentLengthCheckLayer`] using the given [`ContentLengthCheckMode`].
+    pub const fn new(mode: ContentLengthCheckMode) -> Self {
+        Self { mode }
+    }
+
+    /// Create a new [`ContentLengthCheckLayer`] using [`ContentLengthCheckMode::Debug`].
+    pub const fn debug() -> Self {
+        Self::new(ContentLengthCheckMode::Debug)
+    }
+
+    /// Create a new [`ContentLengthCheckLayer`] using [`ContentLengthCheckMode::Strict`].
+    pub const fn strict() -> Self {
+        Self::new(ContentLengthCheckMode::Strict)
+    }
+}
+
+impl<S> Layer<S> for ContentLengthCheckLayer {
+    type Service = ContentLengthCheck<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ContentLengthCheck::new(inner, self.mode)
+    }
+}
+
+/// Middleware that checks the consistency of the `Content-Length` of responses.
+///
+/// See the [module docs](self) for more details.
+pub struct ContentLengthCheck<S> {
+    inner: S,
+    mode: ContentLengthCheckMode,
+}
+
+impl<S> ContentLengthCheck<S> {
+    /// Create a new [`ContentLengthCheck`] using the given [`ContentLengthCheckMode`].
+    pub const fn new(inner: S, mode: ContentLengthCheckMode) -> Self {
+        Self { inner, mode }
+    }
+
+    /// Create a new [`ContentLengthCheck`] using [`ContentLengthCheckMode::Debug`].
+    pub const fn debug(inner: S) -> Self {
+        Self::new(inner, ContentLengthCheckMode::Debug)
+    }
+
+    /// Create a new [`ContentLengthCheck`] using [`ContentLengthCheckMode::Strict`].
+    pub const fn strict(inner: S) -> Self {
+        Self::new(inner, ContentLengthCheckMode::Strict)
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S: fmt::Debug> fmt::Debug for ContentLengthCheck<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContentLengthCheck")
+            .field("inner", &self.inner)
+            .field("mode", &self.mode)
+            .finish()
+    }
+}
+
+impl<S: Clone> Clone for ContentLengthCheck<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            mode: self.mode,
+        }
+    }
+}
+
+impl<State, S, ReqBody, ResBody> Service<State, Request<ReqBody>> for ContentLengthCheck<S>
+where
+    State: Clone + Send + Sync + 'static,
+    S: Service<State, Request<ReqBody>, Response = Response<ResBody>, Error: Into<BoxError>>,
+    ReqBody: Send + 'static,
+    ResBody: HttpBody + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request<ReqBody>,
+    ) -> Result<Self::Response, Self::Error> {
+        let res = self.inner.serve(ctx, req).await.map_err(Into::into)?;
+
+        let Err(err) = check_content_length(res.headers(), res.body().size_hint().exact()) else {
+            return Ok(res);
+        };
+
+        match self.mode {
+            ContentLengthCheckMode::Strict => Err(err.into()),
+            ContentLengthCheckMode::Debug => {
+                if cfg!(debug_assertions) {
+                    panic!("{err}");
+                }
+                tracing::warn!(error = %err, "inconsistent response content length");
+                Ok(res)
+            }
+        }
+    }
+}
+
+fn check_content_length(headers: &HeaderMap, body_len: Option<u64>) -> Result<(), OpaqueError> {
+    let Some(content_length) = headers.get(header::CONTENT_LENGTH) else {
+        return Ok(());
+    };
+
+    let is_chunked = headers
+        .get_all(header::TRANSFER_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|encoding| encoding.trim().eq_ignore_ascii_case("chunked"));
+    if is_chunked {
+        return Err(OpaqueError::from_display(
+            "Content-Length header set alongside chunked Transfer-Encoding",
+        ));
+    }
+
+    let content_length: u64 = content_length
+        .to_str()
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| OpaqueError::from_display("invalid Content-Length header"))?;
+
+    match body_len {
+        Some(body_len) if body_len != content_length => Err(OpaqueError::from_display(format!(
+            "Content-Length header ({content_length}) does not match body length ({body_len})"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, BodyExtractExt};
+    use rama_core::service::service_fn;
+    use std::convert::Infallible;
+
+    fn service(
+        mode: ContentLengthCheckMode,
+        content_length: Option<&'static str>,
+        transfer_encoding: Option<&'static str>,
+        streaming: bool,
+    ) -> impl Service<(), Request, Response = Response, Error = BoxError> {
+        ContentLengthCheckLayer::new(mode).layer(service_fn(move |_req: Request| async move {
+            let body = if streaming {
+                Body::from_stream(futures_lite::stream::iter([Ok::<_, Infallible>(
+                    bytes::Bytes::from_static(b"hello"),
+                )]))
+            } else {
+                Body::from("hello")
+            };
+            let mut builder = Response::builder();
+            if let Some(content_length) = content_length {
+                builder = builder.header(header::CONTENT_LENGTH, content_length);
+            }
+            if let Some(transfer_encoding) = transfer_encoding {
+                builder = builder.header(header::TRANSFER_ENCODING, transfer_encoding);
+            }
+            Ok::<_, Infallible>(builder.body(body).unwrap())
+        }))
+    }
+
+    async fn serve(
+        svc: impl Service<(), Request, Response = Response, Error = BoxError>,
+    ) -> Result<Response, BoxError> {
+        svc.serve(Context::default(), Request::new(Body::empty()))
+            .await
+    }
+
+    #[tokio::test]
+    async fn matching_content_length_passes() {
+        for (content_length, transfer_encoding, streaming) in [
+            (Some("5"), None, false),
+            (None, None, false),
+            (Some("5"), None, true),
+            (None, Some("chunked"), true),
+        ] {
+            let res = serve(service(
+                ContentLengthCheckMode::Strict,
+                content_length,
+                transfer_encoding,
+                streaming,
+            ))
+            .await
+            .unwrap();
+            assert_eq!(res.try_into_string().await.unwrap(), "hello");
+        }
+    }
+
+    #[tokio::test]
+    async fn mismatching_content_length_is_caught() {
+        let err = serve(service(
+            ContentLengthCheckMode::Strict,
+            Some("3"),
+            None,
+            false,
+        ))
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn content_length_with_chunked_is_caught() {
+        let err = serve(service(
+            ContentLengthCheckMode::Strict,
+            Some("5"),
+            Some("gzip, chunked"),
+            true,
+        ))
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("chunked"), "{err}");
+    }
+
+    #[cfg(debug_assertions)]
+    #[tokio::test]
+    #[should_panic(expected = "does not match")]
+    async fn mismatching_content_length_panics_in_debug() {
+        let _ = serve(service(
+            ContentLengthCheckMode::Debug,
+            Some("3"),
+            None,
+            false,
+        ))
+        .await;
+    }
+}
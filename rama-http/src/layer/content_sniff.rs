@@ -0,0 +1,825 @@
+//! Middleware to guard against MIME-confusion uploads,
+//! by comparing the declared `Content-Type` of a request with its sniffed content.
+//!
+//! The leading bytes of the request body are sniffed for well known
+//! magic bytes (see [`sniff_content_type`]), and the request is considered
+//! a mismatch when:
+//!
+//! - the declared type is one with known magic bytes (e.g. `image/png`),
+//!   but the body does not start with them;
+//! - the body is sniffed as active content (e.g. html, a script or an executable)
+//!   while it is declared as something else. The short `MZ` (executable) and `#!`
+//!   (script) signatures are only considered for binary declared types,
+//!   as text content can legitimately start with them;
+//! - the filename of a `Content-Disposition` header has an extension
+//!   which does not match the declared type, as guessed by [`mime_guess`].
+//!
+//! What happens on a mismatch is defined by the [`ContentSniffPolicy`]:
+//! the request is rejected with a `415 Unsupported Media Type` response,
+//! a warning is logged, or the check is skipped altogether.
+//!
+//! Requests without a `Content-Type` are sniffed as well, and are considered
+//! a mismatch when the body is sniffed as active content. Only the reliable
+//! signatures are considered for them, as the body might as well be text.
+//! A `Content-Type` which is present but cannot be parsed is rejected
+//! with a `415 Unsupported Media Type` response by the strict policy,
+//! while the warn policy logs a warning and sniffs the request as if no
+//! `Content-Type` was declared.
+//!
+//! # Multipart requests
+//!
+//! `multipart/*` requests (e.g. `multipart/form-data` uploads) are passed through
+//! as-is, without sniffing any of their parts: only the leading bytes of the body
+//! are sniffed, while the parts (and their declared types) are only known once
+//! the body is parsed. Services accepting multipart uploads should sniff each part
+//! themselves while parsing them, e.g. by passing the leading bytes of each part
+//! to [`sniff_content_type`] and comparing it with the declared type of the part.
+//!
+//! [`mime_guess`]: crate::dep::mime_guess
+//!
+//! # Example
+//!
+//! ```
+//! use rama_core::{service::service_fn, Context, Layer, Service};
+//! use rama_http::layer::content_sniff::ContentSniffLayer;
+//! use rama_http::{header::CONTENT_TYPE, Body, Request, Response, StatusCode};
+//! use std::convert::Infallible;
+//!
+//! async fn upload(_req: Request) -> Result<Response, Infallible> {
+//!     Ok(Response::new(Body::empty()))
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let service = ContentSniffLayer::strict().layer(service_fn(upload));
+//!
+//! let req = Request::builder()
+//!     .method("PUT")
+//!     .header(CONTENT_TYPE, "image/png")
+//!     .body(Body::from("#!/bin/sh\nrm -rf /"))
+//!     .unwrap();
+//! let resp = service.serve(Context::default(), req).await.unwrap();
+//! assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+//! # }
+//! ```
+
+use crate::dep::http_body::{Body as HttpBody, Frame};
+use crate::dep::http_body_util::{BodyExt, BodyStream, StreamBody};
+use crate::dep::{mime::Mime, mime_guess};
+use crate::utils::macros::{composite_http_rejection, define_http_rejection};
+use crate::{header, Body, HeaderMap, IntoResponse, Request, Response};
+use bytes::{Bytes, BytesMut};
+use futures_lite::StreamExt;
+use rama_core::{error::BoxError, Context, Layer, Service};
+use rama_utils::macros::define_inner_service_accessors;
+use std::fmt;
+
+/// The maximum amount of leading bytes of the body that are sniffed.
+const SNIFF_LEN: usize = 512;
+
+define_http_rejection! {
+    #[status = UNSUPPORTED_MEDIA_TYPE]
+    #[body = "Content does not match the declared Content-Type"]
+    /// Rejection used by the [`ContentSniff`] middleware in case the
+    /// sniffed content of a request does not match its declared `Content-Type`.
+    pub struct ContentTypeMismatch(Error);
+}
+
+define_http_rejection! {
+    #[status = UNSUPPORTED_MEDIA_TYPE]
+    #[body = "Content-Type header is invalid"]
+    /// Rejection used by the [`ContentSniff`] middleware in case
+    /// the `Content-Type` of a request is present but cannot be parsed.
+    pub struct InvalidContentType(Error);
+}
+
+define_http_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Request Body failed to be read for content sniffing"]
+    /// Rejection used by the [`ContentSniff`] middleware in case
+    /// the leading bytes of the request body could not be read.
+    pub struct FailedToSniffBody(Error);
+}
+
+composite_http_rejection! {
+    /// Rejection used by the [`ContentSniff`] middleware.
+    pub enum ContentSniffRejection {
+        ContentTypeMismatch,
+        InvalidContentType,
+        FailedToSniffBody,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The policy of the [`ContentSniff`] middleware,
+/// defining what happens when the sniffed content does not match the declared type.
+pub enum ContentSniffPolicy {
+    #[default]
+    /// Reject the request with a `415 Unsupported Media Type` response.
+    Strict,
+    /// Log a warning, but let the request pass.
+    Warn,
+    /// Do not sniff the request at all.
+    Allow,
+}
+
+/// Layer that applies [`ContentSniff`], which checks that the
+/// sniffed content of requests matches their declared `Content-Type`.
+///
+/// See the [module docs](self) for more details.
+#[derive(Debug, Clone, Default)]
+pub struct ContentSniffLayer {
+    policy: ContentSniffPolicy,
+}
+
+impl ContentSniffLayer {
+    /// Create a new [`ContentSniffLayer`] using the given [`ContentSniffPolicy`].
+    pub const fn new(policy: ContentSniffPolicy) -> Self {
+        Self { policy }
+    }
+
+    /// Create a new [`ContentSniffLayer`] using [`ContentSniffPolicy::Strict`].
+    pub const fn strict() -> Self {
+        Self::new(ContentSniffPolicy::Strict)
+    }
+
+    /// Create a new [`ContentSniffLayer`] using [`ContentSniffPolicy::Warn`].
+    pub const fn warn() -> Self {
+        Self::new(ContentSniffPolicy::Warn)
+    }
+
+    /// Create a new [`ContentSniffLayer`] using [`ContentSniffPolicy::Allow`].
+    pub const fn allow() -> Self {
+        Self::new(ContentSniffPolicy::Allow)
+    }
+}
+
+impl<S> Layer<S> for ContentSniffLayer {
+    type Service = ContentSniff<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ContentSniff::new(inner, self.policy)
+    }
+}
+
+/// Middleware that checks that the sniffed content of requests
+/// matches their declared `Content-Type`.
+///
+/// See the [module docs](self) for more details.
+pub struct ContentSniff<S> {
+    inner: S,
+    policy: ContentSniffPolicy,
+}
+
+impl<S> ContentSniff<S> {
+    /// Create a new [`ContentSniff`] using the given [`ContentSniffPolicy`].
+    pub const fn new(inner: S, policy: ContentSniffPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    /// Create a new [`ContentSniff`] using [`ContentSniffPolicy::Strict`].
+    pub const fn strict(inner: S) -> Self {
+        Self::new(inner, ContentSniffPolicy::Strict)
+    }
+
+    /// Create a new [`ContentSniff`] using [`ContentSniffPolicy::Warn`].
+    pub const fn warn(inner: S) -> Self {
+        Self::new(inner, ContentSniffPolicy::Warn)
+    }
+
+    /// Create a new [`ContentSniff`] using [`ContentSniffPolicy::Allow`].
+    pub const fn allow(inner: S) -> Self {
+        Self::new(inner, ContentSniffPolicy::Allow)
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S: fmt::Debug> fmt::Debug for ContentSniff<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContentSniff")
+            .field("inner", &self.inner)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl<S: Clone> Clone for ContentSniff<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            policy: self.policy,
+        }
+    }
+}
+
+impl<State, S, ReqBody, ResBody> Service<State, Request<ReqBody>> for ContentSniff<S>
+where
+    State: Clone + Send + Sync + 'static,
+    S: Service<State, Request, Response = Response<ResBody>>,
+    ReqBody: HttpBody<Data = Bytes, Error: Into<BoxError>> + Send + Sync + 'static,
+    ResBody: HttpBody<Data = Bytes, Error: Into<BoxError>> + Send + Sync + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request<ReqBody>,
+    ) -> Result<Self::Response, Self::Error> {
+        let declared = match self.policy {
+            ContentSniffPolicy::Allow => None,
+            _ => match declared_content_type(req.headers()) {
+                Ok(declared) => declared,
+                Err(reason) => match self.policy {
+                    ContentSniffPolicy::Strict => {
+                        return Ok(InvalidContentType::from_display(reason).into_response());
+                    }
+                    _ => {
+                        tracing::warn!("content sniff: {reason}");
+                        Some(Declared::Missing)
+                    }
+                },
+            },
+        };
+        let declared = match declared {
+            // nothing to sniff
+            Some(Declared::Missing) if req.body().is_end_stream() => None,
+            declared => declared,
+        };
+        let Some(declared) = declared else {
+            let res = self.inner.serve(ctx, req.map(Body::new)).await?;
+            return Ok(res.map(Body::new));
+        };
+
+        let (parts, body) = req.into_parts();
+        let mut body = Body::new(body);
+        let (prefix, trailers) = match read_prefix(&mut body).await {
+            Ok(read) => read,
+            Err(err) => return Ok(FailedToSniffBody::from_display(err).into_response()),
+        };
+
+        let checked = match &declared {
+            Declared::Mime(declared) => check_content_type(declared, &prefix, &parts.headers),
+            Declared::Missing => check_undeclared_content(&prefix),
+        };
+        if let Err(reason) = checked {
+            match self.policy {
+                ContentSniffPolicy::Strict => {
+                    return Ok(ContentTypeMismatch::from_display(reason).into_response());
+                }
+                _ => tracing::warn!("content sniff: {reason}"),
+            }
+        }
+
+        // re-assemble the body from the frames read so far and the remainder of the body
+        let read = [Frame::data(prefix)]
+            .into_iter()
+            .chain(trailers.map(Frame::trailers))
+            .map(Ok);
+        let body = Body::new(StreamBody::new(
+            futures_lite::stream::iter(read).chain(BodyStream::new(body)),
+        ));
+        let res = self
+            .inner
+            .serve(ctx, Request::from_parts(parts, body))
+            .await?;
+        Ok(res.map(Body::new))
+    }
+}
+
+/// The declared type of a request, to check its sniffed content against.
+enum Declared {
+    Mime(Mime),
+    Missing,
+}
+
+/// Returns the declared type of the request, `None` if the request is not to
+/// be sniffed, or the reason why the declared `Content-Type` is invalid.
+fn declared_content_type(headers: &HeaderMap) -> Result<Option<Declared>, String> {
+    let Some(value) = headers.get(header::CONTENT_TYPE) else {
+        return Ok(Some(Declared::Missing));
+    };
+    let mime: Mime = value
+        .to_str()
+        .map_err(|err| format!("invalid Content-Type: {err}"))?
+        .parse()
+        .map_err(|err| format!("invalid Content-Type {value:?}: {err}"))?;
+    if mime.type_() == mime::MULTIPART {
+        // parts are not sniffed, see the module docs
+        tracing::trace!(content_type = %mime, "content sniff: skip multipart request");
+        return Ok(None);
+    }
+    Ok(Some(Declared::Mime(mime)))
+}
+
+/// Read the leading bytes of the body to be sniffed,
+/// together with its trailers in case the body ended while reading.
+async fn read_prefix(body: &mut Body) -> Result<(Bytes, Option<HeaderMap>), BoxError> {
+    let mut prefix = BytesMut::new();
+    while prefix.len() < SNIFF_LEN {
+        match body.frame().await {
+            Some(frame) => match frame?.into_data() {
+                Ok(data) => prefix.extend_from_slice(&data),
+                Err(frame) => {
+                    if let Ok(trailers) = frame.into_trailers() {
+                        return Ok((prefix.freeze(), Some(trailers)));
+                    }
+                }
+            },
+            None => break,
+        }
+    }
+    Ok((prefix.freeze(), None))
+}
+
+fn check_content_type(declared: &Mime, prefix: &[u8], headers: &HeaderMap) -> Result<(), String> {
+    let declared = canonical_essence(declared.essence_str());
+    let binary = is_binary(&declared);
+    let sniffed = sniff(prefix, binary);
+
+    match sniffed {
+        Some(sniffed)
+            if !is_compatible(&declared, sniffed)
+                && (is_active_content(sniffed) || has_signature(&declared, binary)) =>
+        {
+            return Err(format!("declared as {declared}, sniffed as {sniffed}"));
+        }
+        // an empty body has no content to mismatch the declared type
+        None if !prefix.is_empty() && has_signature(&declared, binary) => {
+            return Err(format!(
+                "declared as {declared}, but without matching signature"
+            ));
+        }
+        _ => (),
+    }
+
+    if let Some(filename) = filename(headers) {
+        let guess = mime_guess::from_path(filename);
+        if !guess.is_empty()
+            && !guess
+                .iter()
+                .any(|mime| is_compatible(&declared, &canonical_essence(mime.essence_str())))
+        {
+            return Err(format!(
+                "declared as {declared}, but filename is {filename}"
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_undeclared_content(prefix: &[u8]) -> Result<(), String> {
+    match sniff(prefix, false) {
+        Some(sniffed) if is_active_content(sniffed) => {
+            Err(format!("no Content-Type declared, sniffed as {sniffed}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Sniff the content type of the given leading bytes of a body,
+/// returning `None` if the content is not recognised.
+///
+/// Recognised are common image, audio, document and archive formats,
+/// as well as active content such as html, scripts and executables.
+///
+/// This includes the short `MZ` (executable) and `#!` (script) signatures,
+/// which text content can legitimately start with as well.
+pub fn sniff_content_type(bytes: &[u8]) -> Option<Mime> {
+    sniff(bytes, true).and_then(|essence| essence.parse().ok())
+}
+
+/// Magic bytes of content types, matched at the start of the content.
+const SIGNATURES: &[(&str, &[u8])] = &[
+    ("image/png", b"\x89PNG\r\n\x1a\n"),
+    ("image/jpeg", b"\xff\xd8\xff"),
+    ("image/gif", b"GIF87a"),
+    ("image/gif", b"GIF89a"),
+    ("image/bmp", b"BM"),
+    ("image/x-icon", b"\x00\x00\x01\x00"),
+    ("image/tiff", b"II*\x00"),
+    ("image/tiff", b"MM\x00*"),
+    ("application/pdf", b"%PDF-"),
+    ("application/zip", b"PK\x03\x04"),
+    ("application/gzip", b"\x1f\x8b\x08"),
+    ("audio/mpeg", b"ID3"),
+    // mpeg-1 layer 3 and mpeg-2 layer 3 frame sync, without ID3 tag
+    ("audio/mpeg", b"\xff\xfb"),
+    ("audio/mpeg", b"\xff\xf3"),
+    ("audio/mpeg", b"\xff\xf2"),
+    ("audio/ogg", b"OggS"),
+    ("application/x-executable", b"\x7fELF"),
+];
+
+/// Magic bytes of active content, which are too short to be reliable
+/// for text content, and are thus only matched for binary declared types.
+const BINARY_ONLY_SIGNATURES: &[(&str, &[u8])] = &[
+    ("application/x-msdownload", b"MZ"),
+    ("application/x-sh", b"#!"),
+];
+
+/// Markers of active (text) content, matched case-insensitive
+/// at the start of the content, after leading whitespace.
+const TEXT_MARKERS: &[(&str, &[u8])] = &[
+    ("text/html", b"<!doctype html"),
+    ("text/html", b"<html"),
+    ("text/html", b"<head"),
+    ("text/html", b"<body"),
+    ("text/html", b"<script"),
+    ("text/html", b"<iframe"),
+    ("application/x-httpd-php", b"<?php"),
+    ("image/svg+xml", b"<svg"),
+];
+
+fn sniff(bytes: &[u8], binary: bool) -> Option<&'static str> {
+    let binary_only = if binary { BINARY_ONLY_SIGNATURES } else { &[] };
+    if let Some((essence, _)) = SIGNATURES
+        .iter()
+        .chain(binary_only)
+        .find(|(_, magic)| bytes.starts_with(magic))
+    {
+        return Some(essence);
+    }
+
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" {
+        match &bytes[8..12] {
+            b"WEBP" => return Some("image/webp"),
+            b"WAVE" => return Some("audio/wav"),
+            _ => (),
+        }
+    }
+
+    let text = bytes.trim_ascii_start();
+    TEXT_MARKERS
+        .iter()
+        .find(|(_, marker)| {
+            text.get(..marker.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(marker))
+        })
+        .map(|(essence, _)| *essence)
+}
+
+fn is_active_content(essence: &str) -> bool {
+    matches!(
+        essence,
+        "text/html"
+            | "application/x-httpd-php"
+            | "application/x-msdownload"
+            | "application/x-executable"
+            | "application/x-sh"
+    )
+}
+
+/// Returns `true` if [`sniff`] recognises the given type,
+/// using the same signatures as [`sniff`] for the given `binary` flag.
+fn has_signature(essence: &str, binary: bool) -> bool {
+    let binary_only = if binary { BINARY_ONLY_SIGNATURES } else { &[] };
+    SIGNATURES
+        .iter()
+        .chain(binary_only)
+        .any(|(known, _)| *known == essence)
+        || matches!(essence, "image/webp" | "audio/wav")
+}
+
+/// Returns `true` if the declared type is not a text based type.
+fn is_binary(essence: &str) -> bool {
+    !(essence.starts_with("text/")
+        || essence.ends_with("+json")
+        || essence.ends_with("+xml")
+        || matches!(
+            essence,
+            "application/json"
+                | "application/xml"
+                | "application/javascript"
+                | "application/ecmascript"
+                | "application/x-www-form-urlencoded"
+                | "application/x-sh"
+                | "image/svg+xml"
+        ))
+}
+
+fn is_compatible(declared: &str, sniffed: &str) -> bool {
+    declared == sniffed
+        || (sniffed == "application/zip"
+            && (declared.ends_with("+zip")
+                || declared.starts_with("application/vnd.openxmlformats-officedocument.")
+                || declared.starts_with("application/vnd.oasis.opendocument.")
+                || matches!(
+                    declared,
+                    "application/java-archive" | "application/epub+zip"
+                )))
+        || (sniffed == "application/gzip" && declared == "application/x-tar")
+}
+
+/// Normalise aliases of the same content type.
+fn canonical_essence(essence: &str) -> String {
+    match essence {
+        "image/jpg" | "image/pjpeg" => "image/jpeg",
+        "image/vnd.microsoft.icon" => "image/x-icon",
+        "application/x-gzip" => "application/gzip",
+        "application/x-zip-compressed" => "application/zip",
+        "audio/x-wav" | "audio/wave" | "audio/vnd.wave" => "audio/wav",
+        "audio/mp3" => "audio/mpeg",
+        other => other,
+    }
+    .to_owned()
+}
+
+fn filename(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::CONTENT_DISPOSITION)?.to_str().ok()?;
+    value.split(';').find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("filename")
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StatusCode;
+    use rama_core::service::service_fn;
+    use std::convert::Infallible;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR\x00\x00\x00\x01\x00\x00\x00\x01";
+
+    fn service(
+        policy: ContentSniffPolicy,
+    ) -> impl Service<(), Request, Response = Response, Error = Infallible> {
+        ContentSniffLayer::new(policy).layer(service_fn(|req: Request| async move {
+            // the sniffed body is passed on as a whole
+            let body = req.into_body().collect().await.unwrap().to_bytes();
+            Ok::<_, Infallible>(Response::new(Body::from(body)))
+        }))
+    }
+
+    fn request(content_type: &str, body: &'static [u8]) -> Request {
+        Request::builder()
+            .method("PUT")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    async fn serve(policy: ContentSniffPolicy, req: Request) -> (StatusCode, Bytes) {
+        let res = service(policy)
+            .serve(Context::default(), req)
+            .await
+            .unwrap();
+        let status = res.status();
+        (status, res.into_body().collect().await.unwrap().to_bytes())
+    }
+
+    #[test]
+    fn test_sniff_content_type() {
+        assert_eq!(sniff_content_type(PNG), Some(mime::IMAGE_PNG));
+        assert_eq!(
+            sniff_content_type(b"  \n<!DOCTYPE html><html></html>"),
+            Some(mime::TEXT_HTML)
+        );
+        assert_eq!(
+            sniff_content_type(b"RIFF\x00\x00\x00\x00WEBPVP8 ").map(|m| m.to_string()),
+            Some("image/webp".to_owned())
+        );
+        assert_eq!(sniff_content_type(b"hello world"), None);
+    }
+
+    #[tokio::test]
+    async fn test_png_declared_as_png_passes() {
+        let (status, body) = serve(ContentSniffPolicy::Strict, request("image/png", PNG)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, PNG);
+    }
+
+    #[tokio::test]
+    async fn test_script_declared_as_image_is_rejected() {
+        for content_type in ["image/png", "image/jpeg", "application/octet-stream"] {
+            let req = request(content_type, b"#!/bin/sh\necho pwned");
+            let (status, _) = serve(ContentSniffPolicy::Strict, req).await;
+            assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE, "{content_type}");
+        }
+
+        let req = request("application/octet-stream", b"MZ\x90\x00\x03\x00");
+        let (status, _) = serve(ContentSniffPolicy::Strict, req).await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let req = request("image/gif", b"<script>alert(1)</script>");
+        let (status, _) = serve(ContentSniffPolicy::Strict, req).await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        // warn and allow let the request pass
+        for policy in [ContentSniffPolicy::Warn, ContentSniffPolicy::Allow] {
+            let req = request("image/png", b"#!/bin/sh\necho pwned");
+            let (status, body) = serve(policy, req).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body, "#!/bin/sh\necho pwned");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_short_signatures_in_text_pass() {
+        for (content_type, body) in [
+            ("text/plain", &b"#!/bin/sh is a shebang"[..]),
+            ("text/markdown", b"#!important"),
+            ("text/csv", b"MZ,Mozambique\n"),
+            ("application/json", b"MZ"),
+        ] {
+            let (status, _) = serve(ContentSniffPolicy::Strict, request(content_type, body)).await;
+            assert_eq!(status, StatusCode::OK, "{content_type}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shell_script_declared_as_shell_script_passes() {
+        let body = b"#!/bin/sh\necho hello";
+        let (status, received) = serve(
+            ContentSniffPolicy::Strict,
+            request("application/x-sh", body),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(received, &body[..]);
+    }
+
+    #[tokio::test]
+    async fn test_empty_body_declared_with_signature_passes() {
+        for content_type in ["image/png", "application/pdf", "application/x-msdownload"] {
+            let (status, body) =
+                serve(ContentSniffPolicy::Strict, request(content_type, b"")).await;
+            assert_eq!(status, StatusCode::OK, "{content_type}");
+            assert!(body.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mp3_without_id3_tag_passes() {
+        for body in [
+            &b"\xff\xfb\x90\x44\x00"[..],
+            b"\xff\xf3\x90\x44\x00",
+            b"\xff\xf2\x90\x44\x00",
+            b"ID3\x04\x00",
+        ] {
+            assert_eq!(
+                sniff_content_type(body).unwrap().essence_str(),
+                "audio/mpeg"
+            );
+            let (status, _) = serve(ContentSniffPolicy::Strict, request("audio/mpeg", body)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        let (status, _) = serve(
+            ContentSniffPolicy::Strict,
+            request("audio/mpeg", b"\xff\xd8\xff\xe0"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_multipart_is_not_sniffed() {
+        let req = request(
+            "multipart/form-data; boundary=x",
+            b"--x\r\ncontent-type: image/png\r\n\r\n#!/bin/sh\r\n--x--\r\n",
+        );
+        let (status, _) = serve(ContentSniffPolicy::Strict, req).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_trailers_are_preserved() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("x-checksum", header::HeaderValue::from_static("abc"));
+
+        // echo the received body, including its trailers
+        let service = ContentSniffLayer::strict().layer(service_fn(|req: Request| async move {
+            let collected = req.into_body().collect().await.unwrap();
+            let trailers = collected.trailers().cloned().unwrap_or_default();
+            let body = Body::from(collected.to_bytes()).with_trailers(trailers);
+            Ok::<_, Infallible>(Response::new(body))
+        }));
+        let serve = |req| async {
+            let res = service.serve(Context::default(), req).await.unwrap();
+            let collected = res.into_body().collect().await.unwrap();
+            (collected.trailers().cloned(), collected.to_bytes())
+        };
+
+        // the body ends while reading the prefix
+        let req = Request::builder()
+            .method("PUT")
+            .header(header::CONTENT_TYPE, "image/png")
+            .body(Body::from(PNG).with_trailers(trailers.clone()))
+            .unwrap();
+        let (received, body) = serve(req).await;
+        assert_eq!(body, PNG);
+        assert_eq!(received, Some(trailers.clone()));
+
+        // the body is longer than the prefix
+        let mut large = PNG.to_vec();
+        large.resize(SNIFF_LEN * 3, 0);
+        let req = Request::builder()
+            .method("PUT")
+            .header(header::CONTENT_TYPE, "image/png")
+            .body(
+                Body::from_stream(futures_lite::stream::iter(
+                    large
+                        .chunks(100)
+                        .map(|chunk| Ok::<_, Infallible>(Bytes::copy_from_slice(chunk)))
+                        .collect::<Vec<_>>(),
+                ))
+                .with_trailers(trailers.clone()),
+            )
+            .unwrap();
+        let (received, body) = serve(req).await;
+        assert_eq!(body, large);
+        assert_eq!(received, Some(trailers));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_content_passes() {
+        let (status, _) = serve(
+            ContentSniffPolicy::Strict,
+            request("text/csv", b"a,b\n1,2\n"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let req = request(
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            b"PK\x03\x04rest",
+        );
+        let (status, _) = serve(ContentSniffPolicy::Strict, req).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_content_type() {
+        for content_type in ["not a mime", "png", ""] {
+            let (status, _) = serve(ContentSniffPolicy::Strict, request(content_type, PNG)).await;
+            assert_eq!(
+                status,
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "{content_type:?}"
+            );
+        }
+
+        // warn sniffs the request as if no content type was declared
+        let (status, body) = serve(ContentSniffPolicy::Warn, request("not a mime", PNG)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, PNG);
+
+        let (status, _) = serve(ContentSniffPolicy::Allow, request("not a mime", PNG)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_missing_content_type_is_sniffed() {
+        let undeclared = |body: &'static [u8]| {
+            Request::builder()
+                .method("PUT")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        for body in [
+            &b"<html><script>alert(1)</script></html>"[..],
+            b"\x7fELF\x02\x01\x01",
+            b"  <?php system($_GET['cmd']); ?>",
+        ] {
+            let (status, _) = serve(ContentSniffPolicy::Strict, undeclared(body)).await;
+            assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+            let (status, _) = serve(ContentSniffPolicy::Warn, undeclared(body)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        // inactive or unknown content, as well as the short signatures, pass
+        for body in [PNG, b"hello world", b"#!important", b"MZ,Mozambique\n", b""] {
+            let (status, received) = serve(ContentSniffPolicy::Strict, undeclared(body)).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(received, body);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filename_extension_mismatch_is_rejected() {
+        let mut req = request("image/png", PNG);
+        req.headers_mut().insert(
+            header::CONTENT_DISPOSITION,
+            header::HeaderValue::from_static(r#"attachment; filename="avatar.png""#),
+        );
+        let (status, _) = serve(ContentSniffPolicy::Strict, req).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let mut req = request("image/png", PNG);
+        req.headers_mut().insert(
+            header::CONTENT_DISPOSITION,
+            header::HeaderValue::from_static(r#"attachment; filename="avatar.php""#),
+        );
+        let (status, _) = serve(ContentSniffPolicy::Strict, req).await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+}
@@ -15,7 +15,7 @@ use bytes::{Buf, Bytes};
 use futures_lite::ready;
 use pin_project_lite::pin_project;
 use std::task::Context;
-use std::{io, marker::PhantomData, pin::Pin, task::Poll};
+use std::{fmt, io, marker::PhantomData, pin::Pin, task::Poll};
 use tokio_util::io::StreamReader;
 
 pin_project! {
@@ -29,6 +29,9 @@ pin_project! {
     {
         #[pin]
         pub(crate) inner: BodyInner<B>,
+        limits: DecompressionLimits,
+        decompressed_len: usize,
+        limit_exceeded: bool,
     }
 }
 
@@ -37,11 +40,9 @@ where
     B: Body + Default,
 {
     fn default() -> Self {
-        Self {
-            inner: BodyInner::Identity {
-                inner: B::default(),
-            },
-        }
+        Self::new(BodyInner::Identity {
+            inner: B::default(),
+        })
     }
 }
 
@@ -50,10 +51,84 @@ where
     B: Body,
 {
     pub(crate) fn new(inner: BodyInner<B>) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            limits: DecompressionLimits::default(),
+            decompressed_len: 0,
+            limit_exceeded: false,
+        }
+    }
+
+    pub(crate) fn with_limits(mut self, limits: DecompressionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// Limits applied by [`DecompressionBody`] on the decompressed data,
+/// guarding against compression bombs.
+pub(crate) struct DecompressionLimits {
+    pub(crate) max_size: Option<usize>,
+    pub(crate) max_ratio: Option<u32>,
+}
+
+impl DecompressionLimits {
+    fn check(&self, decompressed: usize, compressed: usize) -> Result<(), DecompressionLimitError> {
+        if let Some(limit) = self.max_size {
+            if decompressed > limit {
+                return Err(DecompressionLimitError::MaxSizeExceeded { limit });
+            }
+        }
+        if let Some(limit) = self.max_ratio {
+            if decompressed > compressed.saturating_mul(limit as usize) {
+                return Err(DecompressionLimitError::MaxRatioExceeded { limit });
+            }
+        }
+        Ok(())
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+/// Error returned by a [`DecompressionBody`] when the decompressed data
+/// exceeds one of the configured limits, aborting the decompression.
+pub enum DecompressionLimitError {
+    /// The decompressed data exceeds the maximum size (in bytes).
+    MaxSizeExceeded {
+        /// Maximum size of the decompressed data.
+        limit: usize,
+    },
+    /// The ratio of the decompressed over the compressed data exceeds the maximum ratio.
+    MaxRatioExceeded {
+        /// Maximum compression ratio.
+        limit: u32,
+    },
+}
+
+impl fmt::Display for DecompressionLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxSizeExceeded { limit } => {
+                write!(f, "decompressed body exceeds maximum size of {limit} bytes")
+            }
+            Self::MaxRatioExceeded { limit } => {
+                write!(
+                    f,
+                    "decompressed body exceeds maximum compression ratio of {limit}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecompressionLimitError {}
+
+/// Amount of compressed bytes read so far from the body wrapped by a decoder.
+fn compressed_len<B: Body>(read: &AsyncReadBody<B>) -> usize {
+    read.get_ref().get_ref().data_len()
+}
+
 type GzipBody<B> = WrapBody<GzipDecoder<B>>;
 type DeflateBody<B> = WrapBody<ZlibDecoder<B>>;
 type BrotliBody<B> = WrapBody<BrotliDecoder<B>>;
@@ -121,20 +196,51 @@ where
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
-        match self.project().inner.project() {
-            BodyInnerProj::Gzip { inner } => inner.poll_frame(cx),
-            BodyInnerProj::Deflate { inner } => inner.poll_frame(cx),
-            BodyInnerProj::Brotli { inner } => inner.poll_frame(cx),
-            BodyInnerProj::Zstd { inner } => inner.poll_frame(cx),
-            BodyInnerProj::Identity { inner } => match ready!(inner.poll_frame(cx)) {
-                Some(Ok(frame)) => {
-                    let frame = frame.map_data(|mut buf| buf.copy_to_bytes(buf.remaining()));
-                    Poll::Ready(Some(Ok(frame)))
+        let this = self.project();
+        if *this.limit_exceeded {
+            return Poll::Ready(None);
+        }
+
+        let (result, compressed) = match this.inner.project() {
+            BodyInnerProj::Gzip { mut inner } => {
+                let result = ready!(inner.as_mut().poll_frame(cx));
+                (result, compressed_len(inner.read.get_ref()))
+            }
+            BodyInnerProj::Deflate { mut inner } => {
+                let result = ready!(inner.as_mut().poll_frame(cx));
+                (result, compressed_len(inner.read.get_ref()))
+            }
+            BodyInnerProj::Brotli { mut inner } => {
+                let result = ready!(inner.as_mut().poll_frame(cx));
+                (result, compressed_len(inner.read.get_ref()))
+            }
+            BodyInnerProj::Zstd { mut inner } => {
+                let result = ready!(inner.as_mut().poll_frame(cx));
+                (result, compressed_len(inner.read.get_ref()))
+            }
+            BodyInnerProj::Identity { inner } => {
+                return match ready!(inner.poll_frame(cx)) {
+                    Some(Ok(frame)) => {
+                        let frame = frame.map_data(|mut buf| buf.copy_to_bytes(buf.remaining()));
+                        Poll::Ready(Some(Ok(frame)))
+                    }
+                    Some(Err(err)) => Poll::Ready(Some(Err(err.into()))),
+                    None => Poll::Ready(None),
                 }
-                Some(Err(err)) => Poll::Ready(Some(Err(err.into()))),
-                None => Poll::Ready(None),
-            },
+            }
+        };
+
+        if let Some(Ok(frame)) = &result {
+            if let Some(data) = frame.data_ref() {
+                *this.decompressed_len = this.decompressed_len.saturating_add(data.len());
+            }
+            if let Err(err) = this.limits.check(*this.decompressed_len, compressed) {
+                *this.limit_exceeded = true;
+                return Poll::Ready(Some(Err(err.into())));
+            }
         }
+
+        Poll::Ready(result)
     }
 
     fn size_hint(&self) -> SizeHint {
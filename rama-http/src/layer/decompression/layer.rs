@@ -1,4 +1,4 @@
-use super::Decompression;
+use super::{body::DecompressionLimits, Decompression};
 use crate::layer::util::compression::AcceptEncoding;
 use rama_core::Layer;
 
@@ -11,6 +11,7 @@ use rama_core::Layer;
 #[derive(Debug, Default, Clone)]
 pub struct DecompressionLayer {
     accept: AcceptEncoding,
+    limits: DecompressionLimits,
 }
 
 impl<S> Layer<S> for DecompressionLayer {
@@ -20,6 +21,7 @@ impl<S> Layer<S> for DecompressionLayer {
         Decompression {
             inner: service,
             accept: self.accept,
+            limits: self.limits,
         }
     }
 }
@@ -77,4 +79,36 @@ impl DecompressionLayer {
         self.accept.set_zstd(enable);
         self
     }
+
+    /// Sets the maximum size (in bytes) of the decompressed response body.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn max_decompressed_size(mut self, limit: usize) -> Self {
+        self.limits.max_size = Some(limit);
+        self
+    }
+
+    /// Sets the maximum size (in bytes) of the decompressed response body.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn set_max_decompressed_size(&mut self, limit: usize) -> &mut Self {
+        self.limits.max_size = Some(limit);
+        self
+    }
+
+    /// Sets the maximum ratio of the decompressed over the compressed response body size.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn max_compression_ratio(mut self, ratio: u32) -> Self {
+        self.limits.max_ratio = Some(ratio);
+        self
+    }
+
+    /// Sets the maximum ratio of the decompressed over the compressed response body size.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn set_max_compression_ratio(&mut self, ratio: u32) -> &mut Self {
+        self.limits.max_ratio = Some(ratio);
+        self
+    }
 }
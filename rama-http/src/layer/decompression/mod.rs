@@ -94,6 +94,20 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! # Limits
+//!
+//! A small compressed body can decompress into an enormous amount of data
+//! (a so called compression bomb). To guard against this, all decompression
+//! services and layers can limit the decompressed body:
+//!
+//! - by a maximum decompressed size (in bytes);
+//! - by a maximum compression ratio, being the decompressed size over
+//!   the amount of compressed bytes read so far.
+//!
+//! Both limits are checked while the body is being decompressed.
+//! Once either is exceeded the [`DecompressionBody`] aborts with a
+//! [`DecompressionLimitError`]. By default neither limit is applied.
 
 mod request;
 
@@ -102,7 +116,11 @@ mod layer;
 mod service;
 
 #[doc(inline)]
-pub use self::{body::DecompressionBody, layer::DecompressionLayer, service::Decompression};
+pub use self::{
+    body::{DecompressionBody, DecompressionLimitError},
+    layer::DecompressionLayer,
+    service::Decompression,
+};
 
 #[doc(inline)]
 pub use self::request::layer::RequestDecompressionLayer;
@@ -182,4 +200,53 @@ mod tests {
             .insert("content-encoding", "gzip".parse().unwrap());
         Ok(res)
     }
+
+    #[tokio::test]
+    async fn abort_decompression_bomb() {
+        for (client, expected) in [
+            (
+                Decompression::new(service_fn(handle_gzip_bomb)).max_decompressed_size(64 * 1024),
+                DecompressionLimitError::MaxSizeExceeded { limit: 64 * 1024 },
+            ),
+            (
+                Decompression::new(service_fn(handle_gzip_bomb)).max_compression_ratio(100),
+                DecompressionLimitError::MaxRatioExceeded { limit: 100 },
+            ),
+        ] {
+            let req = Request::new(Body::empty());
+            let res = client.serve(Context::default(), req).await.unwrap();
+
+            let err = res.into_body().collect().await.unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<DecompressionLimitError>(),
+                Some(&expected)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn decompress_within_limits() {
+        let client = Decompression::new(Compression::new(service_fn(handle)))
+            .max_decompressed_size(1024)
+            .max_compression_ratio(10);
+
+        let req = Request::builder()
+            .header("accept-encoding", "gzip")
+            .body(Body::empty())
+            .unwrap();
+        let res = client.serve(Context::default(), req).await.unwrap();
+
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "Hello, World!");
+    }
+
+    async fn handle_gzip_bomb(_req: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(&vec![0; 16 * 1024 * 1024]).unwrap();
+
+        let mut res = Response::new(Body::from(encoder.finish().unwrap()));
+        res.headers_mut()
+            .insert("content-encoding", "gzip".parse().unwrap());
+        Ok(res)
+    }
 }
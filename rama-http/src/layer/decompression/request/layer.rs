@@ -1,4 +1,5 @@
 use super::service::RequestDecompression;
+use crate::layer::decompression::body::DecompressionLimits;
 use crate::layer::util::compression::AcceptEncoding;
 use rama_core::Layer;
 
@@ -17,6 +18,7 @@ use rama_core::Layer;
 pub struct RequestDecompressionLayer {
     accept: AcceptEncoding,
     pass_through_unaccepted: bool,
+    limits: DecompressionLimits,
 }
 
 impl<S> Layer<S> for RequestDecompressionLayer {
@@ -27,6 +29,7 @@ impl<S> Layer<S> for RequestDecompressionLayer {
             inner: service,
             accept: self.accept,
             pass_through_unaccepted: self.pass_through_unaccepted,
+            limits: self.limits,
         }
     }
 }
@@ -96,4 +99,36 @@ impl RequestDecompressionLayer {
         self.pass_through_unaccepted = enable;
         self
     }
+
+    /// Sets the maximum size (in bytes) of the decompressed request body.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn max_decompressed_size(mut self, limit: usize) -> Self {
+        self.limits.max_size = Some(limit);
+        self
+    }
+
+    /// Sets the maximum size (in bytes) of the decompressed request body.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn set_max_decompressed_size(&mut self, limit: usize) -> &mut Self {
+        self.limits.max_size = Some(limit);
+        self
+    }
+
+    /// Sets the maximum ratio of the decompressed over the compressed request body size.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn max_compression_ratio(mut self, ratio: u32) -> Self {
+        self.limits.max_ratio = Some(ratio);
+        self
+    }
+
+    /// Sets the maximum ratio of the decompressed over the compressed request body size.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn set_max_compression_ratio(&mut self, ratio: u32) -> &mut Self {
+        self.limits.max_ratio = Some(ratio);
+        self
+    }
 }
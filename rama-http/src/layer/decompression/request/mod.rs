@@ -6,7 +6,7 @@ mod tests {
     use super::service::RequestDecompression;
 
     use crate::dep::http_body_util::BodyExt;
-    use crate::layer::decompression::DecompressionBody;
+    use crate::layer::decompression::{DecompressionBody, DecompressionLimitError};
     use crate::{header, Body, Request, Response, StatusCode};
     use rama_core::service::service_fn;
     use rama_core::{Context, Service};
@@ -45,6 +45,49 @@ mod tests {
         let _ = svc.serve(Context::default(), req).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn abort_decompression_exceeding_max_size() {
+        let req = request_gzip_bomb();
+        let svc = RequestDecompression::new(service_fn(
+            |req: Request<DecompressionBody<Body>>| async move {
+                let err = req.into_body().collect().await.unwrap_err();
+                assert_eq!(
+                    err.downcast_ref::<DecompressionLimitError>(),
+                    Some(&DecompressionLimitError::MaxSizeExceeded { limit: 64 * 1024 })
+                );
+                Ok::<_, Infallible>(Response::new(Body::empty()))
+            },
+        ))
+        .max_decompressed_size(64 * 1024);
+        let _ = svc.serve(Context::default(), req).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn abort_decompression_exceeding_max_ratio() {
+        let req = request_gzip_bomb();
+        let svc = RequestDecompression::new(service_fn(
+            |req: Request<DecompressionBody<Body>>| async move {
+                let err = req.into_body().collect().await.unwrap_err();
+                assert_eq!(
+                    err.downcast_ref::<DecompressionLimitError>(),
+                    Some(&DecompressionLimitError::MaxRatioExceeded { limit: 100 })
+                );
+                Ok::<_, Infallible>(Response::new(Body::empty()))
+            },
+        ))
+        .max_compression_ratio(100);
+        let _ = svc.serve(Context::default(), req).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn decompress_within_limits() {
+        let req = request_gzip();
+        let svc = RequestDecompression::new(service_fn(assert_request_is_decompressed))
+            .max_decompressed_size(1024)
+            .max_compression_ratio(10);
+        let _ = svc.serve(Context::default(), req).await.unwrap();
+    }
+
     async fn assert_request_is_decompressed(
         req: Request<DecompressionBody<Body>>,
     ) -> Result<Response<Body>, Infallible> {
@@ -85,6 +128,16 @@ mod tests {
             .unwrap()
     }
 
+    fn request_gzip_bomb() -> Request<Body> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&vec![0; 16 * 1024 * 1024]).unwrap();
+        let body = encoder.finish().unwrap();
+        Request::builder()
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
     async fn read_body(body: &mut DecompressionBody<Body>) -> Vec<u8> {
         body.collect().await.unwrap().to_bytes().to_vec()
     }
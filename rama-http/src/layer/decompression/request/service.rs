@@ -3,7 +3,7 @@ use std::fmt;
 use crate::dep::http_body::Body;
 use crate::dep::http_body_util::{combinators::UnsyncBoxBody, BodyExt, Empty};
 use crate::layer::{
-    decompression::body::BodyInner,
+    decompression::body::{BodyInner, DecompressionLimits},
     decompression::DecompressionBody,
     util::compression::{AcceptEncoding, CompressionLevel, WrapBody},
     util::content_encoding::SupportedEncodings,
@@ -29,6 +29,7 @@ pub struct RequestDecompression<S> {
     pub(super) inner: S,
     pub(super) accept: AcceptEncoding,
     pub(super) pass_through_unaccepted: bool,
+    pub(super) limits: DecompressionLimits,
 }
 
 impl<S: fmt::Debug> fmt::Debug for RequestDecompression<S> {
@@ -37,6 +38,7 @@ impl<S: fmt::Debug> fmt::Debug for RequestDecompression<S> {
             .field("inner", &self.inner)
             .field("accept", &self.accept)
             .field("pass_through_unaccepted", &self.pass_through_unaccepted)
+            .field("limits", &self.limits)
             .finish()
     }
 }
@@ -47,6 +49,7 @@ impl<S: Clone> Clone for RequestDecompression<S> {
             inner: self.inner.clone(),
             accept: self.accept,
             pass_through_unaccepted: self.pass_through_unaccepted,
+            limits: self.limits,
         }
     }
 }
@@ -104,7 +107,7 @@ where
             } else {
                 BodyInner::identity(body)
             };
-        let body = DecompressionBody::new(body);
+        let body = DecompressionBody::new(body).with_limits(self.limits);
         let req = Request::from_parts(parts, body);
         self.inner
             .serve(ctx, req)
@@ -140,6 +143,7 @@ impl<S> RequestDecompression<S> {
             inner: service,
             accept: AcceptEncoding::default(),
             pass_through_unaccepted: false,
+            limits: DecompressionLimits::default(),
         }
     }
 
@@ -208,4 +212,36 @@ impl<S> RequestDecompression<S> {
         self.accept.set_zstd(enable);
         self
     }
+
+    /// Sets the maximum size (in bytes) of the decompressed request body.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn max_decompressed_size(mut self, limit: usize) -> Self {
+        self.limits.max_size = Some(limit);
+        self
+    }
+
+    /// Sets the maximum size (in bytes) of the decompressed request body.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn set_max_decompressed_size(&mut self, limit: usize) -> &mut Self {
+        self.limits.max_size = Some(limit);
+        self
+    }
+
+    /// Sets the maximum ratio of the decompressed over the compressed request body size.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn max_compression_ratio(mut self, ratio: u32) -> Self {
+        self.limits.max_ratio = Some(ratio);
+        self
+    }
+
+    /// Sets the maximum ratio of the decompressed over the compressed request body size.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn set_max_compression_ratio(&mut self, ratio: u32) -> &mut Self {
+        self.limits.max_ratio = Some(ratio);
+        self
+    }
 }
@@ -1,6 +1,9 @@
 use std::fmt;
 
-use super::{body::BodyInner, DecompressionBody};
+use super::{
+    body::{BodyInner, DecompressionLimits},
+    DecompressionBody,
+};
 use crate::dep::http_body::Body;
 use crate::layer::util::{
     compression::{AcceptEncoding, CompressionLevel, WrapBody},
@@ -22,6 +25,7 @@ use rama_utils::macros::define_inner_service_accessors;
 pub struct Decompression<S> {
     pub(crate) inner: S,
     pub(crate) accept: AcceptEncoding,
+    pub(crate) limits: DecompressionLimits,
 }
 
 impl<S> Decompression<S> {
@@ -30,6 +34,7 @@ impl<S> Decompression<S> {
         Self {
             inner: service,
             accept: AcceptEncoding::default(),
+            limits: DecompressionLimits::default(),
         }
     }
 
@@ -82,6 +87,38 @@ impl<S> Decompression<S> {
         self.accept.set_zstd(enable);
         self
     }
+
+    /// Sets the maximum size (in bytes) of the decompressed response body.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn max_decompressed_size(mut self, limit: usize) -> Self {
+        self.limits.max_size = Some(limit);
+        self
+    }
+
+    /// Sets the maximum size (in bytes) of the decompressed response body.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn set_max_decompressed_size(&mut self, limit: usize) -> &mut Self {
+        self.limits.max_size = Some(limit);
+        self
+    }
+
+    /// Sets the maximum ratio of the decompressed over the compressed response body size.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn max_compression_ratio(mut self, ratio: u32) -> Self {
+        self.limits.max_ratio = Some(ratio);
+        self
+    }
+
+    /// Sets the maximum ratio of the decompressed over the compressed response body size.
+    ///
+    /// See [limits](crate::layer::decompression#limits).
+    pub fn set_max_compression_ratio(&mut self, ratio: u32) -> &mut Self {
+        self.limits.max_ratio = Some(ratio);
+        self
+    }
 }
 
 impl<S: fmt::Debug> fmt::Debug for Decompression<S> {
@@ -89,6 +126,7 @@ impl<S: fmt::Debug> fmt::Debug for Decompression<S> {
         f.debug_struct("Decompression")
             .field("inner", &self.inner)
             .field("accept", &self.accept)
+            .field("limits", &self.limits)
             .finish()
     }
 }
@@ -98,6 +136,7 @@ impl<S: Clone> Clone for Decompression<S> {
         Decompression {
             inner: self.inner.clone(),
             accept: self.accept,
+            limits: self.limits,
         }
     }
 }
@@ -157,7 +196,7 @@ where
                 entry.remove();
                 parts.headers.remove(header::CONTENT_LENGTH);
 
-                Response::from_parts(parts, body)
+                Response::from_parts(parts, body.with_limits(self.limits))
             } else {
                 Response::from_parts(parts, DecompressionBody::new(BodyInner::identity(body)))
             };
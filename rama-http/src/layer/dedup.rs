@@ -0,0 +1,502 @@
+//! Middleware that drops duplicate deliveries of a request,
+//! identified by a delivery id header.
+//!
+//! Webhooks and other at-least-once delivery mechanisms may deliver
+//! the same request more than once. The [`Dedup`] middleware remembers
+//! the delivery ids it has seen for a configurable ttl, in a set bounded
+//! to a configurable capacity. Duplicate deliveries within that ttl
+//! are answered with a `200 OK` response (or another configured status),
+//! without calling the inner service.
+//!
+//! A delivery id is only remembered as handled once the inner service handled it
+//! successfully. In case the inner service failed to handle it (with an error
+//! or a server error status), or was cancelled (e.g. because the client disconnected),
+//! the delivery can be retried. Concurrent duplicates wait for the delivery in flight,
+//! and are only handled in case that one did not succeed.
+//! Requests without the delivery id header are passed through as-is.
+//!
+//! # Example
+//!
+//! ```
+//! use rama_core::{service::service_fn, Context, Layer, Service};
+//! use rama_core::error::BoxError;
+//! use rama_http::layer::dedup::DedupLayer;
+//! use rama_http::{header::HeaderName, Body, Request, Response, StatusCode};
+//! use std::convert::Infallible;
+//! use std::time::Duration;
+//!
+//! async fn handle_event(_ctx: Context<()>, _req: Request) -> Result<Response, Infallible> {
+//!     let mut res = Response::new(Body::empty());
+//!     *res.status_mut() = StatusCode::ACCEPTED;
+//!     Ok(res)
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), BoxError> {
+//! let service = DedupLayer::new(HeaderName::from_static("x-delivery-id"))
+//!     .with_ttl(Duration::from_secs(3600))
+//!     .layer(service_fn(handle_event));
+//!
+//! let req = || {
+//!     Request::builder()
+//!         .method("POST")
+//!         .uri("/webhook")
+//!         .header("x-delivery-id", "c3a1f6a0-5c1e-4d9b-9a59-7f3e0d0b6b1e")
+//!         .body(Body::from(r#"{"event":"push"}"#))
+//! };
+//! let resp = service.serve(Context::default(), req()?).await?;
+//! assert_eq!(resp.status(), StatusCode::ACCEPTED);
+//!
+//! // duplicate delivery, the handler is not called again
+//! let resp = service.serve(Context::default(), req()?).await?;
+//! assert_eq!(resp.status(), StatusCode::OK);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{header::HeaderName, Body, HeaderValue, Request, Response, StatusCode};
+use moka::sync::Cache;
+use rama_core::{Context, Layer, Service};
+use rama_utils::macros::define_inner_service_accessors;
+use std::{fmt, sync::Arc, time::Duration};
+
+/// The default duration for which the [`Dedup`] middleware remembers a delivery id.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The default maximum amount of delivery ids remembered by the [`Dedup`] middleware.
+pub const DEFAULT_CAPACITY: u64 = 10_000;
+
+/// Delivery state, locked while the delivery is being handled,
+/// and set to `true` once it has been handled successfully.
+type Delivery = Arc<tokio::sync::Mutex<bool>>;
+
+/// Layer that applies the [`Dedup`] middleware.
+///
+/// Services created by the same layer share the delivery ids seen.
+///
+/// See the [module docs](self) for more information.
+pub struct DedupLayer {
+    header_name: HeaderName,
+    ttl: Duration,
+    capacity: u64,
+    duplicate_status: StatusCode,
+    seen: Cache<HeaderValue, Delivery>,
+}
+
+impl fmt::Debug for DedupLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DedupLayer")
+            .field("header_name", &self.header_name)
+            .field("ttl", &self.ttl)
+            .field("capacity", &self.capacity)
+            .field("duplicate_status", &self.duplicate_status)
+            .finish()
+    }
+}
+
+impl Clone for DedupLayer {
+    fn clone(&self) -> Self {
+        Self {
+            header_name: self.header_name.clone(),
+            ttl: self.ttl,
+            capacity: self.capacity,
+            duplicate_status: self.duplicate_status,
+            seen: self.seen.clone(),
+        }
+    }
+}
+
+impl DedupLayer {
+    /// Create a new [`DedupLayer`], identifying deliveries by the given header,
+    /// remembering up to [`DEFAULT_CAPACITY`] delivery ids for the [`DEFAULT_TTL`].
+    pub fn new(header_name: HeaderName) -> Self {
+        Self {
+            header_name,
+            ttl: DEFAULT_TTL,
+            capacity: DEFAULT_CAPACITY,
+            duplicate_status: StatusCode::OK,
+            seen: seen_cache(DEFAULT_CAPACITY, DEFAULT_TTL),
+        }
+    }
+
+    /// Set the duration for which a delivery id is remembered.
+    ///
+    /// This resets the delivery ids seen so far.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.set_ttl(ttl);
+        self
+    }
+
+    /// Set the duration for which a delivery id is remembered.
+    ///
+    /// This resets the delivery ids seen so far.
+    pub fn set_ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.ttl = ttl;
+        self.seen = seen_cache(self.capacity, self.ttl);
+        self
+    }
+
+    /// Set the maximum amount of delivery ids remembered,
+    /// evicting the least recently seen ones first.
+    ///
+    /// This resets the delivery ids seen so far.
+    pub fn with_capacity(mut self, capacity: u64) -> Self {
+        self.set_capacity(capacity);
+        self
+    }
+
+    /// Set the maximum amount of delivery ids remembered,
+    /// evicting the least recently seen ones first.
+    ///
+    /// This resets the delivery ids seen so far.
+    pub fn set_capacity(&mut self, capacity: u64) -> &mut Self {
+        self.capacity = capacity;
+        self.seen = seen_cache(self.capacity, self.ttl);
+        self
+    }
+
+    /// Set the status of the response returned for duplicate deliveries,
+    /// `200 OK` by default.
+    pub fn with_duplicate_status(mut self, status: StatusCode) -> Self {
+        self.duplicate_status = status;
+        self
+    }
+
+    /// Set the status of the response returned for duplicate deliveries,
+    /// `200 OK` by default.
+    pub fn set_duplicate_status(&mut self, status: StatusCode) -> &mut Self {
+        self.duplicate_status = status;
+        self
+    }
+}
+
+fn seen_cache(capacity: u64, ttl: Duration) -> Cache<HeaderValue, Delivery> {
+    Cache::builder()
+        .max_capacity(capacity)
+        .time_to_live(ttl)
+        .build()
+}
+
+impl<S> Layer<S> for DedupLayer {
+    type Service = Dedup<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Dedup {
+            inner,
+            header_name: self.header_name.clone(),
+            duplicate_status: self.duplicate_status,
+            seen: self.seen.clone(),
+        }
+    }
+}
+
+/// Middleware that drops duplicate deliveries of a request,
+/// identified by a delivery id header.
+///
+/// See the [module docs](self) for more information.
+pub struct Dedup<S> {
+    inner: S,
+    header_name: HeaderName,
+    duplicate_status: StatusCode,
+    seen: Cache<HeaderValue, Delivery>,
+}
+
+impl<S: fmt::Debug> fmt::Debug for Dedup<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dedup")
+            .field("inner", &self.inner)
+            .field("header_name", &self.header_name)
+            .field("duplicate_status", &self.duplicate_status)
+            .finish()
+    }
+}
+
+impl<S: Clone> Clone for Dedup<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            header_name: self.header_name.clone(),
+            duplicate_status: self.duplicate_status,
+            seen: self.seen.clone(),
+        }
+    }
+}
+
+impl<S> Dedup<S> {
+    /// Create a new [`Dedup`] middleware, identifying deliveries by the given header,
+    /// remembering up to [`DEFAULT_CAPACITY`] delivery ids for the [`DEFAULT_TTL`].
+    pub fn new(inner: S, header_name: HeaderName) -> Self {
+        DedupLayer::new(header_name).layer(inner)
+    }
+
+    /// Set the status of the response returned for duplicate deliveries,
+    /// `200 OK` by default.
+    pub fn with_duplicate_status(mut self, status: StatusCode) -> Self {
+        self.duplicate_status = status;
+        self
+    }
+
+    /// Set the status of the response returned for duplicate deliveries,
+    /// `200 OK` by default.
+    pub fn set_duplicate_status(&mut self, status: StatusCode) -> &mut Self {
+        self.duplicate_status = status;
+        self
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S, State, ReqBody, ResBody> Service<State, Request<ReqBody>> for Dedup<S>
+where
+    S: Service<State, Request<ReqBody>, Response = Response<ResBody>>,
+    State: Clone + Send + Sync + 'static,
+    ReqBody: Send + 'static,
+    ResBody: Into<Body> + Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request<ReqBody>,
+    ) -> Result<Self::Response, Self::Error> {
+        let Some(id) = req.headers().get(&self.header_name).cloned() else {
+            return self
+                .inner
+                .serve(ctx, req)
+                .await
+                .map(|res| res.map(Into::into));
+        };
+
+        // the lock is held while handling the delivery, such that concurrent
+        // duplicates wait for it, and is released as-is in case the handler
+        // fails or its future is dropped, such that the delivery can be retried
+        let delivery = self.seen.get_with(id.clone(), Delivery::default);
+        let mut handled = delivery.lock_owned().await;
+        if *handled {
+            tracing::trace!(id = ?id, "drop duplicate delivery");
+            let mut res = Response::new(Body::empty());
+            *res.status_mut() = self.duplicate_status;
+            return Ok(res);
+        }
+
+        let res = self.inner.serve(ctx, req).await?;
+        *handled = !res.status().is_server_error();
+        Ok(res.map(Into::into))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::service::service_fn;
+    use std::{
+        convert::Infallible,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    static X_DELIVERY_ID: HeaderName = HeaderName::from_static("x-delivery-id");
+
+    fn handler(
+        calls: Arc<AtomicUsize>,
+        status: StatusCode,
+    ) -> impl Service<(), Request, Response = Response, Error = Infallible> {
+        service_fn(move |_req: Request| {
+            let calls = calls.clone();
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                let mut res = Response::new(Body::empty());
+                *res.status_mut() = status;
+                Ok::<_, Infallible>(res)
+            }
+        })
+    }
+
+    fn request(id: Option<&'static str>) -> Request {
+        let mut builder = Request::builder().method("POST").uri("/webhook");
+        if let Some(id) = id {
+            builder = builder.header(&X_DELIVERY_ID, id);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_delivery_is_dropped() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = DedupLayer::new(X_DELIVERY_ID.clone())
+            .layer(handler(calls.clone(), StatusCode::ACCEPTED));
+
+        // first delivery
+        let res = service
+            .serve(Context::default(), request(Some("a")))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // duplicate delivery
+        let res = service
+            .serve(Context::default(), request(Some("a")))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // other deliveries and requests without delivery id are passed through
+        let res = service
+            .serve(Context::default(), request(Some("b")))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        for _ in 0..2 {
+            let res = service
+                .serve(Context::default(), request(None))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::ACCEPTED);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_delivery_custom_status() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = DedupLayer::new(X_DELIVERY_ID.clone())
+            .with_duplicate_status(StatusCode::ALREADY_REPORTED)
+            .layer(handler(calls.clone(), StatusCode::OK));
+
+        for expected_status in [StatusCode::OK, StatusCode::ALREADY_REPORTED] {
+            let res = service
+                .serve(Context::default(), request(Some("a")))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), expected_status);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_outside_ttl_is_handled() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = DedupLayer::new(X_DELIVERY_ID.clone())
+            .with_ttl(Duration::from_millis(50))
+            .layer(handler(calls.clone(), StatusCode::ACCEPTED));
+
+        for expected_status in [StatusCode::ACCEPTED, StatusCode::OK] {
+            let res = service
+                .serve(Context::default(), request(Some("a")))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), expected_status);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let res = service
+            .serve(Context::default(), request(Some("a")))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_delivery_can_be_retried() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = DedupLayer::new(X_DELIVERY_ID.clone()).layer(service_fn({
+            let calls = calls.clone();
+            move |_req: Request| {
+                let first = calls.fetch_add(1, Ordering::SeqCst) == 0;
+                async move {
+                    if first {
+                        tokio::time::sleep(Duration::from_secs(60)).await;
+                    }
+                    Ok::<_, Infallible>(Response::new(Body::empty()))
+                }
+            }
+        }));
+
+        // the first delivery is dropped (e.g. client disconnect) before it is handled
+        assert!(tokio::time::timeout(
+            Duration::from_millis(20),
+            service.serve(Context::default(), request(Some("a")))
+        )
+        .await
+        .is_err());
+
+        // so the redelivery is handled
+        let res = service
+            .serve(Context::default(), request(Some("a")))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_duplicate_waits_for_delivery_in_flight() {
+        for (first_status, expected_calls) in [
+            (StatusCode::ACCEPTED, 1),
+            (StatusCode::SERVICE_UNAVAILABLE, 2),
+        ] {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let service = Arc::new(DedupLayer::new(X_DELIVERY_ID.clone()).layer(service_fn({
+                let calls = calls.clone();
+                move |_req: Request| {
+                    let first = calls.fetch_add(1, Ordering::SeqCst) == 0;
+                    async move {
+                        let mut res = Response::new(Body::empty());
+                        if first {
+                            tokio::time::sleep(Duration::from_millis(50)).await;
+                            *res.status_mut() = first_status;
+                        }
+                        Ok::<_, Infallible>(res)
+                    }
+                }
+            })));
+
+            let first = tokio::spawn({
+                let service = service.clone();
+                async move {
+                    service
+                        .serve(Context::default(), request(Some("a")))
+                        .await
+                        .unwrap()
+                }
+            });
+            tokio::time::sleep(Duration::from_millis(10)).await;
+
+            // the duplicate is only answered once the first delivery completed
+            let duplicate = service
+                .serve(Context::default(), request(Some("a")))
+                .await
+                .unwrap();
+            assert!(first.is_finished());
+            assert_eq!(first.await.unwrap().status(), first_status);
+            // a duplicate of a failed delivery is handled instead
+            assert_eq!(duplicate.status(), StatusCode::OK);
+            assert_eq!(calls.load(Ordering::SeqCst), expected_calls);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_can_be_retried() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = DedupLayer::new(X_DELIVERY_ID.clone())
+            .layer(handler(calls.clone(), StatusCode::SERVICE_UNAVAILABLE));
+
+        for _ in 0..2 {
+            let res = service
+                .serve(Context::default(), request(Some("a")))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,751 @@
+//! Middleware that makes `POST` and `PATCH` requests safe to retry,
+//! by honoring the `Idempotency-Key` header.
+//!
+//! The first response produced for a given key and route (method and path)
+//! is cached in an [`IdempotencyStore`] for a configurable ttl.
+//! Requests replayed with the same key and body within that ttl
+//! get the cached response, without calling the inner service again,
+//! while requests reusing the key for a different body
+//! are rejected with a `409 Conflict` response.
+//!
+//! Duplicate requests arriving while the first one is still in flight
+//! wait for it to complete, after which they get its cached response.
+//!
+//! Responses with a server error status are not cached,
+//! such that the request can be retried. Requests without an `Idempotency-Key`
+//! header, or with a method other than `POST` or `PATCH`, are passed through as-is.
+//!
+//! Request and response bodies are collected in memory, up to a configurable
+//! maximum size ([`DEFAULT_MAX_BODY_SIZE`] by default), also respecting the request
+//! [`BodyLimit`] if one is set in the [`Context`]. Requests with a larger body are
+//! rejected with a `413 Payload Too Large` response, while larger responses
+//! are returned as-is without being cached.
+//!
+//! Request bodies are fingerprinted using SHA-256.
+//!
+//! [`BodyLimit`]: crate::BodyLimit
+//!
+//! # Example
+//!
+//! ```
+//! use rama_core::{service::service_fn, Context, Layer, Service};
+//! use rama_core::error::BoxError;
+//! use rama_http::layer::idempotency::{IdempotencyLayer, MemoryIdempotencyStore};
+//! use rama_http::{Body, Request, Response, StatusCode};
+//! use std::convert::Infallible;
+//! use std::time::Duration;
+//!
+//! async fn create_order(_ctx: Context<()>, _req: Request) -> Result<Response, Infallible> {
+//!     let mut res = Response::new(Body::from("order created"));
+//!     *res.status_mut() = StatusCode::CREATED;
+//!     Ok(res)
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), BoxError> {
+//! let service = IdempotencyLayer::new(MemoryIdempotencyStore::new())
+//!     .with_ttl(Duration::from_secs(3600))
+//!     .layer(service_fn(create_order));
+//!
+//! let req = Request::builder()
+//!     .method("POST")
+//!     .uri("/orders")
+//!     .header("idempotency-key", "8e03978e-40d5-43e8-bc93-6894a57f9324")
+//!     .body(Body::from(r#"{"item":"rama"}"#))?;
+//! let resp = service.serve(Context::default(), req).await?;
+//! assert_eq!(resp.status(), StatusCode::CREATED);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    dep::http_body::{Body as HttpBody, Frame},
+    dep::http_body_util::{BodyExt, BodyStream, StreamBody},
+    header::IDEMPOTENCY_KEY,
+    utils::macros::{composite_http_rejection, define_http_rejection},
+    Body, BodyLimit, HeaderMap, IntoResponse, Method, Request, Response,
+};
+use bytes::{Bytes, BytesMut};
+use futures_lite::StreamExt;
+use rama_core::{error::BoxError, Context, Layer, Service};
+use rama_crypto::digest::sha256;
+use rama_utils::macros::define_inner_service_accessors;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+mod store;
+#[doc(inline)]
+pub use store::{CachedResponse, IdempotencyKey, IdempotencyStore, MemoryIdempotencyStore};
+
+define_http_rejection! {
+    #[status = CONFLICT]
+    #[body = "Idempotency-Key is already used for a different request"]
+    /// Rejection used by the [`Idempotency`] middleware in case the
+    /// `Idempotency-Key` of a request was already used for a request with a different body.
+    pub struct IdempotencyKeyConflict(Error);
+}
+
+define_http_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Invalid idempotent request"]
+    /// Rejection used by the [`Idempotency`] middleware in case the
+    /// `Idempotency-Key` header is invalid or the request body could not be read.
+    pub struct InvalidIdempotentRequest(Error);
+}
+
+define_http_rejection! {
+    #[status = PAYLOAD_TOO_LARGE]
+    #[body = "Idempotent request body is too large"]
+    /// Rejection used by the [`Idempotency`] middleware in case the
+    /// body of a request exceeds the maximum body size.
+    pub struct IdempotentRequestTooLarge(Error);
+}
+
+composite_http_rejection! {
+    /// Rejection used by the [`Idempotency`] middleware.
+    pub enum IdempotencyRejection {
+        IdempotencyKeyConflict,
+        InvalidIdempotentRequest,
+        IdempotentRequestTooLarge,
+    }
+}
+
+/// The default ttl of responses cached by the [`Idempotency`] middleware.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The default maximum size of the request and response bodies
+/// collected by the [`Idempotency`] middleware.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+type InFlight = Arc<Mutex<HashMap<IdempotencyKey, Arc<tokio::sync::Mutex<()>>>>>;
+
+/// Layer that applies the [`Idempotency`] middleware.
+///
+/// Services created by the same layer share the requests in flight.
+///
+/// See the [module docs](self) for more information.
+pub struct IdempotencyLayer<T> {
+    store: Arc<T>,
+    ttl: Duration,
+    max_body_size: usize,
+    in_flight: InFlight,
+}
+
+impl<T: fmt::Debug> fmt::Debug for IdempotencyLayer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdempotencyLayer")
+            .field("store", &self.store)
+            .field("ttl", &self.ttl)
+            .field("max_body_size", &self.max_body_size)
+            .finish()
+    }
+}
+
+impl<T> Clone for IdempotencyLayer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            ttl: self.ttl,
+            max_body_size: self.max_body_size,
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+impl<T> IdempotencyLayer<T> {
+    /// Create a new [`IdempotencyLayer`], caching responses in the given store
+    /// for the [`DEFAULT_TTL`].
+    pub fn new(store: T) -> Self {
+        Self {
+            store: Arc::new(store),
+            ttl: DEFAULT_TTL,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            in_flight: InFlight::default(),
+        }
+    }
+
+    /// Set the duration for which responses are cached.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the duration for which responses are cached.
+    pub fn set_ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the maximum size of the request and response bodies,
+    /// which defaults to [`DEFAULT_MAX_BODY_SIZE`].
+    ///
+    /// Requests with a larger body are rejected with a `413 Payload Too Large`
+    /// response, while larger responses are returned without being cached.
+    pub fn with_max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = size;
+        self
+    }
+
+    /// Set the maximum size of the request and response bodies,
+    /// which defaults to [`DEFAULT_MAX_BODY_SIZE`].
+    ///
+    /// Requests with a larger body are rejected with a `413 Payload Too Large`
+    /// response, while larger responses are returned without being cached.
+    pub fn set_max_body_size(&mut self, size: usize) -> &mut Self {
+        self.max_body_size = size;
+        self
+    }
+}
+
+impl<S, T> Layer<S> for IdempotencyLayer<T> {
+    type Service = Idempotency<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Idempotency {
+            inner,
+            store: self.store.clone(),
+            ttl: self.ttl,
+            max_body_size: self.max_body_size,
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+/// Middleware that caches and replays responses of requests with an `Idempotency-Key`.
+///
+/// See the [module docs](self) for more information.
+pub struct Idempotency<S, T> {
+    inner: S,
+    store: Arc<T>,
+    ttl: Duration,
+    max_body_size: usize,
+    in_flight: InFlight,
+}
+
+impl<S: fmt::Debug, T: fmt::Debug> fmt::Debug for Idempotency<S, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Idempotency")
+            .field("inner", &self.inner)
+            .field("store", &self.store)
+            .field("ttl", &self.ttl)
+            .field("max_body_size", &self.max_body_size)
+            .finish()
+    }
+}
+
+impl<S: Clone, T> Clone for Idempotency<S, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            store: self.store.clone(),
+            ttl: self.ttl,
+            max_body_size: self.max_body_size,
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+impl<S, T> Idempotency<S, T> {
+    /// Create a new [`Idempotency`] middleware, caching responses in the given store
+    /// for the [`DEFAULT_TTL`].
+    pub fn new(inner: S, store: T) -> Self {
+        IdempotencyLayer::new(store).layer(inner)
+    }
+
+    /// Set the duration for which responses are cached.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the duration for which responses are cached.
+    pub fn set_ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the maximum size of the request and response bodies,
+    /// which defaults to [`DEFAULT_MAX_BODY_SIZE`].
+    ///
+    /// Requests with a larger body are rejected with a `413 Payload Too Large`
+    /// response, while larger responses are returned without being cached.
+    pub fn with_max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = size;
+        self
+    }
+
+    /// Set the maximum size of the request and response bodies,
+    /// which defaults to [`DEFAULT_MAX_BODY_SIZE`].
+    ///
+    /// Requests with a larger body are rejected with a `413 Payload Too Large`
+    /// response, while larger responses are returned without being cached.
+    pub fn set_max_body_size(&mut self, size: usize) -> &mut Self {
+        self.max_body_size = size;
+        self
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S, T, State, ReqBody, ResBody> Service<State, Request<ReqBody>> for Idempotency<S, T>
+where
+    S: Service<State, Request, Response = Response<ResBody>, Error: Into<BoxError>>,
+    T: IdempotencyStore,
+    State: Clone + Send + Sync + 'static,
+    ReqBody: HttpBody<Data = Bytes, Error: Into<BoxError>> + Send + Sync + 'static,
+    ResBody: HttpBody<Data = Bytes, Error: Into<BoxError>> + Send + Sync + 'static,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request<ReqBody>,
+    ) -> Result<Self::Response, Self::Error> {
+        let key = match req.headers().get(&IDEMPOTENCY_KEY) {
+            Some(key) if matches!(*req.method(), Method::POST | Method::PATCH) => key,
+            _ => {
+                let res = self
+                    .inner
+                    .serve(ctx, req.map(Body::new))
+                    .await
+                    .map_err(Into::into)?;
+                return Ok(res.map(Body::new));
+            }
+        };
+        let Some(key) = key
+            .to_str()
+            .ok()
+            .filter(|key| !key.is_empty())
+            .map(|key| IdempotencyKey::new(key, req.method().clone(), req.uri().path()))
+        else {
+            return Ok(
+                InvalidIdempotentRequest::from_display("invalid Idempotency-Key header")
+                    .into_response(),
+            );
+        };
+
+        let (parts, body) = req.into_parts();
+        let limit = match ctx.get::<BodyLimit>().and_then(BodyLimit::request) {
+            Some(limit) => limit.min(self.max_body_size),
+            None => self.max_body_size,
+        };
+        let (body, trailers) = match collect_limited(body, limit).await {
+            Ok(Collected::Complete { data, trailers }) => (data, trailers),
+            Ok(Collected::Exceeded(_)) => {
+                return Ok(IdempotentRequestTooLarge::from_display(format!(
+                    "request body exceeds {limit} bytes"
+                ))
+                .into_response());
+            }
+            Err(err) => {
+                return Ok(InvalidIdempotentRequest::from_display(err).into_response());
+            }
+        };
+        let fingerprint = sha256(&body);
+
+        // duplicate requests wait for the request in flight to complete,
+        // after which they find its response in the store
+        let _in_flight = InFlightGuard::acquire(&self.in_flight, &key).await;
+
+        if let Some(cached) = self.store.load(&key).await? {
+            if cached.fingerprint != fingerprint {
+                return Ok(IdempotencyKeyConflict::from_display(format!(
+                    "Idempotency-Key '{}' is already used for {} {}",
+                    key.key(),
+                    key.method(),
+                    key.path()
+                ))
+                .into_response());
+            }
+            tracing::trace!(key = key.key(), "replay cached idempotent response");
+            return Ok(cached.into_response());
+        }
+
+        let res = self
+            .inner
+            .serve(
+                ctx,
+                Request::from_parts(parts, body_with_trailers(body, trailers)),
+            )
+            .await
+            .map_err(Into::into)?;
+        if res.status().is_server_error() {
+            return Ok(res.map(Body::new));
+        }
+
+        let (parts, body) = res.into_parts();
+        let (body, trailers) = match collect_limited(body, self.max_body_size).await? {
+            Collected::Complete { data, trailers } => (data, trailers),
+            Collected::Exceeded(body) => {
+                tracing::debug!(
+                    key = key.key(),
+                    "idempotent response body too large to be cached"
+                );
+                return Ok(Response::from_parts(parts, body));
+            }
+        };
+        self.store
+            .store(
+                key,
+                CachedResponse {
+                    fingerprint,
+                    status: parts.status,
+                    headers: parts.headers.clone(),
+                    body: body.clone(),
+                    trailers: trailers.clone(),
+                },
+                self.ttl,
+            )
+            .await?;
+
+        Ok(Response::from_parts(
+            parts,
+            body_with_trailers(body, trailers),
+        ))
+    }
+}
+
+/// The result of [`collect_limited`].
+enum Collected {
+    /// The complete body, within the limit,
+    /// together with its trailers (if any).
+    Complete {
+        data: Bytes,
+        trailers: Option<HeaderMap>,
+    },
+    /// The body exceeds the limit, re-assembled from
+    /// the frames read so far and the remainder of the body.
+    Exceeded(Body),
+}
+
+/// Collect the data (and trailers) of the given body,
+/// as long as it does not exceed the given limit.
+async fn collect_limited<B>(body: B, limit: usize) -> Result<Collected, BoxError>
+where
+    B: HttpBody<Data = Bytes, Error: Into<BoxError>> + Send + Sync + 'static,
+{
+    let mut body = Body::new(body);
+    let mut data = BytesMut::new();
+    let mut trailers: Option<HeaderMap> = None;
+    while let Some(frame) = body.frame().await {
+        let frame = frame?;
+        let Some(chunk) = frame.data_ref() else {
+            if let Some(frame_trailers) = frame.trailers_ref() {
+                match trailers.as_mut() {
+                    Some(trailers) => trailers.extend(frame_trailers.clone()),
+                    None => trailers = Some(frame_trailers.clone()),
+                }
+            }
+            continue;
+        };
+        if data.len() + chunk.len() > limit {
+            let read = futures_lite::stream::iter([Ok(Frame::data(data.freeze())), Ok(frame)]);
+            let body = StreamBody::new(read.chain(BodyStream::new(body)));
+            return Ok(Collected::Exceeded(Body::new(body)));
+        }
+        data.extend_from_slice(chunk);
+    }
+    Ok(Collected::Complete {
+        data: data.freeze(),
+        trailers,
+    })
+}
+
+/// Create a [`Body`] from collected data, re-attaching its trailers (if any).
+fn body_with_trailers(data: Bytes, trailers: Option<HeaderMap>) -> Body {
+    let body = Body::from(data);
+    match trailers {
+        Some(trailers) => body.with_trailers(trailers),
+        None => body,
+    }
+}
+
+/// Holds the lock of an [`IdempotencyKey`] while its request is in flight,
+/// cleaning up the lock once no other request is waiting for it.
+struct InFlightGuard {
+    in_flight: InFlight,
+    key: IdempotencyKey,
+    lock: Arc<tokio::sync::Mutex<()>>,
+    guard: Option<tokio::sync::OwnedMutexGuard<()>>,
+}
+
+impl InFlightGuard {
+    async fn acquire(in_flight: &InFlight, key: &IdempotencyKey) -> Self {
+        let lock = in_flight
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let guard = lock.clone().lock_owned().await;
+        Self {
+            in_flight: in_flight.clone(),
+            key: key.clone(),
+            lock,
+            guard: Some(guard),
+        }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        drop(self.guard.take());
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|err| err.into_inner());
+        // one reference is held by the map, the other one by us,
+        // any other reference belongs to a request waiting for the lock
+        if Arc::strong_count(&self.lock) == 2 {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StatusCode;
+    use rama_core::service::service_fn;
+    use std::{
+        convert::Infallible,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    fn handler(
+        calls: Arc<AtomicUsize>,
+    ) -> impl Service<(), Request, Response = Response, Error = Infallible> {
+        service_fn(move |_ctx, req: Request| {
+            let calls = calls.clone();
+            async move {
+                let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                let body = req.into_body().collect().await.unwrap().to_bytes();
+                let mut res = Response::new(Body::from(format!(
+                    "call {n}: {}",
+                    String::from_utf8_lossy(&body)
+                )));
+                *res.status_mut() = StatusCode::CREATED;
+                Ok::<_, Infallible>(res)
+            }
+        })
+    }
+
+    fn request(key: Option<&str>, body: &'static str) -> Request {
+        let mut builder = Request::builder().method("POST").uri("/orders");
+        if let Some(key) = key {
+            builder = builder.header(&IDEMPOTENCY_KEY, key);
+        }
+        builder.body(Body::from(body)).unwrap()
+    }
+
+    async fn body(res: Response) -> String {
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_replayed_key_returns_cached_response() {
+        let store = MemoryIdempotencyStore::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = IdempotencyLayer::new(store.clone()).layer(handler(calls.clone()));
+
+        let res = service
+            .serve(Context::default(), request(Some("a"), "order"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(body(res).await, "call 1: order");
+
+        let res = service
+            .serve(Context::default(), request(Some("a"), "order"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(body(res).await, "call 1: order");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(store.len(), 1);
+
+        // other keys and requests without key are not cached
+        let res = service
+            .serve(Context::default(), request(Some("b"), "order"))
+            .await
+            .unwrap();
+        assert_eq!(body(res).await, "call 2: order");
+        let res = service
+            .serve(Context::default(), request(None, "order"))
+            .await
+            .unwrap();
+        assert_eq!(body(res).await, "call 3: order");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_expired_response_is_not_replayed() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = IdempotencyLayer::new(MemoryIdempotencyStore::new())
+            .with_ttl(Duration::ZERO)
+            .layer(handler(calls.clone()));
+
+        for _ in 0..2 {
+            let res = service
+                .serve(Context::default(), request(Some("a"), "order"))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::CREATED);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_body_is_rejected() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service =
+            IdempotencyLayer::new(MemoryIdempotencyStore::new()).layer(handler(calls.clone()));
+
+        let res = service
+            .serve(Context::default(), request(Some("a"), "order"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let res = service
+            .serve(Context::default(), request(Some("a"), "other order"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_duplicates_wait_for_first() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let layer = IdempotencyLayer::new(MemoryIdempotencyStore::new());
+        let service = Arc::new(layer.layer(handler(calls.clone())));
+
+        let handles: Vec<_> = (0..5)
+            .map(|_| {
+                let service = service.clone();
+                tokio::spawn(async move {
+                    service
+                        .serve(Context::default(), request(Some("a"), "order"))
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            let res = handle.await.unwrap();
+            assert_eq!(res.status(), StatusCode::CREATED);
+            assert_eq!(body(res).await, "call 1: order");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(layer.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_request_body_too_large_is_rejected() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = IdempotencyLayer::new(MemoryIdempotencyStore::new())
+            .with_max_body_size(8)
+            .layer(handler(calls.clone()));
+
+        let res = service
+            .serve(Context::default(), request(Some("a"), "order"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let res = service
+            .serve(Context::default(), request(Some("b"), "large order"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // the request body limit of the context applies as well
+        let mut ctx = Context::default();
+        ctx.insert(BodyLimit::request_only(4));
+        let res = service
+            .serve(ctx, request(Some("c"), "order"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_response_body_too_large_is_not_cached() {
+        let store = MemoryIdempotencyStore::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = IdempotencyLayer::new(store.clone())
+            .with_max_body_size(12)
+            .layer(handler(calls.clone()));
+
+        for n in 1..=2 {
+            let res = service
+                .serve(Context::default(), request(Some("a"), "order"))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::CREATED);
+            assert_eq!(body(res).await, format!("call {n}: order"));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(store.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_collect_limited_preserves_trailers() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("x-checksum", crate::HeaderValue::from_static("abc"));
+        let body = Body::from("hello world").with_trailers(trailers.clone());
+
+        let Collected::Exceeded(body) = collect_limited(body, 4).await.unwrap() else {
+            panic!("expected body to exceed limit");
+        };
+        let collected = body.collect().await.unwrap();
+        assert_eq!(collected.trailers(), Some(&trailers));
+        assert_eq!(collected.to_bytes(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn test_trailers_are_forwarded_and_replayed() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = IdempotencyLayer::new(MemoryIdempotencyStore::new()).layer(service_fn({
+            let calls = calls.clone();
+            move |_ctx, req: Request| {
+                let calls = calls.clone();
+                async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    let collected = req.into_body().collect().await.unwrap();
+                    let trailers = collected.trailers().cloned().unwrap();
+                    Ok::<_, Infallible>(Response::new(
+                        Body::from(collected.to_bytes()).with_trailers(trailers),
+                    ))
+                }
+            }
+        }));
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("x-checksum", crate::HeaderValue::from_static("abc"));
+        let req = || {
+            Request::builder()
+                .method("POST")
+                .uri("/orders")
+                .header(&IDEMPOTENCY_KEY, "a")
+                .body(Body::from("order").with_trailers(trailers.clone()))
+                .unwrap()
+        };
+
+        for _ in 0..2 {
+            let res = service.serve(Context::default(), req()).await.unwrap();
+            let collected = res.into_body().collect().await.unwrap();
+            assert_eq!(collected.trailers(), Some(&trailers));
+            assert_eq!(collected.to_bytes(), "order");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
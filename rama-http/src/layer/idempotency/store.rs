@@ -0,0 +1,171 @@
+//! Storage of the responses cached by the [`Idempotency`] middleware.
+//!
+//! [`Idempotency`]: super::Idempotency
+
+use crate::{HeaderMap, Method, Response, StatusCode};
+use bytes::Bytes;
+use rama_core::error::BoxError;
+use rama_crypto::digest::SHA256_LEN;
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// The key under which an [`IdempotencyStore`] stores a [`CachedResponse`],
+/// made up of the `Idempotency-Key` header value and the route of the request.
+pub struct IdempotencyKey {
+    key: String,
+    method: Method,
+    path: String,
+}
+
+impl IdempotencyKey {
+    /// Create a new [`IdempotencyKey`].
+    pub fn new(key: impl Into<String>, method: Method, path: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            method,
+            path: path.into(),
+        }
+    }
+
+    /// The value of the `Idempotency-Key` header.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The method of the request.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The path of the request.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A response cached by the [`Idempotency`] middleware,
+/// together with the fingerprint of the request body that produced it.
+///
+/// [`Idempotency`]: super::Idempotency
+pub struct CachedResponse {
+    /// SHA-256 fingerprint of the body of the request that produced this response,
+    /// used to detect conflicting requests reusing the same key.
+    pub fingerprint: [u8; SHA256_LEN],
+    /// The status of the response.
+    pub status: StatusCode,
+    /// The headers of the response.
+    pub headers: HeaderMap,
+    /// The (collected) body of the response.
+    pub body: Bytes,
+    /// The trailers of the response, if any.
+    pub trailers: Option<HeaderMap>,
+}
+
+impl CachedResponse {
+    /// Turn this cached response into a [`Response`], to replay it.
+    pub fn into_response(self) -> Response {
+        let mut res = Response::new(super::body_with_trailers(self.body, self.trailers));
+        *res.status_mut() = self.status;
+        *res.headers_mut() = self.headers;
+        res
+    }
+}
+
+/// A store used by the [`Idempotency`] middleware to cache responses.
+///
+/// [`Idempotency`]: super::Idempotency
+pub trait IdempotencyStore: Send + Sync + 'static {
+    /// Load the response cached for the given key,
+    /// returning `None` if there is none or if it expired.
+    fn load<'a>(
+        &'a self,
+        key: &'a IdempotencyKey,
+    ) -> impl Future<Output = Result<Option<CachedResponse>, BoxError>> + Send + 'a;
+
+    /// Store the response for the given key, to be kept for the given ttl.
+    fn store(
+        &self,
+        key: IdempotencyKey,
+        response: CachedResponse,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<(), BoxError>> + Send + '_;
+}
+
+impl<S> IdempotencyStore for Arc<S>
+where
+    S: IdempotencyStore,
+{
+    fn load<'a>(
+        &'a self,
+        key: &'a IdempotencyKey,
+    ) -> impl Future<Output = Result<Option<CachedResponse>, BoxError>> + Send + 'a {
+        (**self).load(key)
+    }
+
+    fn store(
+        &self,
+        key: IdempotencyKey,
+        response: CachedResponse,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<(), BoxError>> + Send + '_ {
+        (**self).store(key, response, ttl)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// An in-memory [`IdempotencyStore`].
+///
+/// Expired responses are removed when new responses are stored.
+/// Clones of this store share the same responses.
+pub struct MemoryIdempotencyStore {
+    entries: Arc<Mutex<HashMap<IdempotencyKey, (Instant, CachedResponse)>>>,
+}
+
+impl MemoryIdempotencyStore {
+    /// Create a new empty [`MemoryIdempotencyStore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the amount of responses in this store, including expired ones.
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .len()
+    }
+
+    /// Returns `true` if this store contains no responses.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl IdempotencyStore for MemoryIdempotencyStore {
+    async fn load(&self, key: &IdempotencyKey) -> Result<Option<CachedResponse>, BoxError> {
+        let entries = self.entries.lock().unwrap_or_else(|err| err.into_inner());
+        Ok(entries
+            .get(key)
+            .filter(|(expires_at, _)| *expires_at > Instant::now())
+            .map(|(_, response)| response.clone()))
+    }
+
+    async fn store(
+        &self,
+        key: IdempotencyKey,
+        response: CachedResponse,
+        ttl: Duration,
+    ) -> Result<(), BoxError> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|err| err.into_inner());
+        entries.retain(|_, (expires_at, _)| *expires_at > now);
+        entries.insert(key, (now + ttl, response));
+        Ok(())
+    }
+}
@@ -0,0 +1,453 @@
+//! Middleware to observe requests and responses (e.g. for logging),
+//! with sensitive header values redacted.
+//!
+//! The observer gets a body-less copy of the request or response,
+//! in which the values of the headers configured in [`Redaction`] are masked.
+//! The request or response itself (including its body) is passed on untouched.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::convert::Infallible;
+//! use rama_core::error::BoxError;
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Service, Layer};
+//! use rama_http::{Body, Request, Response, header::AUTHORIZATION};
+//! use rama_http::layer::inspect::{InspectRequestLayer, InspectResponseLayer};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), BoxError> {
+//! async fn handle(req: Request) -> Result<Response, Infallible> {
+//!     assert_eq!(req.headers()[AUTHORIZATION], "Bearer secret");
+//!     Ok(Response::new(Body::default()))
+//! }
+//!
+//! let svc = (
+//!     InspectRequestLayer::new(|req: &Request<()>| {
+//!         assert_eq!(req.headers()[AUTHORIZATION], "[REDACTED]");
+//!         tracing::info!(uri = %req.uri(), headers = ?req.headers(), "incoming request");
+//!     }),
+//!     InspectResponseLayer::new(|res: &Response<()>| {
+//!         tracing::info!(status = %res.status(), "outgoing response");
+//!     }),
+//! ).layer(service_fn(handle));
+//!
+//! let request = Request::builder()
+//!     .header(AUTHORIZATION, "Bearer secret")
+//!     .body(Body::default())?;
+//!
+//! let _ = svc.serve(Context::default(), request).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    header::{AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, SET_COOKIE},
+    HeaderMap, HeaderName, HeaderValue, Request, Response,
+};
+use rama_core::{Context, Layer, Service};
+use rama_utils::macros::define_inner_service_accessors;
+use std::fmt;
+
+const REDACTED: HeaderValue = HeaderValue::from_static("[REDACTED]");
+
+#[derive(Debug, Clone)]
+/// Which header values to mask in the copies given to an observer.
+///
+/// By default the `Authorization`, `Proxy-Authorization`,
+/// `Cookie` and `Set-Cookie` headers are redacted.
+pub struct Redaction {
+    headers: Vec<HeaderName>,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self {
+            headers: vec![AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE, SET_COOKIE],
+        }
+    }
+}
+
+impl Redaction {
+    /// Create a [`Redaction`] which does not redact any header.
+    pub fn none() -> Self {
+        Self {
+            headers: Vec::new(),
+        }
+    }
+
+    /// Redact the given header as well.
+    ///
+    /// Multiple headers can be pushed, all of which are redacted.
+    pub fn with_pushed_header(mut self, header: HeaderName) -> Self {
+        self.headers.push(header);
+        self
+    }
+
+    /// Redact the given header as well.
+    ///
+    /// Multiple headers can be pushed, all of which are redacted.
+    pub fn push_header(&mut self, header: HeaderName) -> &mut Self {
+        self.headers.push(header);
+        self
+    }
+
+    fn redact(&self, headers: &HeaderMap) -> HeaderMap {
+        let mut headers = headers.clone();
+        for name in &self.headers {
+            if let crate::header::Entry::Occupied(mut entry) = headers.entry(name) {
+                for value in entry.iter_mut() {
+                    *value = REDACTED;
+                }
+            }
+        }
+        headers
+    }
+}
+
+/// Layer that applies [`InspectRequest`], which calls an observer
+/// with a redacted, body-less copy of each request.
+///
+/// See the [module docs](crate::layer::inspect) for more details.
+#[derive(Clone)]
+pub struct InspectRequestLayer<F> {
+    observer: F,
+    redaction: Redaction,
+}
+
+impl<F> fmt::Debug for InspectRequestLayer<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InspectRequestLayer")
+            .field("observer", &std::any::type_name::<F>())
+            .field("redaction", &self.redaction)
+            .finish()
+    }
+}
+
+impl<F> InspectRequestLayer<F> {
+    /// Create a new [`InspectRequestLayer`], using the default [`Redaction`].
+    pub fn new(observer: F) -> Self {
+        Self {
+            observer,
+            redaction: Redaction::default(),
+        }
+    }
+
+    /// Overwrite the [`Redaction`] used for the observed requests.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Overwrite the [`Redaction`] used for the observed requests.
+    pub fn set_redaction(&mut self, redaction: Redaction) -> &mut Self {
+        self.redaction = redaction;
+        self
+    }
+}
+
+impl<S, F: Clone> Layer<S> for InspectRequestLayer<F> {
+    type Service = InspectRequest<S, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InspectRequest {
+            inner,
+            observer: self.observer.clone(),
+            redaction: self.redaction.clone(),
+        }
+    }
+}
+
+/// Middleware that calls an observer with a redacted,
+/// body-less copy of each request, prior to serving it.
+///
+/// See the [module docs](crate::layer::inspect) for more details.
+#[derive(Clone)]
+pub struct InspectRequest<S, F> {
+    inner: S,
+    observer: F,
+    redaction: Redaction,
+}
+
+impl<S: fmt::Debug, F> fmt::Debug for InspectRequest<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InspectRequest")
+            .field("inner", &self.inner)
+            .field("observer", &std::any::type_name::<F>())
+            .field("redaction", &self.redaction)
+            .finish()
+    }
+}
+
+impl<S, F> InspectRequest<S, F> {
+    /// Create a new [`InspectRequest`], using the default [`Redaction`].
+    pub fn new(inner: S, observer: F) -> Self {
+        Self {
+            inner,
+            observer,
+            redaction: Redaction::default(),
+        }
+    }
+
+    /// Overwrite the [`Redaction`] used for the observed requests.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Overwrite the [`Redaction`] used for the observed requests.
+    pub fn set_redaction(&mut self, redaction: Redaction) -> &mut Self {
+        self.redaction = redaction;
+        self
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<ReqBody, S, F, State> Service<State, Request<ReqBody>> for InspectRequest<S, F>
+where
+    S: Service<State, Request<ReqBody>>,
+    F: Fn(&Request<()>) + Send + Sync + 'static,
+    State: Clone + Send + Sync + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request<ReqBody>,
+    ) -> Result<Self::Response, Self::Error> {
+        let mut observed = Request::new(());
+        *observed.method_mut() = req.method().clone();
+        *observed.uri_mut() = req.uri().clone();
+        *observed.version_mut() = req.version();
+        *observed.headers_mut() = self.redaction.redact(req.headers());
+        (self.observer)(&observed);
+
+        self.inner.serve(ctx, req).await
+    }
+}
+
+/// Layer that applies [`InspectResponse`], which calls an observer
+/// with a redacted, body-less copy of each response.
+///
+/// See the [module docs](crate::layer::inspect) for more details.
+#[derive(Clone)]
+pub struct InspectResponseLayer<F> {
+    observer: F,
+    redaction: Redaction,
+}
+
+impl<F> fmt::Debug for InspectResponseLayer<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InspectResponseLayer")
+            .field("observer", &std::any::type_name::<F>())
+            .field("redaction", &self.redaction)
+            .finish()
+    }
+}
+
+impl<F> InspectResponseLayer<F> {
+    /// Create a new [`InspectResponseLayer`], using the default [`Redaction`].
+    pub fn new(observer: F) -> Self {
+        Self {
+            observer,
+            redaction: Redaction::default(),
+        }
+    }
+
+    /// Overwrite the [`Redaction`] used for the observed responses.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Overwrite the [`Redaction`] used for the observed responses.
+    pub fn set_redaction(&mut self, redaction: Redaction) -> &mut Self {
+        self.redaction = redaction;
+        self
+    }
+}
+
+impl<S, F: Clone> Layer<S> for InspectResponseLayer<F> {
+    type Service = InspectResponse<S, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InspectResponse {
+            inner,
+            observer: self.observer.clone(),
+            redaction: self.redaction.clone(),
+        }
+    }
+}
+
+/// Middleware that calls an observer with a redacted,
+/// body-less copy of each (successful) response.
+///
+/// See the [module docs](crate::layer::inspect) for more details.
+#[derive(Clone)]
+pub struct InspectResponse<S, F> {
+    inner: S,
+    observer: F,
+    redaction: Redaction,
+}
+
+impl<S: fmt::Debug, F> fmt::Debug for InspectResponse<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InspectResponse")
+            .field("inner", &self.inner)
+            .field("observer", &std::any::type_name::<F>())
+            .field("redaction", &self.redaction)
+            .finish()
+    }
+}
+
+impl<S, F> InspectResponse<S, F> {
+    /// Create a new [`InspectResponse`], using the default [`Redaction`].
+    pub fn new(inner: S, observer: F) -> Self {
+        Self {
+            inner,
+            observer,
+            redaction: Redaction::default(),
+        }
+    }
+
+    /// Overwrite the [`Redaction`] used for the observed responses.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Overwrite the [`Redaction`] used for the observed responses.
+    pub fn set_redaction(&mut self, redaction: Redaction) -> &mut Self {
+        self.redaction = redaction;
+        self
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<ReqBody, ResBody, S, F, State> Service<State, Request<ReqBody>> for InspectResponse<S, F>
+where
+    S: Service<State, Request<ReqBody>, Response = Response<ResBody>>,
+    F: Fn(&Response<()>) + Send + Sync + 'static,
+    State: Clone + Send + Sync + 'static,
+    ReqBody: Send + 'static,
+    ResBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request<ReqBody>,
+    ) -> Result<Self::Response, Self::Error> {
+        let res = self.inner.serve(ctx, req).await?;
+
+        let mut observed = Response::new(());
+        *observed.status_mut() = res.status();
+        *observed.version_mut() = res.version();
+        *observed.headers_mut() = self.redaction.redact(res.headers());
+        (self.observer)(&observed);
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{header::CONTENT_TYPE, Body};
+    use rama_core::service::service_fn;
+    use std::{
+        convert::Infallible,
+        sync::{Arc, Mutex},
+    };
+
+    #[tokio::test]
+    async fn test_inspect_redacts_observed_copy_only() {
+        let observed_req = Arc::new(Mutex::new(None));
+        let observed_res = Arc::new(Mutex::new(None));
+
+        let svc = (
+            InspectRequestLayer::new({
+                let observed = observed_req.clone();
+                move |req: &Request<()>| {
+                    *observed.lock().unwrap() = Some(req.headers().clone());
+                }
+            }),
+            InspectResponseLayer::new({
+                let observed = observed_res.clone();
+                move |res: &Response<()>| {
+                    *observed.lock().unwrap() = Some(res.headers().clone());
+                }
+            }),
+        )
+            .layer(service_fn(|req: Request| async move {
+                // downstream gets the original values
+                assert_eq!(req.headers()[AUTHORIZATION], "Bearer secret");
+                assert_eq!(req.headers()[COOKIE], "session=secret");
+                assert_eq!(req.headers()[CONTENT_TYPE], "text/plain");
+                Ok::<_, Infallible>(
+                    Response::builder()
+                        .header(SET_COOKIE, "session=secret")
+                        .header(SET_COOKIE, "theme=dark")
+                        .header(CONTENT_TYPE, "text/plain")
+                        .body(Body::from("hello"))
+                        .unwrap(),
+                )
+            }));
+
+        let req = Request::builder()
+            .header(AUTHORIZATION, "Bearer secret")
+            .header(COOKIE, "session=secret")
+            .header(CONTENT_TYPE, "text/plain")
+            .body(Body::empty())
+            .unwrap();
+        let res = svc.serve(Context::default(), req).await.unwrap();
+
+        // caller gets the original values
+        let values: Vec<_> = res.headers().get_all(SET_COOKIE).iter().collect();
+        assert_eq!(values, ["session=secret", "theme=dark"]);
+
+        let observed = observed_req.lock().unwrap().take().unwrap();
+        assert_eq!(observed[AUTHORIZATION], "[REDACTED]");
+        assert_eq!(observed[COOKIE], "[REDACTED]");
+        assert_eq!(observed[CONTENT_TYPE], "text/plain");
+
+        let observed = observed_res.lock().unwrap().take().unwrap();
+        let values: Vec<_> = observed.get_all(SET_COOKIE).iter().collect();
+        assert_eq!(values, ["[REDACTED]", "[REDACTED]"]);
+        assert_eq!(observed[CONTENT_TYPE], "text/plain");
+    }
+
+    #[tokio::test]
+    async fn test_inspect_custom_redaction() {
+        let observed_req = Arc::new(Mutex::new(None));
+
+        let svc = InspectRequestLayer::new({
+            let observed = observed_req.clone();
+            move |req: &Request<()>| {
+                *observed.lock().unwrap() = Some(req.headers().clone());
+            }
+        })
+        .with_redaction(Redaction::none().with_pushed_header(HeaderName::from_static("x-api-key")))
+        .layer(service_fn(|_req: Request| async move {
+            Ok::<_, Infallible>(Response::new(Body::empty()))
+        }));
+
+        let req = Request::builder()
+            .header(AUTHORIZATION, "Bearer secret")
+            .header("x-api-key", "secret")
+            .body(Body::empty())
+            .unwrap();
+        svc.serve(Context::default(), req).await.unwrap();
+
+        let observed = observed_req.lock().unwrap().take().unwrap();
+        assert_eq!(observed[AUTHORIZATION], "Bearer secret");
+        assert_eq!(observed["x-api-key"], "[REDACTED]");
+    }
+}
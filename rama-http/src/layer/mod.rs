@@ -17,23 +17,30 @@
 //! [`Service`]: rama_core::Service
 
 pub mod auth;
+pub mod body_digest;
 pub mod body_limit;
 pub mod catch_panic;
 pub mod classify;
 pub mod collect_body;
+pub mod content_length;
+pub mod content_sniff;
 pub mod cors;
+pub mod dedup;
 pub mod dns;
 pub mod error_handling;
 pub mod follow_redirect;
 pub mod forwarded;
 pub mod header_config;
 pub mod header_option_value;
+pub mod inspect;
 pub mod map_request_body;
 pub mod map_response_body;
 pub mod normalize_path;
 pub mod propagate_headers;
 pub mod proxy_auth;
+pub mod rate_limit_headers;
 pub mod remove_header;
+pub mod reporting_headers;
 pub mod request_id;
 pub mod required_header;
 pub mod retry;
@@ -49,6 +56,9 @@ pub mod validate_request;
 #[cfg(feature = "telemetry")]
 pub mod opentelemetry;
 
+#[cfg(feature = "crypto")]
+pub mod idempotency;
+
 pub(crate) mod util;
 
 #[cfg(feature = "compression")]
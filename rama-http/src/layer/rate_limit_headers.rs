@@ -0,0 +1,334 @@
+//! Middleware to communicate the rate limit state to the client using response headers.
+//!
+//! The [`RateLimitState`] inserted into the [`Context`] by a rate limit policy,
+//! such as the [`TokenBucketPolicy`], is added to the response using the
+//! `RateLimit` and `RateLimit-Policy` headers of the [IETF RateLimit header fields draft],
+//! and optionally the legacy `X-RateLimit-*` headers.
+//!
+//! Requests rejected by the [`TokenBucketPolicy`] never reach this middleware,
+//! use [`RateLimitHeadersLayer::rate_limited_response`] to turn the
+//! resulting [`RateLimited`] error into a `429 Too Many Requests` response
+//! with the same headers.
+//!
+//! [IETF RateLimit header fields draft]: https://datatracker.ietf.org/doc/html/draft-ietf-httpapi-ratelimit-headers-07
+//!
+//! # Example
+//!
+//! ```
+//! use rama_core::layer::limit::{policy::TokenBucketPolicy, Limit};
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Layer, Service};
+//! use rama_http::layer::rate_limit_headers::RateLimitHeadersLayer;
+//! use rama_http::{Body, Request, Response, StatusCode};
+//! use std::{convert::Infallible, time::Duration};
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let headers_layer = RateLimitHeadersLayer::new();
+//! let service = Limit::new(
+//!     headers_layer
+//!         .clone()
+//!         .layer(service_fn(|_req: Request| async move {
+//!             Ok::<_, Infallible>(Response::new(Body::empty()))
+//!         })),
+//!     TokenBucketPolicy::new(1, Duration::from_secs(60)),
+//! )
+//! .with_error_into_response_fn(move |err| {
+//!     Ok::<_, Infallible>(headers_layer.rate_limited_response(&err))
+//! });
+//!
+//! let response = service
+//!     .serve(Context::default(), Request::new(Body::empty()))
+//!     .await
+//!     .unwrap();
+//! assert_eq!(response.headers()["ratelimit"], "limit=1, remaining=0, reset=60");
+//! assert_eq!(response.headers()["ratelimit-policy"], "1;w=60");
+//!
+//! let response = service
+//!     .serve(Context::default(), Request::new(Body::empty()))
+//!     .await
+//!     .unwrap();
+//! assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+//! assert_eq!(response.headers()["retry-after"], "60");
+//! # }
+//! ```
+//!
+//! [`TokenBucketPolicy`]: rama_core::layer::limit::policy::TokenBucketPolicy
+
+use crate::{
+    header::RETRY_AFTER, Body, HeaderMap, HeaderName, HeaderValue, Request, Response, StatusCode,
+};
+use rama_core::{
+    layer::limit::policy::{RateLimitState, RateLimited},
+    Context, Layer, Service,
+};
+use rama_utils::macros::define_inner_service_accessors;
+use std::{fmt, time::Duration};
+
+static RATELIMIT: HeaderName = HeaderName::from_static("ratelimit");
+static RATELIMIT_POLICY: HeaderName = HeaderName::from_static("ratelimit-policy");
+static X_RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+static X_RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+static X_RATELIMIT_RESET: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+
+/// Layer that applies [`RateLimitHeaders`] which adds the rate limit headers to responses.
+///
+/// See the [module docs](self) for more details.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitHeadersLayer {
+    legacy_headers: bool,
+}
+
+impl RateLimitHeadersLayer {
+    /// Create a new [`RateLimitHeadersLayer`].
+    pub const fn new() -> Self {
+        Self {
+            legacy_headers: false,
+        }
+    }
+
+    /// Define whether or not the legacy `X-RateLimit-Limit`, `X-RateLimit-Remaining`
+    /// and `X-RateLimit-Reset` headers are added as well.
+    ///
+    /// Disabled by default.
+    pub const fn with_legacy_headers(mut self, enabled: bool) -> Self {
+        self.legacy_headers = enabled;
+        self
+    }
+
+    /// Define whether or not the legacy `X-RateLimit-Limit`, `X-RateLimit-Remaining`
+    /// and `X-RateLimit-Reset` headers are added as well.
+    ///
+    /// Disabled by default.
+    pub fn set_legacy_headers(&mut self, enabled: bool) -> &mut Self {
+        self.legacy_headers = enabled;
+        self
+    }
+
+    /// Create a `429 Too Many Requests` [`Response`] for the given [`RateLimited`] error,
+    /// containing the rate limit headers and a `Retry-After` header.
+    pub fn rate_limited_response(&self, err: &RateLimited) -> Response {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+        let headers = response.headers_mut();
+        insert_rate_limit_headers(headers, err.state(), self.legacy_headers);
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from(seconds_ceil(err.retry_after())),
+        );
+        response
+    }
+}
+
+impl<S> Layer<S> for RateLimitHeadersLayer {
+    type Service = RateLimitHeaders<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitHeaders {
+            inner,
+            legacy_headers: self.legacy_headers,
+        }
+    }
+}
+
+/// Middleware which adds the [`RateLimitState`] found in the [`Context`]
+/// as rate limit headers to the response.
+///
+/// See the [module docs](self) for more details.
+pub struct RateLimitHeaders<S> {
+    inner: S,
+    legacy_headers: bool,
+}
+
+impl<S> RateLimitHeaders<S> {
+    /// Create a new [`RateLimitHeaders`].
+    pub const fn new(inner: S) -> Self {
+        Self {
+            inner,
+            legacy_headers: false,
+        }
+    }
+
+    /// Define whether or not the legacy `X-RateLimit-Limit`, `X-RateLimit-Remaining`
+    /// and `X-RateLimit-Reset` headers are added as well.
+    ///
+    /// Disabled by default.
+    pub fn with_legacy_headers(mut self, enabled: bool) -> Self {
+        self.legacy_headers = enabled;
+        self
+    }
+
+    /// Define whether or not the legacy `X-RateLimit-Limit`, `X-RateLimit-Remaining`
+    /// and `X-RateLimit-Reset` headers are added as well.
+    ///
+    /// Disabled by default.
+    pub fn set_legacy_headers(&mut self, enabled: bool) -> &mut Self {
+        self.legacy_headers = enabled;
+        self
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S: fmt::Debug> fmt::Debug for RateLimitHeaders<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimitHeaders")
+            .field("inner", &self.inner)
+            .field("legacy_headers", &self.legacy_headers)
+            .finish()
+    }
+}
+
+impl<S: Clone> Clone for RateLimitHeaders<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            legacy_headers: self.legacy_headers,
+        }
+    }
+}
+
+impl<State, S, ReqBody, ResBody> Service<State, Request<ReqBody>> for RateLimitHeaders<S>
+where
+    State: Clone + Send + Sync + 'static,
+    S: Service<State, Request<ReqBody>, Response = Response<ResBody>>,
+    ReqBody: Send + 'static,
+    ResBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request<ReqBody>,
+    ) -> Result<Self::Response, Self::Error> {
+        let state = ctx.get::<RateLimitState>().copied();
+        let mut response = self.inner.serve(ctx, req).await?;
+        if let Some(state) = state {
+            insert_rate_limit_headers(response.headers_mut(), &state, self.legacy_headers);
+        }
+        Ok(response)
+    }
+}
+
+fn insert_rate_limit_headers(headers: &mut HeaderMap, state: &RateLimitState, legacy: bool) {
+    let reset = seconds_ceil(state.reset);
+    if let Ok(value) = HeaderValue::try_from(format!(
+        "limit={}, remaining={}, reset={reset}",
+        state.limit, state.remaining
+    )) {
+        headers.insert(RATELIMIT.clone(), value);
+    }
+    if let Ok(value) =
+        HeaderValue::try_from(format!("{};w={}", state.limit, seconds_ceil(state.window)))
+    {
+        headers.insert(RATELIMIT_POLICY.clone(), value);
+    }
+
+    if legacy {
+        headers.insert(X_RATELIMIT_LIMIT.clone(), HeaderValue::from(state.limit));
+        headers.insert(
+            X_RATELIMIT_REMAINING.clone(),
+            HeaderValue::from(state.remaining),
+        );
+        headers.insert(X_RATELIMIT_RESET.clone(), HeaderValue::from(reset));
+    }
+}
+
+/// Delta-seconds as used by the headers, rounded up to not
+/// invite clients to retry (a bit) too early.
+fn seconds_ceil(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::{
+        layer::limit::{policy::TokenBucketPolicy, Limit},
+        service::service_fn,
+    };
+    use std::convert::Infallible;
+
+    async fn echo(_req: Request) -> Result<Response, Infallible> {
+        Ok(Response::new(Body::empty()))
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_headers_decrement() {
+        let layer = RateLimitHeadersLayer::new().with_legacy_headers(true);
+        let service = Limit::new(
+            layer.clone().layer(service_fn(echo)),
+            TokenBucketPolicy::new(3, Duration::from_secs(60)),
+        )
+        .with_error_into_response_fn(move |err| {
+            Ok::<_, Infallible>(layer.rate_limited_response(&err))
+        });
+
+        for (remaining, reset) in [("2", "20"), ("1", "40"), ("0", "60")] {
+            let response = service
+                .serve(Context::default(), Request::new(Body::empty()))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let headers = response.headers();
+            assert_eq!(
+                headers["ratelimit"],
+                format!("limit=3, remaining={remaining}, reset={reset}")
+            );
+            assert_eq!(headers["ratelimit-policy"], "3;w=60");
+            assert_eq!(headers["x-ratelimit-limit"], "3");
+            assert_eq!(headers["x-ratelimit-remaining"], remaining);
+            assert_eq!(headers["x-ratelimit-reset"], reset);
+            assert!(headers.get(RETRY_AFTER).is_none());
+        }
+
+        let response = service
+            .serve(Context::default(), Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let headers = response.headers();
+        assert_eq!(headers["ratelimit"], "limit=3, remaining=0, reset=60");
+        assert_eq!(headers["ratelimit-policy"], "3;w=60");
+        assert_eq!(headers["x-ratelimit-remaining"], "0");
+        assert_eq!(headers[RETRY_AFTER], "20");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_headers_without_state() {
+        let service = RateLimitHeaders::new(service_fn(echo));
+
+        let response = service
+            .serve(Context::default(), Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert!(response.headers().get("ratelimit").is_none());
+        assert!(response.headers().get("ratelimit-policy").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_headers_no_legacy_by_default() {
+        let service = RateLimitHeaders::new(service_fn(echo));
+
+        let mut ctx = Context::default();
+        ctx.insert(RateLimitState {
+            limit: 10,
+            remaining: 5,
+            reset: Duration::from_millis(2500),
+            window: Duration::from_secs(5),
+        });
+        let response = service
+            .serve(ctx, Request::new(Body::empty()))
+            .await
+            .unwrap();
+
+        let headers = response.headers();
+        assert_eq!(headers["ratelimit"], "limit=10, remaining=5, reset=3");
+        assert_eq!(headers["ratelimit-policy"], "10;w=5");
+        assert!(headers.get("x-ratelimit-limit").is_none());
+    }
+}
@@ -0,0 +1,237 @@
+//! Middleware to configure client side error reporting using response headers.
+//!
+//! The [`ReportingHeadersLayer`] adds the configured [`Nel`] (Network Error Logging),
+//! [`ReportTo`] and [`ReportingEndpoints`] headers to all responses,
+//! such that user agents report network errors (e.g. dns, tcp or tls failures)
+//! and other reports (e.g. CSP violations) to the configured endpoints.
+//!
+//! Headers already set by the inner service are left untouched.
+//!
+//! The reports can be received using a [`ReportCollector`] endpoint.
+//!
+//! # Example
+//!
+//! ```
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Layer, Service};
+//! use rama_http::headers::{Nel, ReportTo, ReportToGroup, ReportingEndpoints};
+//! use rama_http::layer::reporting_headers::ReportingHeadersLayer;
+//! use rama_http::{Body, Request, Response};
+//! use std::{convert::Infallible, time::Duration};
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let max_age = Duration::from_secs(86400);
+//! let service = ReportingHeadersLayer::new()
+//!     .with_nel(Nel::new("default", max_age))
+//!     .with_report_to(ReportTo::new(ReportToGroup::new(
+//!         max_age,
+//!         "https://example.com/reports",
+//!     )))
+//!     .with_reporting_endpoints(
+//!         ReportingEndpoints::try_new("default", "https://example.com/reports").unwrap(),
+//!     )
+//!     .layer(service_fn(|_req: Request| async move {
+//!         Ok::<_, Infallible>(Response::new(Body::empty()))
+//!     }));
+//!
+//! let response = service
+//!     .serve(Context::default(), Request::new(Body::empty()))
+//!     .await
+//!     .unwrap();
+//! assert_eq!(response.headers()["nel"], r#"{"report_to":"default","max_age":86400}"#);
+//! assert_eq!(
+//!     response.headers()["reporting-endpoints"],
+//!     r#"default="https://example.com/reports""#,
+//! );
+//! # }
+//! ```
+//!
+//! [`ReportCollector`]: crate::service::web::reporting::ReportCollector
+
+use crate::headers::{Header, HeaderExt, Nel, ReportTo, ReportingEndpoints};
+use crate::{HeaderMap, Request, Response};
+use rama_core::{Context, Layer, Service};
+use rama_utils::macros::define_inner_service_accessors;
+use std::{fmt, sync::Arc};
+
+/// Layer that applies [`ReportingHeaders`] which adds the configured
+/// reporting headers to responses.
+///
+/// See the [module docs](self) for more details.
+#[derive(Debug, Clone, Default)]
+pub struct ReportingHeadersLayer {
+    headers: HeaderMap,
+}
+
+impl ReportingHeadersLayer {
+    /// Create a new [`ReportingHeadersLayer`], without any headers configured.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the [`Nel`] header to be added to responses.
+    pub fn with_nel(mut self, nel: Nel) -> Self {
+        self.set_nel(nel);
+        self
+    }
+
+    /// Set the [`Nel`] header to be added to responses.
+    pub fn set_nel(&mut self, nel: Nel) -> &mut Self {
+        self.set_header(nel)
+    }
+
+    /// Set the [`ReportTo`] header to be added to responses.
+    pub fn with_report_to(mut self, report_to: ReportTo) -> Self {
+        self.set_report_to(report_to);
+        self
+    }
+
+    /// Set the [`ReportTo`] header to be added to responses.
+    pub fn set_report_to(&mut self, report_to: ReportTo) -> &mut Self {
+        self.set_header(report_to)
+    }
+
+    /// Set the [`ReportingEndpoints`] header to be added to responses.
+    pub fn with_reporting_endpoints(mut self, endpoints: ReportingEndpoints) -> Self {
+        self.set_reporting_endpoints(endpoints);
+        self
+    }
+
+    /// Set the [`ReportingEndpoints`] header to be added to responses.
+    pub fn set_reporting_endpoints(&mut self, endpoints: ReportingEndpoints) -> &mut Self {
+        self.set_header(endpoints)
+    }
+
+    fn set_header<H: Header>(&mut self, header: H) -> &mut Self {
+        self.headers
+            .insert(H::name().clone(), header.encode_to_value());
+        self
+    }
+}
+
+impl<S> Layer<S> for ReportingHeadersLayer {
+    type Service = ReportingHeaders<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ReportingHeaders {
+            inner,
+            headers: Arc::new(self.headers.clone()),
+        }
+    }
+}
+
+/// Middleware which adds the configured reporting headers to responses.
+///
+/// See the [module docs](self) for more details.
+pub struct ReportingHeaders<S> {
+    inner: S,
+    headers: Arc<HeaderMap>,
+}
+
+impl<S> ReportingHeaders<S> {
+    define_inner_service_accessors!();
+}
+
+impl<S: fmt::Debug> fmt::Debug for ReportingHeaders<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReportingHeaders")
+            .field("inner", &self.inner)
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+impl<S: Clone> Clone for ReportingHeaders<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            headers: self.headers.clone(),
+        }
+    }
+}
+
+impl<State, S, ReqBody, ResBody> Service<State, Request<ReqBody>> for ReportingHeaders<S>
+where
+    State: Clone + Send + Sync + 'static,
+    S: Service<State, Request<ReqBody>, Response = Response<ResBody>>,
+    ReqBody: Send + 'static,
+    ResBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request<ReqBody>,
+    ) -> Result<Self::Response, Self::Error> {
+        let mut response = self.inner.serve(ctx, req).await?;
+        let headers = response.headers_mut();
+        for (name, value) in self.headers.iter() {
+            if !headers.contains_key(name) {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headers::ReportToGroup;
+    use crate::{Body, HeaderValue};
+    use rama_core::service::service_fn;
+    use std::{convert::Infallible, time::Duration};
+
+    #[tokio::test]
+    async fn test_reporting_headers() {
+        let service = ReportingHeadersLayer::new()
+            .with_nel(Nel::new("nel", Duration::from_secs(60)).with_failure_fraction(0.5))
+            .with_report_to(ReportTo::new(
+                ReportToGroup::new(Duration::from_secs(60), "https://example.com/nel")
+                    .with_name("nel"),
+            ))
+            .layer(service_fn(|req: Request| async move {
+                let mut response = Response::new(Body::empty());
+                if req.uri().path() == "/custom" {
+                    response.headers_mut().insert(
+                        crate::header::NEL.clone(),
+                        HeaderValue::from_static(r#"{"report_to":"nel","max_age":0}"#),
+                    );
+                }
+                Ok::<_, Infallible>(response)
+            }));
+
+        let response = service
+            .serve(Context::default(), Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()["nel"],
+            r#"{"report_to":"nel","max_age":60,"failure_fraction":0.5}"#
+        );
+        assert_eq!(
+            response.headers()["report-to"],
+            r#"{"group":"nel","max_age":60,"endpoints":[{"url":"https://example.com/nel"}]}"#
+        );
+        assert!(!response.headers().contains_key("reporting-endpoints"));
+
+        let response = service
+            .serve(
+                Context::default(),
+                Request::builder()
+                    .uri("/custom")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()["nel"],
+            r#"{"report_to":"nel","max_age":0}"#
+        );
+        assert!(response.headers().contains_key("report-to"));
+    }
+}
@@ -5,12 +5,14 @@ use std::fmt;
 /// Retry requests based on a policy
 pub struct RetryLayer<P> {
     policy: P,
+    max_buffer_size: Option<usize>,
 }
 
 impl<P: fmt::Debug> fmt::Debug for RetryLayer<P> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("RetryLayer")
             .field("policy", &self.policy)
+            .field("max_buffer_size", &self.max_buffer_size)
             .finish()
     }
 }
@@ -19,6 +21,7 @@ impl<P: Clone> Clone for RetryLayer<P> {
     fn clone(&self) -> Self {
         Self {
             policy: self.policy.clone(),
+            max_buffer_size: self.max_buffer_size,
         }
     }
 }
@@ -26,7 +29,28 @@ impl<P: Clone> Clone for RetryLayer<P> {
 impl<P> RetryLayer<P> {
     /// Creates a new [`RetryLayer`] from a retry policy.
     pub const fn new(policy: P) -> Self {
-        RetryLayer { policy }
+        RetryLayer {
+            policy,
+            max_buffer_size: None,
+        }
+    }
+
+    /// Set the maximum size of the request body that is buffered,
+    /// in order to be able to replay it when retrying the request.
+    ///
+    /// See [`Retry::with_max_buffer_size`] for more information.
+    pub fn with_max_buffer_size(mut self, size: usize) -> Self {
+        self.max_buffer_size = Some(size);
+        self
+    }
+
+    /// Set the maximum size of the request body that is buffered,
+    /// in order to be able to replay it when retrying the request.
+    ///
+    /// See [`Retry::set_max_buffer_size`] for more information.
+    pub fn set_max_buffer_size(&mut self, size: usize) -> &mut Self {
+        self.max_buffer_size = Some(size);
+        self
     }
 }
 
@@ -38,6 +62,10 @@ where
 
     fn layer(&self, service: S) -> Self::Service {
         let policy = self.policy.clone();
-        Retry::new(policy, service)
+        Retry {
+            policy,
+            inner: service,
+            max_buffer_size: self.max_buffer_size,
+        }
     }
 }
@@ -1,7 +1,7 @@
 //! Middleware for retrying "failed" requests.
 
 use crate::dep::http_body::Body as HttpBody;
-use crate::dep::http_body_util::BodyExt;
+use crate::dep::http_body_util::{BodyExt, LengthLimitError, Limited};
 use crate::Request;
 use rama_core::error::BoxError;
 use rama_core::{Context, Service};
@@ -29,6 +29,7 @@ pub use self::policy::{Policy, PolicyResult};
 pub struct Retry<P, S> {
     policy: P,
     inner: S,
+    max_buffer_size: Option<usize>,
 }
 
 impl<P, S> std::fmt::Debug for Retry<P, S>
@@ -40,6 +41,7 @@ where
         f.debug_struct("Retry")
             .field("policy", &self.policy)
             .field("inner", &self.inner)
+            .field("max_buffer_size", &self.max_buffer_size)
             .finish()
     }
 }
@@ -53,6 +55,7 @@ where
         Retry {
             policy: self.policy.clone(),
             inner: self.inner.clone(),
+            max_buffer_size: self.max_buffer_size,
         }
     }
 }
@@ -65,9 +68,34 @@ impl<P, S> Retry<P, S> {
         Retry {
             policy,
             inner: service,
+            max_buffer_size: None,
         }
     }
 
+    /// Set the maximum size of the request body that is buffered,
+    /// in order to be able to replay it when retrying the request.
+    ///
+    /// Requests with a body exceeding this limit are refused with a [`RetryError`],
+    /// without being served by the inner service.
+    ///
+    /// By default the request body is buffered without any limit.
+    pub fn with_max_buffer_size(mut self, size: usize) -> Self {
+        self.max_buffer_size = Some(size);
+        self
+    }
+
+    /// Set the maximum size of the request body that is buffered,
+    /// in order to be able to replay it when retrying the request.
+    ///
+    /// Requests with a body exceeding this limit are refused with a [`RetryError`],
+    /// without being served by the inner service.
+    ///
+    /// By default the request body is buffered without any limit.
+    pub fn set_max_buffer_size(&mut self, size: usize) -> &mut Self {
+        self.max_buffer_size = Some(size);
+        self
+    }
+
     define_inner_service_accessors!();
 }
 
@@ -81,6 +109,7 @@ pub struct RetryError {
 #[derive(Debug)]
 enum RetryErrorKind {
     BodyConsume,
+    BodyTooLarge,
     Service,
 }
 
@@ -97,11 +126,20 @@ impl std::fmt::Display for RetryErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RetryErrorKind::BodyConsume => write!(f, "failed to consume body"),
+            RetryErrorKind::BodyTooLarge => write!(f, "body too large to buffer for retries"),
             RetryErrorKind::Service => write!(f, "service error"),
         }
     }
 }
 
+impl RetryError {
+    /// Returns `true` if the request was refused because its body
+    /// exceeds the maximum buffer size of the [`Retry`] service.
+    pub fn is_body_too_large(&self) -> bool {
+        matches!(self.kind, RetryErrorKind::BodyTooLarge)
+    }
+}
+
 impl std::error::Error for RetryError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         self.inner.as_ref().and_then(|e| e.source())
@@ -127,10 +165,33 @@ where
 
         // consume body so we can clone the request if desired
         let (parts, body) = request.into_parts();
-        let body = body.collect().await.map_err(|e| RetryError {
-            kind: RetryErrorKind::BodyConsume,
-            inner: Some(e.into()),
-        })?;
+        let body = match self.max_buffer_size {
+            Some(limit) => {
+                if body.size_hint().lower() > limit as u64 {
+                    return Err(RetryError {
+                        kind: RetryErrorKind::BodyTooLarge,
+                        inner: None,
+                    });
+                }
+                Limited::new(body, limit).collect().await.map_err(|e| {
+                    if e.is::<LengthLimitError>() {
+                        RetryError {
+                            kind: RetryErrorKind::BodyTooLarge,
+                            inner: None,
+                        }
+                    } else {
+                        RetryError {
+                            kind: RetryErrorKind::BodyConsume,
+                            inner: Some(e),
+                        }
+                    }
+                })?
+            }
+            None => body.collect().await.map_err(|e| RetryError {
+                kind: RetryErrorKind::BodyConsume,
+                inner: Some(e.into()),
+            })?,
+        };
         let body = RetryBody::new(body.to_bytes());
         let mut request = Request::from_parts(parts, body);
 
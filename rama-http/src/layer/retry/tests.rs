@@ -223,6 +223,97 @@ async fn retry_mutating_policy() {
     assert_eq!(response_counter.load(Ordering::Acquire), 3);
 }
 
+#[tokio::test]
+async fn retry_buffered_streaming_body() {
+    struct Svc {
+        errored: AtomicBool,
+    }
+
+    impl Service<State, Request<RetryBody>> for Svc {
+        type Response = Response;
+        type Error = OpaqueError;
+
+        async fn serve(
+            &self,
+            _ctx: Context<State>,
+            req: Request<RetryBody>,
+        ) -> Result<Self::Response, Self::Error> {
+            assert_eq!(req.try_into_string().await.unwrap(), "hello world");
+            if self.errored.swap(true, Ordering::AcqRel) {
+                Ok("ok".into_response())
+            } else {
+                Err(error!("retry me"))
+            }
+        }
+    }
+
+    let svc = RetryLayer::new(RetryErrors)
+        .with_max_buffer_size(32)
+        .layer(Svc {
+            errored: AtomicBool::new(false),
+        });
+
+    let resp = svc
+        .serve(
+            Context::default(),
+            streaming_request(&["hello", " ", "world"]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.try_into_string().await.unwrap(), "ok");
+}
+
+#[tokio::test]
+async fn retry_refuses_to_buffer_body_over_limit() {
+    let served = Arc::new(AtomicUsize::new(0));
+    let svc =
+        RetryLayer::new(RetryErrors)
+            .with_max_buffer_size(8)
+            .layer(rama_core::service::service_fn({
+                let served = served.clone();
+                move |_ctx, _req: Request<RetryBody>| {
+                    let served = served.clone();
+                    async move {
+                        served.fetch_add(1, Ordering::AcqRel);
+                        Ok::<_, OpaqueError>("ok".into_response())
+                    }
+                }
+            }));
+
+    // size only known while streaming
+    let err = svc
+        .serve(
+            Context::default(),
+            streaming_request(&["hello", " ", "world"]),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.is_body_too_large(), "{err}");
+
+    // size known upfront
+    let req = Request::builder()
+        .method("POST")
+        .uri("http://localhost")
+        .body(crate::Body::from("hello world"))
+        .unwrap();
+    let err = svc.serve(Context::default(), req).await.unwrap_err();
+    assert!(err.is_body_too_large(), "{err}");
+
+    assert_eq!(served.load(Ordering::Acquire), 0);
+}
+
+fn streaming_request(chunks: &[&'static str]) -> Request {
+    let chunks: Vec<Result<_, std::io::Error>> = chunks
+        .iter()
+        .map(|chunk| Ok(bytes::Bytes::from_static(chunk.as_bytes())))
+        .collect();
+    Request::builder()
+        .method("POST")
+        .uri("http://localhost")
+        .body(crate::Body::from_stream(futures_lite::stream::iter(chunks)))
+        .unwrap()
+}
+
 type State = ();
 type InnerError = &'static str;
 type Error = rama_core::error::OpaqueError;
@@ -47,8 +47,8 @@ use std::{
 };
 
 pub use rama_ua::{
-    DeviceKind, HttpAgent, PlatformKind, TlsAgent, UserAgent, UserAgentInfo, UserAgentKind,
-    UserAgentOverwrites,
+    DeviceClass, DeviceKind, HttpAgent, PlatformKind, TlsAgent, UserAgent, UserAgentInfo,
+    UserAgentKind, UserAgentOverwrites,
 };
 
 /// A [`Service`] that classifies the [`UserAgent`] of incoming [`Request`]s.
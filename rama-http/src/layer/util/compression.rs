@@ -231,6 +231,7 @@ pin_project! {
         body: B,
         yielded_all_data: bool,
         non_data_frame: Option<Frame<B::Data>>,
+        data_len: usize,
     }
 }
 
@@ -244,9 +245,15 @@ where
             body,
             yielded_all_data: false,
             non_data_frame: None,
+            data_len: 0,
         }
     }
 
+    /// Get the amount of data bytes yielded so far
+    pub(crate) fn data_len(&self) -> usize {
+        self.data_len
+    }
+
     /// Get a reference to the inner body
     pub(crate) fn get_ref(&self) -> &B {
         &self.body
@@ -284,7 +291,10 @@ where
 
             match std::task::ready!(this.body.poll_frame(cx)) {
                 Some(Ok(frame)) => match frame.into_data() {
-                    Ok(data) => return Poll::Ready(Some(Ok(data))),
+                    Ok(data) => {
+                        *this.data_len = this.data_len.saturating_add(data.remaining());
+                        return Poll::Ready(Some(Ok(data)));
+                    }
                     Err(frame) => {
                         *this.yielded_all_data = true;
                         *this.non_data_frame = Some(frame);
@@ -347,6 +357,11 @@ impl<S, E> StreamErrorIntoIoError<S, E> {
         Self { inner, error: None }
     }
 
+    /// Get a reference to the inner inner
+    pub(crate) fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
     /// Get a pinned mutable reference to the inner inner
     pub(crate) fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
         self.project().inner
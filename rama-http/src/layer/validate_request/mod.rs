@@ -138,6 +138,8 @@
 //! ```
 
 mod accept_header;
+mod precondition;
+mod request_validator;
 mod validate;
 mod validate_fn;
 mod validate_request_header;
@@ -145,6 +147,16 @@ mod validate_request_header;
 #[doc(inline)]
 pub use accept_header::AcceptHeader;
 #[doc(inline)]
+pub use precondition::{
+    PreconditionFailed, PreconditionLayer, PreconditionRejection, PreconditionRequired,
+    PreconditionValidator, ResourceVersion, ResourceVersionProvider,
+};
+#[doc(inline)]
+pub use request_validator::{
+    MissingRequiredHeader, MissingRequiredQueryParam, RequestBodyTooLarge,
+    RequestValidationRejection, RequestValidator, UnsupportedContentType, ValidateRequestLayer,
+};
+#[doc(inline)]
 pub use validate::ValidateRequest;
 #[doc(inline)]
 pub use validate_fn::{BoxValidateRequestFn, ValidateRequestFn};
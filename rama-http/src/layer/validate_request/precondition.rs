@@ -0,0 +1,389 @@
+use super::{ValidateRequest, ValidateRequestHeaderLayer};
+use crate::headers::{ETag, HeaderMapExt, IfMatch, IfUnmodifiedSince};
+use crate::utils::macros::{composite_http_rejection, define_http_rejection};
+use crate::{IntoResponse, Method, Request, Response};
+use rama_core::Context;
+use std::future::Future;
+use std::time::SystemTime;
+
+define_http_rejection! {
+    #[status = PRECONDITION_FAILED]
+    #[body = "Precondition failed"]
+    /// Rejection used by the [`PreconditionValidator`]
+    /// in case the `If-Match` or `If-Unmodified-Since` precondition
+    /// does not hold for the current version of the resource.
+    pub struct PreconditionFailed(Error);
+}
+
+define_http_rejection! {
+    #[status = PRECONDITION_REQUIRED]
+    #[body = "Precondition required"]
+    /// Rejection used by the [`PreconditionValidator`]
+    /// in case a precondition is required but neither
+    /// an `If-Match` nor an `If-Unmodified-Since` header is present.
+    pub struct PreconditionRequired(Error);
+}
+
+composite_http_rejection! {
+    /// Rejection used by the [`PreconditionValidator`].
+    pub enum PreconditionRejection {
+        PreconditionFailed,
+        PreconditionRequired,
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The current version of a resource,
+/// against which the preconditions of a request are evaluated.
+pub struct ResourceVersion {
+    etag: Option<ETag>,
+    last_modified: Option<SystemTime>,
+}
+
+impl ResourceVersion {
+    /// Create a new [`ResourceVersion`], without [`ETag`] or last modification date.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the current [`ETag`] of the resource.
+    pub fn with_etag(mut self, etag: ETag) -> Self {
+        self.etag = Some(etag);
+        self
+    }
+
+    /// Set the current [`ETag`] of the resource.
+    pub fn set_etag(&mut self, etag: ETag) -> &mut Self {
+        self.etag = Some(etag);
+        self
+    }
+
+    /// Set the last modification date of the resource.
+    pub fn with_last_modified(mut self, last_modified: SystemTime) -> Self {
+        self.last_modified = Some(last_modified);
+        self
+    }
+
+    /// Set the last modification date of the resource.
+    pub fn set_last_modified(&mut self, last_modified: SystemTime) -> &mut Self {
+        self.last_modified = Some(last_modified);
+        self
+    }
+
+    /// The current [`ETag`] of the resource, if known.
+    pub fn etag(&self) -> Option<&ETag> {
+        self.etag.as_ref()
+    }
+
+    /// The last modification date of the resource, if known.
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        self.last_modified
+    }
+}
+
+/// Fetches the current [`ResourceVersion`] of the resource targeted by a request.
+///
+/// Implemented for async functions (and closures) taking
+/// the [`Context`] and [`Request`] by reference.
+pub trait ResourceVersionProvider<S, B>: Send + Sync + 'static {
+    /// Fetch the current [`ResourceVersion`] of the targeted resource,
+    /// or `None` in case the resource does not exist.
+    fn resource_version(
+        &self,
+        ctx: &Context<S>,
+        req: &Request<B>,
+    ) -> impl Future<Output = Option<ResourceVersion>> + Send + '_;
+}
+
+impl<S, B, F, Fut> ResourceVersionProvider<S, B> for F
+where
+    F: Fn(&Context<S>, &Request<B>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Option<ResourceVersion>> + Send + 'static,
+{
+    fn resource_version(
+        &self,
+        ctx: &Context<S>,
+        req: &Request<B>,
+    ) -> impl Future<Output = Option<ResourceVersion>> + Send + '_ {
+        self(ctx, req)
+    }
+}
+
+/// Layer that enforces preconditions on writes using a [`PreconditionValidator`].
+///
+/// See [`PreconditionValidator`] for more information.
+pub type PreconditionLayer<P> = ValidateRequestHeaderLayer<PreconditionValidator<P>>;
+
+impl<P> ValidateRequestHeaderLayer<PreconditionValidator<P>> {
+    /// Enforce preconditions on writes using the given [`PreconditionValidator`].
+    pub fn precondition(validator: PreconditionValidator<P>) -> Self {
+        Self::custom(validator)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A [`ValidateRequest`] implementation enforcing the `If-Match` and `If-Unmodified-Since`
+/// preconditions on `PUT`, `PATCH` and `DELETE` requests, e.g. for optimistic concurrency control.
+///
+/// The current [`ResourceVersion`] is fetched using a [`ResourceVersionProvider`],
+/// only for requests which have a precondition to evaluate.
+/// As defined in [RFC 9110 (section 13.2.2)], the `If-Unmodified-Since` header
+/// is ignored when an `If-Match` header is present.
+///
+/// A request is rejected with `412 Precondition Failed` ([`PreconditionFailed`]) when:
+///
+/// - none of the entity tags of the `If-Match` header strongly match the current [`ETag`],
+///   or the resource does not exist (also for `If-Match: *`);
+/// - the resource was modified after the date of the `If-Unmodified-Since` header.
+///
+/// Requests without any of these headers are rejected with `428 Precondition Required`
+/// ([`PreconditionRequired`]) if preconditions are required, and let through otherwise.
+/// All other methods are always let through.
+///
+/// [RFC 9110 (section 13.2.2)]: https://www.rfc-editor.org/rfc/rfc9110#section-13.2.2
+///
+/// # Example
+///
+/// ```
+/// use rama_http::layer::validate_request::{
+///     PreconditionLayer, PreconditionValidator, ResourceVersion,
+/// };
+/// use rama_http::headers::ETag;
+/// use rama_http::{header::IF_MATCH, Body, Method, Request, Response, StatusCode};
+/// use rama_core::{service::service_fn, Context, Layer, Service};
+/// use std::convert::Infallible;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let service = PreconditionLayer::precondition(
+///     PreconditionValidator::new(|_ctx: &Context<()>, _req: &Request| async {
+///         Some(ResourceVersion::new().with_etag("\"v2\"".parse::<ETag>().unwrap()))
+///     })
+///     .with_required(true),
+/// )
+/// .layer(service_fn(|_req: Request| async {
+///     Ok::<_, Infallible>(Response::new(Body::empty()))
+/// }));
+///
+/// let req = Request::builder()
+///     .method(Method::PUT)
+///     .header(IF_MATCH, "\"v1\"")
+///     .body(Body::empty())
+///     .unwrap();
+/// let resp = service.serve(Context::default(), req).await.unwrap();
+/// assert_eq!(resp.status(), StatusCode::PRECONDITION_FAILED);
+/// # }
+/// ```
+pub struct PreconditionValidator<P> {
+    provider: P,
+    required: bool,
+}
+
+impl<P> PreconditionValidator<P> {
+    /// Create a new [`PreconditionValidator`] using the given [`ResourceVersionProvider`],
+    /// which does not require preconditions to be present.
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            required: false,
+        }
+    }
+
+    /// Require `PUT`, `PATCH` and `DELETE` requests to have a precondition,
+    /// rejecting them with `428 Precondition Required` otherwise.
+    pub fn with_required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Require `PUT`, `PATCH` and `DELETE` requests to have a precondition,
+    /// rejecting them with `428 Precondition Required` otherwise.
+    pub fn set_required(&mut self, required: bool) -> &mut Self {
+        self.required = required;
+        self
+    }
+
+    /// Check the preconditions of the given request,
+    /// returning the rejection in case they do not hold.
+    pub async fn check<S, B>(
+        &self,
+        ctx: &Context<S>,
+        req: &Request<B>,
+    ) -> Result<(), PreconditionRejection>
+    where
+        P: ResourceVersionProvider<S, B>,
+    {
+        if !matches!(*req.method(), Method::PUT | Method::PATCH | Method::DELETE) {
+            return Ok(());
+        }
+
+        if let Some(if_match) = req.headers().typed_get::<IfMatch>() {
+            let passes = match self.provider.resource_version(ctx, req).await {
+                Some(version) => {
+                    if_match.is_any()
+                        || version
+                            .etag()
+                            .is_some_and(|etag| if_match.precondition_passes(etag))
+                }
+                None => false,
+            };
+            return if passes {
+                Ok(())
+            } else {
+                Err(PreconditionFailed::from_display("If-Match").into())
+            };
+        }
+
+        if let Some(if_unmodified_since) = req.headers().typed_get::<IfUnmodifiedSince>() {
+            let last_modified = self
+                .provider
+                .resource_version(ctx, req)
+                .await
+                .and_then(|version| version.last_modified());
+            return match last_modified {
+                Some(last_modified) if !if_unmodified_since.precondition_passes(last_modified) => {
+                    Err(PreconditionFailed::from_display("If-Unmodified-Since").into())
+                }
+                _ => Ok(()),
+            };
+        }
+
+        if self.required {
+            return Err(PreconditionRequired::from_display(
+                "missing If-Match or If-Unmodified-Since",
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+impl<P, S, B> ValidateRequest<S, B> for PreconditionValidator<P>
+where
+    P: ResourceVersionProvider<S, B>,
+    S: Clone + Send + Sync + 'static,
+    B: Send + Sync + 'static,
+{
+    type ResponseBody = crate::Body;
+
+    async fn validate(
+        &self,
+        ctx: Context<S>,
+        req: Request<B>,
+    ) -> Result<(Context<S>, Request<B>), Response<Self::ResponseBody>> {
+        match self.check(&ctx, &req).await {
+            Ok(()) => Ok((ctx, req)),
+            Err(rejection) => Err(rejection.into_response()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{header, Body, StatusCode};
+    use rama_core::{service::service_fn, Layer, Service};
+    use std::convert::Infallible;
+    use std::time::Duration;
+
+    fn last_modified() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+    }
+
+    fn service(
+        required: bool,
+    ) -> impl Service<(), Request, Response = Response, Error = Infallible> {
+        PreconditionLayer::precondition(
+            PreconditionValidator::new(|_ctx: &Context<()>, req: &Request| {
+                let exists = req.uri().path() != "/missing";
+                async move {
+                    exists.then(|| {
+                        ResourceVersion::new()
+                            .with_etag("\"v2\"".parse().unwrap())
+                            .with_last_modified(last_modified())
+                    })
+                }
+            })
+            .with_required(required),
+        )
+        .layer(service_fn(|_req: Request| async {
+            Ok::<_, Infallible>(Response::new(Body::empty()))
+        }))
+    }
+
+    fn request(method: Method, uri: &str, header: Option<(header::HeaderName, &str)>) -> Request {
+        let mut builder = Request::builder().method(method).uri(uri);
+        if let Some((name, value)) = header {
+            builder = builder.header(name, value);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    async fn status(required: bool, req: Request) -> StatusCode {
+        service(required)
+            .serve(Context::default(), req)
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_precondition_if_match() {
+        for (if_match, uri, expected) in [
+            ("\"v2\"", "/", StatusCode::OK),
+            ("\"v1\", \"v2\"", "/", StatusCode::OK),
+            ("*", "/", StatusCode::OK),
+            ("\"v1\"", "/", StatusCode::PRECONDITION_FAILED),
+            ("W/\"v2\"", "/", StatusCode::PRECONDITION_FAILED),
+            ("*", "/missing", StatusCode::PRECONDITION_FAILED),
+        ] {
+            for method in [Method::PUT, Method::PATCH, Method::DELETE] {
+                let req = request(method.clone(), uri, Some((header::IF_MATCH, if_match)));
+                assert_eq!(
+                    status(false, req).await,
+                    expected,
+                    "{method} {uri} If-Match: {if_match}"
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_precondition_if_unmodified_since() {
+        for (date, expected) in [
+            ("Tue, 14 Nov 2023 22:13:20 GMT", StatusCode::OK),
+            ("Wed, 15 Nov 2023 00:00:00 GMT", StatusCode::OK),
+            (
+                "Tue, 14 Nov 2023 22:13:19 GMT",
+                StatusCode::PRECONDITION_FAILED,
+            ),
+        ] {
+            let req = request(Method::PUT, "/", Some((header::IF_UNMODIFIED_SINCE, date)));
+            assert_eq!(status(true, req).await, expected, "{date}");
+        }
+
+        // If-Unmodified-Since is ignored when If-Match is present
+        let mut req = request(Method::PUT, "/", Some((header::IF_MATCH, "\"v2\"")));
+        req.headers_mut().insert(
+            header::IF_UNMODIFIED_SINCE,
+            "Mon, 01 Jan 2001 00:00:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(status(true, req).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_precondition_required() {
+        let req = request(Method::PUT, "/", None);
+        assert_eq!(status(true, req).await, StatusCode::PRECONDITION_REQUIRED);
+
+        let req = request(Method::PUT, "/", None);
+        assert_eq!(status(false, req).await, StatusCode::OK);
+
+        // safe methods are never checked
+        let req = request(Method::GET, "/", None);
+        assert_eq!(status(true, req).await, StatusCode::OK);
+        let req = request(Method::GET, "/", Some((header::IF_MATCH, "\"v1\"")));
+        assert_eq!(status(true, req).await, StatusCode::OK);
+    }
+}
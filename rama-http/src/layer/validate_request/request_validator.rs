@@ -0,0 +1,381 @@
+use super::{ValidateRequest, ValidateRequestHeaderLayer};
+use crate::dep::mime::Mime;
+use crate::utils::macros::{composite_http_rejection, define_http_rejection};
+use crate::{header, BodyLimit, HeaderName, IntoResponse, Request, Response};
+use rama_core::Context;
+
+define_http_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Missing required header"]
+    /// Rejection used by the [`RequestValidator`]
+    /// in case a required header is missing.
+    pub struct MissingRequiredHeader(Error);
+}
+
+define_http_rejection! {
+    #[status = UNSUPPORTED_MEDIA_TYPE]
+    #[body = "Unsupported content type"]
+    /// Rejection used by the [`RequestValidator`]
+    /// in case the `Content-Type` of the request is not allowed.
+    pub struct UnsupportedContentType(Error);
+}
+
+define_http_rejection! {
+    #[status = PAYLOAD_TOO_LARGE]
+    #[body = "Request body too large"]
+    /// Rejection used by the [`RequestValidator`]
+    /// in case the `Content-Length` of the request exceeds the maximum body size.
+    pub struct RequestBodyTooLarge(Error);
+}
+
+define_http_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Missing required query parameter"]
+    /// Rejection used by the [`RequestValidator`]
+    /// in case a required query parameter is missing.
+    pub struct MissingRequiredQueryParam(Error);
+}
+
+composite_http_rejection! {
+    /// Rejection used by the [`RequestValidator`].
+    ///
+    /// Contains one variant for each check of the [`RequestValidator`].
+    pub enum RequestValidationRejection {
+        MissingRequiredHeader,
+        UnsupportedContentType,
+        RequestBodyTooLarge,
+        MissingRequiredQueryParam,
+    }
+}
+
+/// Layer that validates requests using a [`RequestValidator`].
+///
+/// See [`RequestValidator`] for more information.
+pub type ValidateRequestLayer = ValidateRequestHeaderLayer<RequestValidator>;
+
+impl ValidateRequestHeaderLayer<RequestValidator> {
+    /// Validate requests using the given [`RequestValidator`].
+    pub fn validator(validator: RequestValidator) -> Self {
+        Self::custom(validator)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A declarative [`ValidateRequest`] implementation,
+/// combining common checks on requests.
+///
+/// The checks are executed in the following order,
+/// rejecting the request with the first failing check:
+///
+/// 1. all required headers are present ([`MissingRequiredHeader`], `400 Bad Request`);
+/// 2. the `Content-Type` is one of the allowed content types ([`UnsupportedContentType`],
+///    `415 Unsupported Media Type`), only checked for requests which declare a body;
+/// 3. the `Content-Length` does not exceed the max body size ([`RequestBodyTooLarge`],
+///    `413 Payload Too Large`);
+/// 4. all required query parameters are present ([`MissingRequiredQueryParam`], `400 Bad Request`).
+///
+/// The max body size is also inserted as a request [`BodyLimit`] in the [`Context`],
+/// such that it is respected by the body extractors for requests without a `Content-Length`.
+///
+/// # Example
+///
+/// ```
+/// use rama_http::layer::validate_request::{RequestValidator, ValidateRequestLayer};
+/// use rama_http::{header::CONTENT_TYPE, Body, Request, Response, StatusCode};
+/// use rama_core::{service::service_fn, Context, Layer, Service};
+/// use std::convert::Infallible;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let service = ValidateRequestLayer::validator(
+///     RequestValidator::new()
+///         .with_required_header("x-api-key".parse().unwrap())
+///         .with_allowed_content_type(mime::APPLICATION_JSON)
+///         .with_max_body_size(1024),
+/// )
+/// .layer(service_fn(|_req: Request| async {
+///     Ok::<_, Infallible>(Response::new(Body::empty()))
+/// }));
+///
+/// let req = Request::builder()
+///     .header(CONTENT_TYPE, "application/json")
+///     .body(Body::from("{}"))
+///     .unwrap();
+/// let resp = service.serve(Context::default(), req).await.unwrap();
+/// assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+/// # }
+/// ```
+pub struct RequestValidator {
+    required_headers: Vec<HeaderName>,
+    allowed_content_types: Vec<Mime>,
+    max_body_size: Option<usize>,
+    required_query_params: Vec<String>,
+}
+
+impl RequestValidator {
+    /// Create a new [`RequestValidator`], which allows all requests.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require the given header to be present.
+    pub fn with_required_header(mut self, name: HeaderName) -> Self {
+        self.required_headers.push(name);
+        self
+    }
+
+    /// Require the given header to be present.
+    pub fn set_required_header(&mut self, name: HeaderName) -> &mut Self {
+        self.required_headers.push(name);
+        self
+    }
+
+    /// Allow the given content type, ignoring its parameters.
+    ///
+    /// All content types are allowed when no content type is defined.
+    pub fn with_allowed_content_type(mut self, content_type: Mime) -> Self {
+        self.allowed_content_types.push(content_type);
+        self
+    }
+
+    /// Allow the given content type, ignoring its parameters.
+    ///
+    /// All content types are allowed when no content type is defined.
+    pub fn set_allowed_content_type(&mut self, content_type: Mime) -> &mut Self {
+        self.allowed_content_types.push(content_type);
+        self
+    }
+
+    /// Set the maximum size (in bytes) of the request body.
+    pub fn with_max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = Some(size);
+        self
+    }
+
+    /// Set the maximum size (in bytes) of the request body.
+    pub fn set_max_body_size(&mut self, size: usize) -> &mut Self {
+        self.max_body_size = Some(size);
+        self
+    }
+
+    /// Require the query parameter with the given name to be present.
+    pub fn with_required_query_param(mut self, name: impl Into<String>) -> Self {
+        self.required_query_params.push(name.into());
+        self
+    }
+
+    /// Require the query parameter with the given name to be present.
+    pub fn set_required_query_param(&mut self, name: impl Into<String>) -> &mut Self {
+        self.required_query_params.push(name.into());
+        self
+    }
+
+    /// Check the given request, returning the rejection of the first failing check.
+    pub fn check<B>(&self, req: &Request<B>) -> Result<(), RequestValidationRejection> {
+        let headers = req.headers();
+
+        if let Some(name) = self
+            .required_headers
+            .iter()
+            .find(|name| !headers.contains_key(*name))
+        {
+            return Err(MissingRequiredHeader::from_display(name.to_string()).into());
+        }
+
+        let content_length = headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok());
+
+        if !self.allowed_content_types.is_empty() {
+            match headers.get(header::CONTENT_TYPE) {
+                Some(value) => {
+                    let mime = value.to_str().ok().and_then(|s| s.parse::<Mime>().ok());
+                    if !mime.is_some_and(|mime| {
+                        self.allowed_content_types
+                            .iter()
+                            .any(|allowed| allowed.essence_str() == mime.essence_str())
+                    }) {
+                        return Err(
+                            UnsupportedContentType::from_display(format!("{value:?}")).into()
+                        );
+                    }
+                }
+                None => {
+                    let has_body = content_length.is_some_and(|len| len > 0)
+                        || headers.contains_key(header::TRANSFER_ENCODING);
+                    if has_body {
+                        return Err(
+                            UnsupportedContentType::from_display("missing Content-Type").into()
+                        );
+                    }
+                }
+            }
+        }
+
+        if let (Some(max), Some(len)) = (self.max_body_size, content_length) {
+            if len > max as u64 {
+                return Err(RequestBodyTooLarge::from_display(format!(
+                    "{len} bytes exceeds limit of {max} bytes"
+                ))
+                .into());
+            }
+        }
+
+        if !self.required_query_params.is_empty() {
+            if let Some(name) = self
+                .required_query_params
+                .iter()
+                .find(|name| crate::uri::query_param(req.uri(), name).is_none())
+            {
+                return Err(MissingRequiredQueryParam::from_display(name.clone()).into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<S, B> ValidateRequest<S, B> for RequestValidator
+where
+    S: Clone + Send + Sync + 'static,
+    B: Send + 'static,
+{
+    type ResponseBody = crate::Body;
+
+    async fn validate(
+        &self,
+        mut ctx: Context<S>,
+        req: Request<B>,
+    ) -> Result<(Context<S>, Request<B>), Response<Self::ResponseBody>> {
+        if let Err(rejection) = self.check(&req) {
+            return Err(rejection.into_response());
+        }
+
+        if let Some(max) = self.max_body_size {
+            let current = ctx.get::<BodyLimit>().copied();
+            let request = current
+                .and_then(|limit| limit.request())
+                .map_or(max, |limit| limit.min(max));
+            // preserve the response limit, if any (0 means no limit)
+            let response = current.and_then(|limit| limit.response()).unwrap_or(0);
+            ctx.insert(BodyLimit::asymmetric(request, response));
+        }
+
+        Ok((ctx, req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, StatusCode};
+    use rama_core::{service::service_fn, Layer, Service};
+    use std::convert::Infallible;
+
+    fn service() -> impl Service<(), Request, Response = Response, Error = Infallible> {
+        ValidateRequestLayer::validator(
+            RequestValidator::new()
+                .with_required_header(HeaderName::from_static("x-api-key"))
+                .with_allowed_content_type(mime::APPLICATION_JSON)
+                .with_max_body_size(16)
+                .with_required_query_param("id"),
+        )
+        .layer(service_fn(|ctx: Context<()>, _req: Request| async move {
+            assert_eq!(
+                ctx.get::<BodyLimit>().and_then(BodyLimit::request),
+                Some(16)
+            );
+            Ok::<_, Infallible>(Response::new(Body::empty()))
+        }))
+    }
+
+    fn request(api_key: bool, content_type: &str, body: &'static str, uri: &str) -> Request {
+        let mut builder = Request::builder()
+            .uri(uri)
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_LENGTH, body.len());
+        if api_key {
+            builder = builder.header("x-api-key", "secret");
+        }
+        builder.body(Body::from(body)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_validator_passes() {
+        let resp = service()
+            .serve(
+                Context::default(),
+                request(true, "application/json; charset=utf-8", "{}", "/?id=1"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_request_validator_rejections() {
+        for (req, expected_status) in [
+            (
+                request(false, "application/json", "{}", "/?id=1"),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                request(true, "text/plain", "{}", "/?id=1"),
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ),
+            (
+                request(
+                    true,
+                    "application/json",
+                    r#"{"foo":"bar","baz":42}"#,
+                    "/?id=1",
+                ),
+                StatusCode::PAYLOAD_TOO_LARGE,
+            ),
+            (
+                request(true, "application/json", "{}", "/?foo=1"),
+                StatusCode::BAD_REQUEST,
+            ),
+        ] {
+            let resp = service().serve(Context::default(), req).await.unwrap();
+            assert_eq!(resp.status(), expected_status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_validator_preserves_response_body_limit() {
+        let service = ValidateRequestLayer::validator(
+            RequestValidator::new().with_max_body_size(16),
+        )
+        .layer(service_fn(|ctx: Context<()>, _req: Request| async move {
+            let limit = ctx.get::<BodyLimit>().unwrap();
+            assert_eq!(limit.request(), Some(16));
+            assert_eq!(limit.response(), Some(2048));
+            Ok::<_, Infallible>(Response::new(Body::empty()))
+        }));
+
+        // as inserted by an outer (transport) `BodyLimitLayer`
+        let mut ctx = Context::default();
+        ctx.insert(BodyLimit::asymmetric(1024, 2048));
+
+        let resp = service
+            .serve(ctx, request(true, "application/json", "{}", "/"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_request_validator_typed_rejection() {
+        let validator =
+            RequestValidator::new().with_required_header(HeaderName::from_static("x-api-key"));
+        let err = validator.check(&Request::new(())).unwrap_err();
+        assert!(matches!(
+            err,
+            RequestValidationRejection::MissingRequiredHeader(_)
+        ));
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(err.body_text(), "Missing required header: x-api-key");
+    }
+}
@@ -21,8 +21,8 @@
 pub use ::rama_http_types::{
     header, proto,
     response::{self, IntoResponse, Response},
-    Body, BodyDataStream, BodyExtractExt, BodyLimit, HeaderMap, HeaderName, HeaderValue, Method,
-    Request, Scheme, StatusCode, Uri, Version,
+    uri, Body, BodyDataStream, BodyExtractExt, BodyLimit, HeaderMap, HeaderName, HeaderValue,
+    Method, Request, Scheme, StatusCode, Uri, Version,
 };
 
 pub mod headers;
@@ -33,6 +33,9 @@ pub mod service;
 
 pub mod io;
 
+pub mod multipart;
+pub mod sse;
+
 pub mod utils;
 
 pub mod dep {
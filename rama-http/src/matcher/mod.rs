@@ -28,7 +28,9 @@ pub use version::VersionMatcher;
 
 mod path;
 #[doc(inline)]
-pub use path::{PathMatcher, UriParams, UriParamsDeserializeError};
+pub use path::{
+    strip_prefix_parts, PathMatcher, StrippedPrefix, UriParams, UriParamsDeserializeError,
+};
 
 mod header;
 #[doc(inline)]
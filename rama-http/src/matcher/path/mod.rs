@@ -1,4 +1,4 @@
-use crate::{IntoResponse, Request, StatusCode};
+use crate::{IntoResponse, Request, StatusCode, Uri};
 use rama_core::{context::Extensions, Context};
 use std::collections::HashMap;
 
@@ -242,6 +242,120 @@ impl PathMatcher {
     }
 }
 
+#[derive(Debug, Clone)]
+/// The parts of a [`Uri`] of which a path prefix was stripped,
+/// as returned by [`strip_prefix_parts`].
+pub struct StrippedPrefix {
+    prefix: String,
+    path: String,
+    query: Option<String>,
+    params: UriParams,
+}
+
+impl StrippedPrefix {
+    /// The (raw) path prefix that was stripped, e.g. `/api`.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// The (raw) remaining path, which always starts with a `/`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The (raw) query of the [`Uri`], if any.
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    /// The remaining path followed by the query, if any, e.g. `/v1?x=1`.
+    pub fn path_and_query(&self) -> String {
+        match &self.query {
+            Some(query) => format!("{}?{}", self.path, query),
+            None => self.path.clone(),
+        }
+    }
+
+    /// The [`UriParams`] matched by the prefix,
+    /// with the remaining path as [glob](UriParams::glob).
+    pub fn params(&self) -> &UriParams {
+        &self.params
+    }
+
+    /// Consume the [`StrippedPrefix`] into its [`UriParams`].
+    pub fn into_params(self) -> UriParams {
+        self.params
+    }
+}
+
+/// Strip the given path prefix from the path of the [`Uri`],
+/// returning the stripped prefix, remaining path and query.
+///
+/// The prefix is matched segment per segment, in the same way as a [`PathMatcher`]:
+/// literal segments are matched case-insensitive and segments starting with a `:`
+/// capture a (percent-decoded) parameter. Contrary to the [`PathMatcher`], the path
+/// segments are percent-decoded before matching literal segments, such that `/%61pi`
+/// is stripped by the `/api` prefix as well.
+///
+/// Trailing slashes of the prefix are ignored, and stripping a prefix equal to
+/// the entire path results in the root path `/`.
+///
+/// `None` is returned in case the path does not start with the given prefix.
+///
+/// # Example
+///
+/// ```
+/// use rama_http::{matcher::strip_prefix_parts, Uri};
+///
+/// let uri = Uri::from_static("/api/v1?x=1");
+/// let parts = strip_prefix_parts("/api", &uri).unwrap();
+/// assert_eq!(parts.prefix(), "/api");
+/// assert_eq!(parts.path_and_query(), "/v1?x=1");
+/// ```
+pub fn strip_prefix_parts(prefix: &str, uri: &Uri) -> Option<StrippedPrefix> {
+    let full_path = uri.path();
+    let mut rest = full_path;
+    let mut params = UriParams::default();
+
+    for fragment in prefix.trim().split('/').filter(|s| !s.is_empty()) {
+        let trimmed = rest.strip_prefix('/').unwrap_or(rest);
+        let (segment, remainder) = match trimmed.find('/') {
+            Some(idx) => trimmed.split_at(idx),
+            None => (trimmed, ""),
+        };
+        if segment.is_empty() {
+            return None;
+        }
+        let decoded = percent_encoding::percent_decode(segment.as_bytes()).decode_utf8_lossy();
+
+        match fragment.strip_prefix(':') {
+            Some(name) => params.insert(name.to_lowercase(), decoded.into_owned()),
+            None => {
+                if !fragment.eq_ignore_ascii_case(&decoded) {
+                    return None;
+                }
+            }
+        }
+
+        rest = remainder;
+    }
+
+    let prefix = full_path[..full_path.len() - rest.len()].to_owned();
+    let path = if rest.is_empty() {
+        "/".to_owned()
+    } else {
+        rest.to_owned()
+    };
+    params.glob = Some(path.clone());
+
+    Some(StrippedPrefix {
+        prefix,
+        path,
+        query: uri.query().map(ToOwned::to_owned),
+        params,
+    })
+}
+
 impl<State, Body> rama_core::matcher::Matcher<State, Request<Body>> for PathMatcher {
     fn matches(
         &self,
@@ -265,6 +379,54 @@ impl<State, Body> rama_core::matcher::Matcher<State, Request<Body>> for PathMatc
 mod test {
     use super::*;
 
+    #[test]
+    fn test_strip_prefix_parts() {
+        let uri = Uri::from_static("/api/v1?x=1");
+        let parts = strip_prefix_parts("/api", &uri).unwrap();
+        assert_eq!(parts.prefix(), "/api");
+        assert_eq!(parts.path(), "/v1");
+        assert_eq!(parts.query(), Some("x=1"));
+        assert_eq!(parts.path_and_query(), "/v1?x=1");
+        assert_eq!(parts.params().glob(), Some("/v1"));
+
+        for (prefix, path, expected_prefix, expected_path) in [
+            ("/api/", "/api/v1", "/api", "/v1"),
+            ("api", "/API/v1/", "/API", "/v1/"),
+            ("/api", "/api", "/api", "/"),
+            ("/api", "/api/", "/api", "/"),
+            ("/api", "/%61pi/v1", "/%61pi", "/v1"),
+            ("/", "/api/v1", "", "/api/v1"),
+        ] {
+            let uri: Uri = path.parse().unwrap();
+            let parts = strip_prefix_parts(prefix, &uri).unwrap();
+            assert_eq!(parts.prefix(), expected_prefix, "{prefix} - {path}");
+            assert_eq!(parts.path(), expected_path, "{prefix} - {path}");
+            assert_eq!(parts.query(), None);
+        }
+
+        for (prefix, path) in [
+            ("/api", "/apiv1"),
+            ("/api", "/"),
+            ("/api/v1", "/api"),
+            ("/api", "//api"),
+        ] {
+            let uri: Uri = path.parse().unwrap();
+            assert!(
+                strip_prefix_parts(prefix, &uri).is_none(),
+                "{prefix} - {path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_strip_prefix_parts_params() {
+        let uri = Uri::from_static("/users/glen%20dc/posts/42?sort=asc");
+        let parts = strip_prefix_parts("/users/:name", &uri).unwrap();
+        assert_eq!(parts.prefix(), "/users/glen%20dc");
+        assert_eq!(parts.path_and_query(), "/posts/42?sort=asc");
+        assert_eq!(parts.params().get("name"), Some("glen dc"));
+    }
+
     #[test]
     fn test_path_matcher_match_path() {
         struct TestCase {
@@ -0,0 +1,36 @@
+//! Multipart response support.
+//!
+//! Use [`Multipart`] to build a `multipart/byteranges` or `multipart/mixed`
+//! (or any other `multipart/*`) response, streaming each [`Part`] with its own headers.
+//! The parts are streamed one after the other and never buffered as a whole.
+//!
+//! # Example
+//!
+//! ```
+//! use rama_http::headers::{ContentRange, ContentType};
+//! use rama_http::multipart::{Multipart, Part};
+//! use rama_http::{IntoResponse, StatusCode};
+//!
+//! let resp = Multipart::byteranges()
+//!     .with_part(
+//!         Part::new("Hello")
+//!             .with_typed_header(ContentType::text())
+//!             .with_typed_header(ContentRange::bytes(0..5, 13).unwrap()),
+//!     )
+//!     .with_part(
+//!         Part::new("World")
+//!             .with_typed_header(ContentType::text())
+//!             .with_typed_header(ContentRange::bytes(7..12, 13).unwrap()),
+//!     )
+//!     .into_response();
+//!
+//! assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+//! assert!(resp.headers()["content-type"]
+//!     .to_str()
+//!     .unwrap()
+//!     .starts_with("multipart/byteranges; boundary="));
+//! ```
+
+mod response;
+#[doc(inline)]
+pub use response::{Multipart, Part};
@@ -0,0 +1,324 @@
+use crate::headers::{Header, HeaderMapExt};
+use crate::{header, Body, HeaderMap, HeaderName, HeaderValue, IntoResponse, Response, StatusCode};
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_lite::{stream, StreamExt};
+use rama_core::error::BoxError;
+
+/// A single part of a [`Multipart`] response,
+/// consisting of its own headers and a (streaming) body.
+#[derive(Debug)]
+pub struct Part {
+    headers: HeaderMap,
+    body: Body,
+}
+
+impl Part {
+    /// Create a new [`Part`] for the given body, without any headers.
+    pub fn new(body: impl Into<Body>) -> Self {
+        Self {
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Add a header to this [`Part`].
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Add a header to this [`Part`].
+    pub fn set_header(&mut self, name: HeaderName, value: HeaderValue) -> &mut Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Add a typed header to this [`Part`], overwriting any existing value of that header.
+    pub fn with_typed_header<H: Header>(mut self, header: H) -> Self {
+        self.headers.typed_insert(header);
+        self
+    }
+
+    /// Add a typed header to this [`Part`], overwriting any existing value of that header.
+    pub fn set_typed_header<H: Header>(&mut self, header: H) -> &mut Self {
+        self.headers.typed_insert(header);
+        self
+    }
+
+    /// Returns a reference to the headers of this [`Part`].
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
+
+/// A `multipart/*` response, streaming its [`Part`]s
+/// separated by a (generated) boundary.
+///
+/// See the [module docs](super) for an example.
+#[derive(Debug)]
+pub struct Multipart {
+    subtype: &'static str,
+    boundary: String,
+    parts: Vec<Part>,
+}
+
+impl Multipart {
+    /// Create a new [`Multipart`] response of the given `multipart/<subtype>`,
+    /// using a randomly generated boundary.
+    pub fn new(subtype: &'static str) -> Self {
+        Self {
+            subtype,
+            boundary: format!(
+                "{:016x}{:016x}",
+                rand::random::<u64>(),
+                rand::random::<u64>()
+            ),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Create a new `multipart/mixed` [`Multipart`] response.
+    pub fn mixed() -> Self {
+        Self::new("mixed")
+    }
+
+    /// Create a new `multipart/byteranges` [`Multipart`] response,
+    /// which is served as a `206 Partial Content` response.
+    ///
+    /// Each part is expected to have a `Content-Range` header.
+    pub fn byteranges() -> Self {
+        Self::new("byteranges")
+    }
+
+    /// Overwrite the generated boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the boundary is empty, longer than 70 characters,
+    /// or contains characters not allowed in a boundary by RFC 2046.
+    pub fn with_boundary(mut self, boundary: impl Into<String>) -> Self {
+        self.boundary = valid_boundary(boundary.into());
+        self
+    }
+
+    /// Overwrite the generated boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the boundary is empty, longer than 70 characters,
+    /// or contains characters not allowed in a boundary by RFC 2046.
+    pub fn set_boundary(&mut self, boundary: impl Into<String>) -> &mut Self {
+        self.boundary = valid_boundary(boundary.into());
+        self
+    }
+
+    /// Add a [`Part`] to this [`Multipart`] response.
+    pub fn with_part(mut self, part: Part) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    /// Add a [`Part`] to this [`Multipart`] response.
+    pub fn set_part(&mut self, part: Part) -> &mut Self {
+        self.parts.push(part);
+        self
+    }
+
+    /// Returns the boundary used to delimit the parts.
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// Returns the `Content-Type` of this [`Multipart`] response.
+    pub fn content_type(&self) -> HeaderValue {
+        HeaderValue::try_from(format!(
+            "multipart/{}; boundary={}",
+            self.subtype, self.boundary
+        ))
+        .expect("valid subtype and boundary")
+    }
+
+    /// Turn the parts into a streaming [`Body`].
+    pub fn into_body(self) -> Body {
+        let boundary = self.boundary;
+        let close = Bytes::from(if self.parts.is_empty() {
+            format!("--{boundary}--\r\n")
+        } else {
+            format!("\r\n--{boundary}--\r\n")
+        });
+
+        let parts =
+            stream::iter(self.parts.into_iter().enumerate()).flat_map(move |(index, part)| {
+                let head = encode_part_head(&boundary, index == 0, &part.headers);
+                stream::once(Ok::<_, BoxError>(head)).chain(part.body.into_data_stream())
+            });
+        Body::from_stream(parts.chain(stream::once(Ok(close))))
+    }
+}
+
+impl IntoResponse for Multipart {
+    fn into_response(self) -> Response {
+        let status = if self.subtype == "byteranges" {
+            StatusCode::PARTIAL_CONTENT
+        } else {
+            StatusCode::OK
+        };
+        let content_type = self.content_type();
+        (
+            status,
+            [(header::CONTENT_TYPE, content_type)],
+            self.into_body(),
+        )
+            .into_response()
+    }
+}
+
+fn encode_part_head(boundary: &str, first: bool, headers: &HeaderMap) -> Bytes {
+    let mut buf = BytesMut::new();
+    if !first {
+        buf.put_slice(b"\r\n");
+    }
+    buf.put_slice(b"--");
+    buf.put_slice(boundary.as_bytes());
+    buf.put_slice(b"\r\n");
+    for (name, value) in headers {
+        buf.put_slice(name.as_str().as_bytes());
+        buf.put_slice(b": ");
+        buf.put_slice(value.as_bytes());
+        buf.put_slice(b"\r\n");
+    }
+    buf.put_slice(b"\r\n");
+    buf.freeze()
+}
+
+fn valid_boundary(boundary: String) -> String {
+    // bchars from RFC 2046, section 5.1.1, excluding the space
+    // to not have to deal with trailing spaces
+    assert!(
+        !boundary.is_empty()
+            && boundary.len() <= 70
+            && boundary.bytes().all(|b| {
+                b.is_ascii_alphanumeric()
+                    || matches!(
+                        b,
+                        b'\''
+                            | b'('
+                            | b')'
+                            | b'+'
+                            | b'_'
+                            | b','
+                            | b'-'
+                            | b'.'
+                            | b'/'
+                            | b':'
+                            | b'='
+                            | b'?'
+                    )
+            }),
+        "invalid multipart boundary: {boundary:?}"
+    );
+    boundary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headers::{ContentRange, ContentType};
+    use crate::BodyExtractExt;
+
+    /// Parse a multipart body into the (raw) headers and body of each part.
+    fn parse(body: &str, boundary: &str) -> Vec<(Vec<(String, String)>, String)> {
+        let delimiter = format!("--{boundary}");
+        let body = body
+            .strip_suffix(&format!("\r\n{delimiter}--\r\n"))
+            .expect("close delimiter");
+        body.strip_prefix(&format!("{delimiter}\r\n"))
+            .expect("first delimiter")
+            .split(&format!("\r\n{delimiter}\r\n"))
+            .map(|part| {
+                let (head, body) = part.split_once("\r\n\r\n").expect("part head");
+                let headers = head
+                    .split("\r\n")
+                    .map(|line| {
+                        let (name, value) = line.split_once(": ").expect("header line");
+                        (name.to_owned(), value.to_owned())
+                    })
+                    .collect();
+                (headers, body.to_owned())
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_multipart_byteranges_response() {
+        let content = "Hello, World!";
+
+        let multipart = Multipart::byteranges()
+            .with_part(
+                Part::new(&content[0..5])
+                    .with_typed_header(ContentType::text())
+                    .with_typed_header(ContentRange::bytes(0..5, 13).unwrap()),
+            )
+            .with_part(
+                Part::new(Body::from_stream(stream::iter([
+                    Ok::<_, BoxError>(&content[7..9]),
+                    Ok(&content[9..12]),
+                ])))
+                .with_typed_header(ContentType::text())
+                .with_typed_header(ContentRange::bytes(7..12, 13).unwrap()),
+            );
+        let boundary = multipart.boundary().to_owned();
+
+        let resp = multipart.into_response();
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            resp.headers()[header::CONTENT_TYPE],
+            format!("multipart/byteranges; boundary={boundary}")
+        );
+
+        let body = resp.into_body().try_into_string().await.unwrap();
+        let parts = parse(&body, &boundary);
+        assert_eq!(parts.len(), 2);
+
+        for ((headers, body), (range, expected)) in parts
+            .iter()
+            .zip([("bytes 0-4/13", "Hello"), ("bytes 7-11/13", "World")])
+        {
+            assert_eq!(
+                headers,
+                &[
+                    ("content-type".to_owned(), "text/plain".to_owned()),
+                    ("content-range".to_owned(), range.to_owned()),
+                ]
+            );
+            assert_eq!(body, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multipart_mixed_custom_boundary() {
+        let resp = Multipart::mixed()
+            .with_boundary("simple-boundary")
+            .with_part(Part::new("foo"))
+            .with_part(Part::new("bar").with_header(
+                HeaderName::from_static("x-part"),
+                HeaderValue::from_static("2"),
+            ))
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers()[header::CONTENT_TYPE],
+            "multipart/mixed; boundary=simple-boundary"
+        );
+        assert_eq!(
+            resp.into_body().try_into_string().await.unwrap(),
+            "--simple-boundary\r\n\r\nfoo\r\n--simple-boundary\r\nx-part: 2\r\n\r\nbar\r\n--simple-boundary--\r\n"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_multipart_invalid_boundary() {
+        let _ = Multipart::mixed().with_boundary("no spaces");
+    }
+}
@@ -43,3 +43,32 @@ impl IfUnmodifiedSince {
             .map(|time| IfUnmodifiedSince(time.into()))
     }
 }
+
+pub(super) enum IfRange {
+    Date(HttpDate),
+    /// An entity tag or invalid value,
+    /// which never matches as no (strong) entity tags are generated for served files.
+    Other,
+}
+
+impl IfRange {
+    /// Check if the requested range(s) can be served,
+    /// as the resource is unchanged since the supplied validator.
+    ///
+    /// A date only matches if it is exactly the last modification date.
+    pub(super) fn precondition_passes(&self, last_modified: Option<&LastModified>) -> bool {
+        match self {
+            IfRange::Date(date) => last_modified.is_some_and(|modified| modified.0 == *date),
+            IfRange::Other => false,
+        }
+    }
+
+    /// Convert a header value into a IfRange
+    pub(super) fn from_header_value(value: &HeaderValue) -> IfRange {
+        std::str::from_utf8(value.as_bytes())
+            .ok()
+            .and_then(|value| httpdate::parse_http_date(value.trim()).ok())
+            .map(|time| IfRange::Date(time.into()))
+            .unwrap_or(IfRange::Other)
+    }
+}
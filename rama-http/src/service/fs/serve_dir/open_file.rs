@@ -1,5 +1,5 @@
 use super::{
-    headers::{IfModifiedSince, IfUnmodifiedSince, LastModified},
+    headers::{IfModifiedSince, IfRange, IfUnmodifiedSince, LastModified},
     ServeVariant,
 };
 use crate::layer::util::content_encoding::{Encoding, QValue};
@@ -54,6 +54,11 @@ pub(super) async fn open_file(
         .get(header::IF_MODIFIED_SINCE)
         .and_then(IfModifiedSince::from_header_value);
 
+    let if_range = req
+        .headers()
+        .get(header::IF_RANGE)
+        .map(IfRange::from_header_value);
+
     let mime = match variant {
         ServeVariant::Directory {
             append_index_html_on_directories,
@@ -95,6 +100,7 @@ pub(super) async fn open_file(
             return Ok(output);
         }
 
+        let range_header = check_if_range(range_header, if_range.as_ref(), last_modified.as_ref());
         let maybe_range = try_parse_range(range_header.as_deref(), meta.len());
 
         Ok(OpenFileOutput::FileOpened(Box::new(FileOpened {
@@ -118,6 +124,7 @@ pub(super) async fn open_file(
             return Ok(output);
         }
 
+        let range_header = check_if_range(range_header, if_range.as_ref(), last_modified.as_ref());
         let maybe_range = try_parse_range(range_header.as_deref(), meta.len());
         if let Some(Ok(ranges)) = maybe_range.as_ref() {
             // if there is any other amount of ranges than 1 we'll return an
@@ -138,6 +145,19 @@ pub(super) async fn open_file(
     }
 }
 
+/// Drop the range header in case the If-Range precondition fails,
+/// such that the full (changed) resource is served instead.
+fn check_if_range(
+    range_header: Option<String>,
+    if_range: Option<&IfRange>,
+    modified: Option<&LastModified>,
+) -> Option<String> {
+    match if_range {
+        Some(if_range) if !if_range.precondition_passes(modified) => None,
+        _ => range_header,
+    }
+}
+
 fn check_modified_headers(
     modified: Option<&LastModified>,
     if_unmodified_since: Option<IfUnmodifiedSince>,
@@ -509,6 +509,57 @@ async fn read_partial_in_bounds() {
     assert_eq!(body, source);
 }
 
+#[tokio::test]
+async fn read_partial_if_range_matches() {
+    let svc = ServeDir::new("..");
+
+    let req = Request::builder()
+        .uri("/README.md")
+        .body(Body::empty())
+        .unwrap();
+    let res = svc.serve(Context::default(), req).await.unwrap();
+    let last_modified = res.headers()[header::LAST_MODIFIED].clone();
+
+    let req = Request::builder()
+        .uri("/README.md")
+        .header(header::RANGE, "bytes=9-1023")
+        .header(header::IF_RANGE, last_modified)
+        .body(Body::empty())
+        .unwrap();
+    let res = svc.serve(Context::default(), req).await.unwrap();
+
+    assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    let file_contents = std::fs::read("../README.md").unwrap();
+    assert_eq!(body, Bytes::from(file_contents[9..=1023].to_vec()));
+}
+
+#[tokio::test]
+async fn read_partial_if_range_mismatch_serves_full_resource() {
+    let svc = ServeDir::new("..");
+    let file_contents = std::fs::read("../README.md").unwrap();
+
+    for if_range in [
+        // date no longer matching the last modification date
+        "Sun, 06 Nov 1994 08:49:37 GMT",
+        // entity tags are never generated, and thus never match
+        "\"abc\"",
+    ] {
+        let req = Request::builder()
+            .uri("/README.md")
+            .header(header::RANGE, "bytes=9-1023")
+            .header(header::IF_RANGE, if_range)
+            .body(Body::empty())
+            .unwrap();
+        let res = svc.serve(Context::default(), req).await.unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK, "{if_range}");
+        assert!(res.headers().get(header::CONTENT_RANGE).is_none());
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, Bytes::from(file_contents.clone()), "{if_range}");
+    }
+}
+
 #[tokio::test]
 async fn read_partial_accepts_out_of_bounds_range() {
     let svc = ServeDir::new("..");
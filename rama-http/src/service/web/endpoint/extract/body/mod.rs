@@ -19,6 +19,10 @@ mod form;
 #[doc(inline)]
 pub use form::*;
 
+mod raw;
+#[doc(inline)]
+pub use raw::*;
+
 /// Extractor to get the response body.
 #[derive(Debug)]
 pub struct Body(pub http::Body);
@@ -0,0 +1,195 @@
+use super::BytesRejection;
+use crate::dep::http_body_util::BodyExt;
+use crate::service::web::extract::FromRequest;
+use crate::{IntoResponse, Request, Response};
+use std::fmt;
+
+/// Extractor to get the raw request body, collected as [`Bytes`],
+/// alongside the value extracted from that same body by another body extractor,
+/// such as [`Json`] or [`Form`].
+///
+/// The request body is only collected once, and the inner extractor
+/// is given a request with a body containing exactly these raw bytes.
+/// This is useful when the exact bytes are needed next to the parsed value,
+/// e.g. to verify the (HMAC) signature of a webhook payload.
+///
+/// # Example
+///
+/// ```
+/// use rama_http::service::web::extract::{Json, RawAndParsed};
+///
+/// #[derive(Debug, serde::Deserialize)]
+/// struct Event {
+///     kind: String,
+/// }
+///
+/// async fn handler(RawAndParsed { raw, parsed: Json(event) }: RawAndParsed<Json<Event>>) {
+///     // verify the signature using `raw`, and use the parsed `event`
+///     # let _ = (raw, event.kind);
+/// }
+/// ```
+///
+/// [`Bytes`]: https://docs.rs/bytes/latest/bytes/struct.Bytes.html
+/// [`Json`]: super::Json
+/// [`Form`]: super::Form
+#[derive(Debug, Clone)]
+pub struct RawAndParsed<T> {
+    /// The raw request body.
+    pub raw: bytes::Bytes,
+    /// The value extracted from the request body.
+    pub parsed: T,
+}
+
+/// Rejection used for [`RawAndParsed`].
+///
+/// Either the request body failed to be collected,
+/// or the inner extractor rejected the request.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RawAndParsedRejection<R> {
+    #[allow(missing_docs)]
+    BytesRejection(BytesRejection),
+    /// Rejection of the inner extractor.
+    Parsed(R),
+}
+
+impl<R: IntoResponse> IntoResponse for RawAndParsedRejection<R> {
+    fn into_response(self) -> Response {
+        match self {
+            Self::BytesRejection(inner) => inner.into_response(),
+            Self::Parsed(inner) => inner.into_response(),
+        }
+    }
+}
+
+impl<R> From<BytesRejection> for RawAndParsedRejection<R> {
+    fn from(inner: BytesRejection) -> Self {
+        Self::BytesRejection(inner)
+    }
+}
+
+impl<R: fmt::Display> fmt::Display for RawAndParsedRejection<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BytesRejection(inner) => write!(f, "{inner}"),
+            Self::Parsed(inner) => write!(f, "{inner}"),
+        }
+    }
+}
+
+impl<R: std::error::Error + 'static> std::error::Error for RawAndParsedRejection<R> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::BytesRejection(inner) => inner.source(),
+            Self::Parsed(inner) => Some(inner),
+        }
+    }
+}
+
+impl<T> FromRequest for RawAndParsed<T>
+where
+    T: FromRequest,
+{
+    type Rejection = RawAndParsedRejection<T::Rejection>;
+
+    async fn from_request(req: Request) -> Result<Self, Self::Rejection> {
+        let (parts, body) = req.into_parts();
+        let raw = body
+            .collect()
+            .await
+            .map_err(BytesRejection::from_err)?
+            .to_bytes();
+
+        let req = Request::from_parts(parts, crate::Body::from(raw.clone()));
+        let parsed = T::from_request(req)
+            .await
+            .map_err(RawAndParsedRejection::Parsed)?;
+
+        Ok(Self { raw, parsed })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::service::web::extract::{Form, Json};
+    use crate::service::web::WebService;
+    use crate::{Method, StatusCode};
+    use rama_core::{Context, Service};
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Input {
+        name: String,
+        age: u8,
+    }
+
+    #[tokio::test]
+    async fn test_raw_and_parsed_json() {
+        const PAYLOAD: &str = "{\"name\":  \"glen\",\n \"age\": 42 }";
+
+        let service = WebService::default().post(
+            "/",
+            |RawAndParsed { raw, parsed }: RawAndParsed<Json<Input>>| async move {
+                assert_eq!(raw, PAYLOAD.as_bytes());
+                assert_eq!(parsed.name, "glen");
+                assert_eq!(parsed.age, 42);
+            },
+        );
+
+        let req = Request::builder()
+            .method(Method::POST)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(PAYLOAD.into())
+            .unwrap();
+        let resp = service.serve(Context::default(), req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_raw_and_parsed_form() {
+        const PAYLOAD: &str = "name=Devan&age=29&extra=%F0%9F%A6%99";
+
+        let service = WebService::default().post(
+            "/",
+            |RawAndParsed { raw, parsed }: RawAndParsed<Form<Input>>| async move {
+                assert_eq!(raw, PAYLOAD.as_bytes());
+                assert_eq!(parsed.name, "Devan");
+                assert_eq!(parsed.age, 29);
+            },
+        );
+
+        let req = Request::builder()
+            .method(Method::POST)
+            .header(
+                http::header::CONTENT_TYPE,
+                "application/x-www-form-urlencoded",
+            )
+            .body(PAYLOAD.into())
+            .unwrap();
+        let resp = service.serve(Context::default(), req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_raw_and_parsed_inner_rejection() {
+        let service = WebService::default().post("/", |_: RawAndParsed<Json<Input>>| async move {
+            StatusCode::OK
+        });
+
+        let req = Request::builder()
+            .method(Method::POST)
+            .header(http::header::CONTENT_TYPE, "text/plain")
+            .body(r#"{"name": "glen", "age": 42}"#.into())
+            .unwrap();
+        let resp = service.serve(Context::default(), req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let req = Request::builder()
+            .method(Method::POST)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(r#"{"name": "glen"}"#.into())
+            .unwrap();
+        let resp = service.serve(Context::default(), req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
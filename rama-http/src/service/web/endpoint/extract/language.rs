@@ -0,0 +1,234 @@
+use super::FromRequestContextRefPair;
+use crate::dep::http::request::Parts;
+use crate::header::ACCEPT_LANGUAGE;
+use crate::headers::{Quality, QualityValue};
+use crate::utils::macros::define_http_rejection;
+use rama_core::Context;
+use rama_utils::macros::impl_deref;
+use std::cmp::Reverse;
+
+/// [`Context`] extension defining the languages supported by a web service,
+/// used by the [`NegotiatedLanguage`] extractor.
+///
+/// The first language is the default language,
+/// which is used when none of the supported languages is accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupportedLanguages {
+    languages: Vec<String>,
+}
+
+impl SupportedLanguages {
+    /// Create a new [`SupportedLanguages`] with the given default language.
+    pub fn new(default: impl Into<String>) -> Self {
+        Self {
+            languages: vec![default.into()],
+        }
+    }
+
+    /// Add a supported language, with a lower preference
+    /// than the languages that were already added.
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.languages.push(language.into());
+        self
+    }
+
+    /// Add a supported language, with a lower preference
+    /// than the languages that were already added.
+    pub fn set_language(&mut self, language: impl Into<String>) -> &mut Self {
+        self.languages.push(language.into());
+        self
+    }
+
+    /// Returns the default language.
+    pub fn default_language(&self) -> &str {
+        &self.languages[0]
+    }
+
+    /// Returns an iterator over the supported languages,
+    /// in order of preference.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.languages.iter().map(String::as_str)
+    }
+
+    /// Select the best supported language for the given language ranges,
+    /// as found in the `Accept-Language` header.
+    ///
+    /// Ranges are matched in order of their quality, using the header order
+    /// to break ties. A range matches a supported language if they are equal
+    /// (ignoring case), or if one is a prefix of the other ending at a `-`,
+    /// such that `en` matches `en-US` and vice versa. The wildcard `*` matches
+    /// any supported language not explicitly rejected with `q=0`.
+    fn negotiate<'a>(&self, ranges: impl Iterator<Item = &'a str>) -> &str {
+        let mut ranges: Vec<QualityValue<String>> = ranges
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|range| !range.is_empty())
+            .filter_map(|range| range.parse().ok())
+            .collect();
+        // stable sort, preserving the header order for equal qualities
+        ranges.sort_by_key(|range| Reverse(range.quality));
+
+        let zero = Quality::from(0u16);
+        let is_rejected = |language: &str| {
+            ranges
+                .iter()
+                .any(|range| range.quality == zero && range_matches(&range.value, language))
+        };
+
+        for range in ranges.iter().filter(|range| range.quality > zero) {
+            let matched = if range.value == "*" {
+                self.iter().find(|language| !is_rejected(language))
+            } else {
+                self.iter()
+                    .find(|language| range_matches(&range.value, language))
+            };
+            if let Some(language) = matched {
+                return language;
+            }
+        }
+
+        self.default_language()
+    }
+}
+
+fn range_matches(range: &str, language: &str) -> bool {
+    fn is_prefix(prefix: &str, s: &str) -> bool {
+        s.len() > prefix.len()
+            && s.as_bytes()[prefix.len()] == b'-'
+            && s[..prefix.len()].eq_ignore_ascii_case(prefix)
+    }
+
+    range.eq_ignore_ascii_case(language) || is_prefix(range, language) || is_prefix(language, range)
+}
+
+/// Extractor that selects the best language, out of the [`SupportedLanguages`]
+/// found in the [`Context`], for the `Accept-Language` header of the request.
+///
+/// The default language of the [`SupportedLanguages`] is selected
+/// when the header is missing or none of the supported languages is accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegotiatedLanguage(pub String);
+
+impl_deref!(NegotiatedLanguage: String);
+
+define_http_rejection! {
+    #[status = INTERNAL_SERVER_ERROR]
+    #[body = "No supported languages defined to negotiate the language"]
+    /// Rejection type used if the [`NegotiatedLanguage`] extractor
+    /// cannot find the [`SupportedLanguages`] in the [`Context`].
+    pub struct MissingSupportedLanguages;
+}
+
+impl<S> FromRequestContextRefPair<S> for NegotiatedLanguage
+where
+    S: Clone + Send + Sync + 'static,
+{
+    type Rejection = MissingSupportedLanguages;
+
+    async fn from_request_context_ref_pair(
+        ctx: &Context<S>,
+        parts: &Parts,
+    ) -> Result<Self, Self::Rejection> {
+        let supported = ctx
+            .get::<SupportedLanguages>()
+            .ok_or(MissingSupportedLanguages)?;
+        let ranges = parts
+            .headers
+            .get_all(ACCEPT_LANGUAGE)
+            .iter()
+            .filter_map(|value| value.to_str().ok());
+        Ok(NegotiatedLanguage(supported.negotiate(ranges).to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Request;
+
+    fn supported() -> SupportedLanguages {
+        SupportedLanguages::new("en-US")
+            .with_language("nl-BE")
+            .with_language("fr")
+    }
+
+    async fn negotiate(accept_language: Option<&str>) -> String {
+        let mut builder = Request::builder().uri("/");
+        if let Some(value) = accept_language {
+            builder = builder.header(ACCEPT_LANGUAGE, value);
+        }
+        let (parts, _) = builder.body(()).unwrap().into_parts();
+
+        let mut ctx = Context::default();
+        ctx.insert(supported());
+        let NegotiatedLanguage(language) =
+            NegotiatedLanguage::from_request_context_ref_pair(&ctx, &parts)
+                .await
+                .unwrap();
+        language
+    }
+
+    #[tokio::test]
+    async fn test_negotiated_language_quality_ordering() {
+        for (accept_language, expected) in [
+            ("nl-BE", "nl-BE"),
+            ("fr;q=0.5, nl-BE;q=0.8", "nl-BE"),
+            ("fr;q=0.8, nl-BE;q=0.5, en-US;q=0.1", "fr"),
+            ("de, fr;q=0.9, nl;q=0.9", "fr"),
+            ("de, nl;q=0.9, fr;q=0.9", "nl-BE"),
+            ("FR-ca", "fr"),
+            ("en", "en-US"),
+            ("de, invalid;q=2, nl-be;q=0.3", "nl-BE"),
+        ] {
+            assert_eq!(
+                negotiate(Some(accept_language)).await,
+                expected,
+                "accept-language: {accept_language}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_negotiated_language_wildcard() {
+        for (accept_language, expected) in [
+            ("*", "en-US"),
+            ("de, *;q=0.5", "en-US"),
+            ("en-US;q=0, *", "nl-BE"),
+            ("en;q=0, nl;q=0, *;q=0.1", "fr"),
+            ("de, *;q=0.1, fr;q=0.2", "fr"),
+        ] {
+            assert_eq!(
+                negotiate(Some(accept_language)).await,
+                expected,
+                "accept-language: {accept_language}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_negotiated_language_fallback() {
+        for accept_language in [None, Some(""), Some("de, ja;q=0.5"), Some("fr;q=0")] {
+            assert_eq!(
+                negotiate(accept_language).await,
+                "en-US",
+                "accept-language: {accept_language:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_negotiated_language_missing_supported_languages() {
+        let (parts, _) = Request::builder()
+            .uri("/")
+            .header(ACCEPT_LANGUAGE, "en")
+            .body(())
+            .unwrap()
+            .into_parts();
+        assert!(NegotiatedLanguage::from_request_context_ref_pair(
+            &Context::<()>::default(),
+            &parts
+        )
+        .await
+        .is_err());
+    }
+}
@@ -20,6 +20,10 @@ mod query;
 #[doc(inline)]
 pub use query::Query;
 
+mod language;
+#[doc(inline)]
+pub use language::{MissingSupportedLanguages, NegotiatedLanguage, SupportedLanguages};
+
 mod method;
 mod request;
 
@@ -29,7 +33,7 @@ pub use typed_header::{TypedHeader, TypedHeaderRejection, TypedHeaderRejectionRe
 
 mod body;
 #[doc(inline)]
-pub use body::{Body, Bytes, Form, Json, Text};
+pub use body::{Body, Bytes, Form, Json, RawAndParsed, Text};
 
 mod option;
 #[doc(inline)]
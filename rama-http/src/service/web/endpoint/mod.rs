@@ -8,6 +8,8 @@ pub mod extract;
 pub(crate) struct Endpoint<State> {
     pub(crate) matcher: HttpMatcher<State, Body>,
     pub(crate) service: BoxService<State, Request, Response, Infallible>,
+    /// whether this endpoint can serve `HEAD` requests as a `GET` endpoint
+    pub(crate) auto_head: bool,
 }
 
 /// utility trait to accept multiple types as an endpoint service for [`super::WebService`]
@@ -11,3 +11,5 @@ pub use endpoint::{extract, EndpointServiceFn, IntoEndpointService};
 pub mod k8s;
 #[doc(inline)]
 pub use k8s::{k8s_health, k8s_health_builder};
+
+pub mod reporting;
@@ -0,0 +1,403 @@
+//! Endpoint to receive the reports delivered by user agents.
+//!
+//! User agents deliver their reports, such as the network error reports
+//! configured using the [`Nel`] header, by `POST`ing a json array of [`Report`]s
+//! to the endpoints configured using the [`ReportTo`] or [`ReportingEndpoints`] header.
+//!
+//! The [`ReportCollector`] is an endpoint service which parses these reports
+//! and passes them to the inner [`Service`] for processing (e.g. logging or storing them).
+//! Note that the reports of cross-origin endpoints are only delivered if
+//! the endpoint allows it, which can be achieved using the [`CorsLayer`].
+//!
+//! # Example
+//!
+//! ```
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Service};
+//! use rama_http::service::web::reporting::{Report, ReportCollector};
+//! use rama_http::{Body, Method, Request, StatusCode};
+//! use std::convert::Infallible;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let service = ReportCollector::new(service_fn(|reports: Vec<Report>| async move {
+//!     for report in reports {
+//!         if let Some(nel) = report.network_error() {
+//!             tracing::warn!(url = %report.url, error = %nel.kind, "network error reported");
+//!         }
+//!     }
+//!     Ok::<_, Infallible>(())
+//! }));
+//!
+//! let request = Request::builder()
+//!     .method(Method::POST)
+//!     .header("content-type", "application/reports+json")
+//!     .body(Body::from(
+//!         r#"[{"age":0,"type":"network-error","url":"https://example.com/","body":{"phase":"dns","type":"dns.name_not_resolved"}}]"#,
+//!     ))
+//!     .unwrap();
+//! let response = service.serve(Context::default(), request).await.unwrap();
+//! assert_eq!(response.status(), StatusCode::NO_CONTENT);
+//! # }
+//! ```
+//!
+//! [`Nel`]: crate::headers::Nel
+//! [`ReportTo`]: crate::headers::ReportTo
+//! [`ReportingEndpoints`]: crate::headers::ReportingEndpoints
+//! [`CorsLayer`]: crate::layer::cors::CorsLayer
+
+use crate::dep::http_body_util::{BodyExt, Limited};
+use crate::{header::CONTENT_TYPE, Body, Method, Request, Response, StatusCode};
+use rama_core::{Context, Service};
+use rama_utils::macros::define_inner_service_accessors;
+use serde::{Deserialize, Serialize};
+use std::{convert::Infallible, fmt};
+
+/// A report delivered by a user agent, as defined in the [Reporting API].
+///
+/// [Reporting API]: https://www.w3.org/TR/reporting-1/#serialize-reports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    /// The type of the report, e.g. `network-error` or `csp-violation`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The number of milliseconds between the generation and delivery of the report.
+    #[serde(default)]
+    pub age: u64,
+    /// The url of the document or request that generated the report.
+    pub url: String,
+    /// The `User-Agent` of the user agent that generated the report.
+    #[serde(default)]
+    pub user_agent: String,
+    /// The body of the report, its format depending on the type of report.
+    #[serde(default)]
+    pub body: serde_json::Value,
+}
+
+impl Report {
+    /// The type of a network error report.
+    pub const NETWORK_ERROR: &'static str = "network-error";
+
+    /// Returns the body of this report parsed as a [`NetworkErrorReport`],
+    /// in case this is a valid network error report.
+    pub fn network_error(&self) -> Option<NetworkErrorReport> {
+        if self.kind != Self::NETWORK_ERROR {
+            return None;
+        }
+        NetworkErrorReport::deserialize(&self.body).ok()
+    }
+}
+
+/// The body of a network error report, as defined in the
+/// [W3C Network Error Logging] specification.
+///
+/// [W3C Network Error Logging]: https://www.w3.org/TR/network-error-logging/#generate-a-network-error-report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkErrorReport {
+    /// The phase of the request in which the error occurred,
+    /// one of `dns`, `connection` or `application`.
+    pub phase: String,
+    /// The type of the error, e.g. `tcp.refused` or `ok` in case of success.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The sampling rate that was applied for this report.
+    #[serde(default)]
+    pub sampling_fraction: f64,
+    /// The elapsed number of milliseconds between the start of the request
+    /// and its completion or abortion.
+    #[serde(default)]
+    pub elapsed_time: u64,
+    /// The ip address of the server the request was sent to, if known.
+    #[serde(default)]
+    pub server_ip: String,
+    /// The ALPN id of the network protocol used, e.g. `http/1.1` or `h2`.
+    #[serde(default)]
+    pub protocol: String,
+    /// The referrer of the request.
+    #[serde(default)]
+    pub referrer: String,
+    /// The method of the request.
+    #[serde(default)]
+    pub method: String,
+    /// The status code of the response, if any.
+    #[serde(default)]
+    pub status_code: u16,
+}
+
+impl NetworkErrorReport {
+    /// Returns `true` if this report is about a successful request.
+    pub fn is_success(&self) -> bool {
+        self.kind == "ok"
+    }
+}
+
+/// Endpoint service which receives the [`Report`]s delivered by user agents,
+/// passing them to the inner [`Service`].
+///
+/// Responds with `204 No Content` if the reports were processed by the inner service,
+/// and with a `4xx` status code for requests which do not deliver valid reports.
+///
+/// See the [module docs](self) for more details.
+pub struct ReportCollector<S> {
+    inner: S,
+    max_body_size: usize,
+}
+
+impl<S> ReportCollector<S> {
+    /// The default maximum size of the body of a report delivery.
+    pub const DEFAULT_MAX_BODY_SIZE: usize = 64 * 1024;
+
+    /// Create a new [`ReportCollector`] passing the received reports
+    /// to the given [`Service`].
+    pub const fn new(inner: S) -> Self {
+        Self {
+            inner,
+            max_body_size: Self::DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    /// Set the maximum size of the body of a report delivery.
+    ///
+    /// Defaults to [`Self::DEFAULT_MAX_BODY_SIZE`].
+    pub const fn with_max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = size;
+        self
+    }
+
+    /// Set the maximum size of the body of a report delivery.
+    ///
+    /// Defaults to [`Self::DEFAULT_MAX_BODY_SIZE`].
+    pub fn set_max_body_size(&mut self, size: usize) -> &mut Self {
+        self.max_body_size = size;
+        self
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S: fmt::Debug> fmt::Debug for ReportCollector<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReportCollector")
+            .field("inner", &self.inner)
+            .field("max_body_size", &self.max_body_size)
+            .finish()
+    }
+}
+
+impl<S: Clone> Clone for ReportCollector<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            max_body_size: self.max_body_size,
+        }
+    }
+}
+
+fn is_reports_content_type(req: &Request) -> bool {
+    req.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<mime::Mime>().ok())
+        .is_some_and(|mime| {
+            matches!(
+                mime.essence_str(),
+                "application/reports+json" | "application/json"
+            )
+        })
+}
+
+fn status_response(status: StatusCode) -> Response {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
+
+impl<State, S> Service<State, Request> for ReportCollector<S>
+where
+    State: Clone + Send + Sync + 'static,
+    S: Service<State, Vec<Report>, Response = (), Error: fmt::Display>,
+{
+    type Response = Response;
+    type Error = Infallible;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        if req.method() != Method::POST {
+            return Ok(status_response(StatusCode::METHOD_NOT_ALLOWED));
+        }
+        if !is_reports_content_type(&req) {
+            return Ok(status_response(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+        }
+
+        let body = match Limited::new(req.into_body(), self.max_body_size)
+            .collect()
+            .await
+        {
+            Ok(body) => body.to_bytes(),
+            Err(err) => {
+                tracing::debug!(error = %err, "report collector: failed to collect body");
+                return Ok(status_response(StatusCode::PAYLOAD_TOO_LARGE));
+            }
+        };
+        let reports: Vec<Report> = match serde_json::from_slice(&body) {
+            Ok(reports) => reports,
+            Err(err) => {
+                tracing::debug!(error = %err, "report collector: invalid reports");
+                return Ok(status_response(StatusCode::BAD_REQUEST));
+            }
+        };
+
+        match self.inner.serve(ctx, reports).await {
+            Ok(()) => Ok(status_response(StatusCode::NO_CONTENT)),
+            Err(err) => {
+                tracing::error!(error = %err, "report collector: failed to process reports");
+                Ok(status_response(StatusCode::INTERNAL_SERVER_ERROR))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::error::OpaqueError;
+    use rama_core::service::service_fn;
+    use std::sync::{Arc, Mutex};
+
+    const NEL_REPORTS: &str = r#"[{
+        "age": 20,
+        "type": "network-error",
+        "url": "https://example.com/thing.js",
+        "user_agent": "Mozilla/5.0",
+        "body": {
+            "referrer": "https://example.com/",
+            "sampling_fraction": 1.0,
+            "server_ip": "2001:db8::1",
+            "protocol": "h2",
+            "method": "GET",
+            "request_headers": {},
+            "response_headers": {},
+            "status_code": 0,
+            "elapsed_time": 143,
+            "phase": "connection",
+            "type": "tcp.refused"
+        }
+    }, {
+        "age": 1,
+        "type": "csp-violation",
+        "url": "https://example.com/",
+        "body": { "blockedURL": "https://evil.example/script.js" }
+    }]"#;
+
+    fn request(method: Method, content_type: &str, body: &'static str) -> Request {
+        Request::builder()
+            .method(method)
+            .header(CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_nel_report() {
+        let reports: Vec<Report> = serde_json::from_str(NEL_REPORTS).unwrap();
+        assert_eq!(reports.len(), 2);
+
+        let report = &reports[0];
+        assert_eq!(report.kind, Report::NETWORK_ERROR);
+        assert_eq!(report.age, 20);
+        assert_eq!(report.url, "https://example.com/thing.js");
+        assert_eq!(report.user_agent, "Mozilla/5.0");
+
+        let nel = report.network_error().unwrap();
+        assert_eq!(
+            nel,
+            NetworkErrorReport {
+                phase: "connection".to_owned(),
+                kind: "tcp.refused".to_owned(),
+                sampling_fraction: 1.0,
+                elapsed_time: 143,
+                server_ip: "2001:db8::1".to_owned(),
+                protocol: "h2".to_owned(),
+                referrer: "https://example.com/".to_owned(),
+                method: "GET".to_owned(),
+                status_code: 0,
+            }
+        );
+        assert!(!nel.is_success());
+
+        assert_eq!(reports[1].kind, "csp-violation");
+        assert!(reports[1].network_error().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_report_collector() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let service = ReportCollector::new(service_fn({
+            let received = received.clone();
+            move |reports: Vec<Report>| {
+                let received = received.clone();
+                async move {
+                    received.lock().unwrap().extend(reports);
+                    Ok::<_, Infallible>(())
+                }
+            }
+        }));
+
+        let response = service
+            .serve(
+                Context::default(),
+                request(Method::POST, "application/reports+json", NEL_REPORTS),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(received.lock().unwrap().len(), 2);
+
+        for (req, status) in [
+            (
+                request(Method::GET, "application/reports+json", NEL_REPORTS),
+                StatusCode::METHOD_NOT_ALLOWED,
+            ),
+            (
+                request(Method::POST, "text/plain", NEL_REPORTS),
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ),
+            (
+                request(Method::POST, "application/json", r#"{"type":"x"}"#),
+                StatusCode::BAD_REQUEST,
+            ),
+        ] {
+            let response = service.serve(Context::default(), req).await.unwrap();
+            assert_eq!(response.status(), status);
+        }
+        assert_eq!(received.lock().unwrap().len(), 2);
+
+        let service = service.with_max_body_size(16);
+        let response = service
+            .serve(
+                Context::default(),
+                request(Method::POST, "application/reports+json", NEL_REPORTS),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_report_collector_inner_error() {
+        let service = ReportCollector::new(service_fn(|_reports: Vec<Report>| async move {
+            Err::<(), _>(OpaqueError::from_display("storage unavailable"))
+        }));
+        let response = service
+            .serve(
+                Context::default(),
+                request(Method::POST, "application/reports+json", "[]"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -1,8 +1,10 @@
 use super::{endpoint::Endpoint, IntoEndpointService};
 use crate::{
+    dep::http_body::Body as _,
+    header::CONTENT_LENGTH,
     matcher::{HttpMatcher, UriParams},
     service::fs::ServeDir,
-    Body, IntoResponse, Request, Response, StatusCode, Uri,
+    Body, HeaderValue, IntoResponse, Method, Request, Response, StatusCode, Uri,
 };
 use rama_core::{
     context::Extensions,
@@ -21,12 +23,15 @@ use std::{convert::Infallible, fmt, future::Future, marker::PhantomData, sync::A
 pub struct WebService<State> {
     endpoints: Vec<Arc<Endpoint<State>>>,
     not_found: Arc<BoxService<State, Request, Response, Infallible>>,
+    auto_head: bool,
     _phantom: PhantomData<State>,
 }
 
 impl<State> std::fmt::Debug for WebService<State> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("WebService").finish()
+        f.debug_struct("WebService")
+            .field("auto_head", &self.auto_head)
+            .finish()
     }
 }
 
@@ -35,6 +40,7 @@ impl<State> Clone for WebService<State> {
         Self {
             endpoints: self.endpoints.clone(),
             not_found: self.not_found.clone(),
+            auto_head: self.auto_head,
             _phantom: PhantomData,
         }
     }
@@ -51,12 +57,41 @@ where
             not_found: Arc::new(
                 service_fn(|| async { Ok(StatusCode::NOT_FOUND.into_response()) }).boxed(),
             ),
+            auto_head: false,
             _phantom: PhantomData,
         }
     }
 
+    /// define whether or not `HEAD` requests are automatically served by the GET routes.
+    ///
+    /// When enabled, a `HEAD` request that matches no route is served
+    /// by the GET route (added using [`Self::get`]) matching the same request,
+    /// with the body of its response discarded and its headers preserved,
+    /// including the `Content-Length` of the discarded body if known.
+    ///
+    /// Use [`Self::get_without_auto_head`] for GET routes which are
+    /// not to be served for `HEAD` requests, e.g. because they have side effects.
+    ///
+    /// Disabled by default.
+    pub fn auto_head(mut self, enabled: bool) -> Self {
+        self.auto_head = enabled;
+        self
+    }
+
     /// add a GET route to the web service, using the given service.
+    ///
+    /// This route also serves `HEAD` requests in case [`Self::auto_head`] is enabled.
     pub fn get<I, T>(self, path: &str, service: I) -> Self
+    where
+        I: IntoEndpointService<State, T>,
+    {
+        let matcher = HttpMatcher::method_get().and_path(path);
+        self.push_endpoint(matcher, service, true)
+    }
+
+    /// add a GET route to the web service, using the given service,
+    /// which is never used to serve `HEAD` requests, even if [`Self::auto_head`] is enabled.
+    pub fn get_without_auto_head<I, T>(self, path: &str, service: I) -> Self
     where
         I: IntoEndpointService<State, T>,
     {
@@ -147,13 +182,26 @@ where
     }
 
     /// add a route to the web service which matches the given matcher, using the given service.
-    pub fn on<I, T>(mut self, matcher: HttpMatcher<State, Body>, service: I) -> Self
+    pub fn on<I, T>(self, matcher: HttpMatcher<State, Body>, service: I) -> Self
+    where
+        I: IntoEndpointService<State, T>,
+    {
+        self.push_endpoint(matcher, service, false)
+    }
+
+    fn push_endpoint<I, T>(
+        mut self,
+        matcher: HttpMatcher<State, Body>,
+        service: I,
+        auto_head: bool,
+    ) -> Self
     where
         I: IntoEndpointService<State, T>,
     {
         let endpoint = Endpoint {
             matcher,
             service: service.into_endpoint_service().boxed(),
+            auto_head,
         };
         self.endpoints.push(Arc::new(endpoint));
         self
@@ -249,10 +297,45 @@ where
             // clear the extensions for the next matcher
             ext.clear();
         }
+
+        if self.auto_head && req.method() == Method::HEAD {
+            // serve the HEAD request as a GET request by one of the GET routes
+            let (mut parts, body) = req.into_parts();
+            parts.method = Method::GET;
+            let req = Request::from_parts(parts, body);
+            for endpoint in self.endpoints.iter().filter(|endpoint| endpoint.auto_head) {
+                if endpoint.matcher.matches(Some(&mut ext), &ctx, &req) {
+                    ctx.extend(ext);
+                    let res = endpoint.service.serve(ctx, req).await?;
+                    return Ok(discard_body(res));
+                }
+                ext.clear();
+            }
+
+            let (mut parts, body) = req.into_parts();
+            parts.method = Method::HEAD;
+            return self
+                .not_found
+                .serve(ctx, Request::from_parts(parts, body))
+                .await;
+        }
+
         self.not_found.serve(ctx, req).await
     }
 }
 
+/// discard the body of a response, preserving its headers,
+/// including the `Content-Length` of the discarded body if known.
+fn discard_body(res: Response) -> Response {
+    let (mut parts, body) = res.into_parts();
+    if !parts.headers.contains_key(CONTENT_LENGTH) {
+        if let Some(len) = body.size_hint().exact() {
+            parts.headers.insert(CONTENT_LENGTH, HeaderValue::from(len));
+        }
+    }
+    Response::from_parts(parts, Body::empty())
+}
+
 #[doc(hidden)]
 #[macro_export]
 /// Create a new [`Service`] from a chain of matcher-service tuples.
@@ -332,8 +415,10 @@ pub use crate::__match_service as match_service;
 #[cfg(test)]
 mod test {
     use crate::dep::http_body_util::BodyExt;
+    use crate::header::CONTENT_TYPE;
     use crate::matcher::MethodMatcher;
     use crate::Body;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     use super::*;
 
@@ -492,4 +577,104 @@ mod test {
         let res = get_response(&svc, "https://www.test.io").await;
         assert_eq!(res.status(), StatusCode::NOT_FOUND);
     }
+
+    fn request(method: Method, path: &str) -> Request {
+        Request::builder()
+            .method(method)
+            .uri(format!("https://www.example.com{path}"))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn hello() -> Response {
+        (
+            [("x-hello", "world"), ("content-type", "text/plain")],
+            "hello",
+        )
+            .into_response()
+    }
+
+    #[tokio::test]
+    async fn test_auto_head_for_get_route() {
+        let svc = WebService::default().auto_head(true).get("/hello", hello);
+
+        let get = svc
+            .serve(Context::default(), request(Method::GET, "/hello"))
+            .await
+            .unwrap();
+        let head = svc
+            .serve(Context::default(), request(Method::HEAD, "/hello"))
+            .await
+            .unwrap();
+
+        assert_eq!(head.status(), StatusCode::OK);
+        assert_eq!(head.headers()["x-hello"], get.headers()["x-hello"]);
+        assert_eq!(head.headers()[CONTENT_TYPE], get.headers()[CONTENT_TYPE]);
+        assert_eq!(head.headers()[CONTENT_LENGTH], "5");
+
+        assert!(head
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes()
+            .is_empty());
+        assert_eq!(get.into_body().collect().await.unwrap().to_bytes(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_auto_head_prefers_head_route() {
+        let svc = WebService::default()
+            .auto_head(true)
+            .get("/hello", hello)
+            .head("/hello", StatusCode::NO_CONTENT);
+
+        let res = svc
+            .serve(Context::default(), request(Method::HEAD, "/hello"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_auto_head_opt_out() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = WebService::default()
+            .auto_head(true)
+            .get_without_auto_head("/delete-me", {
+                let calls = calls.clone();
+                move || {
+                    let calls = calls.clone();
+                    async move {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        StatusCode::OK
+                    }
+                }
+            });
+
+        let res = svc
+            .serve(Context::default(), request(Method::HEAD, "/delete-me"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let res = svc
+            .serve(Context::default(), request(Method::GET, "/delete-me"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_auto_head_disabled_by_default() {
+        let svc = WebService::default().get("/hello", hello);
+
+        let res = svc
+            .serve(Context::default(), request(Method::HEAD, "/hello"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -0,0 +1,186 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default)]
+/// A server-sent event, as defined by the [`text/event-stream`] format.
+///
+/// Multi-line data is sent as multiple `data:` lines,
+/// which the client joins again using newlines.
+///
+/// [`text/event-stream`]: https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation
+pub struct Event {
+    event: Option<String>,
+    data: Option<String>,
+    id: Option<String>,
+    retry: Option<Duration>,
+    comment: Option<String>,
+}
+
+impl Event {
+    /// Create a new empty [`Event`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the name of the event, dispatched by the client as the event type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name contains a newline or carriage return.
+    pub fn with_event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(single_line("event", event.into()));
+        self
+    }
+
+    /// Set the name of the event, dispatched by the client as the event type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name contains a newline or carriage return.
+    pub fn set_event(&mut self, event: impl Into<String>) -> &mut Self {
+        self.event = Some(single_line("event", event.into()));
+        self
+    }
+
+    /// Set the data of the event.
+    pub fn with_data(mut self, data: impl Into<String>) -> Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// Set the data of the event.
+    pub fn set_data(&mut self, data: impl Into<String>) -> &mut Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// Set the id of the event, used by the client as the `Last-Event-ID` when reconnecting.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id contains a newline, carriage return or null character.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(event_id(id.into()));
+        self
+    }
+
+    /// Set the id of the event, used by the client as the `Last-Event-ID` when reconnecting.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id contains a newline, carriage return or null character.
+    pub fn set_id(&mut self, id: impl Into<String>) -> &mut Self {
+        self.id = Some(event_id(id.into()));
+        self
+    }
+
+    /// Set the reconnection time the client has to use when the connection is lost.
+    pub fn with_retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Set the reconnection time the client has to use when the connection is lost.
+    pub fn set_retry(&mut self, retry: Duration) -> &mut Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Set a comment, which is ignored by the client.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the comment contains a newline or carriage return.
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(single_line("comment", comment.into()));
+        self
+    }
+
+    /// Set a comment, which is ignored by the client.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the comment contains a newline or carriage return.
+    pub fn set_comment(&mut self, comment: impl Into<String>) -> &mut Self {
+        self.comment = Some(single_line("comment", comment.into()));
+        self
+    }
+
+    /// Encode the event in the `text/event-stream` format,
+    /// including the blank line that terminates it.
+    pub fn encode(&self) -> Bytes {
+        let mut buf = BytesMut::new();
+        if let Some(comment) = &self.comment {
+            put_field(&mut buf, "", comment);
+        }
+        if let Some(event) = &self.event {
+            put_field(&mut buf, "event", event);
+        }
+        if let Some(data) = &self.data {
+            for line in data.split('\n') {
+                put_field(&mut buf, "data", line.strip_suffix('\r').unwrap_or(line));
+            }
+        }
+        if let Some(id) = &self.id {
+            put_field(&mut buf, "id", id);
+        }
+        if let Some(retry) = self.retry {
+            put_field(&mut buf, "retry", &retry.as_millis().to_string());
+        }
+        buf.put_u8(b'\n');
+        buf.freeze()
+    }
+}
+
+fn put_field(buf: &mut BytesMut, name: &str, value: &str) {
+    buf.put_slice(name.as_bytes());
+    buf.put_u8(b':');
+    if !value.is_empty() {
+        buf.put_u8(b' ');
+        buf.put_slice(value.as_bytes());
+    }
+    buf.put_u8(b'\n');
+}
+
+fn single_line(field: &str, value: String) -> String {
+    assert!(
+        !value.contains(['\n', '\r']),
+        "sse event {field} cannot contain newlines or carriage returns"
+    );
+    value
+}
+
+fn event_id(value: String) -> String {
+    assert!(
+        !value.contains('\0'),
+        "sse event id cannot contain null characters"
+    );
+    single_line("id", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_encode() {
+        assert_eq!(Event::new().encode(), "\n");
+        assert_eq!(Event::new().with_data("hello").encode(), "data: hello\n\n");
+        assert_eq!(
+            Event::new()
+                .with_comment("keep")
+                .with_event("greet")
+                .with_data("hello\r\nworld\n")
+                .with_id("42")
+                .with_retry(Duration::from_secs(3))
+                .encode(),
+            ": keep\nevent: greet\ndata: hello\ndata: world\ndata:\nid: 42\nretry: 3000\n\n"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_event_name_with_newline() {
+        let _ = Event::new().with_event("foo\nbar");
+    }
+}
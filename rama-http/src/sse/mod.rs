@@ -0,0 +1,41 @@
+//! Server-sent events (SSE) support.
+//!
+//! Use [`Sse`] to turn a [`Stream`] of [`Event`]s into a `text/event-stream` response,
+//! with periodic heartbeats, an optional `retry:` hint and a buffer
+//! that disconnects clients which cannot keep up with the events.
+//!
+//! [`Stream`]: futures_lite::Stream
+//!
+//! # Example
+//!
+//! ```
+//! use rama_core::{service::service_fn, Context, Service};
+//! use rama_http::sse::{Event, Sse};
+//! use rama_http::{IntoResponse, Request, Response, StatusCode};
+//! use std::{convert::Infallible, time::Duration};
+//!
+//! async fn events(_ctx: Context<()>, _req: Request) -> Result<Response, Infallible> {
+//!     let stream = futures_lite::stream::iter(
+//!         (0..3).map(|i| Event::new().with_event("tick").with_data(i.to_string())),
+//!     );
+//!     Ok(Sse::new(stream)
+//!         .with_heartbeat(Duration::from_secs(10))
+//!         .with_retry(Duration::from_secs(5))
+//!         .into_response())
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let service = service_fn(events);
+//! let resp = service.serve(Context::default(), Request::default()).await.unwrap();
+//! assert_eq!(resp.status(), StatusCode::OK);
+//! # }
+//! ```
+
+mod event;
+#[doc(inline)]
+pub use event::Event;
+
+mod server;
+#[doc(inline)]
+pub use server::{SlowConsumer, Sse, DEFAULT_BUFFER_CAPACITY, DEFAULT_HEARTBEAT_INTERVAL};
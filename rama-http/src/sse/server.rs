@@ -0,0 +1,337 @@
+use super::Event;
+use crate::{
+    dep::http_body::{Body as HttpBody, Frame, SizeHint},
+    header, Body, HeaderValue, IntoResponse, Response,
+};
+use bytes::Bytes;
+use futures_lite::{Stream, StreamExt};
+use rama_core::error::BoxError;
+use rama_utils::macros::error::static_str_error;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    sync::mpsc::{self, error::TrySendError},
+    time::{Instant, Sleep},
+};
+
+/// The default interval of the heartbeats sent by [`Sse`].
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The default amount of events [`Sse`] buffers for a client.
+pub const DEFAULT_BUFFER_CAPACITY: usize = 64;
+
+static_str_error! {
+    #[doc = "sse client disconnected as it could not keep up with the events"]
+    pub struct SlowConsumer;
+}
+
+/// A server-sent events response, streaming the [`Event`]s of a [`Stream`]
+/// as a `text/event-stream` body.
+///
+/// The events of the stream are produced by a spawned task into a buffer
+/// of a limited capacity. A client that is too slow to keep up,
+/// such that this buffer is full when the next event is produced,
+/// is disconnected, at which point the stream is dropped as well.
+///
+/// A comment is sent as heartbeat when no event was sent for the heartbeat interval,
+/// in order to keep the connection alive through intermediaries.
+///
+/// See the [module docs](super) for an example.
+pub struct Sse<S> {
+    stream: S,
+    heartbeat: Option<Duration>,
+    retry: Option<Duration>,
+    buffer_capacity: usize,
+}
+
+impl<S> fmt::Debug for Sse<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sse")
+            .field("stream", &format_args!("{}", std::any::type_name::<S>()))
+            .field("heartbeat", &self.heartbeat)
+            .field("retry", &self.retry)
+            .field("buffer_capacity", &self.buffer_capacity)
+            .finish()
+    }
+}
+
+impl<S> Sse<S> {
+    /// Create a new [`Sse`] response for the given stream of events.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            heartbeat: Some(DEFAULT_HEARTBEAT_INTERVAL),
+            retry: None,
+            buffer_capacity: DEFAULT_BUFFER_CAPACITY,
+        }
+    }
+
+    /// Set the interval after which a heartbeat is sent when no event was sent.
+    pub fn with_heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat = Some(interval);
+        self
+    }
+
+    /// Set the interval after which a heartbeat is sent when no event was sent.
+    pub fn set_heartbeat(&mut self, interval: Duration) -> &mut Self {
+        self.heartbeat = Some(interval);
+        self
+    }
+
+    /// Do not send any heartbeats.
+    pub fn without_heartbeat(mut self) -> Self {
+        self.heartbeat = None;
+        self
+    }
+
+    /// Set the reconnection time hint, sent to the client prior to the first event.
+    pub fn with_retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Set the reconnection time hint, sent to the client prior to the first event.
+    pub fn set_retry(&mut self, retry: Duration) -> &mut Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Set the amount of events buffered for the client,
+    /// after which the client is disconnected as a [`SlowConsumer`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    pub fn with_buffer_capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "sse buffer capacity cannot be zero");
+        self.buffer_capacity = capacity;
+        self
+    }
+
+    /// Set the amount of events buffered for the client,
+    /// after which the client is disconnected as a [`SlowConsumer`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    pub fn set_buffer_capacity(&mut self, capacity: usize) -> &mut Self {
+        assert!(capacity > 0, "sse buffer capacity cannot be zero");
+        self.buffer_capacity = capacity;
+        self
+    }
+}
+
+impl<S> IntoResponse for Sse<S>
+where
+    S: Stream<Item = Event> + Send + 'static,
+{
+    fn into_response(self) -> Response {
+        let (tx, rx) = mpsc::channel(self.buffer_capacity);
+        let overflow = Arc::new(AtomicBool::new(false));
+        tokio::spawn(produce(self.stream, tx, overflow.clone()));
+
+        let body = SseBody {
+            rx,
+            overflow,
+            retry: self
+                .retry
+                .map(|retry| Event::new().with_retry(retry).encode()),
+            heartbeat: self
+                .heartbeat
+                .map(|interval| (interval, Box::pin(tokio::time::sleep(interval)))),
+        };
+
+        (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("text/event-stream"),
+                ),
+                (header::CACHE_CONTROL, HeaderValue::from_static("no-cache")),
+            ],
+            Body::new(body),
+        )
+            .into_response()
+    }
+}
+
+async fn produce<S>(stream: S, tx: mpsc::Sender<Bytes>, overflow: Arc<AtomicBool>)
+where
+    S: Stream<Item = Event> + Send + 'static,
+{
+    let mut stream = std::pin::pin!(stream);
+    loop {
+        let event = tokio::select! {
+            event = stream.next() => event,
+            _ = tx.closed() => return,
+        };
+        let Some(event) = event else {
+            return;
+        };
+        match tx.try_send(event.encode()) {
+            Ok(()) => (),
+            Err(TrySendError::Full(_)) => {
+                tracing::debug!("sse: disconnect slow consumer");
+                overflow.store(true, Ordering::Release);
+                return;
+            }
+            Err(TrySendError::Closed(_)) => return,
+        }
+    }
+}
+
+struct SseBody {
+    rx: mpsc::Receiver<Bytes>,
+    overflow: Arc<AtomicBool>,
+    retry: Option<Bytes>,
+    heartbeat: Option<(Duration, Pin<Box<Sleep>>)>,
+}
+
+impl SseBody {
+    fn reset_heartbeat(&mut self) {
+        if let Some((interval, sleep)) = &mut self.heartbeat {
+            sleep.as_mut().reset(Instant::now() + *interval);
+        }
+    }
+}
+
+impl HttpBody for SseBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+
+        if let Some(retry) = this.retry.take() {
+            return Poll::Ready(Some(Ok(Frame::data(retry))));
+        }
+
+        if this.overflow.load(Ordering::Acquire) {
+            return Poll::Ready(Some(Err(SlowConsumer.into())));
+        }
+
+        match this.rx.poll_recv(cx) {
+            Poll::Ready(Some(event)) => {
+                this.reset_heartbeat();
+                return Poll::Ready(Some(Ok(Frame::data(event))));
+            }
+            Poll::Ready(None) => {
+                // the producer might have stopped in between
+                // the overflow check and the receive
+                return Poll::Ready(
+                    this.overflow
+                        .load(Ordering::Acquire)
+                        .then(|| Err(SlowConsumer.into())),
+                );
+            }
+            Poll::Pending => (),
+        }
+
+        if let Some((_, sleep)) = &mut this.heartbeat {
+            if sleep.as_mut().poll(cx).is_ready() {
+                this.reset_heartbeat();
+                return Poll::Ready(Some(Ok(Frame::data(Bytes::from_static(b":\n\n")))));
+            }
+        }
+
+        Poll::Pending
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dep::http_body_util::BodyExt;
+    use crate::StatusCode;
+    use rama_core::error::OpaqueError;
+
+    async fn next_frame(body: &mut Body) -> Option<Result<Bytes, OpaqueError>> {
+        tokio::time::timeout(Duration::from_secs(1), body.frame())
+            .await
+            .expect("frame within timeout")
+            .map(|frame| frame.map(|frame| frame.into_data().unwrap()))
+    }
+
+    #[tokio::test]
+    async fn test_sse_events_framing() {
+        let events = futures_lite::stream::iter([
+            Event::new().with_event("greet").with_data("hello\nworld"),
+            Event::new().with_id("2").with_data("{}"),
+        ]);
+        let res = Sse::new(events)
+            .with_retry(Duration::from_secs(3))
+            .into_response();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+        assert_eq!(
+            res.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-cache"
+        );
+
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(
+            body,
+            "retry: 3000\n\nevent: greet\ndata: hello\ndata: world\n\ndata: {}\nid: 2\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sse_heartbeat_on_idle() {
+        let (tx, rx) = mpsc::channel::<Event>(1);
+        let events = futures_lite::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|event| (event, rx))
+        });
+        let mut body = Sse::new(events)
+            .with_heartbeat(Duration::from_millis(20))
+            .into_response()
+            .into_body();
+
+        assert_eq!(next_frame(&mut body).await.unwrap().unwrap(), ":\n\n");
+        assert_eq!(next_frame(&mut body).await.unwrap().unwrap(), ":\n\n");
+
+        tx.send(Event::new().with_data("event")).await.unwrap();
+        assert_eq!(
+            next_frame(&mut body).await.unwrap().unwrap(),
+            "data: event\n\n"
+        );
+        assert_eq!(next_frame(&mut body).await.unwrap().unwrap(), ":\n\n");
+
+        drop(tx);
+        assert!(next_frame(&mut body).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sse_disconnect_slow_consumer() {
+        let events =
+            futures_lite::stream::iter((0..10).map(|i| Event::new().with_id(i.to_string())));
+        let mut body = Sse::new(events)
+            .with_buffer_capacity(2)
+            .into_response()
+            .into_body();
+
+        // give the producer the time to fill up the buffer
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let err = next_frame(&mut body).await.unwrap().unwrap_err();
+        assert!(err.downcast_ref::<SlowConsumer>().is_some());
+    }
+}
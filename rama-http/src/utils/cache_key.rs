@@ -0,0 +1,183 @@
+use crate::headers::{HeaderMapExt, Vary};
+use crate::{Request, Response};
+use std::fmt;
+
+/// A canonical cache key for a response to a request,
+/// incorporating the request header values the response varies on.
+///
+/// The key is computed from the request method and uri,
+/// followed by the value of each request header listed in the
+/// response's [`Vary`] header. Header names are matched case-insensitively
+/// and listed in a sorted order, while the values are normalized such that
+/// differences in whitespace around (and within) the comma-separated
+/// elements do not lead to distinct keys.
+///
+/// A `Vary: *` response cannot be cached, in which case no key is computed.
+///
+/// # Example
+///
+/// ```
+/// use rama_http::headers::Vary;
+/// use rama_http::utils::CacheKey;
+/// use rama_http::Request;
+///
+/// let vary = Vary::any();
+/// let req = Request::builder().uri("/").body(()).unwrap();
+/// assert!(CacheKey::from_vary(&req, Some(&vary)).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CacheKey(String);
+
+impl CacheKey {
+    /// Compute the [`CacheKey`] for a response to the given request,
+    /// varying on the given [`Vary`] header (if any) of that response.
+    ///
+    /// Returns `None` in case of `Vary: *`, as such a response is uncacheable.
+    pub fn from_vary<Body>(req: &Request<Body>, vary: Option<&Vary>) -> Option<Self> {
+        let mut names: Vec<String> = match vary {
+            Some(vary) if vary.is_any() => return None,
+            Some(vary) => vary
+                .iter_strs()
+                .map(|name| name.trim().to_ascii_lowercase())
+                .filter(|name| !name.is_empty())
+                .collect(),
+            None => Vec::new(),
+        };
+        if names.iter().any(|name| name == "*") {
+            return None;
+        }
+        names.sort_unstable();
+        names.dedup();
+
+        let mut key = format!("{} {}", req.method(), req.uri());
+        for name in names {
+            // newlines cannot be part of a header name or value,
+            // making it a safe separator for the components of the key
+            key.push('\n');
+            key.push_str(&name);
+
+            let mut values = req.headers().get_all(name.as_str()).iter().peekable();
+            if values.peek().is_none() {
+                // absent header, which is distinct from an empty header
+                continue;
+            }
+            key.push(':');
+            let elements = values.flat_map(|value| {
+                String::from_utf8_lossy(value.as_bytes())
+                    .split(',')
+                    .map(|element| element.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .filter(|element| !element.is_empty())
+                    .collect::<Vec<_>>()
+            });
+            for (index, element) in elements.enumerate() {
+                if index > 0 {
+                    key.push(',');
+                }
+                key.push_str(&element);
+            }
+        }
+
+        Some(Self(key))
+    }
+
+    /// Compute the [`CacheKey`] for the given response to the given request,
+    /// varying on the [`Vary`] header(s) of that response.
+    ///
+    /// Returns `None` in case of `Vary: *`, as such a response is uncacheable.
+    pub fn from_response<ReqBody, ResBody>(
+        req: &Request<ReqBody>,
+        res: &Response<ResBody>,
+    ) -> Option<Self> {
+        Self::from_vary(req, res.headers().typed_get::<Vary>().as_ref())
+    }
+
+    /// Returns the canonical key as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::VARY;
+
+    fn request(headers: &[(&str, &str)]) -> Request<()> {
+        let mut builder = Request::builder().uri("https://example.com/assets/app.js");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(()).unwrap()
+    }
+
+    fn response(vary: &[&str]) -> Response<()> {
+        let mut builder = Response::builder();
+        for value in vary {
+            builder = builder.header(VARY, *value);
+        }
+        builder.body(()).unwrap()
+    }
+
+    #[test]
+    fn test_cache_key_vary_accept_encoding() {
+        let res = response(&["Accept-Encoding"]);
+
+        let gzip = CacheKey::from_response(&request(&[("accept-encoding", "gzip")]), &res).unwrap();
+        let br = CacheKey::from_response(&request(&[("accept-encoding", "br")]), &res).unwrap();
+        let none = CacheKey::from_response(&request(&[]), &res).unwrap();
+        let empty = CacheKey::from_response(&request(&[("accept-encoding", "")]), &res).unwrap();
+        assert_ne!(gzip, br);
+        assert_ne!(gzip, none);
+        assert_ne!(none, empty);
+
+        // header casing and whitespace are normalized
+        assert_eq!(
+            CacheKey::from_response(&request(&[("Accept-Encoding", "gzip,br")]), &res),
+            CacheKey::from_response(&request(&[("accept-encoding", " gzip ,  br ")]), &res),
+        );
+        assert_eq!(
+            CacheKey::from_response(&request(&[("accept-encoding", "gzip, br")]), &res),
+            CacheKey::from_response(
+                &request(&[("accept-encoding", "gzip"), ("accept-encoding", "br")]),
+                &res
+            ),
+        );
+
+        // headers not varied upon do not affect the key
+        assert_eq!(
+            Some(gzip),
+            CacheKey::from_response(
+                &request(&[("accept-encoding", "gzip"), ("accept-language", "nl")]),
+                &res
+            ),
+        );
+    }
+
+    #[test]
+    fn test_cache_key_vary_names_are_canonical() {
+        let req = request(&[("accept-encoding", "gzip"), ("accept-language", "nl")]);
+        assert_eq!(
+            CacheKey::from_response(&req, &response(&["Accept-Language, accept-encoding"])),
+            CacheKey::from_response(&req, &response(&["ACCEPT-ENCODING", "accept-language"])),
+        );
+        assert_ne!(
+            CacheKey::from_response(&req, &response(&["accept-encoding"])),
+            CacheKey::from_response(&req, &response(&[])),
+        );
+    }
+
+    #[test]
+    fn test_cache_key_vary_any_is_uncacheable() {
+        let req = request(&[("accept-encoding", "gzip")]);
+        assert!(CacheKey::from_response(&req, &response(&["*"])).is_none());
+        assert!(CacheKey::from_response(&req, &response(&["accept-encoding, *"])).is_none());
+        assert!(CacheKey::from_vary(&req, Some(&Vary::any())).is_none());
+        assert!(CacheKey::from_vary(&req, None).is_some());
+    }
+}
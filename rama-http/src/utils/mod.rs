@@ -4,6 +4,10 @@ mod header_value;
 #[doc(inline)]
 pub use header_value::{HeaderValueErr, HeaderValueGetter};
 
+mod cache_key;
+#[doc(inline)]
+pub use cache_key::CacheKey;
+
 #[doc(hidden)]
 #[macro_use]
 pub(crate) mod macros;
@@ -1,4 +1,4 @@
-use super::{parse_utils, Domain, DomainAddress, Host};
+use super::{parse_utils, Domain, DomainAddress, Host, HostKind};
 use rama_core::error::{ErrorContext, OpaqueError};
 use std::net::{Ipv4Addr, Ipv6Addr};
 use std::{
@@ -41,6 +41,53 @@ impl Authority {
     pub fn into_parts(self) -> (Host, u16) {
         (self.host, self.port)
     }
+
+    /// Gets the [`HostKind`] of the [`Host`].
+    pub fn host_kind(&self) -> HostKind {
+        match &self.host {
+            Host::Name(_) => HostKind::Domain,
+            Host::Address(IpAddr::V4(_)) => HostKind::Ipv4,
+            Host::Address(IpAddr::V6(_)) => HostKind::Ipv6,
+        }
+    }
+
+    /// Returns `true` if the [`Host`] is a [`Domain`],
+    /// which has to be resolved prior to connecting to it.
+    pub fn requires_resolution(&self) -> bool {
+        matches!(self.host, Host::Name(_))
+    }
+
+    /// Gets the [`Domain`] reference, if the [`Host`] is a [`Domain`].
+    pub fn domain(&self) -> Option<&Domain> {
+        match &self.host {
+            Host::Name(domain) => Some(domain),
+            Host::Address(_) => None,
+        }
+    }
+
+    /// Gets the [`IpAddr`], if the [`Host`] is an IP address.
+    pub fn ip_addr(&self) -> Option<IpAddr> {
+        match self.host {
+            Host::Address(ip) => Some(ip),
+            Host::Name(_) => None,
+        }
+    }
+
+    /// Gets the [`Ipv4Addr`], if the [`Host`] is an IPv4 address.
+    pub fn ipv4_addr(&self) -> Option<Ipv4Addr> {
+        match self.host {
+            Host::Address(IpAddr::V4(ip)) => Some(ip),
+            _ => None,
+        }
+    }
+
+    /// Gets the [`Ipv6Addr`], if the [`Host`] is an IPv6 address.
+    pub fn ipv6_addr(&self) -> Option<Ipv6Addr> {
+        match self.host {
+            Host::Address(IpAddr::V6(ip)) => Some(ip),
+            _ => None,
+        }
+    }
 }
 
 impl From<(Domain, u16)> for Authority {
@@ -314,4 +361,40 @@ mod tests {
             assert_eq!(authority.to_string(), expected, "{}", msg);
         }
     }
+
+    #[test]
+    fn test_host_kind_domain() {
+        let authority: Authority = "example.com:443".parse().unwrap();
+        assert_eq!(authority.host_kind(), HostKind::Domain);
+        assert!(authority.requires_resolution());
+        assert_eq!(
+            authority.domain(),
+            Some(&Domain::from_static("example.com"))
+        );
+        assert_eq!(authority.ip_addr(), None);
+        assert_eq!(authority.ipv4_addr(), None);
+        assert_eq!(authority.ipv6_addr(), None);
+    }
+
+    #[test]
+    fn test_host_kind_ipv4() {
+        let authority: Authority = "127.0.0.1:8080".parse().unwrap();
+        assert_eq!(authority.host_kind(), HostKind::Ipv4);
+        assert!(!authority.requires_resolution());
+        assert_eq!(authority.domain(), None);
+        assert_eq!(authority.ip_addr(), Some(Ipv4Addr::LOCALHOST.into()));
+        assert_eq!(authority.ipv4_addr(), Some(Ipv4Addr::LOCALHOST));
+        assert_eq!(authority.ipv6_addr(), None);
+    }
+
+    #[test]
+    fn test_host_kind_ipv6() {
+        let authority: Authority = "[::1]:8080".parse().unwrap();
+        assert_eq!(authority.host_kind(), HostKind::Ipv6);
+        assert!(!authority.requires_resolution());
+        assert_eq!(authority.domain(), None);
+        assert_eq!(authority.ip_addr(), Some(Ipv6Addr::LOCALHOST.into()));
+        assert_eq!(authority.ipv4_addr(), None);
+        assert_eq!(authority.ipv6_addr(), Some(Ipv6Addr::LOCALHOST));
+    }
 }
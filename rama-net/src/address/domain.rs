@@ -330,7 +330,12 @@ const fn is_valid_label(name: &[u8], start: usize, stop: usize) -> bool {
         let mut i = start;
         while i < stop {
             let c = name[i];
-            if !c.is_ascii_alphanumeric() && (c != b'-' || i == start) {
+            // a leading underscore is allowed for underscored node names,
+            // such as the service and protocol labels of SRV records (RFC 8552)
+            if !c.is_ascii_alphanumeric()
+                && (c != b'-' || i == start)
+                && (c != b'_' || i != start || stop - start == 1)
+            {
                 return false;
             }
             i += 1;
@@ -404,6 +409,7 @@ mod tests {
             ".example.com.",
             "rr5---sn-q4fl6n6s.video.com", // multiple dashes
             "127.0.0.1",
+            "_sip._tcp.example.com", // underscored node names
         ] {
             let msg = format!("to parse: {}", str);
             assert_eq!(Domain::try_from(str.to_owned()).expect(msg.as_str()), str);
@@ -430,6 +436,9 @@ mod tests {
             "2001:db8:3333:4444:5555:6666:7777:8888",
             "-example.com",
             "local!host",
+            "_.example.com",
+            "s_ip.example.com",
+            "sip_.example.com",
             "thislabeliswaytoolongforbeingeversomethingwewishtocareabout-example.com",
             "example-thislabeliswaytoolongforbeingeversomethingwewishtocareabout.com",
             "こんにちは",
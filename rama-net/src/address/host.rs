@@ -42,6 +42,18 @@ impl Host {
     pub const EXAMPLE_NAME: Self = Self::Name(Domain::from_static("example.com"));
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The kind of a [`Host`], distinguishing
+/// between the IP versions of a [`Host::Address`].
+pub enum HostKind {
+    /// A domain, which needs to be resolved to connect to it.
+    Domain,
+    /// An IPv4 address.
+    Ipv4,
+    /// An IPv6 address.
+    Ipv6,
+}
+
 impl PartialEq<str> for Host {
     fn eq(&self, other: &str) -> bool {
         match self {
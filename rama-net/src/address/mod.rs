@@ -9,7 +9,7 @@
 
 mod host;
 #[doc(inline)]
-pub use host::Host;
+pub use host::{Host, HostKind};
 
 mod domain;
 #[doc(inline)]
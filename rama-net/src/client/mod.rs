@@ -3,3 +3,15 @@
 mod conn;
 #[doc(inline)]
 pub use conn::{ConnectorService, EstablishedClientConnection};
+
+mod timings;
+#[doc(inline)]
+pub use timings::{ConnectTimings, PhaseTiming};
+
+mod proxy_target;
+#[doc(inline)]
+pub use proxy_target::{
+    ProxyCredentials, ProxyTarget, ProxyTargetConnector, ProxyTargetConnectorLayer,
+};
+
+pub mod pool;
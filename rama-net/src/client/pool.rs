@@ -0,0 +1,1094 @@
+//! Pool of reusable client connections.
+//!
+//! A [`Pool`] keeps idle connections around per key (e.g. the target [`Authority`]),
+//! such that they can be checked out again later instead of establishing a new connection.
+//!
+//! Use [`Pool::close_on_shutdown`] to tie the pool to a graceful shutdown,
+//! which closes the idle connections and refuses new checkouts once the shutdown
+//! is triggered, while letting connections that are in use finish.
+//!
+//! Use [`Pool::warmup`] to establish connections to known targets ahead of time,
+//! in order to reduce the latency of the first requests to those targets.
+//!
+//! Use a [`PooledConnector`] to wrap a connector (e.g. a tcp connector),
+//! such that its connections are checked out from the pool when available.
+//!
+//! A [`PooledConnection`] is only returned to the pool when it is explicitly
+//! [released](PooledConnection::release), e.g. after a clean request-response exchange.
+//! Dropping it discards the connection, such that a connection left in an unknown
+//! state (e.g. after an error) is never handed out again. Idle connections are
+//! also discarded on checkout once they have been idle for longer than the
+//! [idle timeout](Pool::with_idle_timeout) or fail the [health check](Pool::with_health_check).
+//!
+//! [`Authority`]: crate::address::Authority
+
+use super::{ConnectorService, EstablishedClientConnection};
+use parking_lot::Mutex;
+use rama_core::{error::BoxError, graceful::ShutdownGuard, Service};
+use rama_utils::macros::{define_inner_service_accessors, error::static_str_error};
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    net::SocketAddr,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    sync::{Arc, Weak},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+const DEFAULT_MAX_IDLE_PER_KEY: usize = 8;
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+static_str_error! {
+    #[doc = "connection pool is shut down"]
+    pub struct PoolShutdownErr;
+}
+
+static_str_error! {
+    #[doc = "connection pool has reached the max idle connections for this key"]
+    pub struct PoolLimitReached;
+}
+
+#[derive(Debug)]
+/// The per-target result of a [`Pool::warmup`].
+pub struct WarmupResult<K> {
+    /// The key of the target.
+    pub key: K,
+    /// The address connected to, or the reason the connection
+    /// could not be established (or pooled).
+    pub result: Result<SocketAddr, BoxError>,
+}
+
+/// A pool of reusable client connections, grouped per key.
+pub struct Pool<C, K> {
+    state: Arc<Mutex<PoolState<C, K>>>,
+    max_idle_per_key: usize,
+    idle_timeout: Option<Duration>,
+    health_check: Option<HealthCheck<C>>,
+}
+
+type HealthCheck<C> = Arc<dyn Fn(&C) -> bool + Send + Sync + 'static>;
+
+struct PoolState<C, K> {
+    idle: HashMap<K, Vec<IdleConnection<C>>>,
+    closed: bool,
+}
+
+struct IdleConnection<C> {
+    conn: C,
+    addr: SocketAddr,
+    idle_since: Instant,
+}
+
+impl<C> IdleConnection<C> {
+    fn is_expired(&self, idle_timeout: Option<Duration>, now: Instant) -> bool {
+        idle_timeout.is_some_and(|timeout| now.duration_since(self.idle_since) >= timeout)
+    }
+}
+
+impl<C, K> fmt::Debug for Pool<C, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock();
+        f.debug_struct("Pool")
+            .field("idle", &state.idle.values().map(Vec::len).sum::<usize>())
+            .field("closed", &state.closed)
+            .field("max_idle_per_key", &self.max_idle_per_key)
+            .field("idle_timeout", &self.idle_timeout)
+            .field("health_check", &self.health_check.is_some())
+            .finish()
+    }
+}
+
+impl<C, K> Clone for Pool<C, K> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            max_idle_per_key: self.max_idle_per_key,
+            idle_timeout: self.idle_timeout,
+            health_check: self.health_check.clone(),
+        }
+    }
+}
+
+impl<C, K> Default for Pool<C, K> {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(PoolState {
+                idle: HashMap::new(),
+                closed: false,
+            })),
+            max_idle_per_key: DEFAULT_MAX_IDLE_PER_KEY,
+            idle_timeout: Some(DEFAULT_IDLE_TIMEOUT),
+            health_check: None,
+        }
+    }
+}
+
+impl<C, K> Pool<C, K> {
+    /// Create a new empty [`Pool`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum amount of idle connections kept per key.
+    ///
+    /// Connections released while this limit is reached are dropped instead.
+    pub fn with_max_idle_per_key(mut self, max: usize) -> Self {
+        self.max_idle_per_key = max;
+        self
+    }
+
+    /// Set the maximum amount of idle connections kept per key.
+    ///
+    /// Connections released while this limit is reached are dropped instead.
+    pub fn set_max_idle_per_key(&mut self, max: usize) -> &mut Self {
+        self.max_idle_per_key = max;
+        self
+    }
+
+    /// Set the maximum duration a connection is kept idle in the pool.
+    ///
+    /// Connections which have been idle for longer are discarded instead of checked out.
+    /// By default connections are kept idle for at most 90 seconds.
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum duration a connection is kept idle in the pool,
+    /// or keep idle connections around without a time limit in case `None` is given.
+    ///
+    /// Connections which have been idle for longer are discarded instead of checked out.
+    pub fn maybe_with_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Set the maximum duration a connection is kept idle in the pool.
+    ///
+    /// Connections which have been idle for longer are discarded instead of checked out.
+    /// By default connections are kept idle for at most 90 seconds.
+    pub fn set_idle_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum duration a connection is kept idle in the pool,
+    /// or keep idle connections around without a time limit in case `None` is given.
+    ///
+    /// Connections which have been idle for longer are discarded instead of checked out.
+    pub fn maybe_set_idle_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Set the health check used to verify an idle connection
+    /// is still usable when it is checked out.
+    ///
+    /// Idle connections for which the check returns `false` are discarded,
+    /// e.g. because the peer closed the connection while it was idle.
+    pub fn with_health_check<F>(mut self, check: F) -> Self
+    where
+        F: Fn(&C) -> bool + Send + Sync + 'static,
+    {
+        self.health_check = Some(Arc::new(check));
+        self
+    }
+
+    /// Set the health check used to verify an idle connection
+    /// is still usable when it is checked out.
+    ///
+    /// Idle connections for which the check returns `false` are discarded,
+    /// e.g. because the peer closed the connection while it was idle.
+    pub fn set_health_check<F>(&mut self, check: F) -> &mut Self
+    where
+        F: Fn(&C) -> bool + Send + Sync + 'static,
+    {
+        self.health_check = Some(Arc::new(check));
+        self
+    }
+
+    /// Close the pool, dropping all its idle connections.
+    ///
+    /// Checkouts fail from then on with a [`PoolShutdownErr`],
+    /// and connections still in use are dropped once released.
+    pub fn close(&self) {
+        close_state(&self.state);
+    }
+
+    /// Returns `true` if the pool is closed.
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().closed
+    }
+
+    /// Returns the total amount of idle connections in the pool.
+    pub fn idle_count(&self) -> usize {
+        self.state.lock().idle.values().map(Vec::len).sum()
+    }
+}
+
+impl<C, K> Pool<C, K>
+where
+    C: Send + 'static,
+    K: Send + 'static,
+{
+    /// Close the pool as soon as the shutdown of the given guard is triggered.
+    ///
+    /// The task is spawned as a graceful task of the given guard, which is released
+    /// as soon as the pool is closed. The task does not hold on to the pool,
+    /// so dropping the pool is not delayed by it.
+    pub fn close_on_shutdown(&self, guard: ShutdownGuard) {
+        let state = Arc::downgrade(&self.state);
+        guard.spawn_task_fn(|guard| async move {
+            guard.cancelled().await;
+            if let Some(state) = state.upgrade() {
+                tracing::trace!("connection pool: shutdown triggered");
+                close_state(&state);
+            }
+        });
+    }
+}
+
+fn close_state<C, K>(state: &Mutex<PoolState<C, K>>) {
+    let idle = {
+        let mut state = state.lock();
+        state.closed = true;
+        std::mem::take(&mut state.idle)
+    };
+    tracing::trace!(
+        idle = idle.values().map(Vec::len).sum::<usize>(),
+        "connection pool: closed"
+    );
+    // drop the idle connections outside of the lock
+    drop(idle);
+}
+
+impl<C, K> Pool<C, K>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Check out an idle connection for the given key, if any.
+    ///
+    /// The most recently released connection is checked out first.
+    /// Idle connections which exceeded the idle timeout or fail
+    /// the health check are discarded along the way.
+    ///
+    /// Returns a [`PoolShutdownErr`] in case the pool is closed.
+    pub fn checkout(&self, key: &K) -> Result<Option<PooledConnection<C, K>>, PoolShutdownErr> {
+        loop {
+            let idle = {
+                let mut state = self.state.lock();
+                if state.closed {
+                    return Err(PoolShutdownErr::new());
+                }
+                let Some(idle) = state.idle.get_mut(key) else {
+                    return Ok(None);
+                };
+                let conn = idle.pop();
+                if idle.is_empty() {
+                    state.idle.remove(key);
+                }
+                match conn {
+                    Some(idle) => idle,
+                    None => return Ok(None),
+                }
+            };
+            // expired or unhealthy connections are dropped outside of the lock
+            if idle.is_expired(self.idle_timeout, Instant::now()) {
+                tracing::trace!(addr = %idle.addr, "connection pool: discard expired idle connection");
+                continue;
+            }
+            if let Some(check) = &self.health_check {
+                if !check(&idle.conn) {
+                    tracing::trace!(addr = %idle.addr, "connection pool: discard unhealthy idle connection");
+                    continue;
+                }
+            }
+            return Ok(Some(self.pooled(key.clone(), idle.conn, idle.addr)));
+        }
+    }
+
+    /// Register a newly established connection to the given address
+    /// for the given key with this pool, such that it can be returned
+    /// to the pool as an idle connection once [released](PooledConnection::release).
+    ///
+    /// Returns a [`PoolShutdownErr`] in case the pool is closed.
+    pub fn register(
+        &self,
+        key: K,
+        conn: C,
+        addr: SocketAddr,
+    ) -> Result<PooledConnection<C, K>, PoolShutdownErr> {
+        if self.is_closed() {
+            return Err(PoolShutdownErr::new());
+        }
+        Ok(self.pooled(key, conn, addr))
+    }
+
+    /// Returns the amount of idle connections in the pool for the given key.
+    pub fn idle_count_for(&self, key: &K) -> usize {
+        self.state
+            .lock()
+            .idle
+            .get(key)
+            .map(Vec::len)
+            .unwrap_or_default()
+    }
+
+    /// Eagerly establish connections to the given targets using the given connector,
+    /// and add them to the pool as idle connections.
+    ///
+    /// Each target is a key together with the request used to connect to it,
+    /// a target can be repeated to establish multiple connections for the same key.
+    /// All connections are established concurrently, each using a clone of the given
+    /// [`Context`] and spawned using its [`Executor`], such that the first requests
+    /// to those targets can reuse them.
+    /// Use the full connector stack (e.g. including tls) for the connections
+    /// to be warmed up as far as possible, e.g. including the tls handshake and alpn negotiation.
+    ///
+    /// Targets for which the pool already has (or would get)
+    /// the max idle connections per key are skipped with a [`PoolLimitReached`] error.
+    /// The same error is returned for an established connection which could not be pooled,
+    /// because the pool reached the max idle connections for its key in the meantime,
+    /// in which case the connection is dropped.
+    ///
+    /// Returns the result of each target, in the order of the given targets.
+    ///
+    /// [`Context`]: rama_core::Context
+    /// [`Executor`]: rama_core::rt::Executor
+    pub async fn warmup<S, State, Request, I>(
+        &self,
+        connector: S,
+        ctx: rama_core::Context<State>,
+        targets: I,
+    ) -> Vec<WarmupResult<K>>
+    where
+        S: ConnectorService<State, Request, Connection = C> + Clone,
+        C: Send + 'static,
+        K: Send + 'static,
+        State: Clone + Send + Sync + 'static,
+        Request: Send + 'static,
+        I: IntoIterator<Item = (K, Request)>,
+    {
+        let mut planned: HashMap<K, usize> = HashMap::new();
+        let handles: Vec<_> = targets
+            .into_iter()
+            .map(|(key, req)| {
+                let count = planned.entry(key.clone()).or_default();
+                *count += 1;
+                if self.idle_count_for(&key) + *count > self.max_idle_per_key {
+                    return (key, None);
+                }
+
+                let connector = connector.clone();
+                let conn_ctx = ctx.clone();
+                let handle = ctx.spawn(async move {
+                    connector
+                        .connect(conn_ctx, req)
+                        .await
+                        .map(|established| (established.conn, established.addr))
+                        .map_err(Into::into)
+                });
+                (key, Some(handle))
+            })
+            .collect();
+
+        let mut results = Vec::with_capacity(handles.len());
+        for (key, handle) in handles {
+            let result = match handle {
+                None => Err(PoolLimitReached::new().into()),
+                Some(handle) => match handle.await {
+                    Ok(Ok((conn, addr))) => self
+                        .register(key.clone(), conn, addr)
+                        .map_err(Into::into)
+                        .and_then(PooledConnection::try_release)
+                        .map(|_| addr),
+                    Ok(Err(err)) => Err(err),
+                    Err(err) => Err(err.into()),
+                },
+            };
+            match &result {
+                Ok(addr) => {
+                    tracing::trace!(%addr, "connection pool: warmup connection established")
+                }
+                Err(err) => tracing::debug!(error = %err, "connection pool: warmup failed"),
+            }
+            results.push(WarmupResult { key, result });
+        }
+        results
+    }
+
+    fn pooled(&self, key: K, conn: C, addr: SocketAddr) -> PooledConnection<C, K> {
+        PooledConnection {
+            conn,
+            key,
+            addr,
+            pool: Arc::downgrade(&self.state),
+            max_idle_per_key: self.max_idle_per_key,
+            idle_timeout: self.idle_timeout,
+        }
+    }
+}
+
+/// A connector which checks out idle connections from a [`Pool`],
+/// and only establishes a new connection using the inner connector
+/// in case no idle connection is available for the key of the request.
+///
+/// The key of a request is computed using the given key function,
+/// e.g. the target [`Authority`] of the request.
+/// Connections are returned to the pool once [released](PooledConnection::release),
+/// and discarded when dropped.
+///
+/// [`Authority`]: crate::address::Authority
+pub struct PooledConnector<S, C, K, F> {
+    inner: S,
+    pool: Pool<C, K>,
+    key_fn: F,
+}
+
+impl<S, C, K, F> PooledConnector<S, C, K, F> {
+    /// Create a new [`PooledConnector`], checking out connections from the given pool
+    /// for the key computed by the given key function, using the inner connector
+    /// to establish new connections.
+    pub const fn new(inner: S, pool: Pool<C, K>, key_fn: F) -> Self {
+        Self {
+            inner,
+            pool,
+            key_fn,
+        }
+    }
+
+    /// Returns a reference to the [`Pool`] used by this connector.
+    pub fn pool(&self) -> &Pool<C, K> {
+        &self.pool
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S: fmt::Debug, C, K, F> fmt::Debug for PooledConnector<S, C, K, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledConnector")
+            .field("inner", &self.inner)
+            .field("pool", &self.pool)
+            .field("key_fn", &std::any::type_name::<F>())
+            .finish()
+    }
+}
+
+impl<S: Clone, C, K, F: Clone> Clone for PooledConnector<S, C, K, F> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            pool: self.pool.clone(),
+            key_fn: self.key_fn.clone(),
+        }
+    }
+}
+
+impl<S, State, Request, C, K, F> Service<State, Request> for PooledConnector<S, C, K, F>
+where
+    S: ConnectorService<State, Request, Connection = C>,
+    State: Clone + Send + Sync + 'static,
+    Request: Send + 'static,
+    C: Send + 'static,
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    F: Fn(&rama_core::Context<State>, &Request) -> K + Send + Sync + 'static,
+{
+    type Response = EstablishedClientConnection<PooledConnection<C, K>, State, Request>;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        ctx: rama_core::Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        let key = (self.key_fn)(&ctx, &req);
+        if let Some(conn) = self.pool.checkout(&key)? {
+            tracing::trace!(addr = %conn.addr(), "connection pool: reuse idle connection");
+            return Ok(EstablishedClientConnection {
+                ctx,
+                req,
+                addr: conn.addr(),
+                conn,
+            });
+        }
+
+        let EstablishedClientConnection {
+            ctx,
+            req,
+            conn,
+            addr,
+        } = self.inner.connect(ctx, req).await.map_err(Into::into)?;
+        let conn = self.pool.register(key, conn, addr)?;
+        Ok(EstablishedClientConnection {
+            ctx,
+            req,
+            conn,
+            addr,
+        })
+    }
+}
+
+/// A connection checked out from (or registered with) a [`Pool`].
+///
+/// The connection is only returned to the pool as an idle connection
+/// when it is [released](Self::release), which should only be done once the
+/// connection is known to be in a clean state, e.g. after a complete exchange.
+/// Dropping it discards the connection.
+pub struct PooledConnection<C, K: Eq + Hash> {
+    conn: C,
+    key: K,
+    addr: SocketAddr,
+    pool: Weak<Mutex<PoolState<C, K>>>,
+    max_idle_per_key: usize,
+    idle_timeout: Option<Duration>,
+}
+
+impl<C: fmt::Debug, K: fmt::Debug + Eq + Hash> fmt::Debug for PooledConnection<C, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledConnection")
+            .field("conn", &self.conn)
+            .field("key", &self.key)
+            .field("addr", &self.addr)
+            .finish()
+    }
+}
+
+impl<C, K: Eq + Hash> PooledConnection<C, K> {
+    /// Returns the key of this connection.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the address this connection is connected to.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Return the connection to the pool as an idle connection,
+    /// such that it can be checked out again.
+    ///
+    /// The connection is dropped instead in case the pool is closed (or gone),
+    /// or the pool already has the maximum amount of idle connections for its key.
+    /// Only release a connection once it is in a clean state, e.g. after a
+    /// complete request-response exchange, as it is handed out as-is again.
+    pub fn release(self) {
+        let _ = self.try_release();
+    }
+
+    /// Same as [`Self::release`], but returning a [`PoolShutdownErr`] or [`PoolLimitReached`]
+    /// error in case the connection was dropped instead of returned to the pool.
+    fn try_release(self) -> Result<(), BoxError> {
+        let Some(pool) = self.pool.upgrade() else {
+            return Err(PoolShutdownErr::new().into());
+        };
+        let now = Instant::now();
+        let (result, expired, conn) = {
+            let mut state = pool.lock();
+            if state.closed {
+                return Err(PoolShutdownErr::new().into());
+            }
+            let mut idle = state.idle.remove(&self.key).unwrap_or_default();
+            // idle connections are kept in release order,
+            // so the expired ones are always at the front
+            let expired = idle
+                .iter()
+                .take_while(|idle| idle.is_expired(self.idle_timeout, now))
+                .count();
+            let expired: Vec<_> = idle.drain(..expired).collect();
+            let (result, conn) = if idle.len() < self.max_idle_per_key {
+                idle.push(IdleConnection {
+                    conn: self.conn,
+                    addr: self.addr,
+                    idle_since: now,
+                });
+                (Ok(()), None)
+            } else {
+                (Err(PoolLimitReached::new().into()), Some(self.conn))
+            };
+            if !idle.is_empty() {
+                state.idle.insert(self.key, idle);
+            }
+            (result, expired, conn)
+        };
+        // drop the expired (or rejected) connections outside of the lock
+        drop(expired);
+        drop(conn);
+        result
+    }
+
+    /// Drop the connection, without returning it to the pool,
+    /// e.g. because it is no longer usable.
+    ///
+    /// This is the same as dropping the [`PooledConnection`].
+    pub fn discard(self) {
+        drop(self);
+    }
+
+    /// Take the connection out of the pool's management,
+    /// meaning it will not be returned to the pool.
+    pub fn into_inner(self) -> C {
+        self.conn
+    }
+}
+
+impl<C, K: Eq + Hash> Deref for PooledConnection<C, K> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+impl<C, K: Eq + Hash> DerefMut for PooledConnection<C, K> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.conn
+    }
+}
+
+// the inner connection is never pinned in place, as it is moved out when released
+impl<C, K: Eq + Hash> Unpin for PooledConnection<C, K> {}
+
+impl<C, K> AsyncRead for PooledConnection<C, K>
+where
+    C: AsyncRead + Unpin,
+    K: Eq + Hash,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut **self).poll_read(cx, buf)
+    }
+}
+
+impl<C, K> AsyncWrite for PooledConnection<C, K>
+where
+    C: AsyncWrite + Unpin,
+    K: Eq + Hash,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+        Pin::new(&mut **self).poll_write(cx, buf)
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), std::io::Error>> {
+        Pin::new(&mut **self).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), std::io::Error>> {
+        Pin::new(&mut **self).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::EstablishedClientConnection;
+    use rama_core::{graceful::Shutdown, service::service_fn};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug)]
+    struct TestConn {
+        id: usize,
+        dropped: Arc<AtomicUsize>,
+    }
+
+    impl Drop for TestConn {
+        fn drop(&mut self) {
+            self.dropped.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    const ADDR: SocketAddr =
+        SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), 443);
+
+    fn conn(id: usize, dropped: &Arc<AtomicUsize>) -> TestConn {
+        TestConn {
+            id,
+            dropped: dropped.clone(),
+        }
+    }
+
+    #[test]
+    fn test_pool_reuse_idle_connection() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let pool = Pool::new().with_max_idle_per_key(1);
+
+        assert!(pool.checkout(&"a").unwrap().is_none());
+
+        let first = pool.register("a", conn(1, &dropped), ADDR).unwrap();
+        let second = pool.register("a", conn(2, &dropped), ADDR).unwrap();
+        first.release();
+        assert_eq!(pool.idle_count_for(&"a"), 1);
+        // max idle per key reached
+        second.release();
+        assert_eq!(pool.idle_count_for(&"a"), 1);
+        assert_eq!(dropped.load(Ordering::SeqCst), 1);
+
+        assert!(pool.checkout(&"b").unwrap().is_none());
+        let reused = pool.checkout(&"a").unwrap().unwrap();
+        assert_eq!(reused.id, 1);
+        assert_eq!(*reused.key(), "a");
+        assert_eq!(pool.idle_count(), 0);
+
+        reused.discard();
+        assert_eq!(pool.idle_count(), 0);
+        assert_eq!(dropped.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pool_close_on_shutdown() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let pool = Pool::new();
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let shutdown = Shutdown::new(async move {
+            let _ = rx.await;
+        });
+        pool.close_on_shutdown(shutdown.guard());
+
+        pool.register("a", conn(1, &dropped), ADDR)
+            .unwrap()
+            .release();
+        pool.register("b", conn(2, &dropped), ADDR)
+            .unwrap()
+            .release();
+        let in_use = pool.register("a", conn(3, &dropped), ADDR).unwrap();
+        assert_eq!(pool.idle_count(), 2);
+
+        tx.send(()).unwrap();
+        shutdown.shutdown().await;
+        tokio::time::timeout(std::time::Duration::from_secs(1), async {
+            while !pool.is_closed() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // idle connections are closed
+        assert_eq!(pool.idle_count(), 0);
+        assert_eq!(dropped.load(Ordering::SeqCst), 2);
+
+        // new checkouts fail
+        assert!(pool.checkout(&"a").is_err());
+        assert!(pool.register("c", conn(4, &dropped), ADDR).is_err());
+
+        // in use connections can finish, but are not returned to the pool
+        assert_eq!(in_use.id, 3);
+        in_use.release();
+        assert_eq!(pool.idle_count(), 0);
+        assert_eq!(dropped.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_pool_warmup_reuse_connection() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let connects = Arc::new(AtomicUsize::new(0));
+        let connector = {
+            let dropped = dropped.clone();
+            let connects = connects.clone();
+            Arc::new(service_fn(
+                move |ctx: rama_core::Context<()>, host: &'static str| {
+                    let id = connects.fetch_add(1, Ordering::SeqCst) + 1;
+                    let conn = conn(id, &dropped);
+                    async move {
+                        if host == "down" {
+                            return Err(BoxError::from("connection refused"));
+                        }
+                        Ok(EstablishedClientConnection {
+                            ctx,
+                            req: host,
+                            conn,
+                            addr: ADDR,
+                        })
+                    }
+                },
+            ))
+        };
+
+        let pool = Pool::new().with_max_idle_per_key(2);
+        let results = pool
+            .warmup(
+                connector.clone(),
+                rama_core::Context::default(),
+                [("a", "a"), ("down", "down"), ("a", "a"), ("a", "a")],
+            )
+            .await;
+
+        let results: Vec<_> = results
+            .into_iter()
+            .map(|WarmupResult { key, result }| (key, result))
+            .collect();
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].0, "a");
+        assert!(results[0].1.is_ok());
+        assert_eq!(results[1].0, "down");
+        assert!(results[1].1.is_err());
+        assert!(results[2].1.is_ok());
+        // max idle per key reached, so no connection is established
+        assert!(results[3]
+            .1
+            .as_ref()
+            .unwrap_err()
+            .downcast_ref::<PoolLimitReached>()
+            .is_some());
+        assert_eq!(connects.load(Ordering::SeqCst), 3);
+        assert_eq!(pool.idle_count_for(&"a"), 2);
+        assert_eq!(pool.idle_count_for(&"down"), 0);
+
+        // a subsequent request reuses a pre-warmed connection, without connecting
+        let reused = pool.checkout(&"a").unwrap().unwrap();
+        assert!(reused.id == 1 || reused.id == 3);
+        assert_eq!(connects.load(Ordering::SeqCst), 3);
+        reused.release();
+        assert_eq!(pool.idle_count_for(&"a"), 2);
+
+        // the pool is already warm
+        let results = pool
+            .warmup(connector, rama_core::Context::default(), [("a", "a")])
+            .await;
+        assert!(results[0].result.is_err());
+        assert_eq!(connects.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_pooled_connector_reuse_connection() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let connects = Arc::new(AtomicUsize::new(0));
+        let connector = {
+            let dropped = dropped.clone();
+            let connects = connects.clone();
+            service_fn(move |ctx: rama_core::Context<()>, host: &'static str| {
+                let id = connects.fetch_add(1, Ordering::SeqCst) + 1;
+                let conn = conn(id, &dropped);
+                async move {
+                    Ok::<_, BoxError>(EstablishedClientConnection {
+                        ctx,
+                        req: host,
+                        conn,
+                        addr: ADDR,
+                    })
+                }
+            })
+        };
+
+        fn key(_ctx: &rama_core::Context<()>, host: &&'static str) -> &'static str {
+            host
+        }
+        let connector = PooledConnector::new(connector, Pool::new(), key);
+
+        let first = connector
+            .serve(rama_core::Context::default(), "a")
+            .await
+            .unwrap();
+        assert_eq!(first.conn.id, 1);
+        assert_eq!(first.addr, ADDR);
+        first.conn.release();
+        assert_eq!(connector.pool().idle_count_for(&"a"), 1);
+
+        // the idle connection is reused
+        let reused = connector
+            .serve(rama_core::Context::default(), "a")
+            .await
+            .unwrap();
+        assert_eq!(reused.conn.id, 1);
+        assert_eq!(reused.addr, ADDR);
+
+        // no idle connection for this key (anymore)
+        let other = connector
+            .serve(rama_core::Context::default(), "b")
+            .await
+            .unwrap();
+        assert_eq!(other.conn.id, 2);
+        let second = connector
+            .serve(rama_core::Context::default(), "a")
+            .await
+            .unwrap();
+        assert_eq!(second.conn.id, 3);
+        assert_eq!(connects.load(Ordering::SeqCst), 3);
+
+        connector.pool().close();
+        assert!(connector
+            .serve(rama_core::Context::default(), "a")
+            .await
+            .is_err());
+        assert_eq!(connects.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_pooled_connector_drop_errored_connection() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let connects = Arc::new(AtomicUsize::new(0));
+        let connector = {
+            let dropped = dropped.clone();
+            let connects = connects.clone();
+            service_fn(move |ctx: rama_core::Context<()>, host: &'static str| {
+                let id = connects.fetch_add(1, Ordering::SeqCst) + 1;
+                let conn = conn(id, &dropped);
+                async move {
+                    Ok::<_, BoxError>(EstablishedClientConnection {
+                        ctx,
+                        req: host,
+                        conn,
+                        addr: ADDR,
+                    })
+                }
+            })
+        };
+
+        fn key(_ctx: &rama_core::Context<()>, host: &&'static str) -> &'static str {
+            host
+        }
+        let connector = PooledConnector::new(connector, Pool::new(), key);
+
+        // the exchange failed, so the connection is dropped without being released
+        let errored = connector
+            .serve(rama_core::Context::default(), "a")
+            .await
+            .unwrap();
+        assert_eq!(errored.conn.id, 1);
+        drop(errored);
+        assert_eq!(connector.pool().idle_count_for(&"a"), 0);
+        assert_eq!(dropped.load(Ordering::SeqCst), 1);
+
+        // a new connection is established instead of reusing the errored one
+        let next = connector
+            .serve(rama_core::Context::default(), "a")
+            .await
+            .unwrap();
+        assert_eq!(next.conn.id, 2);
+        assert_eq!(connects.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_pool_discard_expired_idle_connection() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let pool = Pool::new().with_idle_timeout(Duration::from_millis(20));
+
+        pool.register("a", conn(1, &dropped), ADDR)
+            .unwrap()
+            .release();
+        std::thread::sleep(Duration::from_millis(50));
+        pool.register("a", conn(2, &dropped), ADDR)
+            .unwrap()
+            .release();
+        // the expired connection is dropped when releasing a new one
+        assert_eq!(pool.idle_count_for(&"a"), 1);
+        assert_eq!(dropped.load(Ordering::SeqCst), 1);
+
+        std::thread::sleep(Duration::from_millis(50));
+        // the expired connection is discarded instead of checked out
+        assert!(pool.checkout(&"a").unwrap().is_none());
+        assert_eq!(pool.idle_count_for(&"a"), 0);
+        assert_eq!(dropped.load(Ordering::SeqCst), 2);
+
+        // without idle timeout connections are kept around
+        let pool = Pool::new().maybe_with_idle_timeout(None);
+        pool.register("a", conn(3, &dropped), ADDR)
+            .unwrap()
+            .release();
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(pool.checkout(&"a").unwrap().unwrap().id, 3);
+    }
+
+    #[test]
+    fn test_pool_discard_unhealthy_idle_connection() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let pool = Pool::new().with_health_check(|conn: &TestConn| conn.id != 2);
+
+        pool.register("a", conn(1, &dropped), ADDR)
+            .unwrap()
+            .release();
+        pool.register("a", conn(2, &dropped), ADDR)
+            .unwrap()
+            .release();
+        assert_eq!(pool.idle_count_for(&"a"), 2);
+
+        // the unhealthy connection is discarded, the healthy one checked out
+        let conn = pool.checkout(&"a").unwrap().unwrap();
+        assert_eq!(conn.id, 1);
+        assert_eq!(dropped.load(Ordering::SeqCst), 1);
+        assert_eq!(pool.idle_count_for(&"a"), 0);
+    }
+
+    #[test]
+    fn test_pool_remove_empty_idle_entries() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let pool = Pool::new().with_idle_timeout(Duration::from_millis(20));
+
+        pool.register("a", conn(1, &dropped), ADDR)
+            .unwrap()
+            .release();
+        let checked_out = pool.checkout(&"a").unwrap().unwrap();
+        assert!(pool.state.lock().idle.is_empty());
+
+        checked_out.release();
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(pool.checkout(&"a").unwrap().is_none());
+        assert!(pool.state.lock().idle.is_empty());
+
+        let mut pool = pool;
+        pool.set_max_idle_per_key(0).maybe_set_idle_timeout(None);
+        pool.register("a", conn(2, &dropped), ADDR)
+            .unwrap()
+            .release();
+        assert!(pool.state.lock().idle.is_empty());
+        assert_eq!(dropped.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pool_warmup_limit_reached_while_connecting() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let pool = Pool::new().with_max_idle_per_key(1);
+        let connector = {
+            let dropped = dropped.clone();
+            let pool = pool.clone();
+            Arc::new(service_fn(
+                move |ctx: rama_core::Context<()>, host: &'static str| {
+                    // another connection for the same key is released in the meantime
+                    pool.register(host, conn(1, &dropped), ADDR)
+                        .unwrap()
+                        .release();
+                    let conn = conn(2, &dropped);
+                    async move {
+                        Ok::<_, BoxError>(EstablishedClientConnection {
+                            ctx,
+                            req: host,
+                            conn,
+                            addr: ADDR,
+                        })
+                    }
+                },
+            ))
+        };
+
+        let results = pool
+            .warmup(connector, rama_core::Context::default(), [("a", "a")])
+            .await;
+        assert!(results[0]
+            .result
+            .as_ref()
+            .unwrap_err()
+            .downcast_ref::<PoolLimitReached>()
+            .is_some());
+        assert_eq!(pool.idle_count_for(&"a"), 1);
+        assert_eq!(dropped.load(Ordering::SeqCst), 1);
+        assert_eq!(pool.checkout(&"a").unwrap().unwrap().id, 1);
+    }
+}
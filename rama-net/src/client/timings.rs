@@ -0,0 +1,48 @@
+use std::time::{Duration, Instant};
+
+/// Timings of the phases involved in establishing a client connection.
+///
+/// Inserted as an extension into the [`Context`] of the
+/// [`EstablishedClientConnection`] by the connectors of the stack,
+/// each recording the phase it is responsible for once that phase completes.
+/// Phases which were not part of establishing the connection
+/// (e.g. dns resolution when connecting to an IP address directly) are `None`.
+///
+/// [`Context`]: rama_core::Context
+/// [`EstablishedClientConnection`]: super::EstablishedClientConnection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectTimings {
+    /// Resolution of the domain to connect to.
+    pub dns: Option<PhaseTiming>,
+    /// Establishment of the tcp connection.
+    pub tcp_connect: Option<PhaseTiming>,
+    /// Tls handshake on top of the established connection.
+    ///
+    /// Only recorded by the rustls tls connector for now.
+    pub tls: Option<PhaseTiming>,
+}
+
+/// Start and end of a single phase recorded in [`ConnectTimings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhaseTiming {
+    /// Moment the phase started.
+    pub start: Instant,
+    /// Moment the phase completed.
+    pub end: Instant,
+}
+
+impl PhaseTiming {
+    /// Create a new [`PhaseTiming`] for a phase which started at
+    /// the given moment and completed just now.
+    pub fn until_now(start: Instant) -> Self {
+        Self {
+            start,
+            end: Instant::now(),
+        }
+    }
+
+    /// Returns the duration of the phase.
+    pub fn duration(&self) -> Duration {
+        self.end.saturating_duration_since(self.start)
+    }
+}
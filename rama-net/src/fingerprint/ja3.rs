@@ -1,15 +1,17 @@
 use rama_core::context::Extensions;
-use std::{fmt, io};
+use std::{fmt, io, str::FromStr};
 
 use crate::tls::{
-    CipherSuite, ECPointFormat, ExtensionId, ProtocolVersion, SecureTransport, SupportedGroup,
+    client::ClientHello, CipherSuite, ECPointFormat, ExtensionId, ProtocolVersion, SecureTransport,
+    SupportedGroup,
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 /// Data which can be hashed using [`Self::hash`],
 /// and which is also displayed as a "ja3" hash.
 ///
-/// Computed using [`Ja3::compute`].
+/// Computed using [`Ja3::compute`],
+/// or parsed from its string form (e.g. `771,4865-4866,0-23,29-23,0`) using [`Ja3::parse`].
 pub struct Ja3 {
     version: ProtocolVersion,
     cipher_suites: Vec<CipherSuite>,
@@ -27,7 +29,14 @@ impl Ja3 {
             .get::<SecureTransport>()
             .and_then(|st| st.client_hello())
             .ok_or(Ja3ComputeError::MissingClientHello)?;
+        Self::compute_from_client_hello(client_hello)
+    }
 
+    /// Compute the [`Ja3`] (hash) from a parsed [`ClientHello`].
+    ///
+    /// Useful in case the TLS session is not terminated (e.g. in a passthrough proxy),
+    /// and the [`ClientHello`] was instead parsed from the peeked stream data.
+    pub fn compute_from_client_hello(client_hello: &ClientHello) -> Result<Self, Ja3ComputeError> {
         let version = client_hello.protocol_version();
 
         let cipher_suites: Vec<_> = client_hello
@@ -79,6 +88,48 @@ impl Ja3 {
         })
     }
 
+    /// Parse a [`Ja3`] from its (unhashed) string form,
+    /// as produced by the [`Display`] implementation of [`Ja3`].
+    ///
+    /// [`Display`]: std::fmt::Display
+    pub fn parse(s: &str) -> Result<Self, Ja3ParseError> {
+        let mut parts = s.trim().split(',');
+        let (
+            Some(version),
+            Some(cipher_suites),
+            Some(extensions),
+            Some(supported_groups),
+            Some(ec_point_formats),
+            None,
+        ) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        )
+        else {
+            return Err(Ja3ParseError("expected 5 comma separated fields"));
+        };
+
+        let version = version
+            .parse::<u16>()
+            .map_err(|_| Ja3ParseError("invalid tls version"))?
+            .into();
+
+        let cipher_suites = parse_list::<u16, CipherSuite>(cipher_suites, "invalid cipher suite")?
+            .ok_or(Ja3ParseError("empty cipher suites"))?;
+
+        Ok(Self {
+            version,
+            cipher_suites,
+            extensions: parse_list::<u16, _>(extensions, "invalid extension")?,
+            supported_groups: parse_list::<u16, _>(supported_groups, "invalid supported group")?,
+            ec_point_formats: parse_list::<u8, _>(ec_point_formats, "invalid ec point format")?,
+        })
+    }
+
     #[inline]
     /// compute the "ja3" hash from this [`Ja3`] data structure as a String.
     pub fn hash(&self) -> String {
@@ -160,6 +211,32 @@ impl Ja3 {
     }
 }
 
+fn parse_list<N: FromStr, T: From<N>>(
+    s: &str,
+    err: &'static str,
+) -> Result<Option<Vec<T>>, Ja3ParseError> {
+    if s.is_empty() {
+        return Ok(None);
+    }
+    s.split('-')
+        .map(|v| {
+            v.parse::<N>()
+                .map(Into::into)
+                .map_err(|_| Ja3ParseError(err))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+impl FromStr for Ja3 {
+    type Err = Ja3ParseError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
 impl fmt::Display for Ja3 {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -207,6 +284,18 @@ impl fmt::Display for Ja3ComputeError {
 
 impl std::error::Error for Ja3ComputeError {}
 
+#[derive(Debug, Clone)]
+/// error identifying a failure in [`Ja3::parse`]
+pub struct Ja3ParseError(&'static str);
+
+impl fmt::Display for Ja3ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ja3 Parse Error: {}", self.0)
+    }
+}
+
+impl std::error::Error for Ja3ParseError {}
+
 #[cfg(test)]
 mod tests {
     use crate::tls::client::parse_client_hello;
@@ -236,10 +325,19 @@ mod tests {
             expected_ja3_hash: "456523fc94726331a4d5a2e1d40b2cd7",
         }];
         for test_case in test_cases {
+            let client_hello = parse_client_hello(&test_case.client_hello).expect(test_case.pcap);
+
+            // passthrough: computed straight from the parsed client hello
+            let ja3 = Ja3::compute_from_client_hello(&client_hello).expect(test_case.pcap);
+            assert_eq!(
+                test_case.expected_ja3_hash,
+                format!("{ja3:x}"),
+                "pcap: {}",
+                test_case.pcap,
+            );
+
             let mut ext = Extensions::new();
-            ext.insert(SecureTransport::with_client_hello(
-                parse_client_hello(&test_case.client_hello).expect(test_case.pcap),
-            ));
+            ext.insert(SecureTransport::with_client_hello(client_hello));
 
             let ja3 = Ja3::compute(&ext).expect(test_case.pcap);
 
@@ -256,6 +354,37 @@ mod tests {
                 "pcap: {}",
                 test_case.pcap,
             );
+
+            let parsed: Ja3 = test_case.expected_ja3_str.parse().expect(test_case.pcap);
+            assert_eq!(ja3, parsed, "pcap: {}", test_case.pcap);
+            assert_eq!(
+                test_case.expected_ja3_hash,
+                parsed.hash(),
+                "pcap: {}",
+                test_case.pcap,
+            );
+        }
+    }
+
+    #[test]
+    fn test_ja3_parse_optional_fields() {
+        let ja3 = Ja3::parse("769,47-53,,,").unwrap();
+        assert_eq!(ja3.to_string(), "769,47-53,,,");
+    }
+
+    #[test]
+    fn test_ja3_parse_malformed() {
+        for s in [
+            "",
+            "771",
+            "771,4865,0,29,0,1",
+            "foo,4865,0,29,0",
+            "771,,0,29,0",
+            "771,4865-,0,29,0",
+            "771,4865,0,29,256",
+            "771,4865,0-x,29,0",
+        ] {
+            assert!(Ja3::parse(s).is_err(), "input: {s:?}");
         }
     }
 }
@@ -16,6 +16,7 @@ use itertools::Itertools as _;
 use std::{
     borrow::Cow,
     fmt::{self, Write},
+    str::FromStr,
 };
 
 use rama_http_types::{
@@ -24,11 +25,12 @@ use rama_http_types::{
     Method, Request, Version,
 };
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 /// Input data for a "ja4h" hash.
 /// or displaying it.
 ///
-/// Computed using [`Ja4H::compute`].
+/// Computed using [`Ja4H::compute`],
+/// or parsed from its raw (human) string form using [`Ja4H::parse`].
 pub struct Ja4H {
     req_method: HttpRequestMethod,
     version: HttpVersion,
@@ -111,6 +113,82 @@ impl Ja4H {
         format!("{self:?}")
     }
 
+    /// Parse a [`Ja4H`] from its raw (human) string form,
+    /// as produced by [`Ja4H::to_human_string`].
+    ///
+    /// The hashed form (e.g. `ge11cr09enus_df50b14dec48_d733b88e2d70_774e52af4cfe`)
+    /// cannot be parsed, as the hashed chunks cannot be reversed.
+    ///
+    /// Header names containing an underscore are not supported,
+    /// as the underscore is used as the chunk separator.
+    pub fn parse(s: &str) -> Result<Self, Ja4HParseError> {
+        let s = s.trim();
+        let (Some((ja4h_a, rest)), true) = (s.split_once('_'), s.is_ascii()) else {
+            return Err(Ja4HParseError("expected underscore separated ascii chunks"));
+        };
+        let Some((headers, cookies)) = rest.split_once('_') else {
+            return Err(Ja4HParseError("missing cookie chunks"));
+        };
+
+        if ja4h_a.len() != 12 {
+            return Err(Ja4HParseError("invalid first chunk"));
+        }
+
+        let req_method = HttpRequestMethod::parse(&ja4h_a[0..2])?;
+        let version = match &ja4h_a[2..4] {
+            "10" => HttpVersion::Http1_0,
+            "11" => HttpVersion::Http1_1,
+            "20" => HttpVersion::Http2,
+            "30" => HttpVersion::Http3,
+            _ => return Err(Ja4HParseError("invalid http version")),
+        };
+        let has_cookie_header = match &ja4h_a[4..5] {
+            "c" => true,
+            "n" => false,
+            _ => return Err(Ja4HParseError("invalid cookie marker")),
+        };
+        let has_referer_header = match &ja4h_a[5..6] {
+            "r" => true,
+            "n" => false,
+            _ => return Err(Ja4HParseError("invalid referer marker")),
+        };
+        let nr_headers: usize = ja4h_a[6..8]
+            .parse()
+            .map_err(|_| Ja4HParseError("invalid number of headers"))?;
+        let language = match &ja4h_a[8..12] {
+            "0000" => None,
+            language => Some(language.trim_end_matches('0').to_owned()),
+        };
+
+        if headers.is_empty() {
+            return Err(Ja4HParseError("missing headers"));
+        }
+        let headers: Vec<String> = headers.split(',').map(ToOwned::to_owned).collect();
+        if headers.iter().any(String::is_empty) {
+            return Err(Ja4HParseError("empty header name"));
+        }
+        if nr_headers != 99.min(headers.len()) {
+            return Err(Ja4HParseError("number of headers mismatch"));
+        }
+
+        // cookie names can contain underscores, so we try every split
+        // until the cookie pairs match the cookie names
+        let cookie_pairs = cookies
+            .match_indices('_')
+            .find_map(|(index, _)| parse_cookie_pairs(&cookies[..index], &cookies[index + 1..]))
+            .ok_or(Ja4HParseError("invalid cookie chunks"))?;
+
+        Ok(Self {
+            req_method,
+            version,
+            has_cookie_header,
+            has_referer_header,
+            language,
+            headers,
+            cookie_pairs,
+        })
+    }
+
     fn fmt_as(&self, f: &mut fmt::Formatter<'_>, hash_chunks: bool) -> fmt::Result {
         let req_method = &self.req_method;
         let version = self.version;
@@ -169,6 +247,66 @@ impl fmt::Debug for Ja4H {
     }
 }
 
+impl FromStr for Ja4H {
+    type Err = Ja4HParseError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Parse the cookie pairs (`name[=value],...`) using the cookie names (`name,...`),
+/// as values can contain commas as well.
+///
+/// Returns `None` if the pairs do not match the names.
+#[allow(clippy::type_complexity)]
+fn parse_cookie_pairs(names: &str, pairs: &str) -> Option<Option<Vec<(String, Option<String>)>>> {
+    if names.is_empty() {
+        return pairs.is_empty().then_some(None);
+    }
+
+    let names: Vec<_> = names.split(',').collect();
+    let mut result = Vec::with_capacity(names.len());
+    let mut rest = pairs;
+    for (index, name) in names.iter().enumerate() {
+        if name.is_empty() {
+            return None;
+        }
+        rest = rest.strip_prefix(name)?;
+        match names.get(index + 1) {
+            None => {
+                let value = match rest {
+                    "" => None,
+                    rest => Some(rest.strip_prefix('=')?.to_owned()),
+                };
+                result.push(((*name).to_owned(), value));
+                rest = "";
+            }
+            Some(next) => {
+                let is_next = |s: &str| {
+                    s.strip_prefix(',')
+                        .and_then(|s| s.strip_prefix(next))
+                        .is_some_and(|s| s.is_empty() || s.starts_with(['=', ',']))
+                };
+                if is_next(rest) {
+                    result.push(((*name).to_owned(), None));
+                    rest = &rest[1..];
+                } else {
+                    let value = rest.strip_prefix('=')?;
+                    let end = value
+                        .match_indices(',')
+                        .find_map(|(i, _)| is_next(&value[i..]).then_some(i))?;
+                    result.push(((*name).to_owned(), Some(value[..end].to_owned())));
+                    rest = &value[end + 1..];
+                }
+            }
+        }
+    }
+
+    rest.is_empty().then_some(Some(result))
+}
+
 fn format_str_truncate(n: usize, s: &str, f: &mut fmt::Formatter) -> fmt::Result {
     let len = s.chars().count();
     if len > n {
@@ -235,6 +373,18 @@ impl fmt::Display for Ja4HComputeError {
 
 impl std::error::Error for Ja4HComputeError {}
 
+#[derive(Debug, Clone)]
+/// error identifying a failure in [`Ja4H::parse`]
+pub struct Ja4HParseError(&'static str);
+
+impl fmt::Display for Ja4HParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ja4H Parse Error: {}", self.0)
+    }
+}
+
+impl std::error::Error for Ja4HParseError {}
+
 fn hash12(s: impl AsRef<str>) -> Cow<'static, str> {
     use sha2::{Digest as _, Sha256};
 
@@ -247,9 +397,28 @@ fn hash12(s: impl AsRef<str>) -> Cow<'static, str> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct HttpRequestMethod(Method);
 
+impl HttpRequestMethod {
+    fn parse(code: &str) -> Result<Self, Ja4HParseError> {
+        let method = match code {
+            "co" => Method::CONNECT,
+            "de" => Method::DELETE,
+            "ge" => Method::GET,
+            "he" => Method::HEAD,
+            "op" => Method::OPTIONS,
+            "pa" => Method::PATCH,
+            "po" => Method::POST,
+            "pu" => Method::PUT,
+            "tr" => Method::TRACE,
+            _ => Method::from_bytes(code.to_ascii_uppercase().as_bytes())
+                .map_err(|_| Ja4HParseError("invalid http method"))?,
+        };
+        Ok(Self(method))
+    }
+}
+
 impl fmt::Display for HttpRequestMethod {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let code = match self.0 {
@@ -283,7 +452,7 @@ impl From<Method> for HttpRequestMethod {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
 enum HttpVersion {
     Http1_0,
     Http1_1,
@@ -434,6 +603,60 @@ mod tests {
                 "{}",
                 test_case.description
             );
+
+            let parsed: Ja4H = ja4h.to_human_string().parse().expect(test_case.description);
+            assert!(ja4h == parsed, "{}", test_case.description);
+            assert_eq!(
+                test_case.expected_ja4h_str_hash,
+                format!("{parsed}"),
+                "{}",
+                test_case.description
+            );
+        }
+    }
+
+    #[test]
+    fn test_ja4h_parse_cookie_pairs() {
+        for (input, expected) in [
+            ("ge11nn01en00_Host__", None),
+            (
+                "po20cn02enus_Host,Accept_a,b_a=1,b",
+                Some(vec![
+                    ("a".to_owned(), Some("1".to_owned())),
+                    ("b".to_owned(), None),
+                ]),
+            ),
+            (
+                "pu30cn02enus_Host,Accept__a,_b__a=x,y,z,_b=,_",
+                Some(vec![
+                    ("_a".to_owned(), Some("x,y,z".to_owned())),
+                    ("_b".to_owned(), Some(",_".to_owned())),
+                ]),
+            ),
+        ] {
+            let ja4h = Ja4H::parse(input).expect(input);
+            assert_eq!(ja4h.cookie_pairs, expected, "input: {input}");
+            assert_eq!(ja4h.to_human_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_ja4h_parse_malformed() {
+        for s in [
+            "",
+            "ge11cr09enus_df50b14dec48_d733b88e2d70_774e52af4cfe",
+            "ge11nn01en00_Host",
+            "ge11nn01en00__",
+            "ge12nn01en00_Host__",
+            "ge11xn01en00_Host__",
+            "ge11nx01en00_Host__",
+            "ge11nn02en00_Host__",
+            "ge11nn01en00_Host,_",
+            "ge11nn01en00_Host_a_b=1",
+            "ge11nn01en00_Host__a=1",
+            "ge11cn01en_Host__",
+        ] {
+            assert!(Ja4H::parse(s).is_err(), "input: {s:?}");
         }
     }
 }
@@ -2,10 +2,10 @@
 mod http;
 
 #[cfg(feature = "http")]
-pub use http::{Ja4H, Ja4HComputeError};
+pub use http::{Ja4H, Ja4HComputeError, Ja4HParseError};
 
 #[cfg(feature = "tls")]
 mod tls;
 
 #[cfg(feature = "tls")]
-pub use tls::{Ja4, Ja4ComputeError};
+pub use tls::{Ja4, Ja4ComputeError, Ja4ParseError};
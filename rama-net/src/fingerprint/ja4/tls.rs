@@ -1,17 +1,19 @@
 use itertools::Itertools as _;
-use std::{borrow::Cow, fmt};
+use std::{borrow::Cow, fmt, str::FromStr};
 
 use rama_core::context::Extensions;
 
 use crate::tls::{
-    client::NegotiatedTlsParameters, ApplicationProtocol, CipherSuite, ExtensionId,
-    ProtocolVersion, SecureTransport, SignatureScheme,
+    client::{ClientHello, NegotiatedTlsParameters},
+    ApplicationProtocol, CipherSuite, ExtensionId, ProtocolVersion, SecureTransport,
+    SignatureScheme,
 };
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 /// Input data for a "ja4" hash.
 ///
-/// Computed using [`Ja4::compute`].
+/// Computed using [`Ja4::compute`],
+/// or parsed from its raw (human) string form using [`Ja4::parse`].
 pub struct Ja4 {
     protocol: TransportProtocol,
     version: TlsVersion,
@@ -33,13 +35,32 @@ impl Ja4 {
             .and_then(|st| st.client_hello())
             .ok_or(Ja4ComputeError::MissingClientHello)?;
 
-        let version: TlsVersion = match ext.get::<NegotiatedTlsParameters>() {
-            Some(params) => params.protocol_version,
-            None => {
-                tracing::trace!("NegotiatedTlsParameters missing: fallback to client hello tls.. (backward compat)");
-                client_hello.protocol_version()
-            }
-        }.try_into()?;
+        let negotiated_version = ext
+            .get::<NegotiatedTlsParameters>()
+            .map(|params| params.protocol_version);
+        if negotiated_version.is_none() {
+            tracing::trace!(
+                "NegotiatedTlsParameters missing: fallback to client hello tls.. (backward compat)"
+            );
+        }
+
+        Self::compute_from_client_hello(client_hello, negotiated_version)
+    }
+
+    /// Compute the [`Ja4`] (hash) from a parsed [`ClientHello`].
+    ///
+    /// Useful in case the TLS session is not terminated (e.g. in a passthrough proxy),
+    /// and the [`ClientHello`] was instead parsed from the peeked stream data.
+    ///
+    /// The protocol version of the [`ClientHello`] is used
+    /// in case no negotiated protocol version is given.
+    pub fn compute_from_client_hello(
+        client_hello: &ClientHello,
+        negotiated_version: Option<ProtocolVersion>,
+    ) -> Result<Self, Ja4ComputeError> {
+        let version: TlsVersion = negotiated_version
+            .unwrap_or_else(|| client_hello.protocol_version())
+            .try_into()?;
 
         let mut cipher_suites: Vec<_> = client_hello
             .cipher_suites()
@@ -117,6 +138,124 @@ impl Ja4 {
         format!("{self:?}")
     }
 
+    /// Parse a [`Ja4`] from its raw (human) string form,
+    /// as produced by [`Ja4::to_human_string`].
+    ///
+    /// The hashed form (e.g. `t13d1516h2_8daaf6152771_e5627efa2ab1`)
+    /// cannot be parsed, as the hashed chunks cannot be reversed.
+    ///
+    /// The raw form does not contain all data used to compute a [`Ja4`]:
+    /// only the first and second character of the ALPN value are known,
+    /// and the presence of the SNI and ALPN extensions is derived from
+    /// the first chunk. The parsed [`Ja4`] does however produce the same
+    /// fingerprint as the one it was parsed from.
+    pub fn parse(s: &str) -> Result<Self, Ja4ParseError> {
+        let mut chunks = s.trim().split('_');
+        let (Some(ja4_a), Some(cipher_suites), Some(extensions)) =
+            (chunks.next(), chunks.next(), chunks.next())
+        else {
+            return Err(Ja4ParseError(
+                "expected at least 3 underscore separated chunks",
+            ));
+        };
+        let signature_algorithms = chunks.next();
+        if chunks.next().is_some() {
+            return Err(Ja4ParseError(
+                "expected at most 4 underscore separated chunks",
+            ));
+        }
+
+        if ja4_a.len() != 10 || !ja4_a.is_ascii() {
+            return Err(Ja4ParseError("invalid first chunk"));
+        }
+
+        let protocol = match &ja4_a[0..1] {
+            "t" => TransportProtocol::Tcp,
+            "q" => TransportProtocol::Quic,
+            _ => return Err(Ja4ParseError("invalid transport protocol")),
+        };
+        let version = match &ja4_a[1..3] {
+            "10" => TlsVersion::Tls1_0,
+            "11" => TlsVersion::Tls1_1,
+            "12" => TlsVersion::Tls1_2,
+            "13" => TlsVersion::Tls1_3,
+            _ => return Err(Ja4ParseError("invalid tls version")),
+        };
+        let has_sni = match &ja4_a[3..4] {
+            "d" => true,
+            "i" => false,
+            _ => return Err(Ja4ParseError("invalid sni marker")),
+        };
+        let nr_ciphers: usize = ja4_a[4..6]
+            .parse()
+            .map_err(|_| Ja4ParseError("invalid number of cipher suites"))?;
+        let nr_exts: usize = ja4_a[6..8]
+            .parse()
+            .map_err(|_| Ja4ParseError("invalid number of extensions"))?;
+        let alpn = match &ja4_a[8..10] {
+            "00" => None,
+            alpn => Some(ApplicationProtocol::from(alpn)),
+        };
+
+        let cipher_suites: Vec<CipherSuite> =
+            parse_hex_list(cipher_suites, "invalid cipher suite")?;
+        if cipher_suites.is_empty() {
+            return Err(Ja4ParseError("empty cipher suites"));
+        }
+        if !count_matches(nr_ciphers, cipher_suites.len()) {
+            return Err(Ja4ParseError("number of cipher suites mismatch"));
+        }
+
+        let mut extensions: Vec<ExtensionId> = parse_hex_list(extensions, "invalid extension")?;
+        if extensions.iter().any(|ext| {
+            matches!(
+                *ext,
+                ExtensionId::SERVER_NAME | ExtensionId::APPLICATION_LAYER_PROTOCOL_NEGOTIATION
+            )
+        }) {
+            return Err(Ja4ParseError("unexpected sni or alpn extension"));
+        }
+        if has_sni {
+            extensions.push(ExtensionId::SERVER_NAME);
+        }
+        // the alpn extension can be present even if no alpn value is displayed,
+        // which is why we derive it from the number of extensions if possible
+        let has_alpn_ext = if nr_exts < 99 {
+            nr_exts > extensions.len()
+        } else {
+            alpn.is_some()
+        };
+        if has_alpn_ext {
+            extensions.push(ExtensionId::APPLICATION_LAYER_PROTOCOL_NEGOTIATION);
+        }
+        if !count_matches(nr_exts, extensions.len()) {
+            return Err(Ja4ParseError("number of extensions mismatch"));
+        }
+        extensions.sort_unstable_by_key(|k| format!("{k:04x}"));
+
+        let signature_algorithms: Vec<SignatureScheme> = match signature_algorithms {
+            Some(s) => {
+                let v = parse_hex_list(s, "invalid signature algorithm")?;
+                if v.is_empty() {
+                    return Err(Ja4ParseError("empty signature algorithms chunk"));
+                }
+                v
+            }
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            protocol,
+            version,
+            has_sni,
+            alpn,
+            cipher_suites,
+            extensions: (!extensions.is_empty()).then_some(extensions),
+            signature_algorithms: (!signature_algorithms.is_empty())
+                .then_some(signature_algorithms),
+        })
+    }
+
     fn fmt_as(&self, f: &mut fmt::Formatter<'_>, hash_chunks: bool) -> fmt::Result {
         let protocol = self.protocol;
         let version = self.version;
@@ -212,6 +351,36 @@ impl fmt::Debug for Ja4 {
     }
 }
 
+impl FromStr for Ja4 {
+    type Err = Ja4ParseError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+fn parse_hex_list<T: From<u16>>(s: &str, err: &'static str) -> Result<Vec<T>, Ja4ParseError> {
+    if s.is_empty() {
+        return Ok(Vec::new());
+    }
+    s.split(',')
+        .map(|v| {
+            if v.len() != 4 {
+                return Err(Ja4ParseError(err));
+            }
+            u16::from_str_radix(v, 16)
+                .map(Into::into)
+                .map_err(|_| Ja4ParseError(err))
+        })
+        .collect()
+}
+
+/// the counts in the first chunk are capped at 99
+fn count_matches(displayed: usize, actual: usize) -> bool {
+    displayed == 99.min(actual)
+}
+
 fn hash12(s: impl AsRef<str>) -> Cow<'static, str> {
     use sha2::{Digest as _, Sha256};
 
@@ -224,7 +393,7 @@ fn hash12(s: impl AsRef<str>) -> Cow<'static, str> {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
 enum TransportProtocol {
     Tcp,
     Quic,
@@ -240,7 +409,7 @@ impl fmt::Display for TransportProtocol {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
 enum TlsVersion {
     Tls1_0,
     Tls1_1,
@@ -306,6 +475,18 @@ impl fmt::Display for Ja4ComputeError {
 
 impl std::error::Error for Ja4ComputeError {}
 
+#[derive(Debug, Clone)]
+/// error identifying a failure in [`Ja4::parse`]
+pub struct Ja4ParseError(&'static str);
+
+impl fmt::Display for Ja4ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ja4 Parse Error: {}", self.0)
+    }
+}
+
+impl std::error::Error for Ja4ParseError {}
+
 #[cfg(test)]
 mod tests {
     use crate::tls::client::parse_client_hello;
@@ -345,10 +526,23 @@ mod tests {
             expected_ja4_hash: "t13d1716h2_5b57614c22b0_eeeea6562960",
         }];
         for test_case in test_cases {
+            let client_hello = parse_client_hello(&test_case.client_hello).expect(test_case.pcap);
+
+            // passthrough: computed straight from the parsed client hello
+            let ja4 = Ja4::compute_from_client_hello(
+                &client_hello,
+                test_case.negotiated_protocol_version,
+            )
+            .expect(test_case.pcap);
+            assert_eq!(
+                test_case.expected_ja4_hash,
+                format!("{ja4}"),
+                "pcap: {}",
+                test_case.pcap,
+            );
+
             let mut ext = Extensions::new();
-            ext.insert(SecureTransport::with_client_hello(
-                parse_client_hello(&test_case.client_hello).expect(test_case.pcap),
-            ));
+            ext.insert(SecureTransport::with_client_hello(client_hello));
             if let Some(negotiated_protocol_version) = test_case.negotiated_protocol_version {
                 ext.insert(NegotiatedTlsParameters {
                     protocol_version: negotiated_protocol_version,
@@ -372,6 +566,49 @@ mod tests {
                 "pcap: {}",
                 test_case.pcap,
             );
+
+            let parsed: Ja4 = ja4.to_human_string().parse().expect(test_case.pcap);
+            assert!(ja4 == parsed, "pcap: {}", test_case.pcap);
+            assert_eq!(
+                test_case.expected_ja4_str,
+                parsed.to_human_string(),
+                "pcap: {}",
+                test_case.pcap,
+            );
+            assert_eq!(
+                test_case.expected_ja4_hash,
+                format!("{parsed}"),
+                "pcap: {}",
+                test_case.pcap,
+            );
+        }
+    }
+
+    #[test]
+    fn test_ja4_parse_no_extensions() {
+        let ja4 = Ja4::parse("t12i010000_002f_").unwrap();
+        assert_eq!(ja4.to_human_string(), "t12i010000_002f_");
+        assert_eq!(format!("{ja4}"), "t12i010000_ba72b8082249_000000000000");
+    }
+
+    #[test]
+    fn test_ja4_parse_malformed() {
+        for s in [
+            "",
+            "t13d1516h2_8daaf6152771_e5627efa2ab1",
+            "t13d0101h2",
+            "x13d010100_002f_000a",
+            "t14d010100_002f_000a",
+            "t13x010100_002f_000a",
+            "t13d020100_002f_000a",
+            "t13d010400_002f_000a",
+            "t13d0101h2_002f_000a_0403_0804",
+            "t13i010000_002f,_",
+            "t13i010000__",
+            "t13i010100_002f_0000",
+            "t13i010100_002f_000a_",
+        ] {
+            assert!(Ja4::parse(s).is_err(), "input: {s:?}");
         }
     }
 }
@@ -4,13 +4,13 @@
 mod ja4;
 
 #[cfg(feature = "http")]
-pub use ja4::{Ja4H, Ja4HComputeError};
+pub use ja4::{Ja4H, Ja4HComputeError, Ja4HParseError};
 
 #[cfg(feature = "tls")]
-pub use ja4::{Ja4, Ja4ComputeError};
+pub use ja4::{Ja4, Ja4ComputeError, Ja4ParseError};
 
 #[cfg(feature = "tls")]
 mod ja3;
 
 #[cfg(feature = "tls")]
-pub use ja3::{Ja3, Ja3ComputeError};
+pub use ja3::{Ja3, Ja3ComputeError, Ja3ParseError};
@@ -0,0 +1,123 @@
+//! transport modes, used to drive the behaviour of connectors
+//!
+//! See [`TransportMode`] for more information.
+
+use serde::{Deserialize, Serialize};
+use std::{fmt, net::IpAddr, str::FromStr};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// The mode used by (DNS-resolving) connectors
+/// to select which IP families they connect over.
+///
+/// Connectors such as the TCP connector can be configured with a mode globally,
+/// while a [`TransportMode`] added as an extension to the request context
+/// overwrites that mode for that request only.
+pub enum TransportMode {
+    #[default]
+    /// Connect over both IPv4 and IPv6 in parallel,
+    /// using whichever connection gets established first.
+    DualStack,
+    /// Only ever connect over IPv4.
+    Ipv4Only,
+    /// Only ever connect over IPv6.
+    Ipv6Only,
+    /// Connect over IPv6, and only fall back to IPv4
+    /// in case no IPv6 connection could be established.
+    PreferIpv6,
+}
+
+impl TransportMode {
+    /// Returns `true` if this mode allows connecting over IPv4.
+    pub fn allows_ipv4(&self) -> bool {
+        !matches!(self, Self::Ipv6Only)
+    }
+
+    /// Returns `true` if this mode allows connecting over IPv6.
+    pub fn allows_ipv6(&self) -> bool {
+        !matches!(self, Self::Ipv4Only)
+    }
+
+    /// Returns `true` if this mode allows connecting to the given [`IpAddr`].
+    pub fn allows_ip(&self, ip: IpAddr) -> bool {
+        match ip {
+            IpAddr::V4(_) => self.allows_ipv4(),
+            IpAddr::V6(_) => self.allows_ipv6(),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::DualStack => "dual_stack",
+            Self::Ipv4Only => "ipv4_only",
+            Self::Ipv6Only => "ipv6_only",
+            Self::PreferIpv6 => "prefer_ipv6",
+        }
+    }
+}
+
+impl fmt::Display for TransportMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+rama_utils::macros::error::static_str_error! {
+    #[doc = "invalid transport mode"]
+    pub struct InvalidTransportMode;
+}
+
+impl FromStr for TransportMode {
+    type Err = InvalidTransportMode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        [
+            Self::DualStack,
+            Self::Ipv4Only,
+            Self::Ipv6Only,
+            Self::PreferIpv6,
+        ]
+        .into_iter()
+        .find(|mode| mode.as_str().eq_ignore_ascii_case(s))
+        .ok_or_else(InvalidTransportMode::default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transport_mode_allows_ip() {
+        let v4: IpAddr = "127.0.0.1".parse().unwrap();
+        let v6: IpAddr = "::1".parse().unwrap();
+
+        for (mode, allows_v4, allows_v6) in [
+            (TransportMode::DualStack, true, true),
+            (TransportMode::Ipv4Only, true, false),
+            (TransportMode::Ipv6Only, false, true),
+            (TransportMode::PreferIpv6, true, true),
+        ] {
+            assert_eq!(mode.allows_ip(v4), allows_v4, "{mode}");
+            assert_eq!(mode.allows_ip(v6), allows_v6, "{mode}");
+        }
+    }
+
+    #[test]
+    fn test_transport_mode_parse() {
+        for mode in [
+            TransportMode::DualStack,
+            TransportMode::Ipv4Only,
+            TransportMode::Ipv6Only,
+            TransportMode::PreferIpv6,
+        ] {
+            assert_eq!(mode, mode.to_string().parse().unwrap());
+        }
+        assert_eq!(
+            TransportMode::Ipv4Only,
+            " IPV4_ONLY ".parse::<TransportMode>().unwrap()
+        );
+        assert!("ipv5_only".parse::<TransportMode>().is_err());
+    }
+}
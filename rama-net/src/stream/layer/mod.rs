@@ -5,8 +5,10 @@
 mod tracker;
 #[doc(inline)]
 pub use tracker::{
-    BytesRWTrackerHandle, IncomingBytesTrackerLayer, IncomingBytesTrackerService,
-    OutgoingBytesTrackerLayer, OutgoingBytesTrackerService,
+    ByteMetrics, ByteMetricsConnectorLayer, ByteMetricsConnectorService, ByteMetricsLayer,
+    ByteMetricsReporter, ByteMetricsService, BytesRWTrackerHandle, CountingStream,
+    IncomingBytesTrackerLayer, IncomingBytesTrackerService, OutgoingBytesTrackerLayer,
+    OutgoingBytesTrackerService,
 };
 
 #[cfg(feature = "http")]
@@ -0,0 +1,493 @@
+//! Provides [`CountingStream`] which tracks the bytes read and written
+//! for a single connection and reports them once that connection is dropped.
+//!
+//! Use the [`ByteMetricsLayer`] for accepted (server) streams
+//! and the [`ByteMetricsConnectorLayer`] for connected (client) streams.
+
+use super::bytes::{BytesRWTracker, BytesRWTrackerHandle};
+use crate::{
+    client::{ConnectorService, EstablishedClientConnection},
+    stream::Stream,
+};
+use pin_project_lite::pin_project;
+use rama_core::{Context, Layer, Service};
+use rama_utils::macros::define_inner_service_accessors;
+use std::{
+    fmt,
+    future::Future,
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{self, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// The bytes read and written over the lifetime of a single connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ByteMetrics {
+    read: usize,
+    written: usize,
+}
+
+impl ByteMetrics {
+    /// Get the number of bytes read.
+    pub fn read(&self) -> usize {
+        self.read
+    }
+
+    /// Get the number of bytes written.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+}
+
+impl From<&BytesRWTrackerHandle> for ByteMetrics {
+    fn from(handle: &BytesRWTrackerHandle) -> Self {
+        Self {
+            read: handle.read(),
+            written: handle.written(),
+        }
+    }
+}
+
+/// A reporter of [`ByteMetrics`], called once per connection
+/// when the [`CountingStream`] is dropped.
+///
+/// Implemented for any `Fn(ByteMetrics)` closure.
+pub trait ByteMetricsReporter: Send + Sync + 'static {
+    /// Report the [`ByteMetrics`] of a finished connection.
+    fn report(&self, metrics: ByteMetrics);
+}
+
+impl<F> ByteMetricsReporter for F
+where
+    F: Fn(ByteMetrics) + Send + Sync + 'static,
+{
+    fn report(&self, metrics: ByteMetrics) {
+        (self)(metrics)
+    }
+}
+
+pin_project! {
+    /// A [`Stream`] which tracks the number of bytes read and written,
+    /// reporting the final [`ByteMetrics`] to a [`ByteMetricsReporter`] on drop.
+    ///
+    /// [`Stream`]: crate::stream::Stream
+    pub struct CountingStream<S, R>
+    where
+        R: ByteMetricsReporter,
+    {
+        #[pin]
+        stream: BytesRWTracker<S>,
+        reporter: Arc<R>,
+    }
+
+    impl<S, R> PinnedDrop for CountingStream<S, R>
+    where
+        R: ByteMetricsReporter,
+    {
+        fn drop(this: Pin<&mut Self>) {
+            let metrics = ByteMetrics::from(&this.stream.handle());
+            this.reporter.report(metrics);
+        }
+    }
+}
+
+impl<S: fmt::Debug, R: ByteMetricsReporter> fmt::Debug for CountingStream<S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CountingStream")
+            .field("stream", &self.stream)
+            .finish()
+    }
+}
+
+impl<S, R: ByteMetricsReporter> CountingStream<S, R> {
+    fn new(stream: S, reporter: Arc<R>) -> Self {
+        Self {
+            stream: BytesRWTracker::new(stream),
+            reporter,
+        }
+    }
+
+    /// Get the [`ByteMetrics`] of this stream (so far).
+    pub fn metrics(&self) -> ByteMetrics {
+        ByteMetrics::from(&self.stream.handle())
+    }
+
+    /// Get a [`BytesRWTrackerHandle`] that can be used to get the number of bytes
+    /// read and/or written even though the stream is consumed by a protocol
+    /// consumer in a later stage.
+    pub fn handle(&self) -> BytesRWTrackerHandle {
+        self.stream.handle()
+    }
+}
+
+impl<S, R> AsyncRead for CountingStream<S, R>
+where
+    S: AsyncRead,
+    R: ByteMetricsReporter,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.project().stream.poll_read(cx, buf)
+    }
+}
+
+impl<S, R> AsyncWrite for CountingStream<S, R>
+where
+    S: AsyncWrite,
+    R: ByteMetricsReporter,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        self.project().stream.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Result<(), io::Error>> {
+        self.project().stream.poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> Poll<Result<(), io::Error>> {
+        self.project().stream.poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<Result<usize, io::Error>> {
+        self.project().stream.poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.stream.is_write_vectored()
+    }
+}
+
+/// A [`Service`] that wraps a [`Service`]'s input IO [`Stream`] with a [`CountingStream`].
+///
+/// [`Service`]: rama_core::Service
+/// [`Stream`]: crate::stream::Stream
+pub struct ByteMetricsService<S, R> {
+    inner: S,
+    reporter: Arc<R>,
+}
+
+impl<S: fmt::Debug, R> fmt::Debug for ByteMetricsService<S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ByteMetricsService")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<S, R> ByteMetricsService<S, R> {
+    /// Create a new [`ByteMetricsService`].
+    ///
+    /// See [`ByteMetricsService`] for more information.
+    pub fn new(inner: S, reporter: R) -> Self {
+        Self {
+            inner,
+            reporter: Arc::new(reporter),
+        }
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S: Clone, R> Clone for ByteMetricsService<S, R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            reporter: self.reporter.clone(),
+        }
+    }
+}
+
+impl<State, S, R, IO> Service<State, IO> for ByteMetricsService<S, R>
+where
+    State: Clone + Send + Sync + 'static,
+    S: Service<State, CountingStream<IO, R>>,
+    R: ByteMetricsReporter,
+    IO: Stream,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    fn serve(
+        &self,
+        mut ctx: Context<State>,
+        stream: IO,
+    ) -> impl Future<Output = Result<Self::Response, Self::Error>> + Send + '_ {
+        let stream = CountingStream::new(stream, self.reporter.clone());
+        ctx.insert(stream.handle());
+        self.inner.serve(ctx, stream)
+    }
+}
+
+/// A [`Layer`] that wraps a [`Service`]'s input IO [`Stream`] with a [`CountingStream`].
+///
+/// [`Layer`]: rama_core::Layer
+/// [`Service`]: rama_core::Service
+/// [`Stream`]: crate::stream::Stream
+pub struct ByteMetricsLayer<R> {
+    reporter: Arc<R>,
+}
+
+impl<R> fmt::Debug for ByteMetricsLayer<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ByteMetricsLayer").finish()
+    }
+}
+
+impl<R> Clone for ByteMetricsLayer<R> {
+    fn clone(&self) -> Self {
+        Self {
+            reporter: self.reporter.clone(),
+        }
+    }
+}
+
+impl<R> ByteMetricsLayer<R> {
+    /// Create a new [`ByteMetricsLayer`] reporting to the given [`ByteMetricsReporter`].
+    pub fn new(reporter: R) -> Self {
+        Self {
+            reporter: Arc::new(reporter),
+        }
+    }
+}
+
+impl<S, R> Layer<S> for ByteMetricsLayer<R> {
+    type Service = ByteMetricsService<S, R>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ByteMetricsService {
+            inner,
+            reporter: self.reporter.clone(),
+        }
+    }
+}
+
+/// A [`Service`] that wraps a [`Service`]'s output IO [`Stream`] with a [`CountingStream`].
+///
+/// [`Service`]: rama_core::Service
+/// [`Stream`]: crate::stream::Stream
+pub struct ByteMetricsConnectorService<S, R> {
+    inner: S,
+    reporter: Arc<R>,
+}
+
+impl<S: fmt::Debug, R> fmt::Debug for ByteMetricsConnectorService<S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ByteMetricsConnectorService")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<S, R> ByteMetricsConnectorService<S, R> {
+    /// Create a new [`ByteMetricsConnectorService`].
+    ///
+    /// See [`ByteMetricsConnectorService`] for more information.
+    pub fn new(inner: S, reporter: R) -> Self {
+        Self {
+            inner,
+            reporter: Arc::new(reporter),
+        }
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S: Clone, R> Clone for ByteMetricsConnectorService<S, R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            reporter: self.reporter.clone(),
+        }
+    }
+}
+
+impl<S, R, State, Request> Service<State, Request> for ByteMetricsConnectorService<S, R>
+where
+    S: ConnectorService<State, Request, Connection: Stream + Unpin, Error: Send + Sync + 'static>,
+    R: ByteMetricsReporter,
+    State: Clone + Send + Sync + 'static,
+    Request: Send + 'static,
+{
+    type Response = EstablishedClientConnection<CountingStream<S::Connection, R>, State, Request>;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        let EstablishedClientConnection {
+            mut ctx,
+            req,
+            conn,
+            addr,
+        } = self.inner.connect(ctx, req).await?;
+        let conn = CountingStream::new(conn, self.reporter.clone());
+        ctx.insert(conn.handle());
+        Ok(EstablishedClientConnection {
+            ctx,
+            req,
+            conn,
+            addr,
+        })
+    }
+}
+
+/// A [`Layer`] that wraps a [`Service`]'s output IO [`Stream`] with a [`CountingStream`].
+///
+/// [`Layer`]: rama_core::Layer
+/// [`Service`]: rama_core::Service
+/// [`Stream`]: crate::stream::Stream
+pub struct ByteMetricsConnectorLayer<R> {
+    reporter: Arc<R>,
+}
+
+impl<R> fmt::Debug for ByteMetricsConnectorLayer<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ByteMetricsConnectorLayer").finish()
+    }
+}
+
+impl<R> Clone for ByteMetricsConnectorLayer<R> {
+    fn clone(&self) -> Self {
+        Self {
+            reporter: self.reporter.clone(),
+        }
+    }
+}
+
+impl<R> ByteMetricsConnectorLayer<R> {
+    /// Create a new [`ByteMetricsConnectorLayer`] reporting to the given [`ByteMetricsReporter`].
+    pub fn new(reporter: R) -> Self {
+        Self {
+            reporter: Arc::new(reporter),
+        }
+    }
+}
+
+impl<S, R> Layer<S> for ByteMetricsConnectorLayer<R> {
+    type Service = ByteMetricsConnectorService<S, R>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ByteMetricsConnectorService {
+            inner,
+            reporter: self.reporter.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rama_core::service::service_fn;
+    use std::{convert::Infallible, sync::Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_byte_metrics_layer_reports_exact_counts() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let layer = ByteMetricsLayer::new({
+            let reported = reported.clone();
+            move |metrics: ByteMetrics| reported.lock().unwrap().push(metrics)
+        });
+
+        let svc = layer.layer(service_fn(
+            |ctx: Context<()>, mut stream: CountingStream<tokio::io::DuplexStream, _>| async move {
+                let mut buf = [0u8; 11];
+                stream.read_exact(&mut buf).await.unwrap();
+                assert_eq!(&buf, b"hello world");
+                stream.write_all(b"pong").await.unwrap();
+
+                let handle = ctx.get::<BytesRWTrackerHandle>().unwrap();
+                assert_eq!(handle.read(), 11);
+                assert_eq!(handle.written(), 4);
+                Ok::<_, Infallible>(())
+            },
+        ));
+
+        let (mut client, server) = tokio::io::duplex(64);
+        client.write_all(b"hello world").await.unwrap();
+        svc.serve(Context::default(), server).await.unwrap();
+
+        let mut buf = [0u8; 4];
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"pong");
+
+        let reported = reported.lock().unwrap();
+        assert_eq!(
+            reported.as_slice(),
+            &[ByteMetrics {
+                read: 11,
+                written: 4
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_byte_metrics_connector_layer_reports_exact_counts() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let layer = ByteMetricsConnectorLayer::new({
+            let reported = reported.clone();
+            move |metrics: ByteMetrics| reported.lock().unwrap().push(metrics)
+        });
+
+        let (client, mut server) = tokio::io::duplex(64);
+        let client = Mutex::new(Some(client));
+        let connector = layer.layer(service_fn(move |ctx: Context<()>, req: ()| {
+            let conn = client.lock().unwrap().take().unwrap();
+            async move {
+                Ok::<_, Infallible>(EstablishedClientConnection {
+                    ctx,
+                    req,
+                    conn,
+                    addr: ([127, 0, 0, 1], 8080).into(),
+                })
+            }
+        }));
+
+        let EstablishedClientConnection { mut conn, .. } =
+            connector.serve(Context::default(), ()).await.unwrap();
+
+        conn.write_all(b"ping").await.unwrap();
+        let mut buf = [0u8; 4];
+        server.read_exact(&mut buf).await.unwrap();
+        server.write_all(b"hello world").await.unwrap();
+        let mut buf = [0u8; 11];
+        conn.read_exact(&mut buf).await.unwrap();
+
+        assert_eq!(
+            conn.metrics(),
+            ByteMetrics {
+                read: 11,
+                written: 4
+            }
+        );
+        assert!(reported.lock().unwrap().is_empty());
+
+        drop(conn);
+        assert_eq!(
+            reported.lock().unwrap().as_slice(),
+            &[ByteMetrics {
+                read: 11,
+                written: 4
+            }]
+        );
+    }
+}
@@ -9,3 +9,10 @@ pub use incoming::{IncomingBytesTrackerLayer, IncomingBytesTrackerService};
 mod outgoing;
 #[doc(inline)]
 pub use outgoing::{OutgoingBytesTrackerLayer, OutgoingBytesTrackerService};
+
+mod metrics;
+#[doc(inline)]
+pub use metrics::{
+    ByteMetrics, ByteMetricsConnectorLayer, ByteMetricsConnectorService, ByteMetricsLayer,
+    ByteMetricsReporter, ByteMetricsService, CountingStream,
+};
@@ -0,0 +1,171 @@
+use crate::tls::SecureTransport;
+use pin_project_lite::pin_project;
+use std::{
+    io::IoSlice,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{self, AsyncRead, AsyncWrite, ReadBuf};
+
+pin_project! {
+    /// A [`Stream`] which is either a plain stream
+    /// or a stream on which TLS was terminated.
+    ///
+    /// Allows acceptors which serve both plain and TLS traffic
+    /// on the same listener to hand a single stream type to the inner service.
+    /// The [`SecureTransport`] info is carried along with the TLS variant,
+    /// such that it remains available to whoever consumes the stream.
+    ///
+    /// [`Stream`]: super::Stream
+    #[project = MaybeTlsStreamProj]
+    #[derive(Debug)]
+    pub enum MaybeTlsStream<S, T> {
+        /// A plain (non-TLS) stream.
+        Plain {
+            #[pin]
+            stream: S,
+        },
+        /// A stream on which TLS was terminated.
+        Tls {
+            #[pin]
+            stream: T,
+            secure_transport: SecureTransport,
+        },
+    }
+}
+
+impl<S, T> MaybeTlsStream<S, T> {
+    /// Creates a new plain [`MaybeTlsStream`].
+    pub const fn plain(stream: S) -> Self {
+        Self::Plain { stream }
+    }
+
+    /// Creates a new [`MaybeTlsStream`] for a TLS-terminated stream.
+    pub const fn tls(stream: T, secure_transport: SecureTransport) -> Self {
+        Self::Tls {
+            stream,
+            secure_transport,
+        }
+    }
+
+    /// Returns `true` if TLS was terminated on this stream.
+    pub fn is_tls(&self) -> bool {
+        matches!(self, Self::Tls { .. })
+    }
+
+    /// Returns the [`SecureTransport`] info of this stream,
+    /// only available if TLS was terminated on it.
+    pub fn secure_transport(&self) -> Option<&SecureTransport> {
+        match self {
+            Self::Plain { .. } => None,
+            Self::Tls {
+                secure_transport, ..
+            } => Some(secure_transport),
+        }
+    }
+}
+
+impl<S, T> AsyncRead for MaybeTlsStream<S, T>
+where
+    S: AsyncRead,
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.project() {
+            MaybeTlsStreamProj::Plain { stream } => stream.poll_read(cx, buf),
+            MaybeTlsStreamProj::Tls { stream, .. } => stream.poll_read(cx, buf),
+        }
+    }
+}
+
+impl<S, T> AsyncWrite for MaybeTlsStream<S, T>
+where
+    S: AsyncWrite,
+    T: AsyncWrite,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        match self.project() {
+            MaybeTlsStreamProj::Plain { stream } => stream.poll_write(cx, buf),
+            MaybeTlsStreamProj::Tls { stream, .. } => stream.poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        match self.project() {
+            MaybeTlsStreamProj::Plain { stream } => stream.poll_flush(cx),
+            MaybeTlsStreamProj::Tls { stream, .. } => stream.poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        match self.project() {
+            MaybeTlsStreamProj::Plain { stream } => stream.poll_shutdown(cx),
+            MaybeTlsStreamProj::Tls { stream, .. } => stream.poll_shutdown(cx),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize, io::Error>> {
+        match self.project() {
+            MaybeTlsStreamProj::Plain { stream } => stream.poll_write_vectored(cx, bufs),
+            MaybeTlsStreamProj::Tls { stream, .. } => stream.poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            Self::Plain { stream } => stream.is_write_vectored(),
+            Self::Tls { stream, .. } => stream.is_write_vectored(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::Stream;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+    async fn echo<S: Stream + Unpin>(mut stream: S) {
+        let mut buf = [0u8; 5];
+        stream.read_exact(&mut buf).await.unwrap();
+        stream.write_all(&buf).await.unwrap();
+        stream.shutdown().await.unwrap();
+    }
+
+    async fn assert_echo(mut client: DuplexStream) {
+        client.write_all(b"hello").await.unwrap();
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_maybe_tls_stream_plain() {
+        let (client, server) = tokio::io::duplex(64);
+        let stream = MaybeTlsStream::<_, DuplexStream>::plain(server);
+        assert!(!stream.is_tls());
+        assert!(stream.secure_transport().is_none());
+        tokio::join!(echo(stream), assert_echo(client));
+    }
+
+    #[tokio::test]
+    async fn test_maybe_tls_stream_tls() {
+        let (client, server) = tokio::io::duplex(64);
+        let stream = MaybeTlsStream::<DuplexStream, _>::tls(server, SecureTransport::default());
+        assert!(stream.is_tls());
+        assert!(stream.secure_transport().is_some());
+        tokio::join!(echo(stream), assert_echo(client));
+    }
+}
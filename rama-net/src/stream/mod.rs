@@ -11,6 +11,16 @@ mod read;
 #[doc(inline)]
 pub use read::{ChainReader, HeapReader};
 
+mod peek;
+#[doc(inline)]
+pub use peek::PeekStream;
+
+#[cfg(feature = "tls")]
+mod maybe_tls;
+#[cfg(feature = "tls")]
+#[doc(inline)]
+pub use maybe_tls::MaybeTlsStream;
+
 /// A stream is a type that implements `AsyncRead`, `AsyncWrite` and `Send`.
 /// This is specific to Rama and is directly linked to the supertraits of `Tokio`.
 pub trait Stream: AsyncRead + AsyncWrite + Send + 'static {}
@@ -0,0 +1,116 @@
+use bytes::{Buf, Bytes};
+use pin_project_lite::pin_project;
+use std::{
+    io::IoSlice,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{self, AsyncRead, AsyncWrite, ReadBuf};
+
+pin_project! {
+    /// A [`Stream`] which replays already peeked (read) bytes
+    /// before reading from the wrapped stream.
+    ///
+    /// Writes are passed through to the wrapped stream as-is.
+    ///
+    /// Useful for acceptors that have to read some bytes from
+    /// a stream in order to decide how to handle it, e.g. to
+    /// detect whether or not a client initiated a TLS handshake.
+    ///
+    /// [`Stream`]: super::Stream
+    #[derive(Debug, Clone)]
+    pub struct PeekStream<S> {
+        peek: Bytes,
+        #[pin]
+        inner: S,
+    }
+}
+
+impl<S> PeekStream<S> {
+    /// Creates a new [`PeekStream`] which first yields
+    /// the `peek` bytes before reading from the `inner` stream.
+    pub fn new(peek: impl Into<Bytes>, inner: S) -> Self {
+        Self {
+            peek: peek.into(),
+            inner,
+        }
+    }
+
+    /// Returns the peeked bytes which have not been read yet.
+    pub fn peeked(&self) -> &[u8] {
+        &self.peek
+    }
+
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying stream.
+    ///
+    /// Care should be taken to avoid reading from the underlying stream
+    /// directly as long as not all peeked bytes have been read.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consumes the [`PeekStream`], returning the unread peeked bytes
+    /// and the underlying stream.
+    pub fn into_parts(self) -> (Bytes, S) {
+        (self.peek, self.inner)
+    }
+}
+
+impl<S> AsyncRead for PeekStream<S>
+where
+    S: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let me = self.project();
+
+        if !me.peek.is_empty() {
+            let n = me.peek.len().min(buf.remaining());
+            buf.put_slice(&me.peek[..n]);
+            me.peek.advance(n);
+            return Poll::Ready(Ok(()));
+        }
+        me.inner.poll_read(cx, buf)
+    }
+}
+
+impl<S> AsyncWrite for PeekStream<S>
+where
+    S: AsyncWrite,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<Result<usize, io::Error>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
@@ -42,6 +42,34 @@ pub struct NegotiatedTlsParameters {
     pub peer_certificate_chain: Option<DataEncoding>,
 }
 
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+/// Marker which can be added to the [`Context`] of a request
+/// to indicate that it is safe to be sent as TLS 1.3 early data (0-RTT).
+///
+/// Tls client connectors which support early data, and have it enabled,
+/// will only ever send early data for requests marked with this type,
+/// and only in case a resumable session is available for the server.
+///
+/// Early data is not protected against replay attacks:
+/// an attacker can capture it and send it again to the server,
+/// possibly many times. As such it should only be used for requests
+/// which are idempotent and of which the (repeated) processing has no side effects,
+/// e.g. a plain http `GET` request without sensitive (query) parameters.
+///
+/// Early data is currently only supported by the rustls client connector,
+/// other connectors ignore this marker.
+///
+/// [`Context`]: rama_core::Context
+pub struct EarlyDataSafe;
+
+impl EarlyDataSafe {
+    /// Create a new [`EarlyDataSafe`] marker.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
 /// Merge extension lists A and B, with
 /// B overwriting any conflict with A, and otherwise push it to the back.
 pub fn merge_client_hello_lists(
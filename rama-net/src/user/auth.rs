@@ -3,6 +3,7 @@
 use crate::user::{Basic, UserId};
 use rama_core::context::Extensions;
 use rama_core::username::{parse_username, UsernameLabelParser};
+use rama_utils::octets::ct_eq;
 use std::future::Future;
 
 // TODO: decouple this from http
@@ -43,7 +44,7 @@ impl<T: UsernameLabelParser> AuthoritySync<Basic, T> for Basic {
         let username = credentials.username();
         let password = credentials.password();
 
-        if password != self.password() {
+        if !ct_eq(password, self.password()) {
             return false;
         }
 
@@ -61,7 +62,7 @@ impl<T: UsernameLabelParser> AuthoritySync<Basic, T> for Basic {
             }
         };
 
-        if username != self.username() {
+        if !ct_eq(&username, self.username()) {
             return false;
         }
 
@@ -1,6 +1,7 @@
 use base64::engine::general_purpose::STANDARD as ENGINE;
 use base64::Engine;
 use rama_core::error::{ErrorContext, OpaqueError};
+use rama_utils::octets::ct_eq;
 use std::borrow::Cow;
 
 #[cfg(feature = "http")]
@@ -155,7 +156,8 @@ impl Basic {
 
 impl PartialEq<Basic> for Basic {
     fn eq(&self, other: &Basic) -> bool {
-        self.username() == other.username() && self.password() == other.password()
+        // non-short-circuiting, as to not leak which of the two mismatched
+        ct_eq(self.username(), other.username()) & ct_eq(self.password(), other.password())
     }
 }
 
@@ -1,13 +1,22 @@
 use rama_core::error::{ErrorContext, OpaqueError};
+use rama_utils::octets::ct_eq;
 use std::borrow::Cow;
 
 #[cfg(feature = "http")]
 use rama_http_types::{headers::authorization, HeaderValue};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 /// Bearer credentials.
 pub struct Bearer(Cow<'static, str>);
 
+impl PartialEq<Bearer> for Bearer {
+    fn eq(&self, other: &Bearer) -> bool {
+        ct_eq(self.0.as_bytes(), other.0.as_bytes())
+    }
+}
+
+impl Eq for Bearer {}
+
 impl Bearer {
     /// Try to create a [`Bearer`] from a header string.
     pub fn try_from_header_str(value: impl AsRef<str>) -> Result<Self, OpaqueError> {
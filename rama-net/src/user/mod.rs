@@ -10,6 +10,13 @@ mod credentials;
 #[doc(inline)]
 pub use credentials::{Basic, Bearer, ProxyCredential};
 
+mod provider;
+#[doc(inline)]
+pub use provider::{
+    credential_provider_fn, CredentialProvider, CredentialProviderFn, EnvCredentialProvider,
+    ProvideProxyCredential, ProvideProxyCredentialLayer, StaticCredentialProvider,
+};
+
 // todo: decouple from http
 #[cfg(feature = "http")]
 pub mod auth;
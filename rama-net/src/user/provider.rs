@@ -0,0 +1,467 @@
+use super::ProxyCredential;
+use crate::address::ProxyAddress;
+use crate::client::{ProxyCredentials, ProxyTarget};
+use rama_core::{
+    error::{BoxError, ErrorContext, ErrorExt, OpaqueError},
+    Context, Layer, Service,
+};
+use rama_utils::macros::define_inner_service_accessors;
+use std::{borrow::Cow, fmt, future::Future, sync::Arc};
+
+/// A provider of [`ProxyCredential`]s, which can be used
+/// to source the credentials used to authenticate with a proxy
+/// (e.g. via [`ProvideProxyCredentialLayer`]), per request.
+pub trait CredentialProvider<State>: Send + Sync + 'static {
+    /// The error returned in case the credentials could not be provided.
+    type Error: Send + Sync + 'static;
+
+    /// Provide the [`ProxyCredential`] to be used for the current request,
+    /// if any.
+    fn credential<'a>(
+        &'a self,
+        ctx: &'a Context<State>,
+    ) -> impl Future<Output = Result<Option<ProxyCredential>, Self::Error>> + Send + 'a;
+}
+
+impl<State, P> CredentialProvider<State> for Arc<P>
+where
+    P: CredentialProvider<State>,
+{
+    type Error = P::Error;
+
+    #[inline]
+    fn credential<'a>(
+        &'a self,
+        ctx: &'a Context<State>,
+    ) -> impl Future<Output = Result<Option<ProxyCredential>, Self::Error>> + Send + 'a {
+        (**self).credential(ctx)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A [`CredentialProvider`] which always provides the same [`ProxyCredential`].
+pub struct StaticCredentialProvider(ProxyCredential);
+
+impl StaticCredentialProvider {
+    /// Create a new [`StaticCredentialProvider`] for the given credential.
+    pub fn new(credential: impl Into<ProxyCredential>) -> Self {
+        Self(credential.into())
+    }
+}
+
+impl<State> CredentialProvider<State> for StaticCredentialProvider
+where
+    State: Send + Sync + 'static,
+{
+    type Error = std::convert::Infallible;
+
+    async fn credential<'a>(
+        &'a self,
+        _ctx: &'a Context<State>,
+    ) -> Result<Option<ProxyCredential>, Self::Error> {
+        Ok(Some(self.0.clone()))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A [`CredentialProvider`] which reads the [`ProxyCredential`]
+/// from an environment variable, each time it is requested.
+///
+/// The value is expected in the clear string format
+/// accepted by [`ProxyCredential::try_from_clear_str`],
+/// meaning `username:password` for basic credentials
+/// or a token for bearer credentials.
+///
+/// No credential is provided in case the variable is not defined.
+pub struct EnvCredentialProvider {
+    key: Cow<'static, str>,
+}
+
+impl EnvCredentialProvider {
+    /// Create a new [`EnvCredentialProvider`] reading the variable with the given key.
+    pub fn new(key: impl Into<Cow<'static, str>>) -> Self {
+        Self { key: key.into() }
+    }
+
+    /// Returns the key of the environment variable read by this provider.
+    pub fn key(&self) -> &str {
+        self.key.as_ref()
+    }
+}
+
+impl<State> CredentialProvider<State> for EnvCredentialProvider
+where
+    State: Send + Sync + 'static,
+{
+    type Error = OpaqueError;
+
+    async fn credential<'a>(
+        &'a self,
+        _ctx: &'a Context<State>,
+    ) -> Result<Option<ProxyCredential>, Self::Error> {
+        match std::env::var(self.key.as_ref()) {
+            Ok(value) => ProxyCredential::try_from_clear_str(value)
+                .map(Some)
+                .with_context(|| format!("parse proxy credential from env variable {}", self.key)),
+            Err(std::env::VarError::NotPresent) => Ok(None),
+            Err(err) => Err(OpaqueError::from_std(err)
+                .context(format!("read proxy credential env variable {}", self.key))),
+        }
+    }
+}
+
+/// A [`CredentialProvider`] created from an async function,
+/// see [`credential_provider_fn`].
+pub struct CredentialProviderFn<F> {
+    f: F,
+}
+
+impl<F> fmt::Debug for CredentialProviderFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CredentialProviderFn")
+            .field("f", &format_args!("{}", std::any::type_name::<F>()))
+            .finish()
+    }
+}
+
+impl<F: Clone> Clone for CredentialProviderFn<F> {
+    fn clone(&self) -> Self {
+        Self { f: self.f.clone() }
+    }
+}
+
+/// Create a [`CredentialProvider`] from an async function,
+/// which is called with the [`Context`] of each request.
+pub fn credential_provider_fn<F>(f: F) -> CredentialProviderFn<F> {
+    CredentialProviderFn { f }
+}
+
+impl<F, Fut, State, Error> CredentialProvider<State> for CredentialProviderFn<F>
+where
+    F: Fn(&Context<State>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Option<ProxyCredential>, Error>> + Send + 'static,
+    Error: Send + Sync + 'static,
+{
+    type Error = Error;
+
+    fn credential<'a>(
+        &'a self,
+        ctx: &'a Context<State>,
+    ) -> impl Future<Output = Result<Option<ProxyCredential>, Self::Error>> + Send + 'a {
+        (self.f)(ctx)
+    }
+}
+
+/// A [`Layer`] which produces [`ProvideProxyCredential`] services.
+pub struct ProvideProxyCredentialLayer<P> {
+    provider: Arc<P>,
+    overwrite: bool,
+}
+
+impl<P: fmt::Debug> fmt::Debug for ProvideProxyCredentialLayer<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProvideProxyCredentialLayer")
+            .field("provider", &self.provider)
+            .field("overwrite", &self.overwrite)
+            .finish()
+    }
+}
+
+impl<P> Clone for ProvideProxyCredentialLayer<P> {
+    fn clone(&self) -> Self {
+        Self {
+            provider: self.provider.clone(),
+            overwrite: self.overwrite,
+        }
+    }
+}
+
+impl<P> ProvideProxyCredentialLayer<P> {
+    /// Create a new [`ProvideProxyCredentialLayer`] using the given [`CredentialProvider`].
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider: Arc::new(provider),
+            overwrite: false,
+        }
+    }
+
+    /// Overwrite the credential of the proxy
+    /// even if it already has one defined.
+    ///
+    /// By default an existing credential is kept as-is.
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Overwrite the credential of the proxy
+    /// even if it already has one defined.
+    ///
+    /// By default an existing credential is kept as-is.
+    pub fn set_overwrite(&mut self, overwrite: bool) -> &mut Self {
+        self.overwrite = overwrite;
+        self
+    }
+}
+
+impl<S, P> Layer<S> for ProvideProxyCredentialLayer<P> {
+    type Service = ProvideProxyCredential<S, P>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ProvideProxyCredential {
+            inner,
+            provider: self.provider.clone(),
+            overwrite: self.overwrite,
+        }
+    }
+}
+
+/// A [`Service`] which sets the credential of the proxy
+/// found in the [`Context`], using a [`CredentialProvider`].
+///
+/// The credential is set as the [`ProxyCredentials`] of a [`ProxyTarget`],
+/// or else as the credential of a [`ProxyAddress`].
+/// Proxy connectors and layers (e.g. the http proxy connector and
+/// the layer which sets the `Proxy-Authorization` header for plain text requests)
+/// all read the credential using [`ProxyTarget::proxy_address`], and can thus be used
+/// as the inner service of this one, to source their credentials uniformly.
+///
+/// Nothing happens in case there is no proxy defined in the [`Context`].
+pub struct ProvideProxyCredential<S, P> {
+    inner: S,
+    provider: Arc<P>,
+    overwrite: bool,
+}
+
+impl<S: fmt::Debug, P: fmt::Debug> fmt::Debug for ProvideProxyCredential<S, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProvideProxyCredential")
+            .field("inner", &self.inner)
+            .field("provider", &self.provider)
+            .field("overwrite", &self.overwrite)
+            .finish()
+    }
+}
+
+impl<S: Clone, P> Clone for ProvideProxyCredential<S, P> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            provider: self.provider.clone(),
+            overwrite: self.overwrite,
+        }
+    }
+}
+
+impl<S, P> ProvideProxyCredential<S, P> {
+    /// Create a new [`ProvideProxyCredential`] using the given [`CredentialProvider`].
+    pub fn new(inner: S, provider: P) -> Self {
+        Self {
+            inner,
+            provider: Arc::new(provider),
+            overwrite: false,
+        }
+    }
+
+    /// Overwrite the credential of the proxy
+    /// even if it already has one defined.
+    ///
+    /// By default an existing credential is kept as-is.
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Overwrite the credential of the proxy
+    /// even if it already has one defined.
+    ///
+    /// By default an existing credential is kept as-is.
+    pub fn set_overwrite(&mut self, overwrite: bool) -> &mut Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S, P, State, Request> Service<State, Request> for ProvideProxyCredential<S, P>
+where
+    S: Service<State, Request, Error: Into<BoxError>>,
+    P: CredentialProvider<State, Error: Into<BoxError>>,
+    State: Clone + Send + Sync + 'static,
+    Request: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        mut ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        let needs_credential = ProxyTarget::proxy_address(&ctx)
+            .map(|address| self.overwrite || address.credential.is_none())
+            .unwrap_or_default();
+
+        if needs_credential {
+            let credential = self.provider.credential(&ctx).await.map_err(|err| {
+                OpaqueError::from_boxed(err.into()).context("provide proxy credential")
+            })?;
+            if let Some(credential) = credential {
+                tracing::trace!("proxy credential provided for proxy");
+                if ctx.contains::<ProxyTarget>() {
+                    ctx.insert(ProxyCredentials(credential));
+                } else if let Some(address) = ctx.get_mut::<ProxyAddress>() {
+                    address.credential = Some(credential);
+                }
+            }
+        }
+
+        self.inner.serve(ctx, req).await.map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::{Basic, Bearer};
+    use rama_core::service::service_fn;
+    use std::convert::Infallible;
+
+    async fn provided_credential<P>(
+        provider: P,
+        address: &str,
+        overwrite: bool,
+    ) -> Option<ProxyCredential>
+    where
+        P: CredentialProvider<(), Error: Into<BoxError>>,
+    {
+        let svc = ProvideProxyCredentialLayer::new(provider)
+            .overwrite(overwrite)
+            .layer(service_fn(|ctx: Context<()>, ()| async move {
+                Ok::<_, Infallible>(
+                    ctx.get::<ProxyAddress>()
+                        .and_then(|address| address.credential.clone()),
+                )
+            }));
+
+        let mut ctx = Context::default();
+        ctx.insert(ProxyAddress::try_from(address).unwrap());
+        svc.serve(ctx, ()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_static_credential_provider() {
+        let provider = StaticCredentialProvider::new(Basic::new("john", "secret"));
+
+        let credential = provided_credential(provider.clone(), "proxy.io:8080", false)
+            .await
+            .unwrap();
+        assert_eq!(credential, Basic::new("john", "secret").into());
+        #[cfg(feature = "http")]
+        assert_eq!(credential.as_header_value(), "Basic am9objpzZWNyZXQ=");
+
+        // existing credentials are kept by default
+        let credential = provided_credential(provider.clone(), "jane:pass@proxy.io:8080", false)
+            .await
+            .unwrap();
+        assert_eq!(credential, Basic::new("jane", "pass").into());
+
+        let credential = provided_credential(provider, "jane:pass@proxy.io:8080", true)
+            .await
+            .unwrap();
+        assert_eq!(credential, Basic::new("john", "secret").into());
+    }
+
+    #[tokio::test]
+    async fn test_callback_credential_provider() {
+        #[derive(Debug, Clone)]
+        struct Tenant(&'static str);
+
+        let provider = credential_provider_fn(|ctx: &Context<()>| {
+            let tenant = ctx.get::<Tenant>().cloned();
+            async move {
+                match tenant {
+                    Some(Tenant(name)) => Ok(Some(Bearer::try_from_clear_str(name)?.into())),
+                    None => Err(OpaqueError::from_display("missing tenant")),
+                }
+            }
+        });
+        let svc = ProvideProxyCredentialLayer::new(provider).layer(service_fn(
+            |ctx: Context<()>, ()| async move {
+                Ok::<_, Infallible>(
+                    ctx.get::<ProxyAddress>()
+                        .and_then(|address| address.credential.clone()),
+                )
+            },
+        ));
+
+        let mut ctx = Context::default();
+        ctx.insert(ProxyAddress::try_from("proxy.io:8080").unwrap());
+        ctx.insert(Tenant("acme"));
+        let credential = svc.serve(ctx, ()).await.unwrap().unwrap();
+        assert_eq!(
+            credential,
+            Bearer::try_from_clear_str("acme").unwrap().into()
+        );
+        #[cfg(feature = "http")]
+        assert_eq!(credential.as_header_value(), "Bearer acme");
+
+        // per-request provisioning can fail
+        let mut ctx = Context::default();
+        ctx.insert(ProxyAddress::try_from("proxy.io:8080").unwrap());
+        assert!(svc.serve(ctx, ()).await.is_err());
+
+        // provider is not called if there is no proxy in use
+        assert!(svc.serve(Context::default(), ()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_env_credential_provider() {
+        let key = "RAMA_NET_TEST_ENV_CREDENTIAL_PROVIDER";
+        let provider = EnvCredentialProvider::new(key);
+
+        std::env::remove_var(key);
+        assert!(
+            provided_credential(provider.clone(), "proxy.io:8080", false)
+                .await
+                .is_none()
+        );
+
+        std::env::set_var(key, "john:secret");
+        let credential = provided_credential(provider, "proxy.io:8080", false)
+            .await
+            .unwrap();
+        assert_eq!(credential, Basic::new("john", "secret").into());
+        std::env::remove_var(key);
+    }
+
+    #[tokio::test]
+    async fn test_credential_provider_with_proxy_target() {
+        let svc = ProvideProxyCredentialLayer::new(StaticCredentialProvider::new(Basic::new(
+            "john", "secret",
+        )))
+        .layer(service_fn(|ctx: Context<()>, ()| async move {
+            Ok::<_, Infallible>((
+                ctx.get::<ProxyCredentials>().cloned(),
+                ProxyTarget::proxy_address(&ctx).and_then(|address| address.credential),
+            ))
+        }));
+
+        let mut ctx = Context::default();
+        ctx.insert(ProxyTarget::new(([127, 0, 0, 1], 8080)));
+        let (credentials, credential) = svc.serve(ctx, ()).await.unwrap();
+        assert_eq!(
+            credentials,
+            Some(ProxyCredentials(Basic::new("john", "secret").into()))
+        );
+        assert_eq!(credential, Some(Basic::new("john", "secret").into()));
+
+        // existing credentials are kept by default
+        let mut ctx = Context::default();
+        ctx.insert(ProxyTarget::new(([127, 0, 0, 1], 8080)));
+        ctx.insert(ProxyCredentials(Basic::new("jane", "pass").into()));
+        let (_, credential) = svc.serve(ctx, ()).await.unwrap();
+        assert_eq!(credential, Some(Basic::new("jane", "pass").into()));
+    }
+}
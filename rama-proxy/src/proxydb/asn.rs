@@ -0,0 +1,219 @@
+use rama_net::asn::{Asn, InvalidAsn};
+use serde::{de, Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A filter matching an Autonomous System Number (ASN),
+/// either a single [`Asn`] or an inclusive range of them.
+///
+/// The string representation of a single [`Asn`] is its number (e.g. `7018`),
+/// while a range is represented as its inclusive bounds
+/// separated by `..` (e.g. `1000..2000`).
+pub enum AsnMatch {
+    /// Match a single [`Asn`].
+    Single(Asn),
+    /// Match any [`Asn`] within the range, bounds inclusive.
+    Range {
+        /// The lower bound (inclusive) of the range.
+        start: Asn,
+        /// The upper bound (inclusive) of the range.
+        end: Asn,
+    },
+}
+
+impl AsnMatch {
+    /// Create a new [`AsnMatch`] matching a single [`Asn`].
+    pub fn single(asn: Asn) -> Self {
+        Self::Single(asn)
+    }
+
+    /// Create a new [`AsnMatch`] matching any [`Asn`]
+    /// within the given range, bounds inclusive.
+    pub fn range(start: Asn, end: Asn) -> Self {
+        Self::Range { start, end }
+    }
+
+    /// Returns `true` if the given [`Asn`] is matched by this filter.
+    ///
+    /// An unspecified [`Asn`] is never matched by a range.
+    pub fn is_match(&self, asn: &Asn) -> bool {
+        match self {
+            Self::Single(single) => single == asn,
+            Self::Range { start, end } => {
+                !asn.is_any() && (start.as_u32()..=end.as_u32()).contains(&asn.as_u32())
+            }
+        }
+    }
+}
+
+impl From<Asn> for AsnMatch {
+    fn from(asn: Asn) -> Self {
+        Self::Single(asn)
+    }
+}
+
+impl fmt::Display for AsnMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Single(asn) => write!(f, "{}", asn.as_u32()),
+            Self::Range { start, end } => write!(f, "{}..{}", start.as_u32(), end.as_u32()),
+        }
+    }
+}
+
+impl FromStr for AsnMatch {
+    type Err = InvalidAsn;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.try_into()
+    }
+}
+
+impl TryFrom<&str> for AsnMatch {
+    type Error = InvalidAsn;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.split_once("..") {
+            Some((start, end)) => {
+                let start = Asn::try_from(start.trim())?;
+                let end = Asn::try_from(end.trim())?;
+                if start.is_any() || start.as_u32() > end.as_u32() {
+                    return Err(InvalidAsn::new());
+                }
+                Ok(Self::Range { start, end })
+            }
+            None => Ok(Self::Single(value.trim().try_into()?)),
+        }
+    }
+}
+
+impl TryFrom<String> for AsnMatch {
+    type Error = InvalidAsn;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.as_str().try_into()
+    }
+}
+
+impl TryFrom<&String> for AsnMatch {
+    type Error = InvalidAsn;
+
+    fn try_from(value: &String) -> Result<Self, Self::Error> {
+        value.as_str().try_into()
+    }
+}
+
+impl Serialize for AsnMatch {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Single(asn) => asn.serialize(serializer),
+            Self::Range { .. } => self.to_string().serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for AsnMatch {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = AsnMatch;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an asn number or an inclusive asn range (e.g. `1..100`)")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                u32::try_from(value)
+                    .ok()
+                    .and_then(|value| Asn::try_from(value).ok())
+                    .map(AsnMatch::Single)
+                    .ok_or_else(|| E::custom("invalid asn"))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                u64::try_from(value)
+                    .map_err(|_| E::custom("invalid asn"))
+                    .and_then(|value| self.visit_u64(value))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                value.try_into().map_err(|_| E::custom("invalid asn match"))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asn_match_single() {
+        let filter = AsnMatch::single(Asn::from_static(7018));
+        assert!(filter.is_match(&Asn::from_static(7018)));
+        assert!(!filter.is_match(&Asn::from_static(7019)));
+        assert!(!filter.is_match(&Asn::unspecified()));
+    }
+
+    #[test]
+    fn test_asn_match_range_boundaries() {
+        let filter = AsnMatch::range(Asn::from_static(100), Asn::from_static(200));
+        assert!(!filter.is_match(&Asn::from_static(99)));
+        assert!(filter.is_match(&Asn::from_static(100)));
+        assert!(filter.is_match(&Asn::from_static(150)));
+        assert!(filter.is_match(&Asn::from_static(200)));
+        assert!(!filter.is_match(&Asn::from_static(201)));
+        assert!(!filter.is_match(&Asn::unspecified()));
+    }
+
+    #[test]
+    fn test_asn_match_parse() {
+        for (input, expected) in [
+            ("7018", AsnMatch::single(Asn::from_static(7018))),
+            (" 7018 ", AsnMatch::single(Asn::from_static(7018))),
+            (
+                "100..200",
+                AsnMatch::range(Asn::from_static(100), Asn::from_static(200)),
+            ),
+            (
+                "42..42",
+                AsnMatch::range(Asn::from_static(42), Asn::from_static(42)),
+            ),
+        ] {
+            assert_eq!(input.parse::<AsnMatch>().unwrap(), expected, "{input}");
+        }
+
+        for input in [
+            "", "foo", "..", "1..", "..2", "200..100", "0..100", "1..2..3",
+        ] {
+            assert!(input.parse::<AsnMatch>().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_asn_match_serde() {
+        for (filter, json) in [
+            (AsnMatch::single(Asn::from_static(7018)), "7018"),
+            (
+                AsnMatch::range(Asn::from_static(100), Asn::from_static(200)),
+                "\"100..200\"",
+            ),
+        ] {
+            assert_eq!(serde_json::to_string(&filter).unwrap(), json);
+            assert_eq!(serde_json::from_str::<AsnMatch>(json).unwrap(), filter);
+        }
+        assert_eq!(
+            serde_json::from_str::<AsnMatch>("\"7018\"").unwrap(),
+            AsnMatch::single(Asn::from_static(7018))
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use super::ProxySessionKey;
 use rama_net::transport::{TransportContext, TransportProtocol};
 
 /// The context as relevant to the proxy layer.
@@ -5,12 +6,23 @@ use rama_net::transport::{TransportContext, TransportProtocol};
 pub struct ProxyContext {
     /// The transport protocol used by the proxy.
     pub protocol: TransportProtocol,
+
+    /// The key of the session for which a proxy is to be selected, if sticky.
+    ///
+    /// A [`ProxyDB`] which supports sticky selection, such as the [`MemoryProxyDB`],
+    /// consistently selects the same proxy for the same key,
+    /// for as long as that proxy remains a candidate.
+    ///
+    /// [`ProxyDB`]: super::ProxyDB
+    /// [`MemoryProxyDB`]: crate::MemoryProxyDB
+    pub sticky_key: Option<ProxySessionKey>,
 }
 
 impl From<TransportContext> for ProxyContext {
     fn from(ctx: TransportContext) -> Self {
         Self {
             protocol: ctx.protocol,
+            sticky_key: None,
         }
     }
 }
@@ -19,6 +31,7 @@ impl From<&TransportContext> for ProxyContext {
     fn from(ctx: &TransportContext) -> Self {
         Self {
             protocol: ctx.protocol,
+            sticky_key: None,
         }
     }
 }
@@ -4,6 +4,7 @@ use rama_net::{
     asn::{Asn, InvalidAsn},
     user::ProxyCredential,
 };
+use rama_utils::str::NonEmptyString;
 use std::path::Path;
 use tokio::{
     fs::File,
@@ -37,36 +38,35 @@ impl ProxyCsvRowReader {
 
     /// Read the next row from the CSV file.
     pub async fn next(&mut self) -> Result<Option<Proxy>, ProxyCsvRowReaderError> {
-        match &mut self.data {
-            ProxyCsvRowReaderData::File(lines) => {
-                let line = lines.next_line().await?;
-                match line {
-                    Some(line) => Ok(Some(match parse_csv_row(&line) {
-                        Some(proxy) => proxy,
-                        None => {
-                            return Err(ProxyCsvRowReaderError {
-                                kind: ProxyCsvRowReaderErrorKind::InvalidRow(line),
-                            });
-                        }
-                    })),
-                    None => Ok(None),
-                }
-            }
-            ProxyCsvRowReaderData::Raw(lines) => match lines.pop() {
-                Some(line) => Ok(Some(match parse_csv_row(&line) {
-                    Some(proxy) => proxy,
-                    None => {
-                        return Err(ProxyCsvRowReaderError {
-                            kind: ProxyCsvRowReaderErrorKind::InvalidRow(line),
-                        });
-                    }
-                })),
-                None => Ok(None),
-            },
+        let line = match &mut self.data {
+            ProxyCsvRowReaderData::File(lines) => lines.next_line().await?,
+            ProxyCsvRowReaderData::Raw(lines) => lines.pop(),
+        };
+        line.map(parse_csv_line).transpose()
+    }
+}
+
+fn parse_csv_line(line: String) -> Result<Proxy, ProxyCsvRowReaderError> {
+    match parse_csv_row(&line) {
+        Some(proxy) => Ok(proxy),
+        None => {
+            let kind = if parse_csv_id(&line).is_none() {
+                ProxyCsvRowReaderErrorKind::InvalidId(line)
+            } else {
+                ProxyCsvRowReaderErrorKind::InvalidRow(line)
+            };
+            Err(ProxyCsvRowReaderError { kind })
         }
     }
 }
 
+fn parse_csv_id(row: &str) -> Option<NonEmptyString> {
+    row.split(',')
+        .next()
+        .map(strip_csv_quotes)
+        .and_then(|id| NonEmptyString::new_trimmed(id).ok())
+}
+
 fn strip_csv_quotes(p: &str) -> &str {
     p.strip_prefix('"')
         .and_then(|p| p.strip_suffix('"'))
@@ -74,9 +74,8 @@ fn strip_csv_quotes(p: &str) -> &str {
 }
 
 pub(crate) fn parse_csv_row(row: &str) -> Option<Proxy> {
-    let mut iter = row.split(',').map(strip_csv_quotes);
-
-    let id = iter.next().and_then(|s| s.try_into().ok())?;
+    let id = parse_csv_id(row)?;
+    let mut iter = row.split(',').map(strip_csv_quotes).skip(1);
 
     let tcp = iter.next().and_then(parse_csv_bool)?;
     let udp = iter.next().and_then(parse_csv_bool)?;
@@ -112,6 +111,11 @@ pub(crate) fn parse_csv_row(row: &str) -> Option<Proxy> {
         }
     }
 
+    let weight = match iter.next() {
+        Some(value) => parse_csv_opt_weight(value).ok()?,
+        None => None,
+    };
+
     // Ensure there are no more values in the row
     if iter.next().is_some() {
         return None;
@@ -136,6 +140,7 @@ pub(crate) fn parse_csv_row(row: &str) -> Option<Proxy> {
         city,
         carrier,
         asn,
+        weight,
     })
 }
 
@@ -165,6 +170,14 @@ fn parse_csv_opt_asn(value: &str) -> Result<Option<Asn>, InvalidAsn> {
     }
 }
 
+fn parse_csv_opt_weight(value: &str) -> Result<Option<u32>, std::num::ParseIntError> {
+    if value.is_empty() {
+        Ok(None)
+    } else {
+        value.parse().map(Some)
+    }
+}
+
 #[derive(Debug)]
 enum ProxyCsvRowReaderData {
     File(Lines<BufReader<File>>),
@@ -177,6 +190,13 @@ pub struct ProxyCsvRowReaderError {
     kind: ProxyCsvRowReaderErrorKind,
 }
 
+impl ProxyCsvRowReaderError {
+    /// Returns the kind of error that occurred.
+    pub fn kind(&self) -> &ProxyCsvRowReaderErrorKind {
+        &self.kind
+    }
+}
+
 #[derive(Debug)]
 /// The kind of error that can occur when reading a Proxy CSV row.
 pub enum ProxyCsvRowReaderErrorKind {
@@ -184,6 +204,8 @@ pub enum ProxyCsvRowReaderErrorKind {
     IoError(std::io::Error),
     /// The CSV row is invalid, and could not be parsed.
     InvalidRow(String),
+    /// The id of the CSV row is empty or only contains whitespace.
+    InvalidId(String),
 }
 
 impl std::fmt::Display for ProxyCsvRowReaderError {
@@ -191,6 +213,9 @@ impl std::fmt::Display for ProxyCsvRowReaderError {
         match &self.kind {
             ProxyCsvRowReaderErrorKind::IoError(err) => write!(f, "I/O error: {}", err),
             ProxyCsvRowReaderErrorKind::InvalidRow(row) => write!(f, "Invalid row: {}", row),
+            ProxyCsvRowReaderErrorKind::InvalidId(row) => {
+                write!(f, "Invalid row: empty or whitespace-only id: {}", row)
+            }
         }
     }
 }
@@ -199,7 +224,8 @@ impl std::error::Error for ProxyCsvRowReaderError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match &self.kind {
             ProxyCsvRowReaderErrorKind::IoError(err) => Some(err),
-            ProxyCsvRowReaderErrorKind::InvalidRow(_) => None,
+            ProxyCsvRowReaderErrorKind::InvalidRow(_)
+            | ProxyCsvRowReaderErrorKind::InvalidId(_) => None,
         }
     }
 }
@@ -292,6 +318,7 @@ mod tests {
                     city: None,
                     carrier: None,
                     asn: None,
+                    weight: None,
                 },
             ),
             // more happy row tests
@@ -316,6 +343,7 @@ mod tests {
                     city: Some("city".into()),
                     carrier: Some("carrier".into()),
                     asn: None,
+                    weight: None,
                 },
             ),
             (
@@ -339,6 +367,31 @@ mod tests {
                     city: Some("*".into()),
                     carrier: Some("carrier".into()),
                     asn: Some(Asn::from_static(13335)),
+                    weight: None,
+                },
+            ),
+            (
+                "123,1,0,False,,True,,null,false,true,host:1234,,americas,*,*,*,carrier,13335,,5",
+                Proxy {
+                    id: NonEmptyString::from_static("123"),
+                    address: ProxyAddress::from_str("host:1234").unwrap(),
+                    tcp: true,
+                    udp: false,
+                    http: false,
+                    https: false,
+                    socks5: true,
+                    socks5h: false,
+                    datacenter: false,
+                    residential: false,
+                    mobile: true,
+                    pool_id: None,
+                    continent: Some("americas".into()),
+                    country: Some("*".into()),
+                    state: Some("*".into()),
+                    city: Some("*".into()),
+                    carrier: Some("carrier".into()),
+                    asn: Some(Asn::from_static(13335)),
+                    weight: Some(5),
                 },
             ),
             (
@@ -362,6 +415,7 @@ mod tests {
                     city: Some("*".into()),
                     carrier: Some("carrier".into()),
                     asn: Some(Asn::unspecified()),
+                    weight: None,
                 },
             ),
             (
@@ -385,6 +439,7 @@ mod tests {
                     city: None,
                     carrier: None,
                     asn: None,
+                    weight: None,
                 },
             ),
         ] {
@@ -405,6 +460,7 @@ mod tests {
             assert_eq!(proxy.city, output.city);
             assert_eq!(proxy.carrier, output.carrier);
             assert_eq!(proxy.asn, output.asn);
+            assert_eq!(proxy.weight, output.weight);
         }
     }
 
@@ -434,6 +490,12 @@ mod tests {
             "id,,,,,,,foo,authority,,,,,,,,",
             // invalid credentials
             "id,,,,,,,,authority,,,,,:foo",
+            // invalid weights
+            "id,1,,1,,,,1,,,authority,,,,,,,,,foo",
+            "id,1,,1,,,,1,,,authority,,,,,,,,,-1",
+            "id,1,,1,,,,1,,,authority,,,,,,,,,1.5",
+            // too many columns after the weight
+            "id,1,,1,,,,1,,,authority,,,,,,,,,1,",
         ] {
             assert!(parse_csv_row(input).is_none(), "input: {}", input);
         }
@@ -532,11 +594,36 @@ mod tests {
         assert!(reader.next().await.is_err());
     }
 
+    #[tokio::test]
+    async fn test_proxy_csv_row_reader_failure_blank_id() {
+        for row in [
+            ",1,,1,,,,,,,authority,,,,,,,",
+            "  ,1,,1,,,,,,,authority,,,,,,,",
+            "\" \",1,,1,,,,,,,authority,,,,,,,",
+        ] {
+            let mut reader = ProxyCsvRowReader::raw(row);
+            let err = reader.next().await.unwrap_err();
+            assert!(
+                matches!(err.kind(), ProxyCsvRowReaderErrorKind::InvalidId(_)),
+                "row: {row:?}, err: {err}"
+            );
+            assert!(err.to_string().contains("id"), "err: {err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_proxy_csv_row_reader_trimmed_id() {
+        let mut reader = ProxyCsvRowReader::raw(" id ,1,,1,,,,,,,authority,,,,,,,");
+        let proxy = reader.next().await.unwrap().unwrap();
+        assert_eq!(proxy.id, "id");
+    }
+
     #[test]
     fn test_proxy_is_match_happy_path_proxy_with_any_filter_string_cases() {
         let proxy = parse_csv_row("id,1,,1,,,,,,,authority,*,*,*,*,*,*,0").unwrap();
         let ctx = ProxyContext {
             protocol: TransportProtocol::Tcp,
+            sticky_key: None,
         };
 
         for filter in [
@@ -585,6 +672,7 @@ mod tests {
                 .unwrap();
         let ctx = ProxyContext {
             protocol: TransportProtocol::Tcp,
+            sticky_key: None,
         };
 
         for filter in [
@@ -620,7 +708,7 @@ mod tests {
                 state: Some(vec![StringFilter::new("state")]),
                 city: Some(vec![StringFilter::new("city")]),
                 carrier: Some(vec![StringFilter::new("carrier")]),
-                asn: Some(vec![Asn::from_static(42)]),
+                asn: Some(vec![Asn::from_static(42).into()]),
                 ..Default::default()
             },
             ProxyFilter {
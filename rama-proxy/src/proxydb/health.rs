@@ -0,0 +1,870 @@
+use super::{Proxy, ProxyContext, ProxyDB, ProxyFilter, ProxyID, ProxyQueryPredicate};
+use parking_lot::RwLock;
+use rama_core::error::{BoxError, ErrorContext, OpaqueError};
+use rama_net::{address::Authority, user::ProxyCredential};
+use rama_utils::macros::error::static_str_error;
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    task::JoinSet,
+};
+
+/// Health of a [`Proxy`] as last observed by a [`ProxyHealthCheck`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProxyHealth {
+    /// The [`Proxy`] was not (yet) probed,
+    /// e.g. because the probe does not support it.
+    Unknown,
+    /// The last probe of the [`Proxy`] succeeded.
+    Healthy,
+    /// The last probe of the [`Proxy`] failed.
+    Unhealthy,
+}
+
+impl ProxyHealth {
+    /// Returns `false` only if the [`Proxy`] is known to be unhealthy.
+    pub fn is_available(&self) -> bool {
+        !matches!(self, Self::Unhealthy)
+    }
+}
+
+impl fmt::Display for ProxyHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown => write!(f, "unknown"),
+            Self::Healthy => write!(f, "healthy"),
+            Self::Unhealthy => write!(f, "unhealthy"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct HealthState {
+    /// Amount of linked health checks currently running.
+    probing: AtomicUsize,
+    /// Reference point of [`HealthEntry::last_seen`].
+    epoch: Instant,
+    proxies: RwLock<HashMap<String, HealthEntry>>,
+}
+
+#[derive(Debug)]
+struct HealthEntry {
+    proxy: Proxy,
+    health: ProxyHealth,
+    /// Milliseconds since [`HealthState::epoch`] at which the proxy was last
+    /// registered, atomic such that it can be refreshed under a read lock.
+    last_seen: AtomicU64,
+}
+
+impl Default for HealthState {
+    fn default() -> Self {
+        Self {
+            probing: AtomicUsize::new(0),
+            epoch: Instant::now(),
+            proxies: RwLock::default(),
+        }
+    }
+}
+
+impl HealthState {
+    fn now(&self) -> u64 {
+        self.epoch.elapsed().as_millis() as u64
+    }
+
+    fn register(&self, proxy: &Proxy) {
+        let now = self.now();
+        if let Some(entry) = self.proxies.read().get(proxy.id.as_str()) {
+            entry.last_seen.store(now, Ordering::Relaxed);
+            return;
+        }
+        self.proxies
+            .write()
+            .entry(proxy.id.as_str().to_owned())
+            .or_insert_with(|| HealthEntry {
+                proxy: proxy.clone(),
+                health: ProxyHealth::Unknown,
+                last_seen: AtomicU64::new(now),
+            });
+    }
+
+    fn prune(&self, ttl: Duration) {
+        let deadline = self.now().saturating_sub(ttl.as_millis() as u64);
+        self.proxies.write().retain(|id, entry| {
+            let keep = entry.last_seen.load(Ordering::Relaxed) >= deadline;
+            if !keep {
+                tracing::trace!(proxy.id = %id, "proxy health check: prune expired proxy");
+            }
+            keep
+        });
+    }
+
+    fn is_probing(&self) -> bool {
+        self.probing.load(Ordering::Acquire) > 0
+    }
+
+    fn is_available(&self, proxy: &Proxy) -> bool {
+        // fail-open: without a running health check
+        // the known health states can no longer be trusted
+        if !self.is_probing() {
+            return true;
+        }
+        self.proxies
+            .read()
+            .get(proxy.id.as_str())
+            .map(|entry| entry.health.is_available())
+            .unwrap_or(true)
+    }
+}
+
+/// A [`ProxyDB`] wrapper which excludes the [`Proxy`]s
+/// found to be unhealthy by a linked [`ProxyHealthCheck`].
+///
+/// The [`Proxy`]s to be probed are registered using [`Self::with_proxies`],
+/// and any [`Proxy`] returned by the inner [`ProxyDB`] is registered as well.
+/// A [`Proxy`] is excluded from the query results for as long
+/// as its last probe failed.
+///
+/// Registered proxies can be removed using [`Self::remove_proxies`],
+/// or pruned automatically once they were not registered (or returned by
+/// the inner [`ProxyDB`]) for the [ttl] of the health check.
+///
+/// [ttl]: ProxyHealthCheck::with_proxy_ttl
+///
+/// The wrapper fails open: while no linked [`ProxyHealthCheck`] is running
+/// (e.g. it was not yet started, or it was stopped or panicked)
+/// all proxies are considered available.
+///
+/// # Example
+///
+/// ```
+/// use rama_core::rt::Executor;
+/// use rama_proxy::{HealthCheckedProxyDB, Proxy, TcpConnectProbe};
+/// use std::time::Duration;
+///
+/// # fn example(proxies: Vec<Proxy>, inner: impl rama_proxy::ProxyDB) {
+/// let db = HealthCheckedProxyDB::new(inner).with_proxies(proxies);
+/// Executor::default().spawn_task(
+///     db.health_check(TcpConnectProbe::default())
+///         .with_interval(Duration::from_secs(60))
+///         .run(),
+/// );
+/// # }
+/// ```
+pub struct HealthCheckedProxyDB<D> {
+    inner: D,
+    state: Arc<HealthState>,
+}
+
+impl<D: fmt::Debug> fmt::Debug for HealthCheckedProxyDB<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HealthCheckedProxyDB")
+            .field("inner", &self.inner)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<D: Clone> Clone for HealthCheckedProxyDB<D> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<D> HealthCheckedProxyDB<D> {
+    /// Create a new [`HealthCheckedProxyDB`] wrapping the given [`ProxyDB`].
+    pub fn new(inner: D) -> Self {
+        Self {
+            inner,
+            state: Default::default(),
+        }
+    }
+
+    /// Register the given [`Proxy`]s to be probed.
+    pub fn with_proxies(self, proxies: impl IntoIterator<Item = Proxy>) -> Self {
+        self.register_proxies(proxies);
+        self
+    }
+
+    /// Register the given [`Proxy`]s to be probed.
+    pub fn register_proxies(&self, proxies: impl IntoIterator<Item = Proxy>) {
+        for proxy in proxies {
+            self.state.register(&proxy);
+        }
+    }
+
+    /// Remove the [`Proxy`]s with the given ids, such that they are no longer probed.
+    ///
+    /// A removed [`Proxy`] is registered again once returned by the inner [`ProxyDB`].
+    pub fn remove_proxies<I>(&self, ids: I)
+    where
+        I: IntoIterator<Item: AsRef<str>>,
+    {
+        let mut proxies = self.state.proxies.write();
+        for id in ids {
+            proxies.remove(id.as_ref());
+        }
+    }
+
+    /// Create the [`ProxyHealthCheck`] for this [`HealthCheckedProxyDB`],
+    /// probing the registered proxies using the given [`ProxyProbe`].
+    ///
+    /// The health check only starts once [`ProxyHealthCheck::run`] is awaited,
+    /// typically by spawning it as a background task.
+    pub fn health_check<P>(&self, probe: P) -> ProxyHealthCheck<P> {
+        ProxyHealthCheck {
+            state: self.state.clone(),
+            probe: Arc::new(probe),
+            interval: ProxyHealthCheck::<P>::DEFAULT_INTERVAL,
+            probe_timeout: ProxyHealthCheck::<P>::DEFAULT_PROBE_TIMEOUT,
+            proxy_ttl: None,
+        }
+    }
+
+    /// Get the current [`ProxyHealth`] of the [`Proxy`] with the given id,
+    /// or `None` if no such [`Proxy`] is registered.
+    pub fn health(&self, id: impl AsRef<str>) -> Option<ProxyHealth> {
+        self.state
+            .proxies
+            .read()
+            .get(id.as_ref())
+            .map(|entry| entry.health)
+    }
+
+    /// Get the current [`ProxyHealth`] of all registered proxies.
+    pub fn health_states(&self) -> Vec<(ProxyID, ProxyHealth)> {
+        self.state
+            .proxies
+            .read()
+            .values()
+            .map(|entry| (ProxyID::from(entry.proxy.id.clone()), entry.health))
+            .collect()
+    }
+
+    /// Returns `true` if at least one linked [`ProxyHealthCheck`] is currently running.
+    ///
+    /// Unhealthy proxies are only excluded while this is the case.
+    pub fn is_health_check_running(&self) -> bool {
+        self.state.is_probing()
+    }
+}
+
+impl<D> ProxyDB for HealthCheckedProxyDB<D>
+where
+    D: ProxyDB,
+{
+    type Error = D::Error;
+
+    async fn get_proxy_if(
+        &self,
+        ctx: ProxyContext,
+        filter: ProxyFilter,
+        predicate: impl ProxyQueryPredicate,
+    ) -> Result<Proxy, Self::Error> {
+        let predicate = HealthPredicate {
+            state: self.state.clone(),
+            predicate,
+        };
+        let proxy = self.inner.get_proxy_if(ctx, filter, predicate).await?;
+        self.state.register(&proxy);
+        Ok(proxy)
+    }
+
+    async fn get_proxy(
+        &self,
+        ctx: ProxyContext,
+        filter: ProxyFilter,
+    ) -> Result<Proxy, Self::Error> {
+        self.get_proxy_if(ctx, filter, true).await
+    }
+}
+
+#[derive(Debug, Clone)]
+struct HealthPredicate<P> {
+    state: Arc<HealthState>,
+    predicate: P,
+}
+
+impl<P: ProxyQueryPredicate> ProxyQueryPredicate for HealthPredicate<P> {
+    fn execute(&self, proxy: &Proxy) -> bool {
+        self.predicate.execute(proxy) && self.state.is_available(proxy)
+    }
+}
+
+static_str_error! {
+    #[doc = "proxy not probed: not supported by the probe"]
+    pub struct ProxyNotProbedErr;
+}
+
+/// A probe used by a [`ProxyHealthCheck`] to check the health of a [`Proxy`].
+///
+/// A probe which cannot check a given [`Proxy`], e.g. because it does
+/// not support its protocol, returns a [`ProxyNotProbedErr`],
+/// in which case its [`ProxyHealth`] is reported as [`ProxyHealth::Unknown`].
+///
+/// Implemented for [`TcpConnectProbe`], [`HttpConnectProbe`]
+/// and any async function taking a [`Proxy`].
+pub trait ProxyProbe: Send + Sync + 'static {
+    /// Probe the given [`Proxy`], returning an error if it is unhealthy.
+    fn probe(&self, proxy: &Proxy) -> impl Future<Output = Result<(), BoxError>> + Send;
+}
+
+impl<F, Fut, E> ProxyProbe for F
+where
+    F: Fn(Proxy) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: Into<BoxError>,
+{
+    fn probe(&self, proxy: &Proxy) -> impl Future<Output = Result<(), BoxError>> + Send {
+        let fut = self(proxy.clone());
+        async move { fut.await.map_err(Into::into) }
+    }
+}
+
+/// A [`ProxyProbe`] which considers a [`Proxy`] healthy
+/// if a tcp connection can be established with it.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct TcpConnectProbe;
+
+impl TcpConnectProbe {
+    /// Create a new [`TcpConnectProbe`].
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ProxyProbe for TcpConnectProbe {
+    async fn probe(&self, proxy: &Proxy) -> Result<(), BoxError> {
+        TcpStream::connect(proxy.address.authority.to_string())
+            .await
+            .context("tcp connect to proxy")?;
+        Ok(())
+    }
+}
+
+/// A [`ProxyProbe`] which considers a (plain text) http [`Proxy`] healthy
+/// if it accepts a `CONNECT` request to the configured target
+/// with a `2xx` status code.
+///
+/// The [`ProxyCredential`] of the [`Proxy`], if any,
+/// is used as the `Proxy-Authorization` header.
+///
+/// Proxies using another protocol than plain text http (e.g. https or socks5)
+/// are not probed, and reported as such using a [`ProxyNotProbedErr`].
+#[derive(Debug, Clone)]
+pub struct HttpConnectProbe {
+    target: Authority,
+}
+
+impl HttpConnectProbe {
+    const MAX_RESPONSE_HEAD_SIZE: usize = 8 * 1024;
+
+    /// Create a new [`HttpConnectProbe`] which
+    /// requests to `CONNECT` to the given target.
+    pub fn new(target: Authority) -> Self {
+        Self { target }
+    }
+}
+
+impl ProxyProbe for HttpConnectProbe {
+    async fn probe(&self, proxy: &Proxy) -> Result<(), BoxError> {
+        if let Some(protocol) = &proxy.address.protocol {
+            if !protocol.is_http() || protocol.is_secure() {
+                return Err(ProxyNotProbedErr::new().into());
+            }
+        }
+
+        let mut stream = TcpStream::connect(proxy.address.authority.to_string())
+            .await
+            .context("tcp connect to proxy")?;
+
+        let mut request = format!(
+            "CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n",
+            target = self.target
+        );
+        match &proxy.address.credential {
+            Some(ProxyCredential::Basic(basic)) => {
+                request.push_str(&format!(
+                    "Proxy-Authorization: {}\r\n",
+                    basic.as_header_string()
+                ));
+            }
+            Some(ProxyCredential::Bearer(bearer)) => {
+                request.push_str(&format!(
+                    "Proxy-Authorization: {}\r\n",
+                    bearer.as_header_string()
+                ));
+            }
+            None => (),
+        }
+        request.push_str("\r\n");
+
+        stream
+            .write_all(request.as_bytes())
+            .await
+            .context("write http connect request")?;
+
+        let mut head = Vec::with_capacity(512);
+        let mut buf = [0u8; 512];
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+            if head.len() > Self::MAX_RESPONSE_HEAD_SIZE {
+                return Err(
+                    OpaqueError::from_display("http connect response head too large").into(),
+                );
+            }
+            let n = stream
+                .read(&mut buf)
+                .await
+                .context("read http connect response")?;
+            if n == 0 {
+                return Err(
+                    OpaqueError::from_display("http connect response: unexpected eof").into(),
+                );
+            }
+            head.extend_from_slice(&buf[..n]);
+        }
+
+        let status = head
+            .split(|b| *b == b' ')
+            .nth(1)
+            .and_then(|status| std::str::from_utf8(status).ok())
+            .and_then(|status| status.parse::<u16>().ok())
+            .context("parse http connect response status")?;
+        if !(200..300).contains(&status) {
+            return Err(OpaqueError::from_display(format!(
+                "http connect response: unexpected status code {status}"
+            ))
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Background health check linked to a [`HealthCheckedProxyDB`],
+/// created using [`HealthCheckedProxyDB::health_check`].
+///
+/// Every interval it probes all registered proxies concurrently
+/// using its [`ProxyProbe`], updating their [`ProxyHealth`].
+/// A probe which does not finish within the probe timeout is
+/// considered to have failed.
+///
+/// Multiple health checks can be linked to the same [`HealthCheckedProxyDB`],
+/// which fails open once none of them is running anymore.
+pub struct ProxyHealthCheck<P> {
+    state: Arc<HealthState>,
+    probe: Arc<P>,
+    interval: Duration,
+    probe_timeout: Duration,
+    proxy_ttl: Option<Duration>,
+}
+
+impl<P: fmt::Debug> fmt::Debug for ProxyHealthCheck<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyHealthCheck")
+            .field("state", &self.state)
+            .field("probe", &self.probe)
+            .field("interval", &self.interval)
+            .field("probe_timeout", &self.probe_timeout)
+            .field("proxy_ttl", &self.proxy_ttl)
+            .finish()
+    }
+}
+
+impl<P> ProxyHealthCheck<P> {
+    /// Default interval between two probe rounds.
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+
+    /// Default timeout for a single probe.
+    pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Set the interval between two probe rounds.
+    ///
+    /// Defaults to [`Self::DEFAULT_INTERVAL`].
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the interval between two probe rounds.
+    ///
+    /// Defaults to [`Self::DEFAULT_INTERVAL`].
+    pub fn set_interval(&mut self, interval: Duration) -> &mut Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the timeout for a single probe.
+    ///
+    /// Defaults to [`Self::DEFAULT_PROBE_TIMEOUT`].
+    pub fn with_probe_timeout(mut self, timeout: Duration) -> Self {
+        self.probe_timeout = timeout;
+        self
+    }
+
+    /// Set the timeout for a single probe.
+    ///
+    /// Defaults to [`Self::DEFAULT_PROBE_TIMEOUT`].
+    pub fn set_probe_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.probe_timeout = timeout;
+        self
+    }
+
+    /// Set the ttl of registered proxies.
+    ///
+    /// Proxies which were not registered, or returned by the inner [`ProxyDB`],
+    /// within this ttl are pruned before each probe round, and thus no longer probed.
+    /// By default registered proxies are kept until they are
+    /// [removed](HealthCheckedProxyDB::remove_proxies).
+    pub fn with_proxy_ttl(mut self, ttl: Duration) -> Self {
+        self.proxy_ttl = Some(ttl);
+        self
+    }
+
+    /// Set the ttl of registered proxies.
+    ///
+    /// Proxies which were not registered, or returned by the inner [`ProxyDB`],
+    /// within this ttl are pruned before each probe round, and thus no longer probed.
+    /// By default registered proxies are kept until they are
+    /// [removed](HealthCheckedProxyDB::remove_proxies).
+    pub fn set_proxy_ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.proxy_ttl = Some(ttl);
+        self
+    }
+}
+
+impl<P: ProxyProbe> ProxyHealthCheck<P> {
+    /// Run the health check until the returned future is dropped.
+    ///
+    /// Unhealthy proxies are only excluded by the linked
+    /// [`HealthCheckedProxyDB`] for as long as this future is running.
+    pub async fn run(self) {
+        let _guard = ProbingGuard::new(self.state.clone());
+
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            self.probe_all().await;
+        }
+    }
+
+    async fn probe_all(&self) {
+        if let Some(ttl) = self.proxy_ttl {
+            self.state.prune(ttl);
+        }
+
+        let proxies: Vec<_> = self
+            .state
+            .proxies
+            .read()
+            .values()
+            .map(|entry| entry.proxy.clone())
+            .collect();
+
+        let mut probes = JoinSet::new();
+        for proxy in proxies {
+            let probe = self.probe.clone();
+            let timeout = self.probe_timeout;
+            probes.spawn(async move {
+                let result = match tokio::time::timeout(timeout, probe.probe(&proxy)).await {
+                    Ok(result) => result,
+                    Err(_) => Err(OpaqueError::from_display("probe timed out").into()),
+                };
+                (ProxyID::from(proxy.id), result)
+            });
+        }
+
+        while let Some(result) = probes.join_next().await {
+            let (id, result) = match result {
+                Ok(output) => output,
+                Err(err) => {
+                    tracing::debug!(error = %err, "proxy health check: probe task failed");
+                    continue;
+                }
+            };
+            let health = match result {
+                Ok(()) => ProxyHealth::Healthy,
+                Err(err) if err.downcast_ref::<ProxyNotProbedErr>().is_some() => {
+                    tracing::trace!(proxy.id = %id, "proxy health check: proxy not probed");
+                    ProxyHealth::Unknown
+                }
+                Err(err) => {
+                    tracing::debug!(proxy.id = %id, error = %err, "proxy health check: probe failed");
+                    ProxyHealth::Unhealthy
+                }
+            };
+            if let Some(entry) = self.state.proxies.write().get_mut(id.as_str()) {
+                if entry.health != health {
+                    tracing::trace!(proxy.id = %id, %health, "proxy health check: health changed");
+                }
+                entry.health = health;
+            }
+        }
+    }
+}
+
+/// Counts the health check as running for as long as it is alive,
+/// such that the [`HealthCheckedProxyDB`] fails open once all
+/// linked health checks stopped, panicked or were dropped.
+struct ProbingGuard(Arc<HealthState>);
+
+impl ProbingGuard {
+    fn new(state: Arc<HealthState>) -> Self {
+        state.probing.fetch_add(1, Ordering::AcqRel);
+        Self(state)
+    }
+}
+
+impl Drop for ProbingGuard {
+    fn drop(&mut self) {
+        self.0.probing.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_net::transport::TransportProtocol;
+    use tokio::net::TcpListener;
+
+    fn proxy(id: &'static str, address: &str) -> Proxy {
+        Proxy {
+            address: address.try_into().unwrap(),
+            ..crate::proxydb::test_proxy(id)
+        }
+    }
+
+    fn ctx() -> ProxyContext {
+        ProxyContext {
+            protocol: TransportProtocol::Tcp,
+            sticky_key: None,
+        }
+    }
+
+    async fn wait_for_health<D>(db: &HealthCheckedProxyDB<D>, id: &str, health: ProxyHealth) {
+        for _ in 0..200 {
+            if db.health(id) == Some(health) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("proxy {id} never became {health}");
+    }
+
+    #[tokio::test]
+    async fn test_health_checked_proxy_db_excludes_unhealthy_and_fails_open() {
+        let db = HealthCheckedProxyDB::new(proxy("1", "127.0.0.1:1"));
+        assert!(db.get_proxy(ctx(), ProxyFilter::default()).await.is_ok());
+        assert_eq!(db.health("1"), Some(ProxyHealth::Unknown));
+
+        let handle = tokio::spawn(
+            db.health_check(|_proxy: Proxy| async {
+                Err::<(), _>(OpaqueError::from_display("dead"))
+            })
+            .with_interval(Duration::from_millis(10))
+            .run(),
+        );
+
+        wait_for_health(&db, "1", ProxyHealth::Unhealthy).await;
+        assert!(db.is_health_check_running());
+        assert!(db.get_proxy(ctx(), ProxyFilter::default()).await.is_err());
+        assert_eq!(
+            db.health_states(),
+            vec![(
+                ProxyID::from(proxy("1", "127.0.0.1:1").id),
+                ProxyHealth::Unhealthy
+            )]
+        );
+
+        handle.abort();
+        let _ = handle.await;
+
+        assert!(!db.is_health_check_running());
+        assert!(db.get_proxy(ctx(), ProxyFilter::default()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_health_checked_proxy_db_fails_open_on_panic() {
+        let db = HealthCheckedProxyDB::new(proxy("1", "127.0.0.1:1"))
+            .with_proxies([proxy("1", "127.0.0.1:1")]);
+
+        let check = db.health_check(|_proxy: Proxy| async {
+            Err::<(), _>(OpaqueError::from_display("dead"))
+        });
+        let handle = tokio::spawn(async move {
+            let mut check = std::pin::pin!(check.with_interval(Duration::from_millis(10)).run());
+            let _ = tokio::time::timeout(Duration::from_millis(50), &mut check).await;
+            panic!("health check died");
+        });
+
+        wait_for_health(&db, "1", ProxyHealth::Unhealthy).await;
+        assert!(handle.await.unwrap_err().is_panic());
+        assert!(!db.is_health_check_running());
+        assert!(db.get_proxy(ctx(), ProxyFilter::default()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_health_checked_proxy_db_with_multiple_health_checks() {
+        let db = HealthCheckedProxyDB::new(proxy("1", "127.0.0.1:1"))
+            .with_proxies([proxy("1", "127.0.0.1:1")]);
+
+        let spawn_check = || {
+            tokio::spawn(
+                db.health_check(|_proxy: Proxy| async {
+                    Err::<(), _>(OpaqueError::from_display("dead"))
+                })
+                .with_interval(Duration::from_millis(10))
+                .run(),
+            )
+        };
+        let first = spawn_check();
+        let second = spawn_check();
+
+        wait_for_health(&db, "1", ProxyHealth::Unhealthy).await;
+        assert!(db.get_proxy(ctx(), ProxyFilter::default()).await.is_err());
+
+        // stopping one health check keeps the other one in charge
+        first.abort();
+        let _ = first.await;
+        assert!(db.is_health_check_running());
+        assert!(db.get_proxy(ctx(), ProxyFilter::default()).await.is_err());
+
+        second.abort();
+        let _ = second.await;
+        assert!(!db.is_health_check_running());
+        assert!(db.get_proxy(ctx(), ProxyFilter::default()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_health_checked_proxy_db_remove_and_prune_proxies() {
+        let db = HealthCheckedProxyDB::new(proxy("1", "127.0.0.1:1"))
+            .with_proxies([proxy("1", "127.0.0.1:1"), proxy("2", "127.0.0.1:2")]);
+        assert_eq!(db.health_states().len(), 2);
+
+        db.remove_proxies(["2"]);
+        assert_eq!(db.health("2"), None);
+        assert_eq!(db.health_states().len(), 1);
+
+        db.register_proxies([proxy("3", "127.0.0.1:3")]);
+        let handle = tokio::spawn(
+            db.health_check(|_proxy: Proxy| async { Ok::<_, OpaqueError>(()) })
+                .with_interval(Duration::from_millis(10))
+                .with_proxy_ttl(Duration::from_millis(50))
+                .run(),
+        );
+
+        // proxy 1 is kept alive by being returned by the inner db
+        for _ in 0..20 {
+            db.get_proxy(ctx(), ProxyFilter::default()).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(db.health("1"), Some(ProxyHealth::Healthy));
+        assert_eq!(db.health("3"), None);
+
+        handle.abort();
+        let _ = handle.await;
+    }
+
+    #[tokio::test]
+    async fn test_tcp_connect_probe() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let alive = proxy("alive", &listener.local_addr().unwrap().to_string());
+
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let dead = proxy("dead", &closed.local_addr().unwrap().to_string());
+        drop(closed);
+
+        assert!(TcpConnectProbe::new().probe(&alive).await.is_ok());
+        assert!(TcpConnectProbe::new().probe(&dead).await.is_err());
+    }
+
+    async fn serve_http_connect_once(
+        response: &'static str,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 512];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (addr, handle)
+    }
+
+    #[tokio::test]
+    async fn test_http_connect_probe() {
+        let probe = HttpConnectProbe::new("example.com:443".parse().unwrap());
+
+        let (addr, handle) =
+            serve_http_connect_once("HTTP/1.1 200 Connection Established\r\n\r\n").await;
+        let authorized = proxy("ok", &format!("http://john:secret@{addr}"));
+        probe.probe(&authorized).await.unwrap();
+        let request = handle.await.unwrap();
+        assert!(request.starts_with("CONNECT example.com:443 HTTP/1.1\r\n"));
+        assert!(request.contains("Proxy-Authorization: Basic am9objpzZWNyZXQ=\r\n"));
+
+        let (addr, handle) = serve_http_connect_once(
+            "HTTP/1.1 407 Proxy Authentication Required\r\nContent-Length: 0\r\n\r\n",
+        )
+        .await;
+        let denied = proxy("denied", &addr);
+        assert!(probe.probe(&denied).await.is_err());
+        let request = handle.await.unwrap();
+        assert!(!request.contains("Proxy-Authorization"));
+    }
+
+    #[tokio::test]
+    async fn test_http_connect_probe_skips_non_plain_http_proxies() {
+        let probe = HttpConnectProbe::new("example.com:443".parse().unwrap());
+
+        // a tls proxy which would accept any connection
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let tls = proxy("tls", &format!("https://{addr}"));
+        let socks5 = proxy("socks5", &format!("socks5://{addr}"));
+
+        for proxy in [&tls, &socks5] {
+            assert!(probe
+                .probe(proxy)
+                .await
+                .unwrap_err()
+                .downcast_ref::<ProxyNotProbedErr>()
+                .is_some());
+        }
+        // no connection was made to the proxy
+        assert!(
+            tokio::time::timeout(Duration::from_millis(20), listener.accept())
+                .await
+                .is_err()
+        );
+
+        let db = HealthCheckedProxyDB::new(proxy("tls", "127.0.0.1:1")).with_proxies([tls]);
+        db.health_check(probe).probe_all().await;
+        assert_eq!(db.health("tls"), Some(ProxyHealth::Unknown));
+        assert!(db
+            .health_states()
+            .iter()
+            .all(|(_, health)| health.is_available()));
+    }
+}
@@ -72,6 +72,14 @@ pub struct Proxy {
     #[cfg_attr(feature = "memory-db", venndb(filter, any))]
     ///  Autonomous System Number (ASN).
     pub asn: Option<Asn>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Relative weight of the proxy, used for weighted random selection
+    /// among the proxies matching a [`ProxyFilter`].
+    ///
+    /// Defaults to `1` when not specified. A proxy with a weight of `0`
+    /// is never randomly selected, but can still be selected by its id.
+    pub weight: Option<u32>,
 }
 
 #[cfg(feature = "memory-db")]
@@ -83,6 +91,15 @@ fn proxydb_insert_validator(proxy: &Proxy) -> bool {
 }
 
 impl Proxy {
+    /// The default weight of a [`Proxy`] which has no weight specified.
+    pub const DEFAULT_WEIGHT: u32 = 1;
+
+    /// Returns the weight of the proxy, used for weighted random selection,
+    /// defaulting to [`Proxy::DEFAULT_WEIGHT`] when not specified.
+    pub fn weight(&self) -> u32 {
+        self.weight.unwrap_or(Self::DEFAULT_WEIGHT)
+    }
+
     /// Check if the proxy is a match for the given[`ProxyContext`] and [`ProxyFilter`].
     pub fn is_match(&self, ctx: &ProxyContext, filter: &ProxyFilter) -> bool {
         if let Some(id) = &filter.id {
@@ -155,9 +172,12 @@ impl Proxy {
             && filter
                 .asn
                 .as_ref()
+                .filter(|a| !a.is_empty())
                 .map(|a| {
-                    let asn = self.asn.as_ref();
-                    a.iter().any(|a| Some(a) == asn)
+                    self.asn
+                        .as_ref()
+                        .map(|asn| a.iter().any(|a| a.is_match(asn)))
+                        .unwrap_or_default()
                 })
                 .unwrap_or(true)
             && filter
@@ -0,0 +1,350 @@
+use super::Proxy;
+use std::path::Path;
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, AsyncReadExt, BufReader, Lines},
+};
+
+#[derive(Debug)]
+/// A JSON Reader that can be used to create a [`Proxy`] database from a JSON file or raw data.
+///
+/// The data is either a JSON array of [`Proxy`] objects,
+/// or newline-delimited JSON (NDJSON) with one [`Proxy`] object per line.
+/// Unknown fields are ignored, such that newer data can be read by older readers.
+pub struct ProxyJsonReader {
+    data: ProxyJsonReaderData,
+}
+
+impl ProxyJsonReader {
+    /// Create a new [`ProxyJsonReader`] from the given JSON or NDJSON file.
+    ///
+    /// A JSON array is read in full, while NDJSON is read line by line.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, ProxyJsonReaderError> {
+        let file = tokio::fs::File::open(path).await?;
+        let mut reader = BufReader::new(file);
+
+        // skip leading whitespace to detect the format,
+        // keeping track of the lines skipped for NDJSON line numbers
+        let mut line = 0;
+        let is_array = loop {
+            let buf = reader.fill_buf().await?;
+            if buf.is_empty() {
+                break false;
+            }
+            let n = buf.iter().take_while(|b| b.is_ascii_whitespace()).count();
+            if n < buf.len() {
+                break buf[n] == b'[';
+            }
+            line += buf.iter().filter(|b| **b == b'\n').count();
+            reader.consume(n);
+        };
+
+        if is_array {
+            let mut data = String::new();
+            reader.read_to_string(&mut data).await?;
+            return Ok(Self::raw(data));
+        }
+
+        Ok(ProxyJsonReader {
+            data: ProxyJsonReaderData::File {
+                lines: reader.lines(),
+                line,
+            },
+        })
+    }
+
+    /// Create a new [`ProxyJsonReader`] from the given JSON or NDJSON data.
+    pub fn raw(data: impl AsRef<str>) -> Self {
+        let data = data.as_ref();
+        let data = if data.trim_start().starts_with('[') {
+            match serde_json::from_str::<Vec<serde_json::Value>>(data) {
+                Ok(records) => ProxyJsonReaderData::Records {
+                    records: records.into_iter(),
+                    record: 0,
+                },
+                Err(err) => ProxyJsonReaderData::Invalid(Some(err)),
+            }
+        } else {
+            ProxyJsonReaderData::Raw {
+                lines: data.lines().rev().map(str::to_owned).collect(),
+                line: 0,
+            }
+        };
+        ProxyJsonReader { data }
+    }
+
+    /// Read the next [`Proxy`] from the JSON data.
+    pub async fn next(&mut self) -> Result<Option<Proxy>, ProxyJsonReaderError> {
+        match &mut self.data {
+            ProxyJsonReaderData::File { lines, line } => loop {
+                let Some(value) = lines.next_line().await? else {
+                    return Ok(None);
+                };
+                *line += 1;
+                if let Some(proxy) = parse_json_line(&value, *line)? {
+                    return Ok(Some(proxy));
+                }
+            },
+            ProxyJsonReaderData::Raw { lines, line } => loop {
+                let Some(value) = lines.pop() else {
+                    return Ok(None);
+                };
+                *line += 1;
+                if let Some(proxy) = parse_json_line(&value, *line)? {
+                    return Ok(Some(proxy));
+                }
+            },
+            ProxyJsonReaderData::Records { records, record } => {
+                let Some(value) = records.next() else {
+                    return Ok(None);
+                };
+                *record += 1;
+                serde_json::from_value(value)
+                    .map(Some)
+                    .map_err(|err| ProxyJsonReaderError {
+                        kind: ProxyJsonReaderErrorKind::InvalidRecord {
+                            record: *record,
+                            err,
+                        },
+                    })
+            }
+            ProxyJsonReaderData::Invalid(err) => match err.take() {
+                Some(err) => Err(ProxyJsonReaderError {
+                    kind: ProxyJsonReaderErrorKind::InvalidJson(err),
+                }),
+                None => Ok(None),
+            },
+        }
+    }
+}
+
+/// Parse a single NDJSON line, skipping blank lines.
+fn parse_json_line(value: &str, line: usize) -> Result<Option<Proxy>, ProxyJsonReaderError> {
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_str(value)
+        .map(Some)
+        .map_err(|err| ProxyJsonReaderError {
+            kind: ProxyJsonReaderErrorKind::InvalidLine { line, err },
+        })
+}
+
+#[derive(Debug)]
+enum ProxyJsonReaderData {
+    File {
+        lines: Lines<BufReader<File>>,
+        line: usize,
+    },
+    Raw {
+        lines: Vec<String>,
+        line: usize,
+    },
+    Records {
+        records: std::vec::IntoIter<serde_json::Value>,
+        record: usize,
+    },
+    Invalid(Option<serde_json::Error>),
+}
+
+#[derive(Debug)]
+/// An error that can occur when reading a Proxy from JSON data.
+pub struct ProxyJsonReaderError {
+    kind: ProxyJsonReaderErrorKind,
+}
+
+impl ProxyJsonReaderError {
+    /// Returns the kind of error that occurred.
+    pub fn kind(&self) -> &ProxyJsonReaderErrorKind {
+        &self.kind
+    }
+}
+
+#[derive(Debug)]
+/// The kind of error that can occur when reading a Proxy from JSON data.
+pub enum ProxyJsonReaderErrorKind {
+    /// An I/O error occurred while reading the JSON data.
+    IoError(std::io::Error),
+    /// The JSON array is invalid, and could not be parsed.
+    InvalidJson(serde_json::Error),
+    /// A record (1-based) of the JSON array is not a valid [`Proxy`].
+    InvalidRecord {
+        /// The number of the record, starting from 1.
+        record: usize,
+        /// The error that occurred while parsing the record.
+        err: serde_json::Error,
+    },
+    /// A line (1-based) of the NDJSON data is not a valid [`Proxy`].
+    InvalidLine {
+        /// The number of the line, starting from 1.
+        line: usize,
+        /// The error that occurred while parsing the line.
+        err: serde_json::Error,
+    },
+}
+
+impl std::fmt::Display for ProxyJsonReaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ProxyJsonReaderErrorKind::IoError(err) => write!(f, "I/O error: {}", err),
+            ProxyJsonReaderErrorKind::InvalidJson(err) => write!(f, "Invalid json: {}", err),
+            ProxyJsonReaderErrorKind::InvalidRecord { record, err } => {
+                write!(f, "Invalid record #{}: {}", record, err)
+            }
+            ProxyJsonReaderErrorKind::InvalidLine { line, err } => {
+                write!(f, "Invalid line #{}: {}", line, err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProxyJsonReaderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ProxyJsonReaderErrorKind::IoError(err) => Some(err),
+            ProxyJsonReaderErrorKind::InvalidJson(err)
+            | ProxyJsonReaderErrorKind::InvalidRecord { err, .. }
+            | ProxyJsonReaderErrorKind::InvalidLine { err, .. } => Some(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for ProxyJsonReaderError {
+    fn from(err: std::io::Error) -> Self {
+        Self {
+            kind: ProxyJsonReaderErrorKind::IoError(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_net::asn::Asn;
+
+    const PROXY_A: &str = r#"{"id":"a","address":"john:secret@proxy.example.com:1080","tcp":true,"udp":true,"http":false,"https":false,"socks5":true,"socks5h":false,"datacenter":true,"residential":false,"mobile":false,"pool_id":"poolA","continent":null,"country":"BE","state":null,"city":null,"carrier":null,"asn":7018}"#;
+    const PROXY_B: &str = r#"{"id":"b","address":"proxy.example.com:8080","tcp":true,"udp":false,"http":true,"https":false,"socks5":false,"socks5h":false,"datacenter":false,"residential":true,"mobile":false,"pool_id":null,"continent":null,"country":null,"state":null,"city":null,"carrier":null,"asn":null,"weight":3,"future_field":{"foo":"bar"}}"#;
+
+    async fn read_all(mut reader: ProxyJsonReader) -> Result<Vec<Proxy>, ProxyJsonReaderError> {
+        let mut proxies = Vec::new();
+        while let Some(proxy) = reader.next().await? {
+            proxies.push(proxy);
+        }
+        Ok(proxies)
+    }
+
+    fn assert_proxies(proxies: &[Proxy]) {
+        assert_eq!(proxies.len(), 2);
+
+        let a = &proxies[0];
+        assert_eq!(a.id, "a");
+        assert_eq!(a.address.to_string(), "john:secret@proxy.example.com:1080");
+        assert!(a.tcp && a.udp && a.socks5 && a.datacenter);
+        assert_eq!(a.pool_id, Some("poola".into()));
+        assert_eq!(a.country, Some("be".into()));
+        assert_eq!(a.asn, Some(Asn::from_static(7018)));
+        assert_eq!(a.weight, None);
+
+        let b = &proxies[1];
+        assert_eq!(b.id, "b");
+        assert!(b.tcp && b.http && b.residential);
+        assert_eq!(b.asn, None);
+        assert_eq!(b.weight, Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_proxy_json_reader_array() {
+        let proxies = read_all(ProxyJsonReader::raw(format!(
+            "\n  [\n{PROXY_A},\n{PROXY_B}\n]\n"
+        )))
+        .await
+        .unwrap();
+        assert_proxies(&proxies);
+
+        let proxies = read_all(ProxyJsonReader::raw("[]")).await.unwrap();
+        assert!(proxies.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_proxy_json_reader_ndjson() {
+        let proxies = read_all(ProxyJsonReader::raw(format!("{PROXY_A}\n\n{PROXY_B}\n")))
+            .await
+            .unwrap();
+        assert_proxies(&proxies);
+
+        let proxies = read_all(ProxyJsonReader::raw("")).await.unwrap();
+        assert!(proxies.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_proxy_json_reader_array_invalid_record() {
+        let mut reader = ProxyJsonReader::raw(format!(r#"[{PROXY_A}, {{"id":"c"}}]"#));
+        assert_eq!(reader.next().await.unwrap().unwrap().id, "a");
+        let err = reader.next().await.unwrap_err();
+        assert!(
+            matches!(
+                err.kind(),
+                ProxyJsonReaderErrorKind::InvalidRecord { record: 2, .. }
+            ),
+            "{err}"
+        );
+        assert!(err.to_string().starts_with("Invalid record #2"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_proxy_json_reader_array_invalid_json() {
+        let mut reader = ProxyJsonReader::raw(format!("[{PROXY_A},"));
+        let err = reader.next().await.unwrap_err();
+        assert!(
+            matches!(err.kind(), ProxyJsonReaderErrorKind::InvalidJson(_)),
+            "{err}"
+        );
+        assert!(reader.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_proxy_json_reader_ndjson_invalid_line() {
+        let mut reader = ProxyJsonReader::raw(format!("{PROXY_A}\n\n{{\"id\":\"c\"}}\n{PROXY_B}"));
+        assert_eq!(reader.next().await.unwrap().unwrap().id, "a");
+        let err = reader.next().await.unwrap_err();
+        assert!(
+            matches!(
+                err.kind(),
+                ProxyJsonReaderErrorKind::InvalidLine { line: 3, .. }
+            ),
+            "{err}"
+        );
+        assert_eq!(reader.next().await.unwrap().unwrap().id, "b");
+    }
+
+    #[tokio::test]
+    async fn test_proxy_json_reader_open() {
+        let dir = std::env::temp_dir();
+
+        let path = dir.join(format!("rama-proxy-json-{}.json", std::process::id()));
+        tokio::fs::write(&path, format!("\n[{PROXY_A},{PROXY_B}]"))
+            .await
+            .unwrap();
+        let proxies = read_all(ProxyJsonReader::open(&path).await.unwrap())
+            .await
+            .unwrap();
+        assert_proxies(&proxies);
+
+        let path = dir.join(format!("rama-proxy-json-{}.ndjson", std::process::id()));
+        tokio::fs::write(&path, format!("\n\n{PROXY_A}\nfoo\n{PROXY_B}\n"))
+            .await
+            .unwrap();
+        let mut reader = ProxyJsonReader::open(&path).await.unwrap();
+        assert_eq!(reader.next().await.unwrap().unwrap().id, "a");
+        let err = reader.next().await.unwrap_err();
+        assert!(
+            matches!(
+                err.kind(),
+                ProxyJsonReaderErrorKind::InvalidLine { line: 4, .. }
+            ),
+            "{err}"
+        );
+        assert_eq!(reader.next().await.unwrap().unwrap().id, "b");
+        assert!(reader.next().await.unwrap().is_none());
+    }
+}
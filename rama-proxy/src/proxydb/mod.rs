@@ -1,5 +1,4 @@
 use rama_core::error::{BoxError, ErrorContext, OpaqueError};
-use rama_net::asn::Asn;
 use rama_utils::str::NonEmptyString;
 use serde::{Deserialize, Serialize};
 use std::{fmt, future::Future};
@@ -24,12 +23,35 @@ mod csv;
 #[doc(inline)]
 pub use csv::{ProxyCsvRowReader, ProxyCsvRowReaderError, ProxyCsvRowReaderErrorKind};
 
+#[cfg(feature = "json")]
+mod json;
+
+#[cfg(feature = "json")]
+#[doc(inline)]
+pub use json::{ProxyJsonReader, ProxyJsonReaderError, ProxyJsonReaderErrorKind};
+
+#[cfg(feature = "health-check")]
+mod health;
+
+#[cfg(feature = "health-check")]
+#[doc(inline)]
+pub use health::{
+    HealthCheckedProxyDB, HttpConnectProbe, ProxyHealth, ProxyHealthCheck, ProxyNotProbedErr,
+    ProxyProbe, TcpConnectProbe,
+};
+
 pub(super) mod layer;
 
+pub(super) mod compat;
+
 mod str;
 #[doc(inline)]
 pub use str::StringFilter;
 
+mod asn;
+#[doc(inline)]
+pub use asn::AsnMatch;
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 /// `ID` of the selected proxy. To be inserted into the `Context`,
 /// only if that proxy is selected.
@@ -60,6 +82,42 @@ impl From<NonEmptyString> for ProxyID {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Key of a (scraping) session, used for sticky proxy selection.
+///
+/// Inserted into the `Context` (e.g. by the [`ProxyFilterUsernameParser`]
+/// from a `session` username label), such that a [`ProxyDBService`]
+/// in sticky mode consistently selects the same proxy for the same session.
+///
+/// [`ProxyFilterUsernameParser`]: crate::ProxyFilterUsernameParser
+/// [`ProxyDBService`]: crate::ProxyDBService
+pub struct ProxySessionKey(NonEmptyString);
+
+impl ProxySessionKey {
+    /// View this [`ProxySessionKey`] as a `str`.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl AsRef<str> for ProxySessionKey {
+    fn as_ref(&self) -> &str {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Display for ProxySessionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<NonEmptyString> for ProxySessionKey {
+    fn from(value: NonEmptyString) -> Self {
+        Self(value)
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 /// Filter to select a specific kind of proxy.
 ///
@@ -115,8 +173,11 @@ pub struct ProxyFilter {
     /// The mobile carrier desired.
     pub carrier: Option<Vec<StringFilter>>,
 
-    ///  Autonomous System Number (ASN).
-    pub asn: Option<Vec<Asn>>,
+    /// Autonomous System Number (ASN), or an inclusive range of them.
+    ///
+    /// An empty list matches any proxy, the same as `None`,
+    /// while a proxy without an ASN never matches a non-empty list.
+    pub asn: Option<Vec<AsnMatch>>,
 }
 
 /// The trait to implement to provide a proxy database to other facilities,
@@ -325,11 +386,40 @@ impl ProxyDB for Proxy {
     }
 }
 
+#[cfg(all(test, any(feature = "memory-db", feature = "health-check")))]
+/// Test fixture: a datacenter http proxy with the given id,
+/// to be adapted to the test at hand using struct update syntax.
+fn test_proxy(id: &'static str) -> Proxy {
+    Proxy {
+        id: NonEmptyString::from_static(id),
+        address: rama_net::address::ProxyAddress::try_from("example.com").unwrap(),
+        tcp: true,
+        udp: false,
+        http: true,
+        https: false,
+        socks5: false,
+        socks5h: false,
+        datacenter: true,
+        residential: false,
+        mobile: false,
+        pool_id: None,
+        continent: None,
+        country: None,
+        state: None,
+        city: None,
+        carrier: None,
+        asn: None,
+        weight: None,
+    }
+}
+
 #[cfg(feature = "memory-db")]
 mod memdb {
     use super::*;
     use crate::proxydb::internal::ProxyDBErrorKind;
     use rama_net::transport::TransportProtocol;
+    use rama_utils::rng::{thread_rng, Rng};
+    use std::hash::{DefaultHasher, Hash, Hasher};
 
     /// A fast in-memory ProxyDatabase that is the default choice for Rama.
     #[derive(Debug)]
@@ -404,8 +494,14 @@ mod memdb {
             for carrier in filter.carrier.into_iter().flatten() {
                 query.carrier(carrier);
             }
-            for asn in filter.asn.into_iter().flatten() {
-                query.asn(asn);
+            // ranges cannot be queried using the db indices,
+            // these are filtered on the query results instead
+            if let Some(asn) = filter.asn.filter(|asn| !has_asn_range(asn)) {
+                for asn in asn {
+                    if let AsnMatch::Single(asn) = asn {
+                        query.asn(asn);
+                    }
+                }
             }
 
             if let Some(value) = filter.datacenter {
@@ -458,12 +554,29 @@ mod memdb {
                     }
                 },
                 None => {
+                    let asn_range_filter = filter.asn.clone().filter(|asn| has_asn_range(asn));
+                    let sticky_key = ctx.sticky_key.clone();
                     let query = self.query_from_filter(ctx, filter.clone());
                     match query
                         .execute()
-                        .and_then(|result| result.filter(|proxy| predicate.execute(proxy)))
-                        .map(|result| result.any())
-                    {
+                        .and_then(|result| {
+                            result.filter(|proxy| {
+                                asn_range_filter
+                                    .as_deref()
+                                    .map(|asn| is_asn_range_match(asn, proxy))
+                                    .unwrap_or(true)
+                                    && predicate.execute(proxy)
+                            })
+                        })
+                        .and_then(|result| {
+                            let proxies: Vec<_> = result.iter().collect();
+                            match &sticky_key {
+                                Some(key) => select_sticky(key, &proxies),
+                                // weighted selection using the rama thread rng rather than `result.any()`,
+                                // such that the selection can be made deterministic (e.g. in tests)
+                                None => select_weighted(&mut thread_rng(), &proxies),
+                            }
+                        }) {
                         None => Err(MemoryProxyDBQueryError::not_found()),
                         Some(proxy) => Ok(proxy.clone()),
                     }
@@ -472,6 +585,67 @@ mod memdb {
         }
     }
 
+    /// Select a random proxy, where the chance of a proxy to be selected
+    /// is proportional to its [`Proxy::weight`].
+    ///
+    /// Returns `None` if there are no proxies or all of them have a weight of `0`.
+    fn select_weighted<'a>(rng: &mut impl Rng, proxies: &[&'a Proxy]) -> Option<&'a Proxy> {
+        let total_weight: u64 = proxies.iter().map(|proxy| u64::from(proxy.weight())).sum();
+        if total_weight == 0 {
+            return None;
+        }
+
+        let mut target = rng.next_range(0..total_weight);
+        proxies.iter().copied().find(|proxy| {
+            let weight = u64::from(proxy.weight());
+            if target < weight {
+                true
+            } else {
+                target -= weight;
+                false
+            }
+        })
+    }
+
+    /// Select the proxy for the given session key using (weighted) rendezvous hashing.
+    ///
+    /// The same key maps to the same proxy for as long as that proxy remains a candidate.
+    /// Once it is no longer a candidate, only the keys mapped to that proxy
+    /// fall back to one of the remaining candidates.
+    fn select_sticky<'a>(key: &ProxySessionKey, proxies: &[&'a Proxy]) -> Option<&'a Proxy> {
+        proxies
+            .iter()
+            .copied()
+            .filter(|proxy| proxy.weight() > 0)
+            .map(|proxy| (rendezvous_score(key, proxy), proxy))
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, proxy)| proxy)
+    }
+
+    fn rendezvous_score(key: &ProxySessionKey, proxy: &Proxy) -> f64 {
+        // `DefaultHasher::new` is guaranteed to always create the same hasher
+        let mut hasher = DefaultHasher::new();
+        key.as_str().hash(&mut hasher);
+        proxy.id.as_str().hash(&mut hasher);
+        // uniform value within (0, 1)
+        let value = ((hasher.finish() >> 11) as f64 + 0.5) / (1u64 << 53) as f64;
+        f64::from(proxy.weight()) / -value.ln()
+    }
+
+    fn has_asn_range(asn: &[AsnMatch]) -> bool {
+        asn.iter().any(|asn| matches!(asn, AsnMatch::Range { .. }))
+    }
+
+    /// Post-query filter for ASN filters containing ranges,
+    /// consistent with the db indices, where an unspecified ASN matches any filter.
+    fn is_asn_range_match(asn: &[AsnMatch], proxy: &Proxy) -> bool {
+        proxy
+            .asn
+            .as_ref()
+            .map(|proxy_asn| proxy_asn.is_any() || asn.iter().any(|asn| asn.is_match(proxy_asn)))
+            .unwrap_or_default()
+    }
+
     /// The error type that can be returned by [`MemoryProxyDB`] when some of the proxies
     /// could not be inserted due to a proxy that had a duplicate key or was invalid for some other reason.
     #[derive(Debug)]
@@ -594,6 +768,7 @@ mod memdb {
         use super::*;
         use itertools::Itertools;
         use rama_net::address::ProxyAddress;
+        use rama_net::asn::Asn;
         use rama_utils::str::NonEmptyString;
         use std::str::FromStr;
 
@@ -617,9 +792,33 @@ mod memdb {
         fn h2_proxy_context() -> ProxyContext {
             ProxyContext {
                 protocol: TransportProtocol::Tcp,
+                sticky_key: None,
             }
         }
 
+        #[tokio::test]
+        async fn test_memproxydb_get_proxy_seeded_thread_rng() {
+            let db = memproxydb().await;
+
+            async fn select(db: &MemoryProxyDB) -> Vec<String> {
+                let _guard = rama_utils::rng::seed_thread_rng(42);
+                let mut ids = Vec::new();
+                for _ in 0..16 {
+                    let proxy = db
+                        .get_proxy(h2_proxy_context(), ProxyFilter::default())
+                        .await
+                        .unwrap();
+                    ids.push(proxy.id.to_string());
+                }
+                ids
+            }
+
+            let first = select(&db).await;
+            let second = select(&db).await;
+            assert_eq!(first, second);
+            assert!(first.iter().unique().count() > 1, "ids: {first:?}");
+        }
+
         #[tokio::test]
         async fn test_memproxydb_get_proxy_by_id_found() {
             let db = memproxydb().await;
@@ -715,7 +914,7 @@ mod memdb {
                 },
                 ProxyFilter {
                     id: Some(NonEmptyString::from_static("292096733")),
-                    asn: Some(vec![Asn::from_static(1)]),
+                    asn: Some(vec![Asn::from_static(1).into()]),
                     ..Default::default()
                 },
             ];
@@ -728,6 +927,7 @@ mod memdb {
         fn h3_proxy_context() -> ProxyContext {
             ProxyContext {
                 protocol: TransportProtocol::Udp,
+                sticky_key: None,
             }
         }
 
@@ -842,7 +1042,7 @@ mod memdb {
             let ctx = h2_proxy_context();
             let filter = ProxyFilter {
                 // this will also work for proxies that have 'any' ASN
-                asn: Some(vec![Asn::from_static(42)]),
+                asn: Some(vec![Asn::from_static(42).into()]),
                 ..Default::default()
             };
             let mut found_ids = Vec::new();
@@ -860,6 +1060,66 @@ mod memdb {
             );
         }
 
+        #[tokio::test]
+        async fn test_memorydb_get_asn_range_proxies() {
+            fn proxy(id: &'static str, asn: Option<Asn>) -> Proxy {
+                Proxy {
+                    asn,
+                    ..test_proxy(id)
+                }
+            }
+
+            let proxies = [
+                proxy("1", Some(Asn::from_static(99))),
+                proxy("2", Some(Asn::from_static(100))),
+                proxy("3", Some(Asn::from_static(150))),
+                proxy("4", Some(Asn::from_static(200))),
+                proxy("5", Some(Asn::from_static(201))),
+                proxy("6", Some(Asn::from_static(7018))),
+                proxy("7", None),
+                proxy("8", Some(Asn::unspecified())),
+            ];
+            let db = MemoryProxyDB::try_from_iter(proxies.clone()).unwrap();
+            let ctx = h2_proxy_context();
+
+            // an unspecified ASN is considered to be "any" ASN by the db query,
+            // but does not match a non-empty asn filter when validating a proxy selected by id
+            for (asn, expected_query_ids, expected_match_ids) in [
+                (None, "1,2,3,4,5,6,7,8", "1,2,3,4,5,6,7,8"),
+                (Some(vec![]), "1,2,3,4,5,6,7,8", "1,2,3,4,5,6,7,8"),
+                (Some(vec!["100..200"]), "2,3,4,8", "2,3,4"),
+                (Some(vec!["99..99"]), "1,8", "1"),
+                (Some(vec!["100..200", "7018"]), "2,3,4,6,8", "2,3,4,6"),
+                (Some(vec!["99", "201..300"]), "1,5,8", "1,5"),
+                (Some(vec!["7018"]), "6,8", "6"),
+            ] {
+                let filter = ProxyFilter {
+                    asn: asn.map(|asn| asn.into_iter().map(|s| s.parse().unwrap()).collect()),
+                    ..Default::default()
+                };
+
+                let mut found_ids = Vec::new();
+                for _ in 0..500 {
+                    let proxy = db.get_proxy(ctx.clone(), filter.clone()).await.unwrap();
+                    if !found_ids.contains(&proxy.id) {
+                        found_ids.push(proxy.id);
+                    }
+                }
+                assert_eq!(
+                    found_ids.iter().sorted().join(","),
+                    expected_query_ids,
+                    "filter: {filter:?}"
+                );
+
+                let matched_ids = proxies
+                    .iter()
+                    .filter(|proxy| proxy.is_match(&ctx, &filter))
+                    .map(|proxy| proxy.id.as_str())
+                    .join(",");
+                assert_eq!(matched_ids, expected_match_ids, "filter: {filter:?}");
+            }
+        }
+
         #[tokio::test]
         async fn test_memorydb_get_h3_capable_mobile_residential_be_asterix_proxies() {
             let db = memproxydb().await;
@@ -981,6 +1241,7 @@ mod memdb {
                 city: Some("*".into()),
                 carrier: Some("*".into()),
                 asn: Some(Asn::unspecified()),
+                weight: None,
             }])
             .unwrap();
 
@@ -1073,6 +1334,7 @@ mod memdb {
                 city: Some("NY".into()),
                 carrier: Some("AT&T".into()),
                 asn: Some(Asn::from_static(7018)),
+                weight: None,
             }])
             .unwrap();
 
@@ -1104,7 +1366,7 @@ mod memdb {
                     ..Default::default()
                 },
                 ProxyFilter {
-                    asn: Some(vec![Asn::unspecified()]),
+                    asn: Some(vec![Asn::unspecified().into()]),
                     ..Default::default()
                 },
                 ProxyFilter {
@@ -1114,7 +1376,7 @@ mod memdb {
                     state: Some(vec![StringFilter::new("*")]),
                     city: Some(vec![StringFilter::new("*")]),
                     carrier: Some(vec![StringFilter::new("*")]),
-                    asn: Some(vec![Asn::unspecified()]),
+                    asn: Some(vec![Asn::unspecified().into()]),
                     ..Default::default()
                 },
             ] {
@@ -1158,6 +1420,7 @@ mod memdb {
                     city: Some("NY".into()),
                     carrier: Some("AT&T".into()),
                     asn: Some(Asn::from_static(7018)),
+                    weight: None,
                 },
                 Proxy {
                     id: NonEmptyString::from_static("2"),
@@ -1178,6 +1441,7 @@ mod memdb {
                     city: Some("NY".into()),
                     carrier: Some("AT&T".into()),
                     asn: Some(Asn::from_static(7018)),
+                    weight: None,
                 },
                 Proxy {
                     id: NonEmptyString::from_static("3"),
@@ -1198,6 +1462,7 @@ mod memdb {
                     city: Some("NY".into()),
                     carrier: Some("AT&T".into()),
                     asn: Some(Asn::from_static(7018)),
+                    weight: None,
                 },
                 Proxy {
                     id: NonEmptyString::from_static("4"),
@@ -1218,6 +1483,7 @@ mod memdb {
                     city: Some("NY".into()),
                     carrier: Some("AT&T".into()),
                     asn: Some(Asn::from_static(7018)),
+                    weight: None,
                 },
             ])
             .unwrap();
@@ -1243,6 +1509,93 @@ mod memdb {
             assert!(seen_4);
         }
 
+        fn weighted_proxy(id: &'static str, weight: Option<u32>) -> Proxy {
+            Proxy {
+                weight,
+                ..test_proxy(id)
+            }
+        }
+
+        #[test]
+        fn test_select_weighted() {
+            let proxies = [
+                weighted_proxy("a", None),
+                weighted_proxy("b", Some(3)),
+                weighted_proxy("c", Some(0)),
+            ];
+            let proxies: Vec<_> = proxies.iter().collect();
+
+            let mut rng = rama_utils::rng::HasherRng::seeded(42);
+            let mut counts = [0usize; 3];
+            for _ in 0..4000 {
+                let proxy = select_weighted(&mut rng, &proxies).unwrap();
+                counts[proxies.iter().position(|p| p.id == proxy.id).unwrap()] += 1;
+            }
+            assert_eq!(counts[2], 0, "counts: {counts:?}");
+            assert!(
+                (800..1200).contains(&counts[0]) && (2800..3200).contains(&counts[1]),
+                "counts: {counts:?}"
+            );
+
+            // same seed, same selection
+            let select = |seed| {
+                let mut rng = rama_utils::rng::HasherRng::seeded(seed);
+                (0..16)
+                    .map(|_| select_weighted(&mut rng, &proxies).unwrap().id.to_string())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(select(1), select(1));
+        }
+
+        #[test]
+        fn test_select_weighted_none() {
+            let mut rng = rama_utils::rng::HasherRng::seeded(42);
+            assert!(select_weighted(&mut rng, &[]).is_none());
+
+            let proxies = [weighted_proxy("a", Some(0)), weighted_proxy("b", Some(0))];
+            let proxies: Vec<_> = proxies.iter().collect();
+            assert!(select_weighted(&mut rng, &proxies).is_none());
+        }
+
+        #[tokio::test]
+        async fn test_memproxydb_get_proxy_weighted() {
+            let db = MemoryProxyDB::try_from_iter([
+                weighted_proxy("a", Some(0)),
+                weighted_proxy("b", None),
+            ])
+            .unwrap();
+            let ctx = h2_proxy_context();
+
+            // a proxy with a weight of 0 is never randomly selected
+            for _ in 0..16 {
+                let proxy = db
+                    .get_proxy(ctx.clone(), ProxyFilter::default())
+                    .await
+                    .unwrap();
+                assert_eq!(proxy.id, "b");
+            }
+
+            // ... but can still be selected by id
+            let proxy = db
+                .get_proxy(
+                    ctx.clone(),
+                    ProxyFilter {
+                        id: Some(NonEmptyString::from_static("a")),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+            assert_eq!(proxy.id, "a");
+
+            // no candidate with a non-zero weight means no proxy found
+            let err = db
+                .get_proxy_if(ctx, ProxyFilter::default(), |proxy: &Proxy| proxy.id == "a")
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), MemoryProxyDBQueryErrorKind::NotFound);
+        }
+
         #[tokio::test]
         async fn test_deserialize_url_proxy_filter() {
             for (input, expected_output) in [
@@ -1295,14 +1648,24 @@ mod memdb {
                         state: Some(vec![StringFilter::new("ny")]),
                         city: Some(vec![StringFilter::new("buffalo")]),
                         carrier: Some(vec![StringFilter::new("at&t")]),
-                        asn: Some(vec![Asn::from_static(7018)]),
+                        asn: Some(vec![Asn::from_static(7018).into()]),
                         ..Default::default()
                     },
                 ),
                 (
                     "asn=1&asn=2",
                     ProxyFilter {
-                        asn: Some(vec![Asn::from_static(1), Asn::from_static(2)]),
+                        asn: Some(vec![Asn::from_static(1).into(), Asn::from_static(2).into()]),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "asn=1&asn=100..200",
+                    ProxyFilter {
+                        asn: Some(vec![
+                            Asn::from_static(1).into(),
+                            AsnMatch::range(Asn::from_static(100), Asn::from_static(200)),
+                        ]),
                         ..Default::default()
                     },
                 ),
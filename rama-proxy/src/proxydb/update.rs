@@ -131,6 +131,7 @@ mod tests {
             .get_proxy(
                 ProxyContext {
                     protocol: TransportProtocol::Tcp,
+                    sticky_key: None,
                 },
                 ProxyFilter::default(),
             )
@@ -146,6 +147,7 @@ mod tests {
             .get_proxy(
                 ProxyContext {
                     protocol: TransportProtocol::Tcp,
+                    sticky_key: None,
                 },
                 ProxyFilter::default(),
             )
@@ -171,6 +173,7 @@ mod tests {
             city: Some("city".into()),
             carrier: Some("carrier".into()),
             asn: Some(Asn::from_static(1)),
+            weight: None,
         });
 
         assert_eq!(
@@ -179,6 +182,7 @@ mod tests {
                 .get_proxy(
                     ProxyContext {
                         protocol: TransportProtocol::Tcp,
+                        sticky_key: None,
                     },
                     ProxyFilter::default(),
                 )
@@ -191,6 +195,7 @@ mod tests {
             .get_proxy(
                 ProxyContext {
                     protocol: TransportProtocol::Udp,
+                    sticky_key: None,
                 },
                 ProxyFilter::default(),
             )
@@ -203,6 +208,7 @@ mod tests {
                 .get_proxy(
                     ProxyContext {
                         protocol: TransportProtocol::Tcp,
+                        sticky_key: None,
                     },
                     ProxyFilter::default(),
                 )
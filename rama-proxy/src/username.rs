@@ -1,21 +1,26 @@
-use super::ProxyFilter;
+use super::{ProxyFilter, ProxySessionKey};
 use rama_core::{
     context::Extensions,
     error::{error, OpaqueError},
     username::{UsernameLabelParser, UsernameLabelState, UsernameLabelWriter},
 };
 use rama_utils::macros::match_ignore_ascii_case_str;
+use rama_utils::str::NonEmptyString;
 
 #[derive(Debug, Clone, Default)]
 #[non_exhaustive]
 /// A parser which parses [`ProxyFilter`]s from username labels
 /// and adds it to the [`Context`]'s [`Extensions`].
 ///
+/// A `session` label is parsed as a [`ProxySessionKey`] instead,
+/// to be used for sticky proxy selection.
+///
 /// [`Context`]: rama_core::Context
 /// [`Extensions`]: rama_core::context::Extensions
 pub struct ProxyFilterUsernameParser {
     key: Option<ProxyFilterKey>,
     proxy_filter: ProxyFilter,
+    session: Option<ProxySessionKey>,
 }
 
 #[derive(Debug, Clone)]
@@ -28,6 +33,7 @@ enum ProxyFilterKey {
     City,
     Carrier,
     Asn,
+    Session,
 }
 
 impl ProxyFilterUsernameParser {
@@ -106,11 +112,20 @@ impl UsernameLabelParser for ProxyFilterUsernameParser {
                         None => Some(vec![label.into()]),
                     }
                 }
+                ProxyFilterKey::Session => {
+                    self.session = Some(match NonEmptyString::try_from(label) {
+                        Ok(session) => ProxySessionKey::from(session),
+                        Err(err) => {
+                            tracing::trace!(err = %err, "abort username label parsing: invalid session label");
+                            return UsernameLabelState::Abort;
+                        }
+                    })
+                }
                 ProxyFilterKey::Asn => {
                     let asn = match label.try_into() {
                         Ok(asn) => asn,
                         Err(err) => {
-                            tracing::trace!(err = %err, "failed to parse asn username label; abort username parsing");
+                            tracing::trace!(err = %err, "failed to parse asn (range) username label; abort username parsing");
                             return UsernameLabelState::Abort;
                         }
                     };
@@ -144,6 +159,7 @@ impl UsernameLabelParser for ProxyFilterUsernameParser {
                         "city" => self.key = Some(ProxyFilterKey::City),
                         "carrier" => self.key = Some(ProxyFilterKey::Carrier),
                         "asn" => self.key = Some(ProxyFilterKey::Asn),
+                        "session" => self.key = Some(ProxyFilterKey::Session),
                         _ => return UsernameLabelState::Ignored,
                     }
                 }
@@ -165,6 +181,9 @@ impl UsernameLabelParser for ProxyFilterUsernameParser {
         if self.proxy_filter != ProxyFilter::default() {
             ext.insert(self.proxy_filter);
         }
+        if let Some(session) = self.session {
+            ext.insert(session);
+        }
         Ok(())
     }
 }
@@ -248,7 +267,7 @@ impl<const SEPARATOR: char> UsernameLabelWriter<SEPARATOR> for ProxyFilter {
         if let Some(asn_vec) = &self.asn {
             for asn in asn_vec {
                 composer.write_label("asn")?;
-                composer.write_label(asn.as_u32().to_string())?;
+                composer.write_label(asn.to_string())?;
             }
         }
 
@@ -259,7 +278,7 @@ impl<const SEPARATOR: char> UsernameLabelWriter<SEPARATOR> for ProxyFilter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::StringFilter;
+    use crate::{AsnMatch, StringFilter};
     use rama_core::username::{compose_username, parse_username};
     use rama_net::asn::Asn;
     use rama_utils::str::NonEmptyString;
@@ -490,7 +509,18 @@ mod tests {
                     country: Some(vec![StringFilter::from("us")]),
                     state: Some(vec![StringFilter::from("ny")]),
                     city: Some(vec![StringFilter::from("ny")]),
-                    asn: Some(vec![Asn::from_static(7018)]),
+                    asn: Some(vec![Asn::from_static(7018).into()]),
+                    ..Default::default()
+                }),
+            ),
+            (
+                "john-asn-7018-asn-1000..2000",
+                String::from("john"),
+                Some(ProxyFilter {
+                    asn: Some(vec![
+                        Asn::from_static(7018).into(),
+                        AsnMatch::range(Asn::from_static(1000), Asn::from_static(2000)),
+                    ]),
                     ..Default::default()
                 }),
             ),
@@ -548,6 +578,8 @@ mod tests {
             "john-foo-country",
             "john-country",
             "john-id-", // empty id is invalid
+            "john-session",
+            "john-session-", // empty session is invalid
         ] {
             let mut ext = Extensions::default();
 
@@ -561,6 +593,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_username_session() {
+        let mut ext = Extensions::default();
+        let parser = ProxyFilterUsernameParser::default();
+        let username = parse_username(&mut ext, parser, "john-session-abc123").unwrap();
+        assert_eq!(username, "john");
+        assert_eq!(ext.get::<ProxySessionKey>().unwrap().as_str(), "abc123");
+        assert!(ext.get::<ProxyFilter>().is_none());
+
+        let mut ext = Extensions::default();
+        let parser = ProxyFilterUsernameParser::default();
+        let username = parse_username(&mut ext, parser, "john-country-us-session-42").unwrap();
+        assert_eq!(username, "john");
+        assert_eq!(ext.get::<ProxySessionKey>().unwrap().as_str(), "42");
+        assert_eq!(
+            ext.get::<ProxyFilter>().unwrap(),
+            &ProxyFilter {
+                country: Some(vec![StringFilter::from("us")]),
+                ..Default::default()
+            }
+        );
+    }
+
     #[test]
     fn test_username_negation_key_failures() {
         for username in [
@@ -630,7 +685,11 @@ mod tests {
                     StringFilter::from("at&t"),
                     StringFilter::from("orange"),
                 ]),
-                asn: Some(vec![Asn::from_static(7018), Asn::from_static(1)]),
+                asn: Some(vec![
+                    Asn::from_static(7018).into(),
+                    Asn::from_static(1).into(),
+                    AsnMatch::range(Asn::from_static(100), Asn::from_static(200)),
+                ]),
             },
         ];
 
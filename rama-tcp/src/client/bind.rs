@@ -0,0 +1,225 @@
+use std::{
+    io,
+    net::{IpAddr, SocketAddr},
+};
+use tokio::net::{TcpSocket, TcpStream};
+
+use super::TcpStreamConnector;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The local (source) IP address to bind egress TCP connections to.
+///
+/// Insert it in the [`Context`] to bind the connections established
+/// by a `BindAddressConnectorFactory` to this address,
+/// e.g. to select the egress address per authenticated user on a multi-homed host.
+///
+/// [`Context`]: rama_core::Context
+pub struct BindAddress(IpAddr);
+
+impl BindAddress {
+    /// Create a new [`BindAddress`] for the given [`IpAddr`].
+    pub const fn new(ip: IpAddr) -> Self {
+        Self(ip)
+    }
+
+    /// The [`IpAddr`] to bind to.
+    pub const fn ip(&self) -> IpAddr {
+        self.0
+    }
+}
+
+impl From<IpAddr> for BindAddress {
+    fn from(ip: IpAddr) -> Self {
+        Self(ip)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A [`TcpStreamConnector`] which binds the local socket
+/// to the configured [`BindAddress`] (using an ephemeral port) prior to connecting.
+///
+/// Connecting to an address of another IP family than the [`BindAddress`],
+/// or binding to an address not available on this host, fails the connection attempt.
+pub struct BindTcpStreamConnector {
+    bind: Option<BindAddress>,
+}
+
+impl BindTcpStreamConnector {
+    /// Create a new [`BindTcpStreamConnector`] binding to the given [`BindAddress`].
+    pub fn new(bind: BindAddress) -> Self {
+        Self { bind: Some(bind) }
+    }
+
+    /// Create a new [`BindTcpStreamConnector`] which does not bind,
+    /// leaving the selection of the local address to the OS.
+    pub fn unbound() -> Self {
+        Self { bind: None }
+    }
+
+    /// The [`BindAddress`] used by this [`BindTcpStreamConnector`], if any.
+    pub fn bind_address(&self) -> Option<BindAddress> {
+        self.bind
+    }
+}
+
+impl TcpStreamConnector for BindTcpStreamConnector {
+    type Error = io::Error;
+
+    async fn connect(&self, addr: SocketAddr) -> Result<TcpStream, Self::Error> {
+        let Some(bind) = self.bind else {
+            return TcpStream::connect(addr).await;
+        };
+
+        let socket = match (bind.ip(), addr) {
+            (IpAddr::V4(_), SocketAddr::V4(_)) => TcpSocket::new_v4()?,
+            (IpAddr::V6(_), SocketAddr::V6(_)) => TcpSocket::new_v6()?,
+            (ip, addr) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("bind address {ip} cannot be used to connect to {addr}"),
+                ));
+            }
+        };
+        socket.bind(SocketAddr::new(bind.ip(), 0))?;
+        socket.connect(addr).await
+    }
+}
+
+#[cfg(feature = "http")]
+mod factory {
+    use super::{BindAddress, BindTcpStreamConnector};
+    use crate::client::service::{CreatedTcpStreamConnector, TcpStreamConnectorFactory};
+    use rama_core::Context;
+    use std::{convert::Infallible, future::Future};
+
+    #[derive(Debug, Clone, Default)]
+    /// A [`TcpStreamConnectorFactory`] which creates a [`BindTcpStreamConnector`]
+    /// bound to the [`BindAddress`] found in the [`Context`].
+    ///
+    /// The fallback [`BindAddress`] of the factory is used
+    /// in case no [`BindAddress`] is found in the [`Context`],
+    /// and no binding happens at all in case neither is defined.
+    pub struct BindAddressConnectorFactory {
+        fallback: Option<BindAddress>,
+    }
+
+    impl BindAddressConnectorFactory {
+        /// Create a new [`BindAddressConnectorFactory`].
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Set the [`BindAddress`] used when none is found in the [`Context`].
+        pub fn with_fallback(mut self, bind: BindAddress) -> Self {
+            self.fallback = Some(bind);
+            self
+        }
+
+        /// Set the [`BindAddress`] used when none is found in the [`Context`].
+        pub fn set_fallback(&mut self, bind: BindAddress) -> &mut Self {
+            self.fallback = Some(bind);
+            self
+        }
+    }
+
+    impl<State> TcpStreamConnectorFactory<State> for BindAddressConnectorFactory
+    where
+        State: Send + Sync + 'static,
+    {
+        type Connector = BindTcpStreamConnector;
+        type Error = Infallible;
+
+        fn make_connector(
+            &self,
+            ctx: Context<State>,
+        ) -> impl Future<
+            Output = Result<CreatedTcpStreamConnector<State, Self::Connector>, Self::Error>,
+        > + Send
+               + '_ {
+            let bind = ctx.get::<BindAddress>().copied().or(self.fallback);
+            std::future::ready(Ok(CreatedTcpStreamConnector {
+                ctx,
+                connector: BindTcpStreamConnector { bind },
+            }))
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+pub use factory::BindAddressConnectorFactory;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use tokio::net::TcpListener;
+
+    // only linux routes the entire 127.0.0.0/8 range to the loopback interface,
+    // e.g. on macOS only 127.0.0.1 is available by default
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_bind_tcp_stream_connector() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let bind_ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2));
+        let connector = BindTcpStreamConnector::new(BindAddress::new(bind_ip));
+
+        let stream = connector.connect(addr).await.unwrap();
+        assert_eq!(stream.local_addr().unwrap().ip(), bind_ip);
+
+        let (_, peer_addr) = listener.accept().await.unwrap();
+        assert_eq!(peer_addr.ip(), bind_ip);
+    }
+
+    #[tokio::test]
+    async fn test_bind_tcp_stream_connector_invalid_bind_address() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // not an address of this host
+        let connector =
+            BindTcpStreamConnector::new(BindAddress::new(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))));
+        assert!(connector.connect(addr).await.is_err());
+
+        // ip family mismatch
+        let connector = BindTcpStreamConnector::new(BindAddress::new("::1".parse().unwrap()));
+        let err = connector.connect(addr).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    // see `test_bind_tcp_stream_connector` on why this test is linux only
+    #[cfg(all(feature = "http", target_os = "linux"))]
+    #[tokio::test]
+    async fn test_bind_address_connector_factory_uses_context() {
+        use crate::client::{service::TcpConnector, Request};
+        use rama_core::{Context, Service};
+        use rama_net::client::EstablishedClientConnection;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let connector =
+            TcpConnector::new().with_connector_factory(BindAddressConnectorFactory::new());
+
+        let bind_ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 3));
+        let mut ctx = Context::default();
+        ctx.insert(BindAddress::new(bind_ip));
+
+        let EstablishedClientConnection { conn, .. } = connector
+            .serve(ctx, Request::new(addr.to_string().parse().unwrap()))
+            .await
+            .unwrap();
+        assert_eq!(conn.local_addr().unwrap().ip(), bind_ip);
+
+        let (_, peer_addr) = listener.accept().await.unwrap();
+        assert_eq!(peer_addr.ip(), bind_ip);
+
+        let mut ctx = Context::default();
+        ctx.insert(BindAddress::new(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))));
+        assert!(connector
+            .serve(ctx, Request::new(addr.to_string().parse().unwrap()))
+            .await
+            .is_err());
+    }
+}
@@ -1,10 +1,16 @@
+use super::HappyEyeballs;
 use rama_core::{
     combinators::Either,
+    context::Cancelled,
     error::{BoxError, ErrorContext, OpaqueError},
     Context,
 };
 use rama_dns::{DnsOverwrite, DnsResolver, HickoryDns};
-use rama_net::address::{Authority, Domain, Host};
+use rama_net::{
+    address::{Authority, Domain, Host},
+    client::{ConnectTimings, PhaseTiming},
+    mode::TransportMode,
+};
 use std::{
     future::Future,
     net::{IpAddr, SocketAddr},
@@ -13,7 +19,7 @@ use std::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio::{
     net::TcpStream,
@@ -117,6 +123,9 @@ where
 }
 
 /// Establish a [`TcpStream`] connection for the given [`Authority`].
+///
+/// The IP families connected over are selected by the [`TransportMode`]
+/// found in the [`Context`], defaulting to [`TransportMode::DualStack`].
 pub async fn tcp_connect<State, Dns, Connector>(
     ctx: &Context<State>,
     authority: Authority,
@@ -124,6 +133,51 @@ pub async fn tcp_connect<State, Dns, Connector>(
     dns: Dns,
     connector: Connector,
 ) -> Result<(TcpStream, SocketAddr), OpaqueError>
+where
+    State: Clone + Send + Sync + 'static,
+    Dns: DnsResolver<Error: Into<BoxError>> + Clone,
+    Connector: TcpStreamConnector<Error: Into<BoxError> + Send + 'static> + Clone,
+{
+    let mode = ctx.get::<TransportMode>().copied().unwrap_or_default();
+    let (stream, addr, _) =
+        tcp_connect_with_mode(ctx, authority, allow_overwrites, dns, connector, mode, None).await?;
+    Ok((stream, addr))
+}
+
+/// Establish a [`TcpStream`] connection for the given [`Authority`],
+/// racing the resolved addresses using the [`HappyEyeballs`] algorithm if defined.
+pub(crate) async fn tcp_connect_with_mode<State, Dns, Connector>(
+    ctx: &Context<State>,
+    authority: Authority,
+    allow_overwrites: bool,
+    dns: Dns,
+    connector: Connector,
+    mode: TransportMode,
+    happy_eyeballs: Option<HappyEyeballs>,
+) -> Result<(TcpStream, SocketAddr, ConnectTimings), OpaqueError>
+where
+    State: Clone + Send + Sync + 'static,
+    Dns: DnsResolver<Error: Into<BoxError>> + Clone,
+    Connector: TcpStreamConnector<Error: Into<BoxError> + Send + 'static> + Clone,
+{
+    // abort any in-flight dns lookups and connection attempts
+    // as soon as the cancellation token in the context is cancelled
+    tokio::select! {
+        biased;
+        _ = ctx.cancelled() => Err(OpaqueError::from_std(Cancelled)).context("tcp connect"),
+        res = tcp_connect_with_mode_inner(ctx, authority, allow_overwrites, dns, connector, mode, happy_eyeballs) => res,
+    }
+}
+
+async fn tcp_connect_with_mode_inner<State, Dns, Connector>(
+    ctx: &Context<State>,
+    authority: Authority,
+    allow_overwrites: bool,
+    dns: Dns,
+    connector: Connector,
+    mode: TransportMode,
+    happy_eyeballs: Option<HappyEyeballs>,
+) -> Result<(TcpStream, SocketAddr, ConnectTimings), OpaqueError>
 where
     State: Clone + Send + Sync + 'static,
     Dns: DnsResolver<Error: Into<BoxError>> + Clone,
@@ -133,14 +187,24 @@ where
     let domain = match host {
         Host::Name(domain) => domain,
         Host::Address(ip) => {
+            if !mode.allows_ip(ip) {
+                return Err(OpaqueError::from_display(format!(
+                    "ip address {ip} not allowed by transport mode {mode}"
+                )));
+            }
             // if the authority is already defined as an IP address, we can directly connect to it
             let addr = (ip, port).into();
+            let connect_start = Instant::now();
             let stream = connector
                 .connect(addr)
                 .await
                 .map_err(|err| OpaqueError::from_boxed(err.into()))
                 .context("establish tcp client connection")?;
-            return Ok((stream, addr));
+            let timings = ConnectTimings {
+                tcp_connect: Some(PhaseTiming::until_now(connect_start)),
+                ..Default::default()
+            };
+            return Ok((stream, addr, timings));
         }
     };
 
@@ -152,6 +216,8 @@ where
                 port,
                 dns_overwrite.deref().clone(),
                 connector.clone(),
+                mode,
+                happy_eyeballs,
             )
             .await
             {
@@ -161,9 +227,9 @@ where
     }
 
     //... otherwise we'll try to establish a connection,
-    // with dual-stack parallel connections...
+    // with dual-stack parallel connections (unless the mode says otherwise)...
 
-    tcp_connect_inner(ctx, domain, port, dns, connector).await
+    tcp_connect_inner(ctx, domain, port, dns, connector, mode, happy_eyeballs).await
 }
 
 async fn tcp_connect_inner<State, Dns, Connector>(
@@ -172,54 +238,128 @@ async fn tcp_connect_inner<State, Dns, Connector>(
     port: u16,
     dns: Dns,
     connector: Connector,
-) -> Result<(TcpStream, SocketAddr), OpaqueError>
+    mode: TransportMode,
+    happy_eyeballs: Option<HappyEyeballs>,
+) -> Result<(TcpStream, SocketAddr, ConnectTimings), OpaqueError>
+where
+    State: Clone + Send + Sync + 'static,
+    Dns: DnsResolver<Error: Into<BoxError>> + Clone,
+    Connector: TcpStreamConnector<Error: Into<BoxError> + Send + 'static> + Clone,
+{
+    match mode {
+        TransportMode::DualStack => {
+            tcp_connect_inner_kinds(
+                ctx,
+                domain,
+                port,
+                dns,
+                connector,
+                &[IpKind::Ipv6, IpKind::Ipv4],
+                happy_eyeballs,
+            )
+            .await
+        }
+        TransportMode::Ipv4Only => {
+            tcp_connect_inner_kinds(
+                ctx,
+                domain,
+                port,
+                dns,
+                connector,
+                &[IpKind::Ipv4],
+                happy_eyeballs,
+            )
+            .await
+        }
+        TransportMode::Ipv6Only => {
+            tcp_connect_inner_kinds(
+                ctx,
+                domain,
+                port,
+                dns,
+                connector,
+                &[IpKind::Ipv6],
+                happy_eyeballs,
+            )
+            .await
+        }
+        TransportMode::PreferIpv6 => {
+            match tcp_connect_inner_kinds(
+                ctx,
+                domain.clone(),
+                port,
+                dns.clone(),
+                connector.clone(),
+                &[IpKind::Ipv6],
+                happy_eyeballs,
+            )
+            .await
+            {
+                Ok(tuple) => Ok(tuple),
+                Err(err) => {
+                    tracing::trace!(err = %err, "failed to connect over IPv6: fallback to IPv4");
+                    tcp_connect_inner_kinds(
+                        ctx,
+                        domain,
+                        port,
+                        dns,
+                        connector,
+                        &[IpKind::Ipv4],
+                        happy_eyeballs,
+                    )
+                    .await
+                }
+            }
+        }
+    }
+}
+
+async fn tcp_connect_inner_kinds<State, Dns, Connector>(
+    ctx: &Context<State>,
+    domain: Domain,
+    port: u16,
+    dns: Dns,
+    connector: Connector,
+    ip_kinds: &[IpKind],
+    happy_eyeballs: Option<HappyEyeballs>,
+) -> Result<(TcpStream, SocketAddr, ConnectTimings), OpaqueError>
 where
     State: Clone + Send + Sync + 'static,
     Dns: DnsResolver<Error: Into<BoxError>> + Clone,
     Connector: TcpStreamConnector<Error: Into<BoxError> + Send + 'static> + Clone,
 {
+    if let Some(happy_eyeballs) = happy_eyeballs {
+        return happy_eyeballs
+            .connect(domain, port, dns, connector, ip_kinds)
+            .await;
+    }
+
     let (tx, mut rx) = channel(1);
 
     let connected = Arc::new(AtomicBool::new(false));
+    // stop the pending branches once this future is done or dropped (e.g. cancelled)
+    let _guard = ConnectedGuard(connected.clone());
     let sem = Arc::new(Semaphore::new(3));
 
-    // IPv6
-    let ipv6_tx = tx.clone();
-    let ipv6_domain = domain.clone();
-    let ipv6_connected = connected.clone();
-    let ipv6_sem = sem.clone();
-    ctx.spawn(tcp_connect_inner_branch(
-        dns.clone(),
-        connector.clone(),
-        IpKind::Ipv6,
-        ipv6_domain,
-        port,
-        ipv6_tx,
-        ipv6_connected,
-        ipv6_sem,
-    ));
-
-    // IPv4
-    let ipv4_tx = tx;
-    let ipv4_domain = domain.clone();
-    let ipv4_connected = connected.clone();
-    let ipv4_sem = sem;
-    ctx.spawn(tcp_connect_inner_branch(
-        dns,
-        connector,
-        IpKind::Ipv4,
-        ipv4_domain,
-        port,
-        ipv4_tx,
-        ipv4_connected,
-        ipv4_sem,
-    ));
+    for ip_kind in ip_kinds {
+        ctx.spawn(tcp_connect_inner_branch(
+            dns.clone(),
+            connector.clone(),
+            *ip_kind,
+            domain.clone(),
+            port,
+            tx.clone(),
+            connected.clone(),
+            sem.clone(),
+        ));
+    }
+    drop(tx);
 
     // wait for the first connection to succeed,
     // ignore the rest of the connections (sorry, but not sorry)
-    if let Some((stream, addr)) = rx.recv().await {
+    if let Some(tuple) = rx.recv().await {
         connected.store(true, Ordering::Release);
-        return Ok((stream, addr));
+        return Ok(tuple);
     }
 
     Err(OpaqueError::from_display(format!(
@@ -227,8 +367,16 @@ where
     )))
 }
 
+struct ConnectedGuard(Arc<AtomicBool>);
+
+impl Drop for ConnectedGuard {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum IpKind {
+pub(crate) enum IpKind {
     Ipv4,
     Ipv6,
 }
@@ -240,13 +388,14 @@ async fn tcp_connect_inner_branch<Dns, Connector>(
     ip_kind: IpKind,
     domain: Domain,
     port: u16,
-    tx: Sender<(TcpStream, SocketAddr)>,
+    tx: Sender<(TcpStream, SocketAddr, ConnectTimings)>,
     connected: Arc<AtomicBool>,
     sem: Arc<Semaphore>,
 ) where
     Dns: DnsResolver<Error: Into<BoxError>> + Clone,
     Connector: TcpStreamConnector<Error: Into<BoxError> + Send + 'static> + Clone,
 {
+    let dns_start = Instant::now();
     let ip_it = match ip_kind {
         IpKind::Ipv4 => match dns.ipv4_lookup(domain).await {
             Ok(ips) => Either::A(ips.into_iter().map(IpAddr::V4)),
@@ -265,6 +414,7 @@ async fn tcp_connect_inner_branch<Dns, Connector>(
             }
         },
     };
+    let dns_timing = PhaseTiming::until_now(dns_start);
 
     for (index, ip) in ip_it.enumerate() {
         let addr = (ip, port).into();
@@ -297,10 +447,16 @@ async fn tcp_connect_inner_branch<Dns, Connector>(
 
             tracing::trace!("[{ip_kind:?}] #{index}: tcp connect attempt to {addr}");
 
+            let connect_start = Instant::now();
             match connector.connect(addr).await {
                 Ok(stream) => {
                     tracing::trace!("[{ip_kind:?}] #{index}: tcp connection stablished to {addr}");
-                    if let Err(err) = tx.send((stream, addr)).await {
+                    let timings = ConnectTimings {
+                        dns: Some(dns_timing),
+                        tcp_connect: Some(PhaseTiming::until_now(connect_start)),
+                        tls: None,
+                    };
+                    if let Err(err) = tx.send((stream, addr, timings)).await {
                         tracing::trace!(err = %err, "[{ip_kind:?}] #{index}: failed to send resolved IP address");
                     }
                 }
@@ -312,3 +468,139 @@ async fn tcp_connect_inner_branch<Dns, Connector>(
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::context::CancellationToken;
+    use rama_dns::InMemoryDns;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use tokio::net::TcpListener;
+
+    /// Resolves `example.com` to both an A and an AAAA record,
+    /// and "connects" to the resolved address by connecting to a local IPv4 listener,
+    /// optionally failing for IPv6 addresses.
+    async fn connect(
+        mode: Option<TransportMode>,
+        authority: &str,
+        fail_ipv6: bool,
+    ) -> Result<SocketAddr, OpaqueError> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let mut dns = InMemoryDns::new();
+        dns.insert_addresses(
+            Domain::from_static("example.com"),
+            [
+                IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+                IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+            ],
+        );
+
+        let connector = move |addr: SocketAddr| async move {
+            if fail_ipv6 && addr.is_ipv6() {
+                return Err(std::io::Error::other("ipv6 unreachable"));
+            }
+            TcpStream::connect(local_addr).await
+        };
+
+        let mut ctx = Context::default();
+        if let Some(mode) = mode {
+            ctx.insert(mode);
+        }
+
+        let (_stream, addr) =
+            tcp_connect(&ctx, authority.parse().unwrap(), false, dns, connector).await?;
+        Ok(addr)
+    }
+
+    #[tokio::test]
+    async fn test_tcp_connect_transport_mode_force_ipv4() {
+        let addr = connect(Some(TransportMode::Ipv4Only), "example.com:80", false)
+            .await
+            .unwrap();
+        assert_eq!(addr, "192.0.2.1:80".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_connect_transport_mode_force_ipv6() {
+        let addr = connect(Some(TransportMode::Ipv6Only), "example.com:80", false)
+            .await
+            .unwrap();
+        assert_eq!(addr, "[2001:db8::1]:80".parse().unwrap());
+
+        assert!(
+            connect(Some(TransportMode::Ipv6Only), "example.com:80", true)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tcp_connect_transport_mode_prefer_ipv6() {
+        let addr = connect(Some(TransportMode::PreferIpv6), "example.com:80", false)
+            .await
+            .unwrap();
+        assert_eq!(addr, "[2001:db8::1]:80".parse().unwrap());
+
+        let addr = connect(Some(TransportMode::PreferIpv6), "example.com:80", true)
+            .await
+            .unwrap();
+        assert_eq!(addr, "192.0.2.1:80".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_connect_transport_mode_dual_stack() {
+        let addr = connect(None, "example.com:80", true).await.unwrap();
+        assert_eq!(addr, "192.0.2.1:80".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_connect_transport_mode_ip_address() {
+        assert!(
+            connect(Some(TransportMode::Ipv6Only), "192.0.2.1:80", false)
+                .await
+                .is_err()
+        );
+        let addr = connect(Some(TransportMode::Ipv4Only), "192.0.2.1:80", false)
+            .await
+            .unwrap();
+        assert_eq!(addr, "192.0.2.1:80".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_connect_cancelled() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_address(
+            Domain::from_static("example.com"),
+            IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+        );
+
+        // a connection attempt which never completes
+        let connector = |_: SocketAddr| async move {
+            std::future::pending::<Result<TcpStream, std::io::Error>>().await
+        };
+
+        let token = CancellationToken::new();
+        let mut ctx = Context::default();
+        ctx.insert(token.clone());
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            token.cancel();
+        });
+
+        let start = Instant::now();
+        let err = tcp_connect(
+            &ctx,
+            "example.com:80".parse().unwrap(),
+            false,
+            dns,
+            connector,
+        )
+        .await
+        .unwrap_err();
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(std::error::Error::source(&err).is_some_and(|err| err.is::<Cancelled>()));
+    }
+}
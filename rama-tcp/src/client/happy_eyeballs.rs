@@ -0,0 +1,474 @@
+use rama_core::error::{BoxError, ErrorExt, OpaqueError};
+use rama_dns::DnsResolver;
+use rama_net::{
+    address::Domain,
+    client::{ConnectTimings, PhaseTiming},
+};
+use std::{
+    collections::VecDeque,
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+use tokio::{net::TcpStream, task::JoinSet, time::Instant};
+
+use super::{connect::IpKind, TcpStreamConnector};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Configuration of the Happy Eyeballs (version 2) algorithm, as defined in [RFC 8305],
+/// used to establish a [`TcpStream`] to a domain.
+///
+/// The 'A' and 'AAAA' records of the domain are resolved in parallel,
+/// after which connection attempts are raced over the resolved addresses,
+/// alternating between the IP families and starting with IPv6.
+/// A new attempt is started each time the [connection attempt delay] passes
+/// without any attempt succeeding, or as soon as the previous attempt failed.
+///
+/// The first attempt to succeed wins, all other attempts are cancelled.
+///
+/// Enable it using [`TcpConnector::with_happy_eyeballs`],
+/// which still respects the [`TransportMode`] and [`DnsOverwrite`]
+/// found in the [`Context`]. The delays default to the values recommended by [RFC 8305].
+///
+/// [RFC 8305]: https://datatracker.ietf.org/doc/html/rfc8305
+/// [connection attempt delay]: Self::with_connection_attempt_delay
+/// [`TcpConnector::with_happy_eyeballs`]: crate::client::service::TcpConnector::with_happy_eyeballs
+/// [`TransportMode`]: rama_net::mode::TransportMode
+/// [`DnsOverwrite`]: rama_dns::DnsOverwrite
+/// [`Context`]: rama_core::Context
+pub struct HappyEyeballs {
+    resolution_delay: Duration,
+    connection_attempt_delay: Duration,
+}
+
+impl Default for HappyEyeballs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HappyEyeballs {
+    /// The default resolution delay, as recommended by RFC 8305.
+    pub const DEFAULT_RESOLUTION_DELAY: Duration = Duration::from_millis(50);
+
+    /// The default connection attempt delay, as recommended by RFC 8305.
+    pub const DEFAULT_CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+    /// Create a new [`HappyEyeballs`] configuration,
+    /// using the delays recommended by RFC 8305.
+    pub const fn new() -> Self {
+        Self {
+            resolution_delay: Self::DEFAULT_RESOLUTION_DELAY,
+            connection_attempt_delay: Self::DEFAULT_CONNECTION_ATTEMPT_DELAY,
+        }
+    }
+
+    /// Set the time to wait for the 'AAAA' records to resolve, once the 'A' records are resolved,
+    /// before connection attempts are started using the IPv4 addresses only.
+    ///
+    /// IPv6 addresses resolved after this delay are still used for later attempts.
+    ///
+    /// Defaults to 50ms.
+    pub fn with_resolution_delay(mut self, delay: Duration) -> Self {
+        self.resolution_delay = delay;
+        self
+    }
+
+    /// Set the time to wait for the 'AAAA' records to resolve, once the 'A' records are resolved,
+    /// before connection attempts are started using the IPv4 addresses only.
+    ///
+    /// IPv6 addresses resolved after this delay are still used for later attempts.
+    ///
+    /// Defaults to 50ms.
+    pub fn set_resolution_delay(&mut self, delay: Duration) -> &mut Self {
+        self.resolution_delay = delay;
+        self
+    }
+
+    /// Set the time to wait for a connection attempt to succeed,
+    /// before starting the next one in parallel.
+    ///
+    /// Defaults to 250ms.
+    pub fn with_connection_attempt_delay(mut self, delay: Duration) -> Self {
+        self.connection_attempt_delay = delay;
+        self
+    }
+
+    /// Set the time to wait for a connection attempt to succeed,
+    /// before starting the next one in parallel.
+    ///
+    /// Defaults to 250ms.
+    pub fn set_connection_attempt_delay(&mut self, delay: Duration) -> &mut Self {
+        self.connection_attempt_delay = delay;
+        self
+    }
+
+    /// The time to wait for the 'AAAA' records to resolve,
+    /// once the 'A' records are resolved.
+    pub fn resolution_delay(&self) -> Duration {
+        self.resolution_delay
+    }
+
+    /// The time to wait for a connection attempt to succeed,
+    /// before starting the next one.
+    pub fn connection_attempt_delay(&self) -> Duration {
+        self.connection_attempt_delay
+    }
+
+    /// Establish a [`TcpStream`] to the given [`Domain`] and port,
+    /// only resolving (and connecting over) the given IP families.
+    pub(crate) async fn connect<Dns, Connector>(
+        &self,
+        domain: Domain,
+        port: u16,
+        dns: Dns,
+        connector: Connector,
+        ip_kinds: &[IpKind],
+    ) -> Result<(TcpStream, SocketAddr, ConnectTimings), OpaqueError>
+    where
+        Dns: DnsResolver<Error: Into<BoxError>>,
+        Connector: TcpStreamConnector<Error: Into<BoxError> + Send + 'static>,
+    {
+        let dns_start = std::time::Instant::now();
+        // errors are boxed right away, as the resolver error is not required to be Send
+        let ipv6_lookup = async {
+            dns.ipv6_lookup(domain.clone())
+                .await
+                .map_err(Into::<BoxError>::into)
+        };
+        let ipv4_lookup = async {
+            dns.ipv4_lookup(domain.clone())
+                .await
+                .map_err(Into::<BoxError>::into)
+        };
+        tokio::pin!(ipv6_lookup, ipv4_lookup);
+
+        let mut ipv6_pending = ip_kinds.contains(&IpKind::Ipv6);
+        let mut ipv4_pending = ip_kinds.contains(&IpKind::Ipv4);
+        let mut addresses = Addresses::default();
+
+        // no attempts are made until the AAAA records resolved,
+        // or the resolution delay passed since the A records resolved
+        let mut resolution_deadline = None;
+        let mut dns_timing = None;
+
+        let mut attempts = JoinSet::new();
+        let mut attempt_deadline = None;
+        let mut last_err = None;
+
+        loop {
+            if dns_timing.is_none() && !ipv6_pending && !ipv4_pending {
+                dns_timing = Some(PhaseTiming::until_now(dns_start));
+            }
+
+            if dns_timing.is_some() && attempt_deadline.is_none() {
+                if let Some(ip) = addresses.pop() {
+                    let addr = SocketAddr::new(ip, port);
+                    tracing::trace!(%domain, %addr, "happy eyeballs: tcp connect attempt");
+                    let connector = connector.clone();
+                    attempts.spawn(async move {
+                        let connect_start = std::time::Instant::now();
+                        match connector.connect(addr).await {
+                            Ok(stream) => Ok((stream, addr, PhaseTiming::until_now(connect_start))),
+                            Err(err) => Err((addr, err.into())),
+                        }
+                    });
+                    attempt_deadline = Some(Instant::now() + self.connection_attempt_delay);
+                }
+            }
+
+            if !ipv6_pending && !ipv4_pending && attempts.is_empty() && addresses.is_empty() {
+                let err = last_err.unwrap_or_else(|| {
+                    OpaqueError::from_display("no IP addresses resolved for domain")
+                });
+                return Err(
+                    err.context(format!("happy eyeballs: connect to {domain} (port {port})"))
+                );
+            }
+
+            tokio::select! {
+                result = &mut ipv6_lookup, if ipv6_pending => {
+                    ipv6_pending = false;
+                    dns_timing.get_or_insert_with(|| PhaseTiming::until_now(dns_start));
+                    match result {
+                        Ok(ips) => addresses.ipv6.extend(ips.into_iter().map(IpAddr::V6)),
+                        Err(err) => {
+                            let err = OpaqueError::from_boxed(err);
+                            tracing::trace!(%err, %domain, "happy eyeballs: failed to resolve domain to IPv6 addresses");
+                            last_err = Some(err.context("resolve IPv6 addresses"));
+                        }
+                    }
+                }
+                result = &mut ipv4_lookup, if ipv4_pending => {
+                    ipv4_pending = false;
+                    if ipv6_pending {
+                        resolution_deadline = Some(Instant::now() + self.resolution_delay);
+                    }
+                    match result {
+                        Ok(ips) => addresses.ipv4.extend(ips.into_iter().map(IpAddr::V4)),
+                        Err(err) => {
+                            let err = OpaqueError::from_boxed(err);
+                            tracing::trace!(%err, %domain, "happy eyeballs: failed to resolve domain to IPv4 addresses");
+                            last_err = Some(err.context("resolve IPv4 addresses"));
+                        }
+                    }
+                }
+                _ = sleep_until(resolution_deadline), if dns_timing.is_none() && resolution_deadline.is_some() => {
+                    tracing::trace!(%domain, "happy eyeballs: resolution delay passed, continue without IPv6 addresses");
+                    dns_timing = Some(PhaseTiming::until_now(dns_start));
+                }
+                _ = sleep_until(attempt_deadline), if attempt_deadline.is_some() && !addresses.is_empty() => {
+                    attempt_deadline = None;
+                }
+                Some(result) = attempts.join_next(), if !attempts.is_empty() => {
+                    match result {
+                        Ok(Ok((stream, addr, connect_timing))) => {
+                            tracing::trace!(%domain, %addr, "happy eyeballs: tcp connection established");
+                            // cancel the attempts which lost the race
+                            attempts.shutdown().await;
+                            let timings = ConnectTimings {
+                                dns: dns_timing,
+                                tcp_connect: Some(connect_timing),
+                                tls: None,
+                            };
+                            return Ok((stream, addr, timings));
+                        }
+                        Ok(Err((addr, err))) => {
+                            let err = OpaqueError::from_boxed(err);
+                            tracing::trace!(%err, %domain, %addr, "happy eyeballs: tcp connect attempt failed");
+                            last_err = Some(err.context(format!("tcp connect to {addr}")));
+                        }
+                        Err(err) => {
+                            last_err = Some(
+                                OpaqueError::from_std(err).context("join tcp connect attempt"),
+                            );
+                        }
+                    }
+                    // a failed attempt makes way for the next one immediately
+                    attempt_deadline = None;
+                }
+            }
+        }
+    }
+}
+
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+#[derive(Debug, Default)]
+/// Resolved addresses which are not yet attempted,
+/// popped in alternating order of IP family, starting with IPv6.
+struct Addresses {
+    ipv6: VecDeque<IpAddr>,
+    ipv4: VecDeque<IpAddr>,
+    last_ipv6: bool,
+}
+
+impl Addresses {
+    fn is_empty(&self) -> bool {
+        self.ipv6.is_empty() && self.ipv4.is_empty()
+    }
+
+    fn pop(&mut self) -> Option<IpAddr> {
+        let (first, second) = if self.last_ipv6 {
+            (&mut self.ipv4, &mut self.ipv6)
+        } else {
+            (&mut self.ipv6, &mut self.ipv4)
+        };
+        let ip = first.pop_front().or_else(|| second.pop_front())?;
+        self.last_ipv6 = ip.is_ipv6();
+        Some(ip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::connect::tcp_connect_with_mode;
+    use rama_core::Context;
+    use rama_dns::{DnsOverwrite, InMemoryDns};
+    use rama_net::mode::TransportMode;
+    use std::{
+        net::{Ipv4Addr, Ipv6Addr},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+    };
+    use tokio::net::TcpListener;
+
+    const IPV4: Ipv4Addr = Ipv4Addr::new(192, 0, 2, 1);
+    const IPV6: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+
+    /// Resolves `example.com` to an A and an AAAA record,
+    /// each answered after their own delay.
+    #[derive(Debug, Clone)]
+    struct DelayedDns {
+        dns: InMemoryDns,
+        ipv4_delay: Duration,
+        ipv6_delay: Duration,
+    }
+
+    impl DelayedDns {
+        fn new(ipv4_delay: Duration, ipv6_delay: Duration) -> Self {
+            let mut dns = InMemoryDns::new();
+            dns.insert_addresses(
+                Domain::from_static("example.com"),
+                [IpAddr::V4(IPV4), IpAddr::V6(IPV6)],
+            );
+            Self {
+                dns,
+                ipv4_delay,
+                ipv6_delay,
+            }
+        }
+    }
+
+    impl DnsResolver for DelayedDns {
+        type Error = BoxError;
+
+        async fn ipv4_lookup(&self, domain: Domain) -> Result<Vec<Ipv4Addr>, Self::Error> {
+            tokio::time::sleep(self.ipv4_delay).await;
+            Ok(self.dns.ipv4_lookup(domain).await?)
+        }
+
+        async fn ipv6_lookup(&self, domain: Domain) -> Result<Vec<Ipv6Addr>, Self::Error> {
+            tokio::time::sleep(self.ipv6_delay).await;
+            Ok(self.dns.ipv6_lookup(domain).await?)
+        }
+    }
+
+    struct DropGuard(Arc<AtomicBool>);
+
+    impl Drop for DropGuard {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::Release);
+        }
+    }
+
+    /// "Connects" to `example.com` by connecting to a local IPv4 listener,
+    /// with IPv6 attempts never completing in case `hang_ipv6` is true.
+    async fn connect(
+        ctx: Context<()>,
+        dns: DelayedDns,
+        hang_ipv6: bool,
+        ipv6_dropped: Arc<AtomicBool>,
+    ) -> Result<(SocketAddr, ConnectTimings), OpaqueError> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let connector = move |addr: SocketAddr| {
+            let ipv6_dropped = ipv6_dropped.clone();
+            async move {
+                if hang_ipv6 && addr.is_ipv6() {
+                    let _guard = DropGuard(ipv6_dropped);
+                    std::future::pending::<()>().await;
+                }
+                TcpStream::connect(local_addr).await
+            }
+        };
+
+        let mode = ctx.get::<TransportMode>().copied().unwrap_or_default();
+        let (_stream, addr, timings) = tcp_connect_with_mode(
+            &ctx,
+            "example.com:80".parse().unwrap(),
+            true,
+            dns,
+            connector,
+            mode,
+            Some(
+                HappyEyeballs::new()
+                    .with_resolution_delay(Duration::from_millis(50))
+                    .with_connection_attempt_delay(Duration::from_millis(100)),
+            ),
+        )
+        .await?;
+        Ok((addr, timings))
+    }
+
+    #[tokio::test]
+    async fn test_happy_eyeballs_prefers_ipv6() {
+        let dns = DelayedDns::new(Duration::ZERO, Duration::ZERO);
+        let (addr, timings) = connect(Context::default(), dns, false, Default::default())
+            .await
+            .unwrap();
+        assert_eq!(addr, SocketAddr::new(IPV6.into(), 80));
+        assert!(timings.dns.is_some());
+        assert!(timings.tcp_connect.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_happy_eyeballs_waits_resolution_delay_for_ipv6() {
+        let dns = DelayedDns::new(Duration::ZERO, Duration::from_millis(10));
+        let (addr, _) = connect(Context::default(), dns, false, Default::default())
+            .await
+            .unwrap();
+        assert_eq!(addr, SocketAddr::new(IPV6.into(), 80));
+    }
+
+    #[tokio::test]
+    async fn test_happy_eyeballs_ipv4_after_resolution_delay() {
+        let dns = DelayedDns::new(Duration::ZERO, Duration::from_secs(5));
+        let start = Instant::now();
+        let (addr, _) = connect(Context::default(), dns, false, Default::default())
+            .await
+            .unwrap();
+        assert_eq!(addr, SocketAddr::new(IPV4.into(), 80));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_happy_eyeballs_races_stalled_ipv6() {
+        let dns = DelayedDns::new(Duration::ZERO, Duration::ZERO);
+        let ipv6_dropped = Arc::new(AtomicBool::new(false));
+
+        let start = Instant::now();
+        let (addr, _) = connect(Context::default(), dns, true, ipv6_dropped.clone())
+            .await
+            .unwrap();
+        assert_eq!(addr, SocketAddr::new(IPV4.into(), 80));
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        // the stalled IPv6 attempt lost the race and is cancelled
+        assert!(ipv6_dropped.load(Ordering::Acquire));
+    }
+
+    #[tokio::test]
+    async fn test_happy_eyeballs_transport_mode() {
+        let dns = DelayedDns::new(Duration::ZERO, Duration::ZERO);
+        let mut ctx = Context::default();
+        ctx.insert(TransportMode::Ipv4Only);
+        let (addr, _) = connect(ctx, dns, false, Default::default()).await.unwrap();
+        assert_eq!(addr, SocketAddr::new(IPV4.into(), 80));
+    }
+
+    #[tokio::test]
+    async fn test_happy_eyeballs_dns_overwrite() {
+        let overwrite_ip = Ipv4Addr::new(192, 0, 2, 42);
+        let mut overwrite = InMemoryDns::new();
+        overwrite.insert_address(Domain::from_static("example.com"), IpAddr::V4(overwrite_ip));
+
+        let dns = DelayedDns::new(Duration::ZERO, Duration::ZERO);
+        let mut ctx = Context::default();
+        ctx.insert(DnsOverwrite::from(overwrite));
+        let (addr, _) = connect(ctx, dns, false, Default::default()).await.unwrap();
+        assert_eq!(addr, SocketAddr::new(overwrite_ip.into(), 80));
+    }
+
+    #[tokio::test]
+    async fn test_happy_eyeballs_no_addresses() {
+        assert!(HappyEyeballs::new()
+            .connect(
+                Domain::from_static("example.com"),
+                80,
+                InMemoryDns::new(),
+                (),
+                &[IpKind::Ipv6, IpKind::Ipv4],
+            )
+            .await
+            .is_err());
+    }
+}
@@ -7,6 +7,17 @@ mod connect;
 #[doc(inline)]
 pub use connect::{default_tcp_connect, tcp_connect, TcpStreamConnector};
 
+mod happy_eyeballs;
+#[doc(inline)]
+pub use happy_eyeballs::HappyEyeballs;
+
+mod bind;
+#[cfg(feature = "http")]
+#[doc(inline)]
+pub use bind::BindAddressConnectorFactory;
+#[doc(inline)]
+pub use bind::{BindAddress, BindTcpStreamConnector};
+
 #[cfg(feature = "http")]
 mod request;
 #[cfg(feature = "http")]
@@ -4,25 +4,140 @@ use rama_core::{
 };
 use rama_dns::{DnsResolver, HickoryDns};
 use rama_net::{
-    address::ProxyAddress,
-    client::EstablishedClientConnection,
+    address::{Authority, Domain, Host},
+    client::{EstablishedClientConnection, ProxyTarget},
+    mode::TransportMode,
     transport::{TransportProtocol, TryRefIntoTransportContext},
 };
+use std::fmt;
 use tokio::net::TcpStream;
 
-use crate::client::connect::TcpStreamConnector;
+use crate::client::{connect::TcpStreamConnector, HappyEyeballs};
 
 use super::{CreatedTcpStreamConnector, TcpStreamConnectorCloneFactory, TcpStreamConnectorFactory};
 
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 /// A connector which can be used to establish a TCP connection to a server.
+///
+/// The [`TransportMode`] configured on the connector can be overwritten
+/// per request by adding a [`TransportMode`] to the [`Context`].
 pub struct TcpConnector<Dns = HickoryDns, ConnectorFactory = ()> {
     dns: Dns,
     connector_factory: ConnectorFactory,
+    transport_mode: TransportMode,
+    resolve_domains: bool,
+    happy_eyeballs: Option<HappyEyeballs>,
 }
 
-impl<Dns, Connector> TcpConnector<Dns, Connector> {}
+#[derive(Debug, Clone)]
+/// Error returned by a [`TcpConnector`] which is not allowed to resolve domains,
+/// when it is asked to connect to a domain.
+///
+/// See [`TcpConnector::with_domain_resolution`] for more information.
+pub struct UnresolvedDomainError {
+    domain: Domain,
+}
+
+impl UnresolvedDomainError {
+    /// The domain which the [`TcpConnector`] was asked to connect to.
+    pub fn domain(&self) -> &Domain {
+        &self.domain
+    }
+}
+
+impl fmt::Display for UnresolvedDomainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tcp connector: domain resolution disabled: cannot connect to unresolved domain {}",
+            self.domain
+        )
+    }
+}
+
+impl std::error::Error for UnresolvedDomainError {}
+
+impl<Dns, Connector> TcpConnector<Dns, Connector> {
+    /// Set the [`TransportMode`] used by this [`TcpConnector`],
+    /// unless overwritten by a [`TransportMode`] found in the [`Context`].
+    ///
+    /// By default [`TransportMode::DualStack`] is used.
+    pub fn with_transport_mode(mut self, mode: TransportMode) -> Self {
+        self.transport_mode = mode;
+        self
+    }
+
+    /// Set the [`TransportMode`] used by this [`TcpConnector`],
+    /// unless overwritten by a [`TransportMode`] found in the [`Context`].
+    ///
+    /// By default [`TransportMode::DualStack`] is used.
+    pub fn set_transport_mode(&mut self, mode: TransportMode) -> &mut Self {
+        self.transport_mode = mode;
+        self
+    }
+
+    /// Define whether or not this [`TcpConnector`] resolves domains
+    /// using its [`DnsResolver`].
+    ///
+    /// When disabled the [`TcpConnector`] only connects to IP addresses,
+    /// and fails with an [`UnresolvedDomainError`] when the target is a domain.
+    /// This is useful in combination with a [`DnsAddressResolverConnector`],
+    /// which resolves the domain prior to calling the [`TcpConnector`].
+    ///
+    /// By default domain resolution is enabled.
+    ///
+    /// [`DnsAddressResolverConnector`]: super::DnsAddressResolverConnector
+    pub fn with_domain_resolution(mut self, enabled: bool) -> Self {
+        self.resolve_domains = enabled;
+        self
+    }
+
+    /// Define whether or not this [`TcpConnector`] resolves domains
+    /// using its [`DnsResolver`].
+    ///
+    /// When disabled the [`TcpConnector`] only connects to IP addresses,
+    /// and fails with an [`UnresolvedDomainError`] when the target is a domain.
+    /// This is useful in combination with a [`DnsAddressResolverConnector`],
+    /// which resolves the domain prior to calling the [`TcpConnector`].
+    ///
+    /// By default domain resolution is enabled.
+    ///
+    /// [`DnsAddressResolverConnector`]: super::DnsAddressResolverConnector
+    pub fn set_domain_resolution(&mut self, enabled: bool) -> &mut Self {
+        self.resolve_domains = enabled;
+        self
+    }
+
+    /// Race the connection attempts to the resolved addresses of a domain
+    /// using the given [`HappyEyeballs`] configuration.
+    ///
+    /// By default all resolved addresses of the allowed IP families
+    /// are raced in parallel, without any delay in between the IP families.
+    pub fn with_happy_eyeballs(mut self, happy_eyeballs: HappyEyeballs) -> Self {
+        self.happy_eyeballs = Some(happy_eyeballs);
+        self
+    }
+
+    /// Race the connection attempts to the resolved addresses of a domain
+    /// using the given [`HappyEyeballs`] configuration.
+    ///
+    /// By default all resolved addresses of the allowed IP families
+    /// are raced in parallel, without any delay in between the IP families.
+    pub fn set_happy_eyeballs(&mut self, happy_eyeballs: HappyEyeballs) -> &mut Self {
+        self.happy_eyeballs = Some(happy_eyeballs);
+        self
+    }
+
+    fn ensure_resolved(&self, authority: &Authority) -> Result<(), UnresolvedDomainError> {
+        match authority.host() {
+            Host::Name(domain) if !self.resolve_domains => Err(UnresolvedDomainError {
+                domain: domain.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
 
 impl TcpConnector {
     /// Create a new [`TcpConnector`], which is used to establish a connection to a server.
@@ -33,6 +148,9 @@ impl TcpConnector {
         Self {
             dns: HickoryDns::default(),
             connector_factory: (),
+            transport_mode: TransportMode::default(),
+            resolve_domains: true,
+            happy_eyeballs: None,
         }
     }
 }
@@ -46,6 +164,9 @@ impl<Dns, ConnectorFactory> TcpConnector<Dns, ConnectorFactory> {
         TcpConnector {
             dns,
             connector_factory: self.connector_factory,
+            transport_mode: self.transport_mode,
+            resolve_domains: self.resolve_domains,
+            happy_eyeballs: self.happy_eyeballs,
         }
     }
 }
@@ -60,6 +181,9 @@ where {
         TcpConnector {
             dns: self.dns,
             connector_factory: TcpStreamConnectorCloneFactory(connector),
+            transport_mode: self.transport_mode,
+            resolve_domains: self.resolve_domains,
+            happy_eyeballs: self.happy_eyeballs,
         }
     }
 
@@ -69,6 +193,9 @@ where {
         TcpConnector {
             dns: self.dns,
             connector_factory: factory,
+            transport_mode: self.transport_mode,
+            resolve_domains: self.resolve_domains,
+            happy_eyeballs: self.happy_eyeballs,
         }
     }
 }
@@ -106,16 +233,25 @@ where
             .await
             .map_err(Into::into)?;
 
-        if let Some(proxy) = ctx.get::<ProxyAddress>() {
-            let (conn, addr) = crate::client::tcp_connect(
+        let transport_mode = ctx
+            .get::<TransportMode>()
+            .copied()
+            .unwrap_or(self.transport_mode);
+
+        if let Some(proxy) = ProxyTarget::proxy_address(&ctx) {
+            self.ensure_resolved(&proxy.authority)?;
+            let (conn, addr, timings) = crate::client::connect::tcp_connect_with_mode(
                 &ctx,
-                proxy.authority.clone(),
+                proxy.authority,
                 true,
                 self.dns.clone(),
                 connector,
+                transport_mode,
+                self.happy_eyeballs,
             )
             .await
             .context("tcp connector: conncept to proxy")?;
+            ctx.insert(timings);
             return Ok(EstablishedClientConnection {
                 ctx,
                 req,
@@ -143,10 +279,19 @@ where
         }
 
         let authority = transport_ctx.authority.clone();
-        let (conn, addr) =
-            crate::client::tcp_connect(&ctx, authority, false, self.dns.clone(), connector)
-                .await
-                .context("tcp connector: connect to server")?;
+        self.ensure_resolved(&authority)?;
+        let (conn, addr, timings) = crate::client::connect::tcp_connect_with_mode(
+            &ctx,
+            authority,
+            false,
+            self.dns.clone(),
+            connector,
+            transport_mode,
+            self.happy_eyeballs,
+        )
+        .await
+        .context("tcp connector: connect to server")?;
+        ctx.insert(timings);
 
         Ok(EstablishedClientConnection {
             ctx,
@@ -156,3 +301,173 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{service::DnsAddressResolverLayer, Request};
+    use rama_core::Layer;
+    use rama_dns::InMemoryDns;
+    use rama_net::address::{Domain, ProxyAddress};
+    use rama_net::client::ProxyCredentials;
+    use rama_net::transport::TransportContext;
+    use rama_net::user::{Basic, ProxyCredential};
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_tcp_connector_transport_mode() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let mut dns = InMemoryDns::new();
+        dns.insert_addresses(
+            Domain::from_static("example.com"),
+            [
+                IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+                IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+            ],
+        );
+
+        let connector = TcpConnector::new()
+            .with_dns(dns)
+            .with_connector(move |_addr: SocketAddr| TcpStream::connect(local_addr))
+            .with_transport_mode(TransportMode::Ipv6Only);
+
+        let req = || Request::new("example.com:80".parse().unwrap());
+
+        let EstablishedClientConnection { addr, .. } =
+            connector.serve(Context::default(), req()).await.unwrap();
+        assert_eq!(addr, "[2001:db8::1]:80".parse().unwrap());
+
+        // the transport mode of the request overwrites the one of the connector
+        let mut ctx = Context::default();
+        ctx.insert(TransportMode::Ipv4Only);
+        let EstablishedClientConnection { addr, .. } = connector.serve(ctx, req()).await.unwrap();
+        assert_eq!(addr, "192.0.2.1:80".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_connector_rejects_unresolved_domain() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let connector = TcpConnector::new()
+            .with_dns(InMemoryDns::new())
+            .with_domain_resolution(false);
+
+        let err = connector
+            .serve(
+                Context::default(),
+                Request::new(
+                    format!("example.com:{}", local_addr.port())
+                        .parse()
+                        .unwrap(),
+                ),
+            )
+            .await
+            .unwrap_err();
+        let err = err.downcast_ref::<UnresolvedDomainError>().unwrap();
+        assert_eq!(err.domain(), &Domain::from_static("example.com"));
+
+        // proxy addresses are not resolved either
+        let mut ctx = Context::default();
+        ctx.insert(ProxyAddress::try_from("proxy.example.com:8080").unwrap());
+        let err = connector
+            .serve(ctx, Request::new(local_addr.into()))
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<UnresolvedDomainError>().is_some());
+
+        let mut ctx = Context::default();
+        ctx.insert(ProxyTarget::new((
+            Domain::from_static("proxy.example.com"),
+            8080,
+        )));
+        let err = connector
+            .serve(ctx, Request::new(local_addr.into()))
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<UnresolvedDomainError>().is_some());
+
+        // ip addresses are still connected to
+        let EstablishedClientConnection { addr, .. } = connector
+            .serve(Context::default(), Request::new(local_addr.into()))
+            .await
+            .unwrap();
+        assert_eq!(addr, local_addr);
+    }
+
+    #[tokio::test]
+    async fn test_dns_address_resolver_connector_with_tcp_connector() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let mut dns = InMemoryDns::new();
+        dns.insert_address(Domain::from_static("example.com"), local_addr.ip());
+
+        let connector = DnsAddressResolverLayer::new(dns).layer(
+            TcpConnector::new()
+                .with_dns(InMemoryDns::new())
+                .with_domain_resolution(false),
+        );
+
+        let authority: Authority = format!("example.com:{}", local_addr.port())
+            .parse()
+            .unwrap();
+        let EstablishedClientConnection { ctx, addr, .. } = connector
+            .serve(Context::default(), Request::new(authority.clone()))
+            .await
+            .unwrap();
+        assert_eq!(addr, local_addr);
+        // the original target is restored for the layers wrapping the resolver
+        assert_eq!(ctx.get::<TransportContext>().unwrap().authority, authority);
+
+        // unknown domains fail to resolve
+        let err = connector
+            .serve(
+                Context::default(),
+                Request::new("unknown.example.com:80".parse().unwrap()),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<UnresolvedDomainError>().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dns_address_resolver_connector_with_proxy_target() {
+        let proxy_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_addr = proxy_listener.local_addr().unwrap();
+
+        let mut dns = InMemoryDns::new();
+        dns.insert_address(Domain::from_static("proxy.example.com"), proxy_addr.ip());
+
+        let connector = DnsAddressResolverLayer::new(dns).layer(
+            TcpConnector::new()
+                .with_dns(InMemoryDns::new())
+                .with_domain_resolution(false),
+        );
+
+        let proxy_target: ProxyTarget = ProxyTarget::new(
+            format!("proxy.example.com:{}", proxy_addr.port())
+                .parse::<Authority>()
+                .unwrap(),
+        );
+        let mut ctx = Context::default();
+        ctx.insert(proxy_target.clone());
+        ctx.insert(ProxyCredentials::from(ProxyCredential::Basic(Basic::new(
+            "john", "secret",
+        ))));
+        // the proxy target takes precedence over the proxy address
+        ctx.insert(ProxyAddress::try_from("127.0.0.1:1").unwrap());
+
+        // the target itself is never connected to, as we connect via the proxy
+        let EstablishedClientConnection { ctx, addr, .. } = connector
+            .serve(ctx, Request::new("unknown.example.com:80".parse().unwrap()))
+            .await
+            .unwrap();
+        assert_eq!(addr, proxy_addr);
+        // the original proxy target is restored for the layers wrapping the resolver
+        assert_eq!(ctx.get::<ProxyTarget>().unwrap(), &proxy_target);
+    }
+}
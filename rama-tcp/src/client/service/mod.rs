@@ -6,7 +6,11 @@ pub use forward::{ForwardAuthority, Forwarder};
 
 mod connector;
 #[doc(inline)]
-pub use connector::TcpConnector;
+pub use connector::{TcpConnector, UnresolvedDomainError};
+
+mod resolve;
+#[doc(inline)]
+pub use resolve::{DnsAddressResolverConnector, DnsAddressResolverLayer};
 
 mod select;
 #[doc(inline)]
@@ -0,0 +1,290 @@
+use rama_core::{
+    error::{BoxError, ErrorContext, ErrorExt, OpaqueError},
+    Context, Layer, Service,
+};
+use rama_dns::{DnsOverwrite, DnsResolver, HickoryDns};
+use rama_net::{
+    address::{Authority, Domain, Host, ProxyAddress},
+    client::{ConnectorService, EstablishedClientConnection, ProxyTarget},
+    mode::TransportMode,
+    transport::{TransportContext, TryRefIntoTransportContext},
+};
+use rama_utils::macros::define_inner_service_accessors;
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+};
+
+/// A connector which resolves the domain of the target [`Authority`]
+/// into a [`SocketAddr`] using a [`DnsResolver`], prior to calling the inner connector.
+///
+/// The target is the [`ProxyAddress`] in case one is found in the [`Context`],
+/// and the authority of the [`TransportContext`] otherwise.
+/// The inner connector sees the resolved IP address as authority
+/// of that target, while the [`Context`] of the established connection
+/// has the original (unresolved) target restored again.
+///
+/// Targets are resolved according to the [`TransportMode`] found in the [`Context`],
+/// defaulting to [`TransportMode::DualStack`]. The first resolved address is used,
+/// preferring IPv4 over IPv6 unless the mode states otherwise. Overwrites defined
+/// using a [`DnsOverwrite`] in the [`Context`] take precedence over the [`DnsResolver`].
+///
+/// Combine it with a [`TcpConnector`] configured using
+/// [`TcpConnector::with_domain_resolution`] to ensure that
+/// all connections are established to addresses resolved by this connector.
+///
+/// [`TcpConnector`]: super::TcpConnector
+/// [`TcpConnector::with_domain_resolution`]: super::TcpConnector::with_domain_resolution
+pub struct DnsAddressResolverConnector<S, Dns = HickoryDns> {
+    inner: S,
+    dns: Dns,
+}
+
+impl<S: fmt::Debug, Dns: fmt::Debug> fmt::Debug for DnsAddressResolverConnector<S, Dns> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DnsAddressResolverConnector")
+            .field("inner", &self.inner)
+            .field("dns", &self.dns)
+            .finish()
+    }
+}
+
+impl<S: Clone, Dns: Clone> Clone for DnsAddressResolverConnector<S, Dns> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            dns: self.dns.clone(),
+        }
+    }
+}
+
+impl<S, Dns> DnsAddressResolverConnector<S, Dns> {
+    /// Create a new [`DnsAddressResolverConnector`],
+    /// resolving targets using the given [`DnsResolver`].
+    pub const fn new(inner: S, dns: Dns) -> Self {
+        Self { inner, dns }
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S> DnsAddressResolverConnector<S> {
+    /// Create a new [`DnsAddressResolverConnector`],
+    /// resolving targets using the default [`HickoryDns`] resolver.
+    pub fn hickory(inner: S) -> Self {
+        Self::new(inner, HickoryDns::default())
+    }
+}
+
+impl<State, Request, S, Dns> Service<State, Request> for DnsAddressResolverConnector<S, Dns>
+where
+    State: Clone + Send + Sync + 'static,
+    Request: TryRefIntoTransportContext<State, Error: Into<BoxError> + Send + Sync + 'static>
+        + Send
+        + 'static,
+    S: ConnectorService<State, Request, Connection: Send>,
+    Dns: DnsResolver<Error: Into<BoxError>>,
+{
+    type Response = EstablishedClientConnection<S::Connection, State, Request>;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        mut ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        let mode = ctx.get::<TransportMode>().copied().unwrap_or_default();
+
+        if let Some(target) = ctx.get::<ProxyTarget>().cloned() {
+            let addr = self
+                .resolve(&ctx, target.authority.clone(), mode)
+                .await
+                .context("dns address resolver connector: resolve proxy target")?;
+            ctx.insert(ProxyTarget {
+                authority: addr.into(),
+                ..target.clone()
+            });
+
+            let mut established = self.inner.connect(ctx, req).await.map_err(Into::into)?;
+            established.ctx.insert(target);
+            return Ok(established);
+        }
+
+        if let Some(proxy) = ctx.get::<ProxyAddress>().cloned() {
+            let addr = self
+                .resolve(&ctx, proxy.authority.clone(), mode)
+                .await
+                .context("dns address resolver connector: resolve proxy address")?;
+            ctx.insert(ProxyAddress {
+                authority: addr.into(),
+                ..proxy.clone()
+            });
+
+            let mut established = self.inner.connect(ctx, req).await.map_err(Into::into)?;
+            established.ctx.insert(proxy);
+            return Ok(established);
+        }
+
+        let transport_ctx = ctx
+            .get_or_try_insert_with_ctx(|ctx| req.try_ref_into_transport_ctx(ctx))
+            .map_err(|err| {
+                OpaqueError::from_boxed(err.into()).context(
+                    "dns address resolver connector: compute transport context to get authority",
+                )
+            })?
+            .clone();
+
+        let addr = self
+            .resolve(&ctx, transport_ctx.authority.clone(), mode)
+            .await
+            .context("dns address resolver connector: resolve target address")?;
+        ctx.insert(TransportContext {
+            authority: addr.into(),
+            ..transport_ctx.clone()
+        });
+
+        let mut established = self.inner.connect(ctx, req).await.map_err(Into::into)?;
+        established.ctx.insert(transport_ctx);
+        Ok(established)
+    }
+}
+
+impl<S, Dns> DnsAddressResolverConnector<S, Dns>
+where
+    Dns: DnsResolver<Error: Into<BoxError>>,
+{
+    async fn resolve<State>(
+        &self,
+        ctx: &Context<State>,
+        authority: Authority,
+        mode: TransportMode,
+    ) -> Result<SocketAddr, OpaqueError> {
+        let (host, port) = authority.into_parts();
+        let domain = match host {
+            Host::Name(domain) => domain,
+            Host::Address(ip) => {
+                if !mode.allows_ip(ip) {
+                    return Err(OpaqueError::from_display(format!(
+                        "ip address {ip} not allowed by transport mode {mode}"
+                    )));
+                }
+                return Ok((ip, port).into());
+            }
+        };
+
+        if let Some(dns_overwrite) = ctx.get::<DnsOverwrite>() {
+            match resolve_domain(&**dns_overwrite, domain.clone(), mode).await {
+                Ok(ip) => return Ok((ip, port).into()),
+                Err(err) => {
+                    tracing::trace!(err = %err, %domain, "failed to resolve domain using dns overwrite");
+                }
+            }
+        }
+
+        let ip = resolve_domain(&self.dns, domain, mode).await?;
+        Ok((ip, port).into())
+    }
+}
+
+async fn resolve_domain<Dns>(
+    dns: &Dns,
+    domain: Domain,
+    mode: TransportMode,
+) -> Result<IpAddr, OpaqueError>
+where
+    Dns: DnsResolver<Error: Into<BoxError>>,
+{
+    let (first, fallback) = match mode {
+        TransportMode::DualStack => (IpKind::Ipv4, Some(IpKind::Ipv6)),
+        TransportMode::PreferIpv6 => (IpKind::Ipv6, Some(IpKind::Ipv4)),
+        TransportMode::Ipv4Only => (IpKind::Ipv4, None),
+        TransportMode::Ipv6Only => (IpKind::Ipv6, None),
+    };
+
+    match first.lookup(dns, domain.clone()).await {
+        Ok(ip) => Ok(ip),
+        Err(err) => match fallback {
+            Some(kind) => {
+                tracing::trace!(err = %err, %domain, "failed to resolve domain: fallback to {kind:?}");
+                kind.lookup(dns, domain).await
+            }
+            None => Err(err),
+        },
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum IpKind {
+    Ipv4,
+    Ipv6,
+}
+
+impl IpKind {
+    async fn lookup<Dns>(self, dns: &Dns, domain: Domain) -> Result<IpAddr, OpaqueError>
+    where
+        Dns: DnsResolver<Error: Into<BoxError>>,
+    {
+        let ip = match self {
+            Self::Ipv4 => dns
+                .ipv4_lookup(domain.clone())
+                .await
+                .map_err(|err| OpaqueError::from_boxed(err.into()))?
+                .into_iter()
+                .next()
+                .map(IpAddr::V4),
+            Self::Ipv6 => dns
+                .ipv6_lookup(domain.clone())
+                .await
+                .map_err(|err| OpaqueError::from_boxed(err.into()))?
+                .into_iter()
+                .next()
+                .map(IpAddr::V6),
+        };
+        ip.ok_or_else(|| {
+            OpaqueError::from_display(format!("no {self:?} address found for domain {domain}"))
+        })
+    }
+}
+
+/// A [`Layer`] which wraps a connector in a [`DnsAddressResolverConnector`].
+pub struct DnsAddressResolverLayer<Dns = HickoryDns> {
+    dns: Dns,
+}
+
+impl<Dns: fmt::Debug> fmt::Debug for DnsAddressResolverLayer<Dns> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DnsAddressResolverLayer")
+            .field("dns", &self.dns)
+            .finish()
+    }
+}
+
+impl<Dns: Clone> Clone for DnsAddressResolverLayer<Dns> {
+    fn clone(&self) -> Self {
+        Self {
+            dns: self.dns.clone(),
+        }
+    }
+}
+
+impl<Dns> DnsAddressResolverLayer<Dns> {
+    /// Create a new [`DnsAddressResolverLayer`],
+    /// resolving targets using the given [`DnsResolver`].
+    pub const fn new(dns: Dns) -> Self {
+        Self { dns }
+    }
+}
+
+impl Default for DnsAddressResolverLayer {
+    fn default() -> Self {
+        Self::new(HickoryDns::default())
+    }
+}
+
+impl<S, Dns: Clone> Layer<S> for DnsAddressResolverLayer<Dns> {
+    type Service = DnsAddressResolverConnector<S, Dns>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DnsAddressResolverConnector::new(inner, self.dns.clone())
+    }
+}
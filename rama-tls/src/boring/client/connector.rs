@@ -1,4 +1,5 @@
 use super::TlsConnectorData;
+use crate::exporter::ExportKeyingMaterial;
 use crate::types::TlsTunnel;
 use pin_project_lite::pin_project;
 use private::{ConnectorKindAuto, ConnectorKindSecure, ConnectorKindTunnel};
@@ -122,6 +123,12 @@ impl Default for TlsConnectorLayer<ConnectorKindAuto> {
 /// only if the request requires a secure connection. You can instead use
 /// [`TlsConnector::secure_only`] to force the connector to always
 /// establish a secure connection.
+///
+/// TLS 1.3 early data (0-RTT) is not supported by this connector:
+/// requests marked with [`EarlyDataSafe`] are only sent once the full handshake
+/// is completed, as for any other request.
+///
+/// [`EarlyDataSafe`]: rama_net::tls::client::EarlyDataSafe
 pub struct TlsConnector<S, K = ConnectorKindAuto> {
     inner: S,
     connector_data: Option<TlsConnectorData>,
@@ -504,6 +511,24 @@ impl<S: fmt::Debug> fmt::Debug for AutoTlsStreamData<S> {
     }
 }
 
+impl<S> ExportKeyingMaterial for AutoTlsStream<S> {
+    fn export_keying_material(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        length: usize,
+    ) -> Result<Vec<u8>, OpaqueError> {
+        match &self.inner {
+            AutoTlsStreamData::Secure { inner } => {
+                inner.export_keying_material(label, context, length)
+            }
+            AutoTlsStreamData::Plain { .. } => Err(OpaqueError::from_display(
+                "cannot export keying material from a plain (non-tls) stream",
+            )),
+        }
+    }
+}
+
 impl<S> AsyncRead for AutoTlsStream<S>
 where
     S: Stream + Unpin,
@@ -0,0 +1,73 @@
+use crate::boring::dep::tokio_boring::SslStream;
+use crate::exporter::ExportKeyingMaterial;
+use rama_core::error::{ErrorContext, OpaqueError};
+
+impl<S> ExportKeyingMaterial for SslStream<S> {
+    fn export_keying_material(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        length: usize,
+    ) -> Result<Vec<u8>, OpaqueError> {
+        let mut material = vec![0; length];
+        self.ssl()
+            .export_keying_material(&mut material, label, context)
+            .context("boring: export keying material")?;
+        Ok(material)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boring::dep::boring::ssl::{SslConnector, SslMethod, SslVerifyMode};
+    use crate::boring::server::TlsAcceptorService;
+    use rama_core::{service::service_fn, Context, Service};
+    use rama_net::tls::server::{SelfSignedData, ServerAuth, ServerConfig};
+    use tokio::io::DuplexStream;
+
+    #[tokio::test]
+    async fn test_export_keying_material_matches_on_both_ends() {
+        let acceptor = TlsAcceptorService::new(
+            ServerConfig::new(ServerAuth::SelfSigned(SelfSignedData::default()))
+                .try_into()
+                .unwrap(),
+            service_fn(|stream: SslStream<DuplexStream>| async move {
+                let material =
+                    stream.export_keying_material("EXPORTER-rama-test", Some(b"context"), 32)?;
+                let no_context_material =
+                    stream.export_keying_material("EXPORTER-rama-test", None, 32)?;
+                Ok::<_, OpaqueError>((material, no_context_material))
+            }),
+            false,
+        );
+
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let config = connector.build().configure().unwrap();
+
+        let (client_io, server_io) = tokio::io::duplex(16 * 1024);
+        let (server, client_stream) = tokio::join!(
+            acceptor.serve(Context::default(), server_io),
+            tokio_boring::connect(config, "localhost", client_io),
+        );
+        let (server_material, server_no_context_material) = server.unwrap();
+        let client_stream = client_stream.unwrap();
+
+        let client_material = client_stream
+            .export_keying_material("EXPORTER-rama-test", Some(b"context"), 32)
+            .unwrap();
+        assert_eq!(server_material.len(), 32);
+        assert_eq!(server_material, client_material);
+
+        let other_material = client_stream
+            .export_keying_material("EXPORTER-rama-other", Some(b"context"), 32)
+            .unwrap();
+        assert_ne!(other_material, client_material);
+
+        let no_context_material = client_stream
+            .export_keying_material("EXPORTER-rama-test", None, 32)
+            .unwrap();
+        assert_eq!(no_context_material, server_no_context_material);
+    }
+}
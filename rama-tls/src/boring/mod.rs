@@ -3,6 +3,8 @@
 pub mod client;
 pub mod server;
 
+mod exporter;
+
 pub mod dep {
     //! Dependencies for rama boring modules.
     //!
@@ -10,6 +10,7 @@ use crate::boring::dep::boring::{
         X509NameBuilder, X509,
     },
 };
+use crate::ocsp::OcspStaple;
 use boring::{
     ssl::{ClientHello, NameType, SelectCertError, SslAcceptorBuilder, SslRef},
     x509::extension::{AuthorityKeyIdentifier, SubjectAlternativeName},
@@ -53,6 +54,39 @@ pub(super) struct TlsConfig {
     pub(super) client_cert_chain: Option<Vec<X509>>,
     /// store client certificate chain if true and client provided this
     pub store_client_certificate_chain: bool,
+    /// optionally staple a static DER-encoded OCSP response
+    pub(super) ocsp: Option<Vec<u8>>,
+    /// optionally staple a (refreshed) OCSP response for as long as it did not expire,
+    /// used instead of the static OCSP response if defined
+    pub(super) ocsp_staple: Option<OcspStaple>,
+}
+
+impl TlsAcceptorData {
+    /// Staple the [`OcspResponse`] of the given [`OcspStaple`], for as long as it did not expire.
+    ///
+    /// The [`OcspStaple`] is shared, so it can be kept fresh by an [`OcspRefresher`]
+    /// while in use. When defined it is used instead of the (static) OCSP response
+    /// of the [`ServerAuthData`].
+    ///
+    /// [`OcspResponse`]: crate::ocsp::OcspResponse
+    /// [`OcspRefresher`]: crate::ocsp::OcspRefresher
+    pub fn with_ocsp_staple(mut self, staple: OcspStaple) -> Self {
+        self.set_ocsp_staple(staple);
+        self
+    }
+
+    /// Staple the [`OcspResponse`] of the given [`OcspStaple`], for as long as it did not expire.
+    ///
+    /// The [`OcspStaple`] is shared, so it can be kept fresh by an [`OcspRefresher`]
+    /// while in use. When defined it is used instead of the (static) OCSP response
+    /// of the [`ServerAuthData`].
+    ///
+    /// [`OcspResponse`]: crate::ocsp::OcspResponse
+    /// [`OcspRefresher`]: crate::ocsp::OcspRefresher
+    pub fn set_ocsp_staple(&mut self, staple: OcspStaple) -> &mut Self {
+        Arc::make_mut(&mut self.config).ocsp_staple = Some(staple);
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -277,6 +311,7 @@ impl TryFrom<rama_net::tls::server::ServerConfig> for TlsAcceptorData {
             ),
         };
 
+        let mut ocsp = None;
         let cert_source_kind = match value.server_auth {
             ServerAuth::SelfSigned(data) => {
                 let issued_cert =
@@ -286,6 +321,7 @@ impl TryFrom<rama_net::tls::server::ServerConfig> for TlsAcceptorData {
             ServerAuth::Single(data) => {
                 // server TLS Certs
                 let issued_cert = server_auth_data_to_private_key_and_ca_chain(&data)?;
+                ocsp = data.ocsp;
 
                 TlsCertSourceKind::InMemory(issued_cert)
             }
@@ -342,6 +378,8 @@ impl TryFrom<rama_net::tls::server::ServerConfig> for TlsAcceptorData {
                 protocol_versions: value.protocol_versions.clone(),
                 client_cert_chain,
                 store_client_certificate_chain: value.store_client_certificate_chain,
+                ocsp,
+                ocsp_staple: None,
             }),
         })
     }
@@ -113,6 +113,28 @@ where
             .issue_certs(acceptor_builder, server_host.cloned(), &maybe_client_hello)
             .await?;
 
+        if let Some(ocsp_staple) = tls_config.ocsp_staple.clone() {
+            trace!("tls boring server service: set ocsp staple status callback");
+            acceptor_builder
+                .set_status_callback(move |ssl| match ocsp_staple.ocsp_response() {
+                    Some(response) => {
+                        ssl.set_ocsp_status(response.der())?;
+                        Ok(true)
+                    }
+                    // no response or expired, do not staple
+                    None => Ok(false),
+                })
+                .context("build boring ssl acceptor: set ocsp status callback")?;
+        } else if let Some(ocsp) = tls_config.ocsp.clone() {
+            trace!("tls boring server service: set static ocsp status callback");
+            acceptor_builder
+                .set_status_callback(move |ssl| {
+                    ssl.set_ocsp_status(&ocsp)?;
+                    Ok(true)
+                })
+                .context("build boring ssl acceptor: set ocsp status callback")?;
+        }
+
         if let Some(min_ver) = tls_config.protocol_versions.iter().flatten().min() {
             acceptor_builder
                 .set_min_proto_version(Some((*min_ver).try_into().map_err(|v| {
@@ -257,3 +279,75 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boring::dep::boring::ssl::{SslConnector, SslVerifyMode, StatusType};
+    use crate::ocsp::{OcspResponse, OcspStaple};
+    use rama_core::service::service_fn;
+    use rama_net::tls::server::{SelfSignedData, ServerAuth, ServerConfig};
+    use std::{
+        convert::Infallible,
+        time::{Duration, SystemTime},
+    };
+
+    const OCSP_RESPONSE: &[u8] = b"\x30\x03\x0a\x01\x00";
+
+    /// Handshake with the given acceptor data, returning the stapled ocsp response.
+    async fn handshake(data: TlsAcceptorData) -> Option<Vec<u8>> {
+        let acceptor = TlsAcceptorService::new(
+            data,
+            service_fn(|_stream: SslStream<tokio::io::DuplexStream>| async {
+                Ok::<_, Infallible>(())
+            }),
+            false,
+        );
+
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let mut config = connector.build().configure().unwrap();
+        config.set_status_type(StatusType::OCSP).unwrap();
+
+        let (client, server) = tokio::io::duplex(16 * 1024);
+        let (server, client) = tokio::join!(
+            acceptor.serve(Context::default(), server),
+            tokio_boring::connect(config, "localhost", client),
+        );
+        server.unwrap();
+        client.unwrap().ssl().ocsp_status().map(<[u8]>::to_vec)
+    }
+
+    fn acceptor_data() -> TlsAcceptorData {
+        ServerConfig::new(ServerAuth::SelfSigned(SelfSignedData::default()))
+            .try_into()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ocsp_response_is_stapled() {
+        let staple = OcspStaple::new().with_ocsp_response(OcspResponse::new(
+            OCSP_RESPONSE,
+            SystemTime::now() + Duration::from_secs(3600),
+        ));
+        let data = acceptor_data().with_ocsp_staple(staple.clone());
+        assert_eq!(
+            handshake(data.clone()).await.as_deref(),
+            Some(OCSP_RESPONSE)
+        );
+
+        staple.clear_ocsp_response();
+        assert!(handshake(data).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_ocsp_response_is_not_stapled() {
+        let staple = OcspStaple::new().with_ocsp_response(OcspResponse::new(
+            OCSP_RESPONSE,
+            SystemTime::now() - Duration::from_secs(1),
+        ));
+        assert!(handshake(acceptor_data().with_ocsp_staple(staple))
+            .await
+            .is_none());
+    }
+}
@@ -0,0 +1,43 @@
+//! TLS keying material exporter support, as defined in [RFC 5705].
+//!
+//! Exported keying material can be used to bind application-layer
+//! authentication to the underlying TLS channel (channel binding).
+//!
+//! [`ExportKeyingMaterial`] is implemented for the TLS streams
+//! of both the rustls and boring backends.
+//!
+//! [RFC 5705]: https://datatracker.ietf.org/doc/html/rfc5705
+
+use rama_core::error::OpaqueError;
+
+/// A TLS connection which can export keying material, as defined in [RFC 5705].
+///
+/// Both ends of a connection derive the same keying material
+/// for the same `label` and `context`, which makes it suitable
+/// for application-layer channel binding.
+///
+/// [RFC 5705]: https://datatracker.ietf.org/doc/html/rfc5705
+pub trait ExportKeyingMaterial {
+    /// Derive `length` bytes of keying material from the TLS session,
+    /// using the given `label` and optional `context`.
+    ///
+    /// Returns an error in case the connection is not secured (yet),
+    /// or the material could not be derived by the TLS backend.
+    fn export_keying_material(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        length: usize,
+    ) -> Result<Vec<u8>, OpaqueError>;
+}
+
+impl<T: ExportKeyingMaterial> ExportKeyingMaterial for &T {
+    fn export_keying_material(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        length: usize,
+    ) -> Result<Vec<u8>, OpaqueError> {
+        (**self).export_keying_material(label, context, length)
+    }
+}
@@ -29,7 +29,9 @@ pub use rustls as std;
 #[cfg(feature = "boring")]
 pub use boring as std;
 
+pub mod exporter;
 pub mod keylog;
+pub mod ocsp;
 
 pub mod types {
     //! common tls types
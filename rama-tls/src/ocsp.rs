@@ -0,0 +1,302 @@
+//! OCSP stapling facility used by the tls server implementations supported by rama.
+//!
+//! An [`OcspResponse`] is stapled to the server certificate during the handshake
+//! for as long as it is valid, and dropped once expired, as stapling an expired
+//! response is worse than not stapling one at all.
+//!
+//! The [`OcspRefresher`] keeps the stapled response fresh in the background,
+//! using an [`OcspResponder`] to fetch a new one before the current one expires.
+//! It can update any [`OcspStapler`], such as the [`OcspStaple`] used by the
+//! boring acceptor, or the `OcspStaplingCertResolver` of the rustls backend.
+
+use parking_lot::RwLock;
+use rama_core::error::OpaqueError;
+use std::{
+    fmt,
+    future::Future,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+#[derive(Clone)]
+/// A DER-encoded OCSP response, to be stapled to the server certificate.
+///
+/// The `next_update` time is the time at which the response expires,
+/// which is the `nextUpdate` field of the OCSP response itself.
+pub struct OcspResponse {
+    der: Vec<u8>,
+    next_update: SystemTime,
+}
+
+impl OcspResponse {
+    /// Create a new [`OcspResponse`] from the DER-encoded response
+    /// and the time at which it expires.
+    pub fn new(der: impl Into<Vec<u8>>, next_update: SystemTime) -> Self {
+        Self {
+            der: der.into(),
+            next_update,
+        }
+    }
+
+    /// The DER-encoded OCSP response.
+    pub fn der(&self) -> &[u8] {
+        &self.der
+    }
+
+    /// Consume the [`OcspResponse`], returning the DER-encoded OCSP response.
+    pub fn into_der(self) -> Vec<u8> {
+        self.der
+    }
+
+    /// The time at which the OCSP response expires.
+    pub fn next_update(&self) -> SystemTime {
+        self.next_update
+    }
+
+    /// Returns `true` if the OCSP response is expired at the given time.
+    pub fn is_expired_at(&self, time: SystemTime) -> bool {
+        time >= self.next_update
+    }
+}
+
+impl fmt::Debug for OcspResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OcspResponse")
+            .field("der_len", &self.der.len())
+            .field("next_update", &self.next_update)
+            .finish()
+    }
+}
+
+/// A stapler of [`OcspResponse`]s, which can be kept fresh by an [`OcspRefresher`].
+pub trait OcspStapler: Send + Sync + 'static {
+    /// Staple the given [`OcspResponse`] from now on, until it expires,
+    /// replacing the previous one (if any).
+    fn update_ocsp_response(&self, response: OcspResponse);
+}
+
+impl<T: OcspStapler> OcspStapler for Arc<T> {
+    fn update_ocsp_response(&self, response: OcspResponse) {
+        (**self).update_ocsp_response(response)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A shared [`OcspResponse`] to be stapled by a tls acceptor,
+/// for as long as it did not expire.
+///
+/// Clones share the same [`OcspResponse`], such that it can be updated
+/// at any time, e.g. by an [`OcspRefresher`], while in use by an acceptor.
+pub struct OcspStaple {
+    response: Arc<RwLock<Option<OcspResponse>>>,
+}
+
+impl OcspStaple {
+    /// Create a new [`OcspStaple`], without an [`OcspResponse`] to staple (yet).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Staple the given [`OcspResponse`] from now on, until it expires.
+    pub fn with_ocsp_response(self, response: OcspResponse) -> Self {
+        self.update_ocsp_response(response);
+        self
+    }
+
+    /// Staple the given [`OcspResponse`] from now on, until it expires,
+    /// replacing the previous one (if any).
+    pub fn update_ocsp_response(&self, response: OcspResponse) {
+        *self.response.write() = Some(response);
+    }
+
+    /// Stop stapling an [`OcspResponse`].
+    pub fn clear_ocsp_response(&self) {
+        *self.response.write() = None;
+    }
+
+    /// Returns the time at which the current
+    /// [`OcspResponse`] expires, if there is one.
+    pub fn ocsp_next_update(&self) -> Option<SystemTime> {
+        self.response
+            .read()
+            .as_ref()
+            .map(|response| response.next_update)
+    }
+
+    /// Returns the [`OcspResponse`] to staple right now,
+    /// `None` if there is none or it expired.
+    pub fn ocsp_response(&self) -> Option<OcspResponse> {
+        let response = self.response.read();
+        let response = response.as_ref()?;
+        if response.is_expired_at(SystemTime::now()) {
+            tracing::debug!(
+                next_update = ?response.next_update,
+                "OcspStaple: drop expired ocsp response staple",
+            );
+            return None;
+        }
+        Some(response.clone())
+    }
+}
+
+impl OcspStapler for OcspStaple {
+    fn update_ocsp_response(&self, response: OcspResponse) {
+        OcspStaple::update_ocsp_response(self, response)
+    }
+}
+
+/// A source of (fresh) [`OcspResponse`]s, used by the [`OcspRefresher`].
+///
+/// E.g. an implementation which requests the OCSP response
+/// from the OCSP responder of the certificate issuer.
+pub trait OcspResponder: Send + Sync + 'static {
+    /// Fetch a fresh [`OcspResponse`].
+    fn fetch_ocsp_response(
+        &self,
+    ) -> impl Future<Output = Result<OcspResponse, OpaqueError>> + Send + '_;
+}
+
+/// Keeps the [`OcspResponse`] of an [`OcspStapler`] fresh,
+/// by fetching a new one using an [`OcspResponder`] before the current one expires.
+///
+/// Use [`OcspRefresher::run`] to get the future which does so,
+/// to be spawned as a background task.
+pub struct OcspRefresher<R, S> {
+    stapler: S,
+    responder: R,
+    refresh_before: Duration,
+    retry_interval: Duration,
+}
+
+impl<R: fmt::Debug, S: fmt::Debug> fmt::Debug for OcspRefresher<R, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OcspRefresher")
+            .field("stapler", &self.stapler)
+            .field("responder", &self.responder)
+            .field("refresh_before", &self.refresh_before)
+            .field("retry_interval", &self.retry_interval)
+            .finish()
+    }
+}
+
+impl<R, S> OcspRefresher<R, S> {
+    /// Default time before the expiry of the stapled [`OcspResponse`]
+    /// at which a fresh one is fetched.
+    pub const DEFAULT_REFRESH_BEFORE: Duration = Duration::from_secs(60 * 60);
+
+    /// Default time to wait before retrying to fetch an [`OcspResponse`]
+    /// in case the previous attempt failed.
+    pub const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+    /// Create a new [`OcspRefresher`] updating the [`OcspResponse`]
+    /// of the given [`OcspStapler`] using the given [`OcspResponder`].
+    pub fn new(stapler: S, responder: R) -> Self {
+        Self {
+            stapler,
+            responder,
+            refresh_before: Self::DEFAULT_REFRESH_BEFORE,
+            retry_interval: Self::DEFAULT_RETRY_INTERVAL,
+        }
+    }
+
+    /// Set the time before the expiry of the stapled [`OcspResponse`]
+    /// at which a fresh one is fetched.
+    ///
+    /// Defaults to [`Self::DEFAULT_REFRESH_BEFORE`].
+    pub fn with_refresh_before(mut self, refresh_before: Duration) -> Self {
+        self.refresh_before = refresh_before;
+        self
+    }
+
+    /// Set the time before the expiry of the stapled [`OcspResponse`]
+    /// at which a fresh one is fetched.
+    ///
+    /// Defaults to [`Self::DEFAULT_REFRESH_BEFORE`].
+    pub fn set_refresh_before(&mut self, refresh_before: Duration) -> &mut Self {
+        self.refresh_before = refresh_before;
+        self
+    }
+
+    /// Set the time to wait before retrying to fetch an [`OcspResponse`]
+    /// in case the previous attempt failed.
+    ///
+    /// Defaults to [`Self::DEFAULT_RETRY_INTERVAL`].
+    pub fn with_retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// Set the time to wait before retrying to fetch an [`OcspResponse`]
+    /// in case the previous attempt failed.
+    ///
+    /// Defaults to [`Self::DEFAULT_RETRY_INTERVAL`].
+    pub fn set_retry_interval(&mut self, retry_interval: Duration) -> &mut Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+}
+
+impl<R: OcspResponder, S: OcspStapler> OcspRefresher<R, S> {
+    /// Keep the [`OcspResponse`] fresh, forever.
+    ///
+    /// A fresh [`OcspResponse`] is fetched immediately,
+    /// and from then on each time the stapled one is about to expire.
+    pub async fn run(self) {
+        loop {
+            let wait = match self.responder.fetch_ocsp_response().await {
+                Ok(response) => {
+                    let now = SystemTime::now();
+                    if response.is_expired_at(now) {
+                        tracing::debug!(
+                            next_update = ?response.next_update,
+                            "OcspRefresher: ignore fetched ocsp response which is already expired",
+                        );
+                        self.retry_interval
+                    } else {
+                        let next_update = response.next_update;
+                        self.stapler.update_ocsp_response(response);
+                        tracing::trace!(?next_update, "OcspRefresher: ocsp response updated");
+                        next_update
+                            .checked_sub(self.refresh_before)
+                            .and_then(|refresh_at| refresh_at.duration_since(now).ok())
+                            .unwrap_or_default()
+                            .max(self.retry_interval)
+                    }
+                }
+                Err(err) => {
+                    tracing::debug!(%err, "OcspRefresher: failed to fetch ocsp response");
+                    self.retry_interval
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ocsp_staple_drops_expired_response() {
+        let staple = OcspStaple::new();
+        assert!(staple.ocsp_response().is_none());
+
+        let shared = staple.clone();
+        shared.update_ocsp_response(OcspResponse::new(
+            b"fresh".to_vec(),
+            SystemTime::now() + Duration::from_secs(3600),
+        ));
+        assert_eq!(staple.ocsp_response().unwrap().der(), b"fresh");
+
+        shared.update_ocsp_response(OcspResponse::new(
+            b"expired".to_vec(),
+            SystemTime::now() - Duration::from_secs(1),
+        ));
+        assert!(staple.ocsp_response().is_none());
+        assert!(staple.ocsp_next_update().is_some());
+
+        shared.clear_ocsp_response();
+        assert!(staple.ocsp_next_update().is_none());
+    }
+}
@@ -0,0 +1,99 @@
+//! Certificate chain building and validation.
+//!
+//! Re-exports the [`CertificateChainVerifier`] of [`rama_crypto::x509`],
+//! together with helpers to create one verifying signatures
+//! using the rustls [`CryptoProvider`].
+//!
+//! # Example
+//!
+//! ```
+//! use rama_tls::rustls::chain;
+//! use rama_tls::rustls::dep::pki_types::CertificateDer;
+//!
+//! # fn verify(root: CertificateDer<'static>, leaf: CertificateDer<'static>, intermediates: Vec<CertificateDer<'static>>) -> Result<(), Box<dyn std::error::Error>> {
+//! let verifier = chain::verifier([root])?;
+//! let verified = verifier.verify(&leaf, &intermediates, None)?;
+//! println!("valid until {:?}", verified.leaf_info().not_after());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::rustls::dep::pki_types::CertificateDer;
+use crate::rustls::dep::rustls::{crypto::CryptoProvider, ClientConfig};
+use rama_core::error::OpaqueError;
+
+#[doc(inline)]
+pub use rama_crypto::x509::{
+    CertificateChainError, CertificateChainVerifier, CertificateInfo, KeyUsage, SubjectAltName,
+    VerifiedCertificateChain,
+};
+
+/// Create a [`CertificateChainVerifier`] trusting the given root certificates,
+/// verifying signatures using the algorithms of the configured [`CryptoProvider`].
+///
+/// This is the process-level default installed using [`CryptoProvider::install_default`],
+/// or otherwise the provider selected by the enabled rustls crate features,
+/// the same provider rustls uses to build its client and server configs.
+///
+/// # Panics
+///
+/// Panics in case no process-level default is installed and none can be
+/// selected from the crate features, as is the case for rustls itself.
+pub fn verifier(
+    roots: impl IntoIterator<Item = CertificateDer<'static>>,
+) -> Result<CertificateChainVerifier, OpaqueError> {
+    verifier_with_provider(roots, ClientConfig::builder().crypto_provider())
+}
+
+/// Create a [`CertificateChainVerifier`] trusting the given root certificates,
+/// verifying signatures using the algorithms of the given [`CryptoProvider`].
+pub fn verifier_with_provider(
+    roots: impl IntoIterator<Item = CertificateDer<'static>>,
+    provider: &CryptoProvider,
+) -> Result<CertificateChainVerifier, OpaqueError> {
+    CertificateChainVerifier::new(roots, provider.signature_verification_algorithms.all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rustls::dep::pki_types::ServerName;
+    use crate::rustls::dep::rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+    use crate::rustls::dep::rustls::crypto::aws_lc_rs;
+
+    #[test]
+    fn test_verifier_with_provider() {
+        let root_key = KeyPair::generate().unwrap();
+        let mut root_params = CertificateParams::new(Vec::new()).unwrap();
+        root_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let root = root_params.self_signed(&root_key).unwrap();
+
+        let leaf_key = KeyPair::generate().unwrap();
+        let leaf = CertificateParams::new(vec!["example.com".to_owned()])
+            .unwrap()
+            .signed_by(&leaf_key, &root, &root_key)
+            .unwrap();
+
+        let name = ServerName::try_from("example.com").unwrap();
+        for verifier in [
+            verifier([root.der().clone()]).unwrap(),
+            verifier_with_provider([root.der().clone()], &aws_lc_rs::default_provider()).unwrap(),
+        ] {
+            let verified = verifier.verify(leaf.der(), &[], Some(&name)).unwrap();
+            assert_eq!(verified.chain(), &[leaf.der().clone(), root.der().clone()]);
+            assert_eq!(
+                verified.leaf_info().subject_alt_names(),
+                &[SubjectAltName::Dns("example.com".to_owned())]
+            );
+        }
+
+        // a provider without any signature algorithms cannot verify anything
+        let mut provider = aws_lc_rs::default_provider();
+        provider.signature_verification_algorithms.all = &[];
+        let err = verifier_with_provider([root.der().clone()], &provider)
+            .unwrap()
+            .verify(leaf.der(), &[], None)
+            .unwrap_err();
+        assert!(matches!(err, CertificateChainError::Invalid(_)), "{err}");
+    }
+}
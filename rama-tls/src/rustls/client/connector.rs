@@ -1,19 +1,27 @@
 use super::TlsConnectorData;
+use crate::exporter::ExportKeyingMaterial;
+use crate::rustls::dep::pki_types::ServerName;
+use crate::rustls::dep::rustls::client::{ClientSessionMemoryCache, Resumption};
 use crate::rustls::dep::tokio_rustls::{client::TlsStream, TlsConnector as RustlsConnector};
 use crate::types::TlsTunnel;
+use parking_lot::Mutex;
 use pin_project_lite::pin_project;
 use private::{ConnectorKindAuto, ConnectorKindSecure, ConnectorKindTunnel};
 use rama_core::error::ErrorContext;
 use rama_core::error::{BoxError, ErrorExt, OpaqueError};
 use rama_core::{Context, Layer, Service};
 use rama_net::address::Host;
-use rama_net::client::{ConnectorService, EstablishedClientConnection};
+use rama_net::client::{
+    ConnectTimings, ConnectorService, EstablishedClientConnection, PhaseTiming,
+};
 use rama_net::stream::Stream;
-use rama_net::tls::client::NegotiatedTlsParameters;
+use rama_net::tls::client::{EarlyDataSafe, NegotiatedTlsParameters};
 use rama_net::tls::ApplicationProtocol;
 use rama_net::transport::TryRefIntoTransportContext;
+use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::io::{AsyncRead, AsyncWrite};
 
 /// A [`Layer`] which wraps the given service with a [`TlsConnector`].
@@ -21,6 +29,7 @@ use tokio::io::{AsyncRead, AsyncWrite};
 /// See [`TlsConnector`] for more information.
 pub struct TlsConnectorLayer<K = ConnectorKindAuto> {
     connector_data: Option<TlsConnectorData>,
+    early_data: Option<Arc<EarlyDataState>>,
     kind: K,
 }
 
@@ -28,6 +37,7 @@ impl<K: fmt::Debug> std::fmt::Debug for TlsConnectorLayer<K> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TlsConnectorLayer")
             .field("connector_data", &self.connector_data)
+            .field("early_data", &self.early_data.is_some())
             .field("kind", &self.kind)
             .finish()
     }
@@ -37,12 +47,31 @@ impl<K: Clone> Clone for TlsConnectorLayer<K> {
     fn clone(&self) -> Self {
         Self {
             connector_data: self.connector_data.clone(),
+            early_data: self.early_data.clone(),
             kind: self.kind.clone(),
         }
     }
 }
 
 impl<K> TlsConnectorLayer<K> {
+    /// Enable (or disable) TLS 1.3 early data (0-RTT) for the [`TlsConnector`]s
+    /// created by this layer, see [`TlsConnector::with_early_data`].
+    ///
+    /// All connectors created by this layer share the same session store.
+    pub fn with_early_data(mut self, enabled: bool) -> Self {
+        self.early_data = enabled.then(Default::default);
+        self
+    }
+
+    /// Enable (or disable) TLS 1.3 early data (0-RTT) for the [`TlsConnector`]s
+    /// created by this layer, see [`TlsConnector::with_early_data`].
+    ///
+    /// All connectors created by this layer share the same session store.
+    pub fn set_early_data(&mut self, enabled: bool) -> &mut Self {
+        self.early_data = enabled.then(Default::default);
+        self
+    }
+
     /// Attach [`TlsConnectorData`] to this [`TlsConnectorLayer`],
     /// to be used instead of a globally shared [`TlsConnectorData::default`].
     pub fn with_connector_data(mut self, connector_data: TlsConnectorData) -> Self {
@@ -72,6 +101,7 @@ impl TlsConnectorLayer<ConnectorKindAuto> {
     pub fn auto() -> Self {
         Self {
             connector_data: None,
+            early_data: None,
             kind: ConnectorKindAuto,
         }
     }
@@ -83,6 +113,7 @@ impl TlsConnectorLayer<ConnectorKindSecure> {
     pub fn secure() -> Self {
         Self {
             connector_data: None,
+            early_data: None,
             kind: ConnectorKindSecure,
         }
     }
@@ -94,6 +125,7 @@ impl TlsConnectorLayer<ConnectorKindTunnel> {
     pub fn tunnel(host: Option<Host>) -> Self {
         Self {
             connector_data: None,
+            early_data: None,
             kind: ConnectorKindTunnel { host },
         }
     }
@@ -106,6 +138,7 @@ impl<K: Clone, S> Layer<S> for TlsConnectorLayer<K> {
         TlsConnector {
             inner,
             connector_data: self.connector_data.clone(),
+            early_data: self.early_data.clone(),
             kind: self.kind.clone(),
         }
     }
@@ -124,9 +157,12 @@ impl Default for TlsConnectorLayer<ConnectorKindAuto> {
 /// only if the request requires a secure connection. You can instead use
 /// [`TlsConnector::secure_only`] to force the connector to always
 /// establish a secure connection.
+///
+/// TLS 1.3 early data (0-RTT) can be enabled using [`TlsConnector::with_early_data`].
 pub struct TlsConnector<S, K = ConnectorKindAuto> {
     inner: S,
     connector_data: Option<TlsConnectorData>,
+    early_data: Option<Arc<EarlyDataState>>,
     kind: K,
 }
 
@@ -135,6 +171,7 @@ impl<S: fmt::Debug, K: fmt::Debug> fmt::Debug for TlsConnector<S, K> {
         f.debug_struct("TlsConnector")
             .field("inner", &self.inner)
             .field("connector_data", &self.connector_data)
+            .field("early_data", &self.early_data.is_some())
             .field("kind", &self.kind)
             .finish()
     }
@@ -145,6 +182,7 @@ impl<S: Clone, K: Clone> Clone for TlsConnector<S, K> {
         Self {
             inner: self.inner.clone(),
             connector_data: self.connector_data.clone(),
+            early_data: self.early_data.clone(),
             kind: self.kind.clone(),
         }
     }
@@ -156,10 +194,40 @@ impl<S, K> TlsConnector<S, K> {
         Self {
             inner,
             connector_data: None,
+            early_data: None,
             kind,
         }
     }
 
+    /// Enable (or disable) TLS 1.3 early data (0-RTT) for this [`TlsConnector`].
+    ///
+    /// When enabled, sessions are stored (in memory) to be resumed
+    /// by future connections of this connector (and its clones).
+    /// Early data is only used for requests that are marked
+    /// with [`EarlyDataSafe`] in their [`Context`], and only
+    /// in case a resumable session is available for the server.
+    ///
+    /// Early data can be replayed by an attacker, so only mark
+    /// requests which are idempotent and safe to be processed more than once.
+    /// See [`EarlyDataSafe`] for more information.
+    ///
+    /// When early data is used the handshake completes only once the first
+    /// data is written, and so the [`NegotiatedTlsParameters`] are those of the
+    /// session that is resumed. Should the server reject the early data,
+    /// it is resent after the full handshake, which might negotiate other parameters.
+    pub fn with_early_data(mut self, enabled: bool) -> Self {
+        self.early_data = enabled.then(Default::default);
+        self
+    }
+
+    /// Enable (or disable) TLS 1.3 early data (0-RTT) for this [`TlsConnector`].
+    ///
+    /// See [`TlsConnector::with_early_data`] for more information.
+    pub fn set_early_data(&mut self, enabled: bool) -> &mut Self {
+        self.early_data = enabled.then(Default::default);
+        self
+    }
+
     /// Attach [`TlsConnectorData`] to this [`TlsConnector`],
     /// to be used instead of a globally shared [`TlsConnectorData::default`].
     ///
@@ -273,8 +341,12 @@ where
             "TlsConnector(auto): attempt to secure inner connection",
         );
 
+        let tls_start = Instant::now();
         let connector_data = ctx.get().cloned();
-        let (stream, negotiated_params) = self.handshake(connector_data, server_host, conn).await?;
+        let early_data_safe = ctx.contains::<EarlyDataSafe>();
+        let (stream, negotiated_params) = self
+            .handshake(connector_data, early_data_safe, server_host, conn)
+            .await?;
 
         tracing::trace!(
             authority = %transport_ctx.authority,
@@ -283,6 +355,7 @@ where
         );
 
         ctx.insert(negotiated_params);
+        ctx.get_or_insert_default::<ConnectTimings>().tls = Some(PhaseTiming::until_now(tls_start));
 
         Ok(EstablishedClientConnection {
             ctx,
@@ -332,9 +405,14 @@ where
 
         let server_host = transport_ctx.authority.host().clone();
 
+        let tls_start = Instant::now();
         let connector_data = ctx.get().cloned();
-        let (conn, negotiated_params) = self.handshake(connector_data, server_host, conn).await?;
+        let early_data_safe = ctx.contains::<EarlyDataSafe>();
+        let (conn, negotiated_params) = self
+            .handshake(connector_data, early_data_safe, server_host, conn)
+            .await?;
         ctx.insert(negotiated_params);
+        ctx.get_or_insert_default::<ConnectTimings>().tls = Some(PhaseTiming::until_now(tls_start));
 
         Ok(EstablishedClientConnection {
             ctx,
@@ -388,9 +466,14 @@ where
             }
         };
 
+        let tls_start = Instant::now();
         let connector_data = ctx.get().cloned();
-        let (conn, negotiated_params) = self.handshake(connector_data, server_host, conn).await?;
+        let early_data_safe = ctx.contains::<EarlyDataSafe>();
+        let (conn, negotiated_params) = self
+            .handshake(connector_data, early_data_safe, server_host, conn)
+            .await?;
         ctx.insert(negotiated_params);
+        ctx.get_or_insert_default::<ConnectTimings>().tls = Some(PhaseTiming::until_now(tls_start));
 
         tracing::trace!("TlsConnector(tunnel): connection secured");
         Ok(EstablishedClientConnection {
@@ -408,6 +491,7 @@ impl<S, K> TlsConnector<S, K> {
     async fn handshake<T>(
         &self,
         connector_data: Option<TlsConnectorData>,
+        early_data_safe: bool,
         server_host: Host,
         stream: T,
     ) -> Result<(TlsStream<T>, NegotiatedTlsParameters), BoxError>
@@ -419,16 +503,35 @@ impl<S, K> TlsConnector<S, K> {
             Some(connector_data) => connector_data.try_to_build_config()?,
             None => TlsConnectorData::new_http_auto()?.try_to_build_config()?,
         };
-        let server_name = rustls_pki_types::ServerName::try_from(
-            client_config_data.server_name.unwrap_or(server_host),
-        )?;
+        let server_name =
+            ServerName::try_from(client_config_data.server_name.unwrap_or(server_host))?;
+
+        let mut config = client_config_data.config;
+        let mut early_data_params = None;
+        if let Some(early_data) = self.early_data.as_ref() {
+            config.resumption = Resumption::store(early_data.session_store.clone());
+            if early_data_safe {
+                // only sessions resumed from a previous connection can send early data
+                early_data_params = early_data.negotiated_params(&server_name);
+                config.enable_early_data = early_data_params.is_some();
+            }
+        }
 
-        let connector = RustlsConnector::from(Arc::new(client_config_data.config));
+        let connector =
+            RustlsConnector::from(Arc::new(config)).early_data(early_data_params.is_some());
 
-        let stream = connector.connect(server_name, stream).await?;
+        let stream = connector.connect(server_name.clone(), stream).await?;
 
         let (_, conn_data_ref) = stream.get_ref();
 
+        if conn_data_ref.is_handshaking() {
+            if let Some(params) = early_data_params {
+                // handshake completes once the early data is written
+                tracing::trace!(?server_name, "TlsConnector: early data (0-RTT) used");
+                return Ok((stream, params));
+            }
+        }
+
         let store_server_cert_chain = connector_data
             .is_some_and(|data| data.client_config_input.store_server_certificate_chain);
 
@@ -449,10 +552,57 @@ impl<S, K> TlsConnector<S, K> {
             peer_certificate_chain: server_certificate_chain,
         };
 
+        if let Some(early_data) = self.early_data.as_ref() {
+            early_data.set_negotiated_params(server_name, params.clone());
+        }
+
         Ok((stream, params))
     }
 }
 
+/// Amount of servers for which sessions are remembered, for early data purposes.
+const EARLY_DATA_SESSION_CAPACITY: usize = 256;
+
+/// State shared by a [`TlsConnector`] and its clones, to support early data.
+///
+/// The negotiated parameters of the last full handshake are remembered,
+/// as these are not known yet when the connection is returned during early data.
+struct EarlyDataState {
+    session_store: Arc<ClientSessionMemoryCache>,
+    negotiated_params: Mutex<HashMap<ServerName<'static>, NegotiatedTlsParameters>>,
+}
+
+impl Default for EarlyDataState {
+    fn default() -> Self {
+        Self {
+            session_store: Arc::new(ClientSessionMemoryCache::new(EARLY_DATA_SESSION_CAPACITY)),
+            negotiated_params: Default::default(),
+        }
+    }
+}
+
+impl EarlyDataState {
+    fn negotiated_params(
+        &self,
+        server_name: &ServerName<'static>,
+    ) -> Option<NegotiatedTlsParameters> {
+        self.negotiated_params.lock().get(server_name).cloned()
+    }
+
+    fn set_negotiated_params(
+        &self,
+        server_name: ServerName<'static>,
+        params: NegotiatedTlsParameters,
+    ) {
+        let mut map = self.negotiated_params.lock();
+        if map.len() >= EARLY_DATA_SESSION_CAPACITY && !map.contains_key(&server_name) {
+            // keep it simple, the session store will have evicted most of these sessions as well
+            map.clear();
+        }
+        map.insert(server_name, params);
+    }
+}
+
 pin_project! {
     /// A stream which can be either a secure or a plain stream.
     pub struct AutoTlsStream<S> {
@@ -489,6 +639,24 @@ impl<S: fmt::Debug> fmt::Debug for AutoTlsStreamData<S> {
     }
 }
 
+impl<S> ExportKeyingMaterial for AutoTlsStream<S> {
+    fn export_keying_material(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        length: usize,
+    ) -> Result<Vec<u8>, OpaqueError> {
+        match &self.inner {
+            AutoTlsStreamData::Secure { inner } => {
+                inner.export_keying_material(label, context, length)
+            }
+            AutoTlsStreamData::Plain { .. } => Err(OpaqueError::from_display(
+                "cannot export keying material from a plain (non-tls) stream",
+            )),
+        }
+    }
+}
+
 impl<S> AsyncRead for AutoTlsStream<S>
 where
     S: Stream + Unpin,
@@ -590,4 +758,190 @@ mod tests {
 
         assert_sync::<TlsConnectorLayer>();
     }
+
+    /// Spawn a rustls server which replies with `EARLY:<early data>` (if any),
+    /// followed by `LATE:` and an echo of the regular data.
+    fn spawn_early_data_server() -> std::net::SocketAddr {
+        use crate::rustls::dep::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer};
+        use crate::rustls::dep::rcgen;
+        use crate::rustls::dep::rustls::{ServerConfig, ServerConnection, Stream};
+        use std::io::{Read, Write};
+
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+        let mut server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![cert.der().clone()],
+                PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key_pair.serialize_der())),
+            )
+            .unwrap();
+        server_config.max_early_data_size = 8192;
+        let server_config = Arc::new(server_config);
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || loop {
+            let (mut sock, _) = listener.accept().unwrap();
+            let server_config = server_config.clone();
+            std::thread::spawn(move || {
+                let mut conn = ServerConnection::new(server_config).unwrap();
+                conn.complete_io(&mut sock).unwrap();
+
+                if let Some(mut early_data) = conn.early_data() {
+                    let mut buf = Vec::new();
+                    early_data.read_to_end(&mut buf).unwrap();
+                    let mut stream = Stream::new(&mut conn, &mut sock);
+                    stream.write_all(b"EARLY:").unwrap();
+                    stream.write_all(&buf).unwrap();
+                }
+
+                let mut stream = Stream::new(&mut conn, &mut sock);
+                stream.write_all(b"LATE:").unwrap();
+                loop {
+                    let mut buf = [0; 1024];
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        conn.send_close_notify();
+                        conn.complete_io(&mut sock).unwrap();
+                        break;
+                    }
+                    stream.write_all(&buf[..n]).unwrap();
+                }
+            });
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_early_data_only_for_marked_requests() {
+        use rama_core::service::service_fn;
+        use rama_net::tls::client::{ClientConfig, ServerVerifyMode};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        let server_addr = spawn_early_data_server();
+
+        let connector = TlsConnector::secure(service_fn(
+            move |ctx: Context<()>, req: rama_http_types::Request<()>| async move {
+                let conn = TcpStream::connect(server_addr).await?;
+                Ok::<_, std::io::Error>(EstablishedClientConnection {
+                    ctx,
+                    req,
+                    conn,
+                    addr: server_addr,
+                })
+            },
+        ))
+        .with_connector_data(
+            ClientConfig {
+                server_verify_mode: Some(ServerVerifyMode::Disable),
+                ..Default::default()
+            }
+            .try_into()
+            .unwrap(),
+        )
+        .with_early_data(true);
+
+        let send = |early_data_safe: bool| {
+            let connector = connector.clone();
+            async move {
+                let mut ctx = Context::default();
+                if early_data_safe {
+                    ctx.insert(EarlyDataSafe::new());
+                }
+                let req = rama_http_types::Request::builder()
+                    .uri(format!("https://localhost:{}", server_addr.port()))
+                    .body(())
+                    .unwrap();
+
+                let mut conn = connector.serve(ctx, req).await.unwrap().conn;
+                conn.write_all(b"hello").await.unwrap();
+                conn.flush().await.unwrap();
+                conn.shutdown().await.unwrap();
+
+                let mut buf = Vec::new();
+                conn.read_to_end(&mut buf).await.unwrap();
+                (
+                    String::from_utf8(buf).unwrap(),
+                    conn.get_ref().1.is_early_data_accepted(),
+                )
+            }
+        };
+
+        // no resumable session yet, so no early data can be sent
+        assert_eq!(send(true).await, ("LATE:hello".to_owned(), false));
+
+        // resumed session and marked request: early data
+        assert_eq!(send(true).await, ("EARLY:helloLATE:".to_owned(), true));
+
+        // resumable session available, but request is not marked: no early data
+        assert_eq!(send(false).await, ("LATE:hello".to_owned(), false));
+    }
+
+    #[tokio::test]
+    async fn test_connect_timings_recorded_through_stack() {
+        use crate::rustls::dep::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer};
+        use crate::rustls::dep::rcgen;
+        use crate::rustls::dep::rustls::ServerConfig;
+        use crate::rustls::dep::tokio_rustls::TlsAcceptor;
+        use rama_dns::InMemoryDns;
+        use rama_net::address::Domain;
+        use rama_net::tls::client::{ClientConfig, ServerVerifyMode};
+        use rama_tcp::client::service::TcpConnector;
+        use std::net::{IpAddr, Ipv4Addr};
+        use tokio::net::TcpListener;
+
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["example.com".to_owned()]).unwrap();
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![cert.der().clone()],
+                PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key_pair.serialize_der())),
+            )
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let _stream = acceptor.accept(stream).await.unwrap();
+        });
+
+        let mut dns = InMemoryDns::new();
+        dns.insert_addresses(
+            Domain::from_static("example.com"),
+            [IpAddr::V4(Ipv4Addr::LOCALHOST)],
+        );
+
+        let connector = TlsConnector::secure(TcpConnector::new().with_dns(dns))
+            .with_connector_data(
+                ClientConfig {
+                    server_verify_mode: Some(ServerVerifyMode::Disable),
+                    ..Default::default()
+                }
+                .try_into()
+                .unwrap(),
+            );
+
+        let req = rama_http_types::Request::builder()
+            .uri(format!("https://example.com:{port}"))
+            .body(())
+            .unwrap();
+        let EstablishedClientConnection { ctx, .. } =
+            connector.serve(Context::default(), req).await.unwrap();
+
+        let timings = ctx.get::<ConnectTimings>().unwrap();
+        let dns = timings.dns.unwrap();
+        let tcp_connect = timings.tcp_connect.unwrap();
+        let tls = timings.tls.unwrap();
+
+        assert!(dns.start <= dns.end);
+        assert!(dns.end <= tcp_connect.start);
+        assert!(tcp_connect.start <= tcp_connect.end);
+        assert!(tcp_connect.end <= tls.start);
+        assert!(tls.start <= tls.end);
+    }
 }
@@ -0,0 +1,93 @@
+use crate::exporter::ExportKeyingMaterial;
+use crate::rustls::dep::tokio_rustls::{client, server};
+use rama_core::error::{ErrorContext, OpaqueError};
+
+impl<S> ExportKeyingMaterial for client::TlsStream<S> {
+    fn export_keying_material(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        length: usize,
+    ) -> Result<Vec<u8>, OpaqueError> {
+        self.get_ref()
+            .1
+            .export_keying_material(vec![0; length], label.as_bytes(), context)
+            .context("rustls client: export keying material")
+    }
+}
+
+impl<S> ExportKeyingMaterial for server::TlsStream<S> {
+    fn export_keying_material(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        length: usize,
+    ) -> Result<Vec<u8>, OpaqueError> {
+        self.get_ref()
+            .1
+            .export_keying_material(vec![0; length], label.as_bytes(), context)
+            .context("rustls server: export keying material")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rustls::dep::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer, ServerName};
+    use crate::rustls::dep::rcgen;
+    use crate::rustls::dep::rustls::{ClientConfig, ServerConfig};
+    use crate::rustls::dep::tokio_rustls::{TlsAcceptor, TlsConnector};
+    use crate::rustls::verify::NoServerCertVerifier;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_export_keying_material_matches_on_both_ends() {
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![cert.der().clone()],
+                PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key_pair.serialize_der())),
+            )
+            .unwrap();
+        let client_config = ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoServerCertVerifier::new()))
+            .with_no_client_auth();
+
+        let (client_io, server_io) = tokio::io::duplex(16 * 1024);
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+        let connector = TlsConnector::from(Arc::new(client_config));
+
+        let (server_stream, client_stream) = tokio::join!(
+            acceptor.accept(server_io),
+            connector.connect(ServerName::try_from("localhost").unwrap(), client_io),
+        );
+        let (server_stream, client_stream) = (server_stream.unwrap(), client_stream.unwrap());
+
+        let server_material = server_stream
+            .export_keying_material("EXPORTER-rama-test", Some(b"context"), 32)
+            .unwrap();
+        let client_material = client_stream
+            .export_keying_material("EXPORTER-rama-test", Some(b"context"), 32)
+            .unwrap();
+        assert_eq!(server_material.len(), 32);
+        assert_eq!(server_material, client_material);
+
+        let other_material = client_stream
+            .export_keying_material("EXPORTER-rama-other", Some(b"context"), 32)
+            .unwrap();
+        assert_ne!(other_material, client_material);
+
+        let no_context_material = client_stream
+            .export_keying_material("EXPORTER-rama-test", None, 32)
+            .unwrap();
+        assert_eq!(
+            no_context_material,
+            server_stream
+                .export_keying_material("EXPORTER-rama-test", None, 32)
+                .unwrap()
+        );
+    }
+}
@@ -1,9 +1,11 @@
 //! rustls based TLS support for rama.
 
+pub mod chain;
 pub mod client;
 pub mod server;
 pub mod verify;
 
+mod exporter;
 mod key_log;
 
 pub mod dep {
@@ -1,4 +1,4 @@
-use super::{TlsAcceptorData, TlsAcceptorService};
+use super::{MaybeTlsAcceptorService, TlsAcceptorData, TlsAcceptorService};
 use rama_core::Layer;
 
 /// A [`Layer`] which wraps the given service with a [`TlsAcceptorService`].
@@ -40,3 +40,42 @@ impl<S> Layer<S> for TlsAcceptorLayer {
         TlsAcceptorService::new(self.data.clone(), inner, self.store_client_hello)
     }
 }
+
+/// A [`Layer`] which wraps the given service with a [`MaybeTlsAcceptorService`],
+/// accepting both TLS and plain connections.
+#[derive(Debug, Clone)]
+pub struct MaybeTlsAcceptorLayer {
+    data: TlsAcceptorData,
+    store_client_hello: bool,
+}
+
+impl MaybeTlsAcceptorLayer {
+    /// Creates a new [`MaybeTlsAcceptorLayer`] using the given [`TlsAcceptorData`],
+    /// which is used to configure the inner TLS acceptor.
+    pub const fn new(data: TlsAcceptorData) -> Self {
+        Self {
+            data,
+            store_client_hello: false,
+        }
+    }
+
+    /// Set that the client hello should be stored
+    pub const fn with_store_client_hello(mut self, store: bool) -> Self {
+        self.store_client_hello = store;
+        self
+    }
+
+    /// Set that the client hello should be stored
+    pub fn set_store_client_hello(&mut self, store: bool) -> &mut Self {
+        self.store_client_hello = store;
+        self
+    }
+}
+
+impl<S> Layer<S> for MaybeTlsAcceptorLayer {
+    type Service = MaybeTlsAcceptorService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MaybeTlsAcceptorService::new(self.data.clone(), inner, self.store_client_hello)
+    }
+}
@@ -1,6 +1,7 @@
 //! TLS server support for Rama.
 //!
 //! This module provides a [`TlsAcceptorLayer`] to accept TLS connections and a [`TlsAcceptorService`] to handle them.
+//! Use the [`MaybeTlsAcceptorLayer`] instead to accept both TLS and plain connections on the same listener.
 //!
 //! # Examples
 //!
@@ -15,12 +16,16 @@
 
 mod service;
 #[doc(inline)]
-pub use service::TlsAcceptorService;
+pub use service::{MaybeTlsAcceptorService, TlsAcceptorService};
 
 mod layer;
 #[doc(inline)]
-pub use layer::TlsAcceptorLayer;
+pub use layer::{MaybeTlsAcceptorLayer, TlsAcceptorLayer};
 
 mod acceptor_data;
 #[doc(inline)]
 pub use acceptor_data::TlsAcceptorData;
+
+mod ocsp;
+#[doc(inline)]
+pub use ocsp::OcspStaplingCertResolver;
@@ -0,0 +1,313 @@
+use crate::ocsp::{OcspResponse, OcspStapler};
+use crate::rustls::dep::pki_types::{CertificateDer, PrivateKeyDer};
+use crate::rustls::dep::rustls::{
+    crypto::CryptoProvider,
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+};
+use parking_lot::RwLock;
+use rama_core::error::{ErrorContext, OpaqueError};
+use std::{fmt, sync::Arc, time::SystemTime};
+
+/// A [`ResolvesServerCert`] which staples an [`OcspResponse`]
+/// to its single certificate chain.
+///
+/// The [`OcspResponse`] can be updated at any time, e.g. by an [`OcspRefresher`],
+/// and is no longer stapled once it expired, as stapling an expired
+/// response is worse than not stapling one at all.
+///
+/// Use it as the cert resolver of a [`rustls::ServerConfig`],
+/// which can be turned into a [`TlsAcceptorData`].
+///
+/// [`rustls::ServerConfig`]: crate::rustls::dep::rustls::ServerConfig
+/// [`TlsAcceptorData`]: super::TlsAcceptorData
+/// [`OcspRefresher`]: crate::ocsp::OcspRefresher
+pub struct OcspStaplingCertResolver {
+    certified_key: Arc<CertifiedKey>,
+    stapled: RwLock<Option<StapledKey>>,
+}
+
+struct StapledKey {
+    certified_key: Arc<CertifiedKey>,
+    next_update: SystemTime,
+}
+
+impl fmt::Debug for OcspStaplingCertResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OcspStaplingCertResolver")
+            .field("certified_key", &self.certified_key)
+            .field(
+                "ocsp_next_update",
+                &self
+                    .stapled
+                    .read()
+                    .as_ref()
+                    .map(|stapled| stapled.next_update),
+            )
+            .finish()
+    }
+}
+
+impl OcspStaplingCertResolver {
+    /// Create a new [`OcspStaplingCertResolver`] for the given certificate chain and private key,
+    /// without an [`OcspResponse`] to staple (yet).
+    ///
+    /// The private key is loaded using the default [`CryptoProvider`],
+    /// falling back to the `aws-lc-rs` provider if none was installed.
+    pub fn new(
+        cert_chain: Vec<CertificateDer<'static>>,
+        private_key: PrivateKeyDer<'static>,
+    ) -> Result<Self, OpaqueError> {
+        let provider = CryptoProvider::get_default().cloned().unwrap_or_else(|| {
+            Arc::new(crate::rustls::dep::rustls::crypto::aws_lc_rs::default_provider())
+        });
+        let signing_key = provider
+            .key_provider
+            .load_private_key(private_key)
+            .context("OcspStaplingCertResolver: load private key")?;
+        Ok(Self::from_certified_key(CertifiedKey::new(
+            cert_chain,
+            signing_key,
+        )))
+    }
+
+    /// Create a new [`OcspStaplingCertResolver`] for the given [`CertifiedKey`],
+    /// without an [`OcspResponse`] to staple (yet).
+    ///
+    /// Any OCSP response already part of the [`CertifiedKey`] is ignored.
+    pub fn from_certified_key(mut certified_key: CertifiedKey) -> Self {
+        certified_key.ocsp = None;
+        Self {
+            certified_key: Arc::new(certified_key),
+            stapled: RwLock::new(None),
+        }
+    }
+
+    /// Staple the given [`OcspResponse`] from now on, until it expires.
+    pub fn with_ocsp_response(self, response: OcspResponse) -> Self {
+        self.update_ocsp_response(response);
+        self
+    }
+
+    /// Staple the given [`OcspResponse`] from now on, until it expires,
+    /// replacing the previous one (if any).
+    pub fn update_ocsp_response(&self, response: OcspResponse) {
+        let next_update = response.next_update();
+        let mut certified_key = self.certified_key.as_ref().clone();
+        certified_key.ocsp = Some(response.into_der());
+        *self.stapled.write() = Some(StapledKey {
+            certified_key: Arc::new(certified_key),
+            next_update,
+        });
+    }
+
+    /// Stop stapling an [`OcspResponse`].
+    pub fn clear_ocsp_response(&self) {
+        *self.stapled.write() = None;
+    }
+
+    /// Returns the time at which the currently stapled
+    /// [`OcspResponse`] expires, if there is one.
+    pub fn ocsp_next_update(&self) -> Option<SystemTime> {
+        self.stapled
+            .read()
+            .as_ref()
+            .map(|stapled| stapled.next_update)
+    }
+}
+
+impl ResolvesServerCert for OcspStaplingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        if let Some(stapled) = self.stapled.read().as_ref() {
+            if SystemTime::now() < stapled.next_update {
+                return Some(stapled.certified_key.clone());
+            }
+            tracing::debug!(
+                next_update = ?stapled.next_update,
+                "OcspStaplingCertResolver: drop expired ocsp response staple",
+            );
+        }
+        Some(self.certified_key.clone())
+    }
+}
+
+impl OcspStapler for OcspStaplingCertResolver {
+    fn update_ocsp_response(&self, response: OcspResponse) {
+        OcspStaplingCertResolver::update_ocsp_response(self, response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ocsp::{OcspRefresher, OcspResponder};
+    use crate::rustls::dep::pki_types::{PrivatePkcs8KeyDer, ServerName, UnixTime};
+    use crate::rustls::dep::rcgen;
+    use crate::rustls::dep::rustls::{
+        client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        ClientConfig, DigitallySignedStruct, ServerConfig, SignatureScheme,
+    };
+    use crate::rustls::dep::tokio_rustls::{TlsAcceptor, TlsConnector};
+    use parking_lot::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    const OCSP_RESPONSE: &[u8] = b"\x30\x03\x0a\x01\x00";
+
+    /// Accepts any server cert, recording the stapled ocsp response.
+    #[derive(Debug)]
+    struct RecordOcspVerifier {
+        provider: Arc<CryptoProvider>,
+        ocsp_response: Mutex<Option<Vec<u8>>>,
+    }
+
+    impl ServerCertVerifier for RecordOcspVerifier {
+        fn verify_server_cert(
+            &self,
+            _end_entity: &CertificateDer<'_>,
+            _intermediates: &[CertificateDer<'_>],
+            _server_name: &ServerName<'_>,
+            ocsp_response: &[u8],
+            _now: UnixTime,
+        ) -> Result<ServerCertVerified, crate::rustls::dep::rustls::Error> {
+            *self.ocsp_response.lock() = Some(ocsp_response.to_vec());
+            Ok(ServerCertVerified::assertion())
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, crate::rustls::dep::rustls::Error> {
+            crate::rustls::dep::rustls::crypto::verify_tls12_signature(
+                message,
+                cert,
+                dss,
+                &self.provider.signature_verification_algorithms,
+            )
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, crate::rustls::dep::rustls::Error> {
+            crate::rustls::dep::rustls::crypto::verify_tls13_signature(
+                message,
+                cert,
+                dss,
+                &self.provider.signature_verification_algorithms,
+            )
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.provider
+                .signature_verification_algorithms
+                .supported_schemes()
+        }
+    }
+
+    fn resolver() -> OcspStaplingCertResolver {
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+        OcspStaplingCertResolver::new(
+            vec![cert.der().clone()],
+            PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key_pair.serialize_der())),
+        )
+        .unwrap()
+    }
+
+    /// Handshake with the given resolver, returning the stapled ocsp response.
+    async fn handshake(resolver: Arc<OcspStaplingCertResolver>) -> Vec<u8> {
+        let acceptor = TlsAcceptor::from(Arc::new(
+            ServerConfig::builder()
+                .with_no_client_auth()
+                .with_cert_resolver(resolver),
+        ));
+
+        let verifier = Arc::new(RecordOcspVerifier {
+            provider: Arc::new(crate::rustls::dep::rustls::crypto::aws_lc_rs::default_provider()),
+            ocsp_response: Mutex::new(None),
+        });
+        let connector = TlsConnector::from(Arc::new(
+            ClientConfig::builder()
+                .dangerous()
+                .with_custom_certificate_verifier(verifier.clone())
+                .with_no_client_auth(),
+        ));
+
+        let (client, server) = tokio::io::duplex(16 * 1024);
+        let (server, client) = tokio::join!(
+            acceptor.accept(server),
+            connector.connect(ServerName::try_from("localhost").unwrap(), client),
+        );
+        server.unwrap();
+        client.unwrap();
+
+        let ocsp_response = verifier.ocsp_response.lock().take();
+        ocsp_response.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ocsp_response_is_stapled() {
+        let resolver = Arc::new(resolver().with_ocsp_response(OcspResponse::new(
+            OCSP_RESPONSE,
+            SystemTime::now() + Duration::from_secs(3600),
+        )));
+        assert_eq!(handshake(resolver.clone()).await, OCSP_RESPONSE);
+
+        resolver.clear_ocsp_response();
+        assert!(handshake(resolver).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_expired_ocsp_response_is_not_stapled() {
+        let resolver = Arc::new(resolver().with_ocsp_response(OcspResponse::new(
+            OCSP_RESPONSE,
+            SystemTime::now() - Duration::from_secs(1),
+        )));
+        assert!(handshake(resolver).await.is_empty());
+    }
+
+    #[derive(Debug, Default)]
+    struct CountingResponder {
+        fetches: Arc<AtomicUsize>,
+    }
+
+    impl OcspResponder for CountingResponder {
+        async fn fetch_ocsp_response(&self) -> Result<OcspResponse, OpaqueError> {
+            let n = self.fetches.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(OcspResponse::new(
+                vec![n as u8],
+                SystemTime::now() + Duration::from_millis(100),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ocsp_refresher() {
+        let resolver = Arc::new(resolver());
+        assert!(resolver.ocsp_next_update().is_none());
+
+        let responder = CountingResponder::default();
+        let fetches = responder.fetches.clone();
+        let refresher = OcspRefresher::new(resolver.clone(), responder)
+            .with_refresh_before(Duration::from_millis(50))
+            .with_retry_interval(Duration::from_millis(10));
+        let handle = tokio::spawn(refresher.run());
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(handshake(resolver.clone()).await, [1]);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let fetched = fetches.load(Ordering::SeqCst);
+        assert!(fetched >= 2, "fetched: {fetched}");
+        let stapled = handshake(resolver).await;
+        assert!(stapled[0] >= 2, "stapled: {stapled:?}");
+
+        handle.abort();
+    }
+}
@@ -10,10 +10,11 @@ use rama_core::{
     Context, Service,
 };
 use rama_net::{
-    stream::Stream,
+    stream::{MaybeTlsStream, PeekStream, Stream},
     tls::{client::NegotiatedTlsParameters, ApplicationProtocol},
 };
 use rama_utils::macros::define_inner_service_accessors;
+use tokio::io::AsyncReadExt;
 
 use super::TlsAcceptorData;
 
@@ -71,39 +72,222 @@ where
     type Error = BoxError;
 
     async fn serve(&self, mut ctx: Context<T>, stream: IO) -> Result<Self::Response, Self::Error> {
-        let tls_acceptor_data = ctx.get::<TlsAcceptorData>().unwrap_or(&self.data);
+        let (stream, secure_transport) =
+            accept_tls(&self.data, self.store_client_hello, &mut ctx, stream).await?;
 
-        let acceptor = LazyConfigAcceptor::new(Acceptor::default(), stream);
+        ctx.insert(secure_transport);
+        self.inner.serve(ctx, stream).await.map_err(|err| {
+            OpaqueError::from_boxed(err.into())
+                .context("rustls acceptor: service error")
+                .into_boxed()
+        })
+    }
+}
+
+/// A [`Service`] which accepts both TLS and plain connections on the same stream,
+/// and delegates the resulting [`MaybeTlsStream`] to the given service.
+///
+/// TLS is detected by peeking the first byte of the stream,
+/// which is a handshake record for any client initiating a TLS handshake.
+pub struct MaybeTlsAcceptorService<S> {
+    data: TlsAcceptorData,
+    store_client_hello: bool,
+    inner: S,
+}
 
-        let start = acceptor.await?;
+impl<S> MaybeTlsAcceptorService<S> {
+    /// Creates a new [`MaybeTlsAcceptorService`].
+    pub const fn new(data: TlsAcceptorData, inner: S, store_client_hello: bool) -> Self {
+        Self {
+            data,
+            store_client_hello,
+            inner,
+        }
+    }
 
-        let secure_transport = if self.store_client_hello {
-            SecureTransport::with_client_hello(start.client_hello().into())
+    define_inner_service_accessors!();
+}
+
+impl<S: std::fmt::Debug> std::fmt::Debug for MaybeTlsAcceptorService<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MaybeTlsAcceptorService")
+            .field("data", &self.data)
+            .field("store_client_hello", &self.store_client_hello)
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<S> Clone for MaybeTlsAcceptorService<S>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            store_client_hello: self.store_client_hello,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Content type of a TLS handshake record,
+/// the first byte sent by a client initiating a TLS handshake.
+const TLS_HANDSHAKE_CONTENT_TYPE: u8 = 0x16;
+
+impl<T, S, IO> Service<T, IO> for MaybeTlsAcceptorService<S>
+where
+    T: Send + Sync + 'static,
+    IO: Stream + Unpin + 'static,
+    S: Service<T, MaybeTlsStream<PeekStream<IO>, TlsStream<PeekStream<IO>>>, Error: Into<BoxError>>,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        mut ctx: Context<T>,
+        mut stream: IO,
+    ) -> Result<Self::Response, Self::Error> {
+        let mut peek = [0u8; 1];
+        let n = stream
+            .read(&mut peek)
+            .await
+            .context("rustls maybe-tls acceptor: peek first byte")?;
+        let stream = PeekStream::new(peek[..n].to_vec(), stream);
+
+        let stream = if n == 1 && peek[0] == TLS_HANDSHAKE_CONTENT_TYPE {
+            let (stream, secure_transport) =
+                accept_tls(&self.data, self.store_client_hello, &mut ctx, stream).await?;
+            ctx.insert(secure_transport.clone());
+            MaybeTlsStream::tls(stream, secure_transport)
         } else {
-            SecureTransport::default()
+            MaybeTlsStream::plain(stream)
         };
 
-        let stream = start
-            .into_stream(tls_acceptor_data.server_config.clone())
-            .await?;
-        let (_, conn_data_ref) = stream.get_ref();
-        ctx.insert(NegotiatedTlsParameters {
-            protocol_version: conn_data_ref
-                .protocol_version()
-                .context("no protocol version available")?
-                .into(),
-            application_layer_protocol: conn_data_ref
-                .alpn_protocol()
-                .map(ApplicationProtocol::from),
-            // Currently not supported as this would mean we need to wrap rustls config
-            peer_certificate_chain: None,
-        });
-
-        ctx.insert(secure_transport);
         self.inner.serve(ctx, stream).await.map_err(|err| {
             OpaqueError::from_boxed(err.into())
-                .context("rustls acceptor: service error")
+                .context("rustls maybe-tls acceptor: service error")
                 .into_boxed()
         })
     }
 }
+
+/// Accept a TLS connection on the given stream,
+/// inserting the [`NegotiatedTlsParameters`] into the given [`Context`].
+async fn accept_tls<T, IO>(
+    data: &TlsAcceptorData,
+    store_client_hello: bool,
+    ctx: &mut Context<T>,
+    stream: IO,
+) -> Result<(TlsStream<IO>, SecureTransport), BoxError>
+where
+    IO: Stream + Unpin + 'static,
+{
+    let tls_acceptor_data = ctx.get::<TlsAcceptorData>().unwrap_or(data);
+
+    let acceptor = LazyConfigAcceptor::new(Acceptor::default(), stream);
+
+    let start = acceptor.await?;
+
+    let secure_transport = if store_client_hello {
+        SecureTransport::with_client_hello(start.client_hello().into())
+    } else {
+        SecureTransport::default()
+    };
+
+    let stream = start
+        .into_stream(tls_acceptor_data.server_config.clone())
+        .await?;
+    let (_, conn_data_ref) = stream.get_ref();
+    ctx.insert(NegotiatedTlsParameters {
+        protocol_version: conn_data_ref
+            .protocol_version()
+            .context("no protocol version available")?
+            .into(),
+        application_layer_protocol: conn_data_ref.alpn_protocol().map(ApplicationProtocol::from),
+        // Currently not supported as this would mean we need to wrap rustls config
+        peer_certificate_chain: None,
+    });
+
+    Ok((stream, secure_transport))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rustls::dep::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer, ServerName};
+    use crate::rustls::dep::rcgen;
+    use crate::rustls::dep::rustls::{ClientConfig, RootCertStore, ServerConfig};
+    use crate::rustls::dep::tokio_rustls::TlsConnector;
+    use rama_core::service::service_fn;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_maybe_tls_acceptor_plain_and_tls() {
+        let rcgen::CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![cert.der().clone()],
+                PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key_pair.serialize_der())),
+            )
+            .unwrap();
+
+        let mut root_store = RootCertStore::empty();
+        root_store.add(cert.der().clone()).unwrap();
+        let connector = TlsConnector::from(Arc::new(
+            ClientConfig::builder()
+                .with_root_certificates(root_store)
+                .with_no_client_auth(),
+        ));
+
+        let acceptor = MaybeTlsAcceptorService::new(
+            server_config.into(),
+            service_fn(
+                |ctx: Context<()>,
+                 mut stream: MaybeTlsStream<
+                    PeekStream<tokio::io::DuplexStream>,
+                    TlsStream<PeekStream<tokio::io::DuplexStream>>,
+                >| async move {
+                    assert_eq!(stream.is_tls(), ctx.contains::<SecureTransport>(),);
+                    let mut buf = [0u8; 5];
+                    stream.read_exact(&mut buf).await?;
+                    stream
+                        .write_all(if stream.is_tls() { b"tls" } else { b"tcp" })
+                        .await?;
+                    stream.write_all(&buf).await?;
+                    stream.shutdown().await?;
+                    Ok::<_, std::io::Error>(())
+                },
+            ),
+            false,
+        );
+
+        // plain
+        let (mut client, server) = tokio::io::duplex(1024);
+        let (result, _) = tokio::join!(acceptor.serve(Context::default(), server), async {
+            client.write_all(b"hello").await.unwrap();
+            let mut buf = Vec::new();
+            client.read_to_end(&mut buf).await.unwrap();
+            assert_eq!(buf, b"tcphello");
+        });
+        result.unwrap();
+
+        // tls
+        let (client, server) = tokio::io::duplex(1024);
+        let (result, _) = tokio::join!(acceptor.serve(Context::default(), server), async {
+            let mut client = connector
+                .connect(ServerName::try_from("localhost").unwrap(), client)
+                .await
+                .unwrap();
+            client.write_all(b"hello").await.unwrap();
+            let mut buf = Vec::new();
+            client.read_to_end(&mut buf).await.unwrap();
+            assert_eq!(buf, b"tlshello");
+        });
+        result.unwrap();
+    }
+}
@@ -0,0 +1,335 @@
+use super::parse::contains_any_ignore_ascii_case;
+
+#[cfg(feature = "dns")]
+use rama_core::error::BoxError;
+#[cfg(feature = "dns")]
+use rama_dns::{DnsResolver, ReverseDnsResolver};
+#[cfg(feature = "dns")]
+use rama_net::address::Domain;
+#[cfg(feature = "dns")]
+use std::net::IpAddr;
+
+/// A known (good) bot, such as a search engine crawler.
+///
+/// Bots are detected by matching the `User-Agent` (header) value
+/// against the patterns of the known bots (see [`KnownBot::detect`]).
+/// As anyone can claim to be a bot this way, a detected bot can be verified
+/// using forward-confirmed reverse DNS (see [`KnownBot::verify`]),
+/// which requires the `dns` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KnownBot {
+    name: &'static str,
+    ua_patterns: &'static [&'static str],
+    domains: &'static [&'static str],
+}
+
+impl KnownBot {
+    /// Google's web crawlers.
+    pub const GOOGLEBOT: Self = Self {
+        name: "Googlebot",
+        ua_patterns: &[
+            "Googlebot",
+            "AdsBot-Google",
+            "Mediapartners-Google",
+            "Google-InspectionTool",
+        ],
+        // `googleusercontent.com` is deliberately not listed: any Google Cloud VM
+        // has a forward-confirmed `*.bc.googleusercontent.com` PTR record
+        domains: &["googlebot.com", "google.com"],
+    };
+
+    /// Microsoft's Bing web crawler.
+    pub const BINGBOT: Self = Self {
+        name: "Bingbot",
+        ua_patterns: &["bingbot", "BingPreview", "adidxbot"],
+        domains: &["search.msn.com"],
+    };
+
+    /// Apple's web crawler.
+    pub const APPLEBOT: Self = Self {
+        name: "Applebot",
+        ua_patterns: &["Applebot"],
+        domains: &["applebot.apple.com"],
+    };
+
+    /// Yandex's web crawlers.
+    pub const YANDEXBOT: Self = Self {
+        name: "YandexBot",
+        ua_patterns: &["YandexBot", "YandexImages", "YandexMobileBot"],
+        domains: &["yandex.ru", "yandex.net", "yandex.com"],
+    };
+
+    /// Baidu's web crawler.
+    pub const BAIDUSPIDER: Self = Self {
+        name: "Baiduspider",
+        ua_patterns: &["Baiduspider"],
+        domains: &["crawl.baidu.com", "crawl.baidu.jp"],
+    };
+
+    /// Yahoo's web crawler.
+    pub const YAHOO_SLURP: Self = Self {
+        name: "Yahoo! Slurp",
+        ua_patterns: &["Yahoo! Slurp"],
+        domains: &["crawl.yahoo.net"],
+    };
+
+    /// Returns all the bots known by rama.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::GOOGLEBOT,
+            Self::BINGBOT,
+            Self::APPLEBOT,
+            Self::YANDEXBOT,
+            Self::BAIDUSPIDER,
+            Self::YAHOO_SLURP,
+        ]
+    }
+
+    /// Detect the [`KnownBot`] claimed by the given `User-Agent` (header) value, if any.
+    ///
+    /// Note that this only tells you which bot the user agent claims to be,
+    /// use [`KnownBot::verify`] to verify that claim.
+    pub fn detect(ua: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .find(|bot| contains_any_ignore_ascii_case(ua, bot.ua_patterns).is_some())
+            .copied()
+    }
+
+    /// Returns the name of the [`KnownBot`].
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the (parent) domains that the hosts of the [`KnownBot`] belong to.
+    pub fn domains(&self) -> &'static [&'static str] {
+        self.domains
+    }
+
+    #[cfg(feature = "dns")]
+    /// Verify that the given client [`IpAddr`] belongs to this [`KnownBot`],
+    /// using forward-confirmed reverse DNS (FCrDNS).
+    ///
+    /// The IP is verified if it reverse resolves to a domain of this bot,
+    /// which in turn resolves back to that same IP.
+    ///
+    /// Lookup errors (e.g. because no records exist) are treated as not verified.
+    pub async fn verify<R>(&self, ip: IpAddr, dns: &R) -> bool
+    where
+        R: DnsResolver<Error: Into<BoxError>> + ReverseDnsResolver<Error: Into<BoxError>>,
+    {
+        let domains = match ReverseDnsResolver::reverse_lookup(dns, ip).await {
+            Ok(domains) => domains,
+            Err(err) => {
+                let err: BoxError = err.into();
+                tracing::debug!(%ip, bot = self.name, %err, "reverse dns lookup failed");
+                return false;
+            }
+        };
+
+        for domain in domains {
+            if !self
+                .domains
+                .iter()
+                .any(|parent| domain.is_sub_of(&Domain::from_static(parent)))
+            {
+                tracing::trace!(%ip, bot = self.name, %domain, "ignore domain of unrelated host");
+                continue;
+            }
+
+            let result = match ip {
+                IpAddr::V4(ip) => dns
+                    .ipv4_lookup(domain.clone())
+                    .await
+                    .map(|ips| ips.contains(&ip))
+                    .map_err(Into::<BoxError>::into),
+                IpAddr::V6(ip) => dns
+                    .ipv6_lookup(domain.clone())
+                    .await
+                    .map(|ips| ips.contains(&ip))
+                    .map_err(Into::<BoxError>::into),
+            };
+            match result {
+                Ok(true) => return true,
+                Ok(false) => {
+                    tracing::debug!(%ip, bot = self.name, %domain, "forward dns lookup mismatch");
+                }
+                Err(err) => {
+                    tracing::debug!(%ip, bot = self.name, %domain, %err, "forward dns lookup failed");
+                }
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_bot_detect() {
+        for (ua, expected) in [
+            (
+                "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+                Some(KnownBot::GOOGLEBOT),
+            ),
+            (
+                "Mozilla/5.0 (Linux; Android 6.0.1; Nexus 5X Build/MMB29P) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.6367.118 Mobile Safari/537.36 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+                Some(KnownBot::GOOGLEBOT),
+            ),
+            (
+                "Mozilla/5.0 (compatible; bingbot/2.0; +http://www.bing.com/bingbot.htm)",
+                Some(KnownBot::BINGBOT),
+            ),
+            (
+                "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_5) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/13.1.1 Safari/605.1.15 (Applebot/0.1; +http://www.apple.com/go/applebot)",
+                Some(KnownBot::APPLEBOT),
+            ),
+            (
+                "Mozilla/5.0 (compatible; YandexBot/3.0; +http://yandex.com/bots)",
+                Some(KnownBot::YANDEXBOT),
+            ),
+            (
+                "Mozilla/5.0 (compatible; Baiduspider/2.0; +http://www.baidu.com/search/spider.html)",
+                Some(KnownBot::BAIDUSPIDER),
+            ),
+            (
+                "Mozilla/5.0 (compatible; Yahoo! Slurp; http://help.yahoo.com/help/us/ysearch/slurp)",
+                Some(KnownBot::YAHOO_SLURP),
+            ),
+            (
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
+                None,
+            ),
+            ("curl/8.7.1", None),
+            ("", None),
+        ] {
+            assert_eq!(KnownBot::detect(ua), expected, "UA: {ua}");
+        }
+    }
+
+    #[cfg(feature = "dns")]
+    mod dns {
+        use super::*;
+        use rama_core::error::OpaqueError;
+        use std::net::{Ipv4Addr, Ipv6Addr};
+
+        #[derive(Debug, Default)]
+        struct MockDns {
+            ptr: Vec<(IpAddr, &'static str)>,
+            addr: Vec<(&'static str, IpAddr)>,
+        }
+
+        impl MockDns {
+            fn with_host(mut self, ip: IpAddr, ptr: &'static str, addr: &'static str) -> Self {
+                self.ptr.push((ip, ptr));
+                self.addr.push((addr, ip));
+                self
+            }
+        }
+
+        impl DnsResolver for MockDns {
+            type Error = OpaqueError;
+
+            async fn ipv4_lookup(&self, domain: Domain) -> Result<Vec<Ipv4Addr>, Self::Error> {
+                let ips: Vec<_> = self
+                    .addr
+                    .iter()
+                    .filter(|(name, _)| domain == *name)
+                    .filter_map(|(_, ip)| match ip {
+                        IpAddr::V4(ip) => Some(*ip),
+                        IpAddr::V6(_) => None,
+                    })
+                    .collect();
+                if ips.is_empty() {
+                    return Err(OpaqueError::from_display("no A records found"));
+                }
+                Ok(ips)
+            }
+
+            async fn ipv6_lookup(&self, domain: Domain) -> Result<Vec<Ipv6Addr>, Self::Error> {
+                let ips: Vec<_> = self
+                    .addr
+                    .iter()
+                    .filter(|(name, _)| domain == *name)
+                    .filter_map(|(_, ip)| match ip {
+                        IpAddr::V4(_) => None,
+                        IpAddr::V6(ip) => Some(*ip),
+                    })
+                    .collect();
+                if ips.is_empty() {
+                    return Err(OpaqueError::from_display("no AAAA records found"));
+                }
+                Ok(ips)
+            }
+        }
+
+        impl ReverseDnsResolver for MockDns {
+            type Error = OpaqueError;
+
+            async fn reverse_lookup(&self, ip: IpAddr) -> Result<Vec<Domain>, Self::Error> {
+                let domains: Vec<_> = self
+                    .ptr
+                    .iter()
+                    .filter(|(addr, _)| *addr == ip)
+                    .map(|(_, name)| Domain::from_static(name))
+                    .collect();
+                if domains.is_empty() {
+                    return Err(OpaqueError::from_display("no PTR records found"));
+                }
+                Ok(domains)
+            }
+        }
+
+        #[tokio::test]
+        async fn test_known_bot_verify() {
+            let googlebot_v4 = IpAddr::V4(Ipv4Addr::new(66, 249, 66, 1));
+            let googlebot_v6 = IpAddr::V6(Ipv6Addr::new(0x2001, 0x4860, 0x4801, 0, 0, 0, 0, 1));
+            let spoofed = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7));
+            let mismatch = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 8));
+            let unknown = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 9));
+            let cloud_vm = IpAddr::V4(Ipv4Addr::new(34, 77, 1, 2));
+
+            let dns = MockDns::default()
+                .with_host(
+                    googlebot_v4,
+                    "crawl-66-249-66-1.googlebot.com.",
+                    "crawl-66-249-66-1.googlebot.com.",
+                )
+                .with_host(
+                    googlebot_v6,
+                    "crawl-2001-4860-4801--1.googlebot.com",
+                    "crawl-2001-4860-4801--1.googlebot.com",
+                )
+                // PTR record points to a domain which isn't owned by google
+                .with_host(
+                    spoofed,
+                    "googlebot.com.example.org",
+                    "googlebot.com.example.org",
+                )
+                // PTR record claims to be google, but forward lookup doesn't confirm it
+                .with_host(
+                    mismatch,
+                    "crawl-203-0-113-8.googlebot.com",
+                    "crawl-203-0-113-9.googlebot.com",
+                )
+                // forward-confirmed, but any Google Cloud VM has such a PTR record
+                .with_host(
+                    cloud_vm,
+                    "2.1.77.34.bc.googleusercontent.com",
+                    "2.1.77.34.bc.googleusercontent.com",
+                );
+
+            assert!(KnownBot::GOOGLEBOT.verify(googlebot_v4, &dns).await);
+            assert!(KnownBot::GOOGLEBOT.verify(googlebot_v6, &dns).await);
+
+            assert!(!KnownBot::BINGBOT.verify(googlebot_v4, &dns).await);
+            assert!(!KnownBot::GOOGLEBOT.verify(spoofed, &dns).await);
+            assert!(!KnownBot::GOOGLEBOT.verify(mismatch, &dns).await);
+            assert!(!KnownBot::GOOGLEBOT.verify(unknown, &dns).await);
+            assert!(!KnownBot::GOOGLEBOT.verify(cloud_vm, &dns).await);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::parse_http_user_agent_header;
+use super::{parse::parse_device_class, parse_http_user_agent_header};
 use rama_core::error::OpaqueError;
 use rama_utils::macros::match_ignore_ascii_case_str;
 use serde::{Deserialize, Deserializer, Serialize};
@@ -101,6 +101,17 @@ impl UserAgent {
         }
     }
 
+    /// returns a best-effort guess of the [`DeviceClass`] of the [`UserAgent`].
+    ///
+    /// Unlike [`UserAgent::device`] this distinguishes tablets from other mobile devices,
+    /// and classifies known bots (e.g. crawlers and headless browsers) as [`DeviceClass::Bot`].
+    ///
+    /// Note that this is a heuristic based on the `User-Agent` (header) value,
+    /// e.g. recent iPads advertise themselves as a desktop Mac and are classified as such.
+    pub fn device_class(&self) -> DeviceClass {
+        parse_device_class(&self.header, self.platform(), self.device())
+    }
+
     /// returns the [`UserAgent`] information, containing
     /// the [`UserAgentKind`] and version if known.
     pub fn info(&self) -> Option<UserAgentInfo> {
@@ -208,6 +219,31 @@ impl fmt::Display for DeviceKind {
     }
 }
 
+/// Class of device on which the [`UserAgent`] operates,
+/// as guessed by [`UserAgent::device_class`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeviceClass {
+    /// Personal Computers
+    Desktop,
+    /// Phones and other small mobile devices
+    Mobile,
+    /// Tablets
+    Tablet,
+    /// Crawlers, headless browsers and other automated clients
+    Bot,
+}
+
+impl fmt::Display for DeviceClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceClass::Desktop => write!(f, "Desktop"),
+            DeviceClass::Mobile => write!(f, "Mobile"),
+            DeviceClass::Tablet => write!(f, "Tablet"),
+            DeviceClass::Bot => write!(f, "Bot"),
+        }
+    }
+}
+
 /// Platform within the [`UserAgent`] operates.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PlatformKind {
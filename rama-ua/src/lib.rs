@@ -59,12 +59,16 @@ use serde::{Deserialize, Serialize};
 
 mod info;
 pub use info::{
-    DeviceKind, HttpAgent, PlatformKind, TlsAgent, UserAgent, UserAgentInfo, UserAgentKind,
+    DeviceClass, DeviceKind, HttpAgent, PlatformKind, TlsAgent, UserAgent, UserAgentInfo,
+    UserAgentKind,
 };
 
 mod parse;
 use parse::parse_http_user_agent_header;
 
+mod bot;
+pub use bot::KnownBot;
+
 /// Information that can be used to overwrite the [`UserAgent`] of an http request.
 ///
 /// Used by the `UserAgentClassifier` (see `rama-http`) to overwrite the specified
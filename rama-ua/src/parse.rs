@@ -2,7 +2,7 @@
 
 use super::{
     info::{UserAgentData, UserAgentInfo},
-    DeviceKind, PlatformKind, UserAgent, UserAgentKind,
+    DeviceClass, DeviceKind, PlatformKind, UserAgent, UserAgentKind,
 };
 
 /// Maximum length of a User Agent string that we take into consideration.
@@ -154,6 +154,61 @@ pub(crate) fn parse_http_user_agent_header(header: String) -> UserAgent {
     }
 }
 
+/// Patterns found in the `User-Agent` of well known bots,
+/// such as search engine crawlers, link previewers and headless browsers.
+const BOT_PATTERNS: &[&str] = &[
+    "bot",
+    "crawl",
+    "spider",
+    "slurp",
+    "facebookexternalhit",
+    "Mediapartners-Google",
+    "Headless",
+    "Lighthouse",
+    "curl/",
+    "Wget/",
+    "python-requests",
+    "Go-http-client",
+];
+
+/// Patterns found in the `User-Agent` of tablets.
+const TABLET_PATTERNS: &[&str] = &["iPad", "Tablet", "Kindle", "Silk/", "PlayBook"];
+
+/// Patterns found in the `User-Agent` of phones and other small mobile devices.
+const MOBILE_PATTERNS: &[&str] = &["Mobi", "iPhone", "iPod", "Phone", "Zune"];
+
+/// guess the [`DeviceClass`] of a user agent, using the http user agent string
+/// in combination with the already parsed platform and device kind.
+pub(crate) fn parse_device_class(
+    header: &str,
+    platform: Option<PlatformKind>,
+    device: DeviceKind,
+) -> DeviceClass {
+    let ua = if header.len() > MAX_UA_LENGTH {
+        header.get(..MAX_UA_LENGTH).unwrap_or_default()
+    } else {
+        header
+    };
+
+    if contains_any_ignore_ascii_case(ua, BOT_PATTERNS).is_some() {
+        DeviceClass::Bot
+    } else if contains_any_ignore_ascii_case(ua, TABLET_PATTERNS).is_some() {
+        DeviceClass::Tablet
+    } else if contains_any_ignore_ascii_case(ua, MOBILE_PATTERNS).is_some() {
+        DeviceClass::Mobile
+    } else if platform == Some(PlatformKind::Android)
+        && contains_ignore_ascii_case(ua, "Android").is_some()
+    {
+        // Android tablets, unlike Android phones, do not advertise the "Mobile" token
+        DeviceClass::Tablet
+    } else {
+        match device {
+            DeviceKind::Desktop => DeviceClass::Desktop,
+            DeviceKind::Mobile => DeviceClass::Mobile,
+        }
+    }
+}
+
 fn parse_ua_version_firefox_and_chromium(ua: &str) -> Option<usize> {
     ua.find('/').and_then(|i| {
         let start = i + 1;
@@ -191,7 +246,7 @@ fn contains_ignore_ascii_case(s: &str, sub: &str) -> Option<usize> {
     })
 }
 
-fn contains_any_ignore_ascii_case(s: &str, subs: &[&str]) -> Option<usize> {
+pub(crate) fn contains_any_ignore_ascii_case(s: &str, subs: &[&str]) -> Option<usize> {
     let max = s.len();
     let smallest_length = subs.iter().map(|s| s.len()).min().unwrap_or(0);
     if smallest_length == 0 {
@@ -1,5 +1,8 @@
 //! latency utilities and common types
 
+use parking_lot::Mutex;
+use std::{collections::VecDeque, time::Duration};
+
 /// The latency unit used to report latencies by various parts of the Rama codebase.
 #[derive(Copy, Clone, Debug)]
 pub enum LatencyUnit {
@@ -12,3 +15,286 @@ pub enum LatencyUnit {
     /// Use nanoseconds.
     Nanos,
 }
+
+/// A lightweight latency recorder, computing percentiles
+/// over a rolling window of the most recently recorded latencies.
+///
+/// Latencies are counted in log-linear buckets (similar to a HDR histogram),
+/// with a relative error of at most `12.5%`, such that the memory used is bounded
+/// by the window size, regardless of the recorded values.
+///
+/// Reported percentiles are the (inclusive) upper bound of the bucket
+/// in which the percentile falls, at nanosecond precision.
+///
+/// It is meant for quick in-process reporting, not as a replacement
+/// for a full metrics backend.
+#[derive(Debug)]
+pub struct LatencyHistogram {
+    window: usize,
+    state: Mutex<LatencyHistogramState>,
+}
+
+#[derive(Debug)]
+struct LatencyHistogramState {
+    samples: VecDeque<u16>,
+    counts: Box<[u32; BUCKET_COUNT]>,
+}
+
+/// Amount of sub buckets per power of two, as a power of two itself.
+const SUB_BUCKET_BITS: u32 = 3;
+const SUB_BUCKET_COUNT: usize = 1 << SUB_BUCKET_BITS;
+const BUCKET_COUNT: usize = (64 - SUB_BUCKET_BITS as usize + 1) * SUB_BUCKET_COUNT;
+
+const DEFAULT_WINDOW: usize = 1024;
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+impl LatencyHistogram {
+    /// Create a new [`LatencyHistogram`] computing percentiles
+    /// over (at most) the last `window` recorded latencies.
+    ///
+    /// A window of `0` is treated as a window of `1`.
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Self {
+            window,
+            state: Mutex::new(LatencyHistogramState {
+                samples: VecDeque::with_capacity(window),
+                counts: Box::new([0; BUCKET_COUNT]),
+            }),
+        }
+    }
+
+    /// Returns the size of the rolling window of this [`LatencyHistogram`].
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Resize the rolling window of this [`LatencyHistogram`],
+    /// evicting the oldest recorded latencies in case the window shrinks.
+    ///
+    /// A window of `0` is treated as a window of `1`.
+    pub fn set_window(&mut self, window: usize) -> &mut Self {
+        self.window = window.max(1);
+        self.state.get_mut().evict(self.window);
+        self
+    }
+
+    /// Record a latency, evicting the oldest recorded latency
+    /// in case the window is full.
+    pub fn record(&self, latency: Duration) {
+        self.state.lock().record(self.window, latency);
+    }
+
+    /// Record a latency like [`Self::record`],
+    /// without locking as exclusive access is already guaranteed.
+    pub fn record_mut(&mut self, latency: Duration) {
+        self.state.get_mut().record(self.window, latency);
+    }
+
+    /// Take a [`LatencySnapshot`] of the latencies currently in the window.
+    pub fn snapshot(&self) -> LatencySnapshot {
+        self.state.lock().snapshot()
+    }
+
+    /// Take a [`LatencySnapshot`] like [`Self::snapshot`],
+    /// without locking as exclusive access is already guaranteed.
+    pub fn snapshot_mut(&mut self) -> LatencySnapshot {
+        self.state.get_mut().snapshot()
+    }
+}
+
+impl LatencyHistogramState {
+    fn evict(&mut self, window: usize) {
+        while self.samples.len() > window {
+            if let Some(oldest) = self.samples.pop_front() {
+                self.counts[oldest as usize] -= 1;
+            }
+        }
+    }
+
+    fn record(&mut self, window: usize, latency: Duration) {
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        let index = bucket_index(nanos);
+
+        self.evict(window - 1);
+        self.samples.push_back(index as u16);
+        self.counts[index] += 1;
+    }
+
+    fn snapshot(&self) -> LatencySnapshot {
+        let count = self.samples.len();
+        LatencySnapshot {
+            count,
+            p50: percentile(&self.counts, count, 0.50),
+            p90: percentile(&self.counts, count, 0.90),
+            p99: percentile(&self.counts, count, 0.99),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A snapshot of the percentiles computed by a [`LatencyHistogram`].
+pub struct LatencySnapshot {
+    count: usize,
+    p50: Duration,
+    p90: Duration,
+    p99: Duration,
+}
+
+impl LatencySnapshot {
+    /// Amount of latencies the percentiles were computed over.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The 50th percentile (median) latency, [`Duration::ZERO`] if nothing was recorded.
+    pub fn p50(&self) -> Duration {
+        self.p50
+    }
+
+    /// The 90th percentile latency, [`Duration::ZERO`] if nothing was recorded.
+    pub fn p90(&self) -> Duration {
+        self.p90
+    }
+
+    /// The 99th percentile latency, [`Duration::ZERO`] if nothing was recorded.
+    pub fn p99(&self) -> Duration {
+        self.p99
+    }
+}
+
+fn bucket_index(value: u64) -> usize {
+    if value < SUB_BUCKET_COUNT as u64 {
+        return value as usize;
+    }
+    let exp = 63 - value.leading_zeros();
+    let sub = (value >> (exp - SUB_BUCKET_BITS)) as usize & (SUB_BUCKET_COUNT - 1);
+    (exp - SUB_BUCKET_BITS + 1) as usize * SUB_BUCKET_COUNT + sub
+}
+
+fn bucket_upper_bound(index: usize) -> u64 {
+    if index < SUB_BUCKET_COUNT {
+        return index as u64;
+    }
+    let shift = (index / SUB_BUCKET_COUNT - 1) as u32;
+    let sub = (index % SUB_BUCKET_COUNT) as u64;
+    let lower = (SUB_BUCKET_COUNT as u64 + sub) << shift;
+    lower + ((1u64 << shift) - 1)
+}
+
+fn percentile(counts: &[u32; BUCKET_COUNT], count: usize, quantile: f64) -> Duration {
+    if count == 0 {
+        return Duration::ZERO;
+    }
+    let rank = ((quantile * count as f64).ceil() as usize).clamp(1, count);
+    let mut seen = 0;
+    for (index, bucket_count) in counts.iter().enumerate() {
+        seen += *bucket_count as usize;
+        if seen >= rank {
+            return Duration::from_nanos(bucket_upper_bound(index));
+        }
+    }
+    unreachable!("rank is bound by the total count of all buckets")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_in_bucket(actual: Duration, expected: Duration) {
+        let (actual, expected) = (actual.as_nanos(), expected.as_nanos());
+        assert!(
+            actual >= expected && actual <= expected + expected / 8,
+            "{actual}ns not within the bucket of {expected}ns"
+        );
+    }
+
+    #[test]
+    fn test_bucket_bounds() {
+        for value in (0..10_000).chain([u64::MAX / 3, u64::MAX - 1, u64::MAX]) {
+            let index = bucket_index(value);
+            assert!(index < BUCKET_COUNT, "value: {value}");
+            let upper = bucket_upper_bound(index);
+            assert!(value <= upper, "value: {value}, upper: {upper}");
+            assert!(upper - value <= value / 8, "value: {value}, upper: {upper}");
+        }
+    }
+
+    #[test]
+    fn test_latency_histogram_empty() {
+        let snapshot = LatencyHistogram::default().snapshot();
+        assert_eq!(snapshot.count(), 0);
+        assert_eq!(snapshot.p50(), Duration::ZERO);
+        assert_eq!(snapshot.p99(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_latency_histogram_uniform_distribution() {
+        let histogram = LatencyHistogram::new(1000);
+        for ms in 1..=1000 {
+            histogram.record(Duration::from_millis(ms));
+        }
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.count(), 1000);
+        assert_in_bucket(snapshot.p50(), Duration::from_millis(500));
+        assert_in_bucket(snapshot.p90(), Duration::from_millis(900));
+        assert_in_bucket(snapshot.p99(), Duration::from_millis(990));
+    }
+
+    #[test]
+    fn test_latency_histogram_skewed_distribution() {
+        let histogram = LatencyHistogram::new(100);
+        for _ in 0..95 {
+            histogram.record(Duration::from_micros(250));
+        }
+        for _ in 0..5 {
+            histogram.record(Duration::from_secs(2));
+        }
+
+        let snapshot = histogram.snapshot();
+        assert_in_bucket(snapshot.p50(), Duration::from_micros(250));
+        assert_in_bucket(snapshot.p90(), Duration::from_micros(250));
+        assert_in_bucket(snapshot.p99(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_latency_histogram_rolling_window() {
+        let histogram = LatencyHistogram::new(10);
+        for _ in 0..10 {
+            histogram.record(Duration::from_secs(1));
+        }
+        for _ in 0..10 {
+            histogram.record(Duration::from_millis(1));
+        }
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.count(), 10);
+        assert_in_bucket(snapshot.p99(), Duration::from_millis(1));
+    }
+
+    #[test]
+    fn test_latency_histogram_set_window() {
+        let mut histogram = LatencyHistogram::new(10);
+        for ms in 1..=10 {
+            histogram.record_mut(Duration::from_millis(ms));
+        }
+
+        histogram.set_window(2);
+        assert_eq!(histogram.window(), 2);
+        let snapshot = histogram.snapshot_mut();
+        assert_eq!(snapshot.count(), 2);
+        assert_in_bucket(snapshot.p50(), Duration::from_millis(9));
+
+        histogram.set_window(20);
+        for _ in 0..10 {
+            histogram.record_mut(Duration::from_millis(1));
+        }
+        assert_eq!(histogram.snapshot_mut().count(), 12);
+    }
+}
@@ -0,0 +1,48 @@
+//! Utilities to work with octets (bytes).
+
+use std::hint::black_box;
+
+/// Compare two byte sequences for equality in constant time,
+/// meaning the time taken does not depend on the content of either input.
+///
+/// Use this to compare secrets (e.g. passwords and tokens),
+/// to mitigate timing side channel attacks.
+///
+/// The length of the longest input can still be derived from the time taken,
+/// and inputs of different lengths are never equal.
+pub fn ct_eq(a: impl AsRef<[u8]>, b: impl AsRef<[u8]>) -> bool {
+    let (a, b) = (a.as_ref(), b.as_ref());
+
+    let mut diff = u8::from(a.len() != b.len());
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).copied().unwrap_or_default();
+        let y = b.get(i).copied().unwrap_or_default();
+        diff |= black_box(x ^ y);
+    }
+
+    black_box(diff) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ct_eq() {
+        for (a, b, expected) in [
+            ("", "", true),
+            ("a", "a", true),
+            ("secret", "secret", true),
+            ("secret", "secreT", false),
+            ("secret", "Secret", false),
+            ("secret", "secret\0", false),
+            ("secret\0", "secret", false),
+            ("secret", "", false),
+            ("", "secret", false),
+            ("secret", "terces", false),
+        ] {
+            assert_eq!(ct_eq(a, b), expected, "ct_eq({a:?}, {b:?})");
+            assert_eq!(ct_eq(a.as_bytes(), b), expected, "ct_eq({a:?}, {b:?})");
+        }
+    }
+}
@@ -3,11 +3,17 @@
 //! This module provides a generic [`Rng`] trait and a [`HasherRng`] that
 //! implements the trait based on [`RandomState`] or any other [`Hasher`].
 //!
+//! Rama components that need randomness without being given an [`Rng`]
+//! draw from [`thread_rng`], which can be made deterministic for the current
+//! thread using [`seed_thread_rng`], e.g. to reproduce randomized behaviour in tests.
+//!
 //! [PRNG]: https://en.wikipedia.org/wiki/Pseudorandom_number_generator
 
 use std::{
-    collections::hash_map::RandomState,
+    cell::RefCell,
+    collections::hash_map::{DefaultHasher, RandomState},
     hash::{BuildHasher, Hasher},
+    marker::PhantomData,
     ops::Range,
 };
 
@@ -86,6 +92,16 @@ impl Default for HasherRng {
     }
 }
 
+impl HasherRng<SeededState> {
+    /// Create a new deterministic [`HasherRng`] for the given seed.
+    ///
+    /// Two [`HasherRng`]s created with the same seed
+    /// generate the same sequence of values.
+    pub fn seeded(seed: u64) -> Self {
+        HasherRng::with_hasher(SeededState::new(seed))
+    }
+}
+
 impl<H> HasherRng<H> {
     /// Create a new [`HasherRng`] with the provided hasher.
     pub fn with_hasher(hasher: H) -> Self {
@@ -93,6 +109,31 @@ impl<H> HasherRng<H> {
     }
 }
 
+/// A deterministic [`BuildHasher`] which seeds each [`Hasher`] it builds
+/// with a fixed seed, used by [`HasherRng::seeded`].
+#[derive(Clone, Debug)]
+pub struct SeededState {
+    seed: u64,
+}
+
+impl SeededState {
+    /// Create a new [`SeededState`] for the given seed.
+    pub const fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+}
+
+impl BuildHasher for SeededState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        // `DefaultHasher::new` is guaranteed to always create the same hasher
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(self.seed);
+        hasher
+    }
+}
+
 impl<H> Rng for HasherRng<H>
 where
     H: BuildHasher + Send + Sync + 'static,
@@ -105,6 +146,64 @@ where
     }
 }
 
+thread_local! {
+    static SEEDED_THREAD_RNG: RefCell<Option<HasherRng<SeededState>>> = const { RefCell::new(None) };
+}
+
+/// Returns a [`ThreadRng`], the [`Rng`] used by rama components
+/// which need randomness but are not given an [`Rng`].
+pub fn thread_rng() -> ThreadRng {
+    ThreadRng { _priv: () }
+}
+
+/// The [`Rng`] returned by [`thread_rng`].
+///
+/// It is random by default, but deterministic for as long as a seed is set
+/// for the current thread using [`seed_thread_rng`].
+#[derive(Debug, Clone, Default)]
+pub struct ThreadRng {
+    _priv: (),
+}
+
+impl Rng for ThreadRng {
+    fn next_u64(&mut self) -> u64 {
+        SEEDED_THREAD_RNG.with_borrow_mut(|rng| match rng {
+            Some(rng) => rng.next_u64(),
+            None => HasherRng::default().next_u64(),
+        })
+    }
+}
+
+/// Seed the [`ThreadRng`] of the current thread, making it deterministic
+/// until the returned [`ThreadRngSeedGuard`] is dropped, at which point
+/// the previous state is restored.
+///
+/// Note that this only affects the current thread, so when used with async
+/// code make sure the code drawing from the [`ThreadRng`] runs on that same thread
+/// (e.g. by using a current-thread runtime).
+pub fn seed_thread_rng(seed: u64) -> ThreadRngSeedGuard {
+    let previous = SEEDED_THREAD_RNG.replace(Some(HasherRng::seeded(seed)));
+    ThreadRngSeedGuard {
+        previous,
+        _not_send: PhantomData,
+    }
+}
+
+/// Guard returned by [`seed_thread_rng`], restoring the previous
+/// state of the [`ThreadRng`] of the current thread when dropped.
+#[derive(Debug)]
+#[must_use = "the thread rng is only seeded until the guard is dropped"]
+pub struct ThreadRngSeedGuard {
+    previous: Option<HasherRng<SeededState>>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ThreadRngSeedGuard {
+    fn drop(&mut self) {
+        SEEDED_THREAD_RNG.set(self.previous.take());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,5 +234,33 @@ mod tests {
 
             TestResult::from_bool(n >= range.start && (n < range.end || range.start == range.end))
         }
+
+        fn seeded(seed: u64) -> bool {
+            let mut a = HasherRng::seeded(seed);
+            let mut b = HasherRng::seeded(seed);
+            (0..8).all(|_| a.next_u64() == b.next_u64())
+        }
+    }
+
+    #[test]
+    fn test_seed_thread_rng() {
+        let first: Vec<_> = {
+            let _guard = seed_thread_rng(42);
+            (0..8).map(|_| thread_rng().next_u64()).collect()
+        };
+        let second: Vec<_> = {
+            let _guard = seed_thread_rng(42);
+            (0..8).map(|_| thread_rng().next_u64()).collect()
+        };
+        assert_eq!(first, second);
+
+        let _outer = seed_thread_rng(1);
+        let expected = HasherRng::seeded(1).next_u64();
+        {
+            let _inner = seed_thread_rng(2);
+            assert_ne!(thread_rng().next_u64(), expected);
+        }
+        // outer seed is restored, including its state
+        assert_eq!(thread_rng().next_u64(), expected);
     }
 }
@@ -7,7 +7,7 @@ use std::{borrow::Cow, fmt};
 pub struct NonEmptyString(Cow<'static, str>);
 
 crate::macros::error::static_str_error! {
-    #[doc = "empty string"]
+    #[doc = "empty or whitespace-only string"]
     pub struct EmptyStringErr;
 }
 
@@ -27,6 +27,24 @@ impl NonEmptyString {
         NonEmptyString(Cow::Borrowed(src))
     }
 
+    /// Creates a [`NonEmptyString`] from the given string,
+    /// with its leading and trailing whitespace removed.
+    ///
+    /// Returns an [`EmptyStringErr`] in case the string is empty or only contains whitespace.
+    ///
+    /// This is also how the [`FromStr`] and [`TryFrom`] implementations
+    /// of [`NonEmptyString`] construct it.
+    ///
+    /// [`FromStr`]: std::str::FromStr
+    pub fn new_trimmed(src: impl AsRef<str>) -> Result<NonEmptyString, EmptyStringErr> {
+        let trimmed = src.as_ref().trim();
+        if trimmed.is_empty() {
+            Err(EmptyStringErr::default())
+        } else {
+            Ok(NonEmptyString(Cow::Owned(trimmed.to_owned())))
+        }
+    }
+
     /// Views this [`NonEmptyString`] as a string slice.
     pub fn as_str(&self) -> &str {
         self.0.as_ref()
@@ -54,10 +72,11 @@ impl TryFrom<String> for NonEmptyString {
     type Error = EmptyStringErr;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        if value.is_empty() {
-            Err(Self::Error::default())
-        } else {
+        if value.trim().len() == value.len() && !value.is_empty() {
+            // nothing to trim, so re-use the allocated string
             Ok(Self(Cow::Owned(value)))
+        } else {
+            Self::new_trimmed(value)
         }
     }
 }
@@ -66,11 +85,7 @@ impl TryFrom<&String> for NonEmptyString {
     type Error = EmptyStringErr;
 
     fn try_from(value: &String) -> Result<Self, Self::Error> {
-        if value.is_empty() {
-            Err(Self::Error::default())
-        } else {
-            Ok(Self(Cow::Owned(value.clone())))
-        }
+        Self::new_trimmed(value)
     }
 }
 
@@ -78,11 +93,7 @@ impl TryFrom<&str> for NonEmptyString {
     type Error = EmptyStringErr;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        if value.is_empty() {
-            Err(Self::Error::default())
-        } else {
-            Ok(Self(Cow::Owned(value.to_owned())))
-        }
+        Self::new_trimmed(value)
     }
 }
 
@@ -196,6 +207,34 @@ mod tests {
         assert_try_into_err(&String::from(""));
     }
 
+    #[test]
+    fn test_non_empty_string_whitespace_only_failure() {
+        for input in [" ", "   ", "\t", "\n", " \t\r\n "] {
+            assert_try_into_err(input);
+            assert_try_into_err(input.to_owned());
+            assert!(NonEmptyString::new_trimmed(input).is_err(), "{input:?}");
+            assert!(input.parse::<NonEmptyString>().is_err(), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_non_empty_string_trimmed() {
+        for (input, expected) in [
+            ("a", "a"),
+            (" a", "a"),
+            ("a ", "a"),
+            ("\t hello world\n", "hello world"),
+        ] {
+            assert_eq!(NonEmptyString::new_trimmed(input).unwrap(), expected);
+            assert_eq!(input.parse::<NonEmptyString>().unwrap(), expected);
+            assert_eq!(NonEmptyString::try_from(input).unwrap(), expected);
+            assert_eq!(
+                NonEmptyString::try_from(input.to_owned()).unwrap(),
+                expected
+            );
+        }
+    }
+
     #[test]
     fn test_non_empty_string_construction_success() {
         assert_try_into_ok("a");
@@ -217,5 +256,8 @@ mod tests {
         let test: Test = serde_json::from_str(&source).unwrap();
         assert_eq!("Hello", test.greeting);
         assert_eq!("en", test.language);
+
+        let source = r##"{"greeting": "  ", "language": "en"}"##;
+        assert!(serde_json::from_str::<Test>(source).is_err());
     }
 }
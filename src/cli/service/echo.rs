@@ -19,7 +19,7 @@ use crate::{
             ua::{UserAgent, UserAgentClassifierLayer},
         },
         proto::h1::Http1HeaderMap,
-        proto::h2::PseudoHeaderOrder,
+        proto::h2::{InitialPeerSettings, PriorityFrames, PseudoHeaderOrder, StreamDependency},
         response::Json,
         server::HttpServer,
         IntoResponse, Request, Response, Version,
@@ -296,6 +296,15 @@ where
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 /// The inner echo-service used by the [`EchoServiceBuilder`].
+///
+/// By default it echos all information it has about the request,
+/// its underlying transport and presentation layers as a JSON object.
+///
+/// In case the request has the `fp` query parameter (e.g. `/?fp` or `/?fp=1`)
+/// it instead returns a single flat JSON object containing only the fingerprint
+/// related information (ja3, ja4, ja4h, header order, raw headers, pseudo header order,
+/// h2 settings and priority information and client hints),
+/// making it easy to compare (emulated) clients programmatically.
 pub struct EchoService;
 
 impl Service<(), Request> for EchoService {
@@ -329,6 +338,16 @@ impl Service<(), Request> for EchoService {
             .get::<PseudoHeaderOrder>()
             .map(|o| o.iter().collect());
 
+        let fingerprint_mode = req
+            .uri()
+            .query()
+            .map(|query| {
+                query
+                    .split('&')
+                    .any(|pair| pair.split('=').next() == Some("fp"))
+            })
+            .unwrap_or_default();
+
         let ja4h = Ja4H::compute(&req)
             .inspect_err(|err| tracing::error!(?err, "ja4h compute failure"))
             .ok()
@@ -356,31 +375,94 @@ impl Service<(), Request> for EchoService {
         let body = body.collect().await.unwrap().to_bytes();
         let body = hex::encode(body.as_ref());
 
+        #[cfg(any(feature = "rustls", feature = "boring"))]
+        let ja4 = Ja4::compute(ctx.extensions())
+            .inspect_err(|err| tracing::trace!(?err, "ja4 computation"))
+            .ok()
+            .map(|ja4| {
+                json!({
+                    "hash": format!("{ja4}"),
+                    "raw": format!("{ja4:?}"),
+                })
+            });
+        #[cfg(not(any(feature = "rustls", feature = "boring")))]
+        let ja4: Option<()> = None;
+
+        #[cfg(any(feature = "rustls", feature = "boring"))]
+        let ja3 = Ja3::compute(ctx.extensions())
+            .inspect_err(|err| tracing::trace!(?err, "ja3 computation"))
+            .ok()
+            .map(|ja3| {
+                json!({
+                    "full": format!("{ja3}"),
+                    "hash": format!("{ja3:x}"),
+                })
+            });
+        #[cfg(not(any(feature = "rustls", feature = "boring")))]
+        let ja3: Option<()> = None;
+
+        if fingerprint_mode {
+            let header_order: Vec<_> = headers.iter().map(|(name, _)| name.as_str()).collect();
+            let client_hints: serde_json::Map<_, _> = headers
+                .iter()
+                .filter(|(name, _)| name.as_str().to_ascii_lowercase().starts_with("sec-ch-"))
+                .map(|(name, value)| (name.as_str().to_ascii_lowercase(), value.clone().into()))
+                .collect();
+
+            let stream_dependency_json = |dependency: &StreamDependency| {
+                json!({
+                    "dependency_id": dependency.dependency_id(),
+                    "weight": dependency.weight(),
+                    "exclusive": dependency.is_exclusive(),
+                })
+            };
+            let h2_settings: Option<Vec<_>> =
+                parts
+                    .extensions
+                    .get::<InitialPeerSettings>()
+                    .map(|settings| {
+                        settings
+                            .iter()
+                            .map(|setting| json!({ "id": setting.id(), "value": setting.value() }))
+                            .collect()
+                    });
+            let h2_priority: Option<Vec<_>> =
+                parts.extensions.get::<PriorityFrames>().map(|frames| {
+                    frames
+                        .iter()
+                        .map(|frame| {
+                            let mut priority = stream_dependency_json(&frame.dependency());
+                            priority["stream_id"] = frame.stream_id().into();
+                            priority
+                        })
+                        .collect()
+                });
+            let h2_stream_dependency = parts
+                .extensions
+                .get::<StreamDependency>()
+                .map(stream_dependency_json);
+
+            return Ok(Json(json!({
+                "ja3": ja3,
+                "ja4": ja4,
+                "ja4h": ja4h,
+                "http_version": format!("{:?}", parts.version),
+                "header_order": header_order,
+                "headers": headers,
+                "pseudo_header_order": pseudo_headers,
+                "h2_settings": h2_settings,
+                "h2_priority": h2_priority,
+                "h2_stream_dependency": h2_stream_dependency,
+                "client_hints": client_hints,
+            }))
+            .into_response());
+        }
+
         #[cfg(any(feature = "rustls", feature = "boring"))]
         let tls_client_hello = ctx
             .get::<SecureTransport>()
             .and_then(|st| st.client_hello())
             .map(|hello| {
-                let ja4 = Ja4::compute(ctx.extensions())
-                    .inspect_err(|err| tracing::trace!(?err, "ja4 computation"))
-                    .ok()
-                    .map(|ja4| {
-                        json!({
-                            "hash": format!("{ja4}"),
-                            "raw": format!("{ja4:?}"),
-                        })
-                    });
-
-                let ja3 = Ja3::compute(ctx.extensions())
-                    .inspect_err(|err| tracing::trace!(?err, "ja3 computation"))
-                    .ok()
-                    .map(|ja3| {
-                        json!({
-                            "full": format!("{ja3}"),
-                            "hash": format!("{ja3:x}"),
-                        })
-                    });
-
                 json!({
                     "ja4": ja4,
                     "ja3": ja3,
@@ -175,6 +175,7 @@
 //! - [`rama-utils`](https://crates.io/crates/rama-utils): utilities crate for rama
 //! - [`rama-core`](https://crates.io/crates/rama-core): core crate containing the service, layer and
 //!   context used by all other `rama` code, as well as some other _core_ utilities
+//! - [`rama-crypto`](https://crates.io/crates/rama-crypto): cryptographic support for rama (e.g. JOSE)
 //! - [`rama-net`](https://crates.io/crates/rama-net): rama network types and utilities
 //! - [`rama-dns`](https://crates.io/crates/rama-dns): DNS support for rama
 //! - [`rama-tcp`](https://crates.io/crates/rama-tcp): TCP support for rama
@@ -337,6 +338,10 @@ pub use ::rama_core::telemetry;
 #[doc(inline)]
 pub use ::rama_tls as tls;
 
+#[cfg(feature = "crypto")]
+#[doc(inline)]
+pub use ::rama_crypto as crypto;
+
 #[cfg(feature = "dns")]
 #[doc(inline)]
 pub use ::rama_dns as dns;
@@ -1266,6 +1266,104 @@ async fn disable_keep_alive_post_request() {
     child.join().unwrap();
 }
 
+#[tokio::test]
+async fn http1_max_requests_per_connection() {
+    let (listener, addr) = setup_tcp_listener();
+
+    let child = thread::spawn(move || {
+        let mut req = connect(&addr);
+        let request = b"\
+            GET / HTTP/1.1\r\n\
+            Host: localhost\r\n\
+            \r\n\
+        ";
+
+        req.write_all(request).unwrap();
+        let buf = read_until(&mut req, |buf| buf.ends_with(HELLO.as_bytes())).expect("reading 1");
+        assert!(
+            !s(&buf).contains("connection: close\r\n"),
+            "first response shouldn't have sent close: {:?}",
+            s(&buf),
+        );
+
+        req.write_all(request).unwrap();
+        let mut buf = vec![];
+        req.read_to_end(&mut buf).expect("reading 2");
+        let sbuf = s(&buf);
+        assert!(
+            sbuf.starts_with("HTTP/1.1 200 OK\r\n"),
+            "should receive OK response, but buf: {:?}",
+            sbuf,
+        );
+        assert!(
+            sbuf.contains("connection: close\r\n"),
+            "last response should have sent close: {:?}",
+            sbuf,
+        );
+        assert!(sbuf.ends_with(HELLO));
+    });
+
+    let (socket, _) = listener.accept().await.unwrap();
+    http1::Builder::new()
+        .max_requests_per_connection(2)
+        .serve_connection(
+            socket,
+            RamaHttpService::new(rama::Context::default(), HelloWorld),
+        )
+        .await
+        .unwrap();
+
+    child.join().unwrap();
+}
+
+#[tokio::test]
+async fn http2_max_requests_per_connection_sends_goaway() {
+    let (listener, addr) = setup_tcp_listener();
+
+    let server = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        http2::Builder::new(Executor::new())
+            .max_requests_per_connection(2)
+            .serve_connection(
+                socket,
+                RamaHttpService::new(rama::Context::default(), HelloWorld),
+            )
+            .await
+    });
+
+    let conn = connect_async(addr).await;
+    let (h2, connection) = rama::http::core::h2::client::handshake(conn).await.unwrap();
+    tokio::spawn(connection);
+
+    let mut h2 = h2.ready().await.unwrap();
+    for _ in 0..2 {
+        let request = Request::get("http://localhost/").body(()).unwrap();
+        let (response, _) = h2.send_request(request, true).unwrap();
+        let response = response.await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let mut body = response.into_body();
+        let bytes = body.data().await.unwrap().unwrap();
+        assert_eq!(&bytes[..], HELLO.as_bytes());
+    }
+
+    // the server closes the connection gracefully after the 2nd request...
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("server closes connection")
+        .unwrap()
+        .expect("graceful shutdown");
+
+    // ... which the client received as a GOAWAY
+    let request = Request::get("http://localhost/").body(()).unwrap();
+    let err = match h2.send_request(request, true) {
+        Ok((response, _)) => response.await.unwrap_err(),
+        Err(err) => err,
+    };
+    assert!(err.is_go_away(), "unexpected error: {err:?}");
+    assert!(err.is_remote());
+    assert_eq!(err.reason(), Some(rama::http::core::h2::Reason::NO_ERROR));
+}
+
 #[tokio::test]
 async fn http1_graceful_shutdown_after_upgrade() {
     let (listener, addr) = setup_tcp_listener();
@@ -36,6 +36,35 @@ async fn test_http_echo_acme_data() {
     assert!(lines.contains("world"), "lines: {:?}", lines);
 }
 
+#[tokio::test]
+#[ignore]
+async fn test_http_echo_fingerprint() {
+    let _guard = utils::RamaService::echo(63109, false, None);
+
+    let lines = utils::RamaService::http(vec![
+        "http://127.0.0.1:63109/?fp",
+        "Sec-CH-UA-Platform:\"Linux\"",
+        "foo:bar",
+        "a=4",
+    ])
+    .unwrap();
+    assert!(lines.contains("HTTP/1.1 200 OK"), "lines: {:?}", lines);
+    assert!(lines.contains(r##""ja4h":{"##), "lines: {:?}", lines);
+    assert!(
+        lines.contains(r##""header_order":["##),
+        "lines: {:?}",
+        lines
+    );
+    assert!(lines.contains(r##""foo","bar""##), "lines: {:?}", lines);
+    assert!(
+        lines.contains(r##""client_hints":{"sec-ch-ua-platform":"\"Linux\""}"##),
+        "lines: {:?}",
+        lines
+    );
+    // fingerprint mode only returns fingerprint information
+    assert!(!lines.contains(r##""payload""##), "lines: {:?}", lines);
+}
+
 #[cfg(feature = "boring")]
 #[tokio::test]
 #[ignore]
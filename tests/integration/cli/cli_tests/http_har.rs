@@ -0,0 +1,62 @@
+use super::utils;
+
+#[tokio::test]
+#[ignore]
+async fn test_http_har_export() {
+    const HAR_PATH: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/test_http_har_export.har");
+
+    let _guard = utils::RamaService::echo(63104, false, None);
+
+    let lines = utils::RamaService::http(vec![
+        "--har",
+        HAR_PATH,
+        "--har-bodies",
+        "--har-redact",
+        "-a",
+        "john:secret",
+        "http://127.0.0.1:63104",
+        "foo:bar",
+        "a=4",
+        "q==1",
+    ])
+    .unwrap();
+    assert!(lines.contains("HTTP/1.1 200 OK"), "lines: {:?}", lines);
+
+    let har: serde_json::Value = serde_json::from_slice(&std::fs::read(HAR_PATH).unwrap()).unwrap();
+    assert_eq!(har["log"]["version"], "1.2");
+
+    let entries = har["log"]["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 1, "entries: {:?}", entries);
+    let entry = &entries[0];
+    assert!(entry["startedDateTime"].is_string());
+    assert!(entry["time"].as_f64().unwrap() >= 0.0);
+
+    // connected to an ip over plain text: no dns nor tls involved
+    let timings = &entry["timings"];
+    assert_eq!(timings["dns"], -1.0);
+    assert_eq!(timings["ssl"], -1.0);
+    assert!(timings["connect"].as_f64().unwrap() >= 0.0);
+    assert!(timings["wait"].as_f64().unwrap() >= 0.0);
+
+    let request = &entry["request"];
+    assert_eq!(request["method"], "POST");
+    assert_eq!(request["url"], "http://127.0.0.1:63104/?q=1");
+    assert_eq!(
+        request["queryString"],
+        serde_json::json!([{"name": "q", "value": "1"}])
+    );
+    let headers = request["headers"].as_array().unwrap();
+    assert!(headers.contains(&serde_json::json!({"name": "foo", "value": "bar"})));
+    assert!(headers.contains(&serde_json::json!({"name": "authorization", "value": "[REDACTED]"})));
+    assert!(request["postData"]["text"]
+        .as_str()
+        .unwrap()
+        .contains(r##""a":"4""##));
+
+    let response = &entry["response"];
+    assert_eq!(response["status"], 200);
+    assert!(response["content"]["text"]
+        .as_str()
+        .unwrap()
+        .contains(r##""method":"POST""##));
+}
@@ -0,0 +1,49 @@
+use super::utils;
+
+#[tokio::test]
+#[ignore]
+async fn test_http_proxy_config_upstream_rules() {
+    let _upstream_one = utils::RamaService::echo(63106, false, Some("hello,one".to_owned()));
+    let _upstream_two = utils::RamaService::echo(63107, false, Some("hello,two".to_owned()));
+
+    let proxy_config =
+        std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("test_http_proxy_config.json");
+    std::fs::write(
+        &proxy_config,
+        r##"{
+            "rules": [
+                {
+                    "name": "one",
+                    "match": { "host": "a.test" },
+                    "upstream": "http://127.0.0.1:63106"
+                },
+                {
+                    "name": "two",
+                    "match": { "host": "b.test", "port": 80 },
+                    "upstream": "http://127.0.0.1:63107"
+                }
+            ]
+        }"##,
+    )
+    .unwrap();
+
+    let _guard = utils::RamaService::proxy(63108, Some(&proxy_config));
+
+    let lines = utils::RamaService::http(vec![
+        "-P",
+        "http://127.0.0.1:63108",
+        "http://a.test/.well-known/acme-challenge/hello",
+    ])
+    .unwrap();
+    assert!(lines.contains("HTTP/1.1 200 OK"), "lines: {:?}", lines);
+    assert!(lines.contains("one"), "lines: {:?}", lines);
+
+    let lines = utils::RamaService::http(vec![
+        "-P",
+        "http://127.0.0.1:63108",
+        "http://sub.b.test/.well-known/acme-challenge/hello",
+    ])
+    .unwrap();
+    assert!(lines.contains("HTTP/1.1 200 OK"), "lines: {:?}", lines);
+    assert!(lines.contains("two"), "lines: {:?}", lines);
+}
@@ -0,0 +1,33 @@
+use super::utils;
+
+#[tokio::test]
+#[ignore]
+async fn test_http_serve_spa() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("test_http_serve_spa");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("index.html"), "<h1>rama spa</h1>").unwrap();
+    std::fs::write(dir.join("app.js"), "console.log('rama');").unwrap();
+
+    let _guard = utils::RamaService::serve(63105, &dir, true);
+
+    let lines = utils::RamaService::http(vec![
+        "http://127.0.0.1:63105/some/route",
+        "Accept:text/html",
+    ])
+    .unwrap();
+    assert!(lines.contains("HTTP/1.1 200 OK"), "lines: {:?}", lines);
+    assert!(lines.contains("<h1>rama spa</h1>"), "lines: {:?}", lines);
+
+    let lines = utils::RamaService::http(vec!["http://127.0.0.1:63105/app.js"]).unwrap();
+    assert!(lines.contains("HTTP/1.1 200 OK"), "lines: {:?}", lines);
+    assert!(lines.contains("text/javascript"), "lines: {:?}", lines);
+    assert!(lines.contains("console.log('rama');"), "lines: {:?}", lines);
+
+    let lines = utils::RamaService::http(vec!["http://127.0.0.1:63105/missing.js"]).unwrap();
+    assert!(
+        lines.contains("HTTP/1.1 404 Not Found"),
+        "lines: {:?}",
+        lines
+    );
+    assert!(!lines.contains("<h1>rama spa</h1>"), "lines: {:?}", lines);
+}
@@ -2,4 +2,7 @@ mod utils;
 
 mod help;
 mod http_echo;
+mod http_har;
 mod http_ip;
+mod http_proxy_config;
+mod http_serve;
@@ -111,6 +111,92 @@ impl RamaService {
         Self { process }
     }
 
+    /// Start the rama serve service with the given port and directory.
+    pub(super) fn serve(port: u16, dir: &std::path::Path, spa: bool) -> Self {
+        let mut builder = escargot::CargoBuild::new()
+            .package("rama-cli")
+            .bin("rama")
+            .target_dir("./target/")
+            .run()
+            .unwrap()
+            .command();
+
+        builder
+            .stdout(std::process::Stdio::piped())
+            .arg("serve")
+            .arg("-p")
+            .arg(port.to_string())
+            .arg("--dir")
+            .arg(dir);
+
+        if spa {
+            builder.arg("--spa");
+        }
+
+        let mut process = builder.spawn().unwrap();
+
+        let stdout = process.stdout.take().unwrap();
+        let mut stdout = BufReader::new(stdout).lines();
+
+        for line in &mut stdout {
+            let line = line.unwrap();
+            if line.contains("serve service ready") {
+                break;
+            }
+        }
+
+        thread::spawn(move || {
+            for line in stdout {
+                let line = line.unwrap();
+                println!("rama serve >> {}", line);
+            }
+        });
+
+        Self { process }
+    }
+
+    /// Start the rama proxy service with the given port and optional proxy config file.
+    pub(super) fn proxy(port: u16, proxy_config: Option<&std::path::Path>) -> Self {
+        let mut builder = escargot::CargoBuild::new()
+            .package("rama-cli")
+            .bin("rama")
+            .target_dir("./target/")
+            .run()
+            .unwrap()
+            .command();
+
+        builder
+            .stdout(std::process::Stdio::piped())
+            .arg("proxy")
+            .arg("-p")
+            .arg(port.to_string());
+
+        if let Some(proxy_config) = proxy_config {
+            builder.arg("--proxy-config").arg(proxy_config);
+        }
+
+        let mut process = builder.spawn().unwrap();
+
+        let stdout = process.stdout.take().unwrap();
+        let mut stdout = BufReader::new(stdout).lines();
+
+        for line in &mut stdout {
+            let line = line.unwrap();
+            if line.contains("proxy service ready") {
+                break;
+            }
+        }
+
+        thread::spawn(move || {
+            for line in stdout {
+                let line = line.unwrap();
+                println!("rama proxy >> {}", line);
+            }
+        });
+
+        Self { process }
+    }
+
     /// Run any rama cmd
     pub(super) fn run(args: Vec<&'static str>) -> Result<String, Box<dyn std::error::Error>> {
         let child = escargot::CargoBuild::new()